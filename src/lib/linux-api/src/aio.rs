@@ -0,0 +1,73 @@
+//! Linux native asynchronous I/O.
+//!
+//! We don't generate bindings for linux/aio_abi.h, so the types here are manually translated.
+
+use crate::bindings;
+
+#[allow(non_camel_case_types)]
+pub type aio_context_t = bindings::linux___kernel_ulong_t;
+
+/// Positioned read.
+pub const IOCB_CMD_PREAD: u16 = 0;
+/// Positioned write.
+pub const IOCB_CMD_PWRITE: u16 = 1;
+/// Sync the file's data and metadata.
+pub const IOCB_CMD_FSYNC: u16 = 2;
+/// Sync the file's data.
+pub const IOCB_CMD_FDSYNC: u16 = 3;
+/// Poll the file for events.
+pub const IOCB_CMD_POLL: u16 = 5;
+/// No operation.
+pub const IOCB_CMD_NOOP: u16 = 6;
+/// Positioned vectored read.
+pub const IOCB_CMD_PREADV: u16 = 7;
+/// Positioned vectored write.
+pub const IOCB_CMD_PWRITEV: u16 = 8;
+
+/// The `aio_resfd` field of the iocb is valid, and the eventfd should be notified on completion.
+pub const IOCB_FLAG_RESFD: u32 = 1 << 0;
+/// The `aio_reqprio` field of the iocb is valid.
+pub const IOCB_FLAG_IOPRIO: u32 = 1 << 1;
+
+/// The result of a completed I/O operation, as returned by `io_getevents`.
+#[allow(non_camel_case_types)]
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct io_event {
+    /// The `aio_data` field from the iocb.
+    pub data: u64,
+    /// A pointer to the iocb this event is for.
+    pub obj: u64,
+    /// The result of the operation.
+    pub res: i64,
+    /// A secondary result.
+    pub res2: i64,
+}
+unsafe impl shadow_pod::Pod for io_event {}
+
+/// An I/O control block, as submitted with `io_submit`.
+#[allow(non_camel_case_types)]
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default)]
+pub struct iocb {
+    /// Returned in the `data` field of the completion event.
+    pub aio_data: u64,
+    // The kernel swaps these two fields on big-endian architectures.
+    pub aio_key: u32,
+    /// `RWF_*` flags, as for `preadv2` and `pwritev2`.
+    pub aio_rw_flags: i32,
+
+    pub aio_lio_opcode: u16,
+    pub aio_reqprio: i16,
+    pub aio_fildes: u32,
+
+    pub aio_buf: u64,
+    pub aio_nbytes: u64,
+    pub aio_offset: i64,
+
+    pub aio_reserved2: u64,
+
+    pub aio_flags: u32,
+    pub aio_resfd: u32,
+}
+unsafe impl shadow_pod::Pod for iocb {}
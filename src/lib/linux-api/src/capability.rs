@@ -6,6 +6,9 @@ use crate::errno::Errno;
 
 pub const LINUX_CAPABILITY_VERSION_3: u32 = bindings::LINUX__LINUX_CAPABILITY_VERSION_3;
 
+pub const CAP_SETPCAP: u32 = bindings::LINUX_CAP_SETPCAP;
+pub const CAP_LAST_CAP: u32 = bindings::LINUX_CAP_LAST_CAP;
+
 #[allow(non_camel_case_types)]
 pub type user_cap_header = __user_cap_header_struct;
 #[allow(non_camel_case_types)]
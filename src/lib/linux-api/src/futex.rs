@@ -37,6 +37,20 @@ bitflags::bitflags! {
     }
 }
 
+// Operations and comparisons encoded in the `val3` argument of `FUTEX_WAKE_OP`.
+pub const FUTEX_OP_SET: u32 = bindings::LINUX_FUTEX_OP_SET;
+pub const FUTEX_OP_ADD: u32 = bindings::LINUX_FUTEX_OP_ADD;
+pub const FUTEX_OP_OR: u32 = bindings::LINUX_FUTEX_OP_OR;
+pub const FUTEX_OP_ANDN: u32 = bindings::LINUX_FUTEX_OP_ANDN;
+pub const FUTEX_OP_XOR: u32 = bindings::LINUX_FUTEX_OP_XOR;
+pub const FUTEX_OP_OPARG_SHIFT: u32 = bindings::LINUX_FUTEX_OP_OPARG_SHIFT;
+pub const FUTEX_OP_CMP_EQ: u32 = bindings::LINUX_FUTEX_OP_CMP_EQ;
+pub const FUTEX_OP_CMP_NE: u32 = bindings::LINUX_FUTEX_OP_CMP_NE;
+pub const FUTEX_OP_CMP_LT: u32 = bindings::LINUX_FUTEX_OP_CMP_LT;
+pub const FUTEX_OP_CMP_LE: u32 = bindings::LINUX_FUTEX_OP_CMP_LE;
+pub const FUTEX_OP_CMP_GT: u32 = bindings::LINUX_FUTEX_OP_CMP_GT;
+pub const FUTEX_OP_CMP_GE: u32 = bindings::LINUX_FUTEX_OP_CMP_GE;
+
 /// # Safety
 /// See futex(2). Pointers must be valid or NULL.
 pub unsafe fn futex_raw(
@@ -156,6 +156,7 @@ pub enum IoctlRequest {
     SIOCGHWTSTAMP = bindings::LINUX_SIOCGHWTSTAMP,
     SIOCDEVPRIVATE = bindings::LINUX_SIOCDEVPRIVATE,
     SIOCPROTOPRIVATE = bindings::LINUX_SIOCPROTOPRIVATE,
+    UFFDIO_API = crate::userfaultfd::UFFDIO_API,
 }
 
 impl IoctlRequest {
@@ -0,0 +1,110 @@
+//! System V IPC.
+//!
+//! We don't generate bindings for the IPC headers, so the constants and types here are manually
+//! translated from linux/ipc.h, linux/shm.h, linux/sem.h, and the x86-64 asm-generic/ipcbuf.h and
+//! asm-generic/shmbuf.h.
+
+use crate::bindings;
+
+/// Key for creating a new object that can't be looked up by key.
+pub const IPC_PRIVATE: bindings::linux___kernel_key_t = 0;
+
+// `*get()` flags.
+pub const IPC_CREAT: i32 = 0o1000;
+pub const IPC_EXCL: i32 = 0o2000;
+pub const IPC_NOWAIT: i32 = 0o4000;
+
+// `*ctl()` commands.
+pub const IPC_RMID: i32 = 0;
+pub const IPC_SET: i32 = 1;
+pub const IPC_STAT: i32 = 2;
+pub const IPC_INFO: i32 = 3;
+
+// `shmat()` flags.
+pub const SHM_RDONLY: i32 = 0o10000;
+pub const SHM_RND: i32 = 0o20000;
+pub const SHM_REMAP: i32 = 0o40000;
+pub const SHM_EXEC: i32 = 0o100000;
+
+// `semctl()` commands.
+pub const GETPID: i32 = 11;
+pub const GETVAL: i32 = 12;
+pub const GETALL: i32 = 13;
+pub const GETNCNT: i32 = 14;
+pub const GETZCNT: i32 = 15;
+pub const SETVAL: i32 = 16;
+pub const SETALL: i32 = 17;
+
+/// `semop()` flag to undo the operation when the process exits.
+pub const SEM_UNDO: i16 = 0x1000;
+
+/// The maximum number of semaphores in a set.
+pub const SEMMSL: i32 = 32000;
+/// The maximum number of operations in a single `semop()` call.
+pub const SEMOPM: usize = 500;
+/// The maximum value of a semaphore.
+pub const SEMVMX: i32 = 32767;
+
+/// The minimum size of a shared memory segment.
+pub const SHMMIN: usize = 1;
+/// The default maximum size of a shared memory segment.
+pub const SHMMAX: usize = usize::MAX - (1 << 24);
+
+#[allow(non_camel_case_types)]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct linux_ipc64_perm {
+    pub key: bindings::linux___kernel_key_t,
+    pub uid: bindings::linux___kernel_uid32_t,
+    pub gid: bindings::linux___kernel_gid32_t,
+    pub cuid: bindings::linux___kernel_uid32_t,
+    pub cgid: bindings::linux___kernel_gid32_t,
+    pub mode: bindings::linux___kernel_mode_t,
+    // `__pad1` is zero-sized on x86-64, since `__kernel_mode_t` is 4 bytes.
+    pub seq: core::ffi::c_ushort,
+    pub __pad2: core::ffi::c_ushort,
+    pub __unused1: bindings::linux___kernel_ulong_t,
+    pub __unused2: bindings::linux___kernel_ulong_t,
+}
+
+#[allow(non_camel_case_types)]
+pub type ipc64_perm = linux_ipc64_perm;
+unsafe impl shadow_pod::Pod for ipc64_perm {}
+
+/// The x86-64 `shmctl` syscall always uses the 64-bit version of `struct shmid_ds`.
+#[allow(non_camel_case_types)]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct linux_shmid64_ds {
+    pub shm_perm: linux_ipc64_perm,
+    pub shm_segsz: bindings::linux___kernel_size_t,
+    pub shm_atime: core::ffi::c_long,
+    pub shm_dtime: core::ffi::c_long,
+    pub shm_ctime: core::ffi::c_long,
+    pub shm_cpid: bindings::linux___kernel_pid_t,
+    pub shm_lpid: bindings::linux___kernel_pid_t,
+    pub shm_nattch: core::ffi::c_ulong,
+    pub __unused4: core::ffi::c_ulong,
+    pub __unused5: core::ffi::c_ulong,
+}
+
+#[allow(non_camel_case_types)]
+pub type shmid64_ds = linux_shmid64_ds;
+unsafe impl shadow_pod::Pod for shmid64_ds {}
+
+/// An operation for `semop()`.
+#[allow(non_camel_case_types)]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct linux_sembuf {
+    /// Semaphore index in the set.
+    pub sem_num: core::ffi::c_ushort,
+    /// Semaphore operation.
+    pub sem_op: core::ffi::c_short,
+    /// Operation flags.
+    pub sem_flg: core::ffi::c_short,
+}
+
+#[allow(non_camel_case_types)]
+pub type sembuf = linux_sembuf;
+unsafe impl shadow_pod::Pod for sembuf {}
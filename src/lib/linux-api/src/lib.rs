@@ -51,6 +51,7 @@
 #[allow(unused)]
 mod bindings;
 
+pub mod aio;
 pub mod capability;
 pub mod close_range;
 pub mod epoll;
@@ -60,10 +61,14 @@ pub mod fcntl;
 pub mod futex;
 pub mod inet;
 pub mod ioctls;
+pub mod ipc;
 pub mod ldt;
 pub mod limits;
+pub mod membarrier;
 pub mod mman;
+pub mod mqueue;
 pub mod netlink;
+pub mod personality;
 pub mod poll;
 pub mod posix_types;
 pub mod prctl;
@@ -78,6 +83,7 @@ pub mod syscall;
 pub mod sysinfo;
 pub mod time;
 pub mod ucontext;
+pub mod userfaultfd;
 pub mod utsname;
 pub mod wait;
 
@@ -116,4 +122,10 @@ mod const_conversions {
 
         val as u16
     }
+
+    pub const fn i16_from_u32(val: u32) -> i16 {
+        assert!(val <= (i16::MAX as u32));
+
+        val as i16
+    }
 }
@@ -0,0 +1,27 @@
+// Manually translated from linux/membarrier.h, which isn't included in our generated bindings.
+
+/// Query the set of supported commands.
+pub const MEMBARRIER_CMD_QUERY: i32 = 0;
+/// Barrier across all threads of all processes (also known as `MEMBARRIER_CMD_SHARED`).
+pub const MEMBARRIER_CMD_GLOBAL: i32 = 1 << 0;
+/// Expedited barrier across all processes that registered for it.
+pub const MEMBARRIER_CMD_GLOBAL_EXPEDITED: i32 = 1 << 1;
+/// Register the process for [`MEMBARRIER_CMD_GLOBAL_EXPEDITED`].
+pub const MEMBARRIER_CMD_REGISTER_GLOBAL_EXPEDITED: i32 = 1 << 2;
+/// Expedited barrier across all threads of the calling process.
+pub const MEMBARRIER_CMD_PRIVATE_EXPEDITED: i32 = 1 << 3;
+/// Register the process for [`MEMBARRIER_CMD_PRIVATE_EXPEDITED`].
+pub const MEMBARRIER_CMD_REGISTER_PRIVATE_EXPEDITED: i32 = 1 << 4;
+/// Like [`MEMBARRIER_CMD_PRIVATE_EXPEDITED`], but also serializes instruction execution.
+pub const MEMBARRIER_CMD_PRIVATE_EXPEDITED_SYNC_CORE: i32 = 1 << 5;
+/// Register the process for [`MEMBARRIER_CMD_PRIVATE_EXPEDITED_SYNC_CORE`].
+pub const MEMBARRIER_CMD_REGISTER_PRIVATE_EXPEDITED_SYNC_CORE: i32 = 1 << 6;
+/// Restart the rseq critical sections of the process's threads.
+pub const MEMBARRIER_CMD_PRIVATE_EXPEDITED_RSEQ: i32 = 1 << 7;
+/// Register the process for [`MEMBARRIER_CMD_PRIVATE_EXPEDITED_RSEQ`].
+pub const MEMBARRIER_CMD_REGISTER_PRIVATE_EXPEDITED_RSEQ: i32 = 1 << 8;
+/// Return the commands that the process has registered for.
+pub const MEMBARRIER_CMD_GET_REGISTRATIONS: i32 = 1 << 9;
+
+/// Flag for [`MEMBARRIER_CMD_PRIVATE_EXPEDITED_RSEQ`]: only target the cpu given by `cpu_id`.
+pub const MEMBARRIER_CMD_FLAG_CPU: u32 = 1 << 0;
@@ -0,0 +1,37 @@
+//! POSIX message queues.
+//!
+//! We don't generate bindings for linux/mqueue.h, so the types here are manually translated.
+
+use crate::bindings;
+
+/// The maximum priority of a message, from `MQ_PRIO_MAX` in linux/mqueue.h.
+pub const MQ_PRIO_MAX: u32 = 32768;
+
+/// The default maximum number of messages in a new queue (`/proc/sys/fs/mqueue/msg_default`).
+pub const DFLT_MSG: i64 = 10;
+/// The default maximum message size of a new queue (`/proc/sys/fs/mqueue/msgsize_default`).
+pub const DFLT_MSGSIZE: i64 = 8192;
+/// The maximum number of messages in a queue (`HARD_MSGMAX` in linux/ipc_namespace.h).
+pub const HARD_MSGMAX: i64 = 65536;
+/// The maximum message size (`HARD_MSGSIZEMAX` in linux/ipc_namespace.h).
+pub const HARD_MSGSIZEMAX: i64 = 16 * 1024 * 1024;
+
+#[allow(non_camel_case_types)]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct linux_mq_attr {
+    /// Message queue flags
+    pub mq_flags: bindings::linux___kernel_long_t,
+    /// Maximum number of messages
+    pub mq_maxmsg: bindings::linux___kernel_long_t,
+    /// Maximum message size
+    pub mq_msgsize: bindings::linux___kernel_long_t,
+    /// Number of messages currently queued
+    pub mq_curmsgs: bindings::linux___kernel_long_t,
+    /// Ignored for input, zeroed for output
+    pub __reserved: [bindings::linux___kernel_long_t; 4],
+}
+
+#[allow(non_camel_case_types)]
+pub type mq_attr = linux_mq_attr;
+unsafe impl shadow_pod::Pod for mq_attr {}
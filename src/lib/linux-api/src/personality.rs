@@ -0,0 +1,10 @@
+// Manually translated from linux/personality.h, which isn't included in our generated bindings.
+
+/// The default execution domain.
+pub const PER_LINUX: u32 = 0x0000;
+/// The bits of a personality that select the execution domain.
+pub const PER_MASK: u32 = 0x00ff;
+/// Disable address space layout randomization.
+pub const ADDR_NO_RANDOMIZE: u32 = 0x0040000;
+/// Passing this value to `personality()` queries the personality without changing it.
+pub const PERSONALITY_QUERY: u32 = 0xffffffff;
@@ -1,4 +1,24 @@
-use crate::bindings;
+use crate::{bindings, const_conversions};
+
+bitflags::bitflags! {
+    /// Poll event types, as used in the `events` and `revents` members of `struct pollfd`.
+    #[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+    pub struct PollEvents: i16 {
+        const POLLIN = const_conversions::i16_from_u32(bindings::LINUX_POLLIN);
+        const POLLPRI = const_conversions::i16_from_u32(bindings::LINUX_POLLPRI);
+        const POLLOUT = const_conversions::i16_from_u32(bindings::LINUX_POLLOUT);
+        const POLLERR = const_conversions::i16_from_u32(bindings::LINUX_POLLERR);
+        const POLLHUP = const_conversions::i16_from_u32(bindings::LINUX_POLLHUP);
+        const POLLNVAL = const_conversions::i16_from_u32(bindings::LINUX_POLLNVAL);
+        const POLLRDNORM = const_conversions::i16_from_u32(bindings::LINUX_POLLRDNORM);
+        const POLLRDBAND = const_conversions::i16_from_u32(bindings::LINUX_POLLRDBAND);
+        const POLLWRNORM = const_conversions::i16_from_u32(bindings::LINUX_POLLWRNORM);
+        const POLLWRBAND = const_conversions::i16_from_u32(bindings::LINUX_POLLWRBAND);
+        const POLLMSG = const_conversions::i16_from_u32(bindings::LINUX_POLLMSG);
+        const POLLREMOVE = const_conversions::i16_from_u32(bindings::LINUX_POLLREMOVE);
+        const POLLRDHUP = const_conversions::i16_from_u32(bindings::LINUX_POLLRDHUP);
+    }
+}
 
 pub use bindings::linux_pollfd;
 #[allow(non_camel_case_types)]
@@ -218,3 +218,7 @@ impl From<i32> for PrctlOp {
         Self::new(val)
     }
 }
+
+/// The size of a task's name (`comm`) including the nul terminator, from `TASK_COMM_LEN` in
+/// linux/sched.h. Used by `PR_SET_NAME` and `PR_GET_NAME`.
+pub const TASK_COMM_LEN: usize = 16;
@@ -462,6 +462,17 @@ impl siginfo_t {
         SigInfoCode::try_from_raw(self.inner().lsi_code, self.inner().lsi_signo)
     }
 
+    /// The raw `si_code` value, which may not correspond to a known [`SigInfoCode`].
+    #[inline]
+    pub fn code_raw(&self) -> i32 {
+        self.inner().lsi_code
+    }
+
+    #[inline]
+    pub fn errno(&self) -> i32 {
+        self.inner().lsi_errno
+    }
+
     /// # Safety
     ///
     /// Pointers are safe to dereference iff those used to construct `self` (or set
@@ -535,6 +546,16 @@ impl siginfo_t {
         })
     }
 
+    /// Returns a copy of `self` with its signal number replaced by `signal`.
+    /// e.g. `rt_sigqueueinfo(2)` takes the rest of the siginfo from the caller,
+    /// but the signal number from its `sig` argument.
+    pub fn with_signal(&self, signal: Signal) -> Self {
+        let inner = self.inner();
+        // SAFETY: The other fields are copied from `self`, which already upholds
+        // the [`siginfo_t`] `Invariants`.
+        unsafe { Self::new(signal, inner.lsi_errno, inner.lsi_code, inner.l_sifields) }
+    }
+
     // TODO: Should `sender_pid` actually be `sender_tid`?
     pub fn new_for_kill(signal: Signal, sender_pid: i32, sender_uid: u32) -> Self {
         // sigaction(2):
@@ -575,6 +596,17 @@ impl siginfo_t {
     }
 
     pub fn new_for_timer(signal: Signal, timer_id: i32, overrun: i32) -> Self {
+        Self::new_for_timer_with_sigval(signal, timer_id, overrun, unsafe { core::mem::zeroed() })
+    }
+
+    /// Like [`Self::new_for_timer`], but also fills in the `sigev_value` configured for the
+    /// timer with timer_create(2).
+    pub fn new_for_timer_with_sigval(
+        signal: Signal,
+        timer_id: i32,
+        overrun: i32,
+        sigval: linux_sigval,
+    ) -> Self {
         // sigaction(2):
         // > Signals sent by POSIX.1b timers (since Linux 2.6) fill in si_overrun and
         // > si_timerid.  The si_timerid field is  an  internal ID  used by the kernel
@@ -591,7 +623,7 @@ impl siginfo_t {
                     l_timer: SigInfoDetailsTimer {
                         l_tid: timer_id,
                         l_overrun: overrun,
-                        l_sigval: core::mem::zeroed(),
+                        l_sigval: sigval,
                         l_sys_private: 0,
                     },
                 },
@@ -756,6 +788,7 @@ impl siginfo_t {
         exit_signal: Signal,
         child_pid: i32,
         child_uid: u32,
+        stop_signal: Signal,
         child_utime: i64,
         child_stime: i64,
     ) -> Self {
@@ -764,7 +797,7 @@ impl siginfo_t {
             SigInfoCodeCld::CLD_STOPPED,
             child_pid,
             child_uid,
-            Signal::SIGSTOP,
+            stop_signal,
             child_utime,
             child_stime,
         )
@@ -1439,6 +1472,10 @@ mod rt_sigaction_tests {
     }
 }
 
+pub use bindings::linux_sigval;
+#[allow(non_camel_case_types)]
+pub type sigval = linux_sigval;
+
 pub use bindings::linux_stack_t;
 #[allow(non_camel_case_types)]
 pub type stack_t = linux_stack_t;
@@ -0,0 +1,29 @@
+use crate::bindings;
+
+// Manually translated from linux/userfaultfd.h. The ioctl request numbers are defined there with
+// the `_IOWR` macro, which bindgen doesn't evaluate.
+
+/// The userfaultfd API version.
+pub const UFFD_API: u64 = 0xaa;
+
+/// Flag for the `userfaultfd` syscall: only handle faults from user space.
+pub const UFFD_USER_MODE_ONLY: u32 = 1;
+
+/// `_IOWR(UFFDIO, _UFFDIO_API, struct uffdio_api)`
+pub const UFFDIO_API: u32 = 0xc018_aa3f;
+
+#[allow(non_camel_case_types)]
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default)]
+pub struct linux_uffdio_api {
+    /// The API version requested by userspace (must be [`UFFD_API`]).
+    pub api: bindings::linux___u64,
+    /// The features requested by userspace, and on return the features supported by the kernel.
+    pub features: bindings::linux___u64,
+    /// On return, a bitmask of the ioctls supported by the userfaultfd.
+    pub ioctls: bindings::linux___u64,
+}
+
+#[allow(non_camel_case_types)]
+pub type uffdio_api = linux_uffdio_api;
+unsafe impl shadow_pod::Pod for uffdio_api {}
@@ -205,7 +205,7 @@ impl<'sched, 'pool, 'scope, HostType: Host> SchedulerScope<'sched, 'pool, 'scope
     /// Each call of the closure will be given an element of `data`, and this element will not be
     /// given to any other thread while this closure is running, which means you should not expect
     /// any contention on this element if using interior mutability.  The provided slice **must**
-    /// have a length of at least [`Scheduler::parallelism`]. If the data needs to be initialized,
+    /// have a length of at least [`Scheduler::parallelism`], otherwise this will panic. If the data needs to be initialized,
     /// it should be initialized before calling this function and not at the beginning of the
     /// closure. The element may be given to multiple threads, but never two threads at the same
     /// time.
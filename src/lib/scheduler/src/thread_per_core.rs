@@ -4,6 +4,7 @@
 #![forbid(unsafe_code)]
 
 use std::fmt::Debug;
+use std::num::NonZeroUsize;
 
 use crossbeam::queue::ArrayQueue;
 
@@ -20,6 +21,7 @@ pub struct ThreadPerCoreSched<HostType: Host> {
     thread_hosts: Vec<ArrayQueue<HostType>>,
     thread_hosts_processed: Vec<ArrayQueue<HostType>>,
     hosts_need_swap: bool,
+    max_hosts_per_turn: Option<NonZeroUsize>,
 }
 
 impl<HostType: Host> ThreadPerCoreSched<HostType> {
@@ -70,9 +72,19 @@ impl<HostType: Host> ThreadPerCoreSched<HostType> {
             thread_hosts,
             thread_hosts_processed: thread_hosts_2,
             hosts_need_swap: false,
+            max_hosts_per_turn: None,
         }
     }
 
+    /// Limit the number of hosts a thread may process before it yields. After each turn of at most
+    /// `max` hosts, the thread yields to the OS and then continues with the queue that has the most
+    /// remaining hosts, which lets idle threads steal from heavily-loaded threads sooner. If `None`
+    /// (the default), each thread processes its own hosts before stealing hosts from other
+    /// threads, and never yields.
+    pub fn set_max_hosts_per_turn(&mut self, max: Option<NonZeroUsize>) {
+        self.max_hosts_per_turn = max;
+    }
+
     /// See [`crate::Scheduler::parallelism`].
     pub fn parallelism(&self) -> usize {
         self.num_threads
@@ -96,6 +108,7 @@ impl<HostType: Host> ThreadPerCoreSched<HostType> {
         let thread_hosts = &self.thread_hosts;
         let thread_hosts_processed = &self.thread_hosts_processed;
         let hosts_need_swap = &mut self.hosts_need_swap;
+        let max_hosts_per_turn = self.max_hosts_per_turn;
 
         // we cannot access `self` after calling `pool.scope()` since `SchedulerScope` has a
         // lifetime of `'scope` (which at minimum spans the entire current function)
@@ -105,6 +118,7 @@ impl<HostType: Host> ThreadPerCoreSched<HostType> {
                 thread_hosts,
                 thread_hosts_processed,
                 hosts_need_swap,
+                max_hosts_per_turn,
                 runner: s,
             };
 
@@ -126,6 +140,7 @@ where
     thread_hosts: &'sched Vec<ArrayQueue<HostType>>,
     thread_hosts_processed: &'sched Vec<ArrayQueue<HostType>>,
     hosts_need_swap: &'sched mut bool,
+    max_hosts_per_turn: Option<NonZeroUsize>,
     runner: TaskRunner<'pool, 'scope>,
 }
 
@@ -145,6 +160,7 @@ impl<'sched, 'pool, 'scope, HostType: Host> SchedulerScope<'sched, 'pool, 'scope
                 thread_hosts_from: self.thread_hosts,
                 thread_hosts_to: &self.thread_hosts_processed[i],
                 this_thread_index: i,
+                max_hosts_per_turn: self.max_hosts_per_turn,
             };
 
             f(i, &mut host_iter);
@@ -161,6 +177,14 @@ impl<'sched, 'pool, 'scope, HostType: Host> SchedulerScope<'sched, 'pool, 'scope
     ) where
         T: Sync,
     {
+        let parallelism = self.thread_hosts.len();
+        assert!(
+            data.len() >= parallelism,
+            "run_with_data() requires a slice of at least {parallelism} elements (the scheduler's \
+             parallelism), but was given {}",
+            data.len(),
+        );
+
         self.runner.run(move |i| {
             let this_elem = &data[i];
 
@@ -168,6 +192,7 @@ impl<'sched, 'pool, 'scope, HostType: Host> SchedulerScope<'sched, 'pool, 'scope
                 thread_hosts_from: self.thread_hosts,
                 thread_hosts_to: &self.thread_hosts_processed[i],
                 this_thread_index: i,
+                max_hosts_per_turn: self.max_hosts_per_turn,
             };
 
             f(i, &mut host_iter, this_elem);
@@ -187,6 +212,9 @@ pub struct HostIter<'a, HostType: Host> {
     /// The index of this thread. This is the first queue of `thread_hosts_from` that we take hosts
     /// from.
     this_thread_index: usize,
+    /// The maximum number of hosts to process before yielding. See
+    /// [`ThreadPerCoreSched::set_max_hosts_per_turn`].
+    max_hosts_per_turn: Option<NonZeroUsize>,
 }
 
 impl<'a, HostType: Host> HostIter<'a, HostType> {
@@ -195,6 +223,10 @@ impl<'a, HostType: Host> HostIter<'a, HostType> {
     where
         F: FnMut(HostType) -> HostType,
     {
+        if let Some(max_hosts_per_turn) = self.max_hosts_per_turn {
+            return self.for_each_with_turns(max_hosts_per_turn, f);
+        }
+
         for from_queue in self
             .thread_hosts_from
             .iter()
@@ -208,6 +240,50 @@ impl<'a, HostType: Host> HostIter<'a, HostType> {
             }
         }
     }
+
+    fn for_each_with_turns<F>(&mut self, max_hosts_per_turn: NonZeroUsize, mut f: F)
+    where
+        F: FnMut(HostType) -> HostType,
+    {
+        loop {
+            for _ in 0..max_hosts_per_turn.get() {
+                let Some(host) = self.pop_from_most_loaded() else {
+                    // all queues are empty
+                    return;
+                };
+                self.thread_hosts_to.push(f(host)).unwrap();
+            }
+
+            // let other threads run before we continue
+            std::thread::yield_now();
+        }
+    }
+
+    /// Take a host from the queue with the most remaining hosts, preferring this thread's queue
+    /// when there are ties. Returns `None` if all queues are empty.
+    fn pop_from_most_loaded(&self) -> Option<HostType> {
+        loop {
+            let mut most_loaded: Option<&ArrayQueue<HostType>> = None;
+
+            for queue in self
+                .thread_hosts_from
+                .iter()
+                .cycle()
+                // start from the current thread index
+                .skip(self.this_thread_index)
+                .take(self.thread_hosts_from.len())
+            {
+                if queue.len() > most_loaded.map(|x| x.len()).unwrap_or(0) {
+                    most_loaded = Some(queue);
+                }
+            }
+
+            // another thread may have emptied the queue since we checked its length, so try again
+            if let Some(host) = most_loaded?.pop() {
+                return Some(host);
+            }
+        }
+    }
 }
 
 #[cfg(any(test, doctest))]
@@ -274,6 +350,45 @@ mod tests {
         sched.join();
     }
 
+    #[test]
+    fn test_run_per_thread_init() {
+        std::thread_local! {
+            static THREAD_VALUE: std::cell::Cell<Option<usize>> = const { std::cell::Cell::new(None) };
+        }
+
+        let hosts = [(); 5].map(|_| TestHost {});
+        let mut sched: ThreadPerCoreSched<TestHost> =
+            ThreadPerCoreSched::new(&[None, None, None], hosts, false);
+
+        let visits = [(); 3].map(|_| AtomicU32::new(0));
+
+        // initialize some per-thread state without touching the hosts
+        sched.scope(|s| {
+            s.run(|i| {
+                THREAD_VALUE.with(|x| x.set(Some(i)));
+                visits[i].fetch_add(1, Ordering::SeqCst);
+            });
+        });
+
+        assert!(visits.iter().all(|x| x.load(Ordering::SeqCst) == 1));
+
+        // the hosts weren't moved by `run()`, and each thread kept its state
+        let counter = AtomicU32::new(0);
+        sched.scope(|s| {
+            s.run_with_hosts(|i, hosts| {
+                assert_eq!(THREAD_VALUE.with(|x| x.get()), Some(i));
+                hosts.for_each(|host| {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    host
+                });
+            });
+        });
+
+        assert_eq!(counter.load(Ordering::SeqCst), 5);
+
+        sched.join();
+    }
+
     #[test]
     fn test_run_with_hosts() {
         let hosts = [(); 5].map(|_| TestHost {});
@@ -298,6 +413,45 @@ mod tests {
         sched.join();
     }
 
+    #[test]
+    fn test_max_hosts_per_turn() {
+        #[derive(Debug)]
+        struct WeightedHost {
+            heavy: bool,
+        }
+
+        // hosts are assigned round-robin, so all heavy hosts start on the first thread
+        let hosts = (0..20).map(|i| WeightedHost { heavy: i % 2 == 0 });
+        let mut sched: ThreadPerCoreSched<WeightedHost> =
+            ThreadPerCoreSched::new(&[None, None], hosts, false);
+        sched.set_max_hosts_per_turn(Some(NonZeroUsize::new(1).unwrap()));
+
+        let counts = [AtomicU32::new(0), AtomicU32::new(0)];
+
+        for _ in 0..3 {
+            sched.scope(|s| {
+                s.run_with_hosts(|i, hosts| {
+                    hosts.for_each(|host| {
+                        if host.heavy {
+                            std::thread::sleep(std::time::Duration::from_millis(5));
+                        }
+                        counts[i].fetch_add(1, Ordering::SeqCst);
+                        host
+                    });
+                });
+            });
+        }
+
+        let counts = counts.map(|x| x.into_inner());
+        assert_eq!(counts.iter().sum::<u32>(), 20 * 3);
+
+        // the second thread only has light hosts, so it should have taken some of the first
+        // thread's heavy hosts
+        assert!(counts[1] > 10 * 3, "{counts:?}");
+
+        sched.join();
+    }
+
     #[test]
     fn test_run_with_data() {
         let hosts = [(); 5].map(|_| TestHost {});
@@ -324,4 +478,20 @@ mod tests {
 
         sched.join();
     }
+
+    #[test]
+    #[should_panic(expected = "requires a slice of at least 2 elements")]
+    fn test_run_with_data_too_short() {
+        let hosts = [(); 5].map(|_| TestHost {});
+        let mut sched: ThreadPerCoreSched<TestHost> =
+            ThreadPerCoreSched::new(&[None, None], hosts, false);
+
+        let data = vec![0u32; sched.parallelism() - 1];
+
+        sched.scope(|s| {
+            s.run_with_data(&data, |_, hosts, _| {
+                hosts.for_each(|host| host);
+            });
+        });
+    }
 }
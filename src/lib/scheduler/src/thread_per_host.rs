@@ -3,12 +3,12 @@
 // unsafe code should be isolated to the thread pool
 #![forbid(unsafe_code)]
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::fmt::Debug;
 use std::sync::Mutex;
 use std::thread::LocalKey;
 
-use crate::pools::bounded::{ParallelismBoundedThreadPool, TaskRunner};
+use crate::pools::bounded::{ParallelismBoundedThreadPool, TaskData, TaskRunner};
 use crate::CORE_AFFINITY;
 
 pub trait Host: Debug + Send + 'static {}
@@ -20,6 +20,9 @@ pub struct ThreadPerHostSched<HostType: Host> {
     pool: ParallelismBoundedThreadPool,
     /// Thread-local storage where a thread can store its host.
     host_storage: &'static LocalKey<RefCell<Option<HostType>>>,
+    /// The cpu that each thread's host is pinned to, overriding the cpu of the thread's logical
+    /// processor.
+    host_cpu_ids: Vec<Option<u32>>,
 }
 
 impl<HostType: Host> ThreadPerHostSched<HostType> {
@@ -35,6 +38,25 @@ impl<HostType: Host> ThreadPerHostSched<HostType> {
         host_storage: &'static LocalKey<RefCell<Option<HostType>>>,
         hosts: T,
     ) -> Self
+    where
+        T: IntoIterator<Item = HostType, IntoIter: ExactSizeIterator>,
+    {
+        Self::new_with_host_cpus(cpu_ids, host_storage, hosts, |_| None)
+    }
+
+    /// Like [`ThreadPerHostSched::new`], but `host_cpu` can pin individual hosts to a specific OS
+    /// processor. A host's thread will always run on the cpu returned by `host_cpu`, regardless of
+    /// which logical processor it's running on. If `host_cpu` returns `None` for a host, the host's
+    /// thread will run on the cpu of its logical processor as usual.
+    ///
+    /// A pinned host still occupies one of the logical processors while running, so the cpus used
+    /// by pinned hosts should generally not be included in `cpu_ids`.
+    pub fn new_with_host_cpus<T>(
+        cpu_ids: &[Option<u32>],
+        host_storage: &'static LocalKey<RefCell<Option<HostType>>>,
+        hosts: T,
+        host_cpu: impl Fn(&HostType) -> Option<u32>,
+    ) -> Self
     where
         T: IntoIterator<Item = HostType, IntoIter: ExactSizeIterator>,
     {
@@ -42,8 +64,17 @@ impl<HostType: Host> ThreadPerHostSched<HostType> {
 
         let mut pool = ParallelismBoundedThreadPool::new(cpu_ids, hosts.len(), "shadow-worker");
 
+        // thread `i` will take host `i`, so this is indexed by both the thread and host index
+        let (hosts, host_cpu_ids): (Vec<_>, Vec<_>) = hosts
+            .map(|x| {
+                let cpu_id = host_cpu(&x);
+                (x, cpu_id)
+            })
+            .unzip();
+
         // for determinism, threads will take hosts from a vec rather than a queue
-        let hosts: Vec<Mutex<Option<HostType>>> = hosts.map(|x| Mutex::new(Some(x))).collect();
+        let hosts: Vec<Mutex<Option<HostType>>> =
+            hosts.into_iter().map(|x| Mutex::new(Some(x))).collect();
 
         // have each thread take a host and store it as a thread-local
         pool.scope(|s| {
@@ -56,7 +87,11 @@ impl<HostType: Host> ThreadPerHostSched<HostType> {
             });
         });
 
-        Self { pool, host_storage }
+        Self {
+            pool,
+            host_storage,
+            host_cpu_ids,
+        }
     }
 
     /// See [`crate::Scheduler::parallelism`].
@@ -70,10 +105,14 @@ impl<HostType: Host> ThreadPerHostSched<HostType> {
         f: impl for<'a> FnOnce(SchedulerScope<'a, 'scope, HostType>) + 'scope,
     ) {
         let host_storage = self.host_storage;
+        let parallelism = self.parallelism();
+        let host_cpu_ids = &self.host_cpu_ids;
         self.pool.scope(move |s| {
             let sched_scope = SchedulerScope {
                 runner: s,
                 host_storage,
+                parallelism,
+                host_cpu_ids,
             };
 
             (f)(sched_scope);
@@ -106,16 +145,17 @@ pub struct SchedulerScope<'pool, 'scope, HostType: Host> {
     runner: TaskRunner<'pool, 'scope>,
     /// Thread-local storage where a thread can retrieve its host.
     host_storage: &'static LocalKey<RefCell<Option<HostType>>>,
+    /// The number of logical processors, which is the minimum length of the `run_with_data` slice.
+    parallelism: usize,
+    /// The cpu that each thread's host is pinned to, if any.
+    host_cpu_ids: &'scope [Option<u32>],
 }
 
 impl<'pool, 'scope, HostType: Host> SchedulerScope<'pool, 'scope, HostType> {
     /// See [`crate::SchedulerScope::run`].
     pub fn run(self, f: impl Fn(usize) + Sync + Send + 'scope) {
         self.runner.run(move |task_context| {
-            // update the thread-local core affinity
-            if let Some(cpu_id) = task_context.cpu_id {
-                CORE_AFFINITY.with(|x| x.set(Some(cpu_id)));
-            }
+            set_core_affinity(task_context, self.host_cpu_ids[task_context.thread_idx]);
 
             (f)(task_context.thread_idx)
         });
@@ -124,10 +164,7 @@ impl<'pool, 'scope, HostType: Host> SchedulerScope<'pool, 'scope, HostType> {
     /// See [`crate::SchedulerScope::run_with_hosts`].
     pub fn run_with_hosts(self, f: impl Fn(usize, &mut HostIter<HostType>) + Send + Sync + 'scope) {
         self.runner.run(move |task_context| {
-            // update the thread-local core affinity
-            if let Some(cpu_id) = task_context.cpu_id {
-                CORE_AFFINITY.with(|x| x.set(Some(cpu_id)));
-            }
+            set_core_affinity(task_context, self.host_cpu_ids[task_context.thread_idx]);
 
             self.host_storage.with(|host| {
                 let mut host = host.borrow_mut();
@@ -149,11 +186,16 @@ impl<'pool, 'scope, HostType: Host> SchedulerScope<'pool, 'scope, HostType> {
     ) where
         T: Sync,
     {
+        let parallelism = self.parallelism;
+        assert!(
+            data.len() >= parallelism,
+            "run_with_data() requires a slice of at least {parallelism} elements (the scheduler's \
+             parallelism), but was given {}",
+            data.len(),
+        );
+
         self.runner.run(move |task_context| {
-            // update the thread-local core affinity
-            if let Some(cpu_id) = task_context.cpu_id {
-                CORE_AFFINITY.with(|x| x.set(Some(cpu_id)));
-            }
+            set_core_affinity(task_context, self.host_cpu_ids[task_context.thread_idx]);
 
             let this_elem = &data[task_context.processor_idx];
 
@@ -170,6 +212,38 @@ impl<'pool, 'scope, HostType: Host> SchedulerScope<'pool, 'scope, HostType> {
     }
 }
 
+std::thread_local! {
+    /// The logical processor that this thread was running on when it was last pinned to its host's
+    /// cpu.
+    static PINNED_ON_PROCESSOR: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// Update the thread-local core affinity for the current task. If the thread's host is pinned to
+/// `host_cpu_id`, the thread is moved to that cpu.
+fn set_core_affinity(task_context: &TaskData, host_cpu_id: Option<u32>) {
+    let Some(host_cpu_id) = host_cpu_id else {
+        if let Some(cpu_id) = task_context.cpu_id {
+            CORE_AFFINITY.with(|x| x.set(Some(cpu_id)));
+        }
+        return;
+    };
+
+    // the thread pool re-pins a thread when it moves the thread to a different logical processor,
+    // so we only need to pin the thread again if its logical processor has changed
+    if PINNED_ON_PROCESSOR.with(|x| x.get()) != Some(task_context.processor_idx) {
+        let mut cpus = nix::sched::CpuSet::new();
+        cpus.set(host_cpu_id as usize).unwrap();
+
+        // only set the affinity if not running in miri
+        #[cfg(not(miri))]
+        nix::sched::sched_setaffinity(nix::unistd::Pid::from_raw(0), &cpus).unwrap();
+
+        PINNED_ON_PROCESSOR.with(|x| x.set(Some(task_context.processor_idx)));
+    }
+
+    CORE_AFFINITY.with(|x| x.set(Some(host_cpu_id)));
+}
+
 /// Supports iterating over all hosts assigned to this thread. For this thread-per-host scheduler,
 /// there will only ever be one host per thread.
 pub struct HostIter<HostType: Host> {
@@ -201,6 +275,13 @@ mod tests {
         static SCHED_HOST_STORAGE: RefCell<Option<TestHost>> = const { RefCell::new(None) };
     }
 
+    #[derive(Debug)]
+    struct IdHost(u32);
+
+    std::thread_local! {
+        static SCHED_ID_HOST_STORAGE: RefCell<Option<IdHost>> = const { RefCell::new(None) };
+    }
+
     #[test]
     fn test_parallelism() {
         let hosts = [(); 5].map(|_| TestHost {});
@@ -280,6 +361,57 @@ mod tests {
         sched.join();
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_host_cpus() {
+        // pin to a cpu that we're allowed to run on
+        let allowed = nix::sched::sched_getaffinity(nix::unistd::Pid::from_raw(0)).unwrap();
+        let pinned_cpu = (0..nix::sched::CpuSet::count())
+            .find(|x| allowed.is_set(*x).unwrap())
+            .unwrap() as u32;
+
+        let hosts = (0..5).map(IdHost);
+        let mut sched: ThreadPerHostSched<IdHost> = ThreadPerHostSched::new_with_host_cpus(
+            &[None, None],
+            &SCHED_ID_HOST_STORAGE,
+            hosts,
+            |host| (host.0 == 3).then_some(pinned_cpu),
+        );
+
+        let counter = AtomicU32::new(0);
+
+        for _ in 0..3 {
+            sched.scope(|s| {
+                s.run_with_hosts(|_, hosts| {
+                    hosts.for_each(|host| {
+                        if host.0 == 3 {
+                            assert_eq!(crate::core_affinity(), Some(pinned_cpu));
+
+                            let cpus = nix::sched::sched_getaffinity(nix::unistd::Pid::from_raw(0))
+                                .unwrap();
+                            assert!(cpus.is_set(pinned_cpu as usize).unwrap());
+                            assert_eq!(
+                                (0..nix::sched::CpuSet::count())
+                                    .filter(|x| cpus.is_set(*x).unwrap())
+                                    .count(),
+                                1
+                            );
+                        } else {
+                            assert_eq!(crate::core_affinity(), None);
+                        }
+
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        host
+                    });
+                });
+            });
+        }
+
+        assert_eq!(counter.load(Ordering::SeqCst), 5 * 3);
+
+        sched.join();
+    }
+
     #[test]
     fn test_run_with_data() {
         let hosts = [(); 5].map(|_| TestHost {});
@@ -306,4 +438,20 @@ mod tests {
 
         sched.join();
     }
+
+    #[test]
+    #[should_panic(expected = "requires a slice of at least 2 elements")]
+    fn test_run_with_data_too_short() {
+        let hosts = [(); 5].map(|_| TestHost {});
+        let mut sched: ThreadPerHostSched<TestHost> =
+            ThreadPerHostSched::new(&[None, None], &SCHED_HOST_STORAGE, hosts);
+
+        let data = vec![0u32; sched.parallelism() - 1];
+
+        sched.scope(|s| {
+            s.run_with_data(&data, |_, hosts, _| {
+                hosts.for_each(|host| host);
+            });
+        });
+    }
 }
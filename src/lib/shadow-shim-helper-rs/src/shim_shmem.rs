@@ -1,3 +1,4 @@
+use linux_api::errno::Errno;
 use linux_api::signal::{sigaction, siginfo_t, sigset_t, stack_t, Signal};
 use shadow_shmem::allocator::{ShMemBlock, ShMemBlockSerialized};
 use vasi::VirtualAddressSpaceIndependent;
@@ -180,6 +181,7 @@ impl ProcessShmem {
                     pending_signals: sigset_t::EMPTY,
                     pending_standard_siginfos: [siginfo_t::default();
                         Signal::STANDARD_MAX.as_i32() as usize],
+                    pending_realtime_siginfos: RealtimeSiginfoQueue::new(),
                     signal_actions: [sigaction::default(); Signal::MAX.as_i32() as usize],
                 },
             ),
@@ -201,21 +203,91 @@ pub struct ProcessShmemProtected {
     #[unsafe_assume_virtual_address_space_independent]
     pending_standard_siginfos: [siginfo_t; Signal::STANDARD_MAX.as_i32() as usize],
 
+    // Queued instances of realtime signals.
+    pending_realtime_siginfos: RealtimeSiginfoQueue,
+
     // actions for both standard and realtime signals.
-    // We currently support configuring handlers for realtime signals, but not
-    // actually delivering them. This is to handle the case where handlers are
-    // defensively installed, but not used in practice.
+    // Thread-directed realtime signals can currently only be sent by timers;
+    // e.g. not via `tkill`.
     // SAFETY: we ensure the internal pointers aren't dereferenced
     // outside of its original virtual address space.
     #[unsafe_assume_virtual_address_space_independent]
     signal_actions: [sigaction; Signal::MAX.as_i32() as usize],
 }
 
+/// Maximum number of realtime signals that can be queued for a process or thread.
+pub const REALTIME_SIGNAL_QUEUE_CAPACITY: usize = 32;
+
 // We have several arrays indexed by signal number - 1.
 fn signal_idx(signal: Signal) -> usize {
     (i32::from(signal) - 1) as usize
 }
 
+/// Queued instances of realtime signals, in the order they were sent. Unlike
+/// standard signals, multiple instances of a realtime signal can be pending.
+#[derive(VirtualAddressSpaceIndependent, Copy, Clone)]
+#[repr(C)]
+struct RealtimeSiginfoQueue {
+    // Only the first `len` entries are valid.
+    // SAFETY: we ensure the internal pointers aren't dereferenced
+    // outside of its original virtual address space.
+    #[unsafe_assume_virtual_address_space_independent]
+    siginfos: [siginfo_t; REALTIME_SIGNAL_QUEUE_CAPACITY],
+    len: usize,
+}
+
+impl RealtimeSiginfoQueue {
+    fn new() -> Self {
+        Self {
+            siginfos: [siginfo_t::default(); REALTIME_SIGNAL_QUEUE_CAPACITY],
+            len: 0,
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.len == REALTIME_SIGNAL_QUEUE_CAPACITY
+    }
+
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    fn as_slice(&self) -> &[siginfo_t] {
+        &self.siginfos[..self.len]
+    }
+
+    /// Queue `info`, marking its signal as pending in `pending_signals`. Fails
+    /// with `EAGAIN` if the queue is full.
+    fn push(&mut self, pending_signals: &mut sigset_t, info: &siginfo_t) -> Result<(), Errno> {
+        let signal = info.signal().unwrap();
+        assert!(signal.is_realtime());
+        if self.is_full() {
+            return Err(Errno::EAGAIN);
+        }
+        self.siginfos[self.len] = *info;
+        self.len += 1;
+        pending_signals.add(signal);
+        Ok(())
+    }
+
+    /// Remove and return the oldest instance of `signal`. It's removed from
+    /// `pending_signals` if that was the last instance.
+    fn take(&mut self, pending_signals: &mut sigset_t, signal: Signal) -> siginfo_t {
+        let is_signal = |info: &siginfo_t| matches!(info.signal(), Ok(s) if s == signal);
+
+        let queue = &mut self.siginfos[..self.len];
+        let idx = queue.iter().position(is_signal).unwrap();
+        let info = queue[idx];
+        queue.copy_within(idx + 1.., idx);
+        self.len -= 1;
+
+        if !self.as_slice().iter().any(is_signal) {
+            pending_signals.del(signal);
+        }
+        info
+    }
+}
+
 impl ProcessShmemProtected {
     pub fn pending_standard_siginfo(&self, signal: Signal) -> Option<&siginfo_t> {
         if self.pending_signals.has(signal) {
@@ -230,6 +302,35 @@ impl ProcessShmemProtected {
         self.pending_standard_siginfos[signal_idx(signal)] = *info;
     }
 
+    /// Queue an instance of a realtime signal, and mark it as pending. Fails
+    /// with `EAGAIN` if the queue is full.
+    pub fn queue_realtime_siginfo(&mut self, info: &siginfo_t) -> Result<(), Errno> {
+        self.pending_realtime_siginfos
+            .push(&mut self.pending_signals, info)
+    }
+
+    pub fn realtime_signal_queue_is_full(&self) -> bool {
+        self.pending_realtime_siginfos.is_full()
+    }
+
+    /// The queued instances of realtime signals, oldest first.
+    pub fn queued_realtime_siginfos(&self) -> &[siginfo_t] {
+        self.pending_realtime_siginfos.as_slice()
+    }
+
+    /// Remove and return the siginfo of one instance of the pending `signal`.
+    /// It stays pending if it's a realtime signal with more instances queued.
+    fn take_pending_siginfo(&mut self, signal: Signal) -> siginfo_t {
+        if signal.is_realtime() {
+            return self
+                .pending_realtime_siginfos
+                .take(&mut self.pending_signals, signal);
+        }
+        let info = *self.pending_standard_siginfo(signal).unwrap();
+        self.pending_signals.del(signal);
+        info
+    }
+
     /// # Safety
     ///
     /// Only valid if pointers in `src` sigactions are valid in `self`'s address
@@ -259,6 +360,7 @@ impl ProcessShmemProtected {
     /// This drops all pending signals. Intended primarily for use with exec.
     pub fn clear_pending_signals(&mut self) {
         self.pending_signals = sigset_t::EMPTY;
+        self.pending_realtime_siginfos.clear();
     }
 
     pub fn take_pending_unblocked_signal(
@@ -270,11 +372,17 @@ impl ProcessShmemProtected {
             None
         } else {
             let signal = pending_unblocked_signals.lowest().unwrap();
-            let info = *self.pending_standard_siginfo(signal).unwrap();
-            self.pending_signals.del(signal);
+            let info = self.take_pending_siginfo(signal);
             Some((signal, info))
         }
     }
+
+    /// Take the lowest pending signal that is in `mask`, regardless of whether it's blocked.
+    pub fn take_pending_signal_in(&mut self, mask: sigset_t) -> Option<(Signal, siginfo_t)> {
+        let signal = (self.pending_signals & mask).lowest()?;
+        let info = self.take_pending_siginfo(signal);
+        Some((signal, info))
+    }
 }
 
 #[derive(VirtualAddressSpaceIndependent)]
@@ -299,6 +407,7 @@ impl ThreadShmem {
                     pending_signals: sigset_t::EMPTY,
                     pending_standard_siginfos: [siginfo_t::default();
                         Signal::STANDARD_MAX.as_i32() as usize],
+                    pending_realtime_siginfos: RealtimeSiginfoQueue::new(),
                     blocked_signals: sigset_t::EMPTY,
                     sigaltstack: StackWrapper(stack_t {
                         ss_sp: std::ptr::null_mut(),
@@ -335,6 +444,9 @@ pub struct ThreadShmemProtected {
     #[unsafe_assume_virtual_address_space_independent]
     pending_standard_siginfos: [siginfo_t; Signal::STANDARD_MAX.as_i32() as usize],
 
+    // Queued instances of realtime signals.
+    pending_realtime_siginfos: RealtimeSiginfoQueue,
+
     // Signal mask, e.g. as set by `sigprocmask`.
     // We don't use sigset_t since glibc uses a much larger bitfield than
     // actually supported by the kernel.
@@ -358,6 +470,31 @@ impl ThreadShmemProtected {
         self.pending_standard_siginfos[signal_idx(signal)] = *info;
     }
 
+    /// Queue an instance of a realtime signal, and mark it as pending. Fails
+    /// with `EAGAIN` if the queue is full.
+    pub fn queue_realtime_siginfo(&mut self, info: &siginfo_t) -> Result<(), Errno> {
+        self.pending_realtime_siginfos
+            .push(&mut self.pending_signals, info)
+    }
+
+    /// The queued instances of realtime signals, oldest first.
+    pub fn queued_realtime_siginfos(&self) -> &[siginfo_t] {
+        self.pending_realtime_siginfos.as_slice()
+    }
+
+    /// Remove and return the siginfo of one instance of the pending `signal`.
+    /// It stays pending if it's a realtime signal with more instances queued.
+    fn take_pending_siginfo(&mut self, signal: Signal) -> siginfo_t {
+        if signal.is_realtime() {
+            return self
+                .pending_realtime_siginfos
+                .take(&mut self.pending_signals, signal);
+        }
+        let info = *self.pending_standard_siginfo(signal).unwrap();
+        self.pending_signals.del(signal);
+        info
+    }
+
     /// # Safety
     ///
     /// `stack_t::ss_sp` must not be dereferenced except from corresponding
@@ -381,11 +518,17 @@ impl ThreadShmemProtected {
             None
         } else {
             let signal = pending_unblocked_signals.lowest().unwrap();
-            let info = *self.pending_standard_siginfo(signal).unwrap();
-            self.pending_signals.del(signal);
+            let info = self.take_pending_siginfo(signal);
             Some((signal, info))
         }
     }
+
+    /// Take the lowest pending signal that is in `mask`, regardless of whether it's blocked.
+    pub fn take_pending_signal_in(&mut self, mask: sigset_t) -> Option<(Signal, siginfo_t)> {
+        let signal = (self.pending_signals & mask).lowest()?;
+        let info = self.take_pending_siginfo(signal);
+        Some((signal, info))
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -415,6 +558,21 @@ pub fn take_pending_unblocked_signal(
         })
 }
 
+/// Take the next thread- *or* process-directed signal that is in `mask`, regardless of whether
+/// it's blocked. Used to implement signalfd(2).
+pub fn take_pending_signal_in(
+    lock: &HostShmemProtected,
+    process: &ProcessShmem,
+    thread: &ThreadShmem,
+    mask: sigset_t,
+) -> Option<(Signal, siginfo_t)> {
+    let mut thread_protected = thread.protected.borrow_mut(&lock.root);
+    thread_protected.take_pending_signal_in(mask).or_else(|| {
+        let mut process_protected = process.protected.borrow_mut(&lock.root);
+        process_protected.take_pending_signal_in(mask)
+    })
+}
+
 pub mod export {
     use std::sync::atomic::Ordering;
 
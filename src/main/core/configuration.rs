@@ -438,6 +438,12 @@ pub struct ExperimentalOptions {
     #[clap(help = EXP_HELP.get("strace_logging_mode").unwrap().as_str())]
     pub strace_logging_mode: Option<StraceLoggingMode>,
 
+    /// Append a summary of each process's syscalls to its strace file when it exits
+    #[clap(hide_short_help = true)]
+    #[clap(long, value_name = "bool")]
+    #[clap(help = EXP_HELP.get("strace_summary").unwrap().as_str())]
+    pub strace_summary: Option<bool>,
+
     /// Max amount of execution-time latency allowed to accumulate before the
     /// clock is moved forward. Moving the clock forward is a potentially
     /// expensive operation, so larger values reduce simulation overhead, at the
@@ -477,6 +483,12 @@ pub struct ExperimentalOptions {
     #[clap(help = EXP_HELP.get("report_errors_to_stderr").unwrap().as_str())]
     pub report_errors_to_stderr: Option<bool>,
 
+    /// When true, gzip-compress the log messages written to stdout.
+    #[clap(hide_short_help = true)]
+    #[clap(long, value_name = "bool")]
+    #[clap(help = EXP_HELP.get("gzip_log_output").unwrap().as_str())]
+    pub gzip_log_output: Option<bool>,
+
     /// Use the rust TCP implementation
     #[clap(hide_short_help = true)]
     #[clap(long, value_name = "bool")]
@@ -529,8 +541,10 @@ impl Default for ExperimentalOptions {
                 units::TimePrefix::Sec,
             ))),
             strace_logging_mode: Some(StraceLoggingMode::Off),
+            strace_summary: Some(false),
             scheduler: Some(Scheduler::ThreadPerCore),
             report_errors_to_stderr: Some(true),
+            gzip_log_output: Some(false),
             use_new_tcp: Some(false),
         }
     }
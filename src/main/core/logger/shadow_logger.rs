@@ -1,15 +1,20 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc::{Receiver, Sender};
 use std::sync::Arc;
-use std::sync::{Mutex, RwLock};
+use std::sync::{Mutex, MutexGuard, RwLock};
 use std::time::Duration;
 
 use crossbeam::queue::ArrayQueue;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
 use logger as c_log;
 use once_cell::sync::{Lazy, OnceCell};
 use shadow_shim_helper_rs::emulated_time::EmulatedTime;
 use shadow_shim_helper_rs::util::time::TimeParts;
+use shadow_shim_helper_rs::HostId;
 
 use crate::core::worker::Worker;
 use crate::host::host::HostInfo;
@@ -27,15 +32,23 @@ const SYNC_FLUSH_QD_LINES_THRESHOLD: usize = 10 * ASYNC_FLUSH_QD_LINES_THRESHOLD
 /// Logging thread flushes at least this often.
 const MIN_FLUSH_FREQUENCY: Duration = Duration::from_secs(10);
 
+/// Records written to stdout are formatted into a single buffer, which is
+/// written out with one `write_all` at the end of a flush, or earlier if it
+/// grows to this many bytes.
+const MAX_BATCH_BYTES: usize = 16 * 1024 * 1024;
+
 static SHADOW_LOGGER: Lazy<ShadowLogger> = Lazy::new(ShadowLogger::new);
 
-/// Initialize the Shadow logger.
+/// Initialize the Shadow logger. If `gzip_output` is true, log records written
+/// to stdout are gzip-compressed.
 pub fn init(
     max_log_level: LevelFilter,
     report_errors_to_stderr: bool,
+    gzip_output: bool,
 ) -> Result<(), SetLoggerError> {
     SHADOW_LOGGER.set_max_level(max_log_level);
     SHADOW_LOGGER.set_report_errors_to_stderr(report_errors_to_stderr);
+    SHADOW_LOGGER.set_gzip_output(gzip_output);
 
     log::set_logger(&*SHADOW_LOGGER)?;
 
@@ -93,20 +106,56 @@ pub struct ShadowLogger {
     // large.
     records: ArrayQueue<ShadowLogRecord>,
 
+    // Sequence number for the next record, giving a total order over records
+    // from all threads even when their sim times are equal.
+    next_sequence_number: AtomicU64,
+
     // When false, sends a (still-asynchronous) flush command to the logger
     // thread every time a record is pushed into `records`.
     buffering_enabled: RwLock<bool>,
 
+    // When true, records are written in a compact format with only the sim
+    // time, level, host, and message.
+    compact: RwLock<bool>,
+
+    // When true, warning and error records are written to stderr instead of
+    // stdout.
+    error_to_stderr: RwLock<bool>,
+
+    // If non-empty, only records whose module path is one of these modules (or
+    // a submodule of one) are logged.
+    module_filter: RwLock<Vec<String>>,
+
+    // If set, only records from the host with this name (or from no host) are
+    // logged.
+    host_filter: RwLock<Option<String>>,
+
+    // If set, at most this many records are written per host for each
+    // simulated second, and the rest are dropped.
+    max_records_per_sim_second: RwLock<Option<u64>>,
+
+    // The number of records written and dropped for each host in its most
+    // recent simulated second. Only used while flushing.
+    sim_second_counts: Mutex<BTreeMap<HostId, SimSecondCount>>,
+
+    // If set, called on the logger thread for each record before it's written.
+    record_hook: Mutex<Option<RecordHook>>,
+
     // The maximum log level, unless overridden by a host-specific log level.
     max_log_level: OnceCell<LevelFilter>,
 
     // Whether to report errors to stderr in addition to logging to stdout.
     report_errors_to_stderr: OnceCell<bool>,
+
+    // Whether to gzip-compress the records written to stdout.
+    gzip_output: OnceCell<bool>,
 }
 
 thread_local!(static SENDER: RefCell<Option<Sender<LoggerCommand>>> = const{ RefCell::new(None)});
 thread_local!(static THREAD_NAME: String = get_thread_name());
 thread_local!(static THREAD_ID: nix::unistd::Pid = nix::unistd::gettid());
+// Whether this thread is running the record hook, in the middle of a flush.
+thread_local!(static IN_RECORD_HOOK: Cell<bool> = const { Cell::new(false) });
 
 fn get_thread_name() -> String {
     let mut thread_name = Vec::<i8>::with_capacity(16);
@@ -147,11 +196,20 @@ impl ShadowLogger {
 
         ShadowLogger {
             records: ArrayQueue::new(SYNC_FLUSH_QD_LINES_THRESHOLD),
+            next_sequence_number: AtomicU64::new(0),
             command_sender: Mutex::new(sender),
             command_receiver: Mutex::new(receiver),
             buffering_enabled: RwLock::new(false),
+            compact: RwLock::new(false),
+            error_to_stderr: RwLock::new(false),
+            module_filter: RwLock::new(Vec::new()),
+            host_filter: RwLock::new(None),
+            max_records_per_sim_second: RwLock::new(None),
+            sim_second_counts: Mutex::new(BTreeMap::new()),
+            record_hook: Mutex::new(None),
             max_log_level: OnceCell::new(),
             report_errors_to_stderr: OnceCell::new(),
+            gzip_output: OnceCell::new(),
         }
     }
 
@@ -177,8 +235,47 @@ impl ShadowLogger {
     // self.records. If `done_sender` is provided, it's notified after the flush
     // has completed.
     fn flush_records(&self, done_sender: Option<Sender<()>>) -> std::io::Result<()> {
-        use std::io::Write;
+        let stdout_unlocked = std::io::stdout();
+        let stdout_locked = stdout_unlocked.lock();
+        let mut stdout = std::io::BufWriter::new(stdout_locked);
+
+        // We use a `BufWriter` to try to help ensure we ultimately make a
+        // single `write` syscall per stderr message. We don't lock stderr,
+        // since we only write to it occasionally.
+        let mut stderr = std::io::BufWriter::new(std::io::stderr());
 
+        if !self.gzip_output.get().copied().unwrap_or(false) {
+            self.write_records(&mut stdout, &mut stderr)?;
+        } else if !self.records.is_empty() {
+            // Avoid writing empty gzip members on periodic flushes.
+            self.write_records_gzip(&mut stdout, &mut stderr)?;
+        }
+        drop(stdout);
+        drop(stderr);
+
+        if let Some(done_sender) = done_sender {
+            // We can't log from this thread without risking deadlock, so in the
+            // unlikely case that the calling thread has gone away, just print
+            // directly.
+            done_sender.send(()).unwrap_or_else(|e| {
+                println!(
+                    "WARNING: Logger couldn't notify
+                calling thread: {:?}",
+                    e
+                )
+            });
+        }
+        Ok(())
+    }
+
+    // Write the records in self.records to `stdout`, or to `stderr` where
+    // configured to. Records for `stdout` are batched so that they're written
+    // with as few `write` calls as possible.
+    fn write_records(
+        &self,
+        stdout: &mut impl std::io::Write,
+        stderr: &mut impl std::io::Write,
+    ) -> std::io::Result<()> {
         // Only flush records that are already in the queue, not ones that
         // arrive while we're flushing. Otherwise callers who perform a
         // synchronous flush (whether this flush operation or another one that
@@ -186,12 +283,25 @@ impl ShadowLogger {
         // necessary. Also keeps us from holding the stdout lock indefinitely.
         let mut toflush = self.records.len();
 
-        let stdout_unlocked = std::io::stdout();
-        let stdout_locked = stdout_unlocked.lock();
-        let mut stdout = std::io::BufWriter::new(stdout_locked);
+        let compact = *self.compact.read().unwrap();
+        let error_to_stderr = *self.error_to_stderr.read().unwrap();
+        let report_errors_to_stderr = self.report_errors_to_stderr.get().copied().unwrap_or(false);
+        let max_records_per_sim_second = *self.max_records_per_sim_second.read().unwrap();
+        // If we're flushing from the panic hook (see `init()`), we may have
+        // interrupted a flush on this same thread, so we mustn't block on
+        // locks that it may hold. Also skip the record hook, since it may be
+        // what panicked.
+        let panicking = std::thread::panicking();
+        let record_hook = if panicking {
+            None
+        } else {
+            self.record_hook.lock().unwrap().clone()
+        };
+
+        let mut batch = Vec::new();
 
         while toflush > 0 {
-            let record = match self.records.pop() {
+            let mut record = match self.records.pop() {
                 Some(r) => r,
                 None => {
                     // This can happen if another thread panics while the
@@ -202,46 +312,89 @@ impl ShadowLogger {
             };
             toflush -= 1;
 
-            write!(stdout, "{record}")?;
+            // Numbered as it leaves the queue rather than when it's logged, so
+            // that sequence numbers follow the queue's (and the output's) order.
+            record.sequence_number = self.next_sequence_number.fetch_add(1, Ordering::Relaxed);
+
+            if let Some(record_hook) = record_hook.as_ref() {
+                IN_RECORD_HOOK.with(|x| x.set(true));
+                record_hook(&record);
+                IN_RECORD_HOOK.with(|x| x.set(false));
+            }
+
+            if let Some(max) = max_records_per_sim_second {
+                if let Some(mut sim_second_counts) = self.lock_sim_second_counts(panicking) {
+                    if !count_sim_second_record(&mut sim_second_counts, &record, max, &mut batch)? {
+                        continue;
+                    }
+                }
+            }
+
+            if error_to_stderr && record.level <= Level::Warn {
+                // Flush stdout to avoid confusing interleaving if stdout and stderr are merged.
+                write_batch(stdout, &mut batch)?;
+                stdout.flush()?;
+                write_record(stderr, &record, compact)?;
+                stderr.flush()?;
+                continue;
+            }
 
-            if record.level <= Level::Error && *self.report_errors_to_stderr.get().unwrap() {
+            write_record(&mut batch, &record, compact)?;
+
+            if record.level <= Level::Error && report_errors_to_stderr {
                 // *also* summarize on stderr.
 
                 // First flush stdout to avoid confusing interleaving if stdout and stderr are merged.
+                write_batch(stdout, &mut batch)?;
                 stdout.flush()?;
-
-                // Summarize on stderr. We use a `BufWriter` to try to help
-                // ensure we ultimately make a single `write` syscall, though
-                // the flushes above and below *should* already prevent any
-                // interleaving with stdout.
-                let stderr_unlocked = std::io::stderr();
-                let stderr_locked = stderr_unlocked.lock();
-                let mut stderr = std::io::BufWriter::new(stderr_locked);
                 writeln!(stderr, "Error: {}", record.message)?;
-
-                // Explicitly flush before dropping to detect errors.
                 stderr.flush()?;
-                drop(stderr);
+            }
+
+            if batch.len() >= MAX_BATCH_BYTES {
+                write_batch(stdout, &mut batch)?;
+            }
+        }
+
+        // Summarize the records that were dropped in this flush, even if their
+        // simulated second may not be over yet.
+        if let Some(mut sim_second_counts) = self.lock_sim_second_counts(panicking) {
+            for count in sim_second_counts.values_mut() {
+                count.write_dropped_summary(&mut batch)?;
             }
         }
 
-        // Explicitly flush before dropping to detect errors.
+        write_batch(stdout, &mut batch)?;
+
+        // Explicitly flush to detect errors.
         stdout.flush()?;
-        drop(stdout);
+        stderr.flush()
+    }
 
-        if let Some(done_sender) = done_sender {
-            // We can't log from this thread without risking deadlock, so in the
-            // unlikely case that the calling thread has gone away, just print
-            // directly.
-            done_sender.send(()).unwrap_or_else(|e| {
-                println!(
-                    "WARNING: Logger couldn't notify
-                calling thread: {:?}",
-                    e
-                )
-            });
+    // Lock `sim_second_counts`, unless `panicking` and it's unavailable.
+    fn lock_sim_second_counts(
+        &self,
+        panicking: bool,
+    ) -> Option<MutexGuard<'_, BTreeMap<HostId, SimSecondCount>>> {
+        if panicking {
+            self.sim_second_counts.try_lock().ok()
+        } else {
+            Some(self.sim_second_counts.lock().unwrap())
         }
-        Ok(())
+    }
+
+    // Like `write_records`, but compresses the records written to `stdout` as
+    // a single complete gzip member. Each flush therefore leaves the output
+    // as a valid (multi-member) gzip stream, even if shadow exits without
+    // shutting down the logger.
+    fn write_records_gzip(
+        &self,
+        stdout: &mut impl std::io::Write,
+        stderr: &mut impl std::io::Write,
+    ) -> std::io::Result<()> {
+        let mut encoder = GzEncoder::new(stdout, Compression::default());
+        self.write_records(&mut encoder, stderr)?;
+        encoder.finish()?.flush()
     }
 
     /// When disabled, the logger thread is notified to write each record as
@@ -252,6 +405,148 @@ impl ShadowLogger {
         *writer = buffering_enabled;
     }
 
+    /// When enabled, warning and error records are written to stderr instead of
+    /// stdout.
+    pub fn set_error_to_stderr(&self, error_to_stderr: bool) {
+        let mut writer = self.error_to_stderr.write().unwrap();
+        *writer = error_to_stderr;
+    }
+
+    /// When enabled, records are written in a compact format that omits the
+    /// wall-clock time, thread, and source location.
+    pub fn set_compact(&self, compact: bool) {
+        let mut writer = self.compact.write().unwrap();
+        *writer = compact;
+    }
+
+    /// Only log records from the given modules and their submodules, e.g.
+    /// `shadow_rs::host::syscall`. An empty filter allows all records.
+    pub fn set_module_filter(&self, modules: Vec<String>) {
+        let mut writer = self.module_filter.write().unwrap();
+        *writer = modules;
+    }
+
+    // Whether a record with the given module path passes the module filter.
+    fn module_allowed(&self, module_path: Option<&str>) -> bool {
+        let filter = self.module_filter.read().unwrap();
+        if filter.is_empty() {
+            return true;
+        }
+        let Some(module_path) = module_path else {
+            return false;
+        };
+        filter.iter().any(|module| {
+            module_path
+                .strip_prefix(module.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+        })
+    }
+
+    /// Only log records from the host with the given name. Records that aren't
+    /// associated with a host are always logged.
+    pub fn set_host_filter(&self, host_name: Option<String>) {
+        let mut writer = self.host_filter.write().unwrap();
+        *writer = host_name;
+    }
+
+    // Whether a record from the given host passes the host filter.
+    fn host_allowed(&self, host_info: Option<&HostInfo>) -> bool {
+        let filter = self.host_filter.read().unwrap();
+        match (filter.as_deref(), host_info) {
+            (Some(name), Some(host_info)) => host_info.name == name,
+            _ => true,
+        }
+    }
+
+    /// Write at most `max` records per host for each simulated second. Any
+    /// additional records are dropped, and the number dropped is reported in
+    /// the log. Records without a host or simulation time are never dropped.
+    pub fn set_max_records_per_sim_second(&self, max: Option<u64>) {
+        let mut writer = self.max_records_per_sim_second.write().unwrap();
+        *writer = max;
+    }
+
+    /// Set a hook that's called for each record before it's written, for
+    /// example to collect metrics from the log. The hook is called on the
+    /// logger thread for every record that passes the log level, module, and
+    /// host filters, including records that are later dropped by
+    /// [`set_max_records_per_sim_second`](Self::set_max_records_per_sim_second).
+    /// Records logged by the hook are written by a later flush.
+    pub fn set_record_hook(&self, hook: Option<RecordHook>) {
+        let mut writer = self.record_hook.lock().unwrap();
+        *writer = hook;
+    }
+
+    // Log `record` on behalf of `host_info`, which is the active host (if any)
+    // when called from `Log::log`.
+    fn log_from_host(&self, record: &Record, host_info: Option<Arc<HostInfo>>) {
+        if !self.host_allowed(host_info.as_deref()) {
+            return;
+        }
+
+        let message = std::fmt::format(*record.args());
+
+        let mut shadowrecord = ShadowLogRecord {
+            // Assigned in `write_records`.
+            sequence_number: 0,
+            level: record.level(),
+            file: record.file_static(),
+            module_path: record.module_path_static(),
+            line: record.line(),
+            message,
+            wall_time: Duration::from_micros(unsafe {
+                u64::try_from(c_log::logger_elapsed_micros()).unwrap()
+            }),
+
+            emu_time: Worker::current_time(),
+            thread_name: THREAD_NAME
+                .try_with(|name| (*name).clone())
+                .unwrap_or_else(|_| get_thread_name()),
+            thread_id: THREAD_ID
+                .try_with(|id| *id)
+                .unwrap_or_else(|_| nix::unistd::gettid()),
+            host_info,
+        };
+
+        // The record hook runs on the logger thread during a flush, so waiting
+        // for a flush from there would deadlock.
+        let in_record_hook = IN_RECORD_HOOK.with(Cell::get);
+
+        loop {
+            match self.records.push(shadowrecord) {
+                Ok(()) => break,
+                Err(_) if in_record_hook => {
+                    println!(
+                        "WARNING: Log queue is full; dropping a record logged by the record hook"
+                    );
+                    return;
+                }
+                Err(r) => {
+                    // Queue is full. Flush it and try again.
+                    shadowrecord = r;
+                    self.flush_sync();
+                }
+            }
+        }
+
+        if record.level() == Level::Error {
+            // Unlike in Shadow's C code, we don't abort the program on Error
+            // logs. In Rust the same purpose is filled with `panic` and
+            // `unwrap`. C callers will still exit or abort via the lib/logger wrapper.
+            //
+            // Flush *synchronously*, since we're likely about to crash one way or another.
+            if in_record_hook {
+                self.flush_async();
+            } else {
+                self.flush_sync();
+            }
+        } else if self.records.len() > ASYNC_FLUSH_QD_LINES_THRESHOLD
+            || !*self.buffering_enabled.read().unwrap()
+        {
+            self.flush_async();
+        }
+    }
+
     /// If the maximum log level has not yet been set, returns `LevelFilter::Trace`.
     pub fn max_level(&self) -> LevelFilter {
         self.max_log_level
@@ -274,20 +569,30 @@ impl ShadowLogger {
         self.report_errors_to_stderr.set(val).unwrap()
     }
 
+    /// Set whether to gzip-compress the records written to stdout.
+    ///
+    /// Is only intended to be called from `init()`. Will panic if called more
+    /// than once.
+    fn set_gzip_output(&self, val: bool) {
+        self.gzip_output.set(val).unwrap()
+    }
+
     // Send a flush command to the logger thread.
     fn flush_impl(&self, notify_done: Option<Sender<()>>) {
         self.send_command(LoggerCommand::Flush(notify_done))
     }
 
-    // Send a flush command to the logger thread and block until it's completed.
-    fn flush_sync(&self) {
+    /// Flush all queued records, blocking until they've been written. Must
+    /// only be called once the logger thread is running (see `init()`).
+    pub fn flush_sync(&self) {
         let (done_sender, done_receiver) = std::sync::mpsc::channel();
         self.flush_impl(Some(done_sender));
         done_receiver.recv().unwrap();
     }
 
-    // Send a flush command to the logger thread.
-    fn flush_async(&self) {
+    /// Ask the logger thread to flush all queued records, without waiting for
+    /// them to be written.
+    pub fn flush_async(&self) {
         self.flush_impl(None);
     }
 
@@ -319,10 +624,10 @@ impl ShadowLogger {
 
 impl Log for ShadowLogger {
     fn enabled(&self, metadata: &Metadata) -> bool {
-        let filter = match Worker::with_active_host(|host| host.info().log_level) {
-            Some(Some(level)) => level,
-            _ => self.max_level(),
-        };
+        // the host's log level if there is an active host and it has one set
+        let filter = Worker::with_active_host(|host| host.info().log_level)
+            .flatten()
+            .unwrap_or_else(|| self.max_level());
         metadata.level() <= filter
     }
 
@@ -331,53 +636,13 @@ impl Log for ShadowLogger {
             return;
         }
 
-        let message = std::fmt::format(*record.args());
-
-        let host_info = Worker::with_active_host(|host| host.info().clone());
-
-        let mut shadowrecord = ShadowLogRecord {
-            level: record.level(),
-            file: record.file_static(),
-            module_path: record.module_path_static(),
-            line: record.line(),
-            message,
-            wall_time: Duration::from_micros(unsafe {
-                u64::try_from(c_log::logger_elapsed_micros()).unwrap()
-            }),
-
-            emu_time: Worker::current_time(),
-            thread_name: THREAD_NAME
-                .try_with(|name| (*name).clone())
-                .unwrap_or_else(|_| get_thread_name()),
-            thread_id: THREAD_ID
-                .try_with(|id| *id)
-                .unwrap_or_else(|_| nix::unistd::gettid()),
-            host_info,
-        };
-
-        loop {
-            match self.records.push(shadowrecord) {
-                Ok(()) => break,
-                Err(r) => {
-                    // Queue is full. Flush it and try again.
-                    shadowrecord = r;
-                    self.flush_sync();
-                }
-            }
+        // `Metadata` doesn't include the module path, so we can only check this here.
+        if !self.module_allowed(record.module_path()) {
+            return;
         }
 
-        if record.level() == Level::Error {
-            // Unlike in Shadow's C code, we don't abort the program on Error
-            // logs. In Rust the same purpose is filled with `panic` and
-            // `unwrap`. C callers will still exit or abort via the lib/logger wrapper.
-            //
-            // Flush *synchronously*, since we're likely about to crash one way or another.
-            self.flush_sync();
-        } else if self.records.len() > ASYNC_FLUSH_QD_LINES_THRESHOLD
-            || !*self.buffering_enabled.read().unwrap()
-        {
-            self.flush_async();
-        }
+        let host_info = Worker::with_active_host(|host| host.info().clone());
+        self.log_from_host(record, host_info);
     }
 
     fn flush(&self) {
@@ -385,7 +650,12 @@ impl Log for ShadowLogger {
     }
 }
 
-struct ShadowLogRecord {
+/// A hook called for each log record; see `ShadowLogger::set_record_hook`.
+pub type RecordHook = Arc<dyn Fn(&ShadowLogRecord) + Send + Sync>;
+
+/// A log record, along with the simulation context it was logged in.
+pub struct ShadowLogRecord {
+    sequence_number: u64,
     level: Level,
     file: Option<&'static str>,
     module_path: Option<&'static str>,
@@ -399,42 +669,88 @@ struct ShadowLogRecord {
     host_info: Option<Arc<HostInfo>>,
 }
 
-impl std::fmt::Display for ShadowLogRecord {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        {
-            let parts = TimeParts::from_nanos(self.wall_time.as_nanos());
-            write!(
-                f,
-                "{:02}:{:02}:{:02}.{:06}",
-                parts.hours,
-                parts.mins,
-                parts.secs,
-                parts.nanos / 1000
-            )?;
-        }
-        write!(f, " [{}:{}]", self.thread_id, self.thread_name)?;
+impl ShadowLogRecord {
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn module_path(&self) -> Option<&'static str> {
+        self.module_path
+    }
+
+    pub fn file(&self) -> Option<&'static str> {
+        self.file
+    }
+
+    pub fn line(&self) -> Option<u32> {
+        self.line
+    }
+
+    /// The simulation time when the record was logged, if the simulation was running.
+    pub fn emu_time(&self) -> Option<EmulatedTime> {
+        self.emu_time
+    }
+
+    /// The host that logged the record, if any.
+    pub fn host_info(&self) -> Option<&HostInfo> {
+        self.host_info.as_deref()
+    }
+
+    /// Displays the record in the compact format; see `ShadowLogger::set_compact`.
+    fn compact(&self) -> CompactShadowLogRecord<'_> {
+        CompactShadowLogRecord(self)
+    }
+
+    fn fmt_sim_time(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if let Some(emu_time) = self.emu_time {
             let sim_time = emu_time.duration_since(&EmulatedTime::SIMULATION_START);
             let parts = TimeParts::from_nanos(sim_time.as_nanos());
             write!(
                 f,
-                " {:02}:{:02}:{:02}.{:09}",
+                "{:02}:{:02}:{:02}.{:09}",
                 parts.hours, parts.mins, parts.secs, parts.nanos
-            )?;
+            )
         } else {
-            write!(f, " n/a")?;
+            write!(f, "n/a")
         }
-        write!(f, " [{level}]", level = self.level)?;
+    }
+
+    fn fmt_host(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if let Some(host) = &self.host_info {
             write!(
                 f,
-                " [{hostname}:{ip}]",
+                "[{hostname}:{ip}]",
                 hostname = host.name,
                 ip = host.default_ip,
-            )?;
+            )
         } else {
-            write!(f, " [n/a]",)?;
+            write!(f, "[n/a]",)
         }
+    }
+}
+
+impl std::fmt::Display for ShadowLogRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{} ", self.sequence_number)?;
+        {
+            let parts = TimeParts::from_nanos(self.wall_time.as_nanos());
+            write!(
+                f,
+                "{:02}:{:02}:{:02}.{:06}",
+                parts.hours,
+                parts.mins,
+                parts.secs,
+                parts.nanos / 1000
+            )?;
+        }
+        write!(f, " [{}:{}] ", self.thread_id, self.thread_name)?;
+        self.fmt_sim_time(f)?;
+        write!(f, " [{level}] ", level = self.level)?;
+        self.fmt_host(f)?;
         write!(
             f,
             " [{file}:",
@@ -462,6 +778,112 @@ impl std::fmt::Display for ShadowLogRecord {
     }
 }
 
+fn write_record(
+    writer: &mut impl std::io::Write,
+    record: &ShadowLogRecord,
+    compact: bool,
+) -> std::io::Result<()> {
+    if compact {
+        write!(writer, "{}", record.compact())
+    } else {
+        write!(writer, "{record}")
+    }
+}
+
+// The number of records written and dropped for a host in one simulated
+// second; see `ShadowLogger::set_max_records_per_sim_second`.
+struct SimSecondCount {
+    host_info: Arc<HostInfo>,
+    sim_second: u64,
+    written: u64,
+    dropped: u64,
+}
+
+impl SimSecondCount {
+    fn new(host_info: Arc<HostInfo>, sim_second: u64) -> Self {
+        Self {
+            host_info,
+            sim_second,
+            written: 0,
+            dropped: 0,
+        }
+    }
+
+    // Write a summary of the dropped records if there are any, and reset the
+    // dropped count.
+    fn write_dropped_summary(&mut self, writer: &mut impl std::io::Write) -> std::io::Result<()> {
+        if self.dropped > 0 {
+            writeln!(
+                writer,
+                "[{hostname}:{ip}] Dropped {dropped} log records in simulated second {sim_second} \
+                 (limit of {limit} per simulated second)",
+                hostname = self.host_info.name,
+                ip = self.host_info.default_ip,
+                dropped = self.dropped,
+                sim_second = self.sim_second,
+                limit = self.written,
+            )?;
+            self.dropped = 0;
+        }
+        Ok(())
+    }
+}
+
+// Count `record` towards its host's records in its simulated second, and
+// return whether it should be written. When a host moves on to a new simulated
+// second, a summary of the records dropped in the previous one is written to
+// `writer`.
+fn count_sim_second_record(
+    counts: &mut BTreeMap<HostId, SimSecondCount>,
+    record: &ShadowLogRecord,
+    max: u64,
+    writer: &mut impl std::io::Write,
+) -> std::io::Result<bool> {
+    let (Some(host_info), Some(emu_time)) = (&record.host_info, record.emu_time) else {
+        return Ok(true);
+    };
+
+    let sim_second = emu_time.to_abs_simtime().as_secs();
+
+    let count = counts
+        .entry(host_info.id)
+        .or_insert_with(|| SimSecondCount::new(Arc::clone(host_info), sim_second));
+
+    if count.sim_second != sim_second {
+        count.write_dropped_summary(writer)?;
+        *count = SimSecondCount::new(Arc::clone(host_info), sim_second);
+    }
+
+    if count.written < max {
+        count.written += 1;
+        Ok(true)
+    } else {
+        count.dropped += 1;
+        Ok(false)
+    }
+}
+
+// Write out and clear the batched records, if any.
+fn write_batch(writer: &mut impl std::io::Write, batch: &mut Vec<u8>) -> std::io::Result<()> {
+    if !batch.is_empty() {
+        writer.write_all(batch)?;
+        batch.clear();
+    }
+    Ok(())
+}
+
+struct CompactShadowLogRecord<'a>(&'a ShadowLogRecord);
+
+impl std::fmt::Display for CompactShadowLogRecord<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{} ", self.0.sequence_number)?;
+        self.0.fmt_sim_time(f)?;
+        write!(f, " [{level}] ", level = self.0.level)?;
+        self.0.fmt_host(f)?;
+        writeln!(f, " {msg}", msg = self.0.message)
+    }
+}
+
 enum LoggerCommand {
     // Flush; takes an optional one-shot channel to notify that the flush has completed.
     Flush(Option<Sender<()>>),
@@ -471,6 +893,43 @@ pub fn set_buffering_enabled(buffering_enabled: bool) {
     SHADOW_LOGGER.set_buffering_enabled(buffering_enabled);
 }
 
+pub fn set_compact(compact: bool) {
+    SHADOW_LOGGER.set_compact(compact);
+}
+
+pub fn set_error_to_stderr(error_to_stderr: bool) {
+    SHADOW_LOGGER.set_error_to_stderr(error_to_stderr);
+}
+
+pub fn set_module_filter(modules: Vec<String>) {
+    SHADOW_LOGGER.set_module_filter(modules);
+}
+
+pub fn set_host_filter(host_name: Option<String>) {
+    SHADOW_LOGGER.set_host_filter(host_name);
+}
+
+pub fn set_max_records_per_sim_second(max: Option<u64>) {
+    SHADOW_LOGGER.set_max_records_per_sim_second(max);
+}
+
+pub fn set_record_hook(hook: Option<RecordHook>) {
+    SHADOW_LOGGER.set_record_hook(hook);
+}
+
+/// Flush all queued records, blocking until they've been written. Unlike
+/// `log::logger().flush()`, this always flushes the shadow logger, and must
+/// only be called after `init()`.
+pub fn flush_sync() {
+    SHADOW_LOGGER.flush_sync();
+}
+
+/// Ask the logger thread to flush all queued records, without waiting for
+/// them to be written.
+pub fn flush_async() {
+    SHADOW_LOGGER.flush_async();
+}
+
 mod export {
     use super::*;
 
@@ -482,3 +941,544 @@ mod export {
         set_buffering_enabled(buffering_enabled != 0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::rc::Rc;
+
+    use shadow_shim_helper_rs::simulation_time::SimulationTime;
+
+    use super::*;
+
+    fn test_record(level: Level, message: &str) -> ShadowLogRecord {
+        ShadowLogRecord {
+            sequence_number: 7,
+            level,
+            file: Some("src/main/core/logger/shadow_logger.rs"),
+            module_path: Some("shadow_rs::core::logger::shadow_logger"),
+            line: Some(42),
+            message: message.to_string(),
+            wall_time: Duration::from_micros(1_500_000),
+            emu_time: Some(EmulatedTime::SIMULATION_START + SimulationTime::from_millis(2500)),
+            thread_name: "test-thread".to_string(),
+            thread_id: nix::unistd::Pid::from_raw(1000),
+            host_info: None,
+        }
+    }
+
+    #[test]
+    fn test_format() {
+        let record = test_record(Level::Info, "hello");
+        assert_eq!(
+            record.to_string(),
+            "#7 00:00:01.500000 [1000:test-thread] 00:00:02.500000000 [INFO] [n/a] \
+             [shadow_logger.rs:42] [shadow_rs::core::logger::shadow_logger] hello\n"
+        );
+    }
+
+    #[test]
+    fn test_format_compact() {
+        let record = test_record(Level::Info, "hello");
+        let line = record.compact().to_string();
+        assert_eq!(line, "#7 00:00:02.500000000 [INFO] [n/a] hello\n");
+        assert!(!line.contains("shadow_logger.rs:42"));
+    }
+
+    fn log_from_module(logger: &ShadowLogger, module_path: &'static str, message: &str) {
+        logger.log(
+            &Record::builder()
+                .level(Level::Info)
+                .module_path_static(Some(module_path))
+                .args(format_args!("{message}"))
+                .build(),
+        );
+    }
+
+    #[test]
+    fn test_module_filter() {
+        let logger = ShadowLogger::new();
+        // Avoid sending flush commands to a logger thread that doesn't exist.
+        logger.set_buffering_enabled(true);
+        logger.set_module_filter(vec!["shadow_rs::host::syscall".to_string()]);
+
+        log_from_module(&logger, "shadow_rs::host::syscall", "a");
+        log_from_module(&logger, "shadow_rs::host::syscall::handler", "b");
+        log_from_module(&logger, "shadow_rs::host::syscallx", "c");
+        log_from_module(&logger, "shadow_rs::core::worker", "d");
+
+        let messages: Vec<String> = std::iter::from_fn(|| logger.records.pop())
+            .map(|r| r.message)
+            .collect();
+        assert_eq!(messages, ["a", "b"]);
+
+        // An empty filter allows everything.
+        logger.set_module_filter(Vec::new());
+        log_from_module(&logger, "shadow_rs::core::worker", "e");
+        assert_eq!(logger.records.pop().unwrap().message, "e");
+    }
+
+    fn test_host(id: u32, name: &str) -> Arc<HostInfo> {
+        Arc::new(HostInfo {
+            id: HostId::from(id),
+            name: name.to_string(),
+            default_ip: std::net::Ipv4Addr::new(11, 0, 0, u8::try_from(id).unwrap()),
+            log_level: None,
+        })
+    }
+
+    #[test]
+    fn test_host_filter() {
+        let logger = ShadowLogger::new();
+        // Avoid sending flush commands to a logger thread that doesn't exist.
+        logger.set_buffering_enabled(true);
+        logger.set_host_filter(Some("alice".to_string()));
+
+        let alice = test_host(1, "alice");
+        let bob = test_host(2, "bob");
+        for (host, message) in [
+            (Some(&alice), "a"),
+            (Some(&bob), "b"),
+            (None, "c"),
+            (Some(&alice), "d"),
+        ] {
+            logger.log_from_host(
+                &Record::builder()
+                    .level(Level::Info)
+                    .args(format_args!("{message}"))
+                    .build(),
+                host.cloned(),
+            );
+        }
+
+        let records: Vec<ShadowLogRecord> = std::iter::from_fn(|| logger.records.pop()).collect();
+        let messages: Vec<&str> = records.iter().map(|r| r.message.as_str()).collect();
+        assert_eq!(messages, ["a", "c", "d"]);
+        assert!(records
+            .iter()
+            .filter_map(|r| r.host_info.as_ref())
+            .all(|h| h.name == "alice"));
+    }
+
+    #[test]
+    fn test_sequence_numbers() {
+        let logger = ShadowLogger::new();
+        // Avoid sending flush commands to a logger thread that doesn't exist.
+        logger.set_buffering_enabled(true);
+
+        for i in 0..10 {
+            logger.log_from_host(
+                &Record::builder()
+                    .level(Level::Info)
+                    .args(format_args!("{i}"))
+                    .build(),
+                None,
+            );
+        }
+
+        let mut out = Vec::new();
+        logger
+            .write_records(&mut out, &mut std::io::sink())
+            .unwrap();
+
+        let sequence_numbers: Vec<u64> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let (seq, _) = line.strip_prefix('#').unwrap().split_once(' ').unwrap();
+                seq.parse().unwrap()
+            })
+            .collect();
+        assert_eq!(sequence_numbers.len(), 10);
+        assert!(sequence_numbers.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_flush_sync() {
+        // The logger thread runs forever, so the logger must outlive the test.
+        let logger: &'static ShadowLogger = Box::leak(Box::new(ShadowLogger::new()));
+        std::thread::spawn(move || logger.logger_thread_fn());
+
+        // Don't flush as records are logged.
+        logger.set_buffering_enabled(true);
+        logger.set_compact(true);
+
+        logger.log_from_host(
+            &Record::builder()
+                .level(Level::Info)
+                .args(format_args!("test_flush_sync"))
+                .build(),
+            None,
+        );
+        assert_eq!(logger.records.len(), 1);
+
+        // The record has been written once the flush returns.
+        logger.flush_sync();
+        assert!(logger.records.is_empty());
+    }
+
+    #[test]
+    fn test_error_to_stderr() {
+        let logger = ShadowLogger::new();
+        logger.set_error_to_stderr(true);
+
+        for (level, message) in [
+            (Level::Info, "info message"),
+            (Level::Error, "error message"),
+            (Level::Debug, "debug message"),
+        ] {
+            logger.records.push(test_record(level, message)).unwrap();
+        }
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        logger.write_records(&mut stdout, &mut stderr).unwrap();
+        let stdout = String::from_utf8(stdout).unwrap();
+        let stderr = String::from_utf8(stderr).unwrap();
+
+        assert!(stdout.contains("info message"));
+        assert!(stdout.contains("debug message"));
+        assert!(!stdout.contains("error message"));
+        assert_eq!(stderr.lines().count(), 1);
+        assert!(stderr.contains("[ERROR]"));
+        assert!(stderr.contains("error message"));
+    }
+
+    // Counts the number of `write` calls.
+    #[derive(Default)]
+    struct CountingWriter {
+        bytes: Vec<u8>,
+        writes: usize,
+    }
+
+    impl std::io::Write for CountingWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.writes += 1;
+            self.bytes.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_write_records_batched() {
+        let logger = ShadowLogger::new();
+
+        // Numbered as `write_records` will number them.
+        let records: Vec<ShadowLogRecord> = (0..100)
+            .map(|i| ShadowLogRecord {
+                sequence_number: i,
+                ..test_record(Level::Info, &format!("message {i}"))
+            })
+            .collect();
+        let expected: String = records.iter().map(|r| r.to_string()).collect();
+        for record in records {
+            logger.records.push(record).unwrap();
+        }
+
+        let mut stdout = CountingWriter::default();
+        let mut stderr = CountingWriter::default();
+        logger.write_records(&mut stdout, &mut stderr).unwrap();
+
+        // The output is the same as writing each record individually, but with a single write.
+        assert_eq!(String::from_utf8(stdout.bytes).unwrap(), expected);
+        assert_eq!(stdout.writes, 1);
+        assert_eq!(stderr.writes, 0);
+    }
+
+    #[test]
+    fn test_write_records_batched_with_stderr() {
+        let logger = ShadowLogger::new();
+        logger.set_error_to_stderr(true);
+
+        for (level, message) in [
+            (Level::Info, "a"),
+            (Level::Info, "b"),
+            (Level::Warn, "c"),
+            (Level::Info, "d"),
+        ] {
+            logger.records.push(test_record(level, message)).unwrap();
+        }
+
+        // Records written to stdout before a stderr record are written before it.
+        struct Merged(Rc<RefCell<String>>);
+        impl std::io::Write for Merged {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0
+                    .borrow_mut()
+                    .push_str(std::str::from_utf8(buf).unwrap());
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let merged = Rc::new(RefCell::new(String::new()));
+        logger
+            .write_records(&mut Merged(merged.clone()), &mut Merged(merged.clone()))
+            .unwrap();
+
+        let messages: Vec<String> = merged
+            .borrow()
+            .lines()
+            .map(|line| line.rsplit_once(' ').unwrap().1.to_string())
+            .collect();
+        assert_eq!(messages, ["a", "b", "c", "d"]);
+    }
+
+    /// Compares the throughput of writing records individually with writing
+    /// them in batches. Run with:
+    ///
+    /// `cargo test --release -p shadow-rs -- --ignored --nocapture bench_write_records`
+    #[test]
+    #[ignore]
+    fn bench_write_records() {
+        const NUM_RECORDS: usize = 1_000_000;
+
+        let records =
+            || (0..NUM_RECORDS).map(|i| test_record(Level::Info, &format!("message {i}")));
+        let dev_null = || {
+            std::io::BufWriter::new(
+                std::fs::OpenOptions::new()
+                    .write(true)
+                    .open("/dev/null")
+                    .unwrap(),
+            )
+        };
+
+        // Write each record separately, as the logger used to.
+        let records_vec: Vec<ShadowLogRecord> = records().collect();
+        let mut stdout = dev_null();
+        let start = std::time::Instant::now();
+        for record in &records_vec {
+            write_record(&mut stdout, record, false).unwrap();
+        }
+        stdout.flush().unwrap();
+        let unbatched = start.elapsed();
+
+        let logger = ShadowLogger::new();
+        for record in records() {
+            logger.records.push(record).unwrap();
+        }
+        let mut stdout = dev_null();
+        let start = std::time::Instant::now();
+        logger
+            .write_records(&mut stdout, &mut std::io::sink())
+            .unwrap();
+        let batched = start.elapsed();
+
+        for (name, elapsed) in [("unbatched", unbatched), ("batched", batched)] {
+            println!(
+                "{name}: {NUM_RECORDS} records in {elapsed:?} ({:.0} records/s)",
+                NUM_RECORDS as f64 / elapsed.as_secs_f64()
+            );
+        }
+    }
+
+    #[test]
+    fn test_max_records_per_sim_second() {
+        let logger = ShadowLogger::new();
+        logger.set_max_records_per_sim_second(Some(3));
+
+        let alice = test_host(1, "alice");
+        let bob = test_host(2, "bob");
+
+        let record = |host: &Arc<HostInfo>, millis: u64, message: &str| ShadowLogRecord {
+            host_info: Some(Arc::clone(host)),
+            emu_time: Some(EmulatedTime::SIMULATION_START + SimulationTime::from_millis(millis)),
+            ..test_record(Level::Info, message)
+        };
+
+        // Flood alice's first simulated second, and log a few records for bob in the same second.
+        for i in 0..10 {
+            logger.records.push(record(&alice, 100 + i, "a")).unwrap();
+        }
+        logger.records.push(record(&bob, 500, "b")).unwrap();
+        logger.records.push(record(&alice, 1100, "c")).unwrap();
+
+        let mut stdout = Vec::new();
+        logger.write_records(&mut stdout, &mut Vec::new()).unwrap();
+        let stdout = String::from_utf8(stdout).unwrap();
+        let lines: Vec<&str> = stdout.lines().collect();
+
+        assert_eq!(lines.len(), 6);
+        assert!(lines[..3].iter().all(|l| l.ends_with(" a")));
+        assert!(lines[3].ends_with(" b"));
+        assert_eq!(
+            lines[4],
+            "[alice:11.0.0.1] Dropped 7 log records in simulated second 0 \
+             (limit of 3 per simulated second)"
+        );
+        assert!(lines[5].ends_with(" c"));
+
+        // Records dropped near the end of a flush are summarized at the end of the flush.
+        for i in 0..5 {
+            logger.records.push(record(&bob, 1200 + i, "d")).unwrap();
+        }
+
+        let mut stdout = Vec::new();
+        logger.write_records(&mut stdout, &mut Vec::new()).unwrap();
+        let stdout = String::from_utf8(stdout).unwrap();
+        let lines: Vec<&str> = stdout.lines().collect();
+
+        assert_eq!(lines.len(), 4);
+        assert!(lines[..3].iter().all(|l| l.ends_with(" d")));
+        assert_eq!(
+            lines[3],
+            "[bob:11.0.0.2] Dropped 2 log records in simulated second 1 \
+             (limit of 3 per simulated second)"
+        );
+    }
+
+    #[test]
+    fn test_record_hook() {
+        use std::sync::atomic::AtomicUsize;
+
+        let logger = ShadowLogger::new();
+
+        let errors = Arc::new(AtomicUsize::new(0));
+        let errors_clone = Arc::clone(&errors);
+        logger.set_record_hook(Some(Arc::new(move |record| {
+            if record.level() == Level::Error {
+                errors_clone.fetch_add(1, Ordering::Relaxed);
+            }
+        })));
+
+        for level in [
+            Level::Info,
+            Level::Error,
+            Level::Warn,
+            Level::Error,
+            Level::Trace,
+        ] {
+            logger.records.push(test_record(level, "message")).unwrap();
+        }
+        logger
+            .write_records(&mut std::io::sink(), &mut std::io::sink())
+            .unwrap();
+
+        assert_eq!(errors.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_record_hook_logs() {
+        // The logger thread runs forever, so the logger must outlive the test.
+        let logger: &'static ShadowLogger = Box::leak(Box::new(ShadowLogger::new()));
+        std::thread::spawn(move || logger.logger_thread_fn());
+
+        logger.set_buffering_enabled(true);
+        logger.set_compact(true);
+
+        // Error records are normally flushed synchronously, which the hook can't wait for.
+        logger.set_record_hook(Some(Arc::new(move |record| {
+            if record.message() == "trigger" {
+                logger.log_from_host(
+                    &Record::builder()
+                        .level(Level::Error)
+                        .args(format_args!("logged by the hook"))
+                        .build(),
+                    None,
+                );
+            }
+        })));
+
+        logger.log_from_host(
+            &Record::builder()
+                .level(Level::Info)
+                .args(format_args!("trigger"))
+                .build(),
+            None,
+        );
+        logger.flush_sync();
+
+        // The hook's record is written by the next flush.
+        logger.flush_sync();
+        assert!(logger.records.is_empty());
+    }
+
+    #[test]
+    fn test_record_hook_panics() {
+        // Flushes the logger while unwinding, like the panic hook set by `init()`.
+        struct FlushOnDrop(&'static ShadowLogger);
+
+        impl Drop for FlushOnDrop {
+            fn drop(&mut self) {
+                self.0
+                    .write_records(&mut std::io::sink(), &mut std::io::sink())
+                    .unwrap();
+            }
+        }
+
+        let logger: &'static ShadowLogger = Box::leak(Box::new(ShadowLogger::new()));
+        logger.set_max_records_per_sim_second(Some(10));
+        logger.set_record_hook(Some(Arc::new(move |record| {
+            if record.message() == "panic" {
+                let _flush = FlushOnDrop(logger);
+                panic!("record hook panicked");
+            }
+        })));
+
+        for message in ["before", "panic", "after"] {
+            logger
+                .records
+                .push(test_record(Level::Info, message))
+                .unwrap();
+        }
+
+        let result = std::panic::catch_unwind(|| {
+            logger
+                .write_records(&mut std::io::sink(), &mut std::io::sink())
+                .unwrap();
+        });
+        assert!(result.is_err());
+
+        // The flush while unwinding wrote the remaining record.
+        assert!(logger.records.is_empty());
+
+        // And the logger can still be used.
+        logger.set_record_hook(None);
+        logger
+            .records
+            .push(test_record(Level::Info, "message"))
+            .unwrap();
+        logger
+            .write_records(&mut std::io::sink(), &mut std::io::sink())
+            .unwrap();
+        assert!(logger.records.is_empty());
+    }
+
+    #[test]
+    fn test_gzip_output() {
+        use std::io::Read;
+
+        let logger = ShadowLogger::new();
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        // Write two batches, as from two separate flushes.
+        for batch in [["a", "b"], ["c", "d"]] {
+            for message in batch {
+                logger
+                    .records
+                    .push(test_record(Level::Info, message))
+                    .unwrap();
+            }
+            logger.write_records_gzip(&mut stdout, &mut stderr).unwrap();
+        }
+        assert!(stderr.is_empty());
+
+        let mut decompressed = String::new();
+        flate2::read::MultiGzDecoder::new(stdout.as_slice())
+            .read_to_string(&mut decompressed)
+            .unwrap();
+        let messages: Vec<&str> = decompressed
+            .lines()
+            .map(|line| line.rsplit_once(' ').unwrap().1)
+            .collect();
+        assert_eq!(messages, ["a", "b", "c", "d"]);
+    }
+}
@@ -603,6 +603,7 @@ impl<'a> Manager<'a> {
                 unblocked_syscall_latency: self.config.unblocked_syscall_latency(),
                 unblocked_vdso_latency: self.config.unblocked_vdso_latency(),
                 strace_logging_options: self.config.strace_logging_mode(),
+                strace_summary: self.config.experimental.strace_summary.unwrap(),
                 shim_log_level: host_info
                     .log_level
                     .unwrap_or_else(|| self.config.general.log_level.unwrap())
@@ -48,6 +48,15 @@ impl EventQueue {
         event
     }
 
+    /// The number of [`Event`]s in the queue.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
     /// The time of the next [`Event`] (the time of the earliest event in the queue).
     pub fn next_event_time(&self) -> Option<EmulatedTime> {
         self.queue.peek().map(|x| x.0.time())
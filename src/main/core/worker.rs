@@ -104,6 +104,10 @@ impl Worker {
     }
 
     /// Run `f` with a reference to the current Host, or return None if there is no current Host.
+    ///
+    /// This doesn't panic if called from a thread that isn't a worker thread, or from a worker
+    /// thread that isn't currently running a host, so it's safe to use from code such as the
+    /// logger that may run on any thread.
     #[must_use]
     pub fn with_active_host<F, R>(f: F) -> Option<R>
     where
@@ -814,3 +818,15 @@ mod export {
         EmulatedTime::to_c_emutime(Some(Worker::max_event_runahead_time(host)))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_active_host_without_worker() {
+        // the test thread was never initialized as a worker thread
+        assert!(!Worker::is_alive());
+        assert_eq!(Worker::with_active_host(|host| host.id()), None);
+    }
+}
@@ -0,0 +1,58 @@
+//! Linux native asynchronous I/O contexts, as created by io_setup(2).
+
+use std::collections::VecDeque;
+
+use linux_api::aio::io_event;
+use shadow_shim_helper_rs::syscall_types::ForeignPtr;
+
+/// An AIO context created by io_setup(2).
+///
+/// Shadow performs the submitted operations synchronously, so an operation's completion event is
+/// queued as soon as it's submitted.
+pub struct AioContext {
+    /// The memory mapped in the managed process for this context. The context id is the address
+    /// of this memory, as it is in linux.
+    ring: ForeignPtr<u8>,
+    ring_len: usize,
+    /// The maximum number of completion events that can be queued.
+    max_events: usize,
+    completions: VecDeque<io_event>,
+}
+
+impl AioContext {
+    pub fn new(ring: ForeignPtr<u8>, ring_len: usize, max_events: usize) -> Self {
+        Self {
+            ring,
+            ring_len,
+            max_events,
+            completions: VecDeque::new(),
+        }
+    }
+
+    /// The memory mapped in the managed process for this context, and its length.
+    pub fn ring(&self) -> (ForeignPtr<u8>, usize) {
+        (self.ring, self.ring_len)
+    }
+
+    /// The number of additional completion events that can be queued.
+    pub fn capacity(&self) -> usize {
+        self.max_events - self.completions.len()
+    }
+
+    /// The number of completion events that have been queued but not yet returned.
+    pub fn num_completions(&self) -> usize {
+        self.completions.len()
+    }
+
+    /// Queue a completion event. Panics if there's no capacity remaining.
+    pub fn push_completion(&mut self, event: io_event) {
+        assert!(self.capacity() > 0);
+        self.completions.push_back(event);
+    }
+
+    /// Remove and return up to `max` of the oldest completion events.
+    pub fn take_completions(&mut self, max: usize) -> Vec<io_event> {
+        let count = std::cmp::min(max, self.completions.len());
+        self.completions.drain(..count).collect()
+    }
+}
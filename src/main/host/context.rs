@@ -14,18 +14,14 @@
 //! current `Thread` exists at [`ThreadContext::thread`], it *cannot* also be
 //! accessible via [`ThreadContext::process`] or [`ThreadContext::host`].
 //!
-//! The manner in which they're unavailable isn't implemented yet, but the current
-//! plan is that they'll be temporarily removed from their collections. e.g. something
-//! conceptually like:
+//! The manner in which they're unavailable isn't fully implemented yet. To run
+//! code with another thread of the current process, use
+//! [`ProcessContext::with_thread_id`], which takes its own reference to the
+//! thread so that the process's thread list isn't borrowed while the thread is
+//! in use. e.g.:
 //!
 //! ```ignore
-//! impl Process {
-//!     pub fn continue_thread(&mut self, host_ctx: &mut HostContext, tid: ThreadId) {
-//!         let thread = self.threads.get_mut(tid).take();
-//!         thread.continue(&mut host_ctx.add_process(self));
-//!         self.threads.get_mut(tid).replace(thread);
-//!     }
-//! }
+//! process_ctx.with_thread_id(tid, |thread_ctx| thread_ctx.thread.resume(...));
 //! ```
 //!
 //! The Context objects are designed to allow simultaneously borrowing from multiple
@@ -34,9 +30,13 @@
 //! alternatively be implemented by providing methods that borrow some or all of
 //! their internal references simultaneously.
 
-use std::ops::Deref;
+use std::ops::{Deref, DerefMut};
+
+use shadow_shim_helper_rs::rootedcell::rc::RootedRc;
+use shadow_shim_helper_rs::rootedcell::refcell::RootedRefCell;
 
 use super::managed_thread::ManagedThread;
+use super::memory_manager::MemoryManager;
 use super::process::ProcessId;
 use super::thread::ThreadId;
 use super::{host::Host, process::Process, thread::Thread};
@@ -73,6 +73,40 @@ impl<'a> ProcessContext<'a> {
     pub fn with_thread(&'a self, thread: &'a Thread) -> ThreadContext<'a> {
         ThreadContext::new(self.host, self.process, thread)
     }
+
+    /// Run `f` with a [`ThreadContext`] for the thread `tid` of the current process, or return
+    /// `None` if the process has no such thread.
+    ///
+    /// The process's thread list isn't borrowed while `f` runs, so `f` may add or remove threads.
+    /// The reference to the thread that's held during `f` is released even if `f` panics.
+    pub fn with_thread_id<F, R>(&self, tid: ThreadId, f: F) -> Option<R>
+    where
+        F: FnOnce(&mut ThreadContext) -> R,
+    {
+        let threadrc = self.process.thread_borrow(tid)?.clone(self.host.root());
+        let threadrc = ThreadRcGuard {
+            host: self.host,
+            threadrc: Some(threadrc),
+        };
+
+        let thread = threadrc.threadrc.as_ref().unwrap().borrow(self.host.root());
+        let mut ctx = ThreadContext::new(self.host, self.process, &thread);
+        Some(f(&mut ctx))
+    }
+}
+
+/// Explicitly drops a thread reference when dropped, including when unwinding from a panic.
+struct ThreadRcGuard<'a> {
+    host: &'a Host,
+    threadrc: Option<RootedRc<RootedRefCell<Thread>>>,
+}
+
+impl Drop for ThreadRcGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(threadrc) = self.threadrc.take() {
+            threadrc.explicit_drop_recursive(self.host.root(), self.host);
+        }
+    }
 }
 
 /// Represent the "current" [`Host`], [`Process`], and [`Thread`].
@@ -106,6 +140,31 @@ impl<'a> ThreadContext<'a> {
     pub fn mthread(&self) -> impl Deref<Target = ManagedThread> + '_ {
         self.thread.mthread()
     }
+
+    /// Borrow the current process's [`MemoryManager`]. The returned guard borrows the process
+    /// rather than the context, so other fields of the context remain usable while it's held.
+    ///
+    /// ```no_run
+    /// # use shadow_shim_helper_rs::syscall_types::ForeignPtr;
+    /// # use shadow_rs::host::context::ThreadContext;
+    /// # use linux_api::errno::Errno;
+    /// # fn foo(ctx: &mut ThreadContext) -> Result<(), Errno> {
+    /// let ptr: ForeignPtr<u32> = todo!();
+    /// let val: u32 = ctx.memory().read(ptr)?;
+    /// ctx.memory_mut().write(ptr, &(val + 1))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[track_caller]
+    pub fn memory(&self) -> impl Deref<Target = MemoryManager> + 'a {
+        self.process.memory_borrow()
+    }
+
+    /// Mutably borrow the current process's [`MemoryManager`]. See [`ThreadContext::memory`].
+    #[track_caller]
+    pub fn memory_mut(&self) -> impl DerefMut<Target = MemoryManager> + 'a {
+        self.process.memory_borrow_mut()
+    }
 }
 
 /// Shadow's C code doesn't know about contexts. In places where C code calls
@@ -137,17 +196,9 @@ impl<'a> ThreadContextObjs<'a> {
             .clone(self.host.root());
         let res = {
             let process = processrc.borrow(self.host.root());
-            let threadrc = process
-                .thread_borrow(self.tid)
+            ProcessContext::new(self.host, &process)
+                .with_thread_id(self.tid, f)
                 .unwrap()
-                .clone(self.host.root());
-            let res = {
-                let thread = threadrc.borrow(self.host.root());
-                let mut ctx = ThreadContext::new(self.host, &process, &thread);
-                f(&mut ctx)
-            };
-            threadrc.explicit_drop_recursive(self.host.root(), self.host);
-            res
         };
         processrc.explicit_drop_recursive(self.host.root(), self.host);
         res
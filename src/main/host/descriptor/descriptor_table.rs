@@ -25,6 +25,10 @@ pub struct DescriptorTable {
     // availability in `descriptors`, though.
     next_index: u32,
 
+    // Descriptors can't be added at indices greater than or equal to this. Corresponds to the
+    // `RLIMIT_NOFILE` soft limit of the owning process.
+    fd_limit: u64,
+
     _counter: ObjectCounter,
 }
 
@@ -34,6 +38,7 @@ impl DescriptorTable {
             descriptors: HashMap::new(),
             available_indices: BTreeSet::new(),
             next_index: 0,
+            fd_limit: u64::from(FD_MAX) + 1,
             _counter: ObjectCounter::new("DescriptorTable"),
         }
     }
@@ -44,8 +49,15 @@ impl DescriptorTable {
         &mut self,
         descriptor: Descriptor,
         min_index: DescriptorHandle,
+        fd_limit: u64,
     ) -> Result<DescriptorHandle, Descriptor> {
         let idx = if let Some(idx) = self.available_indices.range(min_index.val()..).next() {
+            // Check if this index is beyond the limit. Since `available_indices` is sorted, there
+            // won't be any other available indices within the limit.
+            if u64::from(*idx) >= fd_limit {
+                return Err(descriptor);
+            }
+
             // Un-borrow from `available_indices`.
             let idx = *idx;
             // Take from `available_indices`
@@ -58,7 +70,7 @@ impl DescriptorTable {
             let mut idx = std::cmp::max(self.next_index, min_index.val());
 
             // Check if this index out of range.
-            if idx > FD_MAX {
+            if u64::from(idx) >= fd_limit {
                 return Err(descriptor);
             }
 
@@ -75,7 +87,7 @@ impl DescriptorTable {
                 trace!("Skipping past in-use index {}", idx);
 
                 // Check if the next index is out of range.
-                if idx >= FD_MAX {
+                if u64::from(idx) + 1 >= fd_limit {
                     return Err(descriptor);
                 }
 
@@ -115,6 +127,29 @@ impl DescriptorTable {
         }
     }
 
+    /// The limit on descriptor indices; new descriptors can only be registered at indices lower
+    /// than this.
+    pub fn fd_limit(&self) -> u64 {
+        self.fd_limit
+    }
+
+    /// Set the limit on descriptor indices, typically from the process's `RLIMIT_NOFILE` soft
+    /// limit. Existing descriptors at or above the limit are unaffected.
+    pub fn set_fd_limit(&mut self, limit: u64) {
+        self.fd_limit = std::cmp::min(limit, u64::from(FD_MAX) + 1);
+    }
+
+    /// Reserve space for at least `additional` more descriptors, so that registering them won't
+    /// need to grow the table. Useful for processes known to open many descriptors.
+    pub fn reserve(&mut self, additional: usize) {
+        self.descriptors.reserve(additional);
+    }
+
+    /// The number of descriptors the table can hold without growing.
+    pub fn capacity(&self) -> usize {
+        self.descriptors.capacity()
+    }
+
     /// Get the descriptor at `idx`, if any.
     pub fn get(&self, idx: DescriptorHandle) -> Option<&Descriptor> {
         self.descriptors.get(&idx)
@@ -157,7 +192,45 @@ impl DescriptorTable {
             Some(x) => x,
             None => unreachable!(),
         };
-        self.add(desc, ZERO)
+        self.add(desc, ZERO, self.fd_limit)
+    }
+
+    /// Like [`register_descriptor`](Self::register_descriptor), but the descriptor may be
+    /// registered at an index beyond the [fd limit](Self::fd_limit). This should only be used for
+    /// descriptors that are registered temporarily, and will be re-registered before they're
+    /// returned to the managed process.
+    pub fn register_descriptor_ignoring_limit(
+        &mut self,
+        desc: Descriptor,
+    ) -> Result<DescriptorHandle, Descriptor> {
+        const ZERO: DescriptorHandle = match DescriptorHandle::new(0) {
+            Some(x) => x,
+            None => unreachable!(),
+        };
+        self.add(desc, ZERO, u64::from(FD_MAX) + 1)
+    }
+
+    /// Register two descriptors and return their fd handles. This is intended for syscalls like
+    /// pipe(2) and socketpair(2) that create descriptors in pairs, where either both or neither
+    /// should be registered. If the descriptors could not both be added, neither is registered and
+    /// they're returned in the `Err`.
+    pub fn register_descriptor_pair(
+        &mut self,
+        desc_1: Descriptor,
+        desc_2: Descriptor,
+    ) -> Result<[DescriptorHandle; 2], [Descriptor; 2]> {
+        let fd_1 = match self.register_descriptor(desc_1) {
+            Ok(fd) => fd,
+            Err(desc_1) => return Err([desc_1, desc_2]),
+        };
+
+        match self.register_descriptor(desc_2) {
+            Ok(fd_2) => Ok([fd_1, fd_2]),
+            Err(desc_2) => {
+                let desc_1 = self.deregister_descriptor(fd_1).unwrap();
+                Err([desc_1, desc_2])
+            }
+        }
     }
 
     /// Register a descriptor and return its fd handle. If the descriptor could not be added, the
@@ -167,7 +240,7 @@ impl DescriptorTable {
         desc: Descriptor,
         min_fd: DescriptorHandle,
     ) -> Result<DescriptorHandle, Descriptor> {
-        self.add(desc, min_fd)
+        self.add(desc, min_fd, self.fd_limit)
     }
 
     /// Register a descriptor with a given fd handle and return the descriptor that it replaced.
@@ -191,8 +264,10 @@ impl DescriptorTable {
 
     /// Remove and return all descriptors.
     pub fn remove_all(&mut self) -> impl Iterator<Item = Descriptor> {
-        // reset the descriptor table
+        // reset the descriptor table, keeping its limit
+        let fd_limit = self.fd_limit;
         let old_self = std::mem::replace(self, Self::new());
+        self.fd_limit = fd_limit;
         // return the old descriptors
         old_self.descriptors.into_values()
     }
@@ -227,6 +302,33 @@ impl DescriptorTable {
     pub fn iter_mut(&mut self) -> impl Iterator<Item = (&DescriptorHandle, &mut Descriptor)> {
         self.descriptors.iter_mut()
     }
+
+    /// Information about each descriptor in the table, sorted by handle. Useful for checking that
+    /// descriptors haven't leaked.
+    pub fn descriptor_info(&self) -> Vec<DescriptorInfo> {
+        let mut info: Vec<_> = self
+            .descriptors
+            .iter()
+            .map(|(handle, descriptor)| DescriptorInfo {
+                handle: *handle,
+                file_type: descriptor.file().type_name(),
+                open_count: descriptor.file().open_count(),
+            })
+            .collect();
+
+        info.sort_by_key(|x| x.handle);
+        info
+    }
+}
+
+/// Information about a descriptor in a [`DescriptorTable`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DescriptorInfo {
+    pub handle: DescriptorHandle,
+    /// The name of the type of file that the descriptor points to.
+    pub file_type: &'static str,
+    /// The number of descriptors (across all descriptor tables) that point to the same open file.
+    pub open_count: usize,
 }
 
 impl Default for DescriptorTable {
@@ -360,3 +462,114 @@ impl std::fmt::Display for DescriptorHandleError {
 }
 
 impl std::error::Error for DescriptorHandleError {}
+
+#[cfg(test)]
+mod tests {
+    use linux_api::fcntl::DescriptorFlags;
+
+    use super::*;
+    use crate::host::descriptor::eventfd::EventFd;
+    use crate::host::descriptor::{CompatFile, File, FileStatus, OpenFile};
+
+    fn new_descriptor() -> Descriptor {
+        let eventfd = EventFd::new(0, false, FileStatus::empty());
+        let file = File::EventFd(std::sync::Arc::new(atomic_refcell::AtomicRefCell::new(
+            eventfd,
+        )));
+        Descriptor::new(CompatFile::New(OpenFile::new(file)))
+    }
+
+    #[test]
+    fn test_register_descriptor_pair() {
+        let mut table = DescriptorTable::new();
+        let fd = table.register_descriptor(new_descriptor()).unwrap();
+
+        let [fd_1, fd_2] = table
+            .register_descriptor_pair(new_descriptor(), new_descriptor())
+            .unwrap();
+        assert_eq!(fd.val(), 0);
+        assert_eq!(fd_1.val(), 1);
+        assert_eq!(fd_2.val(), 2);
+
+        // dropping the returned descriptors will close them
+        drop(table.remove_all());
+    }
+
+    #[test]
+    fn test_register_descriptor_pair_at_limit() {
+        let mut table = DescriptorTable::new();
+        table.set_fd_limit(2);
+        let fd = table.register_descriptor(new_descriptor()).unwrap();
+
+        // there's only room for one more descriptor, so neither should be registered
+        assert!(table
+            .register_descriptor_pair(new_descriptor(), new_descriptor())
+            .is_err());
+        assert_eq!(table.iter().count(), 1);
+
+        // the index freed by the failed registration is still usable
+        let fd_2 = table.register_descriptor(new_descriptor()).unwrap();
+        assert_eq!(fd.val(), 0);
+        assert_eq!(fd_2.val(), 1);
+
+        drop(table.remove_all());
+    }
+
+    #[test]
+    fn test_reserve() {
+        const COUNT: usize = 1000;
+
+        let mut table = DescriptorTable::new();
+        table.reserve(COUNT);
+        let capacity = table.capacity();
+        assert!(capacity >= COUNT);
+
+        for i in 0..COUNT {
+            let fd = table.register_descriptor(new_descriptor()).unwrap();
+            assert_eq!(usize::try_from(fd.val()).unwrap(), i);
+        }
+
+        // the table didn't need to grow
+        assert_eq!(table.capacity(), capacity);
+        assert_eq!(table.iter().count(), COUNT);
+
+        drop(table.remove_all());
+    }
+
+    #[test]
+    fn test_descriptor_info() {
+        let mut table = DescriptorTable::new();
+        assert!(table.descriptor_info().is_empty());
+
+        let fd_1 = table.register_descriptor(new_descriptor()).unwrap();
+        let dup = table.get(fd_1).unwrap().dup(DescriptorFlags::empty());
+        let fd_2 = table.register_descriptor(dup).unwrap();
+        let fd_3 = table.register_descriptor(new_descriptor()).unwrap();
+
+        let info = |handle, open_count| DescriptorInfo {
+            handle,
+            file_type: "EventFd",
+            open_count,
+        };
+
+        // the first two descriptors share an open file
+        assert_eq!(
+            table.descriptor_info(),
+            [info(fd_1, 2), info(fd_2, 2), info(fd_3, 1)],
+        );
+
+        // a cloned table (like after a fork) shares all of the open files
+        let mut table_clone = table.clone();
+        assert_eq!(
+            table_clone.descriptor_info(),
+            [info(fd_1, 4), info(fd_2, 4), info(fd_3, 2)],
+        );
+        drop(table_clone.remove_all());
+
+        drop(table.deregister_descriptor(fd_1).unwrap());
+        assert_eq!(table.descriptor_info(), [info(fd_2, 1), info(fd_3, 1)]);
+
+        drop(table.remove_all());
+        assert!(table.descriptor_info().is_empty());
+    }
+}
@@ -56,6 +56,12 @@ impl Entry {
         self.collected = FileState::empty();
     }
 
+    /// Whether the entry was added with `EPOLLEXCLUSIVE`, in which case a file event should only
+    /// wake one of the epoll instances that are monitoring the file exclusively.
+    pub fn is_exclusive(&self) -> bool {
+        self.interest.contains(EpollEvents::EPOLLEXCLUSIVE)
+    }
+
     pub fn set_priority(&mut self, priority: Option<u64>) {
         self.priority = priority;
     }
@@ -139,8 +145,14 @@ impl Entry {
 
         self.collected.insert(Self::state_from_events(events));
 
+        // One-shot entries are disabled after reporting any event, and aren't reported again
+        // until they're re-armed with `EPOLL_CTL_MOD`. Like linux, we do this by clearing all
+        // event bits of the interest, while keeping the flag bits.
         if self.interest.contains(EpollEvents::EPOLLONESHOT) {
-            self.interest.remove(events)
+            self.interest &= EpollEvents::EPOLLWAKEUP
+                | EpollEvents::EPOLLONESHOT
+                | EpollEvents::EPOLLET
+                | EpollEvents::EPOLLEXCLUSIVE;
         }
 
         log::trace!(
@@ -551,4 +563,30 @@ mod tests {
         assert!(!entry.has_ready_events());
         assert_eq!(entry.collect_ready_events(), None);
     }
+
+    #[test]
+    fn one_shot_disables_all_events() {
+        let in_out_os = EpollEvents::EPOLLIN | EpollEvents::EPOLLOUT | EpollEvents::EPOLLONESHOT;
+        let mut entry = Entry::new(in_out_os, DATA, FileState::WRITABLE);
+
+        assert_eq!(
+            entry.collect_ready_events(),
+            Some((EpollEvents::EPOLLOUT, DATA))
+        );
+
+        // A different event shouldn't be reported, since the entry is disabled.
+        entry.notify(
+            FileState::READABLE | FileState::WRITABLE,
+            FileState::READABLE,
+            FileSignals::empty(),
+        );
+        assert!(!entry.has_ready_events());
+
+        entry.modify(in_out_os, DATA, FileState::READABLE | FileState::WRITABLE);
+        assert_eq!(
+            entry.collect_ready_events(),
+            Some((EpollEvents::EPOLLIN | EpollEvents::EPOLLOUT, DATA))
+        );
+        assert!(!entry.has_ready_events());
+    }
 }
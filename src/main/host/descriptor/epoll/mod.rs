@@ -8,8 +8,12 @@ use linux_api::errno::Errno;
 use linux_api::ioctls::IoctlRequest;
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
-use crate::host::descriptor::listener::{StateEventSource, StateListenHandle, StateListenerFilter};
-use crate::host::descriptor::{File, FileMode, FileSignals, FileState, FileStatus, SyscallResult};
+use crate::host::descriptor::listener::{
+    StateEventSource, StateEventSourceOwner, StateListenHandle, StateListenerFilter,
+};
+use crate::host::descriptor::{
+    anon_inode_stat, File, FileMode, FileSignals, FileState, FileStatus, SyscallResult,
+};
 use crate::host::memory_manager::MemoryManager;
 use crate::host::syscall::io::IoVec;
 use crate::host::syscall::types::SyscallError;
@@ -26,6 +30,10 @@ use super::socket::Socket;
 mod entry;
 mod key;
 
+/// The maximum number of levels of epoll instances nested inside other epoll instances.
+/// <https://github.com/torvalds/linux/blob/2cf0f715623872823a72e451243bbf555d10d032/fs/eventpoll.c#L97>
+const EP_MAX_NESTS: u32 = 4;
+
 pub struct Epoll {
     event_source: StateEventSource,
     status: FileStatus,
@@ -41,6 +49,9 @@ pub struct Epoll {
     monitoring: HashMap<Key, Entry>,
     // Stores keys for entries with events that are ready to be reported.
     ready: BinaryHeap<PriorityKey>,
+    // The epoll instances monitoring this one, with one item for each of their entries. Used to
+    // find the nesting depth above this instance.
+    monitored_by: Vec<Weak<AtomicRefCell<Epoll>>>,
     _counter: ObjectCounter,
 }
 
@@ -54,6 +65,7 @@ impl Epoll {
             pri_counter: u64::MAX,
             monitoring: HashMap::new(),
             ready: BinaryHeap::new(),
+            monitored_by: Vec::new(),
             _counter: ObjectCounter::new("Epoll"),
         };
 
@@ -137,8 +149,8 @@ impl Epoll {
     }
 
     pub fn stat(&self) -> Result<linux_api::stat::stat, SyscallError> {
-        warn_once_then_debug!("We do not yet handle stat calls on epoll fds");
-        Err(Errno::EINVAL.into())
+        warn_once_then_debug!("Not all fields of 'struct stat' are implemented for epoll fds");
+        Ok(anon_inode_stat())
     }
 
     /// Executes an epoll control operation on the target file.
@@ -189,9 +201,22 @@ impl Epoll {
                     HashMapEntry::Occupied(_) => return Err(Errno::EEXIST),
                     HashMapEntry::Vacant(x) => x.insert(entry),
                 };
+
+                if let File::Epoll(target) = key.file() {
+                    let mut target = target.borrow_mut();
+                    target.monitored_by.retain(|x| x.strong_count() > 0);
+                    target.monitored_by.push(weak_self.clone());
+                }
             }
             EpollCtlOp::EPOLL_CTL_MOD => {
                 let entry = self.monitoring.get_mut(&key).ok_or(Errno::ENOENT)?;
+
+                // From epoll_ctl(2): Returns EINVAL when "op was EPOLL_CTL_MOD and the EPOLLEXCLUSIVE
+                // flag has previously been applied to this epfd, fd pair."
+                if entry.is_exclusive() {
+                    return Err(Errno::EINVAL);
+                }
+
                 entry.modify(events, data, state);
             }
             EpollCtlOp::EPOLL_CTL_DEL => {
@@ -203,6 +228,17 @@ impl Epoll {
                 if let Some(pri) = entry.priority() {
                     self.ready.retain(|e| e.priority() != pri)
                 }
+
+                if let File::Epoll(target) = key.file() {
+                    let mut target = target.borrow_mut();
+                    if let Some(idx) = target
+                        .monitored_by
+                        .iter()
+                        .position(|x| x.ptr_eq(&weak_self))
+                    {
+                        target.monitored_by.swap_remove(idx);
+                    }
+                }
             }
         };
 
@@ -213,6 +249,78 @@ impl Epoll {
         Ok(())
     }
 
+    /// Checks that `epoll` can monitor the epoll instance `target`. Returns `ELOOP` if this would
+    /// create a cycle of epoll instances monitoring each other, or if the path of nested epoll
+    /// instances through `epoll` and `target` would be too long. Like linux, this should only be
+    /// checked when adding an entry.
+    pub fn check_nesting(
+        epoll: &Arc<AtomicRefCell<Epoll>>,
+        target: &Arc<AtomicRefCell<Epoll>>,
+    ) -> Result<(), Errno> {
+        // Like linux, we remember the depth of each instance we've visited so that instances
+        // reachable along several paths are only visited once.
+        type Memo = HashMap<*const AtomicRefCell<Epoll>, u32>;
+
+        // The number of levels of epoll instances nested below `epoll`.
+        fn depth_below(
+            inserting_into: &Arc<AtomicRefCell<Epoll>>,
+            epoll: &Arc<AtomicRefCell<Epoll>>,
+            memo: &mut Memo,
+        ) -> Result<u32, Errno> {
+            if let Some(depth) = memo.get(&Arc::as_ptr(epoll)) {
+                return Ok(*depth);
+            }
+
+            let mut depth = 0;
+            for key in epoll.borrow().monitoring.keys() {
+                let File::Epoll(nested) = key.file() else {
+                    continue;
+                };
+
+                if Arc::ptr_eq(nested, inserting_into) {
+                    return Err(Errno::ELOOP);
+                }
+
+                depth = depth.max(depth_below(inserting_into, nested, memo)? + 1);
+                if depth > EP_MAX_NESTS {
+                    return Err(Errno::ELOOP);
+                }
+            }
+
+            memo.insert(Arc::as_ptr(epoll), depth);
+            Ok(depth)
+        }
+
+        // The number of levels of epoll instances that `epoll` is nested below.
+        fn depth_above(epoll: &Arc<AtomicRefCell<Epoll>>, memo: &mut Memo) -> u32 {
+            if let Some(depth) = memo.get(&Arc::as_ptr(epoll)) {
+                return *depth;
+            }
+
+            let depth = epoll
+                .borrow()
+                .monitored_by
+                .iter()
+                .filter_map(Weak::upgrade)
+                .map(|parent| depth_above(&parent, memo) + 1)
+                .max()
+                .unwrap_or(0);
+
+            memo.insert(Arc::as_ptr(epoll), depth);
+            depth
+        }
+
+        let below = depth_below(epoll, target, &mut HashMap::new())?;
+        let above = depth_above(epoll, &mut HashMap::new());
+
+        // `target` will be one level below `epoll`.
+        if above + 1 + below > EP_MAX_NESTS {
+            return Err(Errno::ELOOP);
+        }
+
+        Ok(())
+    }
+
     pub fn add_listener(
         &mut self,
         monitoring_state: FileState,
@@ -296,22 +404,39 @@ impl Epoll {
 
         // Set up a callback so we get informed when the file changes.
         let file = key.file().clone();
-        let handle = file.borrow_mut().add_listener(
-            listen_state,
-            listen_signals,
-            filter,
-            move |state, changed, signals, cb_queue| {
-                if let Some(epoll) = weak_self.upgrade() {
-                    epoll
-                        .borrow_mut()
-                        .notify_entry(&key, state, changed, signals, cb_queue);
-                }
-            },
-        );
+        let handle = if entry.is_exclusive() {
+            // The file stops notifying its exclusive listeners once one of them wakes a waiter.
+            file.borrow_mut().add_exclusive_listener(
+                listen_state,
+                listen_signals,
+                filter,
+                move |state, changed, signals, cb_queue| {
+                    weak_self.upgrade().is_some_and(|epoll| {
+                        epoll
+                            .borrow_mut()
+                            .notify_entry(&key, state, changed, signals, cb_queue)
+                    })
+                },
+            )
+        } else {
+            file.borrow_mut().add_listener(
+                listen_state,
+                listen_signals,
+                filter,
+                move |state, changed, signals, cb_queue| {
+                    if let Some(epoll) = weak_self.upgrade() {
+                        epoll
+                            .borrow_mut()
+                            .notify_entry(&key, state, changed, signals, cb_queue);
+                    }
+                },
+            )
+        };
         entry.set_listener_handle(Some(handle));
     }
 
-    /// The file listener callback for when a monitored entry file status changes.
+    /// The file listener callback for when a monitored entry file status changes. Returns `true` if
+    /// the change made the epoll readable while something was waiting on it.
     fn notify_entry(
         &mut self,
         key: &Key,
@@ -319,11 +444,13 @@ impl Epoll {
         changed: FileState,
         signals: FileSignals,
         cb_queue: &mut CallbackQueue,
-    ) {
+    ) -> bool {
+        let was_readable = self.state.contains(FileState::READABLE);
+
         // Notify entry of file state change if we're still monitoring it.
         match self.monitoring.get_mut(&key.clone()) {
             Some(entry) => entry.notify(state, changed, signals),
-            None => return,
+            None => return false,
         };
 
         // Update our ready set, which removes the key if the file closed.
@@ -336,6 +463,11 @@ impl Epoll {
 
         // Update the readability of the epoll descriptor.
         self.refresh_state(cb_queue);
+
+        // If we were already readable, any waiters have already been woken.
+        !was_readable
+            && self.state.contains(FileState::READABLE)
+            && self.event_source.has_listeners()
     }
 
     /// Ensures that the entry is in the ready set if it should be, or not if it shouldn't be.
@@ -412,3 +544,9 @@ impl Epoll {
         events
     }
 }
+
+impl StateEventSourceOwner for Epoll {
+    fn with_event_source<R>(&mut self, f: impl FnOnce(&mut StateEventSource) -> R) -> R {
+        f(&mut self.event_source)
+    }
+}
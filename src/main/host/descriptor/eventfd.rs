@@ -5,8 +5,10 @@ use linux_api::ioctls::IoctlRequest;
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
 use crate::cshadow as c;
-use crate::host::descriptor::listener::{StateEventSource, StateListenHandle, StateListenerFilter};
-use crate::host::descriptor::{FileMode, FileSignals, FileState, FileStatus};
+use crate::host::descriptor::listener::{
+    StateEventSource, StateEventSourceOwner, StateListenHandle, StateListenerFilter,
+};
+use crate::host::descriptor::{anon_inode_stat, FileMode, FileSignals, FileState, FileStatus};
 use crate::host::memory_manager::MemoryManager;
 use crate::host::syscall::io::{IoVec, IoVecReader, IoVecWriter};
 use crate::host::syscall::types::{SyscallError, SyscallResult};
@@ -196,8 +198,8 @@ impl EventFd {
     }
 
     pub fn stat(&self) -> Result<linux_api::stat::stat, SyscallError> {
-        warn_once_then_debug!("We do not yet handle stat calls on eventfds");
-        Err(Errno::EINVAL.into())
+        warn_once_then_debug!("Not all fields of 'struct stat' are implemented for eventfds");
+        Ok(anon_inode_stat())
     }
 
     pub fn add_listener(
@@ -279,3 +281,9 @@ impl EventFd {
             .notify_listeners(self.state, states_changed, signals, cb_queue);
     }
 }
+
+impl StateEventSourceOwner for EventFd {
+    fn with_event_source<R>(&mut self, f: impl FnOnce(&mut StateEventSource) -> R) -> R {
+        f(&mut self.event_source)
+    }
+}
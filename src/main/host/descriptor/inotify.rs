@@ -0,0 +1,332 @@
+use std::collections::VecDeque;
+use std::ffi::CStr;
+use std::io::Write;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+use linux_api::errno::Errno;
+use linux_api::ioctls::IoctlRequest;
+use linux_api::posix_types::kernel_off_t;
+use shadow_shim_helper_rs::syscall_types::ForeignPtr;
+
+use crate::cshadow as c;
+use crate::host::descriptor::listener::{
+    StateEventSource, StateEventSourceOwner, StateListenHandle, StateListenerFilter,
+};
+use crate::host::descriptor::{anon_inode_stat, FileMode, FileSignals, FileState, FileStatus};
+use crate::host::memory_manager::MemoryManager;
+use crate::host::syscall::io::{IoVec, IoVecWriter};
+use crate::host::syscall::types::{SyscallError, SyscallResult};
+use crate::utility::callback_queue::CallbackQueue;
+use crate::utility::HostTreePointer;
+
+/// An inotify instance, enabling support for inotify(7).
+///
+/// Since the plugin's files live on the real filesystem, watches are added to a native inotify
+/// instance owned by Shadow. We don't let the native instance's readiness leak into the simulation
+/// directly. Instead, pending native events are only moved into this object's buffer when
+/// [`Inotify::refresh`] is called, which the host does at syscall boundaries. This means that
+/// events become readable at deterministic points in the simulation.
+pub struct Inotify {
+    native: OwnedFd,
+    // Complete `inotify_event` records (including their names) that are ready to be read.
+    events: VecDeque<Vec<u8>>,
+    event_source: StateEventSource,
+    status: FileStatus,
+    state: FileState,
+    // Should only be used by `OpenFile` to make sure there is only ever one `OpenFile` instance for
+    // this file,
+    has_open_file: bool,
+}
+
+impl Inotify {
+    pub fn new(status: FileStatus) -> Result<Self, Errno> {
+        // The native instance is always non-blocking; blocking is handled by Shadow.
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(last_errno());
+        }
+
+        Ok(Self {
+            // SAFETY: We just created this fd and nothing else owns it.
+            native: unsafe { OwnedFd::from_raw_fd(fd) },
+            events: VecDeque::new(),
+            event_source: StateEventSource::new(),
+            state: FileState::ACTIVE,
+            status,
+            has_open_file: false,
+        })
+    }
+
+    /// Add or modify a watch for the absolute `path`, returning the watch descriptor.
+    pub fn add_watch(&mut self, path: &CStr, mask: u32) -> Result<i32, Errno> {
+        let wd = unsafe { libc::inotify_add_watch(self.native.as_raw_fd(), path.as_ptr(), mask) };
+        if wd < 0 {
+            return Err(last_errno());
+        }
+        Ok(wd)
+    }
+
+    /// Remove the watch `wd`.
+    pub fn rm_watch(&mut self, wd: i32, cb_queue: &mut CallbackQueue) -> Result<(), Errno> {
+        let rv = unsafe { libc::inotify_rm_watch(self.native.as_raw_fd(), wd) };
+        if rv < 0 {
+            return Err(last_errno());
+        }
+
+        // inotify(7): removing a watch generates an IN_IGNORED event.
+        self.refresh(cb_queue);
+        Ok(())
+    }
+
+    /// Move any events that are pending on the native inotify instance into our buffer and update
+    /// our readable state.
+    pub fn refresh(&mut self, cb_queue: &mut CallbackQueue) {
+        if self.state.contains(FileState::CLOSED) {
+            return;
+        }
+
+        // inotify(7): "sizeof(struct inotify_event) + NAME_MAX + 1 will be sufficient to read at
+        // least one event."
+        const BUF_LEN: usize =
+            std::mem::size_of::<libc::inotify_event>() + linux_api::limits::NAME_MAX + 1;
+        let mut buf = [0u8; BUF_LEN];
+
+        loop {
+            let rv = unsafe {
+                libc::read(
+                    self.native.as_raw_fd(),
+                    buf.as_mut_ptr() as *mut libc::c_void,
+                    buf.len(),
+                )
+            };
+            if rv <= 0 {
+                // Most likely EAGAIN since there are no more events.
+                break;
+            }
+
+            let mut remaining = &buf[..rv as usize];
+            while !remaining.is_empty() {
+                let len = event_len(remaining);
+                self.events.push_back(remaining[..len].to_vec());
+                remaining = &remaining[len..];
+            }
+        }
+
+        let mut new_state = FileState::empty();
+        new_state.set(FileState::READABLE, !self.events.is_empty());
+
+        self.update_state(
+            FileState::READABLE,
+            new_state,
+            FileSignals::empty(),
+            cb_queue,
+        );
+    }
+
+    pub fn status(&self) -> FileStatus {
+        self.status
+    }
+
+    pub fn set_status(&mut self, status: FileStatus) {
+        self.status = status;
+    }
+
+    pub fn mode(&self) -> FileMode {
+        FileMode::READ
+    }
+
+    pub fn has_open_file(&self) -> bool {
+        self.has_open_file
+    }
+
+    pub fn supports_sa_restart(&self) -> bool {
+        true
+    }
+
+    pub fn set_has_open_file(&mut self, val: bool) {
+        self.has_open_file = val;
+    }
+
+    pub fn readv(
+        &mut self,
+        iovs: &[IoVec],
+        offset: Option<kernel_off_t>,
+        _flags: std::ffi::c_int,
+        mem: &mut MemoryManager,
+        cb_queue: &mut CallbackQueue,
+    ) -> Result<isize, SyscallError> {
+        // Inotify instances don't support seeking
+        if offset.is_some() {
+            return Err(Errno::ESPIPE.into());
+        }
+
+        let Some(first) = self.events.front() else {
+            log::trace!("No inotify events can be read right now");
+            return Err(Errno::EWOULDBLOCK.into());
+        };
+
+        let len: usize = iovs.iter().map(|x| x.len).sum();
+
+        // inotify(7): "The behavior when the buffer given to read(2) is too small to return
+        // information about the next event depends on the kernel version: [...] since Linux
+        // 2.6.21, read(2) fails with the error EINVAL."
+        if len < first.len() {
+            log::trace!("Buffer is too small to read the next inotify event");
+            return Err(Errno::EINVAL.into());
+        }
+
+        let mut writer = IoVecWriter::new(iovs, mem);
+        let mut bytes_written = 0;
+        while let Some(event) = self.events.front() {
+            if bytes_written + event.len() > len {
+                break;
+            }
+            writer.write_all(event)?;
+            bytes_written += event.len();
+            self.events.pop_front();
+        }
+
+        self.refresh(cb_queue);
+
+        Ok(bytes_written.try_into().unwrap())
+    }
+
+    pub fn writev(
+        &mut self,
+        _iovs: &[IoVec],
+        _offset: Option<kernel_off_t>,
+        _flags: std::ffi::c_int,
+        _mem: &mut MemoryManager,
+        _cb_queue: &mut CallbackQueue,
+    ) -> Result<isize, SyscallError> {
+        // Inotify instances don't support writing.
+        Err(Errno::EINVAL.into())
+    }
+
+    pub fn close(&mut self, cb_queue: &mut CallbackQueue) -> Result<(), SyscallError> {
+        // Set the closed flag and remove the active and readable flags.
+        self.update_state(
+            FileState::CLOSED | FileState::ACTIVE | FileState::READABLE,
+            FileState::CLOSED,
+            FileSignals::empty(),
+            cb_queue,
+        );
+
+        Ok(())
+    }
+
+    pub fn ioctl(
+        &mut self,
+        request: IoctlRequest,
+        arg_ptr: ForeignPtr<()>,
+        mem: &mut MemoryManager,
+    ) -> SyscallResult {
+        match request {
+            // inotify(7): "returns the number of bytes available to read"
+            IoctlRequest::FIONREAD => {
+                let len: usize = self.events.iter().map(|x| x.len()).sum();
+                let len: libc::c_int = len.try_into().unwrap();
+
+                let arg_ptr = arg_ptr.cast::<libc::c_int>();
+                mem.write(arg_ptr, &len)?;
+
+                Ok(0.into())
+            }
+            _ => {
+                warn_once_then_debug!(
+                    "We do not yet handle ioctl request {request:?} on inotify instances"
+                );
+                Err(Errno::EINVAL.into())
+            }
+        }
+    }
+
+    pub fn stat(&self) -> Result<linux_api::stat::stat, SyscallError> {
+        warn_once_then_debug!(
+            "Not all fields of 'struct stat' are implemented for inotify instances"
+        );
+        Ok(anon_inode_stat())
+    }
+
+    pub fn add_listener(
+        &mut self,
+        monitoring_state: FileState,
+        monitoring_signals: FileSignals,
+        filter: StateListenerFilter,
+        notify_fn: impl Fn(FileState, FileState, FileSignals, &mut CallbackQueue)
+            + Send
+            + Sync
+            + 'static,
+    ) -> StateListenHandle {
+        self.event_source
+            .add_listener(monitoring_state, monitoring_signals, filter, notify_fn)
+    }
+
+    pub fn add_legacy_listener(&mut self, ptr: HostTreePointer<c::StatusListener>) {
+        self.event_source.add_legacy_listener(ptr);
+    }
+
+    pub fn remove_legacy_listener(&mut self, ptr: *mut c::StatusListener) {
+        self.event_source.remove_legacy_listener(ptr);
+    }
+
+    pub fn state(&self) -> FileState {
+        self.state
+    }
+
+    fn update_state(
+        &mut self,
+        mask: FileState,
+        state: FileState,
+        signals: FileSignals,
+        cb_queue: &mut CallbackQueue,
+    ) {
+        let old_state = self.state;
+
+        // Remove the mask, then copy the masked flags.
+        self.state.remove(mask);
+        self.state.insert(state & mask);
+
+        self.handle_state_change(old_state, signals, cb_queue);
+    }
+
+    fn handle_state_change(
+        &mut self,
+        old_state: FileState,
+        signals: FileSignals,
+        cb_queue: &mut CallbackQueue,
+    ) {
+        let states_changed = self.state ^ old_state;
+
+        // Just return if nothing changed.
+        if states_changed.is_empty() && signals.is_empty() {
+            return;
+        }
+
+        self.event_source
+            .notify_listeners(self.state, states_changed, signals, cb_queue);
+    }
+}
+
+impl StateEventSourceOwner for Inotify {
+    fn with_event_source<R>(&mut self, f: impl FnOnce(&mut StateEventSource) -> R) -> R {
+        f(&mut self.event_source)
+    }
+}
+
+/// The errno set by the most recent failed libc call.
+fn last_errno() -> Errno {
+    Errno::try_from(nix::errno::Errno::last() as u16).unwrap()
+}
+
+/// The length of the `inotify_event` record at the start of `buf`, including its name.
+fn event_len(buf: &[u8]) -> usize {
+    const HEADER_LEN: usize = std::mem::size_of::<libc::inotify_event>();
+    let name_len_offset = std::mem::offset_of!(libc::inotify_event, len);
+    let name_len = u32::from_ne_bytes(
+        buf[name_len_offset..name_len_offset + 4]
+            .try_into()
+            .unwrap(),
+    );
+    HEADER_LEN + usize::try_from(name_len).unwrap()
+}
@@ -126,36 +126,92 @@ impl StateEventSource {
     ) -> StateListenHandle {
         self.inner
             .add_listener(move |(state, changed, signals), cb_queue| {
-                // true if any of the bits we're monitoring have changed
-                let flipped = monitoring_state.intersects(changed);
-
-                // true if any of the bits we're monitoring are set
-                let on = monitoring_state.intersects(state);
-
-                let notify = match filter {
-                    // at least one monitored bit is on, and at least one has changed
-                    StateListenerFilter::OffToOn => flipped && on,
-                    // all monitored bits are off, and at least one has changed
-                    StateListenerFilter::OnToOff => flipped && !on,
-                    // at least one monitored bit has changed
-                    StateListenerFilter::Always => flipped,
-                    StateListenerFilter::Never => false,
+                let Some(signals) = Self::filter(
+                    monitoring_state,
+                    monitoring_signals,
+                    filter,
+                    state,
+                    changed,
+                    signals,
+                ) else {
+                    return;
                 };
 
-                // filter the signals to only the ones we're monitoring
-                let signals = signals.intersection(monitoring_signals);
-
-                // also want to notify if a monitored signal was emitted
-                let notify = notify || !signals.is_empty();
+                (notify_fn)(state, changed, signals, cb_queue)
+            })
+    }
 
-                if !notify {
-                    return;
-                }
+    /// Add an exclusive listener. Exclusive listeners are notified in the order they were added
+    /// until one returns `true`, which should mean that it woke a waiter. The filter applies in the
+    /// same way as for [`add_listener`](Self::add_listener), and a listener that's filtered out
+    /// doesn't stop the remaining exclusive listeners from being notified.
+    pub fn add_exclusive_listener(
+        &mut self,
+        monitoring_state: FileState,
+        monitoring_signals: FileSignals,
+        filter: StateListenerFilter,
+        notify_fn: impl Fn(FileState, FileState, FileSignals, &mut CallbackQueue) -> bool
+            + Send
+            + Sync
+            + 'static,
+    ) -> StateListenHandle {
+        self.inner
+            .add_exclusive_listener(move |(state, changed, signals), cb_queue| {
+                let Some(signals) = Self::filter(
+                    monitoring_state,
+                    monitoring_signals,
+                    filter,
+                    state,
+                    changed,
+                    signals,
+                ) else {
+                    return false;
+                };
 
                 (notify_fn)(state, changed, signals, cb_queue)
             })
     }
 
+    /// Returns the signals that should be passed to a listener, or `None` if the listener
+    /// shouldn't be notified.
+    fn filter(
+        monitoring_state: FileState,
+        monitoring_signals: FileSignals,
+        filter: StateListenerFilter,
+        state: FileState,
+        changed: FileState,
+        signals: FileSignals,
+    ) -> Option<FileSignals> {
+        // true if any of the bits we're monitoring have changed
+        let flipped = monitoring_state.intersects(changed);
+
+        // true if any of the bits we're monitoring are set
+        let on = monitoring_state.intersects(state);
+
+        let notify = match filter {
+            // at least one monitored bit is on, and at least one has changed
+            StateListenerFilter::OffToOn => flipped && on,
+            // all monitored bits are off, and at least one has changed
+            StateListenerFilter::OnToOff => flipped && !on,
+            // at least one monitored bit has changed
+            StateListenerFilter::Always => flipped,
+            StateListenerFilter::Never => false,
+        };
+
+        // filter the signals to only the ones we're monitoring
+        let signals = signals.intersection(monitoring_signals);
+
+        // also want to notify if a monitored signal was emitted
+        let notify = notify || !signals.is_empty();
+
+        notify.then_some(signals)
+    }
+
+    /// Returns `true` if anything is listening to this event source.
+    pub fn has_listeners(&self) -> bool {
+        self.inner.has_listeners()
+    }
+
     pub fn add_legacy_listener(&mut self, ptr: HostTreePointer<c::StatusListener>) {
         self.legacy_helper.add_listener(ptr, &mut self.inner);
     }
@@ -182,6 +238,34 @@ impl Default for StateEventSource {
     }
 }
 
+/// A file whose state changes are reported by a [`StateEventSource`]. Files that implement this
+/// get the listener methods that simply forward to their event source.
+pub trait StateEventSourceOwner {
+    /// Call `f` with the file's event source.
+    fn with_event_source<R>(&mut self, f: impl FnOnce(&mut StateEventSource) -> R) -> R;
+
+    /// See [`StateEventSource::add_exclusive_listener`].
+    fn add_exclusive_listener(
+        &mut self,
+        monitoring_state: FileState,
+        monitoring_signals: FileSignals,
+        filter: StateListenerFilter,
+        notify_fn: impl Fn(FileState, FileState, FileSignals, &mut CallbackQueue) -> bool
+            + Send
+            + Sync
+            + 'static,
+    ) -> StateListenHandle {
+        self.with_event_source(|event_source| {
+            event_source.add_exclusive_listener(
+                monitoring_state,
+                monitoring_signals,
+                filter,
+                notify_fn,
+            )
+        })
+    }
+}
+
 mod export {
     use super::*;
 
@@ -5,11 +5,14 @@ use std::sync::Arc;
 use atomic_refcell::AtomicRefCell;
 use linux_api::fcntl::{DescriptorFlags, OFlag};
 use linux_api::ioctls::IoctlRequest;
+use linux_api::stat::SFlag;
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
 use crate::core::worker;
 use crate::cshadow as c;
-use crate::host::descriptor::listener::{StateListenHandle, StateListenerFilter};
+use crate::host::descriptor::listener::{
+    StateEventSourceOwner, StateListenHandle, StateListenerFilter,
+};
 use crate::host::descriptor::socket::{Socket, SocketRef, SocketRefMut};
 use crate::host::host::Host;
 use crate::host::memory_manager::MemoryManager;
@@ -21,11 +24,16 @@ use crate::utility::{HostTreePointer, IsSend, IsSync, ObjectCounter};
 pub mod descriptor_table;
 pub mod epoll;
 pub mod eventfd;
+pub mod inotify;
 pub mod listener;
+pub mod mqueue;
+pub mod pidfd;
 pub mod pipe;
 pub mod shared_buf;
+pub mod signalfd;
 pub mod socket;
 pub mod timerfd;
+pub mod userfaultfd;
 
 bitflags::bitflags! {
     /// These are flags that can potentially be changed from the plugin (analagous to the Linux
@@ -156,7 +164,12 @@ pub enum File {
     EventFd(Arc<AtomicRefCell<eventfd::EventFd>>),
     Socket(Socket),
     TimerFd(Arc<AtomicRefCell<timerfd::TimerFd>>),
+    SignalFd(Arc<AtomicRefCell<signalfd::SignalFd>>),
+    Inotify(Arc<AtomicRefCell<inotify::Inotify>>),
+    PidFd(Arc<AtomicRefCell<pidfd::PidFd>>),
     Epoll(Arc<AtomicRefCell<epoll::Epoll>>),
+    UserFaultFd(Arc<AtomicRefCell<userfaultfd::UserFaultFd>>),
+    MessageQueue(Arc<AtomicRefCell<mqueue::MessageQueue>>),
 }
 
 // will not compile if `File` is not Send + Sync
@@ -170,7 +183,12 @@ impl File {
             Self::EventFd(ref f) => FileRef::EventFd(f.borrow()),
             Self::Socket(ref f) => FileRef::Socket(f.borrow()),
             Self::TimerFd(ref f) => FileRef::TimerFd(f.borrow()),
+            Self::SignalFd(ref f) => FileRef::SignalFd(f.borrow()),
+            Self::Inotify(ref f) => FileRef::Inotify(f.borrow()),
+            Self::PidFd(ref f) => FileRef::PidFd(f.borrow()),
             Self::Epoll(ref f) => FileRef::Epoll(f.borrow()),
+            Self::UserFaultFd(ref f) => FileRef::UserFaultFd(f.borrow()),
+            Self::MessageQueue(ref f) => FileRef::MessageQueue(f.borrow()),
         }
     }
 
@@ -180,7 +198,12 @@ impl File {
             Self::EventFd(ref f) => FileRef::EventFd(f.try_borrow()?),
             Self::Socket(ref f) => FileRef::Socket(f.try_borrow()?),
             Self::TimerFd(ref f) => FileRef::TimerFd(f.try_borrow()?),
+            Self::SignalFd(ref f) => FileRef::SignalFd(f.try_borrow()?),
+            Self::Inotify(ref f) => FileRef::Inotify(f.try_borrow()?),
+            Self::PidFd(ref f) => FileRef::PidFd(f.try_borrow()?),
             Self::Epoll(ref f) => FileRef::Epoll(f.try_borrow()?),
+            Self::UserFaultFd(ref f) => FileRef::UserFaultFd(f.try_borrow()?),
+            Self::MessageQueue(ref f) => FileRef::MessageQueue(f.try_borrow()?),
         })
     }
 
@@ -190,7 +213,12 @@ impl File {
             Self::EventFd(ref f) => FileRefMut::EventFd(f.borrow_mut()),
             Self::Socket(ref f) => FileRefMut::Socket(f.borrow_mut()),
             Self::TimerFd(ref f) => FileRefMut::TimerFd(f.borrow_mut()),
+            Self::SignalFd(ref f) => FileRefMut::SignalFd(f.borrow_mut()),
+            Self::Inotify(ref f) => FileRefMut::Inotify(f.borrow_mut()),
+            Self::PidFd(ref f) => FileRefMut::PidFd(f.borrow_mut()),
             Self::Epoll(ref f) => FileRefMut::Epoll(f.borrow_mut()),
+            Self::UserFaultFd(ref f) => FileRefMut::UserFaultFd(f.borrow_mut()),
+            Self::MessageQueue(ref f) => FileRefMut::MessageQueue(f.borrow_mut()),
         }
     }
 
@@ -200,30 +228,50 @@ impl File {
             Self::EventFd(ref f) => FileRefMut::EventFd(f.try_borrow_mut()?),
             Self::Socket(ref f) => FileRefMut::Socket(f.try_borrow_mut()?),
             Self::TimerFd(ref f) => FileRefMut::TimerFd(f.try_borrow_mut()?),
+            Self::SignalFd(ref f) => FileRefMut::SignalFd(f.try_borrow_mut()?),
+            Self::Inotify(ref f) => FileRefMut::Inotify(f.try_borrow_mut()?),
+            Self::PidFd(ref f) => FileRefMut::PidFd(f.try_borrow_mut()?),
             Self::Epoll(ref f) => FileRefMut::Epoll(f.try_borrow_mut()?),
+            Self::UserFaultFd(ref f) => FileRefMut::UserFaultFd(f.try_borrow_mut()?),
+            Self::MessageQueue(ref f) => FileRefMut::MessageQueue(f.try_borrow_mut()?),
         })
     }
 
+    /// The name of the file's type, such as "Pipe".
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Pipe(_) => "Pipe",
+            Self::EventFd(_) => "EventFd",
+            Self::Socket(_) => "Socket",
+            Self::TimerFd(_) => "TimerFd",
+            Self::SignalFd(_) => "SignalFd",
+            Self::Inotify(_) => "Inotify",
+            Self::PidFd(_) => "PidFd",
+            Self::Epoll(_) => "Epoll",
+            Self::UserFaultFd(_) => "UserFaultFd",
+            Self::MessageQueue(_) => "MessageQueue",
+        }
+    }
+
     pub fn canonical_handle(&self) -> usize {
         match self {
             Self::Pipe(f) => Arc::as_ptr(f) as usize,
             Self::EventFd(f) => Arc::as_ptr(f) as usize,
             Self::Socket(ref f) => f.canonical_handle(),
             Self::TimerFd(f) => Arc::as_ptr(f) as usize,
+            Self::SignalFd(f) => Arc::as_ptr(f) as usize,
+            Self::Inotify(f) => Arc::as_ptr(f) as usize,
+            Self::PidFd(f) => Arc::as_ptr(f) as usize,
             Self::Epoll(f) => Arc::as_ptr(f) as usize,
+            Self::UserFaultFd(f) => Arc::as_ptr(f) as usize,
+            Self::MessageQueue(f) => Arc::as_ptr(f) as usize,
         }
     }
 }
 
 impl std::fmt::Debug for File {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::Pipe(_) => write!(f, "Pipe")?,
-            Self::EventFd(_) => write!(f, "EventFd")?,
-            Self::Socket(_) => write!(f, "Socket")?,
-            Self::TimerFd(_) => write!(f, "TimerFd")?,
-            Self::Epoll(_) => write!(f, "Epoll")?,
-        }
+        write!(f, "{}", self.type_name())?;
 
         if let Ok(file) = self.try_borrow() {
             let state = file.state();
@@ -241,7 +289,12 @@ pub enum FileRef<'a> {
     EventFd(atomic_refcell::AtomicRef<'a, eventfd::EventFd>),
     Socket(SocketRef<'a>),
     TimerFd(atomic_refcell::AtomicRef<'a, timerfd::TimerFd>),
+    SignalFd(atomic_refcell::AtomicRef<'a, signalfd::SignalFd>),
+    Inotify(atomic_refcell::AtomicRef<'a, inotify::Inotify>),
+    PidFd(atomic_refcell::AtomicRef<'a, pidfd::PidFd>),
     Epoll(atomic_refcell::AtomicRef<'a, epoll::Epoll>),
+    UserFaultFd(atomic_refcell::AtomicRef<'a, userfaultfd::UserFaultFd>),
+    MessageQueue(atomic_refcell::AtomicRef<'a, mqueue::MessageQueue>),
 }
 
 /// Wraps a mutably borrowed [`File`]. Created from [`File::borrow_mut`] or
@@ -251,62 +304,67 @@ pub enum FileRefMut<'a> {
     EventFd(atomic_refcell::AtomicRefMut<'a, eventfd::EventFd>),
     Socket(SocketRefMut<'a>),
     TimerFd(atomic_refcell::AtomicRefMut<'a, timerfd::TimerFd>),
+    SignalFd(atomic_refcell::AtomicRefMut<'a, signalfd::SignalFd>),
+    Inotify(atomic_refcell::AtomicRefMut<'a, inotify::Inotify>),
+    PidFd(atomic_refcell::AtomicRefMut<'a, pidfd::PidFd>),
     Epoll(atomic_refcell::AtomicRefMut<'a, epoll::Epoll>),
+    UserFaultFd(atomic_refcell::AtomicRefMut<'a, userfaultfd::UserFaultFd>),
+    MessageQueue(atomic_refcell::AtomicRefMut<'a, mqueue::MessageQueue>),
 }
 
 impl FileRef<'_> {
-    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, SignalFd, Inotify, PidFd, Epoll, UserFaultFd, MessageQueue;
         pub fn state(&self) -> FileState
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, SignalFd, Inotify, PidFd, Epoll, UserFaultFd, MessageQueue;
         pub fn mode(&self) -> FileMode
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, SignalFd, Inotify, PidFd, Epoll, UserFaultFd, MessageQueue;
         pub fn status(&self) -> FileStatus
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, SignalFd, Inotify, PidFd, Epoll, UserFaultFd, MessageQueue;
         pub fn stat(&self) -> Result<linux_api::stat::stat, SyscallError>
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, SignalFd, Inotify, PidFd, Epoll, UserFaultFd, MessageQueue;
         pub fn has_open_file(&self) -> bool
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, SignalFd, Inotify, PidFd, Epoll, UserFaultFd, MessageQueue;
         pub fn supports_sa_restart(&self) -> bool
     );
 }
 
 impl FileRefMut<'_> {
-    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, SignalFd, Inotify, PidFd, Epoll, UserFaultFd, MessageQueue;
         pub fn state(&self) -> FileState
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, SignalFd, Inotify, PidFd, Epoll, UserFaultFd, MessageQueue;
         pub fn mode(&self) -> FileMode
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, SignalFd, Inotify, PidFd, Epoll, UserFaultFd, MessageQueue;
         pub fn status(&self) -> FileStatus
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, SignalFd, Inotify, PidFd, Epoll, UserFaultFd, MessageQueue;
         pub fn stat(&self) -> Result<linux_api::stat::stat, SyscallError>
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, SignalFd, Inotify, PidFd, Epoll, UserFaultFd, MessageQueue;
         pub fn has_open_file(&self) -> bool
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, SignalFd, Inotify, PidFd, Epoll, UserFaultFd, MessageQueue;
         pub fn supports_sa_restart(&self) -> bool
     );
-    enum_passthrough!(self, (val), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (val), Pipe, EventFd, Socket, TimerFd, SignalFd, Inotify, PidFd, Epoll, UserFaultFd, MessageQueue;
         pub fn set_has_open_file(&mut self, val: bool)
     );
-    enum_passthrough!(self, (cb_queue), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (cb_queue), Pipe, EventFd, Socket, TimerFd, SignalFd, Inotify, PidFd, Epoll, UserFaultFd, MessageQueue;
         pub fn close(&mut self, cb_queue: &mut CallbackQueue) -> Result<(), SyscallError>
     );
-    enum_passthrough!(self, (status), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (status), Pipe, EventFd, Socket, TimerFd, SignalFd, Inotify, PidFd, Epoll, UserFaultFd, MessageQueue;
         pub fn set_status(&mut self, status: FileStatus)
     );
-    enum_passthrough!(self, (request, arg_ptr, memory_manager), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (request, arg_ptr, memory_manager), Pipe, EventFd, Socket, TimerFd, SignalFd, Inotify, PidFd, Epoll, UserFaultFd, MessageQueue;
         pub fn ioctl(&mut self, request: IoctlRequest, arg_ptr: ForeignPtr<()>, memory_manager: &mut MemoryManager) -> SyscallResult
     );
-    enum_passthrough!(self, (monitoring_state, monitoring_signals, filter, notify_fn), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (monitoring_state, monitoring_signals, filter, notify_fn), Pipe, EventFd, Socket, TimerFd, SignalFd, Inotify, PidFd, Epoll, UserFaultFd, MessageQueue;
         pub fn add_listener(
             &mut self,
             monitoring_state: FileState,
@@ -315,17 +373,26 @@ impl FileRefMut<'_> {
             notify_fn: impl Fn(FileState, FileState, FileSignals, &mut CallbackQueue) + Send + Sync + 'static,
         ) -> StateListenHandle
     );
-    enum_passthrough!(self, (ptr), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (monitoring_state, monitoring_signals, filter, notify_fn), Pipe, EventFd, Socket, TimerFd, SignalFd, Inotify, PidFd, Epoll, UserFaultFd, MessageQueue;
+        pub fn add_exclusive_listener(
+            &mut self,
+            monitoring_state: FileState,
+            monitoring_signals: FileSignals,
+            filter: StateListenerFilter,
+            notify_fn: impl Fn(FileState, FileState, FileSignals, &mut CallbackQueue) -> bool + Send + Sync + 'static,
+        ) -> StateListenHandle
+    );
+    enum_passthrough!(self, (ptr), Pipe, EventFd, Socket, TimerFd, SignalFd, Inotify, PidFd, Epoll, UserFaultFd, MessageQueue;
         pub fn add_legacy_listener(&mut self, ptr: HostTreePointer<c::StatusListener>)
     );
-    enum_passthrough!(self, (ptr), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (ptr), Pipe, EventFd, Socket, TimerFd, SignalFd, Inotify, PidFd, Epoll, UserFaultFd, MessageQueue;
         pub fn remove_legacy_listener(&mut self, ptr: *mut c::StatusListener)
     );
-    enum_passthrough!(self, (iovs, offset, flags, mem, cb_queue), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (iovs, offset, flags, mem, cb_queue), Pipe, EventFd, Socket, TimerFd, SignalFd, Inotify, PidFd, Epoll, UserFaultFd, MessageQueue;
         pub fn readv(&mut self, iovs: &[IoVec], offset: Option<libc::off_t>, flags: libc::c_int,
                      mem: &mut MemoryManager, cb_queue: &mut CallbackQueue) -> Result<libc::ssize_t, SyscallError>
     );
-    enum_passthrough!(self, (iovs, offset, flags, mem, cb_queue), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (iovs, offset, flags, mem, cb_queue), Pipe, EventFd, Socket, TimerFd, SignalFd, Inotify, PidFd, Epoll, UserFaultFd, MessageQueue;
         pub fn writev(&mut self, iovs: &[IoVec], offset: Option<libc::off_t>, flags: libc::c_int,
                       mem: &mut MemoryManager, cb_queue: &mut CallbackQueue) -> Result<libc::ssize_t, SyscallError>
     );
@@ -338,7 +405,12 @@ impl std::fmt::Debug for FileRef<'_> {
             Self::EventFd(_) => write!(f, "EventFd")?,
             Self::Socket(_) => write!(f, "Socket")?,
             Self::TimerFd(_) => write!(f, "TimerFd")?,
+            Self::SignalFd(_) => write!(f, "SignalFd")?,
+            Self::Inotify(_) => write!(f, "Inotify")?,
+            Self::PidFd(_) => write!(f, "PidFd")?,
             Self::Epoll(_) => write!(f, "Epoll")?,
+            Self::UserFaultFd(_) => write!(f, "UserFaultFd")?,
+            Self::MessageQueue(_) => write!(f, "MessageQueue")?,
         }
 
         let state = self.state();
@@ -354,7 +426,12 @@ impl std::fmt::Debug for FileRefMut<'_> {
             Self::EventFd(_) => write!(f, "EventFd")?,
             Self::Socket(_) => write!(f, "Socket")?,
             Self::TimerFd(_) => write!(f, "TimerFd")?,
+            Self::SignalFd(_) => write!(f, "SignalFd")?,
+            Self::Inotify(_) => write!(f, "Inotify")?,
+            Self::PidFd(_) => write!(f, "PidFd")?,
             Self::Epoll(_) => write!(f, "Epoll")?,
+            Self::UserFaultFd(_) => write!(f, "UserFaultFd")?,
+            Self::MessageQueue(_) => write!(f, "MessageQueue")?,
         }
 
         let state = self.state();
@@ -413,6 +490,12 @@ impl OpenFile {
         }
     }
 
+    /// The number of references to this open file, which is typically the number of descriptors
+    /// (across all descriptor tables) that point to it.
+    pub fn open_count(&self) -> usize {
+        Arc::strong_count(&self.inner)
+    }
+
     pub fn inner_file(&self) -> &File {
         self.inner.file.as_ref().unwrap()
     }
@@ -626,6 +709,11 @@ impl LegacyFileCounter {
         unsafe { self.file.as_ref().unwrap().ptr() }
     }
 
+    /// The number of descriptors (across all descriptor tables) that point to this legacy file.
+    pub fn open_count(&self) -> usize {
+        Arc::<()>::strong_count(&self.open_count)
+    }
+
     /// Should drop `self` immediately after calling this.
     fn close_helper(&mut self, host: &Host) {
         // this isn't subject to race conditions since we should never access descriptors
@@ -658,6 +746,28 @@ pub enum CompatFile {
 }
 
 impl CompatFile {
+    /// The name of the file's type, such as "Pipe" or "RegularFile".
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::New(file) => file.inner_file().type_name(),
+            Self::Legacy(file) => match unsafe { c::legacyfile_getType(file.ptr()) } {
+                c::_LegacyFileType_DT_TCPSOCKET => "TcpSocket",
+                c::_LegacyFileType_DT_EPOLL => "Epoll",
+                c::_LegacyFileType_DT_FILE => "RegularFile",
+                _ => "Unknown",
+            },
+        }
+    }
+
+    /// The number of descriptors (across all descriptor tables) that point to the same open file
+    /// as this one.
+    pub fn open_count(&self) -> usize {
+        match self {
+            Self::New(file) => file.open_count(),
+            Self::Legacy(file) => file.open_count(),
+        }
+    }
+
     /// Close the file. The `host` option is a legacy option for legacy files.
     pub fn close(
         self,
@@ -674,6 +784,23 @@ impl CompatFile {
     }
 }
 
+/// A `struct stat` for a file that isn't backed by a regular filesystem, such as a socket or an
+/// anonymous inode. Only the mode and link count are meaningful; shadow can't give meaningful
+/// values for fields like the device and inode, so they are zero.
+pub fn synthetic_stat(mode: SFlag) -> linux_api::stat::stat {
+    let mut stat: linux_api::stat::stat = shadow_pod::zeroed();
+    stat.st_mode = mode.bits();
+    stat.st_nlink = 1;
+    stat
+}
+
+/// A `struct stat` for a file backed by linux's anonymous inode, such as an eventfd or epoll
+/// file. Linux doesn't set any file type bits for these, and they are readable and writable by
+/// the owner.
+pub fn anon_inode_stat() -> linux_api::stat::stat {
+    synthetic_stat(SFlag::S_IRUSR | SFlag::S_IWUSR)
+}
+
 mod export {
     use super::*;
 
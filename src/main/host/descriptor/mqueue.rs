@@ -0,0 +1,512 @@
+//! POSIX message queues, as created by mq_open(3). A [`QueueBuf`] holds the messages and is shared
+//! by each [`MessageQueue`] file that was opened for it.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use atomic_refcell::AtomicRefCell;
+use linux_api::errno::Errno;
+use linux_api::ioctls::IoctlRequest;
+use linux_api::signal::{sigval, Signal};
+use shadow_shim_helper_rs::syscall_types::ForeignPtr;
+
+use crate::cshadow as c;
+use crate::host::descriptor::listener::{
+    StateEventSource, StateEventSourceOwner, StateListenHandle, StateListenerFilter,
+};
+use crate::host::descriptor::{FileMode, FileSignals, FileState, FileStatus};
+use crate::host::memory_manager::MemoryManager;
+use crate::host::process::ProcessId;
+use crate::host::syscall::io::IoVec;
+use crate::host::syscall::types::{SyscallError, SyscallResult};
+use crate::utility::callback_queue::{CallbackQueue, EventSource, Handle};
+use crate::utility::HostTreePointer;
+
+/// A process's request to be notified when a message arrives on an empty queue. See mq_notify(3).
+#[derive(Copy, Clone)]
+pub struct MqNotify {
+    pub pid: ProcessId,
+    /// The signal to send, or `None` for `SIGEV_NONE`.
+    pub signal: Option<Signal>,
+    pub value: sigval,
+}
+
+/// The messages of a message queue, shared between all files that have the queue open.
+pub struct QueueBuf {
+    /// Ordered by descending priority, and then by the order that they were sent.
+    messages: BTreeMap<(Reverse<u32>, u64), Vec<u8>>,
+    next_seq: u64,
+    max_msgs: usize,
+    msg_size: usize,
+    notify: Option<MqNotify>,
+    state: QueueState,
+    event_source: EventSource<(QueueState, QueueState)>,
+}
+
+impl QueueBuf {
+    pub fn new(max_msgs: usize, msg_size: usize) -> Self {
+        assert_ne!(max_msgs, 0);
+        Self {
+            messages: BTreeMap::new(),
+            next_seq: 0,
+            max_msgs,
+            msg_size,
+            notify: None,
+            state: QueueState::WRITABLE,
+            event_source: EventSource::new(),
+        }
+    }
+
+    pub fn max_msgs(&self) -> usize {
+        self.max_msgs
+    }
+
+    pub fn msg_size(&self) -> usize {
+        self.msg_size
+    }
+
+    pub fn num_msgs(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Add a message to the queue. If the queue was empty and a process registered for
+    /// notification, the registration is removed and returned so that the caller can notify it.
+    pub fn send(
+        &mut self,
+        msg: Vec<u8>,
+        priority: u32,
+        cb_queue: &mut CallbackQueue,
+    ) -> Result<Option<MqNotify>, Errno> {
+        if msg.len() > self.msg_size {
+            return Err(Errno::EMSGSIZE);
+        }
+        if self.messages.len() >= self.max_msgs {
+            return Err(Errno::EAGAIN);
+        }
+
+        // mq_notify(3): "Message notification occurs only when a new message arrives and the queue
+        // was previously empty." Linux also skips the notification if another process is blocked in
+        // mq_receive(3), which we don't track.
+        let notify = if self.messages.is_empty() {
+            self.notify.take()
+        } else {
+            None
+        };
+
+        self.messages
+            .insert((Reverse(priority), self.next_seq), msg);
+        self.next_seq += 1;
+        self.refresh_state(cb_queue);
+
+        Ok(notify)
+    }
+
+    /// Remove the oldest message of the highest priority, and return it with its priority.
+    pub fn receive(&mut self, cb_queue: &mut CallbackQueue) -> Result<(Vec<u8>, u32), Errno> {
+        let Some(((Reverse(priority), _), msg)) = self.messages.pop_first() else {
+            return Err(Errno::EAGAIN);
+        };
+
+        self.refresh_state(cb_queue);
+
+        Ok((msg, priority))
+    }
+
+    /// Register or unregister (if `notify` is `None`) the process `pid` for notification.
+    pub fn set_notify(&mut self, pid: ProcessId, notify: Option<MqNotify>) -> Result<(), Errno> {
+        let registered_pid = self.notify.as_ref().map(|x| x.pid);
+
+        match notify {
+            Some(notify) => {
+                // mq_notify(3): "EBUSY Another process has already registered to receive
+                // notification for this message queue."
+                if registered_pid.is_some() {
+                    return Err(Errno::EBUSY);
+                }
+                self.notify = Some(notify);
+            }
+            None => {
+                if registered_pid == Some(pid) {
+                    self.notify = None;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn add_listener(
+        &mut self,
+        monitoring_state: QueueState,
+        notify_fn: impl Fn(QueueState, &mut CallbackQueue) + Send + Sync + 'static,
+    ) -> QueueHandle {
+        self.event_source
+            .add_listener(move |(state, changed), cb_queue| {
+                // true if any of the bits we're monitoring have changed
+                if !monitoring_state.intersects(changed) {
+                    return;
+                }
+
+                (notify_fn)(state, cb_queue)
+            })
+    }
+
+    pub fn state(&self) -> QueueState {
+        self.state
+    }
+
+    fn refresh_state(&mut self, cb_queue: &mut CallbackQueue) {
+        let old_state = self.state;
+
+        self.state
+            .set(QueueState::READABLE, !self.messages.is_empty());
+        self.state
+            .set(QueueState::WRITABLE, self.messages.len() < self.max_msgs);
+
+        let states_changed = self.state ^ old_state;
+        if states_changed.is_empty() {
+            return;
+        }
+
+        self.event_source
+            .notify_listeners((self.state, states_changed), cb_queue);
+    }
+}
+
+bitflags::bitflags! {
+    #[derive(Default, Copy, Clone, Debug)]
+    pub struct QueueState: u8 {
+        /// There is a message waiting in the queue.
+        const READABLE = 0b00000001;
+        /// The queue isn't full.
+        const WRITABLE = 0b00000010;
+    }
+}
+
+pub type QueueHandle = Handle<(QueueState, QueueState)>;
+
+/// An open message queue description, as returned by mq_open(3).
+pub struct MessageQueue {
+    queue: Option<Arc<AtomicRefCell<QueueBuf>>>,
+    event_source: StateEventSource,
+    state: FileState,
+    mode: FileMode,
+    status: FileStatus,
+    queue_event_handle: Option<QueueHandle>,
+    // should only be used by `OpenFile` to make sure there is only ever one `OpenFile` instance for
+    // this file
+    has_open_file: bool,
+}
+
+impl MessageQueue {
+    /// Create a new [`MessageQueue`]. The new file must be initialized using
+    /// [`MessageQueue::connect_to_queue`] before any of its methods are called.
+    pub fn new(mode: FileMode, status: FileStatus) -> Self {
+        Self {
+            queue: None,
+            event_source: StateEventSource::new(),
+            state: FileState::ACTIVE,
+            mode,
+            status,
+            queue_event_handle: None,
+            has_open_file: false,
+        }
+    }
+
+    pub fn status(&self) -> FileStatus {
+        self.status
+    }
+
+    pub fn set_status(&mut self, status: FileStatus) {
+        self.status = status;
+    }
+
+    pub fn mode(&self) -> FileMode {
+        self.mode
+    }
+
+    pub fn has_open_file(&self) -> bool {
+        self.has_open_file
+    }
+
+    pub fn supports_sa_restart(&self) -> bool {
+        true
+    }
+
+    pub fn set_has_open_file(&mut self, val: bool) {
+        self.has_open_file = val;
+    }
+
+    pub fn queue(&self) -> &Arc<AtomicRefCell<QueueBuf>> {
+        self.queue.as_ref().unwrap()
+    }
+
+    pub fn close(&mut self, cb_queue: &mut CallbackQueue) -> Result<(), SyscallError> {
+        if self.state.contains(FileState::CLOSED) {
+            log::warn!("Attempting to close an already-closed message queue");
+        }
+
+        // drop the event listener handle so that we stop receiving new events
+        if let Some(h) = self.queue_event_handle.take() {
+            h.stop_listening()
+        }
+
+        // the queue stays alive as long as it has a name or is open elsewhere
+        self.queue = None;
+
+        // set the closed flag and remove the active, readable, and writable flags
+        self.update_state(
+            FileState::CLOSED | FileState::ACTIVE | FileState::READABLE | FileState::WRITABLE,
+            FileState::CLOSED,
+            FileSignals::empty(),
+            cb_queue,
+        );
+
+        Ok(())
+    }
+
+    /// Send a message as with mq_send(3). Returns the notification registration that should be
+    /// triggered, if any.
+    pub fn send(
+        &mut self,
+        msg: Vec<u8>,
+        priority: u32,
+        cb_queue: &mut CallbackQueue,
+    ) -> Result<Option<MqNotify>, Errno> {
+        if !self.mode.contains(FileMode::WRITE) {
+            return Err(Errno::EBADF);
+        }
+
+        self.queue().borrow_mut().send(msg, priority, cb_queue)
+    }
+
+    /// Receive a message as with mq_receive(3) into a buffer of `buf_len` bytes.
+    pub fn receive(
+        &mut self,
+        buf_len: usize,
+        cb_queue: &mut CallbackQueue,
+    ) -> Result<(Vec<u8>, u32), Errno> {
+        if !self.mode.contains(FileMode::READ) {
+            return Err(Errno::EBADF);
+        }
+
+        let mut queue = self.queue().borrow_mut();
+
+        // mq_receive(3): "EMSGSIZE msg_len was less than the mq_msgsize attribute of the message
+        // queue."
+        if buf_len < queue.msg_size() {
+            return Err(Errno::EMSGSIZE);
+        }
+
+        queue.receive(cb_queue)
+    }
+
+    pub fn readv(
+        &mut self,
+        _iovs: &[IoVec],
+        _offset: Option<libc::off_t>,
+        _flags: libc::c_int,
+        _mem: &mut MemoryManager,
+        _cb_queue: &mut CallbackQueue,
+    ) -> Result<libc::ssize_t, SyscallError> {
+        // Linux returns a description of the queue, which programs rarely rely on
+        warn_once_then_debug!("We do not yet handle reading from message queue descriptors");
+        Err(Errno::EINVAL.into())
+    }
+
+    pub fn writev(
+        &mut self,
+        _iovs: &[IoVec],
+        _offset: Option<libc::off_t>,
+        _flags: libc::c_int,
+        _mem: &mut MemoryManager,
+        _cb_queue: &mut CallbackQueue,
+    ) -> Result<libc::ssize_t, SyscallError> {
+        warn_once_then_debug!("We do not yet handle writing to message queue descriptors");
+        Err(Errno::EINVAL.into())
+    }
+
+    pub fn ioctl(
+        &mut self,
+        request: IoctlRequest,
+        _arg_ptr: ForeignPtr<()>,
+        _memory_manager: &mut MemoryManager,
+    ) -> SyscallResult {
+        log::warn!("We do not yet handle ioctl request {request:?} on message queues");
+        Err(Errno::EINVAL.into())
+    }
+
+    pub fn stat(&self) -> Result<linux_api::stat::stat, SyscallError> {
+        warn_once_then_debug!("We do not yet handle stat calls on message queues");
+        Err(Errno::EINVAL.into())
+    }
+
+    pub fn connect_to_queue(
+        arc: &Arc<AtomicRefCell<Self>>,
+        queue: Arc<AtomicRefCell<QueueBuf>>,
+        cb_queue: &mut CallbackQueue,
+    ) {
+        let weak = Arc::downgrade(arc);
+        let mq = &mut *arc.borrow_mut();
+
+        let handle = queue.borrow_mut().add_listener(
+            QueueState::READABLE | QueueState::WRITABLE,
+            move |queue_state, cb_queue| {
+                // if the file hasn't been dropped
+                if let Some(mq) = weak.upgrade() {
+                    mq.borrow_mut().align_state_to_queue(queue_state, cb_queue);
+                }
+            },
+        );
+
+        let queue_state = queue.borrow().state();
+
+        mq.queue = Some(queue);
+        mq.queue_event_handle = Some(handle);
+
+        // update the file's initial state to align with the queue's current state
+        mq.align_state_to_queue(queue_state, cb_queue);
+    }
+
+    pub fn add_listener(
+        &mut self,
+        monitoring_state: FileState,
+        monitoring_signals: FileSignals,
+        filter: StateListenerFilter,
+        notify_fn: impl Fn(FileState, FileState, FileSignals, &mut CallbackQueue)
+            + Send
+            + Sync
+            + 'static,
+    ) -> StateListenHandle {
+        self.event_source
+            .add_listener(monitoring_state, monitoring_signals, filter, notify_fn)
+    }
+
+    pub fn add_legacy_listener(&mut self, ptr: HostTreePointer<c::StatusListener>) {
+        self.event_source.add_legacy_listener(ptr);
+    }
+
+    pub fn remove_legacy_listener(&mut self, ptr: *mut c::StatusListener) {
+        self.event_source.remove_legacy_listener(ptr);
+    }
+
+    pub fn state(&self) -> FileState {
+        self.state
+    }
+
+    /// Like Linux, the file is readable and writable based only on the queue's contents, and not
+    /// on the mode that the queue was opened with.
+    fn align_state_to_queue(&mut self, queue_state: QueueState, cb_queue: &mut CallbackQueue) {
+        // if the file is already closed, do nothing
+        if self.state.contains(FileState::CLOSED) {
+            return;
+        }
+
+        let mut file_state = FileState::empty();
+        file_state.set(
+            FileState::READABLE,
+            queue_state.contains(QueueState::READABLE),
+        );
+        file_state.set(
+            FileState::WRITABLE,
+            queue_state.contains(QueueState::WRITABLE),
+        );
+
+        let signals = if file_state.contains(FileState::READABLE) {
+            FileSignals::READ_BUFFER_GREW
+        } else {
+            FileSignals::empty()
+        };
+
+        self.update_state(
+            FileState::READABLE | FileState::WRITABLE,
+            file_state,
+            signals,
+            cb_queue,
+        );
+    }
+
+    fn update_state(
+        &mut self,
+        mask: FileState,
+        state: FileState,
+        signals: FileSignals,
+        cb_queue: &mut CallbackQueue,
+    ) {
+        let old_state = self.state;
+
+        // remove the masked flags, then copy the masked flags
+        self.state.remove(mask);
+        self.state.insert(state & mask);
+
+        self.handle_state_change(old_state, signals, cb_queue);
+    }
+
+    fn handle_state_change(
+        &mut self,
+        old_state: FileState,
+        signals: FileSignals,
+        cb_queue: &mut CallbackQueue,
+    ) {
+        let states_changed = self.state ^ old_state;
+
+        // if nothing changed
+        if states_changed.is_empty() && signals.is_empty() {
+            return;
+        }
+
+        self.event_source
+            .notify_listeners(self.state, states_changed, signals, cb_queue);
+    }
+}
+
+impl StateEventSourceOwner for MessageQueue {
+    fn with_event_source<R>(&mut self, f: impl FnOnce(&mut StateEventSource) -> R) -> R {
+        f(&mut self.event_source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority_order() {
+        let mut queue = QueueBuf::new(4, 8);
+        let mut cb_queue = CallbackQueue::new();
+        let cb_queue = &mut cb_queue;
+
+        assert_eq!(queue.receive(cb_queue), Err(Errno::EAGAIN));
+
+        queue.send(b"a".to_vec(), 1, cb_queue).unwrap();
+        queue.send(b"b".to_vec(), 5, cb_queue).unwrap();
+        queue.send(b"c".to_vec(), 1, cb_queue).unwrap();
+        queue.send(b"d".to_vec(), 5, cb_queue).unwrap();
+        assert!(!queue.state().contains(QueueState::WRITABLE));
+        assert_eq!(
+            queue.send(b"e".to_vec(), 1, cb_queue).err(),
+            Some(Errno::EAGAIN)
+        );
+
+        // highest priority first, and oldest first within a priority
+        let received: Vec<_> = (0..4).map(|_| queue.receive(cb_queue).unwrap()).collect();
+        assert_eq!(
+            received,
+            [
+                (b"b".to_vec(), 5),
+                (b"d".to_vec(), 5),
+                (b"a".to_vec(), 1),
+                (b"c".to_vec(), 1)
+            ]
+        );
+        assert!(!queue.state().contains(QueueState::READABLE));
+
+        assert_eq!(
+            queue.send(vec![0; 9], 1, cb_queue).err(),
+            Some(Errno::EMSGSIZE)
+        );
+
+        cb_queue.run();
+    }
+}
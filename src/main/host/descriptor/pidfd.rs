@@ -0,0 +1,213 @@
+use linux_api::errno::Errno;
+use linux_api::ioctls::IoctlRequest;
+use linux_api::posix_types::kernel_off_t;
+use shadow_shim_helper_rs::syscall_types::ForeignPtr;
+use shadow_shim_helper_rs::HostId;
+
+use crate::cshadow as c;
+use crate::host::descriptor::listener::{
+    StateEventSource, StateEventSourceOwner, StateListenHandle, StateListenerFilter,
+};
+use crate::host::descriptor::{anon_inode_stat, FileMode, FileSignals, FileState, FileStatus};
+use crate::host::memory_manager::MemoryManager;
+use crate::host::process::ProcessId;
+use crate::host::syscall::io::IoVec;
+use crate::host::syscall::types::{SyscallError, SyscallResult};
+use crate::utility::callback_queue::CallbackQueue;
+use crate::utility::HostTreePointer;
+
+/// A file descriptor that refers to a process, enabling support for pidfd_open(2). The pidfd
+/// becomes readable when the process exits.
+pub struct PidFd {
+    host_id: HostId,
+    pid: ProcessId,
+    event_source: StateEventSource,
+    status: FileStatus,
+    state: FileState,
+    // Should only be used by `OpenFile` to make sure there is only ever one `OpenFile` instance for
+    // this file,
+    has_open_file: bool,
+}
+
+impl PidFd {
+    /// Creates a new [`PidFd`] for process `pid` on host `host_id`. If the process has already
+    /// exited, `exited` should be `true`; otherwise the caller should make sure that
+    /// [`PidFd::process_exited`] is called when it exits.
+    pub fn new(host_id: HostId, pid: ProcessId, exited: bool, status: FileStatus) -> Self {
+        let mut state = FileState::ACTIVE;
+        state.set(FileState::READABLE, exited);
+
+        Self {
+            host_id,
+            pid,
+            event_source: StateEventSource::new(),
+            state,
+            status,
+            has_open_file: false,
+        }
+    }
+
+    /// The host of the process that this pidfd refers to.
+    pub fn host_id(&self) -> HostId {
+        self.host_id
+    }
+
+    /// The process that this pidfd refers to.
+    pub fn pid(&self) -> ProcessId {
+        self.pid
+    }
+
+    /// Called when the process that this pidfd refers to exits.
+    pub fn process_exited(&mut self, cb_queue: &mut CallbackQueue) {
+        if self.state.contains(FileState::CLOSED) {
+            return;
+        }
+
+        self.update_state(
+            FileState::READABLE,
+            FileState::READABLE,
+            FileSignals::empty(),
+            cb_queue,
+        );
+    }
+
+    pub fn status(&self) -> FileStatus {
+        self.status
+    }
+
+    pub fn set_status(&mut self, status: FileStatus) {
+        self.status = status;
+    }
+
+    pub fn mode(&self) -> FileMode {
+        FileMode::READ
+    }
+
+    pub fn has_open_file(&self) -> bool {
+        self.has_open_file
+    }
+
+    pub fn supports_sa_restart(&self) -> bool {
+        false
+    }
+
+    pub fn set_has_open_file(&mut self, val: bool) {
+        self.has_open_file = val;
+    }
+
+    pub fn readv(
+        &mut self,
+        _iovs: &[IoVec],
+        _offset: Option<kernel_off_t>,
+        _flags: std::ffi::c_int,
+        _mem: &mut MemoryManager,
+        _cb_queue: &mut CallbackQueue,
+    ) -> Result<isize, SyscallError> {
+        // PidFds can only be polled, not read.
+        Err(Errno::EINVAL.into())
+    }
+
+    pub fn writev(
+        &mut self,
+        _iovs: &[IoVec],
+        _offset: Option<kernel_off_t>,
+        _flags: std::ffi::c_int,
+        _mem: &mut MemoryManager,
+        _cb_queue: &mut CallbackQueue,
+    ) -> Result<isize, SyscallError> {
+        // PidFds don't support writing.
+        Err(Errno::EINVAL.into())
+    }
+
+    pub fn close(&mut self, cb_queue: &mut CallbackQueue) -> Result<(), SyscallError> {
+        // Set the closed flag and remove the active and readable flags.
+        self.update_state(
+            FileState::CLOSED | FileState::ACTIVE | FileState::READABLE,
+            FileState::CLOSED,
+            FileSignals::empty(),
+            cb_queue,
+        );
+
+        Ok(())
+    }
+
+    pub fn ioctl(
+        &mut self,
+        request: IoctlRequest,
+        _arg_ptr: ForeignPtr<()>,
+        _memory_manager: &mut MemoryManager,
+    ) -> SyscallResult {
+        warn_once_then_debug!("We do not yet handle ioctl request {request:?} on PidFds");
+        Err(Errno::EINVAL.into())
+    }
+
+    pub fn stat(&self) -> Result<linux_api::stat::stat, SyscallError> {
+        warn_once_then_debug!("Not all fields of 'struct stat' are implemented for pidfds");
+        Ok(anon_inode_stat())
+    }
+
+    pub fn add_listener(
+        &mut self,
+        monitoring_state: FileState,
+        monitoring_signals: FileSignals,
+        filter: StateListenerFilter,
+        notify_fn: impl Fn(FileState, FileState, FileSignals, &mut CallbackQueue)
+            + Send
+            + Sync
+            + 'static,
+    ) -> StateListenHandle {
+        self.event_source
+            .add_listener(monitoring_state, monitoring_signals, filter, notify_fn)
+    }
+
+    pub fn add_legacy_listener(&mut self, ptr: HostTreePointer<c::StatusListener>) {
+        self.event_source.add_legacy_listener(ptr);
+    }
+
+    pub fn remove_legacy_listener(&mut self, ptr: *mut c::StatusListener) {
+        self.event_source.remove_legacy_listener(ptr);
+    }
+
+    pub fn state(&self) -> FileState {
+        self.state
+    }
+
+    fn update_state(
+        &mut self,
+        mask: FileState,
+        state: FileState,
+        signals: FileSignals,
+        cb_queue: &mut CallbackQueue,
+    ) {
+        let old_state = self.state;
+
+        // Remove the mask, then copy the masked flags.
+        self.state.remove(mask);
+        self.state.insert(state & mask);
+
+        self.handle_state_change(old_state, signals, cb_queue);
+    }
+
+    fn handle_state_change(
+        &mut self,
+        old_state: FileState,
+        signals: FileSignals,
+        cb_queue: &mut CallbackQueue,
+    ) {
+        let states_changed = self.state ^ old_state;
+
+        // Just return if nothing changed.
+        if states_changed.is_empty() && signals.is_empty() {
+            return;
+        }
+
+        self.event_source
+            .notify_listeners(self.state, states_changed, signals, cb_queue);
+    }
+}
+
+impl StateEventSourceOwner for PidFd {
+    fn with_event_source<R>(&mut self, f: impl FnOnce(&mut StateEventSource) -> R) -> R {
+        f(&mut self.event_source)
+    }
+}
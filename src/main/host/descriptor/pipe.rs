@@ -7,7 +7,9 @@ use linux_api::stat::SFlag;
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
 use crate::cshadow as c;
-use crate::host::descriptor::listener::{StateEventSource, StateListenHandle, StateListenerFilter};
+use crate::host::descriptor::listener::{
+    StateEventSource, StateEventSourceOwner, StateListenHandle, StateListenerFilter,
+};
 use crate::host::descriptor::shared_buf::{
     BufferHandle, BufferSignals, BufferState, ReaderHandle, SharedBuf, WriterHandle,
 };
@@ -468,6 +470,12 @@ impl Pipe {
     }
 }
 
+impl StateEventSourceOwner for Pipe {
+    fn with_event_source<R>(&mut self, f: impl FnOnce(&mut StateEventSource) -> R) -> R {
+        f(&mut self.event_source)
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 enum WriteMode {
     Stream,
@@ -15,6 +15,7 @@
 #include <sys/file.h>
 #include <sys/ioctl.h>
 #include <sys/stat.h>
+#include <sys/sysmacros.h>
 #include <sys/syscall.h>
 #include <sys/time.h>
 #include <sys/types.h>
@@ -32,7 +33,18 @@
 
 #define OSFILE_INVALID -1
 
-const int SHADOW_FLAG_MASK = O_CLOEXEC;
+/* We don't do direct or synchronous I/O on the native file: direct I/O has alignment
+ * requirements that the plugin's buffers (copied through Shadow) don't meet, and isn't supported
+ * by all filesystems, and synchronous writes would only slow down the simulation. These flags
+ * are accepted and reported back to the plugin, but otherwise ignored. */
+const int SHADOW_FLAG_MASK = O_CLOEXEC | O_DIRECT | O_SYNC | O_DSYNC;
+
+/* A directory entry, as returned by getdents64. */
+typedef struct _DirEntry {
+    uint64_t ino;
+    unsigned char type;
+    char* name;
+} DirEntry;
 
 struct _RegularFile {
     /* File is a sub-type of a descriptor. */
@@ -51,6 +63,15 @@ struct _RegularFile {
             mode_t modeAtOpen;
             /* The path of the file when it was opened. */
             char* absPathAtOpen;
+            /* If the file is a directory that we've read with getdents, the
+             * entries of the directory sorted by name, so that the order
+             * doesn't depend on the native filesystem. NULL until the first
+             * getdents call, and after a seek to the start of the directory. */
+            DirEntry* dirEntries;
+            size_t numDirEntries;
+            /* Index into dirEntries of the next entry to return; this is also
+             * the directory offset that we report for lseek and d_off. */
+            size_t nextDirEntry;
         } osfile;
         struct {
             off_t cursor;
@@ -115,10 +136,24 @@ static inline int _regularfile_getOSBackedFD(RegularFile* file) {
 
 static inline bool _fd_isValid(int fd) { return fd >= 0; }
 
+static inline int _regularfile_getOSDirFD(RegularFile* dir);
+
 int regularfile_getOSBackedFD(RegularFile* file) { return _regularfile_getOSBackedFD(file); }
 
+static void _regularfile_freeDirEntries(RegularFile* file) {
+    for (size_t i = 0; i < file->osfile.numDirEntries; i++) {
+        free(file->osfile.dirEntries[i].name);
+    }
+    free(file->osfile.dirEntries);
+    file->osfile.dirEntries = NULL;
+    file->osfile.numDirEntries = 0;
+    file->osfile.nextDirEntry = 0;
+}
+
 static void _regularfile_closeHelper(RegularFile* file) {
     if(file && file->type != FILE_TYPE_IN_MEMORY) {
+        _regularfile_freeDirEntries(file);
+
         if (file && _fd_isValid(file->osfile.fd)) {
             trace("On file %p, closing os-backed file %i", file, _regularfile_getOSBackedFD(file));
 
@@ -136,6 +171,9 @@ static void _regularfile_close(LegacyFile* desc, const Host* host) {
 
     trace("Closing file %p with os-backed file %i", file, _regularfile_getOSBackedFD(file));
 
+    /* Closing the last reference to the open file releases the locks that it owns. */
+    filelocktable_releaseOpenFile(host_getFileLockTable(host), (uintptr_t)file);
+
     /* Make sure we mimic the close on the OS-backed file now. */
     _regularfile_closeHelper(file);
 }
@@ -274,6 +312,19 @@ int _regularfile_initRoInMemoryFile(RegularFile* file, int flags, mode_t mode, s
     return 0;
 }
 
+/* Returns true if the os-backed file is the kernel's random or urandom character device. This
+ * catches paths that reach those devices without matching `utility_isRandomPath`, such as
+ * symlinks or non-canonical paths like "/dev/./urandom". */
+static bool _regularfile_isRandomDevice(int osfd) {
+    struct stat statbuf = {0};
+    if (fstat(osfd, &statbuf) < 0 || !S_ISCHR(statbuf.st_mode)) {
+        return false;
+    }
+    // See Documentation/admin-guide/devices.txt: 1:8 is /dev/random and 1:9 is /dev/urandom.
+    return major(statbuf.st_rdev) == 1 &&
+           (minor(statbuf.st_rdev) == 8 || minor(statbuf.st_rdev) == 9);
+}
+
 int regularfile_openat(RegularFile* file, RegularFile* dir, const char* pathname, int flags,
                        mode_t mode, const char* workingDir) {
     MAGIC_ASSERT(file);
@@ -345,6 +396,12 @@ int regularfile_openat(RegularFile* file, RegularFile* dir, const char* pathname
         return -errcode;
     }
 
+    /* Reads from the random devices must come from the host's deterministic rng, regardless of
+     * the path that was used to reach them. */
+    if (file->type == FILE_TYPE_REGULAR && _regularfile_isRandomDevice(osfd)) {
+        file->type = FILE_TYPE_RANDOM;
+    }
+
     /* Store the create information, which is used if we mmap the file later. */
     file->osfile.fd = osfd;
     file->osfile.absPathAtOpen = abspath;
@@ -365,6 +422,66 @@ int regularfile_open(RegularFile* file, const char* pathname, int flags, mode_t
     return regularfile_openat(file, NULL, pathname, flags, mode, workingDir);
 }
 
+int regularfile_openByHandleAt(RegularFile* file, RegularFile* mountDir,
+                               struct file_handle* handle, int flags, const char* workingDir) {
+    MAGIC_ASSERT(file);
+    utility_debugAssert(file->type == FILE_TYPE_NOTSET && file->osfile.fd == OSFILE_INVALID);
+
+    /* Shadow's working directory isn't the plugin's, so we may need to open the plugin's working
+     * directory to use it as the mount fd. */
+    int osMountFd = _regularfile_getOSDirFD(mountDir);
+    int cwdFd = OSFILE_INVALID;
+    if (osMountFd == AT_FDCWD) {
+        cwdFd = open(workingDir, O_PATH | O_DIRECTORY | O_CLOEXEC);
+        if (cwdFd < 0) {
+            return -errno;
+        }
+        osMountFd = cwdFd;
+    }
+
+    // move any flags that shadow handles from 'flags' to 'shadowFlags'
+    file->shadowFlags = flags & SHADOW_FLAG_MASK;
+    flags &= ~SHADOW_FLAG_MASK;
+
+    // we should always use O_CLOEXEC for files opened in shadow
+    flags |= O_CLOEXEC;
+
+    int osfd = open_by_handle_at(osMountFd, handle, flags);
+    int errcode = errno;
+
+    if (cwdFd != OSFILE_INVALID) {
+        close(cwdFd);
+    }
+
+    if (osfd < 0) {
+        trace("RegularFile %p opening by handle returned %i: %s", file, osfd, strerror(errcode));
+        return -errcode;
+    }
+
+    /* We don't have a path for the file, so look up the path that the OS resolved. */
+    char procPath[64] = {0};
+    snprintf(procPath, sizeof(procPath), "/proc/self/fd/%i", osfd);
+    char linkTarget[PATH_MAX] = {0};
+    ssize_t linkLen = readlink(procPath, linkTarget, sizeof(linkTarget) - 1);
+    char* abspath = strdup(linkLen > 0 ? linkTarget : procPath);
+
+    file->type = (utility_isRandomPath(abspath) || _regularfile_isRandomDevice(osfd))
+                     ? FILE_TYPE_RANDOM
+                     : FILE_TYPE_REGULAR;
+    file->osfile.fd = osfd;
+    file->osfile.absPathAtOpen = abspath;
+    file->osfile.flagsAtOpen = flags;
+    file->osfile.modeAtOpen = 0;
+
+    trace("RegularFile %p opened os-backed file %i by handle at absolute path %s", file,
+          _regularfile_getOSBackedFD(file), file->osfile.absPathAtOpen);
+
+    /* The os-backed file is now ready. */
+    legacyfile_adjustStatus(&file->super, FileState_ACTIVE, TRUE, 0);
+
+    return 0;
+}
+
 static void _regularfile_readRandomBytes(RegularFile* file, const Host* host, void* buf,
                                          size_t numBytes) {
     utility_debugAssert(file->type == FILE_TYPE_RANDOM);
@@ -700,30 +817,55 @@ int regularfile_fallocate(RegularFile* file, int mode, off_t offset, off_t lengt
     return (result < 0) ? -errno : result;
 }
 
-int regularfile_fadvise(RegularFile* file, off_t offset, off_t len, int advice) {
+int regularfile_flock(RegularFile* file, const Host* host, int operation) {
     MAGIC_ASSERT(file);
 
     if (!_fd_isValid(_regularfile_getOSBackedFD(file))) {
         return -EBADF;
     }
 
-    trace("RegularFile %p fadvise os-backed file %i", file, _regularfile_getOSBackedFD(file));
+    trace("RegularFile %p flock os-backed file %i", file, _regularfile_getOSBackedFD(file));
+
+    /* We don't lock the native file, since a conflicting lock would block the whole worker.
+     * Instead the locks live in the host's lock table, keyed by the native file, and the lock
+     * is owned by this open file (which is shared by dup'd descriptors, like in Linux). */
+    struct stat statbuf = {0};
+    if (fstat(_regularfile_getOSBackedFD(file), &statbuf) < 0) {
+        return -errno;
+    }
 
-    int result = posix_fadvise(_regularfile_getOSBackedFD(file), offset, len, advice);
-    return (result < 0) ? -errno : result;
+    FileLockTable* table = host_getFileLockTable(host);
+    uintptr_t owner = (uintptr_t)file;
+
+    switch (operation & ~LOCK_NB) {
+        case LOCK_UN: {
+            filelocktable_flockUnlock(table, statbuf.st_dev, statbuf.st_ino, owner);
+            return 0;
+        }
+        case LOCK_SH:
+        case LOCK_EX: {
+            bool exclusive = (operation & ~LOCK_NB) == LOCK_EX;
+            bool locked = filelocktable_flockTryLock(
+                table, statbuf.st_dev, statbuf.st_ino, owner, exclusive);
+            return locked ? 0 : -EWOULDBLOCK;
+        }
+        default: {
+            return -EINVAL;
+        }
+    }
 }
 
-int regularfile_flock(RegularFile* file, int operation) {
+Futex* regularfile_getLockWakeupFutex(RegularFile* file, const Host* host) {
     MAGIC_ASSERT(file);
 
-    if (!_fd_isValid(_regularfile_getOSBackedFD(file))) {
-        return -EBADF;
+    struct stat statbuf = {0};
+    if (!_fd_isValid(_regularfile_getOSBackedFD(file)) ||
+        fstat(_regularfile_getOSBackedFD(file), &statbuf) < 0) {
+        return NULL;
     }
 
-    trace("RegularFile %p flock os-backed file %i", file, _regularfile_getOSBackedFD(file));
-
-    int result = flock(_regularfile_getOSBackedFD(file), operation);
-    return (result < 0) ? -errno : result;
+    return filelocktable_getWakeupFutex(
+        host_getFileLockTable(host), statbuf.st_dev, statbuf.st_ino);
 }
 
 int regularfile_fsetxattr(RegularFile* file, const char* name, const void* value, size_t size,
@@ -789,9 +931,10 @@ int regularfile_sync_range(RegularFile* file, off64_t offset, off64_t nbytes, un
     trace(
         "RegularFile %p sync_file_range os-backed file %i", file, _regularfile_getOSBackedFD(file));
 
-    int result =
-        sync_file_range(_regularfile_getOSBackedFD(file), offset, nbytes, flags);
-    return (result < 0) ? -errno : result;
+    // Writes to the os-backed file are visible to all simulated processes as soon as they
+    // complete, so there's nothing to wait for. Flushing to disk wouldn't be observable from within
+    // the simulation, so we skip it.
+    return 0;
 }
 
 ssize_t regularfile_readahead(RegularFile* file, off64_t offset, size_t count) {
@@ -803,51 +946,259 @@ ssize_t regularfile_readahead(RegularFile* file, off64_t offset, size_t count) {
 
     trace("RegularFile %p readahead os-backed file %i", file, _regularfile_getOSBackedFD(file));
 
-    ssize_t result = readahead(_regularfile_getOSBackedFD(file), offset, count);
-    return (result < 0) ? -errno : result;
+    /* readahead(2): "EBADF fd is not a valid file descriptor or is not open for reading." */
+    int flags = regularfile_getFlagsAtOpen(file);
+    int accmode = flags & O_ACCMODE;
+    if ((flags & O_PATH) || (accmode != O_RDONLY && accmode != O_RDWR)) {
+        return -EBADF;
+    }
+
+    /* readahead(2): "EINVAL fd does not refer to a file type to which readahead() can be
+     * applied." */
+    struct stat statbuf = {0};
+    if (fstat(_regularfile_getOSBackedFD(file), &statbuf) < 0) {
+        return -errno;
+    }
+    if (!S_ISREG(statbuf.st_mode)) {
+        return -EINVAL;
+    }
+
+    /* Shadow doesn't keep a page cache, so there's nothing to read ahead into. */
+    return 0;
+}
+
+/* Seek within an in-memory file. The content has no holes, so SEEK_DATA and
+ * SEEK_HOLE behave as they do for a fully-allocated native file. */
+static off_t _regularfile_lseekInMemory(RegularFile* file, off_t offset, int whence) {
+    off_t len = file->inMemoryFile.contentLen;
+    off_t newOffset;
+
+    switch (whence) {
+        case SEEK_SET: newOffset = offset; break;
+        case SEEK_CUR: newOffset = file->inMemoryFile.cursor + offset; break;
+        case SEEK_END: newOffset = len + offset; break;
+        case SEEK_DATA:
+            if (offset < 0 || offset >= len) {
+                return -ENXIO;
+            }
+            newOffset = offset;
+            break;
+        case SEEK_HOLE:
+            if (offset < 0 || offset >= len) {
+                return -ENXIO;
+            }
+            /* There's an implicit hole at the end of the file. */
+            newOffset = len;
+            break;
+        default: return -EINVAL;
+    }
+
+    if (newOffset < 0) {
+        return -EINVAL;
+    }
+
+    file->inMemoryFile.cursor = newOffset;
+    return newOffset;
 }
 
 off_t regularfile_lseek(RegularFile* file, off_t offset, int whence) {
     MAGIC_ASSERT(file);
 
+    if (file->type == FILE_TYPE_IN_MEMORY) {
+        return _regularfile_lseekInMemory(file, offset, whence);
+    }
+
     if (!_fd_isValid(_regularfile_getOSBackedFD(file))) {
         return -EBADF;
     }
 
     trace("RegularFile %p lseek os-backed file %i", file, _regularfile_getOSBackedFD(file));
 
+    if (file->osfile.dirEntries != NULL) {
+        /* We've read the directory with getdents, so offsets are indexes into
+         * our sorted entries rather than native directory offsets. */
+        if (whence == SEEK_CUR && offset == 0) {
+            return file->osfile.nextDirEntry;
+        } else if (whence != SEEK_SET || offset < 0 ||
+                   (size_t)offset > file->osfile.numDirEntries) {
+            return -EINVAL;
+        } else if (offset == 0) {
+            /* Like rewinddir, re-read the directory on the next getdents to
+             * pick up any changes. */
+            _regularfile_freeDirEntries(file);
+        } else {
+            file->osfile.nextDirEntry = offset;
+            return offset;
+        }
+    }
+
+    /* The native filesystem knows where the holes are, so SEEK_DATA and SEEK_HOLE
+     * (including their ENXIO at or past the end of the file) are handled natively. */
     ssize_t result = lseek(_regularfile_getOSBackedFD(file), offset, whence);
     return (result < 0) ? -errno : result;
 }
 
-int regularfile_getdents(RegularFile* file, struct linux_dirent* dirp, unsigned int count) {
-    MAGIC_ASSERT(file);
+/* The native layout of the records returned by getdents64. */
+struct _linux_dirent64 {
+    uint64_t d_ino;
+    int64_t d_off;
+    unsigned short d_reclen;
+    unsigned char d_type;
+    char d_name[];
+};
 
-    if (!_fd_isValid(_regularfile_getOSBackedFD(file))) {
-        return -EBADF;
+/* The native layout of the records returned by getdents. The type is stored in
+ * the last byte of the record, after the name's null byte and any padding. */
+struct _linux_dirent {
+    unsigned long d_ino;
+    unsigned long d_off;
+    unsigned short d_reclen;
+    char d_name[];
+};
+
+static int _regularfile_compareDirEntries(const void* a, const void* b) {
+    return strcmp(((const DirEntry*)a)->name, ((const DirEntry*)b)->name);
+}
+
+/* Reads all entries of the os-backed directory, and sorts them by name. */
+static int _regularfile_loadDirEntries(RegularFile* file) {
+    utility_debugAssert(file->osfile.dirEntries == NULL);
+
+    int osfd = _regularfile_getOSBackedFD(file);
+    if (lseek(osfd, 0, SEEK_SET) < 0) {
+        return -errno;
     }
 
-    trace("RegularFile %p getdents os-backed file %i", file, _regularfile_getOSBackedFD(file));
+    DirEntry* entries = NULL;
+    size_t numEntries = 0;
+    size_t capacity = 0;
 
-    // getdents is not available for a direct call
-    int result =
-        (int)syscall(SYS_getdents, _regularfile_getOSBackedFD(file), dirp, count);
-    return (result < 0) ? -errno : result;
+    int result = 0;
+    char buf[32768];
+    while (result == 0) {
+        long nread = syscall(SYS_getdents64, osfd, buf, sizeof(buf));
+        if (nread < 0) {
+            result = -errno;
+            break;
+        } else if (nread == 0) {
+            break;
+        }
+
+        for (long pos = 0; pos < nread;) {
+            struct _linux_dirent64* d = (struct _linux_dirent64*)(buf + pos);
+            if (numEntries == capacity) {
+                size_t newCapacity = capacity ? capacity * 2 : 16;
+                DirEntry* newEntries = realloc(entries, newCapacity * sizeof(*entries));
+                if (newEntries == NULL) {
+                    result = -ENOMEM;
+                    break;
+                }
+                entries = newEntries;
+                capacity = newCapacity;
+            }
+            char* name = strdup(d->d_name);
+            if (name == NULL) {
+                result = -ENOMEM;
+                break;
+            }
+            entries[numEntries++] = (DirEntry){
+                .ino = d->d_ino,
+                .type = d->d_type,
+                .name = name,
+            };
+            pos += d->d_reclen;
+        }
+    }
+
+    if (result != 0) {
+        for (size_t i = 0; i < numEntries; i++) {
+            free(entries[i].name);
+        }
+        free(entries);
+        return result;
+    }
+
+    qsort(entries, numEntries, sizeof(*entries), _regularfile_compareDirEntries);
+
+    file->osfile.dirEntries = entries;
+    file->osfile.numDirEntries = numEntries;
+    file->osfile.nextDirEntry = 0;
+    return 0;
 }
 
-int regularfile_getdents64(RegularFile* file, struct linux_dirent64* dirp,
-                    unsigned int count) {
+/* Copies as many sorted directory entries as fit in `count` bytes into `dirp`,
+ * using the getdents64 record layout if `is64`, or the getdents one otherwise. */
+static int _regularfile_getdentsHelper(RegularFile* file, void* dirp, unsigned int count,
+                                       bool is64) {
     MAGIC_ASSERT(file);
 
     if (!_fd_isValid(_regularfile_getOSBackedFD(file))) {
         return -EBADF;
     }
 
-    trace("RegularFile %p getdents64 os-backed file %i", file, _regularfile_getOSBackedFD(file));
+    trace("RegularFile %p %s os-backed file %i", file, is64 ? "getdents64" : "getdents",
+          _regularfile_getOSBackedFD(file));
 
-    int result =
-        (int)syscall(SYS_getdents64, _regularfile_getOSBackedFD(file), dirp, count);
-    return (result < 0) ? -errno : result;
+    if (file->osfile.dirEntries == NULL) {
+        int result = _regularfile_loadDirEntries(file);
+        if (result < 0) {
+            return result;
+        }
+    }
+
+    size_t written = 0;
+    while (file->osfile.nextDirEntry < file->osfile.numDirEntries) {
+        const DirEntry* entry = &file->osfile.dirEntries[file->osfile.nextDirEntry];
+        size_t nameLen = strlen(entry->name) + 1;
+        size_t headerLen = is64 ? offsetof(struct _linux_dirent64, d_name)
+                                : offsetof(struct _linux_dirent, d_name);
+        /* The getdents record has an extra byte for the type after the name. */
+        size_t reclen = headerLen + nameLen + (is64 ? 0 : 1);
+        /* Records are padded to 8-byte alignment. */
+        reclen = (reclen + 7) & ~(size_t)7;
+
+        if (written + reclen > count) {
+            if (written == 0) {
+                /* The result buffer is too small for even one entry. */
+                return -EINVAL;
+            }
+            break;
+        }
+
+        char* record = (char*)dirp + written;
+        memset(record, 0, reclen);
+        /* The offset of the next entry, which can be passed to lseek. */
+        uint64_t nextOffset = file->osfile.nextDirEntry + 1;
+        if (is64) {
+            struct _linux_dirent64* d = (struct _linux_dirent64*)record;
+            d->d_ino = entry->ino;
+            d->d_off = (int64_t)nextOffset;
+            d->d_reclen = reclen;
+            d->d_type = entry->type;
+            memcpy(d->d_name, entry->name, nameLen);
+        } else {
+            struct _linux_dirent* d = (struct _linux_dirent*)record;
+            d->d_ino = entry->ino;
+            d->d_off = nextOffset;
+            d->d_reclen = reclen;
+            memcpy(d->d_name, entry->name, nameLen);
+            record[reclen - 1] = entry->type;
+        }
+
+        written += reclen;
+        file->osfile.nextDirEntry++;
+    }
+
+    return written;
+}
+
+int regularfile_getdents(RegularFile* file, struct linux_dirent* dirp, unsigned int count) {
+    return _regularfile_getdentsHelper(file, dirp, count, false);
+}
+
+int regularfile_getdents64(RegularFile* file, struct linux_dirent64* dirp,
+                    unsigned int count) {
+    return _regularfile_getdentsHelper(file, dirp, count, true);
 }
 
 int regularfile_ioctl(RegularFile* file, unsigned long request, void* arg) {
@@ -872,6 +1223,17 @@ int regularfile_fcntl(RegularFile* file, unsigned long command, void* arg) {
         return -EBADF;
     }
 
+    /* The flags that we track for the plugin and that can be changed with F_SETFL. */
+    const int shadowStatusFlags = O_DIRECT;
+
+    if (command == F_SETFL) {
+        intptr_t arg_int = (intptr_t)arg;
+        file->shadowFlags =
+            (file->shadowFlags & ~shadowStatusFlags) | (arg_int & shadowStatusFlags);
+        arg_int &= ~SHADOW_FLAG_MASK;
+        arg = (void*)arg_int;
+    }
+
     if (command == F_SETFD) {
         intptr_t arg_int = (intptr_t)arg;
         // if the arg contains FD_CLOEXEC
@@ -887,6 +1249,10 @@ int regularfile_fcntl(RegularFile* file, unsigned long command, void* arg) {
 
     int result = fcntl(_regularfile_getOSBackedFD(file), command, arg);
 
+    if (result >= 0 && command == F_GETFL) {
+        result |= file->shadowFlags & ~O_CLOEXEC;
+    }
+
     if (result >= 0 && command == F_GETFD) {
         // if the file should have FD_CLOEXEC
         if (file->shadowFlags & O_CLOEXEC) {
@@ -1222,7 +1588,72 @@ int regularfile_renameat2(RegularFile* oldDir, const char* oldPath, RegularFile*
     return (result < 0) ? -errno : result;
 }
 
+int regularfile_nameToHandleAt(RegularFile* dir, const char* pathname, struct file_handle* handle,
+                               int* mountId, int flags, const char* workingDir) {
+    int osFd = _regularfile_getOSDirFD(dir);
+    const char* pathnameTmp = pathname;
+
+    trace("RegularFile %p name_to_handle_at os-backed file %i, flags %d", dir, osFd, flags);
+
+    if (osFd == AT_FDCWD) {
+        osFd = -1;
+        pathnameTmp = _regularfile_getAbsolutePath(NULL, pathname, workingDir);
+    }
+
+    int result = name_to_handle_at(osFd, pathnameTmp, handle, mountId, flags);
+
+    if (pathnameTmp != pathname) {
+        free((char*)pathnameTmp);
+    }
+
+    return (result < 0) ? -errno : result;
+}
+
 #ifdef SYS_statx
+/* Restrict the statx results to the fields the caller asked for. The kernel may fill in
+ * and claim more than was requested, which would make the results depend on the host
+ * filesystem rather than on what the plugin asked for. */
+static void _regularfile_statxApplyMask(struct statx* statxbuf, unsigned int mask) {
+    statxbuf->stx_mask &= mask;
+
+    if (!(mask & STATX_TYPE)) {
+        statxbuf->stx_mode &= ~S_IFMT;
+    }
+    if (!(mask & STATX_MODE)) {
+        statxbuf->stx_mode &= S_IFMT;
+    }
+    if (!(mask & STATX_NLINK)) {
+        statxbuf->stx_nlink = 0;
+    }
+    if (!(mask & STATX_UID)) {
+        statxbuf->stx_uid = 0;
+    }
+    if (!(mask & STATX_GID)) {
+        statxbuf->stx_gid = 0;
+    }
+    if (!(mask & STATX_ATIME)) {
+        memset(&statxbuf->stx_atime, 0, sizeof(statxbuf->stx_atime));
+    }
+    if (!(mask & STATX_MTIME)) {
+        memset(&statxbuf->stx_mtime, 0, sizeof(statxbuf->stx_mtime));
+    }
+    if (!(mask & STATX_CTIME)) {
+        memset(&statxbuf->stx_ctime, 0, sizeof(statxbuf->stx_ctime));
+    }
+    if (!(mask & STATX_INO)) {
+        statxbuf->stx_ino = 0;
+    }
+    if (!(mask & STATX_SIZE)) {
+        statxbuf->stx_size = 0;
+    }
+    if (!(mask & STATX_BLOCKS)) {
+        statxbuf->stx_blocks = 0;
+    }
+    if (!(mask & STATX_BTIME)) {
+        memset(&statxbuf->stx_btime, 0, sizeof(statxbuf->stx_btime));
+    }
+}
+
 int regularfile_statx(RegularFile* dir, const char* pathname, int flags, unsigned int mask,
                       struct statx* statxbuf, const char* workingDir) {
     int osFd = _regularfile_getOSDirFD(dir);
@@ -1241,6 +1672,11 @@ int regularfile_statx(RegularFile* dir, const char* pathname, int flags, unsigne
         free((char*)pathnameTmp);
     }
 
-    return (result < 0) ? -errno : result;
+    if (result < 0) {
+        return -errno;
+    }
+
+    _regularfile_statxApplyMask(statxbuf, mask);
+    return result;
 }
 #endif
@@ -6,6 +6,7 @@
 #ifndef SRC_MAIN_HOST_DESCRIPTOR_FILE_H_
 #define SRC_MAIN_HOST_DESCRIPTOR_FILE_H_
 
+#include <fcntl.h>
 #include <poll.h>
 #include <stddef.h>
 #include <sys/stat.h>
@@ -17,6 +18,7 @@
 #include <unistd.h>
 
 #include "main/core/definitions.h"
+#include "main/host/futex.h"
 #include "main/host/syscall/kernel_types.h"
 
 /* Mask of all O file flags that we don't pass to the native fd, but instead
@@ -58,6 +60,10 @@ int regularfile_open(RegularFile* file, const char* pathname, int flags, mode_t
                      const char* workingDir);
 int regularfile_openat(RegularFile* file, RegularFile* dir, const char* pathname, int flags,
                        mode_t mode, const char* workingDir);
+/* Open the file referred to by the handle from name_to_handle_at(). The mount dir RegularFile* may
+ * be null, in which case the current working directory will be used instead. */
+int regularfile_openByHandleAt(RegularFile* file, RegularFile* mountDir,
+                               struct file_handle* handle, int flags, const char* workingDir);
 
 // ************************
 // Accessors
@@ -106,8 +112,12 @@ int regularfile_fchown(RegularFile* file, uid_t owner, gid_t group);
 int regularfile_fchmod(RegularFile* file, mode_t mode);
 int regularfile_ftruncate(RegularFile* file, off_t length);
 int regularfile_fallocate(RegularFile* file, int mode, off_t offset, off_t length);
-int regularfile_fadvise(RegularFile* file, off_t offset, off_t len, int advice);
-int regularfile_flock(RegularFile* file, int operation);
+/* The lock is held in the host's lock table. Never blocks; returns -EWOULDBLOCK if the lock
+ * conflicts with a lock held through another open file. */
+int regularfile_flock(RegularFile* file, const Host* host, int operation);
+/* A futex that is woken when a lock on the file is next released. This is a borrowed
+ * reference. */
+Futex* regularfile_getLockWakeupFutex(RegularFile* file, const Host* host);
 int regularfile_fsetxattr(RegularFile* file, const char* name, const void* value, size_t size,
                           int flags);
 ssize_t regularfile_fgetxattr(RegularFile* file, const char* name, void* value, size_t size);
@@ -151,6 +161,8 @@ ssize_t regularfile_readlinkat(RegularFile* dir, const char* pathname, char* buf
                                const char* workingDir);
 int regularfile_renameat2(RegularFile* olddir, const char* oldpath, RegularFile* newdir,
                           const char* newpath, unsigned int flags, const char* workingDir);
+int regularfile_nameToHandleAt(RegularFile* dir, const char* pathname, struct file_handle* handle,
+                               int* mountId, int flags, const char* workingDir);
 #ifdef SYS_statx
 int regularfile_statx(RegularFile* dir, const char* pathname, int flags, unsigned int mask,
                       struct statx* statxbuf, const char* workingDir);
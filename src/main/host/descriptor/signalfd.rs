@@ -0,0 +1,360 @@
+use std::io::Write;
+
+use linux_api::errno::Errno;
+use linux_api::ioctls::IoctlRequest;
+use linux_api::posix_types::kernel_off_t;
+use linux_api::signal::{siginfo_t, sigset_t, sigval, SigInfoDetails, Signal};
+use shadow_shim_helper_rs::shim_shmem::take_pending_signal_in;
+use shadow_shim_helper_rs::syscall_types::ForeignPtr;
+
+use crate::core::worker::Worker;
+use crate::cshadow as c;
+use crate::host::descriptor::listener::{
+    StateEventSource, StateEventSourceOwner, StateListenHandle, StateListenerFilter,
+};
+use crate::host::descriptor::{anon_inode_stat, FileMode, FileSignals, FileState, FileStatus};
+use crate::host::memory_manager::MemoryManager;
+use crate::host::syscall::io::{IoVec, IoVecWriter};
+use crate::host::syscall::types::{SyscallError, SyscallResult};
+use crate::utility::callback_queue::CallbackQueue;
+use crate::utility::HostTreePointer;
+
+pub struct SignalFd {
+    mask: sigset_t,
+    event_source: StateEventSource,
+    status: FileStatus,
+    state: FileState,
+    // Should only be used by `OpenFile` to make sure there is only ever one `OpenFile` instance for
+    // this file,
+    has_open_file: bool,
+}
+
+impl SignalFd {
+    /// Creates a new [`SignalFd`] that accepts the signals in `mask`, enabling support for
+    /// signalfd(2). The caller should call [`SignalFd::refresh_pending`] with the signals that are
+    /// already pending so that the initial readable state is correct.
+    pub fn new(mask: sigset_t, status: FileStatus) -> Self {
+        Self {
+            mask: Self::sanitize_mask(mask),
+            event_source: StateEventSource::new(),
+            state: FileState::ACTIVE,
+            status,
+            has_open_file: false,
+        }
+    }
+
+    /// signalfd(2): "It is not possible to receive SIGKILL or SIGSTOP signals via a signalfd file
+    /// descriptor; these signals are silently ignored if specified in mask."
+    fn sanitize_mask(mut mask: sigset_t) -> sigset_t {
+        mask.del(Signal::SIGKILL);
+        mask.del(Signal::SIGSTOP);
+        mask
+    }
+
+    pub fn mask(&self) -> sigset_t {
+        self.mask
+    }
+
+    /// Replace the set of accepted signals. `pending` should be the signals currently pending for
+    /// the calling thread and its process.
+    pub fn set_mask(&mut self, mask: sigset_t, pending: sigset_t, cb_queue: &mut CallbackQueue) {
+        self.mask = Self::sanitize_mask(mask);
+        self.refresh_pending(pending, cb_queue);
+    }
+
+    /// Update the readable state given the set of currently pending signals.
+    pub fn refresh_pending(&mut self, pending: sigset_t, cb_queue: &mut CallbackQueue) {
+        self.refresh_state(!(pending & self.mask).is_empty(), cb_queue);
+    }
+
+    /// Called when `signal` becomes pending for a process that this [`SignalFd`] was created by.
+    pub fn signal_pending(&mut self, signal: Signal, cb_queue: &mut CallbackQueue) {
+        if self.mask.has(signal) {
+            self.refresh_state(true, cb_queue);
+        }
+    }
+
+    pub fn status(&self) -> FileStatus {
+        self.status
+    }
+
+    pub fn set_status(&mut self, status: FileStatus) {
+        self.status = status;
+    }
+
+    pub fn mode(&self) -> FileMode {
+        FileMode::READ
+    }
+
+    pub fn has_open_file(&self) -> bool {
+        self.has_open_file
+    }
+
+    pub fn supports_sa_restart(&self) -> bool {
+        false
+    }
+
+    pub fn set_has_open_file(&mut self, val: bool) {
+        self.has_open_file = val;
+    }
+
+    pub fn readv(
+        &mut self,
+        iovs: &[IoVec],
+        offset: Option<kernel_off_t>,
+        _flags: std::ffi::c_int,
+        mem: &mut MemoryManager,
+        cb_queue: &mut CallbackQueue,
+    ) -> Result<isize, SyscallError> {
+        // SignalFds don't support seeking
+        if offset.is_some() {
+            return Err(Errno::ESPIPE.into());
+        }
+
+        const RECORD_SIZE: usize = std::mem::size_of::<libc::signalfd_siginfo>();
+
+        let len: usize = iovs.iter().map(|x| x.len).sum();
+
+        // signalfd(2): "The buffer given to read(2) must be at least sizeof(struct
+        // signalfd_siginfo) bytes."
+        if len < RECORD_SIZE {
+            log::trace!("Reading from SignalFd requires a buffer of at least {RECORD_SIZE} bytes");
+            return Err(Errno::EINVAL.into());
+        }
+
+        // signalfd(2): "The return value of the read(2) is the total number of bytes read, which
+        // is a multiple of sizeof(struct signalfd_siginfo)."
+        let mut writer = IoVecWriter::new(iovs, mem);
+        let mut bytes_written = 0;
+        while bytes_written + RECORD_SIZE <= len {
+            let Some((_signal, info)) = Self::take_pending_signal(self.mask) else {
+                break;
+            };
+            let record = signalfd_siginfo_from(&info);
+            // SAFETY: `signalfd_siginfo` is a plain C struct with no padding-dependent invariants,
+            // and `record` was fully initialized from a zeroed value.
+            let bytes = unsafe {
+                std::slice::from_raw_parts(std::ptr::from_ref(&record) as *const u8, RECORD_SIZE)
+            };
+            writer.write_all(bytes)?;
+            bytes_written += RECORD_SIZE;
+        }
+
+        // We may have consumed all of the matching signals.
+        let pending = Self::pending_signals().unwrap_or(sigset_t::EMPTY);
+        self.refresh_pending(pending, cb_queue);
+
+        if bytes_written == 0 {
+            log::trace!("No signals in the SignalFd's mask are pending");
+            return Err(Errno::EWOULDBLOCK.into());
+        }
+
+        Ok(bytes_written.try_into().unwrap())
+    }
+
+    /// Take the lowest signal in `mask` that is pending for the active thread or its process.
+    fn take_pending_signal(mask: sigset_t) -> Option<(Signal, siginfo_t)> {
+        Worker::with_active_host(|host| {
+            Worker::with_active_process(|process| {
+                Worker::with_active_thread(|thread| {
+                    let host_shmem = host.shim_shmem_lock_borrow().unwrap();
+                    take_pending_signal_in(&host_shmem, &process.shmem(), thread.shmem(), mask)
+                })
+            })
+        })
+        .flatten()
+        .flatten()
+        .flatten()
+    }
+
+    /// The signals that are pending for the active thread or its process.
+    fn pending_signals() -> Option<sigset_t> {
+        Worker::with_active_host(|host| {
+            Worker::with_active_process(|process| {
+                Worker::with_active_thread(|thread| {
+                    let host_shmem = host.shim_shmem_lock_borrow().unwrap();
+                    let process_shmem = process.shmem();
+                    let process_pending = process_shmem
+                        .protected
+                        .borrow(&host_shmem.root)
+                        .pending_signals;
+                    let thread_pending = thread
+                        .shmem()
+                        .protected
+                        .borrow(&host_shmem.root)
+                        .pending_signals;
+                    process_pending | thread_pending
+                })
+            })
+        })
+        .flatten()
+        .flatten()
+    }
+
+    pub fn writev(
+        &mut self,
+        _iovs: &[IoVec],
+        _offset: Option<kernel_off_t>,
+        _flags: std::ffi::c_int,
+        _mem: &mut MemoryManager,
+        _cb_queue: &mut CallbackQueue,
+    ) -> Result<isize, SyscallError> {
+        // SignalFds don't support writing.
+        Err(Errno::EINVAL.into())
+    }
+
+    pub fn close(&mut self, cb_queue: &mut CallbackQueue) -> Result<(), SyscallError> {
+        // Set the closed flag and remove the active and readable flags.
+        self.update_state(
+            FileState::CLOSED | FileState::ACTIVE | FileState::READABLE,
+            FileState::CLOSED,
+            FileSignals::empty(),
+            cb_queue,
+        );
+
+        Ok(())
+    }
+
+    pub fn ioctl(
+        &mut self,
+        request: IoctlRequest,
+        _arg_ptr: ForeignPtr<()>,
+        _memory_manager: &mut MemoryManager,
+    ) -> SyscallResult {
+        warn_once_then_debug!("We do not yet handle ioctl request {request:?} on SignalFds");
+        Err(Errno::EINVAL.into())
+    }
+
+    pub fn stat(&self) -> Result<linux_api::stat::stat, SyscallError> {
+        warn_once_then_debug!("Not all fields of 'struct stat' are implemented for signalfds");
+        Ok(anon_inode_stat())
+    }
+
+    pub fn add_listener(
+        &mut self,
+        monitoring_state: FileState,
+        monitoring_signals: FileSignals,
+        filter: StateListenerFilter,
+        notify_fn: impl Fn(FileState, FileState, FileSignals, &mut CallbackQueue)
+            + Send
+            + Sync
+            + 'static,
+    ) -> StateListenHandle {
+        self.event_source
+            .add_listener(monitoring_state, monitoring_signals, filter, notify_fn)
+    }
+
+    pub fn add_legacy_listener(&mut self, ptr: HostTreePointer<c::StatusListener>) {
+        self.event_source.add_legacy_listener(ptr);
+    }
+
+    pub fn remove_legacy_listener(&mut self, ptr: *mut c::StatusListener) {
+        self.event_source.remove_legacy_listener(ptr);
+    }
+
+    pub fn state(&self) -> FileState {
+        self.state
+    }
+
+    fn refresh_state(&mut self, readable: bool, cb_queue: &mut CallbackQueue) {
+        if self.state.contains(FileState::CLOSED) {
+            return;
+        }
+
+        let mut new_state = FileState::empty();
+        new_state.set(FileState::READABLE, readable);
+
+        self.update_state(
+            FileState::READABLE,
+            new_state,
+            FileSignals::empty(),
+            cb_queue,
+        );
+    }
+
+    fn update_state(
+        &mut self,
+        mask: FileState,
+        state: FileState,
+        signals: FileSignals,
+        cb_queue: &mut CallbackQueue,
+    ) {
+        let old_state = self.state;
+
+        // Remove the mask, then copy the masked flags.
+        self.state.remove(mask);
+        self.state.insert(state & mask);
+
+        self.handle_state_change(old_state, signals, cb_queue);
+    }
+
+    fn handle_state_change(
+        &mut self,
+        old_state: FileState,
+        signals: FileSignals,
+        cb_queue: &mut CallbackQueue,
+    ) {
+        let states_changed = self.state ^ old_state;
+
+        // Just return if nothing changed.
+        if states_changed.is_empty() && signals.is_empty() {
+            return;
+        }
+
+        self.event_source
+            .notify_listeners(self.state, states_changed, signals, cb_queue);
+    }
+}
+
+impl StateEventSourceOwner for SignalFd {
+    fn with_event_source<R>(&mut self, f: impl FnOnce(&mut StateEventSource) -> R) -> R {
+        f(&mut self.event_source)
+    }
+}
+
+/// Convert a pending signal's `siginfo_t` into the record format returned by reading a signalfd.
+fn signalfd_siginfo_from(info: &siginfo_t) -> libc::signalfd_siginfo {
+    // SAFETY: `signalfd_siginfo` is a plain C struct, for which all-zeroes is a valid value.
+    let mut ssi: libc::signalfd_siginfo = unsafe { std::mem::zeroed() };
+
+    ssi.ssi_signo = info.signal().map(i32::from).unwrap_or(0) as u32;
+    ssi.ssi_errno = info.errno();
+    ssi.ssi_code = info.code_raw();
+
+    // SAFETY: We only copy out plain integer fields; we never dereference pointers.
+    match unsafe { info.details() } {
+        Some(SigInfoDetails::Kill(kill)) => {
+            ssi.ssi_pid = kill.l_pid as u32;
+            ssi.ssi_uid = kill.l_uid;
+        }
+        Some(SigInfoDetails::Rt(rt)) => {
+            ssi.ssi_pid = rt.l_pid as u32;
+            ssi.ssi_uid = rt.l_uid;
+            copy_sigval(&mut ssi, &rt.l_sigval);
+        }
+        Some(SigInfoDetails::SigChld(chld)) => {
+            ssi.ssi_pid = chld.l_pid as u32;
+            ssi.ssi_uid = chld.l_uid;
+            ssi.ssi_status = chld.l_status;
+            ssi.ssi_utime = chld.l_utime as u64;
+            ssi.ssi_stime = chld.l_stime as u64;
+        }
+        Some(SigInfoDetails::Timer(timer)) => {
+            ssi.ssi_tid = timer.l_tid as u32;
+            ssi.ssi_overrun = timer.l_overrun as u32;
+            copy_sigval(&mut ssi, &timer.l_sigval);
+        }
+        _ => {}
+    }
+
+    ssi
+}
+
+/// Like Linux, copy the signal's value into both `ssi_int` and `ssi_ptr`, since we don't know which
+/// member of the union the sender used.
+fn copy_sigval(ssi: &mut libc::signalfd_siginfo, sigval: &sigval) {
+    // SAFETY: Both members are plain integers (we never dereference the pointer), and the union is
+    // the size of the pointer so reading either is initialized memory.
+    ssi.ssi_int = unsafe { sigval.sival_int };
+    ssi.ssi_ptr = unsafe { sigval.sival_ptr } as u64;
+}
@@ -24,8 +24,18 @@ impl NamespaceEntry {
     }
 }
 
+/// Identifies the socket file that a pathname unix socket is bound to.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct SocketInode {
+    pub dev: u64,
+    pub ino: u64,
+}
+
 pub struct AbstractUnixNamespace {
     address_map: HashMap<UnixSocketType, HashMap<Vec<u8>, NamespaceEntry>>,
+    /// Sockets bound to pathname addresses. These are keyed by the socket file rather than the
+    /// path so that renaming or unlinking the file behaves as it would on Linux.
+    inode_map: HashMap<UnixSocketType, HashMap<SocketInode, NamespaceEntry>>,
 }
 
 impl AbstractUnixNamespace {
@@ -33,14 +43,18 @@ impl AbstractUnixNamespace {
         let mut rv = Self {
             // initializes an empty hash map for each unix socket type
             address_map: HashMap::new(),
+            inode_map: HashMap::new(),
         };
 
         // the namespace code will assume that there is an entry for each socket type
-        rv.address_map
-            .insert(UnixSocketType::Stream, HashMap::new());
-        rv.address_map.insert(UnixSocketType::Dgram, HashMap::new());
-        rv.address_map
-            .insert(UnixSocketType::SeqPacket, HashMap::new());
+        for sock_type in [
+            UnixSocketType::Stream,
+            UnixSocketType::Dgram,
+            UnixSocketType::SeqPacket,
+        ] {
+            rv.address_map.insert(sock_type, HashMap::new());
+            rv.inode_map.insert(sock_type, HashMap::new());
+        }
 
         rv
     }
@@ -61,6 +75,63 @@ impl AbstractUnixNamespace {
             .map(|x| x.socket.upgrade().unwrap())
     }
 
+    /// Look up the socket bound to the socket file `inode`.
+    pub fn lookup_inode(
+        &self,
+        sock_type: UnixSocketType,
+        inode: SocketInode,
+    ) -> Option<Arc<AtomicRefCell<UnixSocket>>> {
+        // see `lookup()` for why the unwrap() is fine
+        self.inode_map
+            .get(&sock_type)
+            .unwrap()
+            .get(&inode)
+            .map(|x| x.socket.upgrade().unwrap())
+    }
+
+    /// Bind the socket to the socket file `inode`, which the caller has just created.
+    pub fn bind_inode(
+        ns_arc: &Arc<AtomicRefCell<Self>>,
+        sock_type: UnixSocketType,
+        inode: SocketInode,
+        socket: &Arc<AtomicRefCell<UnixSocket>>,
+        socket_event_source: &mut StateEventSource,
+    ) {
+        let mut ns = ns_arc.borrow_mut();
+
+        // when the socket closes, remove this entry from the namespace
+        let handle =
+            Self::on_socket_close(Arc::downgrade(ns_arc), socket_event_source, move |ns| {
+                assert!(ns.unbind_inode(sock_type, inode).is_ok());
+            });
+
+        // The file was just created, so any existing entry is for an open socket whose file was
+        // deleted and whose inode number has since been reused. That socket is no longer reachable
+        // by path, so replace it. Dropping the old entry also drops its close listener.
+        ns.inode_map
+            .get_mut(&sock_type)
+            .unwrap()
+            .insert(inode, NamespaceEntry::new(Arc::downgrade(socket), handle));
+    }
+
+    pub fn unbind_inode(
+        &mut self,
+        sock_type: UnixSocketType,
+        inode: SocketInode,
+    ) -> Result<(), BindError> {
+        if self
+            .inode_map
+            .get_mut(&sock_type)
+            .unwrap()
+            .remove(&inode)
+            .is_none()
+        {
+            return Err(BindError::NameNotFound);
+        }
+
+        Ok(())
+    }
+
     pub fn bind(
         ns_arc: &Arc<AtomicRefCell<Self>>,
         sock_type: UnixSocketType,
@@ -12,9 +12,13 @@ use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
 use crate::core::worker::Worker;
 use crate::cshadow as c;
-use crate::host::descriptor::listener::{StateListenHandle, StateListenerFilter};
+use crate::host::descriptor::listener::{
+    StateEventSource, StateEventSourceOwner, StateListenHandle, StateListenerFilter,
+};
 use crate::host::descriptor::socket::inet::{self, InetSocket};
-use crate::host::descriptor::socket::{RecvmsgArgs, RecvmsgReturn, SendmsgArgs, Socket};
+use crate::host::descriptor::socket::{
+    socket_stat, RecvmsgArgs, RecvmsgReturn, SendmsgArgs, Socket,
+};
 use crate::host::descriptor::{
     CompatFile, File, FileMode, FileSignals, FileState, FileStatus, OpenFile, SyscallResult,
 };
@@ -623,8 +627,8 @@ impl LegacyTcpSocket {
     }
 
     pub fn stat(&self) -> Result<linux_api::stat::stat, SyscallError> {
-        warn_once_then_debug!("We do not yet handle stat calls on tcp sockets");
-        Err(Errno::EINVAL.into())
+        warn_once_then_debug!("Not all fields of 'struct stat' are implemented for tcp sockets");
+        Ok(socket_stat())
     }
 
     pub fn listen(
@@ -1054,15 +1058,8 @@ impl LegacyTcpSocket {
                 Ok(bytes_written as libc::socklen_t)
             }
             (libc::SOL_SOCKET, libc::SO_ERROR) => {
-                // return error for failed connect() attempts
-                let conn_err = unsafe { c::tcp_getConnectionError(self.as_legacy_tcp()) };
-
-                let error = if conn_err == -libc::ECONNRESET || conn_err == -libc::ECONNREFUSED {
-                    // result is a positive errcode
-                    -conn_err
-                } else {
-                    0
-                };
+                // return error for failed connect() attempts; the error is cleared once returned
+                let error: libc::c_int = unsafe { c::tcp_takePendingError(self.as_legacy_tcp()) };
 
                 let optval_ptr = optval_ptr.cast::<libc::c_int>();
                 let bytes_written =
@@ -1305,6 +1302,15 @@ impl LegacyTcpSocket {
     }
 }
 
+impl StateEventSourceOwner for LegacyTcpSocket {
+    fn with_event_source<R>(&mut self, f: impl FnOnce(&mut StateEventSource) -> R) -> R {
+        let event_source = unsafe { c::legacyfile_getEventSource(self.as_legacy_file()) };
+        let event_source = unsafe { event_source.as_ref() }.unwrap();
+
+        Worker::with_active_host(|host| f(&mut event_source.borrow_mut(host.root()))).unwrap()
+    }
+}
+
 impl std::ops::Drop for LegacyTcpSocket {
     fn drop(&mut self) {
         unsafe { c::legacyfile_unref(self.socket.ptr() as *mut libc::c_void) };
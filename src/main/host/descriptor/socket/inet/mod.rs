@@ -9,7 +9,9 @@ use shadow_shim_helper_rs::emulated_time::EmulatedTime;
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
 use crate::cshadow as c;
-use crate::host::descriptor::listener::{StateListenHandle, StateListenerFilter};
+use crate::host::descriptor::listener::{
+    StateEventSourceOwner, StateListenHandle, StateListenerFilter,
+};
 use crate::host::descriptor::socket::{RecvmsgArgs, RecvmsgReturn, SendmsgArgs};
 use crate::host::descriptor::{
     FileMode, FileSignals, FileState, FileStatus, OpenFile, SyscallResult,
@@ -351,6 +353,15 @@ impl InetSocketRefMut<'_> {
             notify_fn: impl Fn(FileState, FileState, FileSignals, &mut CallbackQueue) + Send + Sync + 'static,
         ) -> StateListenHandle
     );
+    enum_passthrough!(self, (monitoring_state, monitoring_signals, filter, notify_fn), LegacyTcp, Tcp, Udp;
+        pub fn add_exclusive_listener(
+            &mut self,
+            monitoring_state: FileState,
+            monitoring_signals: FileSignals,
+            filter: StateListenerFilter,
+            notify_fn: impl Fn(FileState, FileState, FileSignals, &mut CallbackQueue) -> bool + Send + Sync + 'static,
+        ) -> StateListenHandle
+    );
     enum_passthrough!(self, (ptr), LegacyTcp, Tcp, Udp;
         pub fn add_legacy_listener(&mut self, ptr: HostTreePointer<c::StatusListener>)
     );
@@ -14,9 +14,13 @@ use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 use crate::core::work::task::TaskRef;
 use crate::core::worker::Worker;
 use crate::cshadow as c;
-use crate::host::descriptor::listener::{StateEventSource, StateListenHandle, StateListenerFilter};
+use crate::host::descriptor::listener::{
+    StateEventSource, StateEventSourceOwner, StateListenHandle, StateListenerFilter,
+};
 use crate::host::descriptor::socket::inet;
-use crate::host::descriptor::socket::{InetSocket, RecvmsgArgs, RecvmsgReturn, SendmsgArgs};
+use crate::host::descriptor::socket::{
+    socket_stat, InetSocket, RecvmsgArgs, RecvmsgReturn, SendmsgArgs,
+};
 use crate::host::descriptor::{File, Socket};
 use crate::host::descriptor::{
     FileMode, FileSignals, FileState, FileStatus, OpenFile, SyscallResult,
@@ -545,8 +549,8 @@ impl TcpSocket {
     }
 
     pub fn stat(&self) -> Result<linux_api::stat::stat, SyscallError> {
-        warn_once_then_debug!("We do not yet handle stat calls on tcp sockets");
-        Err(Errno::EINVAL.into())
+        warn_once_then_debug!("Not all fields of 'struct stat' are implemented for tcp sockets");
+        Ok(socket_stat())
     }
 
     pub fn listen(
@@ -886,6 +890,45 @@ impl TcpSocket {
         cb_queue: &mut CallbackQueue,
     ) -> Result<libc::socklen_t, SyscallError> {
         match (level, optname) {
+            (libc::SOL_TCP, libc::TCP_INFO) => {
+                // the tcp state doesn't expose its rtt or congestion window, so we only report the
+                // connection state
+
+                // from linux's "include/net/tcp_states.h"
+                const TCP_ESTABLISHED: u8 = 1;
+                const TCP_SYN_SENT: u8 = 2;
+                const TCP_CLOSE: u8 = 7;
+                const TCP_LISTEN: u8 = 10;
+
+                let poll = self.tcp_state.poll();
+                let state = if poll.contains(tcp::PollState::LISTENING) {
+                    TCP_LISTEN
+                } else if poll.contains(tcp::PollState::CONNECTING) {
+                    TCP_SYN_SENT
+                } else if poll.contains(tcp::PollState::CONNECTED) {
+                    TCP_ESTABLISHED
+                } else {
+                    TCP_CLOSE
+                };
+
+                let mut info: c::tcp_info = shadow_pod::zeroed();
+                info.tcpi_state = state;
+
+                let optval_ptr = optval_ptr.cast::<c::tcp_info>();
+                let bytes_written = write_partial(mem, &info, optval_ptr, optlen as usize)?;
+
+                Ok(bytes_written as libc::socklen_t)
+            }
+            (libc::SOL_TCP, libc::TCP_NODELAY) => {
+                // shadow doesn't support nagle's algorithm, so shadow always behaves as if
+                // TCP_NODELAY is enabled
+                let val: libc::c_int = 1;
+
+                let optval_ptr = optval_ptr.cast::<libc::c_int>();
+                let bytes_written = write_partial(mem, &val, optval_ptr, optlen as usize)?;
+
+                Ok(bytes_written as libc::socklen_t)
+            }
             (libc::SOL_SOCKET, libc::SO_ERROR) => {
                 // may update the socket's state (for example, reading `SO_ERROR` will make `poll()`
                 // stop returning `POLLERR` for the socket)
@@ -947,11 +990,31 @@ impl TcpSocket {
         &mut self,
         level: libc::c_int,
         optname: libc::c_int,
-        _optval_ptr: ForeignPtr<()>,
-        _optlen: libc::socklen_t,
-        _mem: &MemoryManager,
+        optval_ptr: ForeignPtr<()>,
+        optlen: libc::socklen_t,
+        mem: &MemoryManager,
     ) -> Result<(), SyscallError> {
         match (level, optname) {
+            (libc::SOL_TCP, libc::TCP_NODELAY) => {
+                // Shadow doesn't support nagle's algorithm, so Shadow always behaves as if
+                // TCP_NODELAY is enabled (see the legacy tcp socket for details).
+
+                type OptType = libc::c_int;
+
+                if usize::try_from(optlen).unwrap() < std::mem::size_of::<OptType>() {
+                    return Err(Errno::EINVAL.into());
+                }
+
+                let optval_ptr = optval_ptr.cast::<OptType>();
+                let enable = mem.read(optval_ptr)?;
+
+                if enable == 0 {
+                    log::warn!("Cannot disable TCP_NODELAY since shadow does not implement Nagle's algorithm.");
+                    return Err(Errno::ENOPROTOOPT.into());
+                }
+
+                log::debug!("Ignoring TCP_NODELAY");
+            }
             (libc::SOL_SOCKET, libc::SO_REUSEADDR) => {
                 // TODO: implement this, tor and tgen use it
                 log::trace!("setsockopt SO_REUSEADDR not yet implemented");
@@ -1043,6 +1106,12 @@ impl TcpSocket {
     }
 }
 
+impl StateEventSourceOwner for TcpSocket {
+    fn with_event_source<R>(&mut self, f: impl FnOnce(&mut StateEventSource) -> R) -> R {
+        f(&mut self.event_source)
+    }
+}
+
 fn tcp_error_to_errno(error: tcp::TcpError) -> Errno {
     match error {
         tcp::TcpError::ResetSent => Errno::ECONNRESET,
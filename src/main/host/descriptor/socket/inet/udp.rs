@@ -14,9 +14,13 @@ use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
 use crate::core::worker::Worker;
 use crate::cshadow as c;
-use crate::host::descriptor::listener::{StateEventSource, StateListenHandle, StateListenerFilter};
+use crate::host::descriptor::listener::{
+    StateEventSource, StateEventSourceOwner, StateListenHandle, StateListenerFilter,
+};
 use crate::host::descriptor::socket::inet::{self, InetSocket};
-use crate::host::descriptor::socket::{RecvmsgArgs, RecvmsgReturn, SendmsgArgs, ShutdownFlags};
+use crate::host::descriptor::socket::{
+    socket_stat, RecvmsgArgs, RecvmsgReturn, SendmsgArgs, ShutdownFlags,
+};
 use crate::host::descriptor::{
     File, FileMode, FileSignals, FileState, FileStatus, OpenFile, Socket, SyscallResult,
 };
@@ -648,8 +652,8 @@ impl UdpSocket {
     }
 
     pub fn stat(&self) -> Result<linux_api::stat::stat, SyscallError> {
-        warn_once_then_debug!("We do not yet handle stat calls on udp sockets");
-        Err(Errno::EINVAL.into())
+        warn_once_then_debug!("Not all fields of 'struct stat' are implemented for udp sockets");
+        Ok(socket_stat())
     }
 
     pub fn listen(
@@ -1051,6 +1055,12 @@ impl UdpSocket {
     }
 }
 
+impl StateEventSourceOwner for UdpSocket {
+    fn with_event_source<R>(&mut self, f: impl FnOnce(&mut StateEventSource) -> R) -> R {
+        f(&mut self.event_source)
+    }
+}
+
 /// Non-payload data for a message in the send buffer.
 #[derive(Debug)]
 struct MessageSendHeader {
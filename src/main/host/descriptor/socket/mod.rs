@@ -5,14 +5,17 @@ use inet::{InetSocket, InetSocketRef, InetSocketRefMut};
 use linux_api::errno::Errno;
 use linux_api::ioctls::IoctlRequest;
 use linux_api::socket::Shutdown;
+use linux_api::stat::SFlag;
 use netlink::NetlinkSocket;
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 use unix::UnixSocket;
 
 use crate::cshadow as c;
-use crate::host::descriptor::listener::{StateListenHandle, StateListenerFilter};
+use crate::host::descriptor::listener::{
+    StateEventSourceOwner, StateListenHandle, StateListenerFilter,
+};
 use crate::host::descriptor::{
-    FileMode, FileSignals, FileState, FileStatus, OpenFile, SyscallResult,
+    synthetic_stat, FileMode, FileSignals, FileState, FileStatus, OpenFile, SyscallResult,
 };
 use crate::host::memory_manager::MemoryManager;
 use crate::host::network::namespace::NetworkNamespace;
@@ -280,6 +283,15 @@ impl SocketRefMut<'_> {
             notify_fn: impl Fn(FileState, FileState, FileSignals, &mut CallbackQueue) + Send + Sync + 'static,
         ) -> StateListenHandle
     );
+    enum_passthrough!(self, (monitoring_state, monitoring_signals, filter, notify_fn), Unix, Inet, Netlink;
+        pub fn add_exclusive_listener(
+            &mut self,
+            monitoring_state: FileState,
+            monitoring_signals: FileSignals,
+            filter: StateListenerFilter,
+            notify_fn: impl Fn(FileState, FileState, FileSignals, &mut CallbackQueue) -> bool + Send + Sync + 'static,
+        ) -> StateListenHandle
+    );
     enum_passthrough!(self, (ptr), Unix, Inet, Netlink;
         pub fn add_legacy_listener(&mut self, ptr: HostTreePointer<c::StatusListener>)
     );
@@ -416,3 +428,8 @@ pub struct RecvmsgReturn {
     /// The number of control data bytes read.
     pub control_len: libc::size_t,
 }
+
+/// A `struct stat` for a socket. Linux gives sockets a mode of `S_IFSOCK | 0777`.
+pub fn socket_stat() -> linux_api::stat::stat {
+    synthetic_stat(SFlag::S_IFSOCK | SFlag::S_IRWXU | SFlag::S_IRWXG | SFlag::S_IRWXO)
+}
@@ -21,11 +21,15 @@ use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
 use crate::core::worker::Worker;
 use crate::cshadow as c;
-use crate::host::descriptor::listener::{StateEventSource, StateListenHandle, StateListenerFilter};
+use crate::host::descriptor::listener::{
+    StateEventSource, StateEventSourceOwner, StateListenHandle, StateListenerFilter,
+};
 use crate::host::descriptor::shared_buf::{
     BufferHandle, BufferSignals, BufferState, ReaderHandle, SharedBuf,
 };
-use crate::host::descriptor::socket::{RecvmsgArgs, RecvmsgReturn, SendmsgArgs, Socket};
+use crate::host::descriptor::socket::{
+    socket_stat, RecvmsgArgs, RecvmsgReturn, SendmsgArgs, Socket,
+};
 use crate::host::descriptor::{
     File, FileMode, FileSignals, FileState, FileStatus, OpenFile, SyscallResult,
 };
@@ -336,8 +340,10 @@ impl NetlinkSocket {
     }
 
     pub fn stat(&self) -> Result<linux_api::stat::stat, SyscallError> {
-        warn_once_then_debug!("We do not yet handle stat calls on netlink sockets");
-        Err(Errno::EINVAL.into())
+        warn_once_then_debug!(
+            "Not all fields of 'struct stat' are implemented for netlink sockets"
+        );
+        Ok(socket_stat())
     }
 
     pub fn add_listener(
@@ -371,6 +377,12 @@ impl NetlinkSocket {
     }
 }
 
+impl StateEventSourceOwner for NetlinkSocket {
+    fn with_event_source<R>(&mut self, f: impl FnOnce(&mut StateEventSource) -> R) -> R {
+        f(&mut self.common.event_source)
+    }
+}
+
 struct InitialState {
     bound_addr: Option<NetlinkAddr>,
     reader_handle: ReaderHandle,
@@ -1,4 +1,5 @@
 use std::collections::{LinkedList, VecDeque};
+use std::ffi::{CStr, CString};
 use std::io::Read;
 use std::ops::DerefMut;
 use std::sync::{Arc, Weak};
@@ -10,13 +11,18 @@ use linux_api::socket::Shutdown;
 use nix::sys::socket::MsgFlags;
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
+use crate::core::worker::Worker;
 use crate::cshadow as c;
-use crate::host::descriptor::listener::{StateEventSource, StateListenHandle, StateListenerFilter};
+use crate::host::descriptor::listener::{
+    StateEventSource, StateEventSourceOwner, StateListenHandle, StateListenerFilter,
+};
 use crate::host::descriptor::shared_buf::{
     BufferHandle, BufferSignals, BufferState, ReaderHandle, SharedBuf, WriterHandle,
 };
-use crate::host::descriptor::socket::abstract_unix_ns::AbstractUnixNamespace;
-use crate::host::descriptor::socket::{RecvmsgArgs, RecvmsgReturn, SendmsgArgs, Socket};
+use crate::host::descriptor::socket::abstract_unix_ns::{AbstractUnixNamespace, SocketInode};
+use crate::host::descriptor::socket::{
+    socket_stat, RecvmsgArgs, RecvmsgReturn, SendmsgArgs, Socket,
+};
 use crate::host::descriptor::{
     File, FileMode, FileSignals, FileState, FileStatus, OpenFile, SyscallResult,
 };
@@ -214,8 +220,8 @@ impl UnixSocket {
     }
 
     pub fn stat(&self) -> Result<linux_api::stat::stat, SyscallError> {
-        warn_once_then_debug!("We do not yet handle stat calls on unix sockets");
-        Err(Errno::EINVAL.into())
+        warn_once_then_debug!("Not all fields of 'struct stat' are implemented for unix sockets");
+        Ok(socket_stat())
     }
 
     pub fn listen(
@@ -357,6 +363,12 @@ impl UnixSocket {
     }
 }
 
+impl StateEventSourceOwner for UnixSocket {
+    fn with_event_source<R>(&mut self, f: impl FnOnce(&mut StateEventSource) -> R) -> R {
+        f(&mut self.common.event_source)
+    }
+}
+
 struct ConnOrientedInitial {
     bound_addr: Option<SockaddrUnix<libc::sockaddr_un>>,
 }
@@ -2046,9 +2058,21 @@ impl UnixSocketCommon {
                 Ok(ref name) => SockaddrUnix::new_abstract(name).unwrap(),
                 Err(_) => return Err(Errno::EADDRINUSE.into()),
             }
+        } else if let Some(path) = addr.as_path() {
+            // if given a pathname address
+            let inode = create_socket_file(path)?;
+            let namespace = Arc::clone(&self.namespace);
+            AbstractUnixNamespace::bind_inode(
+                &namespace,
+                self.socket_type,
+                inode,
+                socket,
+                &mut self.event_source,
+            );
+            addr.into_owned()
         } else {
-            log::warn!("Only abstract names are currently supported for unix sockets");
-            return Err(Errno::ENOTSUP.into());
+            log::warn!("Unsupported unix socket address {addr:?}");
+            return Err(Errno::EINVAL.into());
         };
 
         Ok(bound_addr)
@@ -2352,10 +2376,73 @@ fn lookup_address(
         namespace
             .lookup(socket_type, name)
             .ok_or(linux_api::errno::Errno::ECONNREFUSED)
+    } else if let Some(path) = addr.as_path() {
+        // look up the socket from the socket file at the path
+        let inode = socket_file_inode(path)?;
+        namespace
+            .lookup_inode(socket_type, inode)
+            .ok_or(linux_api::errno::Errno::ECONNREFUSED)
     } else {
-        warn_once_then_debug!("Unix sockets with pathname addresses are not yet supported");
-        Err(linux_api::errno::Errno::ENOENT)
+        Err(linux_api::errno::Errno::EINVAL)
+    }
+}
+
+/// The path that shadow should use to access the pathname unix socket address `path`. Relative
+/// paths are relative to the working directory of the current process rather than shadow's.
+fn socket_file_path(path: &CStr) -> CString {
+    if path.to_bytes().starts_with(b"/") {
+        return path.to_owned();
     }
+
+    let mut full_path =
+        Worker::with_active_process(|process| process.current_working_dir().to_bytes().to_vec())
+            .unwrap();
+    full_path.push(b'/');
+    full_path.extend_from_slice(path.to_bytes());
+
+    CString::new(full_path).unwrap()
+}
+
+/// Create the socket file for the pathname unix socket address `path`, and return its inode.
+fn create_socket_file(path: &CStr) -> Result<SocketInode, linux_api::errno::Errno> {
+    let path = socket_file_path(path);
+
+    // unix(7): "the socket file is created with permissions 0777 minus the umask"
+    if unsafe { libc::mknod(path.as_ptr(), libc::S_IFSOCK | 0o777, 0) } != 0 {
+        let errno = last_errno();
+        // bind(2): "EADDRINUSE The given address is already in use."
+        if errno == linux_api::errno::Errno::EEXIST {
+            return Err(linux_api::errno::Errno::EADDRINUSE);
+        }
+        return Err(errno);
+    }
+
+    socket_file_inode(&path)
+}
+
+/// Get the inode of the socket file for the pathname unix socket address `path`.
+fn socket_file_inode(path: &CStr) -> Result<SocketInode, linux_api::errno::Errno> {
+    let path = socket_file_path(path);
+
+    let mut stat = unsafe { std::mem::zeroed::<libc::stat>() };
+    if unsafe { libc::stat(path.as_ptr(), &mut stat) } != 0 {
+        return Err(last_errno());
+    }
+
+    // unix(7): connecting to a file that isn't a socket fails with ECONNREFUSED
+    if stat.st_mode & libc::S_IFMT != libc::S_IFSOCK {
+        return Err(linux_api::errno::Errno::ECONNREFUSED);
+    }
+
+    Ok(SocketInode {
+        dev: stat.st_dev,
+        ino: stat.st_ino,
+    })
+}
+
+/// The errno set by the most recent failed libc call.
+fn last_errno() -> linux_api::errno::Errno {
+    linux_api::errno::Errno::try_from(nix::errno::Errno::last() as u16).unwrap()
 }
 
 fn backlog_to_queue_size(backlog: i32) -> u32 {
@@ -1591,6 +1591,19 @@ gint tcp_getConnectionError(TCP* tcp) {
     }
 }
 
+gint tcp_takePendingError(TCP* tcp) {
+    MAGIC_ASSERT(tcp);
+
+    /* A reset is reported at most once, whether through SO_ERROR or through a
+     * failing connect/send/recv call. */
+    if ((tcp->error & TCPE_CONNECTION_RESET) && !(tcp->flags & TCPF_RESET_SIGNALED)) {
+        tcp->flags |= TCPF_RESET_SIGNALED;
+        return (tcp->flags & TCPF_WAS_ESTABLISHED) ? ECONNRESET : ECONNREFUSED;
+    }
+
+    return 0;
+}
+
 static guint8 _tcp_getTCPInfoState(TCP* tcp) {
     switch(tcp->state) {
         case TCPS_ESTABLISHED: return (guint8) TCP_ESTABLISHED;
@@ -2110,7 +2123,8 @@ static void _tcp_processPacket(LegacySocket* socket, const Host* host, Packet* p
                 /* we need to multiplex a new child */
                 TCP* multiplexed = tcp_new(host, recvBufSize, sendBufSize);
                 Descriptor* desc = descriptor_fromLegacyTcp(multiplexed, /* flags= */ 0);
-                int handle = thread_registerDescriptor(registerInThread, desc);
+                /* The child is re-registered (subject to the fd limit) when it's accepted. */
+                int handle = thread_registerDescriptorIgnoringLimit(registerInThread, desc);
 
                 multiplexed->child =
                     _tcpchild_new(multiplexed, tcp, handle, header->sourceIP, header->sourcePort);
@@ -64,6 +64,11 @@ void tcp_setRustSocket(TCP* tcp, InetSocketWeak* rustSocket);
 gint tcp_getConnectionError(TCP* tcp);
 // clang-format on
 
+/* Returns the pending socket error as a positive errno (ECONNREFUSED or
+ * ECONNRESET), or 0 if there is none. Like the SO_ERROR socket option on
+ * Linux, the error is cleared once it has been returned. */
+gint tcp_takePendingError(TCP* tcp);
+
 void tcp_getInfo(TCP* tcp, struct tcp_info *tcpinfo);
 void tcp_enterServerMode(TCP* tcp, const Host* host, pid_t process, gint backlog);
 void tcp_updateServerBacklog(TCP* tcp, gint backlog);
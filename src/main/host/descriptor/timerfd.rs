@@ -10,8 +10,10 @@ use shadow_shim_helper_rs::{
 };
 
 use crate::cshadow as c;
-use crate::host::descriptor::listener::{StateEventSource, StateListenHandle, StateListenerFilter};
-use crate::host::descriptor::{FileMode, FileSignals, FileState, FileStatus};
+use crate::host::descriptor::listener::{
+    StateEventSource, StateEventSourceOwner, StateListenHandle, StateListenerFilter,
+};
+use crate::host::descriptor::{anon_inode_stat, FileMode, FileSignals, FileState, FileStatus};
 use crate::host::host::Host;
 use crate::host::memory_manager::MemoryManager;
 use crate::host::syscall::io::{IoVec, IoVecWriter};
@@ -211,8 +213,8 @@ impl TimerFd {
     }
 
     pub fn stat(&self) -> Result<linux_api::stat::stat, SyscallError> {
-        warn_once_then_debug!("We do not yet handle stat calls on timerfds");
-        Err(Errno::EINVAL.into())
+        warn_once_then_debug!("Not all fields of 'struct stat' are implemented for timerfds");
+        Ok(anon_inode_stat())
     }
 
     pub fn add_listener(
@@ -292,3 +294,9 @@ impl TimerFd {
             .notify_listeners(self.state, states_changed, signals, cb_queue);
     }
 }
+
+impl StateEventSourceOwner for TimerFd {
+    fn with_event_source<R>(&mut self, f: impl FnOnce(&mut StateEventSource) -> R) -> R {
+        f(&mut self.event_source)
+    }
+}
@@ -0,0 +1,209 @@
+use linux_api::errno::Errno;
+use linux_api::ioctls::IoctlRequest;
+use linux_api::posix_types::kernel_off_t;
+use linux_api::userfaultfd::{uffdio_api, UFFD_API};
+use shadow_shim_helper_rs::syscall_types::ForeignPtr;
+
+use crate::cshadow as c;
+use crate::host::descriptor::listener::{
+    StateEventSource, StateEventSourceOwner, StateListenHandle, StateListenerFilter,
+};
+use crate::host::descriptor::{anon_inode_stat, FileMode, FileSignals, FileState, FileStatus};
+use crate::host::memory_manager::MemoryManager;
+use crate::host::syscall::io::IoVec;
+use crate::host::syscall::types::{SyscallError, SyscallResult};
+use crate::utility::callback_queue::CallbackQueue;
+use crate::utility::HostTreePointer;
+
+/// A minimal userfaultfd(2) that only supports API negotiation. It reports no supported features
+/// or fault-handling ioctls, so programs that probe for userfaultfd support take their fallback
+/// path. Since no memory ranges can be registered, it never has any events to read.
+pub struct UserFaultFd {
+    event_source: StateEventSource,
+    status: FileStatus,
+    state: FileState,
+    // whether the `UFFDIO_API` handshake has completed
+    api_done: bool,
+    // Should only be used by `OpenFile` to make sure there is only ever one `OpenFile` instance for
+    // this file,
+    has_open_file: bool,
+}
+
+impl UserFaultFd {
+    pub fn new(status: FileStatus) -> Self {
+        Self {
+            event_source: StateEventSource::new(),
+            status,
+            state: FileState::ACTIVE,
+            api_done: false,
+            has_open_file: false,
+        }
+    }
+
+    pub fn status(&self) -> FileStatus {
+        self.status
+    }
+
+    pub fn set_status(&mut self, status: FileStatus) {
+        self.status = status;
+    }
+
+    pub fn mode(&self) -> FileMode {
+        FileMode::READ
+    }
+
+    pub fn has_open_file(&self) -> bool {
+        self.has_open_file
+    }
+
+    pub fn supports_sa_restart(&self) -> bool {
+        false
+    }
+
+    pub fn set_has_open_file(&mut self, val: bool) {
+        self.has_open_file = val;
+    }
+
+    pub fn readv(
+        &mut self,
+        _iovs: &[IoVec],
+        offset: Option<kernel_off_t>,
+        _flags: std::ffi::c_int,
+        _mem: &mut MemoryManager,
+        _cb_queue: &mut CallbackQueue,
+    ) -> Result<isize, SyscallError> {
+        if offset.is_some() {
+            return Err(Errno::ESPIPE.into());
+        }
+
+        // reading before the API handshake is an error
+        if !self.api_done {
+            return Err(Errno::EINVAL.into());
+        }
+
+        // there are never any page fault events
+        Err(Errno::EWOULDBLOCK.into())
+    }
+
+    pub fn writev(
+        &mut self,
+        _iovs: &[IoVec],
+        _offset: Option<kernel_off_t>,
+        _flags: std::ffi::c_int,
+        _mem: &mut MemoryManager,
+        _cb_queue: &mut CallbackQueue,
+    ) -> Result<isize, SyscallError> {
+        // userfaultfds don't support writing
+        Err(Errno::EINVAL.into())
+    }
+
+    pub fn close(&mut self, cb_queue: &mut CallbackQueue) -> Result<(), SyscallError> {
+        // set the closed flag and remove the active flag
+        self.update_state(
+            FileState::CLOSED | FileState::ACTIVE,
+            FileState::CLOSED,
+            FileSignals::empty(),
+            cb_queue,
+        );
+
+        Ok(())
+    }
+
+    pub fn ioctl(
+        &mut self,
+        request: IoctlRequest,
+        arg_ptr: ForeignPtr<()>,
+        memory_manager: &mut MemoryManager,
+    ) -> SyscallResult {
+        match request {
+            IoctlRequest::UFFDIO_API => {
+                let arg_ptr = arg_ptr.cast::<uffdio_api>();
+                let api = memory_manager.read(arg_ptr)?;
+
+                // the handshake can only be done once, and we don't support any features
+                if self.api_done || api.api != UFFD_API || api.features != 0 {
+                    memory_manager.write(arg_ptr, &uffdio_api::default())?;
+                    return Err(Errno::EINVAL.into());
+                }
+
+                let reply = uffdio_api {
+                    api: UFFD_API,
+                    features: 0,
+                    // only `UFFDIO_API` itself is supported
+                    ioctls: 1 << (linux_api::userfaultfd::UFFDIO_API & 0xff),
+                };
+                memory_manager.write(arg_ptr, &reply)?;
+                self.api_done = true;
+
+                Ok(0.into())
+            }
+            request => {
+                warn_once_then_debug!(
+                    "We do not yet handle ioctl request {request:?} on userfaultfds"
+                );
+                Err(Errno::EINVAL.into())
+            }
+        }
+    }
+
+    pub fn stat(&self) -> Result<linux_api::stat::stat, SyscallError> {
+        warn_once_then_debug!("Not all fields of 'struct stat' are implemented for userfaultfds");
+        Ok(anon_inode_stat())
+    }
+
+    pub fn add_listener(
+        &mut self,
+        monitoring_state: FileState,
+        monitoring_signals: FileSignals,
+        filter: StateListenerFilter,
+        notify_fn: impl Fn(FileState, FileState, FileSignals, &mut CallbackQueue)
+            + Send
+            + Sync
+            + 'static,
+    ) -> StateListenHandle {
+        self.event_source
+            .add_listener(monitoring_state, monitoring_signals, filter, notify_fn)
+    }
+
+    pub fn add_legacy_listener(&mut self, ptr: HostTreePointer<c::StatusListener>) {
+        self.event_source.add_legacy_listener(ptr);
+    }
+
+    pub fn remove_legacy_listener(&mut self, ptr: *mut c::StatusListener) {
+        self.event_source.remove_legacy_listener(ptr);
+    }
+
+    pub fn state(&self) -> FileState {
+        self.state
+    }
+
+    fn update_state(
+        &mut self,
+        mask: FileState,
+        state: FileState,
+        signals: FileSignals,
+        cb_queue: &mut CallbackQueue,
+    ) {
+        let old_state = self.state;
+
+        // remove the mask, then copy the masked flags
+        self.state.remove(mask);
+        self.state.insert(state & mask);
+
+        let states_changed = self.state ^ old_state;
+
+        // just return if nothing changed
+        if states_changed.is_empty() && signals.is_empty() {
+            return;
+        }
+
+        self.event_source
+            .notify_listeners(self.state, states_changed, signals, cb_queue);
+    }
+}
+
+impl StateEventSourceOwner for UserFaultFd {
+    fn with_event_source<R>(&mut self, f: impl FnOnce(&mut StateEventSource) -> R) -> R {
+        f(&mut self.event_source)
+    }
+}
@@ -0,0 +1,407 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use crate::cshadow as c;
+use crate::host::futex_table::FutexRef;
+use crate::host::process::ProcessId;
+use crate::utility::ObjectCounter;
+
+/// Identifies a file on the native filesystem, so that locks taken through different open files
+/// (possibly by different processes) refer to the same lock state.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FileLockKey {
+    pub dev: u64,
+    pub ino: u64,
+}
+
+/// The mode of a `flock(2)` lock.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FlockMode {
+    Shared,
+    Exclusive,
+}
+
+/// The owner of a POSIX record lock.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RangeLockOwner {
+    /// A traditional `F_SETLK` lock, which is owned by a process.
+    Process(ProcessId),
+    /// An `F_OFD_SETLK` lock, which is owned by an open file.
+    OpenFile(usize),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RangeLockKind {
+    Read,
+    Write,
+}
+
+/// A POSIX record lock on a byte range of a file.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RangeLock {
+    pub owner: RangeLockOwner,
+    pub kind: RangeLockKind,
+    /// The first byte of the range.
+    pub start: u64,
+    /// One past the last byte of the range, or `None` if the range extends to the end of the
+    /// file, however large the file grows.
+    pub end: Option<u64>,
+}
+
+impl RangeLock {
+    fn overlaps(&self, start: u64, end: Option<u64>) -> bool {
+        let starts_before_end = end.map_or(true, |end| self.start < end);
+        let ends_after_start = self.end.map_or(true, |self_end| start < self_end);
+        starts_before_end && ends_after_start
+    }
+
+    /// Returns the parts of this lock that lie outside of the range.
+    fn remove_range(&self, start: u64, end: Option<u64>) -> impl Iterator<Item = RangeLock> {
+        let before = (self.start < start).then_some(RangeLock {
+            end: Some(start),
+            ..*self
+        });
+        let after = end.and_then(|end| {
+            self.end
+                .map_or(true, |self_end| end < self_end)
+                .then_some(RangeLock {
+                    start: end,
+                    ..*self
+                })
+        });
+        before.into_iter().chain(after)
+    }
+}
+
+/// The advisory locks held on the files of a host. Linux keeps this state on the inode; we keep
+/// it here so that locks conflict between the processes of a host without ever blocking on the
+/// native file.
+pub struct FileLockTable {
+    files: HashMap<FileLockKey, FileLocks>,
+    _counter: ObjectCounter,
+}
+
+/// The locks held on a single file.
+#[derive(Default)]
+struct FileLocks {
+    /// `flock(2)` locks, keyed by the open file that owns them.
+    flocks: HashMap<usize, FlockMode>,
+    /// POSIX record locks. An owner's locks never overlap each other.
+    range_locks: Vec<RangeLock>,
+    /// Woken whenever a lock on this file is released, so that blocked lockers can try again.
+    /// Created when the first locker needs to wait.
+    wakeup: Option<FutexRef>,
+}
+
+impl FileLocks {
+    fn is_empty(&self) -> bool {
+        self.flocks.is_empty() && self.range_locks.is_empty() && self.wakeup.is_none()
+    }
+
+    /// Remove the range from the locks of `owner`, splitting locks that only partly overlap it.
+    /// Returns `true` if any lock was changed.
+    fn remove_range_locks(&mut self, owner: RangeLockOwner, start: u64, end: Option<u64>) -> bool {
+        let (removed, mut kept): (Vec<RangeLock>, Vec<RangeLock>) =
+            std::mem::take(&mut self.range_locks)
+                .into_iter()
+                .partition(|lock| lock.owner == owner && lock.overlaps(start, end));
+
+        if removed.is_empty() {
+            self.range_locks = kept;
+            return false;
+        }
+
+        kept.extend(
+            removed
+                .iter()
+                .flat_map(|lock| lock.remove_range(start, end)),
+        );
+        self.range_locks = kept;
+        true
+    }
+
+    fn wake_waiters(&mut self) {
+        // the waiters will re-register if they still can't take their lock
+        if let Some(wakeup) = self.wakeup.take() {
+            wakeup.wake(libc::c_uint::MAX);
+        }
+    }
+}
+
+impl FileLockTable {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            files: HashMap::new(),
+            _counter: ObjectCounter::new("FileLockTable"),
+        }
+    }
+
+    /// Try to take a `flock(2)` lock on the file for `owner`, replacing any lock that `owner`
+    /// already holds. Like Linux, converting a lock is not atomic: if the new lock conflicts,
+    /// `owner` is left holding no lock. Returns `false` if the lock conflicts with a lock held
+    /// by a different owner.
+    pub fn flock_try_lock(&mut self, key: FileLockKey, owner: usize, mode: FlockMode) -> bool {
+        self.flock_unlock(key, owner);
+
+        let locks = self.files.entry(key).or_default();
+
+        let conflicts = locks.flocks.iter().any(|(other, other_mode)| {
+            *other != owner && (mode == FlockMode::Exclusive || *other_mode == FlockMode::Exclusive)
+        });
+
+        if conflicts {
+            if locks.is_empty() {
+                self.files.remove(&key);
+            }
+            return false;
+        }
+
+        locks.flocks.insert(owner, mode);
+        true
+    }
+
+    /// Release the `flock(2)` lock held by `owner`, if any.
+    pub fn flock_unlock(&mut self, key: FileLockKey, owner: usize) {
+        let Entry::Occupied(mut entry) = self.files.entry(key) else {
+            return;
+        };
+
+        if entry.get_mut().flocks.remove(&owner).is_some() {
+            entry.get_mut().wake_waiters();
+        }
+
+        if entry.get().is_empty() {
+            entry.remove();
+        }
+    }
+
+    /// Returns a lock held by a different owner that would conflict with `lock`, if any.
+    pub fn range_conflict(&self, key: FileLockKey, lock: &RangeLock) -> Option<RangeLock> {
+        let locks = self.files.get(&key)?;
+        locks
+            .range_locks
+            .iter()
+            .find(|other| {
+                other.owner != lock.owner
+                    && (lock.kind == RangeLockKind::Write || other.kind == RangeLockKind::Write)
+                    && other.overlaps(lock.start, lock.end)
+            })
+            .copied()
+    }
+
+    /// Try to take a POSIX record lock, replacing the owner's existing locks in the range.
+    /// Returns `false` if the lock conflicts with a lock held by a different owner.
+    pub fn range_try_lock(&mut self, key: FileLockKey, lock: RangeLock) -> bool {
+        if self.range_conflict(key, &lock).is_some() {
+            return false;
+        }
+
+        let locks = self.files.entry(key).or_default();
+        locks.remove_range_locks(lock.owner, lock.start, lock.end);
+        locks.range_locks.push(lock);
+
+        // converting a write lock to a read lock may allow others to take their locks
+        locks.wake_waiters();
+        true
+    }
+
+    /// Release the owner's POSIX record locks within the range.
+    pub fn range_unlock(
+        &mut self,
+        key: FileLockKey,
+        owner: RangeLockOwner,
+        start: u64,
+        end: Option<u64>,
+    ) {
+        let Entry::Occupied(mut entry) = self.files.entry(key) else {
+            return;
+        };
+
+        if entry.get_mut().remove_range_locks(owner, start, end) {
+            entry.get_mut().wake_waiters();
+        }
+
+        if entry.get().is_empty() {
+            entry.remove();
+        }
+    }
+
+    /// Release all locks held by a process, which happens when it exits. Linux also releases
+    /// them when the process closes any descriptor for the file, which we don't model.
+    pub fn release_process(&mut self, pid: ProcessId) {
+        self.release_range_locks(RangeLockOwner::Process(pid));
+    }
+
+    /// Release all locks held by an open file, which happens when it's closed.
+    pub fn release_open_file(&mut self, owner: usize) {
+        self.release_range_locks(RangeLockOwner::OpenFile(owner));
+
+        let keys: Vec<_> = self
+            .files
+            .iter()
+            .filter(|(_, locks)| locks.flocks.contains_key(&owner))
+            .map(|(key, _)| *key)
+            .collect();
+
+        for key in keys {
+            self.flock_unlock(key, owner);
+        }
+    }
+
+    fn release_range_locks(&mut self, owner: RangeLockOwner) {
+        self.files.retain(|_, locks| {
+            if locks.remove_range_locks(owner, 0, None) {
+                locks.wake_waiters();
+            }
+            !locks.is_empty()
+        });
+    }
+
+    /// The futex that is woken when the next lock on the file is released.
+    pub fn wakeup_futex(&mut self, key: FileLockKey) -> &FutexRef {
+        self.files
+            .entry(key)
+            .or_default()
+            .wakeup
+            .get_or_insert_with(|| {
+                // the futex is only used for its listeners, so its address doesn't matter
+                let futex = unsafe { c::futex_new(0usize.into()) };
+                unsafe { FutexRef::new(futex) }
+            })
+    }
+}
+
+mod export {
+    use super::*;
+
+    /// Returns `true` if the lock was taken, or `false` if it conflicts with another lock.
+    #[no_mangle]
+    pub unsafe extern "C-unwind" fn filelocktable_flockTryLock(
+        table: *mut FileLockTable,
+        dev: u64,
+        ino: u64,
+        owner: usize,
+        exclusive: bool,
+    ) -> bool {
+        let table = unsafe { table.as_mut() }.unwrap();
+        let mode = if exclusive {
+            FlockMode::Exclusive
+        } else {
+            FlockMode::Shared
+        };
+        table.flock_try_lock(FileLockKey { dev, ino }, owner, mode)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C-unwind" fn filelocktable_flockUnlock(
+        table: *mut FileLockTable,
+        dev: u64,
+        ino: u64,
+        owner: usize,
+    ) {
+        let table = unsafe { table.as_mut() }.unwrap();
+        table.flock_unlock(FileLockKey { dev, ino }, owner);
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C-unwind" fn filelocktable_releaseOpenFile(
+        table: *mut FileLockTable,
+        owner: usize,
+    ) {
+        let table = unsafe { table.as_mut() }.unwrap();
+        table.release_open_file(owner);
+    }
+
+    /// This returns a borrowed reference. If you don't increment the refcount of the returned
+    /// futex, then the returned pointer will be invalidated if the lock table is mutated.
+    #[no_mangle]
+    pub unsafe extern "C-unwind" fn filelocktable_getWakeupFutex(
+        table: *mut FileLockTable,
+        dev: u64,
+        ino: u64,
+    ) -> *mut c::Futex {
+        let table = unsafe { table.as_mut() }.unwrap();
+        table.wakeup_futex(FileLockKey { dev, ino }).ptr()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lock(owner: u32, kind: RangeLockKind, start: u64, end: Option<u64>) -> RangeLock {
+        RangeLock {
+            owner: RangeLockOwner::Process(ProcessId::try_from(owner).unwrap()),
+            kind,
+            start,
+            end,
+        }
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let l = lock(1000, RangeLockKind::Read, 10, Some(20));
+        assert!(l.overlaps(0, Some(11)));
+        assert!(l.overlaps(19, None));
+        assert!(l.overlaps(0, None));
+        assert!(!l.overlaps(0, Some(10)));
+        assert!(!l.overlaps(20, Some(30)));
+
+        let l = lock(1000, RangeLockKind::Read, 10, None);
+        assert!(l.overlaps(1000, Some(1001)));
+        assert!(!l.overlaps(0, Some(10)));
+    }
+
+    #[test]
+    fn test_remove_range() {
+        let l = lock(1000, RangeLockKind::Read, 10, Some(20));
+        let parts: Vec<_> = l.remove_range(12, Some(15)).collect();
+        assert_eq!(
+            parts,
+            [
+                lock(1000, RangeLockKind::Read, 10, Some(12)),
+                lock(1000, RangeLockKind::Read, 15, Some(20)),
+            ]
+        );
+
+        let parts: Vec<_> = l.remove_range(0, None).collect();
+        assert!(parts.is_empty());
+
+        let l = lock(1000, RangeLockKind::Read, 10, None);
+        let parts: Vec<_> = l.remove_range(0, Some(15)).collect();
+        assert_eq!(parts, [lock(1000, RangeLockKind::Read, 15, None)]);
+    }
+
+    #[test]
+    fn test_range_conflicts() {
+        let key = FileLockKey { dev: 1, ino: 2 };
+        let mut table = FileLockTable::new();
+
+        assert!(table.range_try_lock(key, lock(1000, RangeLockKind::Write, 0, Some(10))));
+        assert!(table.range_try_lock(key, lock(1001, RangeLockKind::Write, 10, Some(20))));
+
+        // overlapping locks conflict unless both are read locks
+        let conflict = table.range_conflict(key, &lock(1001, RangeLockKind::Read, 5, Some(15)));
+        assert_eq!(
+            conflict,
+            Some(lock(1000, RangeLockKind::Write, 0, Some(10)))
+        );
+        assert!(!table.range_try_lock(key, lock(1001, RangeLockKind::Read, 5, Some(15))));
+
+        // an owner's own locks never conflict
+        assert!(table.range_try_lock(key, lock(1000, RangeLockKind::Read, 0, Some(5))));
+
+        // after unlocking part of the range, only the rest conflicts
+        let owner = RangeLockOwner::Process(ProcessId::try_from(1000u32).unwrap());
+        table.range_unlock(key, owner, 5, Some(10));
+        assert!(table.range_try_lock(key, lock(1001, RangeLockKind::Write, 5, None)));
+        assert!(table.range_try_lock(key, lock(1002, RangeLockKind::Read, 0, Some(5))));
+        assert!(!table.range_try_lock(key, lock(1002, RangeLockKind::Write, 0, Some(5))));
+
+        table.release_process(ProcessId::try_from(1000u32).unwrap());
+        table.release_process(ProcessId::try_from(1001u32).unwrap());
+        table.release_process(ProcessId::try_from(1002u32).unwrap());
+        assert!(table.files.is_empty());
+    }
+}
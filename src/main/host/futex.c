@@ -13,14 +13,22 @@
 #include "main/bindings/c/bindings-opaque.h"
 #include "main/core/definitions.h"
 #include "main/core/worker.h"
+#include "main/host/syscall/syscall_condition.h"
 #include "main/utility/utility.h"
 
+typedef struct _FutexWaiter FutexWaiter;
+struct _FutexWaiter {
+    // Whether or not a wakeup has already been performed on the listener
+    bool woken;
+    // The condition that owns the listener
+    SysCallCondition* cond;
+};
+
 struct _Futex {
     // The unique physical address that is used to refer to this futex
     ManagedPhysicalMemoryAddr word;
     // Listeners waiting for wakups on this futex
-    // The key is a listener of type StatusListener*, the value is a boolean that indicates
-    // whether or not a wakeup has already been performed on the listener.
+    // The key is a listener of type StatusListener*, the value is a FutexWaiter*.
     GHashTable* listeners;
     // Manage references
     int referenceCount;
@@ -31,7 +39,7 @@ Futex* futex_new(ManagedPhysicalMemoryAddr word) {
     Futex* futex = malloc(sizeof(*futex));
     *futex = (Futex){.word = word,
                      .listeners = g_hash_table_new_full(
-                         g_direct_hash, g_direct_equal, (GDestroyNotify)statuslistener_unref, NULL),
+                         g_direct_hash, g_direct_equal, (GDestroyNotify)statuslistener_unref, free),
                      .referenceCount = 1,
                      MAGIC_INITIALIZER};
 
@@ -70,24 +78,29 @@ ManagedPhysicalMemoryAddr futex_getAddress(Futex* futex) {
     return futex->word;
 }
 
-unsigned int futex_wake(Futex* futex, unsigned int numWakeups) {
-    MAGIC_ASSERT(futex);
-
-    // We cannot use an iterator here, in case the hash table is modified
-    // in the status changed callback.
+// Returns the listeners in a deterministic order. The caller must free the list.
+static GList* _futex_getSortedListeners(Futex* futex) {
+    // We cannot use an iterator, in case the hash table is modified while
+    // processing the listeners.
     GList* listenerList = g_hash_table_get_keys(futex->listeners);
 
-    // Iterate the listeners in deterministic order and perform the requested
-    // number of wakeups if we can. It's probably better to maintain the items
-    // in a sorted structure, e.g. a ring, to make it easier / more efficient to
-    // iterate deterministically while also moving the ring entry pointer so we
-    // don't always wake up the same listener first on every iteration and
-    // possibly starve the others.
-    GList* item = NULL;
-    if(listenerList != NULL) {
+    // It's probably better to maintain the items in a sorted structure, e.g. a
+    // ring, to make it easier / more efficient to iterate deterministically
+    // while also moving the ring entry pointer so we don't always wake up the
+    // same listener first on every iteration and possibly starve the others.
+    if (listenerList != NULL) {
         listenerList = g_list_sort(listenerList, status_listener_compare);
-        item = g_list_first(listenerList);
     }
+    return listenerList;
+}
+
+unsigned int futex_wake(Futex* futex, unsigned int numWakeups) {
+    MAGIC_ASSERT(futex);
+
+    // Iterate the listeners in deterministic order and perform the requested
+    // number of wakeups if we can.
+    GList* listenerList = _futex_getSortedListeners(futex);
+    GList* item = g_list_first(listenerList);
 
     unsigned int numWoken = 0;
 
@@ -95,37 +108,68 @@ unsigned int futex_wake(Futex* futex, unsigned int numWakeups) {
         StatusListener* listener = item->data;
 
         // Only call if the listener is still valid
-        if (g_hash_table_contains(futex->listeners, listener)) {
-            // If this listener was already woken up, skip it this time
-            bool did_wakeup =
-                (bool)GPOINTER_TO_UINT(g_hash_table_lookup(futex->listeners, listener));
-            if (!did_wakeup) {
-                // Tell the status listener to unblock the thread waiting on the futex
-                statuslistener_onStatusChanged(listener, FileState_FUTEX_WAKEUP, FileState_FUTEX_WAKEUP);
-
-                // Track that we did a wakeup on this listener without destroying the listener
-                g_hash_table_steal(futex->listeners, listener);
-                g_hash_table_insert(futex->listeners, listener, GUINT_TO_POINTER(true));
-
-                // Count the wake-up
-                numWoken++;
-            }
+        FutexWaiter* waiter = g_hash_table_lookup(futex->listeners, listener);
+
+        // If this listener was already woken up, skip it this time
+        if (waiter != NULL && !waiter->woken) {
+            // Track that we did a wakeup on this listener without destroying the listener
+            waiter->woken = true;
+
+            // Tell the status listener to unblock the thread waiting on the futex
+            statuslistener_onStatusChanged(
+                listener, FileState_FUTEX_WAKEUP, FileState_FUTEX_WAKEUP);
+
+            // Count the wake-up
+            numWoken++;
         }
 
         item = g_list_next(item);
     }
 
-    if(listenerList != NULL) {
+    if (listenerList != NULL) {
         g_list_free(listenerList);
     }
     return numWoken;
 }
 
-void futex_addListener(Futex* futex, StatusListener* listener) {
+unsigned int futex_requeue(Futex* futex, Futex* target, unsigned int numRequeues) {
+    MAGIC_ASSERT(futex);
+    MAGIC_ASSERT(target);
+
+    GList* listenerList = _futex_getSortedListeners(futex);
+    GList* item = g_list_first(listenerList);
+
+    unsigned int numRequeued = 0;
+
+    while (item && (numRequeued < numRequeues)) {
+        StatusListener* listener = item->data;
+        FutexWaiter* waiter = g_hash_table_lookup(futex->listeners, listener);
+
+        // Listeners that were already woken up aren't waiting anymore
+        if (waiter != NULL && !waiter->woken) {
+            // This removes the listener from this futex and adds it to the target
+            syscallcondition_requeueFutex(waiter->cond, target);
+            numRequeued++;
+        }
+
+        item = g_list_next(item);
+    }
+
+    if (listenerList != NULL) {
+        g_list_free(listenerList);
+    }
+    return numRequeued;
+}
+
+void futex_addListener(Futex* futex, StatusListener* listener, SysCallCondition* cond) {
     MAGIC_ASSERT(futex);
     utility_debugAssert(listener);
+    utility_debugAssert(cond);
     statuslistener_ref(listener);
-    g_hash_table_insert(futex->listeners, listener, GUINT_TO_POINTER(false));
+
+    FutexWaiter* waiter = malloc(sizeof(*waiter));
+    *waiter = (FutexWaiter){.woken = false, .cond = cond};
+    g_hash_table_insert(futex->listeners, listener, waiter);
 }
 
 void futex_removeListener(Futex* futex, StatusListener* listener) {
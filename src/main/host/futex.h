@@ -13,6 +13,9 @@
 // Opaque futex object.
 typedef struct _Futex Futex;
 
+// Defined in "main/host/syscall/syscall_condition.h", which includes this header.
+typedef struct _SysCallCondition SysCallCondition;
+
 #include "main/bindings/c/bindings-opaque.h"
 #include "main/host/status_listener.h"
 
@@ -33,8 +36,13 @@ ManagedPhysicalMemoryAddr futex_getAddress(Futex* futex);
 // threads that were woken up.
 unsigned int futex_wake(Futex* futex, unsigned int numWakeups);
 
-// Add a listener that will be notified when a wakup occurs
-void futex_addListener(Futex* futex, StatusListener* listener);
+// Move at most the given number of listener threads waiting on this futex to the `target` futex,
+// without waking them up; return the number of threads that were requeued.
+unsigned int futex_requeue(Futex* futex, Futex* target, unsigned int numRequeues);
+
+// Add a listener that will be notified when a wakup occurs. `cond` is the condition that owns the
+// listener, which is updated if the listener is requeued onto another futex.
+void futex_addListener(Futex* futex, StatusListener* listener, SysCallCondition* cond);
 
 // Remove a listener from those that are waiting for wakeups
 void futex_removeListener(Futex* futex, StatusListener* listener);
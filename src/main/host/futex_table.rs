@@ -72,6 +72,16 @@ impl FutexRef {
     pub fn wake(&self, num_wakeups: libc::c_uint) -> libc::c_uint {
         unsafe { c::futex_wake(self.ptr(), num_wakeups) }
     }
+
+    /// Move up to `num_requeues` waiters that haven't yet been woken to the `target` futex.
+    /// Returns the number of waiters that were moved.
+    pub fn requeue(&self, target: &FutexRef, num_requeues: libc::c_uint) -> libc::c_uint {
+        unsafe { c::futex_requeue(self.ptr(), target.ptr(), num_requeues) }
+    }
+
+    pub fn listener_count(&self) -> libc::c_uint {
+        unsafe { c::futex_getListenerCount(self.ptr()) }
+    }
 }
 
 impl std::ops::Drop for FutexRef {
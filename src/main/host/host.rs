@@ -3,16 +3,17 @@
 use std::cell::{Cell, Ref, RefCell, RefMut, UnsafeCell};
 use std::collections::BTreeMap;
 use std::ffi::{CStr, CString, OsString};
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::net::{Ipv4Addr, SocketAddrV4};
 use std::num::NonZeroU8;
 use std::ops::{Deref, DerefMut};
 use std::os::unix::prelude::OsStringExt;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, Weak};
 
 use atomic_refcell::AtomicRefCell;
 use linux_api::signal::{siginfo_t, Signal};
-use log::{debug, trace};
+use log::{debug, trace, warn};
 use logger::LogLevel;
 use once_cell::unsync::OnceCell;
 use rand::SeedableRng;
@@ -38,17 +39,23 @@ use crate::core::work::event_queue::EventQueue;
 use crate::core::work::task::TaskRef;
 use crate::core::worker::Worker;
 use crate::cshadow;
+use crate::host::descriptor::inotify::Inotify;
+use crate::host::descriptor::mqueue::QueueBuf;
 use crate::host::descriptor::socket::abstract_unix_ns::AbstractUnixNamespace;
 use crate::host::descriptor::socket::inet::InetSocket;
+use crate::host::file_lock_table::FileLockTable;
 use crate::host::futex_table::FutexTable;
 use crate::host::network::interface::{FifoPacketPriority, NetworkInterface, PcapOptions};
 use crate::host::network::namespace::NetworkNamespace;
 use crate::host::process::Process;
+use crate::host::sysv_sem_table::SysvSemTable;
+use crate::host::sysv_shm_table::SysvShmTable;
 use crate::host::thread::{Thread, ThreadId};
 use crate::network::relay::{RateLimit, Relay};
 use crate::network::router::Router;
 use crate::network::PacketDevice;
 use crate::utility;
+use crate::utility::callback_queue::CallbackQueue;
 #[cfg(feature = "perf_timers")]
 use crate::utility::perf_timer::PerfTimer;
 
@@ -82,6 +89,7 @@ pub struct HostParameters {
     pub unblocked_syscall_latency: SimulationTime,
     pub unblocked_vdso_latency: SimulationTime,
     pub strace_logging_options: Option<FmtOptions>,
+    pub strace_summary: bool,
     pub shim_log_level: LogLevel,
     pub use_new_tcp: bool,
     pub use_mem_mapper: bool,
@@ -139,6 +147,24 @@ pub struct Host {
     // map address to futex objects
     futex_table: RefCell<FutexTable>,
 
+    // advisory locks held on files by the host's processes
+    file_lock_table: RefCell<FileLockTable>,
+
+    // System V shared memory segments
+    sysv_shm_table: RefCell<SysvShmTable>,
+
+    // System V semaphore sets
+    sysv_sem_table: RefCell<SysvSemTable>,
+
+    // POSIX message queues, by name
+    message_queues: RefCell<BTreeMap<CString, Arc<AtomicRefCell<QueueBuf>>>>,
+
+    // inotify instances, which are checked for new native events at syscall boundaries
+    inotify_instances: RefCell<Vec<Weak<AtomicRefCell<Inotify>>>>,
+
+    // (id, hash) pairs recorded by `SYS_shadow_checkpoint`, in the order they were recorded
+    checkpoints: RefCell<Vec<(u64, u64)>>,
+
     #[cfg(feature = "perf_timers")]
     execution_timer: RefCell<PerfTimer>,
 
@@ -322,6 +348,12 @@ impl Host {
             relay_loopback: Arc::new(relay_loopback),
             tracker: RefCell::new(None),
             futex_table: RefCell::new(FutexTable::new()),
+            file_lock_table: RefCell::new(FileLockTable::new()),
+            sysv_shm_table: RefCell::new(SysvShmTable::new()),
+            sysv_sem_table: RefCell::new(SysvSemTable::new()),
+            message_queues: RefCell::new(BTreeMap::new()),
+            inotify_instances: RefCell::new(Vec::new()),
+            checkpoints: RefCell::new(Vec::new()),
             random,
             shim_shmem,
             shim_shmem_lock: RefCell::new(None),
@@ -514,6 +546,7 @@ impl Host {
                     return None;
                 }
                 process.set_parent_id(ProcessId::INIT);
+                process.handle_parent_death(self, pid);
                 let Some(z) = process.borrow_as_zombie() else {
                     // Not a zombie
                     return None;
@@ -652,6 +685,70 @@ impl Host {
         self.futex_table.borrow_mut()
     }
 
+    #[track_caller]
+    pub fn file_lock_table_borrow_mut(&self) -> impl DerefMut<Target = FileLockTable> + '_ {
+        self.file_lock_table.borrow_mut()
+    }
+
+    #[track_caller]
+    pub fn sysv_shm_table_borrow(&self) -> impl Deref<Target = SysvShmTable> + '_ {
+        self.sysv_shm_table.borrow()
+    }
+
+    #[track_caller]
+    pub fn sysv_shm_table_borrow_mut(&self) -> impl DerefMut<Target = SysvShmTable> + '_ {
+        self.sysv_shm_table.borrow_mut()
+    }
+
+    #[track_caller]
+    pub fn sysv_sem_table_borrow_mut(&self) -> impl DerefMut<Target = SysvSemTable> + '_ {
+        self.sysv_sem_table.borrow_mut()
+    }
+
+    #[track_caller]
+    pub fn message_queues_borrow_mut(
+        &self,
+    ) -> impl DerefMut<Target = BTreeMap<CString, Arc<AtomicRefCell<QueueBuf>>>> + '_ {
+        self.message_queues.borrow_mut()
+    }
+
+    /// Register an inotify instance so that it's checked for new events by
+    /// [`Host::refresh_inotify_instances`].
+    pub fn add_inotify_instance(&self, inotify: &Arc<AtomicRefCell<Inotify>>) {
+        self.inotify_instances
+            .borrow_mut()
+            .push(Arc::downgrade(inotify));
+    }
+
+    /// Move any events pending on the native inotify instances into the simulated instances. This
+    /// should be called at deterministic points, such as after each syscall.
+    #[inline]
+    pub fn refresh_inotify_instances(&self) {
+        // Most hosts never use inotify, so keep this cheap when there's nothing to refresh.
+        if self.inotify_instances.borrow().is_empty() {
+            return;
+        }
+        self.refresh_inotify_instances_slow();
+    }
+
+    #[cold]
+    fn refresh_inotify_instances_slow(&self) {
+        let instances: Vec<_> = {
+            let mut instances = self.inotify_instances.borrow_mut();
+            // Forget closed instances so that we're back on the fast path once they're all gone.
+            instances.retain(|x| x.strong_count() > 0);
+            instances.iter().filter_map(Weak::upgrade).collect()
+        };
+        if instances.is_empty() {
+            return;
+        }
+        CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+            for inotify in instances {
+                inotify.borrow_mut().refresh(cb_queue);
+            }
+        });
+    }
+
     #[allow(non_snake_case)]
     pub fn bw_up_kiBps(&self) -> u64 {
         self.params.requested_bw_up_bits / (8 * 1024)
@@ -779,6 +876,8 @@ impl Host {
 
         assert!(self.processes.borrow().is_empty());
 
+        self.write_checkpoints();
+
         self.stop_execution_timer();
         #[cfg(feature = "perf_timers")]
         debug!(
@@ -788,6 +887,38 @@ impl Host {
         );
     }
 
+    /// Record a determinism checkpoint with the application-provided `id`. Returns a hash of the
+    /// host's current state, which should be the same across simulations with the same
+    /// configuration.
+    pub fn add_checkpoint(&self, id: u64) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        id.hash(&mut hasher);
+        Worker::current_time().unwrap().hash(&mut hasher);
+        self.event_queue.lock().unwrap().len().hash(&mut hasher);
+        let hash = hasher.finish();
+
+        self.checkpoints.borrow_mut().push((id, hash));
+        hash
+    }
+
+    /// Write any recorded checkpoints to a "checkpoints.txt" file in the host's data directory.
+    fn write_checkpoints(&self) {
+        let checkpoints = self.checkpoints.borrow();
+        if checkpoints.is_empty() {
+            return;
+        }
+
+        let contents: String = checkpoints
+            .iter()
+            .map(|(id, hash)| format!("{id} {hash:016x}\n"))
+            .collect();
+
+        let path = self.data_dir_path.join("checkpoints.txt");
+        if let Err(e) = std::fs::write(&path, contents) {
+            warn!("Could not write checkpoints to {}: {e}", path.display());
+        }
+    }
+
     pub fn free_all_applications(&self) {
         trace!("start freeing applications for host '{}'", self.name());
         let processes = std::mem::take(&mut *self.processes.borrow_mut());
@@ -1028,6 +1159,16 @@ impl Host {
         None
     }
 
+    /// Returns the IDs of the processes in the given process group.
+    pub fn process_ids_in_group(&self, group_id: ProcessId) -> Vec<ProcessId> {
+        let processes = self.processes.borrow();
+        processes
+            .iter()
+            .filter(|(_pid, processrc)| processrc.borrow(&self.root).group_id() == group_id)
+            .map(|(pid, _processrc)| *pid)
+            .collect()
+    }
+
     /// Paths of libraries that should be preloaded into managed processes.
     pub fn preload_paths(&self) -> &[PathBuf] {
         &self.preload_paths
@@ -1258,6 +1399,19 @@ mod export {
         &mut *hostrc.futextable_borrow_mut()
     }
 
+    /// Returns a pointer to the Host's FileLockTable.
+    ///
+    /// SAFETY: The returned pointer belongs to and is synchronized by the Host,
+    /// and is invalidated when the Host is no longer accessible to the current
+    /// thread, or something else accesses its FileLockTable.
+    #[no_mangle]
+    pub unsafe extern "C-unwind" fn host_getFileLockTable(
+        hostrc: *const Host,
+    ) -> *mut FileLockTable {
+        let hostrc = unsafe { hostrc.as_ref().unwrap() };
+        &mut *hostrc.file_lock_table_borrow_mut()
+    }
+
     /// Returns the specified process, or NULL if it doesn't exist.
     #[no_mangle]
     pub unsafe extern "C-unwind" fn host_getProcess(
@@ -4,6 +4,7 @@ use std::ffi::CString;
 use std::fmt::Debug;
 use std::fs::File;
 use std::os::raw::c_void;
+use std::os::unix::fs::FileExt;
 use std::os::unix::io::AsRawFd;
 use std::path::PathBuf;
 use std::process;
@@ -126,6 +127,27 @@ impl ShmFile {
         .unwrap();
     }
 
+    /// Copy the data for interval `src` to the same-sized interval starting at `dst_start`. This
+    /// goes through the file rather than through shadow's mappings, which may not be readable or
+    /// writable. The intervals must not overlap.
+    fn copy_within(&self, src: &Interval, dst_start: usize) {
+        // Copy through a bounded buffer, since the interval may be arbitrarily large.
+        const CHUNK_SIZE: usize = 1 << 20;
+        let mut buf = vec![0u8; std::cmp::min(src.len(), CHUNK_SIZE)];
+
+        let mut offset = 0;
+        while offset < src.len() {
+            let len = std::cmp::min(src.len() - offset, CHUNK_SIZE);
+            self.shm_file
+                .read_exact_at(&mut buf[..len], u64::try_from(src.start + offset).unwrap())
+                .unwrap();
+            self.shm_file
+                .write_all_at(&buf[..len], u64::try_from(dst_start + offset).unwrap())
+                .unwrap();
+            offset += len;
+        }
+    }
+
     /// Map the given interval of the file into shadow's address space.
     fn mmap_into_shadow(&self, interval: &Interval, prot: ProtFlags) -> *mut c_void {
         unsafe {
@@ -592,6 +614,11 @@ impl MemoryMapper {
             self.shm_file.mmap_into_plugin(ctx, &interval, prot);
         }
 
+        // File-backed and shared mappings are left as native mappings in the plugin, and we access
+        // them through the plugin's memory rather than through our own mapping. Since the plugin
+        // maps the same underlying file, the kernel keeps `MAP_SHARED` file mappings coherent with
+        // other processes' mappings of the file and with reads and writes through its descriptor.
+        //
         // TODO: We *could* handle file mappings and some shared mappings as well. Doesn't make
         // sense to add that complexity until if/when we see a lot of misses in such regions,
         // though.
@@ -634,6 +661,17 @@ impl MemoryMapper {
         flags: i32,
         new_address: ForeignPtr<u8>,
     ) -> Result<ForeignPtr<u8>, Errno> {
+        if (flags & libc::MREMAP_DONTUNMAP) != 0 {
+            return self.handle_mremap_dontunmap(
+                ctx,
+                old_address,
+                old_size,
+                new_size,
+                flags,
+                new_address,
+            );
+        }
+
         let new_address = {
             let (ctx, thread) = ctx.split_thread();
             thread.native_mremap(&ctx, old_address, old_size, new_size, flags, new_address)?
@@ -752,6 +790,75 @@ impl MemoryMapper {
         Ok(new_address)
     }
 
+    /// Handles `mremap` with `MREMAP_DONTUNMAP`, which moves the pages of a private anonymous
+    /// mapping to a new location but leaves the old range mapped. Afterwards the old range reads
+    /// as zero-filled pages, like a fresh anonymous mapping.
+    ///
+    /// We don't pass this through to the native `mremap`, since the plugin's private anonymous
+    /// mappings are shared mappings of our memory file, and the old and new mappings would end up
+    /// sharing the same pages.
+    fn handle_mremap_dontunmap(
+        &mut self,
+        ctx: &ThreadContext,
+        old_address: ForeignPtr<u8>,
+        old_size: usize,
+        new_size: usize,
+        flags: i32,
+        new_address: ForeignPtr<u8>,
+    ) -> Result<ForeignPtr<u8>, Errno> {
+        // the syscall handler has already checked the flags and sizes
+        assert!((flags & libc::MREMAP_MAYMOVE) != 0);
+        assert_eq!(old_size, new_size);
+
+        let old_interval = usize::from(old_address)..(usize::from(old_address) + old_size);
+
+        // The old range must lie within a single mapping.
+        let region = match self.regions.get(old_interval.start) {
+            Some((interval, region)) if old_interval.end <= interval.end => region.clone(),
+            _ => return Err(Errno::EFAULT),
+        };
+
+        // mremap(2): "EINVAL MREMAP_DONTUNMAP was specified, but one or more pages in the range
+        // specified by old_address and old_size were not private anonymous".
+        if region.sharing != Sharing::Private || region.original_path.is_some() {
+            return Err(Errno::EINVAL);
+        }
+
+        // Private anonymous mappings are always mapped into the memory file.
+        assert!(!region.shadow_base.is_null());
+
+        let mut mmap_flags = MapFlags::MAP_PRIVATE | MapFlags::MAP_ANONYMOUS;
+        if (flags & libc::MREMAP_FIXED) != 0 {
+            let new_interval = usize::from(new_address)..(usize::from(new_address) + new_size);
+            // mremap(2): the new range may not overlap the old range
+            if new_interval.start < old_interval.end && old_interval.start < new_interval.end {
+                return Err(Errno::EINVAL);
+            }
+            mmap_flags |= MapFlags::MAP_FIXED;
+        }
+
+        // Create the new mapping natively so that the OS chooses (or validates) its location, and
+        // then move it into the memory file like any other private anonymous mapping.
+        let new_address = {
+            let (ctx, thread) = ctx.split_thread();
+            thread.native_mmap(&ctx, new_address, new_size, region.prot, mmap_flags, -1, 0)?
+        };
+        self.handle_mmap_result(
+            ctx,
+            ForeignArrayPtr::new(new_address, new_size),
+            region.prot,
+            mmap_flags,
+            -1,
+        );
+
+        // Move the data to the new location, and leave zeroed pages at the old location.
+        self.shm_file
+            .copy_within(&old_interval, usize::from(new_address));
+        self.shm_file.dealloc(&old_interval);
+
+        Ok(new_address)
+    }
+
     /// Execute the requested `brk` and update our mappings accordingly. May invalidate outstanding
     /// pointers. (Rust won't allow mutable methods such as this one to be called with outstanding
     /// borrowed references).
@@ -1007,6 +1114,32 @@ impl MemoryMapper {
         Ok(())
     }
 
+    /// Whether every address in `interval` is part of a region that we know to be mapped in the
+    /// plugin. Adjacent regions are allowed; any gap between them makes the interval unmapped.
+    pub fn is_mapped(&self, interval: &Interval) -> bool {
+        let mut next = interval.start;
+        for (region_interval, _region) in self.regions.iter_from(interval.start) {
+            if next >= interval.end {
+                break;
+            }
+            if region_interval.start > next {
+                // there's a hole before this region
+                return false;
+            }
+            next = region_interval.end;
+        }
+        next >= interval.end
+    }
+
+    /// Whether the region containing `addr` was originally mapped as shared or private by the
+    /// plugin, or `None` if `addr` isn't mapped. Private regions that we've remapped from our
+    /// shared memory file are still reported as private.
+    pub fn sharing(&self, addr: usize) -> Option<Sharing> {
+        self.regions
+            .get(addr)
+            .map(|(_interval, region)| region.sharing)
+    }
+
     // Get a raw pointer to the plugin's memory, if it's been remapped into Shadow.
     // Panics if called with zero-length `src`.
     fn get_mapped_ptr<T: Pod + Debug>(&self, src: ForeignArrayPtr<T>) -> Option<*mut T> {
@@ -15,6 +15,7 @@
 //! all access to process memory must go through it. This includes servicing syscalls that
 //! modify the process address space (such as `mmap`).
 
+use std::cell::RefCell;
 use std::fmt::Debug;
 use std::mem::MaybeUninit;
 use std::ops::{Deref, DerefMut};
@@ -31,6 +32,7 @@ use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
 use super::context::ThreadContext;
 use crate::host::syscall::types::{ForeignArrayPtr, SyscallError};
+use crate::utility::proc_maps::{self, Sharing};
 
 mod memory_copier;
 mod memory_mapper;
@@ -270,7 +272,7 @@ where
     }
 }
 
-fn page_size() -> usize {
+pub fn page_size() -> usize {
     nix::unistd::sysconf(nix::unistd::SysconfVar::PAGE_SIZE)
         .unwrap()
         .unwrap()
@@ -301,10 +303,24 @@ pub struct MemoryManager {
     // accesses.
     memory_mapper: Option<MemoryMapper>,
 
+    // The plugin's shared mappings, read from /proc/[pid]/maps when first needed. Cleared whenever
+    // the plugin's address space changes.
+    shared_mappings: RefCell<Option<Vec<proc_maps::Mapping>>>,
+
     // Native pid of the plugin process.
     pid: Pid,
 }
 
+/// A location in a shared mapping, identified by the mapped object and the offset into it. This is
+/// the same in every process that maps the object.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SharedMemoryLocation {
+    pub device_major: i32,
+    pub device_minor: i32,
+    pub inode: u64,
+    pub offset: usize,
+}
+
 impl MemoryManager {
     /// # Safety
     ///
@@ -325,6 +341,7 @@ impl MemoryManager {
             pid,
             memory_copier: MemoryCopier::new(pid),
             memory_mapper: None,
+            shared_mappings: RefCell::new(None),
         }
     }
 
@@ -597,6 +614,7 @@ impl MemoryManager {
     /// running thread.
     pub fn init_mapper(&mut self, ctx: &ThreadContext) {
         assert!(self.memory_mapper.is_none());
+        self.clear_shared_mappings();
         self.memory_mapper = Some(MemoryMapper::new(self, ctx));
     }
 
@@ -605,6 +623,57 @@ impl MemoryManager {
         self.memory_mapper.is_some()
     }
 
+    /// Whether the plugin memory at `ptr` is in a shared or private mapping. Returns `None` if
+    /// unknown; i.e. if the MemoryMapper isn't in use or `ptr` isn't mapped.
+    pub fn sharing(&self, ptr: ForeignPtr<()>) -> Option<Sharing> {
+        self.memory_mapper.as_ref()?.sharing(usize::from(ptr))
+    }
+
+    /// The location of `ptr` in the object backing its mapping, if `ptr` is in a shared mapping.
+    pub fn shared_memory_location(&self, ptr: ForeignPtr<()>) -> Option<SharedMemoryLocation> {
+        // The memory mapper remaps private regions from its own shared memory file, so they'd look
+        // shared in the process's mappings.
+        if self.sharing(ptr) == Some(Sharing::Private) {
+            return None;
+        }
+
+        let mut shared_mappings = self.shared_mappings.borrow_mut();
+        if shared_mappings.is_none() {
+            let mappings = match proc_maps::mappings_for_pid(self.pid.as_raw_nonzero().get()) {
+                Ok(mappings) => mappings,
+                Err(e) => {
+                    warn!("Couldn't read the memory mappings of {:?}: {e}", self.pid);
+                    return None;
+                }
+            };
+            *shared_mappings = Some(
+                mappings
+                    .into_iter()
+                    .filter(|m| m.sharing == Sharing::Shared)
+                    .collect(),
+            );
+        }
+
+        let addr = usize::from(ptr);
+        let mapping = shared_mappings
+            .as_ref()
+            .unwrap()
+            .iter()
+            .find(|m| m.begin <= addr && addr < m.end)?;
+
+        Some(SharedMemoryLocation {
+            device_major: mapping.device_major,
+            device_minor: mapping.device_minor,
+            inode: mapping.inode,
+            offset: mapping.offset + (addr - mapping.begin),
+        })
+    }
+
+    // Must be called before (or after) any change to the plugin's address space.
+    fn clear_shared_mappings(&mut self) {
+        *self.shared_mappings.get_mut() = None;
+    }
+
     /// Create a write accessor for the specified plugin memory.
     pub fn writer(&mut self, ptr: ForeignArrayPtr<u8>) -> MemoryWriterCursor<'_> {
         MemoryWriterCursor {
@@ -619,6 +688,7 @@ impl MemoryManager {
         ctx: &ThreadContext,
         ptr: ForeignPtr<u8>,
     ) -> Result<ForeignPtr<u8>, SyscallError> {
+        self.clear_shared_mappings();
         match &mut self.memory_mapper {
             Some(mm) => Ok(mm.handle_brk(ctx, ptr)?),
             None => Err(SyscallError::Native),
@@ -635,6 +705,7 @@ impl MemoryManager {
         fd: i32,
         offset: i64,
     ) -> Result<ForeignPtr<u8>, Errno> {
+        self.clear_shared_mappings();
         let addr = {
             let (ctx, thread) = ctx.split_thread();
             thread.native_mmap(&ctx, addr, length, prot, flags, fd, offset)?
@@ -651,6 +722,7 @@ impl MemoryManager {
         addr: ForeignPtr<u8>,
         length: usize,
     ) -> Result<(), SyscallError> {
+        self.clear_shared_mappings();
         if self.memory_mapper.is_some() {
             // Do it ourselves so that we can update our mappings based on
             // whether it succeeded.
@@ -663,12 +735,14 @@ impl MemoryManager {
         }
     }
 
-    fn do_munmap(
+    /// Unmap the range in the plugin, even if we aren't tracking its mappings.
+    pub fn do_munmap(
         &mut self,
         ctx: &ThreadContext,
         addr: ForeignPtr<u8>,
         length: usize,
     ) -> Result<(), Errno> {
+        self.clear_shared_mappings();
         let (ctx, thread) = ctx.split_thread();
         thread.native_munmap(&ctx, addr, length)?;
         if let Some(mm) = &mut self.memory_mapper {
@@ -686,6 +760,7 @@ impl MemoryManager {
         flags: i32,
         new_address: ForeignPtr<u8>,
     ) -> Result<ForeignPtr<u8>, SyscallError> {
+        self.clear_shared_mappings();
         match &mut self.memory_mapper {
             Some(mm) => {
                 Ok(mm.handle_mremap(ctx, old_address, old_size, new_size, flags, new_address)?)
@@ -701,11 +776,49 @@ impl MemoryManager {
         size: usize,
         prot: ProtFlags,
     ) -> Result<(), SyscallError> {
+        self.clear_shared_mappings();
         match &mut self.memory_mapper {
             Some(mm) => Ok(mm.handle_mprotect(ctx, addr, size, prot)?),
             None => Err(SyscallError::Native),
         }
     }
+
+    /// Writes one residency byte per page of `[addr, addr+length)` to `vec`. Every page that we
+    /// know to be mapped is reported as resident (bit 0 set).
+    pub fn handle_mincore(
+        &mut self,
+        addr: ForeignPtr<u8>,
+        length: usize,
+        vec: ForeignPtr<u8>,
+    ) -> Result<(), SyscallError> {
+        let Some(mm) = &self.memory_mapper else {
+            return Err(SyscallError::Native);
+        };
+
+        let start = usize::from(addr);
+        if start % page_size() != 0 {
+            return Err(Errno::EINVAL.into());
+        }
+
+        let num_pages = length.div_ceil(page_size());
+        let Some(end) = num_pages
+            .checked_mul(page_size())
+            .and_then(|len| start.checked_add(len))
+        else {
+            return Err(Errno::ENOMEM.into());
+        };
+
+        if !mm.is_mapped(&(start..end)) {
+            return Err(Errno::ENOMEM.into());
+        }
+
+        // The pages are either remapped into our shared memory file or backed by the plugin's
+        // original mapping, and we don't model swapping, so all of them are resident.
+        let residency = vec![1u8; num_pages];
+        self.copy_to_ptr(ForeignArrayPtr::new(vec, num_pages), &residency)?;
+
+        Ok(())
+    }
 }
 
 /// Memory allocated by Shadow, in a remote address space.
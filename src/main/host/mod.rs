@@ -4,16 +4,21 @@
 //! allows Shadow to intercept their syscalls. It also contains the emulation of Linux hosts,
 //! threads, processes, syscalls, files, network interfaces, etc.
 
+pub mod aio;
 pub mod context;
 pub mod cpu;
 pub mod descriptor;
+pub mod file_lock_table;
 pub mod futex_table;
 #[allow(clippy::module_inception)]
 pub mod host;
 pub mod managed_thread;
 pub mod memory_manager;
 pub mod network;
+pub mod posix_timer;
 pub mod process;
+pub mod sysv_sem_table;
+pub mod sysv_shm_table;
 pub mod status_listener;
 pub mod syscall;
 pub mod thread;
@@ -0,0 +1,110 @@
+//! POSIX per-process interval timers, as created by timer_create(2).
+
+use linux_api::signal::{siginfo_t, sigval, Signal};
+use log::debug;
+
+use super::host::Host;
+use super::process::ProcessId;
+use super::thread::ThreadId;
+use super::timer::Timer;
+
+/// How a [`PosixTimer`] notifies the process when it expires. See sigevent(7).
+#[derive(Copy, Clone)]
+pub enum PosixTimerNotify {
+    /// `SIGEV_NONE`: the expiration isn't reported, but can be observed with timer_gettime(2).
+    None,
+    /// `SIGEV_SIGNAL`: `signal` is sent to the process.
+    Signal { signal: Signal, value: sigval },
+    /// `SIGEV_THREAD_ID`: `signal` is sent to the thread `tid`.
+    ThreadSignal {
+        tid: ThreadId,
+        signal: Signal,
+        value: sigval,
+    },
+}
+
+/// A timer created by timer_create(2).
+pub struct PosixTimer {
+    timer: Timer,
+    notify: PosixTimerNotify,
+    // The number of expirations that occurred while the signal from a previous expiration was still
+    // pending. See timer_getoverrun(2).
+    overrun: i32,
+}
+
+impl PosixTimer {
+    /// Create a new disarmed timer with id `timer_id` for process `pid`.
+    pub fn new(pid: ProcessId, timer_id: i32, notify: PosixTimerNotify) -> Self {
+        Self {
+            timer: Timer::new(move |host| expiration(host, pid, timer_id)),
+            notify,
+            overrun: 0,
+        }
+    }
+
+    pub fn timer(&self) -> &Timer {
+        &self.timer
+    }
+
+    pub fn timer_mut(&mut self) -> &mut Timer {
+        &mut self.timer
+    }
+
+    /// The overrun count, as returned by timer_getoverrun(2).
+    ///
+    /// Linux computes this when the signal is delivered, whereas we count the expirations that
+    /// occur while the signal is still pending. These are the same once the signal has been
+    /// handled.
+    pub fn overrun(&self) -> i32 {
+        self.overrun
+    }
+}
+
+fn expiration(host: &Host, pid: ProcessId, timer_id: i32) {
+    let Some(process) = host.process_borrow(pid) else {
+        debug!("Process {:?} no longer exists", pid);
+        return;
+    };
+    let process = process.borrow(host.root());
+    let Some(runnable) = process.borrow_as_runnable() else {
+        debug!("Process {:?} no longer running", &*process.name());
+        return;
+    };
+
+    let notify = {
+        let mut timers = runnable.posix_timers_borrow_mut();
+        let Some(posix_timer) = timers.get_mut(&timer_id) else {
+            // The timer was deleted after this expiration was scheduled.
+            return;
+        };
+
+        let (tid, signal) = match posix_timer.notify {
+            PosixTimerNotify::None => return,
+            PosixTimerNotify::Signal { signal, .. } => (None, signal),
+            PosixTimerNotify::ThreadSignal { tid, signal, .. } => (Some(tid), signal),
+        };
+
+        // timer_getoverrun(2): "If a timer expires while its signal is still pending from a
+        // previous expiration, then no additional signal is queued; instead, the overrun count is
+        // incremented."
+        if runnable.is_timer_signal_pending(host, tid, signal, timer_id) {
+            posix_timer.overrun = posix_timer.overrun.saturating_add(1);
+            return;
+        }
+
+        posix_timer.overrun = 0;
+        posix_timer.notify
+    };
+
+    match notify {
+        PosixTimerNotify::None => unreachable!(),
+        PosixTimerNotify::Signal { signal, value } => {
+            let siginfo = siginfo_t::new_for_timer_with_sigval(signal, timer_id, 0, value);
+            runnable.signal(host, None, &siginfo);
+        }
+        PosixTimerNotify::ThreadSignal { tid, signal, value } => {
+            let siginfo = siginfo_t::new_for_timer_with_sigval(signal, timer_id, 0, value);
+            runnable.signal_thread(host, tid, &siginfo);
+        }
+    }
+}
@@ -1,24 +1,29 @@
 //! An emulated Linux process.
 
 use std::cell::{Cell, Ref, RefCell, RefMut};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::ffi::{c_char, c_void, CStr, CString};
 use std::fmt::Write;
+use std::hash::{Hash, Hasher};
 use std::num::TryFromIntError;
 use std::ops::{Deref, DerefMut};
 use std::os::fd::AsRawFd;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::Ordering;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 #[cfg(feature = "perf_timers")]
 use std::time::Duration;
 
+use atomic_refcell::AtomicRefCell;
+use linux_api::capability::{user_cap_data, CAP_LAST_CAP};
 use linux_api::errno::Errno;
 use linux_api::fcntl::OFlag;
+use linux_api::personality::{ADDR_NO_RANDOMIZE, PER_LINUX};
 use linux_api::posix_types::Pid;
+use linux_api::resource::{rlimit64, Resource, RLIM_NLIMITS};
 use linux_api::sched::{CloneFlags, SuidDump};
 use linux_api::signal::{
-    defaultaction, siginfo_t, sigset_t, LinuxDefaultAction, SigActionFlags, Signal,
+    defaultaction, siginfo_t, sigset_t, LinuxDefaultAction, SigActionFlags, SigInfoDetails, Signal,
     SignalFromI32Error,
 };
 use log::{debug, trace, warn};
@@ -33,12 +38,17 @@ use shadow_shim_helper_rs::syscall_types::{ForeignPtr, ManagedPhysicalMemoryAddr
 use shadow_shim_helper_rs::HostId;
 use shadow_shmem::allocator::ShMemBlock;
 
+use super::aio::AioContext;
 use super::descriptor::descriptor_table::{DescriptorHandle, DescriptorTable};
 use super::descriptor::listener::StateEventSource;
+use super::descriptor::pidfd::PidFd;
+use super::descriptor::signalfd::SignalFd;
 use super::descriptor::{FileSignals, FileState};
 use super::host::Host;
 use super::memory_manager::{MemoryManager, ProcessMemoryRef, ProcessMemoryRefMut};
+use super::posix_timer::PosixTimer;
 use super::syscall::formatter::StraceFmtMode;
+use super::syscall::summary::SyscallSummary;
 use super::syscall::types::ForeignArrayPtr;
 use super::thread::{Thread, ThreadId};
 use super::timer::Timer;
@@ -130,6 +140,86 @@ pub enum ExitStatus {
     StoppedByShadow,
 }
 
+/// A job-control state change of a process that its parent can wait for, e.g.
+/// with `waitid(2)` and `WSTOPPED` or `WCONTINUED`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum JobControlEvent {
+    /// The process was stopped by the given signal.
+    Stopped(Signal),
+    /// The process was resumed by `SIGCONT`.
+    Continued,
+}
+
+impl JobControlEvent {
+    /// Construct a siginfo describing this event for the process `pid`. Used
+    /// to notify the parent process, and by the `waitid` syscall handler.
+    pub fn siginfo(&self, signal: Signal, pid: ProcessId) -> siginfo_t {
+        match self {
+            JobControlEvent::Stopped(stop_signal) => {
+                siginfo_t::new_for_sigchld_stopped(signal, pid.into(), 0, *stop_signal, 0, 0)
+            }
+            JobControlEvent::Continued => {
+                siginfo_t::new_for_sigchld_continued(signal, pid.into(), 0, 0, 0)
+            }
+        }
+    }
+
+    /// The corresponding `wstatus` value, as returned e.g. by `wait4(2)`.
+    pub fn wait_status(&self) -> i32 {
+        match self {
+            // `WIFSTOPPED` and `WSTOPSIG`
+            JobControlEvent::Stopped(s) => (i32::from(*s) << 8) | 0x7f,
+            // `WIFCONTINUED`
+            JobControlEvent::Continued => 0xffff,
+        }
+    }
+}
+
+/// A process's capability sets, as manipulated by `capget(2)` and `capset(2)`. Each set is a
+/// bitmask indexed by capability number.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Capabilities {
+    pub effective: u64,
+    pub permitted: u64,
+    pub inheritable: u64,
+}
+
+impl Capabilities {
+    /// All capabilities known to the kernel.
+    pub const ALL: u64 = (1 << (CAP_LAST_CAP + 1)) - 1;
+
+    /// The capabilities of a new process. Like linux, a root process has all capabilities in its
+    /// effective and permitted sets, and any other process has none.
+    pub fn new(is_root: bool) -> Self {
+        let caps = if is_root { Self::ALL } else { 0 };
+        Self {
+            effective: caps,
+            permitted: caps,
+            inheritable: 0,
+        }
+    }
+
+    /// Convert from the `_LINUX_CAPABILITY_VERSION_3` data format, ignoring unknown capabilities.
+    pub fn from_user_data(data: &[user_cap_data; 2]) -> Self {
+        let join = |lo: u32, hi: u32| ((u64::from(hi) << 32) | u64::from(lo)) & Self::ALL;
+        Self {
+            effective: join(data[0].effective, data[1].effective),
+            permitted: join(data[0].permitted, data[1].permitted),
+            inheritable: join(data[0].inheritable, data[1].inheritable),
+        }
+    }
+
+    /// Convert to the `_LINUX_CAPABILITY_VERSION_3` data format.
+    pub fn to_user_data(&self) -> [user_cap_data; 2] {
+        let half = |shift: u32| user_cap_data {
+            effective: (self.effective >> shift) as u32,
+            permitted: (self.permitted >> shift) as u32,
+            inheritable: (self.inheritable >> shift) as u32,
+        };
+        [half(0), half(32)]
+    }
+}
+
 #[derive(Debug)]
 struct StraceLogging {
     file: RootedRefCell<std::fs::File>,
@@ -173,22 +263,10 @@ impl Common {
 
     fn physical_address(&self, vptr: ForeignPtr<()>) -> ManagedPhysicalMemoryAddr {
         // We currently don't keep a true system-wide virtual <-> physical address
-        // mapping. Instead we simply assume that no shadow processes map the same
-        // underlying physical memory, and that therefore (pid, virtual address)
-        // uniquely defines a physical address.
-        //
-        // If we ever want to support futexes in memory shared between processes,
-        // we'll need to change this.  The most foolproof way to do so is probably
-        // to change ManagedPhysicalMemoryAddr to be a bigger struct that identifies where
-        // the mapped region came from (e.g. what file), and the offset into that
-        // region. Such "fat" physical pointers might make memory management a
-        // little more cumbersome though, e.g. when using them as keys in the futex
-        // table.
-        //
-        // Alternatively we could hash the region+offset to a 64-bit value, but
-        // then we'd need to deal with potential collisions. On average we'd expect
-        // a collision after 2**32 physical addresses; i.e. they *probably*
-        // wouldn't happen in practice for realistic simulations.
+        // mapping. Instead we simply assume that the memory at `vptr` isn't shared
+        // with any other process, and that therefore (pid, virtual address)
+        // uniquely defines a physical address. Addresses that may be shared between
+        // processes should use `RunnableProcess::futex_physical_address` instead.
 
         // Linux uses the bottom 48-bits for user-space virtual addresses, giving
         // us 16 bits for the pid.
@@ -237,6 +315,10 @@ pub struct RunnableProcess {
     // Shared with forked Processes
     strace_logging: Option<Arc<StraceLogging>>,
 
+    // Per-syscall counts and times, written to the strace file when the process exits. Only
+    // present if the strace summary is enabled.
+    syscall_summary: Option<RefCell<SyscallSummary>>,
+
     // The shim's log file. This gets dup'd into the ManagedProcess
     // where the shim can write to it directly. We persist it to handle the case
     // where we need to recreatea a ManagedProcess and have it continue writing
@@ -249,6 +331,20 @@ pub struct RunnableProcess {
     // and PR_GET_DUMPABLE.
     dumpable: Cell<SuidDump>,
 
+    // The "keep capabilities" flag, as manipulated via the prctl operations PR_SET_KEEPCAPS
+    // and PR_GET_KEEPCAPS.
+    keepcaps: Cell<bool>,
+
+    // The process's execution domain and flags, as manipulated via personality(2).
+    personality: Cell<u32>,
+
+    // The process's capability sets, as manipulated via capget(2) and capset(2).
+    capabilities: Cell<Capabilities>,
+
+    // The signal to send to this process when its parent dies, as manipulated via the prctl
+    // operations PR_SET_PDEATHSIG and PR_GET_PDEATHSIG.
+    parent_death_signal: Cell<Option<Signal>>,
+
     native_pid: Pid,
 
     // timer that tracks the amount of CPU time we spend on plugin execution and processing
@@ -259,6 +355,16 @@ pub struct RunnableProcess {
 
     itimer_real: RefCell<Timer>,
 
+    // Timers created with timer_create(2), keyed by timer id.
+    posix_timers: RefCell<BTreeMap<i32, PosixTimer>>,
+
+    // AIO contexts created with io_setup(2), keyed by context id.
+    aio_contexts: RefCell<BTreeMap<u64, AioContext>>,
+
+    // Resource limits, indexed by `Resource`. Only `RLIMIT_NOFILE` is enforced; the others are
+    // stored so that they can be returned by getrlimit(2).
+    rlimits: RefCell<[rlimit64; RLIM_NLIMITS]>,
+
     // The `RootedRc` lets us hold a reference to a thread without holding a
     // reference to the thread list. e.g. this lets us implement the `clone`
     // syscall, which adds a thread to the list while we have a reference to the
@@ -285,6 +391,24 @@ pub struct RunnableProcess {
     // Listeners for child-events.
     // e.g. these listeners are notified when a child of this process exits.
     child_process_event_listeners: RefCell<StateEventSource>,
+
+    // Signalfds that may be interested in signals sent to this process or its threads.
+    signalfds: RefCell<Vec<Weak<AtomicRefCell<SignalFd>>>>,
+
+    // Pidfds referring to this process, which are notified when it exits.
+    pidfds: RefCell<Vec<Weak<AtomicRefCell<PidFd>>>>,
+
+    // The signal that stopped this process, if it's currently stopped by a
+    // job-control signal such as `SIGSTOP`.
+    stop_signal: Cell<Option<Signal>>,
+
+    // Threads that were scheduled to resume while the process was stopped.
+    // They're resumed when the process is continued.
+    stopped_thread_resumes: RefCell<BTreeSet<ThreadId>>,
+
+    // The most recent job-control state change that the parent hasn't waited
+    // for yet.
+    job_control_event: Cell<Option<JobControlEvent>>,
 }
 
 impl RunnableProcess {
@@ -334,11 +458,10 @@ impl RunnableProcess {
                 .write(clear_child_tid_pvp, &0)
                 .unwrap();
 
-            // Wake the corresponding futex.
+            // Wake the corresponding futex. Like Linux, this is a wake on a (potentially)
+            // process-shared futex.
+            let addr = self.futex_physical_address(clear_child_tid_pvp.cast::<()>(), false);
             let futexes = host.futextable_borrow();
-            let addr = self
-                .common
-                .physical_address(clear_child_tid_pvp.cast::<()>());
 
             if let Some(futex) = futexes.get(addr) {
                 futex.wake(1);
@@ -400,10 +523,69 @@ impl RunnableProcess {
         .unwrap()
     }
 
+    /// Whether syscalls should be recorded in the process's syscall summary.
+    pub fn has_syscall_summary(&self) -> bool {
+        self.syscall_summary.is_some()
+    }
+
+    /// Run `f` with the process's syscall summary, if enabled.
+    pub fn with_syscall_summary<T>(&self, f: impl FnOnce(&mut SyscallSummary) -> T) -> Option<T> {
+        let summary = self.syscall_summary.as_ref()?;
+        Some(f(&mut summary.borrow_mut()))
+    }
+
+    /// Write the syscall summary, if enabled, to the strace file.
+    fn write_syscall_summary(&self, host: &Host) {
+        let (Some(summary), Some(strace_logging)) = (&self.syscall_summary, &self.strace_logging)
+        else {
+            return;
+        };
+
+        // the time is non-deterministic, so only include it in the standard mode
+        let with_time = strace_logging.options == FmtOptions::Standard;
+        let summary = format!(
+            "Syscall summary for process {} ({}):\n{}",
+            self.common.id,
+            self.common.name(),
+            summary.borrow().to_table(with_time),
+        );
+
+        let mut file = strace_logging.file.borrow_mut(host.root());
+        if let Err(e) = std::io::Write::write_all(&mut *file, summary.as_bytes()) {
+            warn!("Failed to write the syscall summary: {e}");
+        }
+    }
+
     pub fn native_pid(&self) -> Pid {
         self.native_pid
     }
 
+    /// Convert the virtual address of a futex word to a physical address that uniquely identifies
+    /// the futex. Unless the futex is private to the process, if `vptr` is in a shared mapping the
+    /// returned address identifies the underlying mapped object and the offset into it, so that
+    /// every process mapping the same memory gets the same address.
+    pub fn futex_physical_address(
+        &self,
+        vptr: ForeignPtr<()>,
+        is_private: bool,
+    ) -> ManagedPhysicalMemoryAddr {
+        if is_private {
+            return self.common.physical_address(vptr);
+        }
+
+        let Some(location) = self.memory_manager.borrow().shared_memory_location(vptr) else {
+            return self.common.physical_address(vptr);
+        };
+
+        // We hash the mapped object and offset down to 48 bits, leaving the "pid" bits of the
+        // address zero so that it can't collide with an unshared address (there is no pid 0). We
+        // could in theory get a collision between two shared addresses, but on average we'd
+        // expect one only after 2**24 of them.
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        location.hash(&mut hasher);
+        ManagedPhysicalMemoryAddr::from(hasher.finish() & ((1 << 48) - 1))
+    }
+
     #[track_caller]
     fn first_live_thread(&self, root: &Root) -> Option<Ref<RootedRc<RootedRefCell<Thread>>>> {
         Ref::filter_map(self.threads.borrow(), |threads| {
@@ -521,37 +703,70 @@ impl RunnableProcess {
             Err(SignalFromI32Error(n)) => panic!("Bad signo {n}"),
         };
 
+        if signal == Signal::SIGCONT || signal == Signal::SIGKILL {
+            // `signal(7)`: SIGCONT continues a stopped process regardless of
+            // its disposition, and SIGKILL must be able to kill it.
+            self.continue_process(host, current_thread, signal);
+        }
+
         // Scope for `process_shmem_protected`
-        {
+        let stop = {
             let host_shmem = host.shim_shmem_lock_borrow().unwrap();
-            let mut process_shmem_protected = self
+            let process_shmem_protected = self
                 .shim_shared_mem_block
                 .protected
-                .borrow_mut(&host_shmem.root);
+                .borrow(&host_shmem.root);
             // SAFETY: We don't try to call any of the function pointers.
             let action = unsafe { process_shmem_protected.signal_action(signal) };
             match unsafe { action.handler() } {
-                linux_api::signal::SignalHandler::Handler(_) => (),
-                linux_api::signal::SignalHandler::Action(_) => (),
+                linux_api::signal::SignalHandler::Handler(_) => false,
+                linux_api::signal::SignalHandler::Action(_) => false,
                 linux_api::signal::SignalHandler::SigIgn => return,
-                linux_api::signal::SignalHandler::SigDfl => {
-                    if defaultaction(signal) == LinuxDefaultAction::IGN {
-                        return;
-                    }
-                }
+                linux_api::signal::SignalHandler::SigDfl => match defaultaction(signal) {
+                    // The process was already continued above.
+                    LinuxDefaultAction::IGN | LinuxDefaultAction::CONT => return,
+                    LinuxDefaultAction::STOP => true,
+                    LinuxDefaultAction::TERM | LinuxDefaultAction::CORE => false,
+                },
             }
+        };
+        if stop {
+            // Stopping is handled here rather than by delivering the signal to
+            // the shim.
+            self.stop_process(host, current_thread, signal);
+            return;
+        }
 
-            if process_shmem_protected.pending_signals.has(signal) {
-                // Signal is already pending. From signal(7):In the case where a
-                // standard signal is already pending, the siginfo_t structure (see
-                // sigaction(2)) associated with that signal is not overwritten on
-                // arrival of subsequent instances of the same signal.
-                return;
+        // Scope for `process_shmem_protected`
+        {
+            let host_shmem = host.shim_shmem_lock_borrow().unwrap();
+            let mut process_shmem_protected = self
+                .shim_shared_mem_block
+                .protected
+                .borrow_mut(&host_shmem.root);
+
+            if signal.is_realtime() {
+                // signal(7): Multiple instances of real-time signals can be
+                // queued.
+                if let Err(e) = process_shmem_protected.queue_realtime_siginfo(siginfo_t) {
+                    debug!("Dropping {signal:?}: {e:?}");
+                    return;
+                }
+            } else {
+                if process_shmem_protected.pending_signals.has(signal) {
+                    // Signal is already pending. From signal(7):In the case where a
+                    // standard signal is already pending, the siginfo_t structure (see
+                    // sigaction(2)) associated with that signal is not overwritten on
+                    // arrival of subsequent instances of the same signal.
+                    return;
+                }
+                process_shmem_protected.pending_signals.add(signal);
+                process_shmem_protected.set_pending_standard_siginfo(signal, siginfo_t);
             }
-            process_shmem_protected.pending_signals.add(signal);
-            process_shmem_protected.set_pending_standard_siginfo(signal, siginfo_t);
         }
 
+        self.notify_signalfds(signal);
+
         if let Some(thread) = current_thread {
             if thread.process_id() == self.common.id() {
                 let host_shmem = host.shim_shmem_lock_borrow().unwrap();
@@ -568,6 +783,311 @@ impl RunnableProcess {
         self.interrupt_with_signal(host, signal);
     }
 
+    /// Stop the process for the job-control signal `signal`. Its threads won't
+    /// run again until it's continued by `SIGCONT` or killed by `SIGKILL`.
+    fn stop_process(&self, host: &Host, current_thread: Option<&Thread>, signal: Signal) {
+        if self.stop_signal.get().is_some() {
+            return;
+        }
+        debug!("Stopping process {:?} with {signal:?}", self.common.id());
+        // TODO: If the current thread belongs to this process (e.g. it called
+        // `raise(SIGSTOP)`), it keeps running until it next blocks.
+        self.stop_signal.set(Some(signal));
+        self.notify_parent_of_job_control_event(
+            host,
+            current_thread,
+            JobControlEvent::Stopped(signal),
+        );
+    }
+
+    /// Continue the process if it's stopped, rescheduling any threads whose
+    /// resumption was deferred. The parent is only notified if `signal` is
+    /// `SIGCONT`.
+    fn continue_process(&self, host: &Host, current_thread: Option<&Thread>, signal: Signal) {
+        if self.stop_signal.take().is_none() {
+            return;
+        }
+        debug!("Continuing process {:?} with {signal:?}", self.common.id());
+        let pid = self.common.id();
+        for tid in std::mem::take(&mut *self.stopped_thread_resumes.borrow_mut()) {
+            let task = TaskRef::new(move |host| host.resume(pid, tid));
+            host.schedule_task_with_delay(task, SimulationTime::ZERO);
+        }
+        if signal == Signal::SIGCONT {
+            self.notify_parent_of_job_control_event(
+                host,
+                current_thread,
+                JobControlEvent::Continued,
+            );
+        }
+    }
+
+    /// Record `event` for the parent to wait for, and notify the parent.
+    fn notify_parent_of_job_control_event(
+        &self,
+        host: &Host,
+        current_thread: Option<&Thread>,
+        event: JobControlEvent,
+    ) {
+        self.job_control_event.set(Some(event));
+
+        let parent_pid = self.common.parent_pid.get();
+        if parent_pid == ProcessId::INIT {
+            trace!("Not notifying parent of {event:?}: parent is 'init'");
+            return;
+        }
+        let Some(parent_rc) = host.process_borrow(parent_pid) else {
+            trace!("Not notifying parent of {event:?}: parent {parent_pid:?} not found");
+            return;
+        };
+        let parent = parent_rc.borrow(host.root());
+        let Some(parent_runnable) = parent.as_runnable() else {
+            trace!("Not notifying parent of {event:?}: {parent_pid:?} not running");
+            return;
+        };
+
+        // `sigaction(2)`: If signum is SIGCHLD, do not receive notification
+        // when child processes stop [...] or resume.
+        let nocldstop = {
+            let host_shmem = host.shim_shmem_lock_borrow().unwrap();
+            let parent_shmem_protected = parent_runnable
+                .shim_shared_mem_block
+                .protected
+                .borrow(&host_shmem.root);
+            // SAFETY: We don't dereference function pointers.
+            let action = unsafe { parent_shmem_protected.signal_action(Signal::SIGCHLD) };
+            action.flags_retain().contains(SigActionFlags::SA_NOCLDSTOP)
+        };
+        if !nocldstop {
+            let siginfo = event.siginfo(Signal::SIGCHLD, self.common.id());
+            parent_runnable.signal(host, current_thread, &siginfo);
+        }
+
+        CallbackQueue::queue_and_run_with_legacy(|q| {
+            let mut parent_child_listeners =
+                parent_runnable.child_process_event_listeners.borrow_mut();
+            parent_child_listeners.notify_listeners(
+                FileState::CHILD_EVENT,
+                FileState::CHILD_EVENT,
+                FileSignals::empty(),
+                q,
+            );
+        });
+    }
+
+    /// The most recent job-control state change that the parent hasn't waited
+    /// for yet, if any.
+    pub fn job_control_event(&self) -> Option<JobControlEvent> {
+        self.job_control_event.get()
+    }
+
+    /// Consume the pending job-control state change, e.g. when the parent
+    /// waits for it without `WNOWAIT`.
+    pub fn take_job_control_event(&self) -> Option<JobControlEvent> {
+        self.job_control_event.take()
+    }
+
+    /// Send the signal described in `siginfo` to the thread `tid` of this process. Intended for use
+    /// when no thread is currently running, e.g. from a timer expiration event.
+    pub fn signal_thread(&self, host: &Host, tid: ThreadId, siginfo: &siginfo_t) {
+        let signal = match siginfo.signal() {
+            Ok(s) => s,
+            Err(SignalFromI32Error(0)) => return,
+            Err(SignalFromI32Error(n)) => panic!("Bad signo {n}"),
+        };
+
+        let threads = self.threads.borrow();
+        let Some(thread) = threads.get(&tid) else {
+            debug!("Thread {tid:?} no longer exists");
+            return;
+        };
+        let thread = thread.borrow(host.root());
+
+        // Scope for the shmem lock, since `wakeup_for_signal` takes its own.
+        let blocked = {
+            let host_shmem = host.shim_shmem_lock_borrow().unwrap();
+            let process_shmem_protected = self
+                .shim_shared_mem_block
+                .protected
+                .borrow(&host_shmem.root);
+            let thread_shmem = thread.shmem();
+            let mut thread_shmem_protected = thread_shmem.protected.borrow_mut(&host_shmem.root);
+
+            // SAFETY: We don't try to call any of the function pointers.
+            let action = unsafe { process_shmem_protected.signal_action(signal) };
+            let ignored = match unsafe { action.handler() } {
+                linux_api::signal::SignalHandler::SigIgn => true,
+                linux_api::signal::SignalHandler::SigDfl => {
+                    defaultaction(signal) == LinuxDefaultAction::IGN
+                }
+                _ => false,
+            };
+            if ignored {
+                return;
+            }
+
+            if signal.is_realtime() {
+                // signal(7): Multiple instances of real-time signals can be
+                // queued.
+                if let Err(e) = thread_shmem_protected.queue_realtime_siginfo(siginfo) {
+                    debug!("Dropping {signal:?}: {e:?}");
+                    return;
+                }
+            } else {
+                if thread_shmem_protected.pending_signals.has(signal) {
+                    return;
+                }
+                thread_shmem_protected.pending_signals.add(signal);
+                thread_shmem_protected.set_pending_standard_siginfo(signal, siginfo);
+            }
+            thread_shmem_protected.blocked_signals.has(signal)
+        };
+
+        self.notify_signalfds(signal);
+
+        if blocked {
+            // It'll be processed when the thread unblocks the signal.
+            return;
+        }
+
+        // We may get `None` here if the thread hasn't run yet. The signal will be delivered when
+        // it does.
+        if let Some(mut cond) = thread.syscall_condition_mut() {
+            cond.wakeup_for_signal(host, signal);
+        }
+    }
+
+    /// Whether the `signal` sent by the POSIX timer `timer_id` is still pending for the thread
+    /// `tid`, or for the process if `tid` is `None`.
+    pub fn is_timer_signal_pending(
+        &self,
+        host: &Host,
+        tid: Option<ThreadId>,
+        signal: Signal,
+        timer_id: i32,
+    ) -> bool {
+        // Another instance of a realtime signal may have been queued by someone else (e.g. glibc
+        // sends all `SIGEV_THREAD` timer signals to the same thread with the same signal), so look
+        // for this timer's instance specifically.
+        let is_timer_siginfo = |info: &siginfo_t| {
+            if !matches!(info.signal(), Ok(s) if s == signal) {
+                return false;
+            }
+            // SAFETY: We don't dereference any pointers.
+            let details = unsafe { info.details() };
+            matches!(details, Some(SigInfoDetails::Timer(timer)) if timer.l_tid == timer_id)
+        };
+
+        let host_shmem = host.shim_shmem_lock_borrow().unwrap();
+        match tid {
+            None => {
+                let protected = self
+                    .shim_shared_mem_block
+                    .protected
+                    .borrow(&host_shmem.root);
+                if signal.is_realtime() {
+                    protected
+                        .queued_realtime_siginfos()
+                        .iter()
+                        .any(is_timer_siginfo)
+                } else {
+                    protected.pending_signals.has(signal)
+                }
+            }
+            Some(tid) => {
+                let threads = self.threads.borrow();
+                let Some(thread) = threads.get(&tid) else {
+                    return false;
+                };
+                let thread = thread.borrow(host.root());
+                let thread_shmem = thread.shmem();
+                let protected = thread_shmem.protected.borrow(&host_shmem.root);
+                if signal.is_realtime() {
+                    protected
+                        .queued_realtime_siginfos()
+                        .iter()
+                        .any(is_timer_siginfo)
+                } else {
+                    protected.pending_signals.has(signal)
+                }
+            }
+        }
+    }
+
+    /// The timers created with timer_create(2), keyed by timer id.
+    pub fn posix_timers_borrow(&self) -> impl Deref<Target = BTreeMap<i32, PosixTimer>> + '_ {
+        self.posix_timers.borrow()
+    }
+
+    /// The timers created with timer_create(2), keyed by timer id.
+    pub fn posix_timers_borrow_mut(
+        &self,
+    ) -> impl DerefMut<Target = BTreeMap<i32, PosixTimer>> + '_ {
+        self.posix_timers.borrow_mut()
+    }
+
+    /// The AIO contexts created with io_setup(2), keyed by context id.
+    pub fn aio_contexts_borrow_mut(
+        &self,
+    ) -> impl DerefMut<Target = BTreeMap<u64, AioContext>> + '_ {
+        self.aio_contexts.borrow_mut()
+    }
+
+    /// The soft and hard limits for `resource`.
+    pub fn rlimit(&self, resource: Resource) -> rlimit64 {
+        self.rlimits.borrow()[usize::try_from(u32::from(resource)).unwrap()]
+    }
+
+    /// Set the soft and hard limits for `resource`. The caller is responsible for validating the
+    /// new limits. Setting `RLIMIT_NOFILE` updates the descriptor tables of the process's threads.
+    pub fn set_rlimit(&self, host: &Host, resource: Resource, limit: rlimit64) {
+        self.rlimits.borrow_mut()[usize::try_from(u32::from(resource)).unwrap()] = limit;
+
+        if resource == Resource::RLIMIT_NOFILE {
+            for thread in self.threads.borrow().values() {
+                let thread = thread.borrow(host.root());
+                thread
+                    .descriptor_table_borrow_mut(host)
+                    .set_fd_limit(limit.rlim_cur);
+            }
+        }
+    }
+
+    /// Register a signalfd so that it's notified when a signal becomes pending for this process or
+    /// one of its threads.
+    pub fn add_signalfd(&self, signalfd: &Arc<AtomicRefCell<SignalFd>>) {
+        let mut signalfds = self.signalfds.borrow_mut();
+        signalfds.retain(|x| x.strong_count() > 0);
+        signalfds.push(Arc::downgrade(signalfd));
+    }
+
+    /// Register a pidfd that refers to this process, so that it becomes readable when this process
+    /// exits.
+    pub fn add_pidfd(&self, pidfd: &Arc<AtomicRefCell<PidFd>>) {
+        let mut pidfds = self.pidfds.borrow_mut();
+        pidfds.retain(|x| x.strong_count() > 0);
+        pidfds.push(Arc::downgrade(pidfd));
+    }
+
+    /// Notify this process's signalfds that `signal` is now pending, so that they can update their
+    /// readable state.
+    pub fn notify_signalfds(&self, signal: Signal) {
+        let signalfds: Vec<_> = self
+            .signalfds
+            .borrow()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect();
+        if signalfds.is_empty() {
+            return;
+        }
+        CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+            for signalfd in signalfds {
+                signalfd.borrow_mut().signal_pending(signal, cb_queue);
+            }
+        });
+    }
+
     /// Adds a new thread to the process and schedules it to run.
     /// Intended for use by `clone`.
     pub fn add_thread(&self, host: &Host, thread: RootedRc<RootedRefCell<Thread>>) {
@@ -607,6 +1127,10 @@ impl RunnableProcess {
         let plugin_name = self.common.plugin_name.clone();
         let name = make_name(host, plugin_name.to_str().unwrap(), pid);
 
+        // `fork(2)`: the child inherits attached System V shared memory segments
+        host.sysv_shm_table_borrow_mut()
+            .fork_process(self.common.id, pid);
+
         let parent_pid = if flags.contains(CloneFlags::CLONE_PARENT) {
             self.common.parent_pid.get()
         } else {
@@ -635,6 +1159,12 @@ impl RunnableProcess {
         // though it might be tricky to map those back to processes.
         let strace_logging = self.strace_logging.as_ref().cloned();
 
+        // The child gets its own summary, written when it exits.
+        let syscall_summary = self
+            .syscall_summary
+            .as_ref()
+            .map(|_| RefCell::new(SyscallSummary::new()));
+
         // `fork(2)`:
         //  > The child does not inherit timers from its parent
         //  > (setitimer(2), alarm(2), timer_create(2)).
@@ -657,18 +1187,36 @@ impl RunnableProcess {
             expected_final_state: None,
             shim_shared_mem_block,
             strace_logging,
+            syscall_summary,
             dumpable: self.dumpable.clone(),
+            keepcaps: self.keepcaps.clone(),
+            personality: self.personality.clone(),
+            capabilities: self.capabilities.clone(),
+            // `prctl(2)`: the parent-death signal is cleared for the child of a fork
+            parent_death_signal: Cell::new(None),
             native_pid,
             #[cfg(feature = "perf_timers")]
             cpu_delay_timer: RefCell::new(PerfTimer::new()),
             #[cfg(feature = "perf_timers")]
             total_run_time: Cell::new(Duration::ZERO),
             itimer_real,
+            posix_timers: Default::default(),
+            // `fork(2)`: "The child does not inherit outstanding asynchronous I/O operations from
+            // its parent"
+            aio_contexts: Default::default(),
+            // `fork(2)`: resource limits are preserved
+            rlimits: RefCell::new(*self.rlimits.borrow()),
             threads,
             unsafe_borrow_mut: RefCell::new(None),
             unsafe_borrows: RefCell::new(Vec::new()),
             memory_manager: Box::new(RefCell::new(unsafe { MemoryManager::new(native_pid) })),
             child_process_event_listeners: Default::default(),
+            // The child inherits the parent's file descriptors, including any signalfds.
+            signalfds: RefCell::new(self.signalfds.borrow().clone()),
+            pidfds: Default::default(),
+            stop_signal: Cell::new(None),
+            stopped_thread_resumes: Default::default(),
+            job_control_event: Cell::new(None),
             shimlog_file: self.shimlog_file.clone(),
         };
         let child_process = Process {
@@ -880,6 +1428,23 @@ pub struct Process {
     state: RefCell<Option<ProcessState>>,
 }
 
+/// Get the resource limits of the shadow process.
+fn native_rlimits() -> [rlimit64; RLIM_NLIMITS] {
+    std::array::from_fn(|resource| {
+        let mut rlim = libc::rlimit64 {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        let resource = resource.try_into().unwrap();
+        let rv = unsafe { libc::prlimit64(0, resource, std::ptr::null(), &mut rlim) };
+        assert_eq!(rv, 0, "prlimit64: {}", std::io::Error::last_os_error());
+        rlimit64 {
+            rlim_cur: rlim.rlim_cur,
+            rlim_max: rlim.rlim_max,
+        }
+    })
+}
+
 fn itimer_real_expiration(host: &Host, pid: ProcessId) {
     let Some(process) = host.process_borrow(pid) else {
         debug!("Process {:?} no longer exists", pid);
@@ -959,10 +1524,13 @@ impl Process {
         let main_thread_id = host.get_new_thread_id();
         let process_id = ProcessId::from(main_thread_id);
 
-        let desc_table = RootedRc::new(
-            host.root(),
-            RootedRefCell::new(host.root(), DescriptorTable::new()),
-        );
+        // Managed processes inherit Shadow's resource limits.
+        let rlimits = native_rlimits();
+
+        let mut desc_table = DescriptorTable::new();
+        let nofile = usize::try_from(u32::from(Resource::RLIMIT_NOFILE)).unwrap();
+        desc_table.set_fd_limit(rlimits[nofile].rlim_cur);
+        let desc_table = RootedRc::new(host.root(), RootedRefCell::new(host.root(), desc_table));
         let itimer_real = RefCell::new(Timer::new(move |host| {
             itimer_real_expiration(host, process_id)
         }));
@@ -988,6 +1556,9 @@ impl Process {
             })
         });
 
+        let syscall_summary = (strace_logging.is_some() && host.params.strace_summary)
+            .then(|| RefCell::new(SyscallSummary::new()));
+
         let shim_shared_mem = ProcessShmem::new(
             &host.shim_shmem_lock_borrow().unwrap().root,
             host.shim_shmem().serialize(),
@@ -1057,8 +1628,15 @@ impl Process {
             host.preload_paths(),
         )?;
         let native_pid = mthread.native_pid();
+        // Like linux, the thread is initially named after the executable's filename.
+        let comm = plugin_path
+            .to_bytes()
+            .rsplit(|c| *c == b'/')
+            .next()
+            .unwrap();
         let main_thread =
-            Thread::wrap_mthread(host, mthread, desc_table, process_id, main_thread_id).unwrap();
+            Thread::wrap_mthread(host, mthread, desc_table, process_id, main_thread_id, comm)
+                .unwrap();
 
         debug!("process '{:?}' started", plugin_name);
 
@@ -1118,8 +1696,21 @@ impl Process {
                         shim_shared_mem_block,
                         memory_manager: Box::new(RefCell::new(memory_manager)),
                         itimer_real,
+                        posix_timers: Default::default(),
+                        aio_contexts: Default::default(),
+                        rlimits: RefCell::new(rlimits),
+                        syscall_summary,
                         strace_logging,
                         dumpable: Cell::new(SuidDump::SUID_DUMP_USER),
+                        keepcaps: Cell::new(false),
+                        // Shadow's address space layout is already deterministic, and the native
+                        // process inherits `ADDR_NO_RANDOMIZE` from Shadow.
+                        personality: Cell::new(PER_LINUX | ADDR_NO_RANDOMIZE),
+                        // The managed process runs with Shadow's credentials.
+                        capabilities: Cell::new(Capabilities::new(
+                            rustix::process::geteuid().is_root(),
+                        )),
+                        parent_death_signal: Cell::new(None),
                         native_pid,
                         unsafe_borrow_mut: RefCell::new(None),
                         unsafe_borrows: RefCell::new(Vec::new()),
@@ -1129,6 +1720,11 @@ impl Process {
                         #[cfg(feature = "perf_timers")]
                         total_run_time: Cell::new(Duration::ZERO),
                         child_process_event_listeners: Default::default(),
+                        signalfds: Default::default(),
+                        pidfds: Default::default(),
+                        stop_signal: Cell::new(None),
+                        stopped_thread_resumes: Default::default(),
+                        job_control_event: Cell::new(None),
                         shimlog_file,
                     }))),
                 },
@@ -1181,6 +1777,67 @@ impl Process {
         self.as_runnable().unwrap().dumpable.set(val)
     }
 
+    /// Get process's "keep capabilities" flag, as manipulated by the prctl operations
+    /// `PR_SET_KEEPCAPS` and `PR_GET_KEEPCAPS`.
+    pub fn keepcaps(&self) -> bool {
+        self.as_runnable().unwrap().keepcaps.get()
+    }
+
+    /// Set process's "keep capabilities" flag, as manipulated by the prctl operations
+    /// `PR_SET_KEEPCAPS` and `PR_GET_KEEPCAPS`.
+    pub fn set_keepcaps(&self, val: bool) {
+        self.as_runnable().unwrap().keepcaps.set(val)
+    }
+
+    /// Get process's personality, as manipulated by personality(2).
+    pub fn personality(&self) -> u32 {
+        self.as_runnable().unwrap().personality.get()
+    }
+
+    /// Set process's personality, as manipulated by personality(2).
+    pub fn set_personality(&self, val: u32) {
+        self.as_runnable().unwrap().personality.set(val)
+    }
+
+    /// Get process's capability sets, as manipulated by capget(2) and capset(2).
+    pub fn capabilities(&self) -> Capabilities {
+        self.as_runnable().unwrap().capabilities.get()
+    }
+
+    /// Set process's capability sets, as manipulated by capget(2) and capset(2).
+    pub fn set_capabilities(&self, val: Capabilities) {
+        self.as_runnable().unwrap().capabilities.set(val)
+    }
+
+    /// Get the signal that the process will receive when its parent dies, as manipulated by the
+    /// prctl operations `PR_SET_PDEATHSIG` and `PR_GET_PDEATHSIG`.
+    pub fn parent_death_signal(&self) -> Option<Signal> {
+        self.as_runnable().unwrap().parent_death_signal.get()
+    }
+
+    /// Set the signal that the process will receive when its parent dies, as manipulated by the
+    /// prctl operations `PR_SET_PDEATHSIG` and `PR_GET_PDEATHSIG`.
+    pub fn set_parent_death_signal(&self, val: Option<Signal>) {
+        self.as_runnable().unwrap().parent_death_signal.set(val)
+    }
+
+    /// Called when this process's parent `parent_pid` has exited. Sends the parent-death signal
+    /// if one was requested.
+    pub fn handle_parent_death(&self, host: &Host, parent_pid: ProcessId) {
+        let Some(runnable) = self.as_runnable() else {
+            return;
+        };
+        let Some(signal) = runnable.parent_death_signal.get() else {
+            return;
+        };
+        trace!(
+            "Sending parent-death signal {signal:?} to {:?}",
+            runnable.common.id
+        );
+        let siginfo = siginfo_t::new_for_kill(signal, parent_pid.into(), 0);
+        runnable.signal(host, None, &siginfo);
+    }
+
     /// Deprecated wrapper for `RunnableProcess::start_cpu_delay_timer`
     #[cfg(feature = "perf_timers")]
     pub fn start_cpu_delay_timer(&self) {
@@ -1207,6 +1864,14 @@ impl Process {
                 debug!("Process {} is no longer running", &*self.name());
                 return;
             };
+            if runnable.stop_signal.get().is_some() {
+                debug!(
+                    "Process {} is stopped; deferring thread {tid}",
+                    &*self.name()
+                );
+                runnable.stopped_thread_resumes.borrow_mut().insert(tid);
+                return;
+            }
             let threads = runnable.threads.borrow();
             let Some(thread) = threads.get(&tid) else {
                 debug!("Thread {} no longer exists", tid);
@@ -1477,6 +2142,17 @@ impl Process {
         self.common().physical_address(vptr)
     }
 
+    /// Panics if the process isn't runnable. See [`RunnableProcess::futex_physical_address`].
+    pub fn futex_physical_address(
+        &self,
+        vptr: ForeignPtr<()>,
+        is_private: bool,
+    ) -> ManagedPhysicalMemoryAddr {
+        self.as_runnable()
+            .unwrap()
+            .futex_physical_address(vptr, is_private)
+    }
+
     pub fn is_running(&self) -> bool {
         self.as_runnable().is_some()
     }
@@ -1501,6 +2177,15 @@ impl Process {
             }
         }
 
+        // The process's record locks are released when it exits.
+        host.file_lock_table_borrow_mut().release_process(self.id());
+
+        // The process's shared memory segments are detached when it exits.
+        host.sysv_shm_table_borrow_mut().release_process(self.id());
+
+        // The process's `SEM_UNDO` semaphore adjustments are applied when it exits.
+        host.sysv_sem_table_borrow_mut().release_process(self.id());
+
         // Intentionally hold the borrow on self.state to ensure the state
         // transition is "atomic".
         let mut opt_state = self.state.borrow_mut();
@@ -1570,6 +2255,10 @@ impl Process {
         };
         log::log!(log_level, "{}", main_result_string);
 
+        runnable.write_syscall_summary(host);
+
+        let pidfds = std::mem::take(&mut *runnable.pidfds.borrow_mut());
+
         let zombie = ZombieProcess {
             common: runnable.into_common(),
             exit_status,
@@ -1577,6 +2266,17 @@ impl Process {
         zombie.notify_parent_of_exit(host);
 
         *opt_state = Some(ProcessState::Zombie(zombie));
+        drop(opt_state);
+
+        // Any pidfds referring to this process are now readable.
+        let pidfds: Vec<_> = pidfds.iter().filter_map(Weak::upgrade).collect();
+        if !pidfds.is_empty() {
+            CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+                for pidfd in pidfds {
+                    pidfd.borrow_mut().process_exited(cb_queue);
+                }
+            });
+        }
     }
 
     /// Deprecated wrapper for `RunnableProcess::add_thread`
@@ -1690,6 +2390,13 @@ impl Process {
             thread.explicit_drop_recursive(host.root(), host);
         }
 
+        // `prctl(2)`: the "keep capabilities" flag is always cleared on an execve
+        runnable.keepcaps.set(false);
+
+        // `execve(2)`: attached System V shared memory segments are detached
+        host.sysv_shm_table_borrow_mut()
+            .release_process(runnable.common.id);
+
         // Recreate the `MemoryManager`
         {
             // We can't safely replace the memory manager if there are outstanding
@@ -1724,6 +2431,12 @@ impl Process {
         // Exit signal is reset to SIGCHLD.
         runnable.common.exit_signal = Some(Signal::SIGCHLD);
 
+        // `execve(2)`: "POSIX timers are not preserved (timer_create(2))."
+        runnable.posix_timers.borrow_mut().clear();
+
+        // The AIO contexts' memory mappings are gone along with the rest of the address space.
+        runnable.aio_contexts.borrow_mut().clear();
+
         // Reset signal actions to default.
         // `execve(2)`:
         // POSIX.1 specifies that the dispositions of any signals that
@@ -2247,12 +2960,13 @@ mod export {
     }
 
     #[no_mangle]
-    pub unsafe extern "C-unwind" fn process_getPhysicalAddress(
+    pub unsafe extern "C-unwind" fn process_getFutexPhysicalAddress(
         proc: *const Process,
         vptr: UntypedForeignPtr,
+        is_private: bool,
     ) -> ManagedPhysicalMemoryAddr {
         let proc = unsafe { proc.as_ref().unwrap() };
-        proc.physical_address(vptr)
+        proc.futex_physical_address(vptr, is_private)
     }
 
     #[no_mangle]
@@ -61,6 +61,10 @@ pub trait SyscallDisplay {
 pub struct SyscallVal<'a, T> {
     pub reg: SyscallReg,
     pub args: [SyscallReg; 6],
+    /// The syscall's return value if this is a syscall argument and the syscall completed
+    /// successfully. Useful for arguments that are written by the syscall, such as an output array
+    /// whose length is the return value.
+    pub rv: Option<SyscallReg>,
     options: FmtOptions,
     mem: &'a MemoryManager,
     _phantom: PhantomData<T>,
@@ -76,6 +80,7 @@ impl<'a, T> SyscallVal<'a, T> {
         Self {
             reg,
             args,
+            rv: None,
             options,
             mem,
             _phantom: PhantomData,
@@ -136,6 +141,23 @@ where
             f: SyscallVal::new(args[5], args, options, mem),
         }
     }
+
+    /// Make the syscall's return value available to the arguments when formatting.
+    pub fn with_result(mut self, rv: &SyscallResult) -> Self {
+        let rv = match rv {
+            SyscallResult::Ok(x) => Some(*x),
+            SyscallResult::Err(_) => None,
+        };
+
+        self.a.rv = rv;
+        self.b.rv = rv;
+        self.c.rv = rv;
+        self.d.rv = rv;
+        self.e.rv = rv;
+        self.f.rv = rv;
+
+        self
+    }
 }
 
 impl<'a, A, B, C, D, E, F> Display for SyscallArgsFmt<'a, A, B, C, D, E, F>
@@ -0,0 +1,447 @@
+use linux_api::aio::{
+    aio_context_t, io_event, iocb, IOCB_CMD_FDSYNC, IOCB_CMD_FSYNC, IOCB_CMD_PREAD,
+    IOCB_CMD_PREADV, IOCB_CMD_PWRITE, IOCB_CMD_PWRITEV, IOCB_FLAG_RESFD,
+};
+use linux_api::errno::Errno;
+use linux_api::time::timespec;
+use shadow_shim_helper_rs::simulation_time::SimulationTime;
+use shadow_shim_helper_rs::syscall_types::ForeignPtr;
+
+use crate::core::worker::Worker;
+use crate::cshadow as c;
+use crate::host::aio::AioContext;
+use crate::host::descriptor::CompatFile;
+use crate::host::memory_manager::page_size;
+use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
+use crate::host::syscall::io::{read_iovecs, IoVec};
+use crate::host::syscall::types::{ForeignArrayPtr, SyscallError};
+
+/// The maximum number of events in an AIO context (`/proc/sys/fs/aio-max-nr`).
+const AIO_MAX_NR: u32 = 65536;
+
+impl SyscallHandler {
+    // <https://github.com/torvalds/linux/tree/v6.3/fs/aio.c>
+    // ```
+    // SYSCALL_DEFINE2(io_setup, unsigned, nr_events, aio_context_t __user *, ctxp)
+    // ```
+    log_syscall!(
+        io_setup,
+        /* rv */ std::ffi::c_int,
+        /* nr_events */ std::ffi::c_uint,
+        /* ctxp */ *const std::ffi::c_void,
+    );
+    pub fn io_setup(
+        ctx: &mut SyscallContext,
+        nr_events: std::ffi::c_uint,
+        ctx_ptr: ForeignPtr<aio_context_t>,
+    ) -> Result<(), SyscallError> {
+        // io_setup(2): "ctx_idp must not point to an AIO context that already exists, and must be
+        // initialized to 0 prior to the call"
+        if ctx.objs.memory().read(ctx_ptr)? != 0 || nr_events == 0 {
+            return Err(Errno::EINVAL.into());
+        }
+
+        if nr_events > AIO_MAX_NR {
+            return Err(Errno::EAGAIN.into());
+        }
+
+        // Like linux, the context id is the address of memory mapped in the process. Libraries
+        // such as libaio read the completion ring from this memory directly if it has a valid
+        // header. Ours is zeroed, so they fall back to using the io_getevents syscall.
+        let ring_len = page_size();
+        let ring = {
+            let (pctx, thread) = ctx.objs.split_thread();
+            thread.malloc_foreign_ptr(&pctx, ring_len)?
+        };
+        let ctx_id = u64::from(ring);
+
+        if let Err(e) = ctx.objs.memory_mut().write(ctx_ptr, &ctx_id) {
+            let (pctx, thread) = ctx.objs.split_thread();
+            thread.free_foreign_ptr(&pctx, ring, ring_len)?;
+            return Err(e.into());
+        }
+
+        let aio_ctx = AioContext::new(ring, ring_len, nr_events.try_into().unwrap());
+        ctx.objs
+            .process
+            .borrow_as_runnable()
+            .unwrap()
+            .aio_contexts_borrow_mut()
+            .insert(ctx_id, aio_ctx);
+
+        Ok(())
+    }
+
+    // <https://github.com/torvalds/linux/tree/v6.3/fs/aio.c>
+    // ```
+    // SYSCALL_DEFINE1(io_destroy, aio_context_t, ctx)
+    // ```
+    log_syscall!(
+        io_destroy,
+        /* rv */ std::ffi::c_int,
+        /* ctx */ aio_context_t,
+    );
+    pub fn io_destroy(ctx: &mut SyscallContext, ctx_id: aio_context_t) -> Result<(), SyscallError> {
+        let Some(aio_ctx) = ctx
+            .objs
+            .process
+            .borrow_as_runnable()
+            .unwrap()
+            .aio_contexts_borrow_mut()
+            .remove(&ctx_id)
+        else {
+            return Err(Errno::EINVAL.into());
+        };
+
+        // Any completion events that haven't been returned are discarded.
+        let (ring, ring_len) = aio_ctx.ring();
+        let (pctx, thread) = ctx.objs.split_thread();
+        thread.free_foreign_ptr(&pctx, ring, ring_len)?;
+
+        Ok(())
+    }
+
+    // <https://github.com/torvalds/linux/tree/v6.3/fs/aio.c>
+    // ```
+    // SYSCALL_DEFINE3(io_submit, aio_context_t, ctx_id, long, nr, struct iocb __user * __user *,
+    //                 iocbpp)
+    // ```
+    log_syscall!(
+        io_submit,
+        /* rv */ std::ffi::c_int,
+        /* ctx_id */ aio_context_t,
+        /* nr */ std::ffi::c_long,
+        /* iocbpp */ *const std::ffi::c_void,
+    );
+    pub fn io_submit(
+        ctx: &mut SyscallContext,
+        ctx_id: aio_context_t,
+        nr: std::ffi::c_long,
+        iocbpp: ForeignPtr<ForeignPtr<iocb>>,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        let Ok(nr) = usize::try_from(nr) else {
+            return Err(Errno::EINVAL.into());
+        };
+
+        let capacity = {
+            let runnable = ctx.objs.process.borrow_as_runnable().unwrap();
+            let aio_contexts = runnable.aio_contexts_borrow_mut();
+            let Some(aio_ctx) = aio_contexts.get(&ctx_id) else {
+                return Err(Errno::EINVAL.into());
+            };
+            aio_ctx.capacity()
+        };
+
+        // Linux clamps the number of iocbs to the size of the context, and then fails with EAGAIN
+        // once there are no more free slots.
+        let iocb_ptrs: Vec<ForeignPtr<iocb>> = {
+            let nr = std::cmp::min(nr, capacity.max(1));
+            let mem = ctx.objs.memory();
+            let ptrs = mem.memory_ref(ForeignArrayPtr::new(iocbpp, nr))?;
+            ptrs.to_vec()
+        };
+
+        let mut num_submitted = 0;
+        for iocb_ptr in iocb_ptrs {
+            // io_submit(2): the number of iocbs submitted is returned if any were submitted
+            // before an error, otherwise the error is returned.
+            let event = match Self::aio_submit_one(ctx, iocb_ptr, num_submitted < capacity) {
+                Ok(event) => event,
+                Err(e) if num_submitted == 0 => return Err(e.into()),
+                Err(_) => break,
+            };
+
+            ctx.objs
+                .process
+                .borrow_as_runnable()
+                .unwrap()
+                .aio_contexts_borrow_mut()
+                .get_mut(&ctx_id)
+                .unwrap()
+                .push_completion(event);
+            num_submitted += 1;
+        }
+
+        Ok(num_submitted.try_into().unwrap())
+    }
+
+    /// Perform the operation in the iocb at `iocb_ptr`, returning its completion event. An error
+    /// is returned if the operation couldn't be submitted; errors from the operation itself are
+    /// returned in the event.
+    fn aio_submit_one(
+        ctx: &mut SyscallContext,
+        iocb_ptr: ForeignPtr<iocb>,
+        has_capacity: bool,
+    ) -> Result<io_event, Errno> {
+        let iocb = ctx.objs.memory().read(iocb_ptr)?;
+
+        if iocb.aio_reserved2 != 0 {
+            return Err(Errno::EINVAL);
+        }
+
+        if iocb.aio_flags & IOCB_FLAG_RESFD != 0 {
+            warn_once_then_debug!("AIO completion notification with an eventfd is unsupported");
+            return Err(Errno::EINVAL);
+        }
+
+        if !has_capacity {
+            return Err(Errno::EAGAIN);
+        }
+
+        let fd = std::ffi::c_int::try_from(iocb.aio_fildes).or(Err(Errno::EBADF))?;
+        let buf = ForeignPtr::<u8>::from(iocb.aio_buf);
+        let len = usize::try_from(iocb.aio_nbytes).unwrap();
+        let offset = iocb.aio_offset;
+
+        let res = match iocb.aio_lio_opcode {
+            IOCB_CMD_PREAD | IOCB_CMD_PWRITE => {
+                let iovs = [IoVec { base: buf, len }];
+                let is_read = iocb.aio_lio_opcode == IOCB_CMD_PREAD;
+                Self::aio_rw(ctx, fd, &iovs, offset, is_read)?
+            }
+            IOCB_CMD_PREADV | IOCB_CMD_PWRITEV => {
+                let iovs = read_iovecs(&ctx.objs.memory(), buf.cast::<libc::iovec>(), len)?;
+                let is_read = iocb.aio_lio_opcode == IOCB_CMD_PREADV;
+                Self::aio_rw(ctx, fd, &iovs, offset, is_read)?
+            }
+            IOCB_CMD_FSYNC | IOCB_CMD_FDSYNC => Self::aio_fsync(ctx, fd)?,
+            opcode => {
+                warn_once_then_debug!("Unsupported AIO operation {opcode}");
+                return Err(Errno::EINVAL);
+            }
+        };
+
+        Ok(io_event {
+            data: iocb.aio_data,
+            obj: u64::from(iocb_ptr),
+            res,
+            res2: 0,
+        })
+    }
+
+    /// Perform an AIO read or write synchronously, returning the number of bytes transferred or a
+    /// negative errno.
+    fn aio_rw(
+        ctx: &mut SyscallContext,
+        fd: std::ffi::c_int,
+        iovs: &[IoVec],
+        offset: i64,
+        is_read: bool,
+    ) -> Result<i64, Errno> {
+        let desc_table = ctx.objs.thread.descriptor_table_borrow(ctx.objs.host);
+        let file = match Self::get_descriptor(&desc_table, fd)?.file() {
+            CompatFile::New(file) => file.clone(),
+            CompatFile::Legacy(file) => {
+                let file = file.ptr();
+                if unsafe { c::legacyfile_getType(file) } != c::_LegacyFileType_DT_FILE {
+                    return Err(Errno::EINVAL);
+                }
+                drop(desc_table);
+
+                // Like rw_verify_area() in linux, the offset can't be negative and the end of the
+                // transfer must be representable.
+                let end = iovs.iter().try_fold(offset, |end, iov| {
+                    end.checked_add(i64::try_from(iov.len).ok()?)
+                });
+                if offset < 0 || end.is_none() {
+                    return Err(Errno::EINVAL);
+                }
+
+                return Ok(Self::aio_rw_regular_file(
+                    ctx,
+                    file as *mut c::RegularFile,
+                    iovs,
+                    offset,
+                    is_read,
+                ));
+            }
+        };
+        drop(desc_table);
+
+        // Shadow's non-legacy files (pipes, sockets, etc) aren't seekable, so the offset is
+        // ignored as it is in linux.
+        let rv = if is_read {
+            Self::readv_helper(ctx, file.inner_file(), iovs, None, 0)
+        } else {
+            Self::writev_helper(ctx, file.inner_file(), iovs, None, 0)
+        };
+
+        Ok(match rv {
+            Ok(n) => n.try_into().unwrap(),
+            Err(SyscallError::Failed(failed)) => -i64::from(failed.errno),
+            // We can't block in io_submit, so an operation that isn't ready fails instead.
+            Err(SyscallError::Blocked(_)) => -i64::from(Errno::EAGAIN),
+            Err(SyscallError::Native) => unreachable!(),
+        })
+    }
+
+    /// Perform an AIO read or write on a regular file, returning the number of bytes transferred
+    /// or a negative errno.
+    fn aio_rw_regular_file(
+        ctx: &mut SyscallContext,
+        file: *mut c::RegularFile,
+        iovs: &[IoVec],
+        offset: i64,
+        is_read: bool,
+    ) -> i64 {
+        let mut total: i64 = 0;
+
+        for iov in iovs {
+            let rv = Self::aio_rw_regular_file_iov(ctx, file, iov, offset + total, is_read);
+
+            // Like preadv(2) and pwritev(2), an error after some data was transferred is
+            // reported as a short transfer.
+            let n = match rv {
+                Ok(n) => n,
+                Err(e) if total == 0 => return -i64::from(e),
+                Err(_) => break,
+            };
+
+            total += i64::try_from(n).unwrap();
+            if n < iov.len {
+                break;
+            }
+        }
+
+        total
+    }
+
+    /// Transfer at most `SYSCALL_IO_BUFSIZE` bytes of `iov`, like the read and write handlers.
+    fn aio_rw_regular_file_iov(
+        ctx: &mut SyscallContext,
+        file: *mut c::RegularFile,
+        iov: &IoVec,
+        offset: i64,
+        is_read: bool,
+    ) -> Result<usize, Errno> {
+        let len = std::cmp::min(iov.len, usize::try_from(c::SYSCALL_IO_BUFSIZE).unwrap());
+        let ptr = ForeignArrayPtr::new(iov.base, len);
+        let mut buf = vec![0u8; len];
+
+        if is_read {
+            let rv = unsafe {
+                c::regularfile_pread(
+                    file,
+                    ctx.objs.host,
+                    buf.as_mut_ptr().cast(),
+                    buf.len(),
+                    offset,
+                )
+            };
+            let n = usize::try_from(rv).map_err(|_| Errno::try_from(-rv as i64).unwrap())?;
+            ctx.objs
+                .memory_mut()
+                .copy_to_ptr(ptr.slice(..n), &buf[..n])?;
+            Ok(n)
+        } else {
+            ctx.objs.memory().copy_from_ptr(&mut buf, ptr)?;
+            let rv = unsafe { c::regularfile_pwrite(file, buf.as_ptr().cast(), buf.len(), offset) };
+            usize::try_from(rv).map_err(|_| Errno::try_from(-rv as i64).unwrap())
+        }
+    }
+
+    /// Perform an AIO fsync, returning 0 or a negative errno.
+    fn aio_fsync(ctx: &mut SyscallContext, fd: std::ffi::c_int) -> Result<i64, Errno> {
+        let desc_table = ctx.objs.thread.descriptor_table_borrow(ctx.objs.host);
+        let CompatFile::Legacy(file) = Self::get_descriptor(&desc_table, fd)?.file() else {
+            // Only regular files support syncing.
+            return Err(Errno::EINVAL);
+        };
+        let file = file.ptr();
+        if unsafe { c::legacyfile_getType(file) } != c::_LegacyFileType_DT_FILE {
+            return Err(Errno::EINVAL);
+        }
+
+        let rv = unsafe { c::regularfile_fsync(file as *mut c::RegularFile) };
+        Ok(rv.into())
+    }
+
+    // <https://github.com/torvalds/linux/tree/v6.3/fs/aio.c>
+    // ```
+    // SYSCALL_DEFINE5(io_getevents, aio_context_t, ctx_id, long, min_nr, long, nr,
+    //                 struct io_event __user *, events, struct __kernel_timespec __user *,
+    //                 timeout)
+    // ```
+    log_syscall!(
+        io_getevents,
+        /* rv */ std::ffi::c_int,
+        /* ctx_id */ aio_context_t,
+        /* min_nr */ std::ffi::c_long,
+        /* nr */ std::ffi::c_long,
+        /* events */ *const std::ffi::c_void,
+        /* timeout */ *const timespec,
+    );
+    pub fn io_getevents(
+        ctx: &mut SyscallContext,
+        ctx_id: aio_context_t,
+        min_nr: std::ffi::c_long,
+        nr: std::ffi::c_long,
+        events_ptr: ForeignPtr<io_event>,
+        timeout_ptr: ForeignPtr<timespec>,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        let (Ok(min_nr), Ok(nr)) = (usize::try_from(min_nr), usize::try_from(nr)) else {
+            return Err(Errno::EINVAL.into());
+        };
+        if min_nr > nr {
+            return Err(Errno::EINVAL.into());
+        }
+
+        let num_completions = {
+            let runnable = ctx.objs.process.borrow_as_runnable().unwrap();
+            let aio_contexts = runnable.aio_contexts_borrow_mut();
+            let Some(aio_ctx) = aio_contexts.get(&ctx_id) else {
+                return Err(Errno::EINVAL.into());
+            };
+            aio_ctx.num_completions()
+        };
+
+        let now = Worker::current_time().unwrap();
+
+        // Operations complete when they're submitted, so we only need to wait if there aren't
+        // enough completions yet. Waiting threads aren't woken by operations submitted from other
+        // threads, so in that case they only see the new completions when the timeout expires.
+        if num_completions < min_nr {
+            let timeout = if let Some(cond) = ctx.objs.thread.syscall_condition() {
+                // we were woken up, either by the timeout or a signal
+                cond.timeout()
+            } else if timeout_ptr.is_null() {
+                warn_once_then_debug!(
+                    "io_getevents without a timeout can't wait for completions; returning early"
+                );
+                None
+            } else {
+                let timeout = ctx.objs.memory().read(timeout_ptr)?;
+                let timeout = SimulationTime::try_from(timeout).or(Err(Errno::EINVAL))?;
+                Some(now + timeout)
+            };
+
+            match timeout {
+                Some(timeout) if timeout > now => {
+                    if ctx.objs.thread.syscall_condition().is_some() {
+                        return Err(SyscallError::new_interrupted(false));
+                    }
+                    return Err(SyscallError::new_blocked_until(timeout, false));
+                }
+                _ => {}
+            }
+        }
+
+        let events = ctx
+            .objs
+            .process
+            .borrow_as_runnable()
+            .unwrap()
+            .aio_contexts_borrow_mut()
+            .get_mut(&ctx_id)
+            .unwrap()
+            .take_completions(nr);
+
+        // These events have been removed from the queue, so a failed write loses them. Linux
+        // behaves the same way.
+        ctx.objs
+            .memory_mut()
+            .copy_to_ptr(ForeignArrayPtr::new(events_ptr, events.len()), &events)?;
+
+        Ok(events.len().try_into().unwrap())
+    }
+}
@@ -1,4 +1,6 @@
-use linux_api::capability::{user_cap_data, user_cap_header, LINUX_CAPABILITY_VERSION_3};
+use linux_api::capability::{
+    user_cap_data, user_cap_header, CAP_SETPCAP, LINUX_CAPABILITY_VERSION_3,
+};
 use linux_api::errno::Errno;
 use linux_api::posix_types::kernel_pid_t;
 use linux_api::sched::CloneFlags;
@@ -10,7 +12,8 @@ use shadow_shim_helper_rs::rootedcell::refcell::RootedRefCell;
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
 use crate::host::descriptor::descriptor_table::DescriptorTable;
-use crate::host::process::ProcessId;
+use crate::host::process::{Capabilities, ProcessId};
+use crate::host::syscall::type_formatting::SyscallCloneFlagsArg;
 use crate::host::thread::Thread;
 
 use super::{SyscallContext, SyscallHandler};
@@ -74,7 +77,7 @@ impl SyscallHandler {
 
             handled_flags.insert(CloneFlags::CLONE_THREAD);
         } else {
-            if ctx.objs.process.memory_borrow().has_mapper() {
+            if ctx.objs.memory().has_mapper() {
                 warn!("Fork with memory mapper unimplemented");
                 return Err(Errno::ENOTSUP);
             }
@@ -221,6 +224,8 @@ impl SyscallHandler {
             desc_table.into_value(),
             child_pid,
             child_tid,
+            // the new thread inherits the name of the calling thread
+            &ctx.objs.thread.comm(),
         )?;
 
         let childrc = ExplicitDropper::new(
@@ -269,8 +274,7 @@ impl SyscallHandler {
 
         if do_parent_settid {
             ctx.objs
-                .process
-                .memory_borrow_mut()
+                .memory_mut()
                 .write(ptid, &kernel_pid_t::from(child_tid))?;
         }
 
@@ -307,7 +311,7 @@ impl SyscallHandler {
     log_syscall!(
         clone,
         /* rv */ kernel_pid_t,
-        /* flags */ CloneFlags,
+        /* flags */ SyscallCloneFlagsArg,
         /* child_stack */ *const std::ffi::c_void,
         /* ptid */ *const kernel_pid_t,
         /* ctid */ *const kernel_pid_t,
@@ -358,7 +362,7 @@ impl SyscallHandler {
             // as much as the caller specified, and zero-fill the rest.
             return Err(Errno::EINVAL);
         }
-        let args = ctx.objs.process.memory_borrow().read(args)?;
+        let args = ctx.objs.memory().read(args)?;
         trace!("clone3 args: {args:?}");
         let Some(flags) = CloneFlags::from_bits(args.flags) else {
             debug!("Couldn't parse clone flags: {:x}", args.flags);
@@ -430,29 +434,33 @@ impl SyscallHandler {
         hdrp: ForeignPtr<user_cap_header>,
         datap: ForeignPtr<[user_cap_data; 2]>,
     ) -> Result<(), Errno> {
-        // If the version is not 3, we return the error
-        let hdrp = ctx.objs.process.memory_borrow().read(hdrp)?;
-        if hdrp.version != LINUX_CAPABILITY_VERSION_3 {
-            warn_once_then_debug!(
-                "The version of Linux capabilities is not supported ({})",
-                hdrp.version
-            );
-            return Err(Errno::EINVAL);
+        let hdr = match Self::read_cap_header(ctx, hdrp) {
+            Ok(hdr) => hdr,
+            // A null data pointer is used to query the kernel's preferred version, which we've
+            // written to the header.
+            Err(Errno::EINVAL) if datap.is_null() => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        if datap.is_null() {
+            return Ok(());
         }
 
-        if !datap.is_null() {
-            // Since we don't provide any capability to the managed plugin, we return zeroes to both
-            // datap[0] and datap[1]
-            let empty = user_cap_data {
-                effective: 0,
-                permitted: 0,
-                inheritable: 0,
+        let caps = if hdr.pid == 0 || hdr.pid == kernel_pid_t::from(ctx.objs.process.id()) {
+            ctx.objs.process.capabilities()
+        } else {
+            let pid = ProcessId::try_from(hdr.pid).or(Err(Errno::ESRCH))?;
+            let Some(process) = ctx.objs.host.process_borrow(pid) else {
+                return Err(Errno::ESRCH);
             };
-            ctx.objs
-                .process
-                .memory_borrow_mut()
-                .write(datap, &[empty, empty])?;
-        }
+            let process = process.borrow(ctx.objs.host.root());
+            if !process.is_running() {
+                return Err(Errno::ESRCH);
+            }
+            process.capabilities()
+        };
+
+        ctx.objs.memory_mut().write(datap, &caps.to_user_data())?;
         Ok(())
     }
 
@@ -467,24 +475,51 @@ impl SyscallHandler {
         hdrp: ForeignPtr<user_cap_header>,
         datap: ForeignPtr<[user_cap_data; 2]>,
     ) -> Result<(), Errno> {
-        // If the version is not 3, we return the error
-        let hdrp = ctx.objs.process.memory_borrow().read(hdrp)?;
-        if hdrp.version != LINUX_CAPABILITY_VERSION_3 {
+        let hdr = Self::read_cap_header(ctx, hdrp)?;
+
+        // A process can only set its own capabilities.
+        if hdr.pid != 0 && hdr.pid != kernel_pid_t::from(ctx.objs.process.id()) {
+            return Err(Errno::EPERM);
+        }
+
+        let data = ctx.objs.memory().read(datap)?;
+        let new = Capabilities::from_user_data(&data);
+        let old = ctx.objs.process.capabilities();
+
+        // capabilities(7): a thread can only add capabilities to its inheritable set that are in
+        // its permitted set, unless it has CAP_SETPCAP.
+        let has_setpcap = old.effective & (1 << CAP_SETPCAP) != 0;
+        if new.inheritable & !(old.inheritable | old.permitted) != 0 && !has_setpcap {
+            return Err(Errno::EPERM);
+        }
+
+        // The permitted set can only shrink, and the effective set must be a subset of the
+        // permitted set.
+        if new.permitted & !old.permitted != 0 || new.effective & !new.permitted != 0 {
+            return Err(Errno::EPERM);
+        }
+
+        debug!("Changing capabilities from {old:?} to {new:?}");
+        ctx.objs.process.set_capabilities(new);
+        Ok(())
+    }
+
+    /// Read the header for `capget` or `capset`. If the version isn't supported, writes the
+    /// supported version to the header and returns `EINVAL`.
+    fn read_cap_header(
+        ctx: &mut SyscallContext,
+        hdrp: ForeignPtr<user_cap_header>,
+    ) -> Result<user_cap_header, Errno> {
+        let mut hdr = ctx.objs.memory().read(hdrp)?;
+        if hdr.version != LINUX_CAPABILITY_VERSION_3 {
             warn_once_then_debug!(
                 "The version of Linux capabilities is not supported ({})",
-                hdrp.version
+                hdr.version
             );
+            hdr.version = LINUX_CAPABILITY_VERSION_3;
+            ctx.objs.memory_mut().write(hdrp, &hdr)?;
             return Err(Errno::EINVAL);
         }
-
-        let datap: [_; 2] = ctx.objs.process.memory_borrow().read(datap)?;
-        for data in &datap {
-            // We don't allow the plugin to set any capability
-            if data.effective != 0 || data.permitted != 0 || data.inheritable != 0 {
-                warn_once_then_debug!("Setting Linux capabilities is not supported");
-                return Err(Errno::EINVAL);
-            }
-        }
-        Ok(())
+        Ok(hdr)
     }
 }
@@ -14,6 +14,7 @@ use crate::host::descriptor::epoll::Epoll;
 use crate::host::descriptor::{CompatFile, Descriptor, File, FileState, OpenFile};
 use crate::host::memory_manager::MemoryManager;
 use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
+use crate::host::syscall::type_formatting::SyscallEpollEventsArg;
 use crate::host::syscall::types::{ForeignArrayPtr, SyscallError};
 use crate::utility::callback_queue::CallbackQueue;
 
@@ -74,7 +75,7 @@ impl SyscallHandler {
             .thread
             .descriptor_table_borrow_mut(ctx.objs.host)
             .register_descriptor(desc)
-            .or(Err(Errno::ENFILE))?;
+            .or(Err(Errno::EMFILE))?;
 
         log::trace!("Created epoll fd {fd}");
 
@@ -87,7 +88,7 @@ impl SyscallHandler {
         /* epfd */ std::ffi::c_int,
         /* op */ std::ffi::c_int,
         /* fd */ std::ffi::c_int,
-        /* event */ *const std::ffi::c_void,
+        /* event */ *const linux_api::epoll::epoll_event,
     );
     pub fn epoll_ctl(
         ctx: &mut SyscallContext,
@@ -164,7 +165,7 @@ impl SyscallHandler {
             // NULL when using EPOLL_CTL_DEL.
             (EpollEvents::empty(), 0)
         } else {
-            let mem = ctx.objs.process.memory_borrow();
+            let mem = ctx.objs.memory();
             let ev = mem.read(event_ptr)?;
 
             let Some(mut events) = EpollEvents::from_bits(ev.events) else {
@@ -179,6 +180,34 @@ impl SyscallHandler {
             (events, ev.data)
         };
 
+        // epoll_ctl(2): EINVAL when "EPOLLEXCLUSIVE was specified in event and fd refers to an
+        // epoll instance", when "op was EPOLL_CTL_MOD and events included EPOLLEXCLUSIVE", or when
+        // "An invalid event type was specified along with EPOLLEXCLUSIVE in events"
+        if events.contains(EpollEvents::EPOLLEXCLUSIVE) {
+            let allowed = EpollEvents::EPOLLIN
+                | EpollEvents::EPOLLOUT
+                | EpollEvents::EPOLLERR
+                | EpollEvents::EPOLLHUP
+                | EpollEvents::EPOLLWAKEUP
+                | EpollEvents::EPOLLET
+                | EpollEvents::EPOLLEXCLUSIVE;
+
+            if op == EpollCtlOp::EPOLL_CTL_MOD
+                || matches!(target, File::Epoll(_))
+                || !allowed.contains(events)
+            {
+                log::debug!("Invalid use of EPOLLEXCLUSIVE with op {op:?} and events {events:?}");
+                return Err(Errno::EINVAL);
+            }
+        }
+
+        // epoll_ctl(2): ELOOP when "fd refers to an epoll instance and this EPOLL_CTL_ADD operation
+        // would result in a circular loop of epoll instances monitoring one another or a nesting
+        // depth of epoll instances greater than 5"
+        if let (EpollCtlOp::EPOLL_CTL_ADD, File::Epoll(target)) = (op, &target) {
+            Epoll::check_nesting(epoll, target)?;
+        }
+
         log::trace!("Calling epoll_ctl on epoll {epfd} with child {fd}");
 
         CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
@@ -194,7 +223,7 @@ impl SyscallHandler {
         epoll_wait,
         /* rv */ std::ffi::c_int,
         /* epfd */ std::ffi::c_int,
-        /* events */ *const std::ffi::c_void,
+        /* events */ SyscallEpollEventsArg,
         /* max_events */ std::ffi::c_int,
         /* timeout */ std::ffi::c_int,
     );
@@ -214,7 +243,7 @@ impl SyscallHandler {
         epoll_pwait,
         /* rv */ std::ffi::c_int,
         /* epfd */ std::ffi::c_int,
-        /* events */ *const std::ffi::c_void,
+        /* events */ SyscallEpollEventsArg,
         /* max_events */ std::ffi::c_int,
         /* timeout */ std::ffi::c_int,
         /* sigmask */ *const std::ffi::c_void,
@@ -234,7 +263,7 @@ impl SyscallHandler {
         let sigmask = if sigmask_ptr.is_null() {
             None
         } else {
-            Some(ctx.objs.process.memory_borrow().read(sigmask_ptr)?)
+            Some(ctx.objs.memory().read(sigmask_ptr)?)
         };
 
         // Note that timeout is given in milliseconds.
@@ -246,7 +275,7 @@ impl SyscallHandler {
         epoll_pwait2,
         /* rv */ std::ffi::c_int,
         /* epfd */ std::ffi::c_int,
-        /* events */ *const std::ffi::c_void,
+        /* events */ SyscallEpollEventsArg,
         /* max_events */ std::ffi::c_int,
         /* timeout */ *const std::ffi::c_void,
         /* sigmask */ *const std::ffi::c_void,
@@ -262,7 +291,7 @@ impl SyscallHandler {
         _sigsetsize: linux_api::posix_types::kernel_size_t,
     ) -> Result<std::ffi::c_int, SyscallError> {
         let (sigmask, timeout) = {
-            let mem = ctx.objs.process.memory_borrow();
+            let mem = ctx.objs.memory();
 
             // epoll_wait(2): "The sigmask argument may be specified as NULL, in which case
             // epoll_pwait() is equivalent to epoll_wait()"
@@ -360,7 +389,7 @@ impl SyscallHandler {
             }
 
             // Write the events out to the managed process memory.
-            let mut mem = ctx.objs.process.memory_borrow_mut();
+            let mut mem = ctx.objs.memory_mut();
             write_events_to_ptr(&mut mem, ready, events_ptr)?;
 
             // Return the number of events we are reporting.
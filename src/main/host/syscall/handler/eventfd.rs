@@ -80,7 +80,7 @@ impl SyscallHandler {
             .thread
             .descriptor_table_borrow_mut(ctx.objs.host)
             .register_descriptor(desc)
-            .or(Err(Errno::ENFILE))?;
+            .or(Err(Errno::EMFILE))?;
 
         log::trace!("eventfd() returning fd {}", fd);
 
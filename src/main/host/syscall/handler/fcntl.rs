@@ -1,11 +1,15 @@
 use linux_api::errno::Errno;
 use linux_api::fcntl::{DescriptorFlags, FcntlCommand, OFlag};
 use log::debug;
+use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
 use crate::cshadow;
 use crate::host::descriptor::{CompatFile, File, FileStatus};
+use crate::host::file_lock_table::{FileLockKey, RangeLock, RangeLockKind, RangeLockOwner};
+use crate::host::syscall::condition::SyscallCondition;
 use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
-use crate::host::syscall::types::SyscallError;
+use crate::host::syscall::types::{Blocked, SyscallError};
+use crate::host::syscall::Trigger;
 
 impl SyscallHandler {
     log_syscall!(
@@ -30,6 +34,7 @@ impl SyscallHandler {
 
         // get the descriptor, or return early if it doesn't exist
         let mut desc_table = ctx.objs.thread.descriptor_table_borrow_mut(ctx.objs.host);
+        let fd_limit = desc_table.fd_limit();
         let desc = Self::get_descriptor_mut(&mut desc_table, fd)?;
 
         let Ok(cmd) = FcntlCommand::try_from(cmd) else {
@@ -40,23 +45,31 @@ impl SyscallHandler {
         Ok(match cmd {
             FcntlCommand::F_SETLK
             | FcntlCommand::F_SETLKW
+            | FcntlCommand::F_OFD_SETLK
             | FcntlCommand::F_OFD_SETLKW
             | FcntlCommand::F_GETLK
             | FcntlCommand::F_OFD_GETLK => {
-                match desc.file() {
+                let file = match desc.file() {
                     CompatFile::New(_) => {
                         warn_once_then_debug!("fcntl({cmd:?}) unimplemented for {:?}", desc.file());
                         return Err(Errno::ENOSYS.into());
                     }
-                    CompatFile::Legacy(_) => {
-                        warn_once_then_debug!(
-                            "Using fcntl({cmd:?}) implementation that assumes no lock contention. \
-                            See https://github.com/shadow/shadow/issues/2258"
-                        );
-                        drop(desc_table);
-                        return legacy_syscall_fn(ctx);
-                    }
+                    CompatFile::Legacy(file) => file.ptr(),
                 };
+
+                if unsafe { cshadow::legacyfile_getType(file) } != cshadow::_LegacyFileType_DT_FILE
+                {
+                    drop(desc_table);
+                    return legacy_syscall_fn(ctx);
+                }
+
+                drop(desc_table);
+                return Self::fcntl_record_lock(
+                    ctx,
+                    file as *mut cshadow::RegularFile,
+                    cmd,
+                    ForeignPtr::<()>::from(arg).cast::<libc::flock>(),
+                );
             }
             FcntlCommand::F_GETFL => {
                 let file = match desc.file() {
@@ -144,21 +157,33 @@ impl SyscallHandler {
                 0
             }
             FcntlCommand::F_DUPFD => {
+                // from 'man 2 fcntl': "EINVAL The value specified in arg is negative or is greater
+                // than the maximum allowable value"
+                if u64::from(arg) >= fd_limit {
+                    return Err(Errno::EINVAL.into());
+                }
+
                 let min_fd = arg.try_into().or(Err(Errno::EINVAL))?;
 
                 let new_desc = desc.dup(DescriptorFlags::empty());
                 let new_fd = desc_table
                     .register_descriptor_with_min_fd(new_desc, min_fd)
-                    .or(Err(Errno::EINVAL))?;
+                    .or(Err(Errno::EMFILE))?;
                 new_fd.into()
             }
             FcntlCommand::F_DUPFD_CLOEXEC => {
+                // from 'man 2 fcntl': "EINVAL The value specified in arg is negative or is greater
+                // than the maximum allowable value"
+                if u64::from(arg) >= fd_limit {
+                    return Err(Errno::EINVAL.into());
+                }
+
                 let min_fd = arg.try_into().or(Err(Errno::EINVAL))?;
 
                 let new_desc = desc.dup(DescriptorFlags::FD_CLOEXEC);
                 let new_fd = desc_table
                     .register_descriptor_with_min_fd(new_desc, min_fd)
-                    .or(Err(Errno::EINVAL))?;
+                    .or(Err(Errno::EMFILE))?;
                 new_fd.into()
             }
             FcntlCommand::F_GETPIPE_SZ => {
@@ -182,4 +207,164 @@ impl SyscallHandler {
             }
         })
     }
+
+    /// Handle the POSIX record lock commands for a regular file. The locks are kept in the host's
+    /// lock table rather than on the native file, so that the host's processes contend for them
+    /// without ever blocking Shadow.
+    fn fcntl_record_lock(
+        ctx: &mut SyscallContext,
+        file: *mut cshadow::RegularFile,
+        cmd: FcntlCommand,
+        flock_ptr: ForeignPtr<libc::flock>,
+    ) -> Result<std::ffi::c_long, SyscallError> {
+        let native_fd = unsafe { cshadow::regularfile_getOSBackedFD(file) };
+        if native_fd < 0 {
+            return Err(Errno::EBADF.into());
+        }
+
+        let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+        if unsafe { libc::fstat(native_fd, &mut stat) } < 0 {
+            return Err(Errno::EBADF.into());
+        }
+
+        let flock = ctx.objs.memory().read(flock_ptr)?;
+
+        let is_ofd = matches!(
+            cmd,
+            FcntlCommand::F_OFD_SETLK | FcntlCommand::F_OFD_SETLKW | FcntlCommand::F_OFD_GETLK
+        );
+
+        // fcntl(2): "If the l_pid field is not zero, the operation fails with the error EINVAL"
+        if is_ofd && flock.l_pid != 0 {
+            return Err(Errno::EINVAL.into());
+        }
+
+        let kind = match i32::from(flock.l_type) {
+            libc::F_RDLCK => Some(RangeLockKind::Read),
+            libc::F_WRLCK => Some(RangeLockKind::Write),
+            libc::F_UNLCK => None,
+            _ => return Err(Errno::EINVAL.into()),
+        };
+
+        let base = match i32::from(flock.l_whence) {
+            libc::SEEK_SET => 0,
+            libc::SEEK_CUR => {
+                let pos = unsafe { cshadow::regularfile_lseek(file, 0, libc::SEEK_CUR) };
+                if pos < 0 {
+                    return Err(Errno::try_from(-pos).unwrap().into());
+                }
+                pos
+            }
+            libc::SEEK_END => stat.st_size,
+            _ => return Err(Errno::EINVAL.into()),
+        };
+
+        let start = base.checked_add(flock.l_start).ok_or(Errno::EOVERFLOW)?;
+
+        // a length of 0 locks to the end of the file, and a negative length locks the bytes
+        // before the start
+        let (start, end) = match flock.l_len {
+            0 => (start, None),
+            len if len > 0 => (start, Some(start.checked_add(len).ok_or(Errno::EOVERFLOW)?)),
+            len => (start + len, Some(start)),
+        };
+
+        let start = u64::try_from(start).or(Err(Errno::EINVAL))?;
+        let end = end.map(|end| u64::try_from(end).unwrap());
+
+        let owner = if is_ofd {
+            RangeLockOwner::OpenFile(file as usize)
+        } else {
+            RangeLockOwner::Process(ctx.objs.process.id())
+        };
+        let key = FileLockKey {
+            dev: stat.st_dev,
+            ino: stat.st_ino,
+        };
+
+        if matches!(cmd, FcntlCommand::F_GETLK | FcntlCommand::F_OFD_GETLK) {
+            let Some(kind) = kind else {
+                return Err(Errno::EINVAL.into());
+            };
+
+            let lock = RangeLock {
+                owner,
+                kind,
+                start,
+                end,
+            };
+            let conflict = ctx
+                .objs
+                .host
+                .file_lock_table_borrow_mut()
+                .range_conflict(key, &lock);
+
+            let flock = match conflict {
+                None => libc::flock {
+                    l_type: libc::F_UNLCK as libc::c_short,
+                    ..flock
+                },
+                Some(conflict) => libc::flock {
+                    l_type: match conflict.kind {
+                        RangeLockKind::Read => libc::F_RDLCK,
+                        RangeLockKind::Write => libc::F_WRLCK,
+                    } as libc::c_short,
+                    l_whence: libc::SEEK_SET as libc::c_short,
+                    l_start: conflict.start.try_into().unwrap(),
+                    l_len: conflict
+                        .end
+                        .map_or(0, |end| end - conflict.start)
+                        .try_into()
+                        .unwrap(),
+                    l_pid: match conflict.owner {
+                        RangeLockOwner::Process(pid) => pid.into(),
+                        // OFD locks aren't owned by a process
+                        RangeLockOwner::OpenFile(_) => -1,
+                    },
+                },
+            };
+
+            ctx.objs.memory_mut().write(flock_ptr, &flock)?;
+            return Ok(0);
+        }
+
+        let mut lock_table = ctx.objs.host.file_lock_table_borrow_mut();
+
+        let Some(kind) = kind else {
+            lock_table.range_unlock(key, owner, start, end);
+            return Ok(0);
+        };
+
+        // fcntl(2): "In order to place a read lock, fd must be open for reading. In order to
+        // place a write lock, fd must be open for writing."
+        let access_mode = unsafe { libc::fcntl(native_fd, libc::F_GETFL) } & libc::O_ACCMODE;
+        let has_access = match kind {
+            RangeLockKind::Read => access_mode != libc::O_WRONLY,
+            RangeLockKind::Write => access_mode != libc::O_RDONLY,
+        };
+        if !has_access {
+            return Err(Errno::EBADF.into());
+        }
+
+        let lock = RangeLock {
+            owner,
+            kind,
+            start,
+            end,
+        };
+        if lock_table.range_try_lock(key, lock) {
+            return Ok(0);
+        }
+
+        if matches!(cmd, FcntlCommand::F_SETLK | FcntlCommand::F_OFD_SETLK) {
+            return Err(Errno::EAGAIN.into());
+        }
+
+        // wait for a lock on the file to be released, and then try again
+        let futex = lock_table.wakeup_futex(key).ptr();
+        Err(SyscallError::Blocked(Blocked {
+            condition: SyscallCondition::new(unsafe { Trigger::from_futex(futex) }),
+            restartable: true,
+        }))
+    }
 }
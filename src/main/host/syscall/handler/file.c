@@ -7,6 +7,7 @@
 
 #include <errno.h>
 #include <fcntl.h>
+#include <sys/file.h>
 #include <sys/stat.h>
 #include <sys/types.h>
 #include <unistd.h>
@@ -18,6 +19,8 @@
 #include "main/host/process.h"
 #include "main/host/syscall/kernel_types.h"
 #include "main/host/syscall/protected.h"
+#include "main/host/syscall/syscall_condition.h"
+#include "main/utility/utility.h"
 
 ///////////////////////////////////////////////////////////
 // Helpers
@@ -212,7 +215,7 @@ SyscallReturn syscallhandler_ftruncate(SyscallHandler* sys, const SyscallArgs* a
     return syscallreturn_makeDoneI64(regularfile_ftruncate(file_desc, args->args[1].as_u64));
 }
 
-SyscallReturn syscallhandler_fadvise64(SyscallHandler* sys, const SyscallArgs* args) {
+SyscallReturn syscallhandler_flock(SyscallHandler* sys, const SyscallArgs* args) {
     int fd = args->args[0].as_i64;
 
     /* Get and validate the file descriptor. */
@@ -222,21 +225,29 @@ SyscallReturn syscallhandler_fadvise64(SyscallHandler* sys, const SyscallArgs* a
         return syscallreturn_makeDoneErrno(-errcode);
     }
 
-    return syscallreturn_makeDoneI64(regularfile_fadvise(
-        file_desc, args->args[1].as_u64, args->args[2].as_u64, args->args[3].as_i64));
-}
+    int operation = args->args[1].as_i64;
+    const Host* host = rustsyscallhandler_getHost(sys);
 
-SyscallReturn syscallhandler_flock(SyscallHandler* sys, const SyscallArgs* args) {
-    int fd = args->args[0].as_i64;
+    if (rustsyscallhandler_wasBlocked(sys) &&
+        thread_unblockedSignalPending(
+            rustsyscallhandler_getThread(sys), host_getShimShmemLock(host))) {
+        trace("flock on file %i interrupted by a signal", fd);
+        return syscallreturn_makeInterrupted(true);
+    }
 
-    /* Get and validate the file descriptor. */
-    RegularFile* file_desc = NULL;
-    int errcode = _syscallhandler_validateFileHelper(sys, fd, &file_desc);
-    if (errcode < 0) {
-        return syscallreturn_makeDoneErrno(-errcode);
+    int result = regularfile_flock(file_desc, host, operation);
+
+    if (result == -EWOULDBLOCK && !(operation & LOCK_NB)) {
+        /* Wait for a lock on the file to be released, and then try again. */
+        trace("flock on file %i conflicts with an existing lock, blocking", fd);
+        Futex* futex = regularfile_getLockWakeupFutex(file_desc, host);
+        utility_debugAssert(futex != NULL);
+        Trigger trigger =
+            (Trigger){.type = TRIGGER_FUTEX, .object = futex, .state = FileState_FUTEX_WAKEUP};
+        return syscallreturn_makeBlocked(syscallcondition_new(trigger), true);
     }
 
-    return syscallreturn_makeDoneI64(regularfile_flock(file_desc, args->args[1].as_i64));
+    return syscallreturn_makeDoneI64(result);
 }
 
 SyscallReturn syscallhandler_fsetxattr(SyscallHandler* sys, const SyscallArgs* args) {
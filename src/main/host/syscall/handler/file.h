@@ -9,7 +9,6 @@
 #include "main/host/syscall/protected.h"
 
 SYSCALL_HANDLER(creat);
-SYSCALL_HANDLER(fadvise64);
 SYSCALL_HANDLER(fallocate);
 SYSCALL_HANDLER(fchmod);
 SYSCALL_HANDLER(fchown);
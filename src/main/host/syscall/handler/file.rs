@@ -31,9 +31,53 @@ impl SyscallHandler {
         Self::legacy_syscall(cshadow::syscallhandler_creat, ctx)
     }
 
-    log_syscall!(fadvise64, /* rv */ std::ffi::c_int);
-    pub fn fadvise64(ctx: &mut SyscallContext) -> SyscallResult {
-        Self::legacy_syscall(cshadow::syscallhandler_fadvise64, ctx)
+    log_syscall!(
+        fadvise64,
+        /* rv */ std::ffi::c_int,
+        /* fd */ std::ffi::c_int,
+        /* offset */ linux_api::posix_types::kernel_off_t,
+        /* len */ linux_api::posix_types::kernel_off_t,
+        /* advice */ std::ffi::c_int,
+    );
+    pub fn fadvise64(
+        ctx: &mut SyscallContext,
+        fd: std::ffi::c_int,
+        _offset: linux_api::posix_types::kernel_off_t,
+        len: linux_api::posix_types::kernel_off_t,
+        advice: std::ffi::c_int,
+    ) -> Result<(), SyscallError> {
+        let is_pipe = {
+            let desc_table = ctx.objs.thread.descriptor_table_borrow(ctx.objs.host);
+            match Self::get_descriptor(&desc_table, fd)?.file() {
+                CompatFile::New(file) => matches!(file.inner_file(), File::Pipe(_)),
+                CompatFile::Legacy(_) => false,
+            }
+        };
+
+        // posix_fadvise(2): "ESPIPE The specified file descriptor refers to a pipe or FIFO."
+        if is_pipe {
+            return Err(Errno::ESPIPE.into());
+        }
+
+        if len < 0 {
+            return Err(Errno::EINVAL.into());
+        }
+
+        match advice {
+            libc::POSIX_FADV_NORMAL
+            | libc::POSIX_FADV_RANDOM
+            | libc::POSIX_FADV_SEQUENTIAL
+            | libc::POSIX_FADV_WILLNEED
+            | libc::POSIX_FADV_DONTNEED
+            | libc::POSIX_FADV_NOREUSE => {}
+            _ => {
+                log::debug!("Invalid fadvise64 advice: {advice}");
+                return Err(Errno::EINVAL.into());
+            }
+        }
+
+        // Shadow doesn't do any readahead or caching of its own, so the advice has no effect.
+        Ok(())
     }
 
     log_syscall!(fallocate, /* rv */ std::ffi::c_int);
@@ -136,16 +180,80 @@ impl SyscallHandler {
         }
     }
 
-    log_syscall!(readahead, /* rv */ std::ffi::c_int);
-    pub fn readahead(ctx: &mut SyscallContext) -> SyscallResult {
+    log_syscall!(
+        readahead,
+        /* rv */ std::ffi::c_int,
+        /* fd */ std::ffi::c_int,
+        /* offset */ linux_api::posix_types::kernel_off_t,
+        /* count */ linux_api::posix_types::kernel_size_t,
+    );
+    pub fn readahead(
+        ctx: &mut SyscallContext,
+        fd: std::ffi::c_int,
+        _offset: linux_api::posix_types::kernel_off_t,
+        _count: linux_api::posix_types::kernel_size_t,
+    ) -> SyscallResult {
+        // Linux returns EINVAL rather than ESPIPE for pipes and sockets, since they don't have a
+        // page cache.
+        if !Self::is_regular_file(ctx, fd)? {
+            return Err(Errno::EINVAL.into());
+        }
+
         Self::legacy_syscall(cshadow::syscallhandler_readahead, ctx)
     }
 
-    log_syscall!(sync_file_range, /* rv */ std::ffi::c_int);
-    pub fn sync_file_range(ctx: &mut SyscallContext) -> SyscallResult {
+    log_syscall!(
+        sync_file_range,
+        /* rv */ std::ffi::c_int,
+        /* fd */ std::ffi::c_int,
+        /* offset */ linux_api::posix_types::kernel_off_t,
+        /* nbytes */ linux_api::posix_types::kernel_off_t,
+        /* flags */ std::ffi::c_uint,
+    );
+    pub fn sync_file_range(
+        ctx: &mut SyscallContext,
+        fd: std::ffi::c_int,
+        offset: linux_api::posix_types::kernel_off_t,
+        nbytes: linux_api::posix_types::kernel_off_t,
+        flags: std::ffi::c_uint,
+    ) -> SyscallResult {
+        let valid_flags = libc::SYNC_FILE_RANGE_WAIT_BEFORE
+            | libc::SYNC_FILE_RANGE_WRITE
+            | libc::SYNC_FILE_RANGE_WAIT_AFTER;
+        if flags & !valid_flags != 0 {
+            log::debug!("Invalid sync_file_range flags: {flags:#x}");
+            return Err(Errno::EINVAL.into());
+        }
+
+        // sync_file_range(2): "EINVAL [...] offset or nbytes is invalid"
+        if offset < 0 || nbytes < 0 || offset.checked_add(nbytes).is_none() {
+            return Err(Errno::EINVAL.into());
+        }
+
+        if !Self::is_regular_file(ctx, fd)? {
+            // sync_file_range(2): "ESPIPE fd refers to something other than a regular file, a
+            // block device, or a directory"
+            return Err(Errno::ESPIPE.into());
+        }
+
         Self::legacy_syscall(cshadow::syscallhandler_sync_file_range, ctx)
     }
 
+    /// Returns whether `fd` refers to a regular file (or directory), or EBADF if `fd` isn't a
+    /// valid descriptor. Shadow only has regular files as legacy files, so all other file types
+    /// such as pipes and sockets return false.
+    fn is_regular_file(ctx: &SyscallContext, fd: std::ffi::c_int) -> Result<bool, Errno> {
+        let desc_table = ctx.objs.thread.descriptor_table_borrow(ctx.objs.host);
+
+        Ok(match Self::get_descriptor(&desc_table, fd)?.file() {
+            CompatFile::New(_) => false,
+            CompatFile::Legacy(file) => {
+                let file_type = unsafe { cshadow::legacyfile_getType(file.ptr()) };
+                file_type == cshadow::_LegacyFileType_DT_FILE
+            }
+        })
+    }
+
     log_syscall!(syncfs, /* rv */ std::ffi::c_int);
     pub fn syncfs(ctx: &mut SyscallContext) -> SyscallResult {
         Self::legacy_syscall(cshadow::syscallhandler_syncfs, ctx)
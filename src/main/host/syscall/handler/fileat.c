@@ -141,6 +141,120 @@ SyscallReturn syscallhandler_openat(SyscallHandler* sys, const SyscallArgs* args
     return syscallreturn_makeDoneI64(handle);
 }
 
+SyscallReturn syscallhandler_name_to_handle_at(SyscallHandler* sys, const SyscallArgs* args) {
+    int dirfd = args->args[0].as_i64;
+    UntypedForeignPtr pathnamePtr = args->args[1].as_ptr; // const char*
+    UntypedForeignPtr handlePtr = args->args[2].as_ptr;   // struct file_handle*
+    UntypedForeignPtr mountIdPtr = args->args[3].as_ptr;  // int*
+    int flags = args->args[4].as_i64;
+
+    /* Validate params. */
+    RegularFile* dir_desc = NULL;
+
+    int errcode = _syscallhandler_validateDirHelper(sys, dirfd, &dir_desc);
+    if (errcode < 0) {
+        return syscallreturn_makeDoneErrno(-errcode);
+    }
+
+    const Process* proc = rustsyscallhandler_getProcess(sys);
+
+    char pathname[PATH_MAX];
+    errcode = process_readString(proc, pathname, pathnamePtr, PATH_MAX);
+    if (errcode < 0) {
+        return syscallreturn_makeDoneErrno(-errcode);
+    }
+
+    /* The caller sets the size of the buffer that follows the handle's header. */
+    _Alignas(struct file_handle) char handleBuf[sizeof(struct file_handle) + MAX_HANDLE_SZ] = {0};
+    struct file_handle* handle = (struct file_handle*)handleBuf;
+
+    errcode = process_readPtr(proc, handle, handlePtr, sizeof(*handle));
+    if (errcode < 0) {
+        return syscallreturn_makeDoneErrno(-errcode);
+    }
+
+    unsigned int bufSize = handle->handle_bytes;
+    if (bufSize > MAX_HANDLE_SZ) {
+        return syscallreturn_makeDoneErrno(EINVAL);
+    }
+
+    int mountId = 0;
+    errcode = regularfile_nameToHandleAt(dir_desc, pathname, handle, &mountId, flags,
+                                         process_getWorkingDir(proc));
+
+    if (errcode == -EOVERFLOW) {
+        /* The OS sets the handle size that would have been needed. */
+        int rv = process_writePtr(proc, handlePtr, handle, sizeof(*handle));
+        return syscallreturn_makeDoneErrno(rv < 0 ? -rv : EOVERFLOW);
+    } else if (errcode < 0) {
+        return syscallreturn_makeDoneErrno(-errcode);
+    }
+
+    utility_debugAssert(handle->handle_bytes <= bufSize);
+
+    errcode = process_writePtr(proc, handlePtr, handle, sizeof(*handle) + handle->handle_bytes);
+    if (errcode < 0) {
+        return syscallreturn_makeDoneErrno(-errcode);
+    }
+
+    errcode = process_writePtr(proc, mountIdPtr, &mountId, sizeof(mountId));
+    if (errcode < 0) {
+        return syscallreturn_makeDoneErrno(-errcode);
+    }
+
+    return syscallreturn_makeDoneI64(0);
+}
+
+SyscallReturn syscallhandler_open_by_handle_at(SyscallHandler* sys, const SyscallArgs* args) {
+    int mountfd = args->args[0].as_i64;
+    UntypedForeignPtr handlePtr = args->args[1].as_ptr; // struct file_handle*
+    int flags = args->args[2].as_i64;
+
+    /* Validate params. */
+    RegularFile* mount_desc = NULL;
+
+    int errcode = _syscallhandler_validateDirHelper(sys, mountfd, &mount_desc);
+    if (errcode < 0) {
+        return syscallreturn_makeDoneErrno(-errcode);
+    }
+
+    /* The handle has a variable length, given in its header. */
+    _Alignas(struct file_handle) char handleBuf[sizeof(struct file_handle) + MAX_HANDLE_SZ] = {0};
+    struct file_handle* handle = (struct file_handle*)handleBuf;
+
+    const Process* proc = rustsyscallhandler_getProcess(sys);
+    errcode = process_readPtr(proc, handle, handlePtr, sizeof(*handle));
+    if (errcode < 0) {
+        return syscallreturn_makeDoneErrno(-errcode);
+    }
+
+    if (handle->handle_bytes == 0 || handle->handle_bytes > MAX_HANDLE_SZ) {
+        return syscallreturn_makeDoneErrno(EINVAL);
+    }
+
+    errcode = process_readPtr(proc, handle, handlePtr, sizeof(*handle) + handle->handle_bytes);
+    if (errcode < 0) {
+        return syscallreturn_makeDoneErrno(-errcode);
+    }
+
+    /* Create and open the file. */
+    RegularFile* file_desc = regularfile_new();
+    errcode = regularfile_openByHandleAt(
+        file_desc, mount_desc, handle, flags & ~O_CLOEXEC, process_getWorkingDir(proc));
+
+    if (errcode < 0) {
+        /* This will unref/free the RegularFile. */
+        legacyfile_close((LegacyFile*)file_desc, rustsyscallhandler_getHost(sys));
+        legacyfile_unref(file_desc);
+        return syscallreturn_makeDoneErrno(-errcode);
+    }
+
+    utility_debugAssert(errcode == 0);
+    Descriptor* desc = descriptor_fromLegacyFile((LegacyFile*)file_desc, flags & O_CLOEXEC);
+    int fd = thread_registerDescriptor(rustsyscallhandler_getThread(sys), desc);
+    return syscallreturn_makeDoneI64(fd);
+}
+
 SyscallReturn syscallhandler_newfstatat(SyscallHandler* sys, const SyscallArgs* args) {
     int dirfd = args->args[0].as_i64;
     UntypedForeignPtr pathnamePtr = args->args[1].as_ptr; // const char*
@@ -285,10 +399,36 @@ SyscallReturn syscallhandler_utimensat(SyscallHandler* sys, const SyscallArgs* a
         return syscallreturn_makeDoneErrno(-errcode);
     }
 
-    const struct timespec* times =
-        process_getReadablePtr(rustsyscallhandler_getProcess(sys), timesPtr, 2 * sizeof(*times));
-    if (!times) {
-        return syscallreturn_makeDoneErrno(EFAULT);
+    /* A NULL times pointer means both timestamps should be set to the current time. */
+    struct timespec times[2] = {
+        {.tv_sec = 0, .tv_nsec = UTIME_NOW},
+        {.tv_sec = 0, .tv_nsec = UTIME_NOW},
+    };
+
+    if (timesPtr.val) {
+        const struct timespec* pluginTimes = process_getReadablePtr(
+            rustsyscallhandler_getProcess(sys), timesPtr, 2 * sizeof(*pluginTimes));
+        if (!pluginTimes) {
+            return syscallreturn_makeDoneErrno(EFAULT);
+        }
+        times[0] = pluginTimes[0];
+        times[1] = pluginTimes[1];
+    }
+
+    /* Resolve UTIME_NOW against the emulated clock rather than letting the kernel use the
+     * real time. UTIME_OMIT is passed through so that the kernel leaves that timestamp
+     * unchanged. */
+    CEmulatedTime now = worker_getCurrentEmulatedTime();
+    for (int i = 0; i < 2; i++) {
+        if (times[i].tv_nsec == UTIME_NOW) {
+            times[i] = (struct timespec){
+                .tv_sec = now / SIMTIME_ONE_SECOND,
+                .tv_nsec = now % SIMTIME_ONE_SECOND,
+            };
+        } else if (times[i].tv_nsec != UTIME_OMIT &&
+                   (times[i].tv_nsec < 0 || times[i].tv_nsec >= 1000000000L)) {
+            return syscallreturn_makeDoneErrno(EINVAL);
+        }
     }
 
     const char* plugin_cwd = process_getWorkingDir(rustsyscallhandler_getProcess(sys));
@@ -16,7 +16,9 @@ SYSCALL_HANDLER(futimesat);
 SYSCALL_HANDLER(linkat);
 SYSCALL_HANDLER(mkdirat);
 SYSCALL_HANDLER(mknodat);
+SYSCALL_HANDLER(name_to_handle_at);
 SYSCALL_HANDLER(newfstatat);
+SYSCALL_HANDLER(open_by_handle_at);
 SYSCALL_HANDLER(openat);
 SYSCALL_HANDLER(readlinkat);
 SYSCALL_HANDLER(renameat);
@@ -65,6 +65,16 @@ impl SyscallHandler {
         Self::legacy_syscall(cshadow::syscallhandler_mknodat, ctx)
     }
 
+    log_syscall!(name_to_handle_at, /* rv */ std::ffi::c_int);
+    pub fn name_to_handle_at(ctx: &mut SyscallContext) -> SyscallResult {
+        Self::legacy_syscall(cshadow::syscallhandler_name_to_handle_at, ctx)
+    }
+
+    log_syscall!(open_by_handle_at, /* rv */ std::ffi::c_int);
+    pub fn open_by_handle_at(ctx: &mut SyscallContext) -> SyscallResult {
+        Self::legacy_syscall(cshadow::syscallhandler_open_by_handle_at, ctx)
+    }
+
     log_syscall!(readlinkat, /* rv */ std::ffi::c_int);
     pub fn readlinkat(ctx: &mut SyscallContext) -> SyscallResult {
         Self::legacy_syscall(cshadow::syscallhandler_readlinkat, ctx)
@@ -23,8 +23,8 @@
 ///////////////////////////////////////////////////////////
 
 static SyscallReturn _syscallhandler_futexWaitHelper(SyscallHandler* sys,
-                                                     UntypedForeignPtr futexVPtr, int expectedVal,
-                                                     UntypedForeignPtr timeoutVPtr,
+                                                     UntypedForeignPtr futexVPtr, bool isPrivate,
+                                                     int expectedVal, UntypedForeignPtr timeoutVPtr,
                                                      TimeoutType type) {
     // This is a new wait operation on the futex for this thread.
     // Check if a timeout was given in the syscall args.
@@ -59,20 +59,10 @@ static SyscallReturn _syscallhandler_futexWaitHelper(SyscallHandler* sys,
         return syscallreturn_makeDoneErrno(EAGAIN);
     }
 
-    // Convert the virtual ptr to a physical ptr that can uniquely identify the futex
     ManagedPhysicalMemoryAddr futexPPtr =
-        process_getPhysicalAddress(rustsyscallhandler_getProcess(sys), futexVPtr);
-
-    // Check if we already have a futex
-    FutexTable* ftable = host_getFutexTable(rustsyscallhandler_getHost(sys));
-    Futex* futex = futextable_get(ftable, futexPPtr);
-
-    if (futex != NULL) {
-        futex_ref(futex);
-    }
+        process_getFutexPhysicalAddress(rustsyscallhandler_getProcess(sys), futexVPtr, isPrivate);
 
     if (rustsyscallhandler_wasBlocked(sys)) {
-        utility_debugAssert(futex != NULL);
         int result = 0;
 
         // We already blocked on wait, so this is either a timeout or wakeup
@@ -91,17 +81,19 @@ static SyscallReturn _syscallhandler_futexWaitHelper(SyscallHandler* sys,
             result = 0;
         }
 
-        // Dynamically clean up the futex if needed
-        if (futex_getListenerCount(futex) == 0) {
-            trace("Dynamically freed a futex object for futex addr %p", (void*)futexPPtr.val);
-            bool success = futextable_remove(ftable, futexPPtr);
-            utility_debugAssert(success);
-        }
-
-        futex_unref(futex);
+        // The futex object was dynamically cleaned up (if needed) when our syscall condition
+        // stopped listening on it.
         return syscallreturn_makeDoneI64(result);
     }
 
+    // Check if we already have a futex
+    FutexTable* ftable = host_getFutexTable(rustsyscallhandler_getHost(sys));
+    Futex* futex = futextable_get(ftable, futexPPtr);
+
+    if (futex != NULL) {
+        futex_ref(futex);
+    }
+
     // We'll need to block, dynamically create a futex if one does not yet exist
     if (!futex) {
         trace("Dynamically created a new futex object for futex addr %p", (void*)futexPPtr.val);
@@ -128,10 +120,10 @@ static SyscallReturn _syscallhandler_futexWaitHelper(SyscallHandler* sys,
 }
 
 static SyscallReturn _syscallhandler_futexWakeHelper(SyscallHandler* sys,
-                                                     UntypedForeignPtr futexVPtr, int numWakeups) {
-    // Convert the virtual ptr to a physical ptr that can uniquely identify the futex
+                                                     UntypedForeignPtr futexVPtr, bool isPrivate,
+                                                     int numWakeups) {
     ManagedPhysicalMemoryAddr futexPPtr =
-        process_getPhysicalAddress(rustsyscallhandler_getProcess(sys), futexVPtr);
+        process_getFutexPhysicalAddress(rustsyscallhandler_getProcess(sys), futexVPtr, isPrivate);
 
     // Lookup the futex in the futex table
     FutexTable* ftable = host_getFutexTable(rustsyscallhandler_getHost(sys));
@@ -153,10 +145,6 @@ static SyscallReturn _syscallhandler_futexWakeHelper(SyscallHandler* sys,
 // System Calls
 ///////////////////////////////////////////////////////////
 
-// TODO: currently only supports uaddr from the same virtual address space (i.e., threads)
-// Support across different address spaces requires us to compute a unique id from the
-// hardware address (i.e., page table and offset). This is needed, e.g., when using
-// futexes across process boundaries.
 SyscallReturn syscallhandler_futex(SyscallHandler* sys, const SyscallArgs* args) {
     utility_debugAssert(sys && args);
 
@@ -170,6 +158,7 @@ SyscallReturn syscallhandler_futex(SyscallHandler* sys, const SyscallArgs* args)
     const int possible_options = FUTEX_PRIVATE_FLAG | FUTEX_CLOCK_REALTIME;
     int options = futex_op & possible_options;
     int operation = futex_op & ~possible_options;
+    bool isPrivate = (options & FUTEX_PRIVATE_FLAG) != 0;
 
     trace("futex called with addr=%p op=%i (operation=%i and options=%i) and val=%i",
           (void*)uaddrptr.val, futex_op, operation, options, val);
@@ -178,19 +167,19 @@ SyscallReturn syscallhandler_futex(SyscallHandler* sys, const SyscallArgs* args)
         case FUTEX_WAIT: {
             trace("Handling FUTEX_WAIT operation %i", operation);
             return _syscallhandler_futexWaitHelper(
-                sys, uaddrptr, val, timeoutptr, TIMEOUT_RELATIVE);
+                sys, uaddrptr, isPrivate, val, timeoutptr, TIMEOUT_RELATIVE);
         }
 
         case FUTEX_WAKE: {
             trace("Handling FUTEX_WAKE operation %i", operation);
-            return _syscallhandler_futexWakeHelper(sys, uaddrptr, val);
+            return _syscallhandler_futexWakeHelper(sys, uaddrptr, isPrivate, val);
         }
 
         case FUTEX_WAIT_BITSET: {
             trace("Handling FUTEX_WAIT_BITSET operation %i bitset %d", operation, val3);
             if (val3 == FUTEX_BITSET_MATCH_ANY) {
                 return _syscallhandler_futexWaitHelper(
-                    sys, uaddrptr, val, timeoutptr, TIMEOUT_ABSOLUTE);
+                    sys, uaddrptr, isPrivate, val, timeoutptr, TIMEOUT_ABSOLUTE);
             }
             // Other bitsets not yet handled.
             break;
@@ -198,7 +187,7 @@ SyscallReturn syscallhandler_futex(SyscallHandler* sys, const SyscallArgs* args)
         case FUTEX_WAKE_BITSET: {
             trace("Handling FUTEX_WAKE_BITSET operation %i bitset %d", operation, val3);
             if (val3 == FUTEX_BITSET_MATCH_ANY) {
-                return _syscallhandler_futexWakeHelper(sys, uaddrptr, val);
+                return _syscallhandler_futexWakeHelper(sys, uaddrptr, isPrivate, val);
             }
             // Other bitsets not yet handled.
             break;
@@ -206,7 +195,6 @@ SyscallReturn syscallhandler_futex(SyscallHandler* sys, const SyscallArgs* args)
 
         case FUTEX_FD:
         case FUTEX_REQUEUE:
-        case FUTEX_WAKE_OP:
         case FUTEX_LOCK_PI:
         case FUTEX_TRYLOCK_PI:
         case FUTEX_UNLOCK_PI:
@@ -1,8 +1,17 @@
 use linux_api::errno::Errno;
-use shadow_shim_helper_rs::syscall_types::ForeignPtr;
+use linux_api::futex::{
+    FutexOpFlags, FUTEX_CMD_MASK, FUTEX_OP_ADD, FUTEX_OP_ANDN, FUTEX_OP_CMP_EQ, FUTEX_OP_CMP_GE,
+    FUTEX_OP_CMP_GT, FUTEX_OP_CMP_LE, FUTEX_OP_CMP_LT, FUTEX_OP_CMP_NE, FUTEX_OP_OPARG_SHIFT,
+    FUTEX_OP_OR, FUTEX_OP_SET, FUTEX_OP_XOR,
+};
+use shadow_shim_helper_rs::syscall_types::{ForeignPtr, ManagedPhysicalMemoryAddr};
 
 use crate::cshadow as c;
+use crate::host::futex_table::FutexRef;
 use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
+use crate::host::syscall::type_formatting::{
+    SyscallFutexOpArg, SyscallFutexTimeoutArg, SyscallFutexVal3Arg,
+};
 use crate::host::syscall::types::SyscallError;
 
 impl SyscallHandler {
@@ -10,24 +19,129 @@ impl SyscallHandler {
         futex,
         /* rv */ std::ffi::c_int,
         /* uaddr */ *const u32,
-        /* op */ std::ffi::c_int,
+        /* op */ SyscallFutexOpArg,
         /* val */ u32,
-        /* utime */ *const std::ffi::c_void,
+        /* utime */ SyscallFutexTimeoutArg</* op */ 1>,
         /* uaddr2 */ *const u32,
-        /* val3 */ u32,
+        /* val3 */ SyscallFutexVal3Arg</* op */ 1>,
     );
     pub fn futex(
         ctx: &mut SyscallContext,
-        _uaddr: ForeignPtr<u32>,
-        _op: std::ffi::c_int,
-        _val: u32,
-        _utime: ForeignPtr<linux_api::time::kernel_timespec>,
-        _uaddr2: ForeignPtr<u32>,
-        _val3: u32,
+        uaddr: ForeignPtr<u32>,
+        op: std::ffi::c_int,
+        val: u32,
+        utime: ForeignPtr<linux_api::time::kernel_timespec>,
+        uaddr2: ForeignPtr<u32>,
+        val3: u32,
     ) -> Result<std::ffi::c_int, SyscallError> {
+        let is_private =
+            FutexOpFlags::from_bits_retain(op).contains(FutexOpFlags::FUTEX_PRIVATE_FLAG);
+        let cmd = FutexOpFlags::from_bits_retain(op & FUTEX_CMD_MASK);
+
+        if cmd == FutexOpFlags::FUTEX_WAKE_OP {
+            // For this operation the timeout argument is instead the number of waiters to wake on
+            // `uaddr2`.
+            let val2 = usize::from(utime) as u32;
+            return Self::futex_wake_op(ctx, uaddr, is_private, val, val2, uaddr2, val3);
+        }
+
+        if cmd == FutexOpFlags::FUTEX_CMP_REQUEUE {
+            // For this operation the timeout argument is instead the maximum number of waiters to
+            // requeue onto `uaddr2`.
+            let val2 = usize::from(utime) as u32;
+            return Self::futex_cmp_requeue(ctx, uaddr, is_private, val, val2, uaddr2, val3);
+        }
+
         Self::legacy_syscall(c::syscallhandler_futex, ctx)
     }
 
+    /// Convert the virtual address of a futex word to a physical address that uniquely identifies
+    /// the futex.
+    fn futex_physical_addr(
+        ctx: &SyscallContext,
+        uaddr: ForeignPtr<u32>,
+        is_private: bool,
+    ) -> ManagedPhysicalMemoryAddr {
+        ctx.objs
+            .process
+            .futex_physical_address(uaddr.cast::<()>(), is_private)
+    }
+
+    /// Wake up to `num_wakeups` waiters on the futex at `uaddr`, returning the number woken.
+    fn futex_wake_helper(
+        ctx: &SyscallContext,
+        uaddr: ForeignPtr<u32>,
+        is_private: bool,
+        num_wakeups: u32,
+    ) -> u32 {
+        let addr = Self::futex_physical_addr(ctx, uaddr, is_private);
+        let futexes = ctx.objs.host.futextable_borrow();
+
+        match futexes.get(addr) {
+            Some(futex) if num_wakeups > 0 => futex.wake(num_wakeups),
+            _ => 0,
+        }
+    }
+
+    fn futex_wake_op(
+        ctx: &mut SyscallContext,
+        uaddr: ForeignPtr<u32>,
+        is_private: bool,
+        val: u32,
+        val2: u32,
+        uaddr2: ForeignPtr<u32>,
+        val3: u32,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        // futex(2): the operation and comparison are encoded in `val3` as:
+        // +---+---+-----------+-----------+
+        // |op |cmp|   oparg   |  cmparg   |
+        // +---+---+-----------+-----------+
+        //   4   4       12          12    <== # of bits
+        let op = (val3 >> 28) & 0xf;
+        let cmp = (val3 >> 24) & 0xf;
+        // Both arguments are sign-extended 12-bit values.
+        let mut oparg = ((val3 << 8) as i32) >> 20;
+        let cmparg = ((val3 << 20) as i32) >> 20;
+
+        if op & FUTEX_OP_OPARG_SHIFT != 0 {
+            // Linux warns about out-of-range shifts and masks them rather than failing.
+            oparg = 1 << (oparg & 31);
+        }
+
+        let old_val = ctx.objs.memory().read(uaddr2)?;
+        let new_val = match op & !FUTEX_OP_OPARG_SHIFT {
+            FUTEX_OP_SET => oparg as u32,
+            FUTEX_OP_ADD => old_val.wrapping_add(oparg as u32),
+            FUTEX_OP_OR => old_val | oparg as u32,
+            FUTEX_OP_ANDN => old_val & !(oparg as u32),
+            FUTEX_OP_XOR => old_val ^ oparg as u32,
+            _ => return Err(Errno::ENOSYS.into()),
+        };
+
+        // The comparison is signed.
+        let old_val_signed = old_val as i32;
+        let should_wake_uaddr2 = match cmp {
+            FUTEX_OP_CMP_EQ => old_val_signed == cmparg,
+            FUTEX_OP_CMP_NE => old_val_signed != cmparg,
+            FUTEX_OP_CMP_LT => old_val_signed < cmparg,
+            FUTEX_OP_CMP_LE => old_val_signed <= cmparg,
+            FUTEX_OP_CMP_GT => old_val_signed > cmparg,
+            FUTEX_OP_CMP_GE => old_val_signed >= cmparg,
+            _ => return Err(Errno::ENOSYS.into()),
+        };
+
+        // Normally this read-modify-write is done atomically. Since Shadow does not run multiple
+        // threads from the same plugin at the same time, we do not use atomic ops.
+        ctx.objs.memory_mut().write(uaddr2, &new_val)?;
+
+        let mut num_woken = Self::futex_wake_helper(ctx, uaddr, is_private, val);
+        if should_wake_uaddr2 {
+            num_woken += Self::futex_wake_helper(ctx, uaddr2, is_private, val2);
+        }
+
+        Ok(num_woken.try_into().unwrap())
+    }
+
     log_syscall!(
         get_robust_list,
         /* rv */ std::ffi::c_int,
@@ -59,4 +173,55 @@ impl SyscallHandler {
         warn_once_then_debug!("set_robust_list was called but we don't yet support it");
         Err(Errno::ENOSYS)
     }
+
+    fn futex_cmp_requeue(
+        ctx: &mut SyscallContext,
+        uaddr: ForeignPtr<u32>,
+        is_private: bool,
+        val: u32,
+        val2: u32,
+        uaddr2: ForeignPtr<u32>,
+        val3: u32,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        // Linux treats both counts as signed.
+        if (val as i32) < 0 || (val2 as i32) < 0 {
+            return Err(Errno::EINVAL.into());
+        }
+
+        // futex(2): "If the value at `uaddr` is not equal to `val3`, then the operation fails
+        // with the error EAGAIN".
+        if ctx.objs.memory().read(uaddr)? != val3 {
+            return Err(Errno::EAGAIN.into());
+        }
+
+        let addr = Self::futex_physical_addr(ctx, uaddr, is_private);
+        let addr2 = Self::futex_physical_addr(ctx, uaddr2, is_private);
+
+        let num_woken = Self::futex_wake_helper(ctx, uaddr, is_private, val);
+
+        let mut futexes = ctx.objs.host.futextable_borrow_mut();
+        let Some(futex) = futexes.get(addr) else {
+            return Ok(num_woken.try_into().unwrap());
+        };
+        if val2 == 0 || futex.listener_count() == 0 {
+            return Ok(num_woken.try_into().unwrap());
+        }
+
+        // Dynamically create the target futex if it doesn't exist yet.
+        if futexes.get(addr2).is_none() {
+            let target = unsafe { FutexRef::new(c::futex_new(addr2)) };
+            assert!(futexes.add(target).is_ok());
+        }
+
+        let futex = futexes.get(addr).unwrap();
+        let target = futexes.get(addr2).unwrap();
+        let num_requeued = futex.requeue(target, val2);
+
+        // If every remaining waiter was requeued, nobody is left to clean up the futex.
+        if futex.listener_count() == 0 {
+            futexes.remove(addr);
+        }
+
+        Ok((num_woken + num_requeued).try_into().unwrap())
+    }
 }
@@ -0,0 +1,163 @@
+use std::ffi::{CStr, CString};
+use std::sync::Arc;
+
+use atomic_refcell::AtomicRefCell;
+use linux_api::errno::Errno;
+use linux_api::fcntl::DescriptorFlags;
+use shadow_shim_helper_rs::syscall_types::ForeignPtr;
+
+use crate::host::descriptor::descriptor_table::DescriptorHandle;
+use crate::host::descriptor::inotify::Inotify;
+use crate::host::descriptor::{CompatFile, Descriptor, File, FileStatus, OpenFile};
+use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
+use crate::host::syscall::type_formatting::SyscallStringArg;
+use crate::host::syscall::types::{ForeignArrayPtr, SyscallError};
+use crate::utility::callback_queue::CallbackQueue;
+
+impl SyscallHandler {
+    log_syscall!(inotify_init, /* rv */ std::ffi::c_int);
+    pub fn inotify_init(ctx: &mut SyscallContext) -> Result<DescriptorHandle, SyscallError> {
+        Self::inotify_init_helper(ctx, 0)
+    }
+
+    log_syscall!(
+        inotify_init1,
+        /* rv */ std::ffi::c_int,
+        /* flags */ std::ffi::c_int,
+    );
+    pub fn inotify_init1(
+        ctx: &mut SyscallContext,
+        flags: std::ffi::c_int,
+    ) -> Result<DescriptorHandle, SyscallError> {
+        Self::inotify_init_helper(ctx, flags)
+    }
+
+    fn inotify_init_helper(
+        ctx: &mut SyscallContext,
+        flags: std::ffi::c_int,
+    ) -> Result<DescriptorHandle, SyscallError> {
+        // inotify_init(2): "EINVAL (inotify_init1()) An invalid value was specified in flags."
+        if flags & !(libc::IN_NONBLOCK | libc::IN_CLOEXEC) != 0 {
+            log::debug!("Invalid inotify_init1 flags: {flags}");
+            return Err(Errno::EINVAL.into());
+        }
+
+        let mut file_flags = FileStatus::empty();
+        let mut desc_flags = DescriptorFlags::empty();
+
+        if flags & libc::IN_NONBLOCK != 0 {
+            file_flags.insert(FileStatus::NONBLOCK);
+        }
+
+        if flags & libc::IN_CLOEXEC != 0 {
+            desc_flags.insert(DescriptorFlags::FD_CLOEXEC);
+        }
+
+        let file = Arc::new(AtomicRefCell::new(Inotify::new(file_flags)?));
+
+        // The host will check the instance for new events after each syscall.
+        ctx.objs.host.add_inotify_instance(&file);
+
+        let mut desc = Descriptor::new(CompatFile::New(OpenFile::new(File::Inotify(file))));
+        desc.set_flags(desc_flags);
+
+        let fd = ctx
+            .objs
+            .thread
+            .descriptor_table_borrow_mut(ctx.objs.host)
+            .register_descriptor(desc)
+            .or(Err(Errno::EMFILE))?;
+
+        log::trace!("inotify_init() returning fd {fd}");
+
+        Ok(fd)
+    }
+
+    log_syscall!(
+        inotify_add_watch,
+        /* rv */ std::ffi::c_int,
+        /* fd */ std::ffi::c_int,
+        /* pathname */ SyscallStringArg,
+        /* mask */ u32,
+    );
+    pub fn inotify_add_watch(
+        ctx: &mut SyscallContext,
+        fd: std::ffi::c_int,
+        pathname: ForeignPtr<std::ffi::c_char>,
+        mask: u32,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        let inotify = get_inotify(ctx, fd)?;
+
+        let mut path_buf = [0u8; linux_api::limits::PATH_MAX];
+        let path_buf_capacity = path_buf.len();
+        let path = ctx.objs.memory().copy_str_from_ptr(
+            &mut path_buf,
+            ForeignArrayPtr::new(pathname.cast::<u8>(), path_buf_capacity),
+        )?;
+
+        if path.is_empty() {
+            return Err(Errno::ENOENT.into());
+        }
+
+        // The native watch is added from Shadow's own process, so relative paths need to be
+        // resolved against the plugin's working directory.
+        let abs_path = absolute_path(&ctx.objs.process.current_working_dir(), path);
+
+        let wd = inotify.borrow_mut().add_watch(&abs_path, mask)?;
+        Ok(wd)
+    }
+
+    log_syscall!(
+        inotify_rm_watch,
+        /* rv */ std::ffi::c_int,
+        /* fd */ std::ffi::c_int,
+        /* wd */ std::ffi::c_int,
+    );
+    pub fn inotify_rm_watch(
+        ctx: &mut SyscallContext,
+        fd: std::ffi::c_int,
+        wd: std::ffi::c_int,
+    ) -> Result<(), SyscallError> {
+        let inotify = get_inotify(ctx, fd)?;
+
+        CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+            inotify.borrow_mut().rm_watch(wd, cb_queue)
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Get the inotify instance for `fd`, or `EINVAL` if `fd` isn't an inotify instance.
+fn get_inotify(
+    ctx: &mut SyscallContext,
+    fd: std::ffi::c_int,
+) -> Result<Arc<AtomicRefCell<Inotify>>, Errno> {
+    let desc_table = ctx.objs.thread.descriptor_table_borrow(ctx.objs.host);
+    let desc = SyscallHandler::get_descriptor(&desc_table, fd)?;
+
+    // inotify_add_watch(2): "EINVAL [...] fd is not an inotify file descriptor."
+    let CompatFile::New(file) = desc.file() else {
+        return Err(Errno::EINVAL);
+    };
+    let File::Inotify(inotify) = file.inner_file() else {
+        return Err(Errno::EINVAL);
+    };
+
+    Ok(Arc::clone(inotify))
+}
+
+/// Resolve `path` relative to `base_dir` if it isn't already absolute.
+fn absolute_path(base_dir: &CStr, path: &CStr) -> CString {
+    let path_bytes = path.to_bytes();
+    if path_bytes.first() == Some(&b'/') {
+        return path.to_owned();
+    }
+
+    let base_dir_bytes = base_dir.to_bytes();
+    let mut abs_path = Vec::with_capacity(base_dir_bytes.len() + path_bytes.len() + 1);
+    abs_path.extend(base_dir_bytes);
+    abs_path.push(b'/');
+    abs_path.extend(path_bytes);
+    CString::new(abs_path).unwrap()
+}
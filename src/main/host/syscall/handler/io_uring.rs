@@ -0,0 +1,82 @@
+use linux_api::errno::Errno;
+use shadow_shim_helper_rs::syscall_types::ForeignPtr;
+
+use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
+use crate::host::syscall::types::SyscallError;
+
+// Shadow doesn't support io_uring. Runtimes such as tokio and glib probe for it with
+// `io_uring_setup` and fall back to epoll if it returns ENOSYS, so we handle these syscalls
+// explicitly rather than let them reach the "unsupported syscall" warning.
+
+impl SyscallHandler {
+    // <https://github.com/torvalds/linux/tree/v6.3/io_uring/io_uring.c>
+    // ```
+    // SYSCALL_DEFINE2(io_uring_setup, u32, entries, struct io_uring_params __user *, params)
+    // ```
+    log_syscall!(
+        io_uring_setup,
+        /* rv */ std::ffi::c_int,
+        /* entries */ u32,
+        /* params */ *const std::ffi::c_void,
+    );
+    pub fn io_uring_setup(
+        _ctx: &mut SyscallContext,
+        _entries: u32,
+        _params: ForeignPtr<std::ffi::c_void>,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        log::debug!("io_uring is not supported; returning ENOSYS");
+        Err(Errno::ENOSYS.into())
+    }
+
+    // <https://github.com/torvalds/linux/tree/v6.3/io_uring/io_uring.c>
+    // ```
+    // SYSCALL_DEFINE6(io_uring_enter, unsigned int, fd, u32, to_submit, u32, min_complete,
+    //                 u32, flags, const void __user *, argp, size_t, argsz)
+    // ```
+    log_syscall!(
+        io_uring_enter,
+        /* rv */ std::ffi::c_int,
+        /* fd */ std::ffi::c_uint,
+        /* to_submit */ u32,
+        /* min_complete */ u32,
+        /* flags */ u32,
+        /* argp */ *const std::ffi::c_void,
+        /* argsz */ libc::size_t,
+    );
+    pub fn io_uring_enter(
+        _ctx: &mut SyscallContext,
+        _fd: std::ffi::c_uint,
+        _to_submit: u32,
+        _min_complete: u32,
+        _flags: u32,
+        _argp: ForeignPtr<std::ffi::c_void>,
+        _argsz: libc::size_t,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        log::debug!("io_uring is not supported; returning ENOSYS");
+        Err(Errno::ENOSYS.into())
+    }
+
+    // <https://github.com/torvalds/linux/tree/v6.3/io_uring/io_uring.c>
+    // ```
+    // SYSCALL_DEFINE4(io_uring_register, unsigned int, fd, unsigned int, opcode,
+    //                 void __user *, arg, unsigned int, nr_args)
+    // ```
+    log_syscall!(
+        io_uring_register,
+        /* rv */ std::ffi::c_int,
+        /* fd */ std::ffi::c_uint,
+        /* opcode */ std::ffi::c_uint,
+        /* arg */ *const std::ffi::c_void,
+        /* nr_args */ std::ffi::c_uint,
+    );
+    pub fn io_uring_register(
+        _ctx: &mut SyscallContext,
+        _fd: std::ffi::c_uint,
+        _opcode: std::ffi::c_uint,
+        _arg: ForeignPtr<std::ffi::c_void>,
+        _nr_args: std::ffi::c_uint,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        log::debug!("io_uring is not supported; returning ENOSYS");
+        Err(Errno::ENOSYS.into())
+    }
+}
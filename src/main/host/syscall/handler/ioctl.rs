@@ -7,6 +7,7 @@ use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 use crate::cshadow as c;
 use crate::host::descriptor::{CompatFile, FileStatus};
 use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
+use crate::host::syscall::type_formatting::SyscallIoctlRequestArg;
 use crate::host::syscall::types::SyscallResult;
 
 impl SyscallHandler {
@@ -14,7 +15,7 @@ impl SyscallHandler {
         ioctl,
         /* rv */ std::ffi::c_int,
         /* fd */ std::ffi::c_uint,
-        /* cmd */ std::ffi::c_uint,
+        /* cmd */ SyscallIoctlRequestArg,
         /* arg */ std::ffi::c_ulong,
     );
     pub fn ioctl(
@@ -72,7 +73,7 @@ impl SyscallHandler {
         // all file types that shadow implements should support non-blocking operation
         if cmd == IoctlRequest::FIONBIO {
             let arg_ptr = arg_ptr.cast::<std::ffi::c_int>();
-            let arg = ctx.objs.process.memory_borrow_mut().read(arg_ptr)?;
+            let arg = ctx.objs.memory_mut().read(arg_ptr)?;
 
             let mut status = file.status();
             status.set(FileStatus::NONBLOCK, arg != 0);
@@ -82,6 +83,6 @@ impl SyscallHandler {
         }
 
         // handle file-specific ioctls
-        file.ioctl(cmd, arg_ptr, &mut ctx.objs.process.memory_borrow_mut())
+        file.ioctl(cmd, arg_ptr, &mut ctx.objs.memory_mut())
     }
 }
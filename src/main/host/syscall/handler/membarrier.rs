@@ -0,0 +1,55 @@
+use linux_api::errno::Errno;
+use linux_api::membarrier::{
+    MEMBARRIER_CMD_GLOBAL, MEMBARRIER_CMD_GLOBAL_EXPEDITED, MEMBARRIER_CMD_PRIVATE_EXPEDITED,
+    MEMBARRIER_CMD_QUERY, MEMBARRIER_CMD_REGISTER_GLOBAL_EXPEDITED,
+    MEMBARRIER_CMD_REGISTER_PRIVATE_EXPEDITED,
+};
+
+use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
+use crate::host::syscall::types::SyscallError;
+
+/// The membarrier commands that we support.
+const SUPPORTED_COMMANDS: std::ffi::c_int = MEMBARRIER_CMD_GLOBAL
+    | MEMBARRIER_CMD_GLOBAL_EXPEDITED
+    | MEMBARRIER_CMD_REGISTER_GLOBAL_EXPEDITED
+    | MEMBARRIER_CMD_PRIVATE_EXPEDITED
+    | MEMBARRIER_CMD_REGISTER_PRIVATE_EXPEDITED;
+
+impl SyscallHandler {
+    log_syscall!(
+        membarrier,
+        /* rv */ std::ffi::c_int,
+        /* cmd */ std::ffi::c_int,
+        /* flags */ std::ffi::c_uint,
+        /* cpu_id */ std::ffi::c_int,
+    );
+    pub fn membarrier(
+        _ctx: &mut SyscallContext,
+        cmd: std::ffi::c_int,
+        flags: std::ffi::c_uint,
+        _cpu_id: std::ffi::c_int,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        // the only flag is `MEMBARRIER_CMD_FLAG_CPU`, which is only valid for
+        // `MEMBARRIER_CMD_PRIVATE_EXPEDITED_RSEQ` (which we don't support)
+        if flags != 0 {
+            log::debug!("Unsupported membarrier flags: {flags:#x}");
+            return Err(Errno::EINVAL.into());
+        }
+
+        if cmd == MEMBARRIER_CMD_QUERY {
+            return Ok(SUPPORTED_COMMANDS);
+        }
+
+        // membarrier(2) takes a single command, not a bitmask of commands
+        if cmd.count_ones() != 1 || cmd & SUPPORTED_COMMANDS == 0 {
+            log::debug!("Unsupported membarrier command: {cmd:#x}");
+            return Err(Errno::EINVAL.into());
+        }
+
+        // Only one thread of a host runs at a time, and a thread that stops running has already
+        // gone through a memory barrier when it switched back to Shadow, so there's nothing to do.
+        // Linux returns EPERM for the private expedited command if the process hasn't registered
+        // for it, but we don't bother tracking registrations.
+        Ok(0)
+    }
+}
@@ -1,5 +1,5 @@
 use std::os::unix::ffi::OsStrExt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use linux_api::errno::Errno;
 use linux_api::fcntl::OFlag;
@@ -8,9 +8,10 @@ use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
 use crate::cshadow as c;
 use crate::host::descriptor::{CompatFile, FileState};
-use crate::host::memory_manager::AllocdMem;
+use crate::host::memory_manager::{page_size, AllocdMem};
 use crate::host::syscall::handler::{SyscallContext, SyscallHandler, ThreadContext};
 use crate::host::syscall::types::SyscallError;
+use crate::utility::proc_maps;
 
 impl SyscallHandler {
     log_syscall!(
@@ -23,7 +24,7 @@ impl SyscallHandler {
         addr: ForeignPtr<u8>,
     ) -> Result<ForeignPtr<u8>, SyscallError> {
         // delegate to the memory manager
-        let mut memory_manager = ctx.objs.process.memory_borrow_mut();
+        let mut memory_manager = ctx.objs.memory_mut();
         memory_manager.handle_brk(ctx.objs, addr)
     }
 
@@ -63,14 +64,140 @@ impl SyscallHandler {
 
         let flags = flags as i32;
 
+        // mremap(2): "EINVAL MREMAP_DONTUNMAP was specified without specifying MREMAP_MAYMOVE" and
+        // "EINVAL MREMAP_DONTUNMAP was specified, but ... old_size was not equal to new_size"
+        if (flags & libc::MREMAP_DONTUNMAP) != 0
+            && ((flags & libc::MREMAP_MAYMOVE) == 0 || old_size != new_size)
+        {
+            return Err(Errno::EINVAL.into());
+        }
+
         let old_addr = ForeignPtr::<()>::from(old_addr).cast::<u8>();
         let new_addr = ForeignPtr::<()>::from(new_addr).cast::<u8>();
 
         // delegate to the memory manager
-        let mut memory_manager = ctx.objs.process.memory_borrow_mut();
+        let mut memory_manager = ctx.objs.memory_mut();
         memory_manager.handle_mremap(ctx.objs, old_addr, old_size, new_size, flags, new_addr)
     }
 
+    // <https://github.com/torvalds/linux/tree/v6.3/mm/mmap.c#L2942>
+    // ```
+    // SYSCALL_DEFINE5(remap_file_pages, unsigned long, start, unsigned long, size,
+    //                 unsigned long, prot, unsigned long, pgoff, unsigned long, flags)
+    // ```
+    log_syscall!(
+        remap_file_pages,
+        /* rv */ std::ffi::c_int,
+        /* start */ *const std::ffi::c_void,
+        /* size */ std::ffi::c_ulong,
+        /* prot */ std::ffi::c_ulong,
+        /* pgoff */ std::ffi::c_ulong,
+        /* flags */ std::ffi::c_ulong,
+    );
+    pub fn remap_file_pages(
+        ctx: &mut SyscallContext,
+        start: std::ffi::c_ulong,
+        size: std::ffi::c_ulong,
+        prot: std::ffi::c_ulong,
+        pgoff: std::ffi::c_ulong,
+        flags: std::ffi::c_ulong,
+    ) -> Result<(), SyscallError> {
+        // This syscall is deprecated, and Linux emulates it by mapping the requested pages of the
+        // file over the existing mapping. We do the same.
+
+        // the only supported value for `prot` is 0
+        if prot != 0 {
+            return Err(Errno::EINVAL.into());
+        }
+
+        let page_size = page_size();
+        let start: usize = start.try_into().unwrap();
+        let start = start & !(page_size - 1);
+        let size: usize = size.try_into().unwrap();
+        let size = size & !(page_size - 1);
+        let pgoff: usize = pgoff.try_into().unwrap();
+
+        let Some(end) = start.checked_add(size).filter(|end| *end > start) else {
+            return Err(Errno::EINVAL.into());
+        };
+        let Some(offset) = pgoff
+            .checked_mul(page_size)
+            .filter(|offset| offset.checked_add(size).is_some())
+        else {
+            return Err(Errno::EINVAL.into());
+        };
+
+        // find the existing mapping, which must be a single shared mapping of a file
+        let native_pid = ctx.objs.thread.native_pid().as_raw_nonzero().get();
+        let mappings = proc_maps::mappings_for_pid(native_pid).map_err(|e| {
+            log::warn!("Unable to read the mappings for process {native_pid}: {e}");
+            Errno::EINVAL
+        })?;
+        let Some(mapping) = mappings
+            .into_iter()
+            .find(|m| m.begin <= start && start < m.end)
+        else {
+            return Err(Errno::EINVAL.into());
+        };
+
+        if end > mapping.end {
+            log::debug!("remap_file_pages range spans multiple mappings");
+            return Err(Errno::EINVAL.into());
+        }
+        if mapping.sharing != proc_maps::Sharing::Shared {
+            log::debug!("remap_file_pages on a private mapping");
+            return Err(Errno::EINVAL.into());
+        }
+        let path = match mapping.path {
+            Some(proc_maps::MappingPath::Path(path)) if !mapping.deleted => path,
+            _ => {
+                log::debug!("remap_file_pages on a mapping without an accessible file");
+                return Err(Errno::EINVAL.into());
+            }
+        };
+
+        let mut map_prot = ProtFlags::empty();
+        if mapping.read {
+            map_prot |= ProtFlags::PROT_READ;
+        }
+        if mapping.write {
+            map_prot |= ProtFlags::PROT_WRITE;
+        }
+        if mapping.execute {
+            map_prot |= ProtFlags::PROT_EXEC;
+        }
+
+        let mut map_flags = MapFlags::MAP_SHARED | MapFlags::MAP_FIXED | MapFlags::MAP_POPULATE;
+        map_flags |= MapFlags::from_bits_retain(flags) & MapFlags::MAP_NONBLOCK;
+
+        let open_flags = if mapping.write {
+            OFlag::O_RDWR
+        } else {
+            OFlag::O_RDONLY
+        };
+
+        // this fd exists in the plugin and not shadow; make sure to close this before returning
+        let Ok(plugin_fd) = Self::open_plugin_path(ctx.objs, &path, open_flags, 0) else {
+            log::debug!("Unable to open '{}' in the plugin", path.display());
+            return Err(Errno::EINVAL.into());
+        };
+
+        let mmap_result = ctx.objs.memory_mut().do_mmap(
+            ctx.objs,
+            ForeignPtr::<()>::from(start).cast::<u8>(),
+            size,
+            map_prot,
+            map_flags,
+            plugin_fd,
+            offset as i64,
+        );
+
+        Self::close_plugin_file(ctx.objs, plugin_fd);
+
+        mmap_result?;
+        Ok(())
+    }
+
     // <https://github.com/torvalds/linux/tree/v6.3/mm/mmap.c#L2786>
     // ```
     // SYSCALL_DEFINE2(munmap, unsigned long, addr, size_t, len)
@@ -90,7 +217,7 @@ impl SyscallHandler {
         let addr = ForeignPtr::<()>::from(addr).cast::<u8>();
 
         // delegate to the memory manager
-        let mut memory_manager = ctx.objs.process.memory_borrow_mut();
+        let mut memory_manager = ctx.objs.memory_mut();
         memory_manager.handle_munmap(ctx.objs, addr, len)
     }
 
@@ -128,10 +255,36 @@ impl SyscallHandler {
         };
 
         // delegate to the memory manager
-        let mut memory_manager = ctx.objs.process.memory_borrow_mut();
+        let mut memory_manager = ctx.objs.memory_mut();
         memory_manager.handle_mprotect(ctx.objs, addr, len, prot)
     }
 
+    // <https://github.com/torvalds/linux/tree/v6.3/mm/mincore.c#L232>
+    // ```
+    // SYSCALL_DEFINE3(mincore, unsigned long, start, size_t, len,
+    //                 unsigned char __user *, vec)
+    // ```
+    log_syscall!(
+        mincore,
+        /* rv */ std::ffi::c_int,
+        /* addr */ *const std::ffi::c_void,
+        /* length */ usize,
+        /* vec */ *const std::ffi::c_void,
+    );
+    pub fn mincore(
+        ctx: &mut SyscallContext,
+        addr: std::ffi::c_ulong,
+        len: usize,
+        vec: ForeignPtr<u8>,
+    ) -> Result<(), SyscallError> {
+        let addr: usize = addr.try_into().unwrap();
+        let addr = ForeignPtr::<()>::from(addr).cast::<u8>();
+
+        // delegate to the memory manager
+        let mut memory_manager = ctx.objs.memory_mut();
+        memory_manager.handle_mincore(addr, len, vec)
+    }
+
     // <https://github.com/torvalds/linux/tree/v6.3/arch/x86/kernel/sys_x86_64.c#L86>
     // ```
     // SYSCALL_DEFINE6(mmap, unsigned long, addr, unsigned long, len,
@@ -257,7 +410,7 @@ impl SyscallHandler {
         };
 
         // delegate execution of the mmap itself to the memory manager
-        let mut memory_manager = ctx.objs.process.memory_borrow_mut();
+        let mut memory_manager = ctx.objs.memory_mut();
         let mmap_result = memory_manager.do_mmap(
             ctx.objs,
             addr,
@@ -309,6 +462,53 @@ impl SyscallHandler {
             return Err(());
         };
 
+        // attempt to open the file in the plugin with the same flags as what the shadow RegularFile
+        // object has
+
+        // from man 2 open
+        let creation_flags = OFlag::empty()
+            | OFlag::O_CLOEXEC
+            | OFlag::O_CREAT
+            | OFlag::O_DIRECTORY
+            | OFlag::O_EXCL
+            | OFlag::O_NOCTTY
+            | OFlag::O_NOFOLLOW
+            | OFlag::O_TMPFILE
+            | OFlag::O_TRUNC;
+
+        // the flags linux is using
+        let native_flags = OFlag::from_bits_retain(unsafe {
+            libc::fcntl(c::regularfile_getOSBackedFD(file), libc::F_GETFL)
+        });
+
+        // get original flags that were used to open the file
+        let mut flags = OFlag::from_bits_retain(unsafe { c::regularfile_getFlagsAtOpen(file) });
+        // use only the file creation flags, except O_CLOEXEC
+        flags &= creation_flags.difference(OFlag::O_CLOEXEC);
+        // add any file access mode and file status flags that shadow doesn't implement
+        flags |= native_flags.difference(OFlag::from_bits_retain(unsafe { c::SHADOW_FLAG_MASK }));
+        // add any flags that shadow implements
+        flags |= OFlag::from_bits_retain(unsafe { c::regularfile_getShadowFlags(file) });
+        // be careful not to try re-creating or truncating it
+        flags -= OFlag::O_CREAT | OFlag::O_EXCL | OFlag::O_TMPFILE | OFlag::O_TRUNC;
+        // shadow doesn't do direct I/O, and the plugin's filesystem may not support it
+        flags -= OFlag::O_DIRECT;
+        // don't use O_NOFOLLOW since it will prevent the plugin from opening the
+        // /proc/<shadow-pid>/fd/<linux-fd> file, which is a symbolic link
+        flags -= OFlag::O_NOFOLLOW;
+
+        let mode = unsafe { c::regularfile_getModeAtOpen(file) };
+
+        Self::open_plugin_path(ctx, &path, flags, mode)
+    }
+
+    /// Instruct the plugin to open the file at `path`. Returns the plugin's fd.
+    pub(super) fn open_plugin_path(
+        ctx: &ThreadContext,
+        path: &Path,
+        flags: OFlag,
+        mode: u32,
+    ) -> Result<i32, ()> {
         let path_bytes = path.as_os_str().as_bytes();
 
         // TODO: do we really want to continue if we need to truncate the path and we already know
@@ -346,41 +546,6 @@ impl SyscallHandler {
             }
         }
 
-        // attempt to open the file in the plugin with the same flags as what the shadow RegularFile
-        // object has
-
-        // from man 2 open
-        let creation_flags = OFlag::empty()
-            | OFlag::O_CLOEXEC
-            | OFlag::O_CREAT
-            | OFlag::O_DIRECTORY
-            | OFlag::O_EXCL
-            | OFlag::O_NOCTTY
-            | OFlag::O_NOFOLLOW
-            | OFlag::O_TMPFILE
-            | OFlag::O_TRUNC;
-
-        // the flags linux is using
-        let native_flags = OFlag::from_bits_retain(unsafe {
-            libc::fcntl(c::regularfile_getOSBackedFD(file), libc::F_GETFL)
-        });
-
-        // get original flags that were used to open the file
-        let mut flags = OFlag::from_bits_retain(unsafe { c::regularfile_getFlagsAtOpen(file) });
-        // use only the file creation flags, except O_CLOEXEC
-        flags &= creation_flags.difference(OFlag::O_CLOEXEC);
-        // add any file access mode and file status flags that shadow doesn't implement
-        flags |= native_flags.difference(OFlag::from_bits_retain(unsafe { c::SHADOW_FLAG_MASK }));
-        // add any flags that shadow implements
-        flags |= OFlag::from_bits_retain(unsafe { c::regularfile_getShadowFlags(file) });
-        // be careful not to try re-creating or truncating it
-        flags -= OFlag::O_CREAT | OFlag::O_EXCL | OFlag::O_TMPFILE | OFlag::O_TRUNC;
-        // don't use O_NOFOLLOW since it will prevent the plugin from opening the
-        // /proc/<shadow-pid>/fd/<linux-fd> file, which is a symbolic link
-        flags -= OFlag::O_NOFOLLOW;
-
-        let mode = unsafe { c::regularfile_getModeAtOpen(file) };
-
         // instruct the plugin to open the file at the path we sent
         let (process_ctx, thread) = ctx.split_thread();
         let open_result = thread.native_open(
@@ -412,7 +577,7 @@ impl SyscallHandler {
     }
 
     /// Instruct the plugin to close the file at the given fd.
-    fn close_plugin_file(ctx: &ThreadContext, plugin_fd: i32) {
+    pub(super) fn close_plugin_file(ctx: &ThreadContext, plugin_fd: i32) {
         let (ctx, thread) = ctx.split_thread();
         let result = thread.native_close(&ctx, plugin_fd);
 
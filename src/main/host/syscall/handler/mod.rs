@@ -6,6 +6,7 @@ use std::time::Duration;
 use linux_api::errno::Errno;
 use linux_api::syscall::SyscallNum;
 use shadow_shim_helper_rs::simulation_time::SimulationTime;
+use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 use shadow_shim_helper_rs::syscall_types::SyscallArgs;
 use shadow_shim_helper_rs::syscall_types::SyscallReg;
 use shadow_shim_helper_rs::util::SendPointer;
@@ -22,11 +23,13 @@ use crate::host::syscall::is_shadow_syscall;
 use crate::host::syscall::types::SyscallReturn;
 use crate::host::syscall::types::{SyscallError, SyscallResult};
 use crate::host::thread::ThreadId;
+use crate::utility::callback_queue::CallbackQueue;
 use crate::utility::counter::Counter;
 
 #[cfg(feature = "perf_timers")]
 use crate::utility::perf_timer::PerfTimer;
 
+mod aio;
 mod clone;
 mod close_range;
 mod epoll;
@@ -35,16 +38,25 @@ mod fcntl;
 mod file;
 mod fileat;
 mod futex;
+mod inotify;
+mod io_uring;
 mod ioctl;
+mod membarrier;
 mod mman;
+mod mqueue;
+mod personality;
+mod pidfd;
 mod poll;
 mod prctl;
 mod random;
 mod resource;
 mod sched;
 mod select;
+mod sem;
 mod shadow;
+mod shm;
 mod signal;
+mod signalfd;
 mod socket;
 mod stat;
 mod sysinfo;
@@ -52,6 +64,7 @@ mod time;
 mod timerfd;
 mod uio;
 mod unistd;
+mod userfaultfd;
 mod wait;
 
 type LegacySyscallFn =
@@ -169,8 +182,33 @@ impl SyscallHandler {
         #[cfg(feature = "perf_timers")]
         let timer = PerfTimer::new();
 
+        // only measure the time if it will be used, to avoid the overhead otherwise
+        let summary_start = ctx
+            .process
+            .borrow_as_runnable()
+            .is_some_and(|runnable| runnable.has_syscall_summary())
+            .then(std::time::Instant::now);
+
         let mut rv = self.run_handler(ctx, args);
 
+        if let Some(summary_start) = summary_start {
+            let elapsed = summary_start.elapsed();
+            // the process may no longer be runnable if the syscall exited it
+            if let Some(runnable) = ctx.process.borrow_as_runnable() {
+                runnable.with_syscall_summary(|summary| {
+                    summary.add_time(syscall_name, elapsed);
+                    if !matches!(rv, Err(SyscallError::Blocked(_))) {
+                        let failed = matches!(rv, Err(SyscallError::Failed(_)));
+                        summary.add_call(syscall_name, failed);
+                    }
+                });
+            }
+        }
+
+        // The syscall may have modified files on the native filesystem, so make any resulting
+        // inotify events visible now.
+        ctx.host.refresh_inotify_instances();
+
         #[cfg(feature = "perf_timers")]
         {
             // add the cumulative elapsed seconds
@@ -335,6 +373,10 @@ impl SyscallHandler {
             SyscallNum::new(c::ShadowSyscallNum_SYS_shadow_init_memory_manager);
         const NR_shadow_hostname_to_addr_ipv4: SyscallNum =
             SyscallNum::new(c::ShadowSyscallNum_SYS_shadow_hostname_to_addr_ipv4);
+        const NR_shadow_get_sim_time: SyscallNum =
+            SyscallNum::new(c::ShadowSyscallNum_SYS_shadow_get_sim_time);
+        const NR_shadow_checkpoint: SyscallNum =
+            SyscallNum::new(c::ShadowSyscallNum_SYS_shadow_checkpoint);
 
         let mut ctx = SyscallContext {
             objs: ctx,
@@ -358,7 +400,7 @@ impl SyscallHandler {
                             &rv,
                             strace_fmt_options,
                             ctx.objs.thread.id(),
-                            &*ctx.objs.process.memory_borrow(),
+                            &*ctx.objs.memory(),
                         )
                         .unwrap();
                     });
@@ -373,12 +415,14 @@ impl SyscallHandler {
             //
             SyscallNum::NR_accept => handle!(accept),
             SyscallNum::NR_accept4 => handle!(accept4),
+            SyscallNum::NR_adjtimex => handle!(adjtimex),
             SyscallNum::NR_alarm => handle!(alarm),
             SyscallNum::NR_bind => handle!(bind),
             SyscallNum::NR_brk => handle!(brk),
             SyscallNum::NR_capget => handle!(capget),
             SyscallNum::NR_capset => handle!(capset),
             SyscallNum::NR_chdir => handle!(chdir),
+            SyscallNum::NR_clock_adjtime => handle!(clock_adjtime),
             SyscallNum::NR_clock_getres => handle!(clock_getres),
             SyscallNum::NR_clock_nanosleep => handle!(clock_nanosleep),
             SyscallNum::NR_clone => handle!(clone),
@@ -424,6 +468,7 @@ impl SyscallHandler {
             SyscallNum::NR_futex => handle!(futex),
             SyscallNum::NR_futimesat => handle!(futimesat),
             SyscallNum::NR_get_robust_list => handle!(get_robust_list),
+            SyscallNum::NR_getcpu => handle!(getcpu),
             SyscallNum::NR_getdents => handle!(getdents),
             SyscallNum::NR_getdents64 => handle!(getdents64),
             SyscallNum::NR_getitimer => handle!(getitimer),
@@ -433,25 +478,50 @@ impl SyscallHandler {
             SyscallNum::NR_getpid => handle!(getpid),
             SyscallNum::NR_getppid => handle!(getppid),
             SyscallNum::NR_getrandom => handle!(getrandom),
+            SyscallNum::NR_getrlimit => handle!(getrlimit),
             SyscallNum::NR_getsid => handle!(getsid),
             SyscallNum::NR_getsockname => handle!(getsockname),
             SyscallNum::NR_getsockopt => handle!(getsockopt),
             SyscallNum::NR_gettid => handle!(gettid),
+            SyscallNum::NR_inotify_add_watch => handle!(inotify_add_watch),
+            SyscallNum::NR_inotify_init => handle!(inotify_init),
+            SyscallNum::NR_inotify_init1 => handle!(inotify_init1),
+            SyscallNum::NR_inotify_rm_watch => handle!(inotify_rm_watch),
+            SyscallNum::NR_io_destroy => handle!(io_destroy),
+            SyscallNum::NR_io_getevents => handle!(io_getevents),
+            SyscallNum::NR_io_setup => handle!(io_setup),
+            SyscallNum::NR_io_submit => handle!(io_submit),
+            SyscallNum::NR_io_uring_enter => handle!(io_uring_enter),
+            SyscallNum::NR_io_uring_register => handle!(io_uring_register),
+            SyscallNum::NR_io_uring_setup => handle!(io_uring_setup),
             SyscallNum::NR_ioctl => handle!(ioctl),
             SyscallNum::NR_kill => handle!(kill),
             SyscallNum::NR_linkat => handle!(linkat),
             SyscallNum::NR_listen => handle!(listen),
             SyscallNum::NR_lseek => handle!(lseek),
+            SyscallNum::NR_membarrier => handle!(membarrier),
+            SyscallNum::NR_mincore => handle!(mincore),
             SyscallNum::NR_mkdirat => handle!(mkdirat),
             SyscallNum::NR_mknodat => handle!(mknodat),
             SyscallNum::NR_mmap => handle!(mmap),
             SyscallNum::NR_mprotect => handle!(mprotect),
+            SyscallNum::NR_mq_getsetattr => handle!(mq_getsetattr),
+            SyscallNum::NR_mq_notify => handle!(mq_notify),
+            SyscallNum::NR_mq_open => handle!(mq_open),
+            SyscallNum::NR_mq_timedreceive => handle!(mq_timedreceive),
+            SyscallNum::NR_mq_timedsend => handle!(mq_timedsend),
+            SyscallNum::NR_mq_unlink => handle!(mq_unlink),
             SyscallNum::NR_mremap => handle!(mremap),
             SyscallNum::NR_munmap => handle!(munmap),
+            SyscallNum::NR_name_to_handle_at => handle!(name_to_handle_at),
             SyscallNum::NR_nanosleep => handle!(nanosleep),
             SyscallNum::NR_newfstatat => handle!(newfstatat),
             SyscallNum::NR_open => handle!(open),
+            SyscallNum::NR_open_by_handle_at => handle!(open_by_handle_at),
             SyscallNum::NR_openat => handle!(openat),
+            SyscallNum::NR_personality => handle!(personality),
+            SyscallNum::NR_pidfd_open => handle!(pidfd_open),
+            SyscallNum::NR_pidfd_send_signal => handle!(pidfd_send_signal),
             SyscallNum::NR_pipe => handle!(pipe),
             SyscallNum::NR_pipe2 => handle!(pipe2),
             SyscallNum::NR_poll => handle!(poll),
@@ -461,6 +531,8 @@ impl SyscallHandler {
             SyscallNum::NR_preadv => handle!(preadv),
             SyscallNum::NR_preadv2 => handle!(preadv2),
             SyscallNum::NR_prlimit64 => handle!(prlimit64),
+            SyscallNum::NR_process_vm_readv => handle!(process_vm_readv),
+            SyscallNum::NR_process_vm_writev => handle!(process_vm_writev),
             SyscallNum::NR_pselect6 => handle!(pselect6),
             SyscallNum::NR_pwrite64 => handle!(pwrite64),
             SyscallNum::NR_pwritev => handle!(pwritev),
@@ -471,24 +543,39 @@ impl SyscallHandler {
             SyscallNum::NR_readv => handle!(readv),
             SyscallNum::NR_recvfrom => handle!(recvfrom),
             SyscallNum::NR_recvmsg => handle!(recvmsg),
+            SyscallNum::NR_remap_file_pages => handle!(remap_file_pages),
             SyscallNum::NR_renameat => handle!(renameat),
             SyscallNum::NR_renameat2 => handle!(renameat2),
             SyscallNum::NR_rseq => handle!(rseq),
             SyscallNum::NR_rt_sigaction => handle!(rt_sigaction),
+            SyscallNum::NR_rt_sigpending => handle!(rt_sigpending),
             SyscallNum::NR_rt_sigprocmask => handle!(rt_sigprocmask),
+            SyscallNum::NR_rt_sigqueueinfo => handle!(rt_sigqueueinfo),
+            SyscallNum::NR_rt_sigtimedwait => handle!(rt_sigtimedwait),
             SyscallNum::NR_sched_getaffinity => handle!(sched_getaffinity),
             SyscallNum::NR_sched_setaffinity => handle!(sched_setaffinity),
             SyscallNum::NR_select => handle!(select),
+            SyscallNum::NR_semctl => handle!(semctl),
+            SyscallNum::NR_semget => handle!(semget),
+            SyscallNum::NR_semop => handle!(semop),
+            SyscallNum::NR_semtimedop => handle!(semtimedop),
             SyscallNum::NR_sendmsg => handle!(sendmsg),
             SyscallNum::NR_sendto => handle!(sendto),
             SyscallNum::NR_set_robust_list => handle!(set_robust_list),
             SyscallNum::NR_set_tid_address => handle!(set_tid_address),
             SyscallNum::NR_setitimer => handle!(setitimer),
             SyscallNum::NR_setpgid => handle!(setpgid),
+            SyscallNum::NR_setrlimit => handle!(setrlimit),
             SyscallNum::NR_setsid => handle!(setsid),
             SyscallNum::NR_setsockopt => handle!(setsockopt),
+            SyscallNum::NR_shmat => handle!(shmat),
+            SyscallNum::NR_shmctl => handle!(shmctl),
+            SyscallNum::NR_shmdt => handle!(shmdt),
+            SyscallNum::NR_shmget => handle!(shmget),
             SyscallNum::NR_shutdown => handle!(shutdown),
             SyscallNum::NR_sigaltstack => handle!(sigaltstack),
+            SyscallNum::NR_signalfd => handle!(signalfd),
+            SyscallNum::NR_signalfd4 => handle!(signalfd4),
             SyscallNum::NR_socket => handle!(socket),
             SyscallNum::NR_socketpair => handle!(socketpair),
             SyscallNum::NR_statx => handle!(statx),
@@ -497,12 +584,18 @@ impl SyscallHandler {
             SyscallNum::NR_syncfs => handle!(syncfs),
             SyscallNum::NR_sysinfo => handle!(sysinfo),
             SyscallNum::NR_tgkill => handle!(tgkill),
+            SyscallNum::NR_timer_create => handle!(timer_create),
+            SyscallNum::NR_timer_delete => handle!(timer_delete),
+            SyscallNum::NR_timer_getoverrun => handle!(timer_getoverrun),
+            SyscallNum::NR_timer_gettime => handle!(timer_gettime),
+            SyscallNum::NR_timer_settime => handle!(timer_settime),
             SyscallNum::NR_timerfd_create => handle!(timerfd_create),
             SyscallNum::NR_timerfd_gettime => handle!(timerfd_gettime),
             SyscallNum::NR_timerfd_settime => handle!(timerfd_settime),
             SyscallNum::NR_tkill => handle!(tkill),
             SyscallNum::NR_uname => handle!(uname),
             SyscallNum::NR_unlinkat => handle!(unlinkat),
+            SyscallNum::NR_userfaultfd => handle!(userfaultfd),
             SyscallNum::NR_utimensat => handle!(utimensat),
             SyscallNum::NR_vfork => handle!(vfork),
             SyscallNum::NR_waitid => handle!(waitid),
@@ -512,6 +605,8 @@ impl SyscallHandler {
             //
             // CUSTOM SHADOW-SPECIFIC SYSCALLS
             //
+            NR_shadow_checkpoint => handle!(shadow_checkpoint),
+            NR_shadow_get_sim_time => handle!(shadow_get_sim_time),
             NR_shadow_hostname_to_addr_ipv4 => handle!(shadow_hostname_to_addr_ipv4),
             NR_shadow_init_memory_manager => handle!(shadow_init_memory_manager),
             NR_shadow_yield => handle!(shadow_yield),
@@ -542,7 +637,6 @@ impl SyscallHandler {
             | SyscallNum::NR_getgroups
             | SyscallNum::NR_getresgid
             | SyscallNum::NR_getresuid
-            | SyscallNum::NR_getrlimit
             | SyscallNum::NR_getuid
             | SyscallNum::NR_getxattr
             | SyscallNum::NR_lchown
@@ -568,7 +662,6 @@ impl SyscallHandler {
             | SyscallNum::NR_setresgid
             | SyscallNum::NR_setresuid
             | SyscallNum::NR_setreuid
-            | SyscallNum::NR_setrlimit
             | SyscallNum::NR_setuid
             | SyscallNum::NR_setxattr
             | SyscallNum::NR_stat
@@ -672,6 +765,50 @@ impl SyscallHandler {
         }
     }
 
+    /// Internal helper that registers a pair of descriptors, such as the two ends of a pipe, and
+    /// writes their fds to `fd_ptr`. Returns EMFILE if the descriptors can't both be registered.
+    /// If an error is returned, neither descriptor remains registered.
+    fn register_descriptor_pair(
+        ctx: &mut SyscallContext,
+        desc_1: Descriptor,
+        desc_2: Descriptor,
+        fd_ptr: ForeignPtr<[std::ffi::c_int; 2]>,
+    ) -> Result<(), SyscallError> {
+        let mut dt = ctx.objs.thread.descriptor_table_borrow_mut(ctx.objs.host);
+
+        let fds = match dt.register_descriptor_pair(desc_1, desc_2) {
+            Ok(fds) => fds,
+            Err(descs) => {
+                // we've reached the fd limit, so close the unregistered descriptors
+                CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+                    for desc in descs {
+                        // ignore any errors when closing
+                        desc.close(ctx.objs.host, cb_queue);
+                    }
+                });
+                return Err(Errno::EMFILE.into());
+            }
+        };
+
+        // try to write them to the caller
+        let write_res = ctx.objs.memory_mut().write(fd_ptr, &fds.map(i32::from));
+
+        // clean up in case of error
+        if let Err(e) = write_res {
+            CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+                for fd in fds {
+                    // ignore any errors when closing
+                    dt.deregister_descriptor(fd)
+                        .unwrap()
+                        .close(ctx.objs.host, cb_queue);
+                }
+            });
+            return Err(e.into());
+        }
+
+        Ok(())
+    }
+
     /// Run a legacy C syscall handler.
     fn legacy_syscall<T: From<SyscallReg>>(
         syscall: LegacySyscallFn,
@@ -0,0 +1,478 @@
+use std::ffi::CString;
+use std::sync::Arc;
+
+use atomic_refcell::AtomicRefCell;
+use linux_api::errno::Errno;
+use linux_api::fcntl::DescriptorFlags;
+use linux_api::mqueue::{
+    mq_attr, DFLT_MSG, DFLT_MSGSIZE, HARD_MSGMAX, HARD_MSGSIZEMAX, MQ_PRIO_MAX,
+};
+use linux_api::signal::{siginfo_t, sigval, Signal};
+use shadow_shim_helper_rs::emulated_time::EmulatedTime;
+use shadow_shim_helper_rs::simulation_time::SimulationTime;
+use shadow_shim_helper_rs::syscall_types::ForeignPtr;
+
+use crate::core::worker::Worker;
+use crate::host::descriptor::descriptor_table::DescriptorHandle;
+use crate::host::descriptor::mqueue::{MessageQueue, MqNotify, QueueBuf};
+use crate::host::descriptor::{
+    CompatFile, Descriptor, File, FileMode, FileState, FileStatus, OpenFile,
+};
+use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
+use crate::host::syscall::type_formatting::SyscallStringArg;
+use crate::host::syscall::types::{ForeignArrayPtr, SyscallError};
+use crate::utility::callback_queue::CallbackQueue;
+
+impl SyscallHandler {
+    // <https://github.com/torvalds/linux/tree/v6.3/ipc/mqueue.c#L944>
+    // ```
+    // SYSCALL_DEFINE4(mq_open, const char __user *, u_name, int, oflag, umode_t, mode,
+    //                 struct mq_attr __user *, u_attr)
+    // ```
+    log_syscall!(
+        mq_open,
+        /* rv */ std::ffi::c_int,
+        /* name */ SyscallStringArg,
+        /* oflag */ linux_api::fcntl::OFlag,
+        /* mode */ nix::sys::stat::Mode,
+        /* attr */ *const std::ffi::c_void,
+    );
+    pub fn mq_open(
+        ctx: &mut SyscallContext,
+        name_ptr: ForeignPtr<std::ffi::c_char>,
+        oflag: std::ffi::c_int,
+        _mode: linux_api::posix_types::kernel_mode_t,
+        attr_ptr: ForeignPtr<mq_attr>,
+    ) -> Result<DescriptorHandle, SyscallError> {
+        let name = read_queue_name(ctx, name_ptr)?;
+
+        let mode = match oflag & libc::O_ACCMODE {
+            libc::O_RDONLY => FileMode::READ,
+            libc::O_WRONLY => FileMode::WRITE,
+            libc::O_RDWR => FileMode::READ | FileMode::WRITE,
+            _ => return Err(Errno::EINVAL.into()),
+        };
+
+        // whether this call created the queue
+        let (queue, created) = {
+            let mut queues = ctx.objs.host.message_queues_borrow_mut();
+
+            match queues.get(&name) {
+                Some(queue) => {
+                    if oflag & libc::O_CREAT != 0 && oflag & libc::O_EXCL != 0 {
+                        return Err(Errno::EEXIST.into());
+                    }
+                    (Arc::clone(queue), false)
+                }
+                None => {
+                    if oflag & libc::O_CREAT == 0 {
+                        return Err(Errno::ENOENT.into());
+                    }
+
+                    let (max_msgs, msg_size) = if attr_ptr.is_null() {
+                        (DFLT_MSG, DFLT_MSGSIZE)
+                    } else {
+                        let attr = ctx.objs.memory().read(attr_ptr)?;
+                        // We don't check the caller's capabilities, so allow up to the hard limits
+                        // that apply to privileged processes.
+                        if !(1..=HARD_MSGMAX).contains(&attr.mq_maxmsg)
+                            || !(1..=HARD_MSGSIZEMAX).contains(&attr.mq_msgsize)
+                        {
+                            return Err(Errno::EINVAL.into());
+                        }
+                        (attr.mq_maxmsg, attr.mq_msgsize)
+                    };
+
+                    let queue =
+                        QueueBuf::new(max_msgs.try_into().unwrap(), msg_size.try_into().unwrap());
+                    let queue = Arc::new(AtomicRefCell::new(queue));
+                    queues.insert(name.clone(), Arc::clone(&queue));
+                    (queue, true)
+                }
+            }
+        };
+
+        let mut file_status = FileStatus::empty();
+        if oflag & libc::O_NONBLOCK != 0 {
+            file_status.insert(FileStatus::NONBLOCK);
+        }
+
+        let file = Arc::new(AtomicRefCell::new(MessageQueue::new(mode, file_status)));
+
+        // set the file object to listen for events on the queue
+        CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+            MessageQueue::connect_to_queue(&file, queue, cb_queue);
+        });
+
+        let mut desc = Descriptor::new(CompatFile::New(OpenFile::new(File::MessageQueue(file))));
+        if oflag & libc::O_CLOEXEC != 0 {
+            desc.set_flags(DescriptorFlags::FD_CLOEXEC);
+        }
+
+        let res = ctx
+            .objs
+            .thread
+            .descriptor_table_borrow_mut(ctx.objs.host)
+            .register_descriptor(desc);
+
+        match res {
+            Ok(fd) => Ok(fd),
+            Err(desc) => {
+                // we've reached the fd limit, so close the descriptor and, like Linux, don't leave
+                // behind a queue that we created
+                CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+                    desc.close(ctx.objs.host, cb_queue);
+                });
+                if created {
+                    ctx.objs.host.message_queues_borrow_mut().remove(&name);
+                }
+                Err(Errno::EMFILE.into())
+            }
+        }
+    }
+
+    // <https://github.com/torvalds/linux/tree/v6.3/ipc/mqueue.c#L955>
+    // ```
+    // SYSCALL_DEFINE1(mq_unlink, const char __user *, u_name)
+    // ```
+    log_syscall!(
+        mq_unlink,
+        /* rv */ std::ffi::c_int,
+        /* name */ SyscallStringArg,
+    );
+    pub fn mq_unlink(
+        ctx: &mut SyscallContext,
+        name_ptr: ForeignPtr<std::ffi::c_char>,
+    ) -> Result<(), SyscallError> {
+        let name = read_queue_name(ctx, name_ptr)?;
+
+        // the queue is destroyed once all of its descriptors are closed
+        if ctx
+            .objs
+            .host
+            .message_queues_borrow_mut()
+            .remove(&name)
+            .is_none()
+        {
+            return Err(Errno::ENOENT.into());
+        }
+
+        Ok(())
+    }
+
+    // <https://github.com/torvalds/linux/tree/v6.3/ipc/mqueue.c#L1237>
+    // ```
+    // SYSCALL_DEFINE5(mq_timedsend, mqd_t, mqdes, const char __user *, u_msg_ptr,
+    //                 size_t, msg_len, unsigned int, msg_prio,
+    //                 const struct __kernel_timespec __user *, u_abs_timeout)
+    // ```
+    log_syscall!(
+        mq_timedsend,
+        /* rv */ std::ffi::c_int,
+        /* mqdes */ std::ffi::c_int,
+        /* msg_ptr */ *const std::ffi::c_void,
+        /* msg_len */ libc::size_t,
+        /* msg_prio */ std::ffi::c_uint,
+        /* abs_timeout */ *const linux_api::time::timespec,
+    );
+    pub fn mq_timedsend(
+        ctx: &mut SyscallContext,
+        mqdes: std::ffi::c_int,
+        msg_ptr: ForeignPtr<u8>,
+        msg_len: libc::size_t,
+        msg_prio: std::ffi::c_uint,
+        abs_timeout_ptr: ForeignPtr<linux_api::time::timespec>,
+    ) -> Result<(), SyscallError> {
+        let mq = get_message_queue(ctx, mqdes)?;
+
+        if msg_prio >= MQ_PRIO_MAX {
+            return Err(Errno::EINVAL.into());
+        }
+
+        let timeout = read_abs_timeout(ctx, abs_timeout_ptr)?;
+
+        // check the size before copying a message that could never be sent
+        if msg_len > mq.borrow().queue().borrow().msg_size() {
+            return Err(Errno::EMSGSIZE.into());
+        }
+
+        let mut msg = vec![0u8; msg_len];
+        ctx.objs
+            .memory()
+            .copy_from_ptr(&mut msg, ForeignArrayPtr::new(msg_ptr, msg_len))?;
+
+        let result = CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+            mq.borrow_mut().send(msg, msg_prio, cb_queue)
+        });
+
+        match result {
+            Ok(notify) => {
+                if let Some(notify) = notify {
+                    send_notification(ctx, notify);
+                }
+                Ok(())
+            }
+            Err(Errno::EAGAIN) => Err(block_on_queue(mq, FileState::WRITABLE, timeout)),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // <https://github.com/torvalds/linux/tree/v6.3/ipc/mqueue.c#L1251>
+    // ```
+    // SYSCALL_DEFINE5(mq_timedreceive, mqd_t, mqdes, char __user *, u_msg_ptr,
+    //                 size_t, msg_len, unsigned int __user *, u_msg_prio,
+    //                 const struct __kernel_timespec __user *, u_abs_timeout)
+    // ```
+    log_syscall!(
+        mq_timedreceive,
+        /* rv */ libc::ssize_t,
+        /* mqdes */ std::ffi::c_int,
+        /* msg_ptr */ *const std::ffi::c_void,
+        /* msg_len */ libc::size_t,
+        /* msg_prio */ *const std::ffi::c_uint,
+        /* abs_timeout */ *const linux_api::time::timespec,
+    );
+    pub fn mq_timedreceive(
+        ctx: &mut SyscallContext,
+        mqdes: std::ffi::c_int,
+        msg_ptr: ForeignPtr<u8>,
+        msg_len: libc::size_t,
+        msg_prio_ptr: ForeignPtr<std::ffi::c_uint>,
+        abs_timeout_ptr: ForeignPtr<linux_api::time::timespec>,
+    ) -> Result<libc::ssize_t, SyscallError> {
+        let mq = get_message_queue(ctx, mqdes)?;
+
+        let timeout = read_abs_timeout(ctx, abs_timeout_ptr)?;
+
+        let result = CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+            mq.borrow_mut().receive(msg_len, cb_queue)
+        });
+
+        let (msg, priority) = match result {
+            Ok(x) => x,
+            Err(Errno::EAGAIN) => {
+                return Err(block_on_queue(mq, FileState::READABLE, timeout));
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        // like linux, the message has been removed from the queue even if we fail to copy it out
+        let mut mem = ctx.objs.memory_mut();
+        mem.copy_to_ptr(ForeignArrayPtr::new(msg_ptr, msg.len()), &msg)?;
+        if !msg_prio_ptr.is_null() {
+            mem.write(msg_prio_ptr, &priority)?;
+        }
+
+        Ok(msg.len().try_into().unwrap())
+    }
+
+    // <https://github.com/torvalds/linux/tree/v6.3/ipc/mqueue.c#L1416>
+    // ```
+    // SYSCALL_DEFINE2(mq_notify, mqd_t, mqdes, const struct sigevent __user *, u_notification)
+    // ```
+    log_syscall!(
+        mq_notify,
+        /* rv */ std::ffi::c_int,
+        /* mqdes */ std::ffi::c_int,
+        /* notification */ *const std::ffi::c_void,
+    );
+    pub fn mq_notify(
+        ctx: &mut SyscallContext,
+        mqdes: std::ffi::c_int,
+        sevp: ForeignPtr<libc::sigevent>,
+    ) -> Result<(), SyscallError> {
+        let pid = ctx.objs.process.id();
+
+        let notify = if sevp.is_null() {
+            None
+        } else {
+            let sev = ctx.objs.memory().read(sevp)?;
+            let value = sigval {
+                sival_ptr: sev.sigev_value.sival_ptr,
+            };
+
+            let signal = match sev.sigev_notify {
+                libc::SIGEV_NONE => None,
+                libc::SIGEV_SIGNAL => {
+                    Some(Signal::try_from(sev.sigev_signo).map_err(|_| Errno::EINVAL)?)
+                }
+                // glibc implements SIGEV_THREAD using a netlink socket
+                libc::SIGEV_THREAD => {
+                    warn_once_then_debug!("mq_notify with SIGEV_THREAD is not supported");
+                    return Err(Errno::ENOSYS.into());
+                }
+                notify => {
+                    log::debug!("Invalid sigev_notify {notify}");
+                    return Err(Errno::EINVAL.into());
+                }
+            };
+
+            Some(MqNotify { pid, signal, value })
+        };
+
+        let mq = get_message_queue(ctx, mqdes)?;
+        let mq = mq.borrow();
+        mq.queue().borrow_mut().set_notify(pid, notify)?;
+
+        Ok(())
+    }
+
+    // <https://github.com/torvalds/linux/tree/v6.3/ipc/mqueue.c#L1477>
+    // ```
+    // SYSCALL_DEFINE3(mq_getsetattr, mqd_t, mqdes, const struct mq_attr __user *, u_mqstat,
+    //                 struct mq_attr __user *, u_omqstat)
+    // ```
+    log_syscall!(
+        mq_getsetattr,
+        /* rv */ std::ffi::c_int,
+        /* mqdes */ std::ffi::c_int,
+        /* mqstat */ *const std::ffi::c_void,
+        /* omqstat */ *const std::ffi::c_void,
+    );
+    pub fn mq_getsetattr(
+        ctx: &mut SyscallContext,
+        mqdes: std::ffi::c_int,
+        new_attr_ptr: ForeignPtr<mq_attr>,
+        old_attr_ptr: ForeignPtr<mq_attr>,
+    ) -> Result<(), SyscallError> {
+        let mq = get_message_queue(ctx, mqdes)?;
+
+        let new_attr = if new_attr_ptr.is_null() {
+            None
+        } else {
+            let attr = ctx.objs.memory().read(new_attr_ptr)?;
+            // mq_getattr(3): "The only attribute that can be modified is the setting of the
+            // O_NONBLOCK flag in mq_flags."
+            if attr.mq_flags & !libc::c_long::from(libc::O_NONBLOCK) != 0 {
+                return Err(Errno::EINVAL.into());
+            }
+            Some(attr)
+        };
+
+        let mut mq = mq.borrow_mut();
+
+        if !old_attr_ptr.is_null() {
+            let queue = mq.queue().borrow();
+            let nonblock = mq.status().contains(FileStatus::NONBLOCK);
+            let old_attr = mq_attr {
+                mq_flags: if nonblock { libc::O_NONBLOCK.into() } else { 0 },
+                mq_maxmsg: queue.max_msgs().try_into().unwrap(),
+                mq_msgsize: queue.msg_size().try_into().unwrap(),
+                mq_curmsgs: queue.num_msgs().try_into().unwrap(),
+                __reserved: [0; 4],
+            };
+            ctx.objs.memory_mut().write(old_attr_ptr, &old_attr)?;
+        }
+
+        if let Some(new_attr) = new_attr {
+            let mut status = mq.status();
+            status.set(FileStatus::NONBLOCK, new_attr.mq_flags != 0);
+            mq.set_status(status);
+        }
+
+        Ok(())
+    }
+}
+
+/// Read and validate a queue name. glibc removes the leading slash before making the syscall.
+fn read_queue_name(
+    ctx: &mut SyscallContext,
+    name_ptr: ForeignPtr<std::ffi::c_char>,
+) -> Result<CString, Errno> {
+    let mut name_buf = [0u8; linux_api::limits::NAME_MAX + 1];
+    let name_buf_capacity = name_buf.len();
+    let name = ctx.objs.memory().copy_str_from_ptr(
+        &mut name_buf,
+        ForeignArrayPtr::new(name_ptr.cast::<u8>(), name_buf_capacity),
+    )?;
+
+    if name.is_empty() {
+        return Err(Errno::ENOENT);
+    }
+
+    // mq_open(3): "EACCES The name contained more than one slash."
+    if name.to_bytes().contains(&b'/') {
+        return Err(Errno::EACCES);
+    }
+
+    Ok(name.to_owned())
+}
+
+/// Read the absolute `CLOCK_REALTIME` timeout. If the syscall was previously blocked, returns the
+/// timeout that was used when it blocked.
+fn read_abs_timeout(
+    ctx: &mut SyscallContext,
+    abs_timeout_ptr: ForeignPtr<linux_api::time::timespec>,
+) -> Result<Option<EmulatedTime>, Errno> {
+    // the condition will only exist after a wakeup
+    if let Some(cond) = ctx.objs.thread.syscall_condition() {
+        return Ok(cond.timeout());
+    }
+
+    if abs_timeout_ptr.is_null() {
+        return Ok(None);
+    }
+
+    let ts = ctx.objs.memory().read(abs_timeout_ptr)?;
+    let since_epoch = SimulationTime::try_from(ts).or(Err(Errno::EINVAL))?;
+    Ok(Some(EmulatedTime::UNIX_EPOCH + since_epoch))
+}
+
+/// Get the message queue for `mqdes`, or `EBADF` if `mqdes` isn't a message queue descriptor.
+fn get_message_queue(
+    ctx: &mut SyscallContext,
+    mqdes: std::ffi::c_int,
+) -> Result<Arc<AtomicRefCell<MessageQueue>>, Errno> {
+    let desc_table = ctx.objs.thread.descriptor_table_borrow(ctx.objs.host);
+    let desc = SyscallHandler::get_descriptor(&desc_table, mqdes)?;
+
+    let CompatFile::New(file) = desc.file() else {
+        return Err(Errno::EBADF);
+    };
+    let File::MessageQueue(mq) = file.inner_file() else {
+        return Err(Errno::EBADF);
+    };
+
+    Ok(Arc::clone(mq))
+}
+
+/// The error to return when the queue is full or empty: `EAGAIN` for a non-blocking descriptor,
+/// `ETIMEDOUT` if the timeout has passed, and otherwise block until the queue is in `state`.
+fn block_on_queue(
+    mq: Arc<AtomicRefCell<MessageQueue>>,
+    state: FileState,
+    timeout: Option<EmulatedTime>,
+) -> SyscallError {
+    if mq.borrow().status().contains(FileStatus::NONBLOCK) {
+        return Errno::EAGAIN.into();
+    }
+
+    if timeout.is_some_and(|timeout| timeout <= Worker::current_time().unwrap()) {
+        return Errno::ETIMEDOUT.into();
+    }
+
+    let restartable = mq.borrow().supports_sa_restart();
+    let mut rv = SyscallError::new_blocked_on_file(File::MessageQueue(mq), state, restartable);
+    if timeout.is_some() {
+        rv.blocked_condition().unwrap().set_timeout(timeout);
+    }
+
+    rv
+}
+
+/// Notify the process that registered with mq_notify(3) that a message arrived.
+fn send_notification(ctx: &mut SyscallContext, notify: MqNotify) {
+    let Some(signal) = notify.signal else {
+        return;
+    };
+
+    let Some(process) = ctx.objs.host.process_borrow(notify.pid) else {
+        log::debug!("Process {:?} no longer exists", notify.pid);
+        return;
+    };
+    let process = process.borrow(ctx.objs.host.root());
+
+    let sender_pid = ctx.objs.process.id().into();
+    let siginfo = siginfo_t::new_for_mq(signal, sender_pid, 0, notify.value);
+    process.signal(ctx.objs.host, Some(ctx.objs.thread), &siginfo);
+}
@@ -0,0 +1,37 @@
+use linux_api::personality::{PERSONALITY_QUERY, PER_LINUX, PER_MASK};
+
+use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
+use crate::host::syscall::types::SyscallError;
+
+impl SyscallHandler {
+    // <https://github.com/torvalds/linux/tree/v6.3/kernel/exec_domain.c#L47>
+    // ```
+    // SYSCALL_DEFINE1(personality, unsigned int, personality)
+    // ```
+    log_syscall!(
+        personality,
+        /* rv */ std::ffi::c_int,
+        /* personality */ std::ffi::c_uint,
+    );
+    pub fn personality(
+        ctx: &mut SyscallContext,
+        personality: std::ffi::c_uint,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        let old = ctx.objs.process.personality();
+
+        if personality != PERSONALITY_QUERY {
+            // We only store the personality. Flags such as `ADDR_NO_RANDOMIZE` are already in
+            // effect since Shadow's address space layout is deterministic, but other execution
+            // domains won't have any effect.
+            if personality & PER_MASK != PER_LINUX {
+                warn_once_then_debug!(
+                    "Unsupported execution domain in personality {personality:#x}"
+                );
+            }
+            ctx.objs.process.set_personality(personality);
+        }
+
+        // personality(2): "On success, the previous persona is returned."
+        Ok(old as std::ffi::c_int)
+    }
+}
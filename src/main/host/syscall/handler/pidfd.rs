@@ -0,0 +1,142 @@
+use std::sync::Arc;
+
+use atomic_refcell::AtomicRefCell;
+use linux_api::errno::Errno;
+use linux_api::fcntl::{DescriptorFlags, OFlag};
+use shadow_shim_helper_rs::syscall_types::ForeignPtr;
+
+use crate::host::descriptor::descriptor_table::DescriptorHandle;
+use crate::host::descriptor::pidfd::PidFd;
+use crate::host::descriptor::{CompatFile, Descriptor, File, FileStatus, OpenFile};
+use crate::host::process::ProcessId;
+use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
+use crate::host::syscall::type_formatting::SyscallSignalArg;
+use crate::host::syscall::types::SyscallError;
+
+impl SyscallHandler {
+    log_syscall!(
+        pidfd_open,
+        /* rv */ std::ffi::c_int,
+        /* pid */ linux_api::posix_types::kernel_pid_t,
+        /* flags */ std::ffi::c_uint,
+    );
+    pub fn pidfd_open(
+        ctx: &mut SyscallContext,
+        pid: linux_api::posix_types::kernel_pid_t,
+        flags: std::ffi::c_uint,
+    ) -> Result<DescriptorHandle, SyscallError> {
+        // pidfd_open(2): "PIDFD_NONBLOCK: Return a nonblocking file descriptor." It has the same
+        // value as O_NONBLOCK.
+        let nonblock_flag = OFlag::O_NONBLOCK.bits() as std::ffi::c_uint;
+        if flags & !nonblock_flag != 0 {
+            log::debug!("Invalid pidfd_open flags: {flags}");
+            return Err(Errno::EINVAL.into());
+        }
+
+        if pid <= 0 {
+            return Err(Errno::EINVAL.into());
+        }
+
+        // Process ids are per-host, so this can only name a process on the caller's host.
+        let pid = ProcessId::try_from(pid).or(Err(Errno::ESRCH))?;
+        let Some(target_process) = ctx.objs.host.process_borrow(pid) else {
+            log::debug!("Process {pid} not found");
+            return Err(Errno::ESRCH.into());
+        };
+        let target_process = &*target_process.borrow(ctx.objs.host.root());
+
+        let mut file_flags = FileStatus::empty();
+        if flags & nonblock_flag != 0 {
+            file_flags.insert(FileStatus::NONBLOCK);
+        }
+
+        // A zombie process has already exited, so its pidfd is readable immediately.
+        let runnable = target_process.borrow_as_runnable();
+        let file = Arc::new(AtomicRefCell::new(PidFd::new(
+            ctx.objs.host.id(),
+            pid,
+            runnable.is_none(),
+            file_flags,
+        )));
+        if let Some(runnable) = runnable {
+            runnable.add_pidfd(&file);
+        }
+
+        // pidfd_open(2): "The close-on-exec flag is set on the file descriptor."
+        let mut desc = Descriptor::new(CompatFile::New(OpenFile::new(File::PidFd(file))));
+        desc.set_flags(DescriptorFlags::FD_CLOEXEC);
+
+        let fd = ctx
+            .objs
+            .thread
+            .descriptor_table_borrow_mut(ctx.objs.host)
+            .register_descriptor(desc)
+            .or(Err(Errno::EMFILE))?;
+
+        log::trace!("pidfd_open() returning fd {fd}");
+
+        Ok(fd)
+    }
+
+    log_syscall!(
+        pidfd_send_signal,
+        /* rv */ std::ffi::c_int,
+        /* pidfd */ std::ffi::c_int,
+        /* sig */ SyscallSignalArg,
+        /* info */ *const std::ffi::c_void,
+        /* flags */ std::ffi::c_uint,
+    );
+    pub fn pidfd_send_signal(
+        ctx: &mut SyscallContext,
+        pidfd: std::ffi::c_int,
+        sig: std::ffi::c_int,
+        info: ForeignPtr<linux_api::signal::siginfo_t>,
+        flags: std::ffi::c_uint,
+    ) -> Result<(), SyscallError> {
+        // pidfd_send_signal(2): "The flags argument is reserved for future use; currently, this
+        // argument must be specified as 0."
+        if flags != 0 {
+            return Err(Errno::EINVAL.into());
+        }
+
+        if !info.is_null() {
+            warn_once_then_debug!("pidfd_send_signal with a non-NULL info is unsupported");
+            return Err(Errno::ENOTSUP.into());
+        }
+
+        let file = {
+            let desc_table = ctx.objs.thread.descriptor_table_borrow(ctx.objs.host);
+            let desc = Self::get_descriptor(&desc_table, pidfd)?;
+            let CompatFile::New(file) = desc.file() else {
+                return Err(Errno::EBADF.into());
+            };
+            file.inner_file().clone()
+        };
+
+        // pidfd_send_signal(2): "EBADF pidfd is not a valid PID file descriptor."
+        let File::PidFd(ref pidfd) = file else {
+            return Err(Errno::EBADF.into());
+        };
+
+        let (host_id, pid) = {
+            let pidfd = pidfd.borrow();
+            (pidfd.host_id(), pidfd.pid())
+        };
+
+        if host_id != ctx.objs.host.id() {
+            // We can't signal processes on other hosts.
+            return Err(Errno::EPERM.into());
+        }
+
+        // pidfd_send_signal(2): "ESRCH The target process does not exist (i.e., it has terminated
+        // and been waited on)."
+        let Some(target_process) = ctx.objs.host.process_borrow(pid) else {
+            return Err(Errno::ESRCH.into());
+        };
+        let target_process = &*target_process.borrow(ctx.objs.host.root());
+
+        Self::signal_process(ctx.objs, target_process, sig)?;
+
+        Ok(())
+    }
+}
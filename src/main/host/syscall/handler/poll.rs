@@ -2,13 +2,14 @@ use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
 use crate::cshadow as c;
 use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
+use crate::host::syscall::type_formatting::SyscallPollFdsArg;
 use crate::host::syscall::types::SyscallError;
 
 impl SyscallHandler {
     log_syscall!(
         poll,
         /* rv */ std::ffi::c_int,
-        /* ufds */ *const std::ffi::c_void,
+        /* ufds */ SyscallPollFdsArg</* nfds */ 1>,
         /* nfds */ std::ffi::c_uint,
         /* timeout_msecs */ std::ffi::c_int,
     );
@@ -24,7 +25,7 @@ impl SyscallHandler {
     log_syscall!(
         ppoll,
         /* rv */ std::ffi::c_int,
-        /* ufds */ *const std::ffi::c_void,
+        /* ufds */ SyscallPollFdsArg</* nfds */ 1>,
         /* nfds */ std::ffi::c_uint,
         /* tsp */ *const linux_api::time::kernel_timespec,
         /* sigmask */ *const std::ffi::c_void,
@@ -1,10 +1,11 @@
 use linux_api::errno::Errno;
-use linux_api::prctl::PrctlOp;
+use linux_api::prctl::{PrctlOp, TASK_COMM_LEN};
 use linux_api::sched::SuidDump;
+use linux_api::signal::Signal;
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
 use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
-use crate::host::syscall::types::SyscallError;
+use crate::host::syscall::types::{ForeignArrayPtr, SyscallError};
 
 impl SyscallHandler {
     log_syscall!(
@@ -38,14 +39,10 @@ impl SyscallHandler {
             | PrctlOp::PR_GET_FPEMU
             | PrctlOp::PR_SET_FPEXC
             | PrctlOp::PR_GET_FPEXC
-            | PrctlOp::PR_SET_KEEPCAPS
-            | PrctlOp::PR_GET_KEEPCAPS
             | PrctlOp::PR_MCE_KILL
             | PrctlOp::PR_MCE_KILL_GET
             | PrctlOp::PR_MPX_ENABLE_MANAGEMENT
             | PrctlOp::PR_MPX_DISABLE_MANAGEMENT
-            | PrctlOp::PR_SET_NAME
-            | PrctlOp::PR_GET_NAME
             | PrctlOp::PR_SET_NO_NEW_PRIVS
             | PrctlOp::PR_GET_NO_NEW_PRIVS
             | PrctlOp::PR_SET_MM
@@ -78,11 +75,7 @@ impl SyscallHandler {
             // effect.
             | PrctlOp::PR_SET_TIMERSLACK
             // Wouldn't actually hurt correctness, but could significantly hurt performance.
-            | PrctlOp::PR_SET_SPECULATION_CTRL
-            // We use this signal to ensure managed processes die when Shadow does. Allowing the
-            // process to override it could end up allowing orphaned managed processes to live on
-            // after shadow exits.
-            | PrctlOp::PR_SET_PDEATHSIG => {
+            | PrctlOp::PR_SET_SPECULATION_CTRL => {
                 log::warn!("Not allowing unimplemented prctl {option}");
                 Err(Errno::EINVAL.into())
             }
@@ -90,7 +83,52 @@ impl SyscallHandler {
                 let out_ptr = ForeignPtr::from(arg2)
                     .cast::<ForeignPtr<linux_api::posix_types::kernel_pid_t>>();
                 let tid_addr = ctx.objs.thread.get_tid_address();
-                ctx.objs.process.memory_borrow_mut().write(out_ptr, &tid_addr)?;
+                ctx.objs.memory_mut().write(out_ptr, &tid_addr)?;
+                Ok(0)
+            }
+            PrctlOp::PR_SET_NAME => {
+                // the name is truncated to `TASK_COMM_LEN - 1` bytes, even if it's not terminated
+                let name_ptr = ForeignArrayPtr::new(ForeignPtr::from(arg2).cast(), TASK_COMM_LEN);
+                let mem = ctx.objs.memory();
+                let name = mem.memory_ref_prefix(name_ptr)?;
+                ctx.objs.thread.set_comm(&name);
+
+                let comm = ctx.objs.thread.comm();
+                log::debug!(
+                    "Thread {:?} set its name to {:?}",
+                    ctx.objs.thread.id(),
+                    std::ffi::CStr::from_bytes_until_nul(&comm).unwrap(),
+                );
+                Ok(0)
+            }
+            PrctlOp::PR_GET_NAME => {
+                let out_ptr = ForeignPtr::from(arg2).cast::<[u8; TASK_COMM_LEN]>();
+                ctx.objs
+                    .memory_mut()
+                    .write(out_ptr, &ctx.objs.thread.comm())?;
+                Ok(0)
+            }
+            // We use the native parent-death signal to ensure managed processes die when Shadow
+            // does, so we can't let the process override it natively. Instead we emulate it, and
+            // send the signal when the emulated parent process exits.
+            PrctlOp::PR_SET_PDEATHSIG => {
+                let signal = match i32::try_from(arg2) {
+                    Ok(0) => None,
+                    Ok(signal) => Some(Signal::try_from(signal).or(Err(Errno::EINVAL))?),
+                    Err(_) => return Err(Errno::EINVAL.into()),
+                };
+                ctx.objs.process.set_parent_death_signal(signal);
+                Ok(0)
+            }
+            PrctlOp::PR_GET_PDEATHSIG => {
+                let out_ptr = ForeignPtr::from(arg2).cast::<std::ffi::c_int>();
+                let signal = ctx
+                    .objs
+                    .process
+                    .parent_death_signal()
+                    .map(i32::from)
+                    .unwrap_or(0);
+                ctx.objs.memory_mut().write(out_ptr, &signal)?;
                 Ok(0)
             }
             PrctlOp::PR_SET_DUMPABLE => {
@@ -105,6 +143,16 @@ impl SyscallHandler {
             PrctlOp::PR_GET_DUMPABLE => {
                 Ok(ctx.objs.process.dumpable().val())
             }
+            // Shadow doesn't model capabilities changing on uid transitions, so this flag has no
+            // effect other than being returned by `PR_GET_KEEPCAPS`.
+            PrctlOp::PR_SET_KEEPCAPS => match arg2 {
+                0 | 1 => {
+                    ctx.objs.process.set_keepcaps(arg2 == 1);
+                    Ok(0)
+                }
+                _ => Err(Errno::EINVAL.into()),
+            },
+            PrctlOp::PR_GET_KEEPCAPS => Ok(ctx.objs.process.keepcaps().into()),
             _ => {
                 log::warn!("Unknown prctl operation {option}");
                 Err(Errno::EINVAL.into())
@@ -18,16 +18,28 @@ impl SyscallHandler {
         ctx: &mut SyscallContext,
         buf_ptr: ForeignPtr<u8>,
         count: usize,
-        _flags: std::ffi::c_uint,
+        flags: std::ffi::c_uint,
     ) -> Result<isize, Errno> {
-        // We ignore the flags arg, because we use the same random source for both
-        // random and urandom, and it never blocks anyway.
+        // getrandom(2): "EINVAL An invalid flag was specified in flags."
+        if flags & !(libc::GRND_NONBLOCK | libc::GRND_RANDOM | libc::GRND_INSECURE) != 0 {
+            debug!("Invalid getrandom flags: {flags}");
+            return Err(Errno::EINVAL);
+        }
+
+        // Linux rejects GRND_INSECURE in combination with GRND_RANDOM.
+        if flags & libc::GRND_INSECURE != 0 && flags & libc::GRND_RANDOM != 0 {
+            debug!("getrandom flags GRND_INSECURE and GRND_RANDOM are mutually exclusive");
+            return Err(Errno::EINVAL);
+        }
+
+        // Otherwise the flags don't change anything. We use the same deterministic random source
+        // for both the random and urandom sources, and it's always initialized so it never blocks.
 
         trace!("Trying to read {count} random bytes.");
 
         // Get a native-process mem buffer where we can copy the random bytes.
         let dst_ptr = ForeignArrayPtr::new(buf_ptr, count);
-        let mut memory = ctx.objs.process.memory_borrow_mut();
+        let mut memory = ctx.objs.memory_mut();
         let mut mem_ref = match memory.memory_ref_mut_uninit(dst_ptr) {
             Ok(m) => m,
             Err(e) => {
@@ -1,10 +1,62 @@
 use linux_api::errno::Errno;
+use linux_api::resource::{rlimit, rlimit64, Resource};
+use log::*;
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
+use crate::host::host::Host;
+use crate::host::process::{ProcessId, RunnableProcess};
 use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
 use crate::host::syscall::types::SyscallError;
 
 impl SyscallHandler {
+    log_syscall!(
+        getrlimit,
+        /* rv */ std::ffi::c_int,
+        /* resource */ std::ffi::c_uint,
+        /* rlim */ *const std::ffi::c_void,
+    );
+    pub fn getrlimit(
+        ctx: &mut SyscallContext,
+        resource: std::ffi::c_uint,
+        rlim_ptr: ForeignPtr<rlimit>,
+    ) -> Result<(), SyscallError> {
+        let old = {
+            let runnable = ctx.objs.process.borrow_as_runnable().unwrap();
+            Self::prlimit_helper(ctx.objs.host, &runnable, resource, None)?
+        };
+
+        let old = rlimit {
+            rlim_cur: old.rlim_cur,
+            rlim_max: old.rlim_max,
+        };
+        ctx.objs.memory_mut().write(rlim_ptr, &old)?;
+
+        Ok(())
+    }
+
+    log_syscall!(
+        setrlimit,
+        /* rv */ std::ffi::c_int,
+        /* resource */ std::ffi::c_uint,
+        /* rlim */ *const std::ffi::c_void,
+    );
+    pub fn setrlimit(
+        ctx: &mut SyscallContext,
+        resource: std::ffi::c_uint,
+        rlim_ptr: ForeignPtr<rlimit>,
+    ) -> Result<(), SyscallError> {
+        let new = ctx.objs.memory().read(rlim_ptr)?;
+        let new = rlimit64 {
+            rlim_cur: new.rlim_cur,
+            rlim_max: new.rlim_max,
+        };
+
+        let runnable = ctx.objs.process.borrow_as_runnable().unwrap();
+        Self::prlimit_helper(ctx.objs.host, &runnable, resource, Some(new))?;
+
+        Ok(())
+    }
+
     log_syscall!(
         prlimit64,
         /* rv */ std::ffi::c_int,
@@ -14,25 +66,80 @@ impl SyscallHandler {
         /* old_rlim */ *const std::ffi::c_void,
     );
     pub fn prlimit64(
-        _ctx: &mut SyscallContext,
+        ctx: &mut SyscallContext,
         pid: linux_api::posix_types::kernel_pid_t,
         resource: std::ffi::c_uint,
-        _new_rlim: ForeignPtr<()>,
-        _old_rlim: ForeignPtr<()>,
+        new_rlim_ptr: ForeignPtr<rlimit64>,
+        old_rlim_ptr: ForeignPtr<rlimit64>,
     ) -> Result<(), SyscallError> {
         log::trace!("prlimit64 called on pid {pid} for resource {resource}");
 
-        // TODO: For determinism, we may want to enforce static limits for certain resources, like
-        // RLIMIT_NOFILE. Some applications like Tor will change behavior depending on these limits.
+        let new = if new_rlim_ptr.is_null() {
+            None
+        } else {
+            Some(ctx.objs.memory().read(new_rlim_ptr)?)
+        };
 
-        if pid == 0 {
+        let old = if pid == 0 || pid == libc::pid_t::from(ctx.objs.process.id()) {
             // process is calling prlimit on itself
-            Err(SyscallError::Native)
+            let runnable = ctx.objs.process.borrow_as_runnable().unwrap();
+            Self::prlimit_helper(ctx.objs.host, &runnable, resource, new)?
         } else {
-            // TODO: We do not currently support adjusting other processes limits. To support it, we
-            // just need to find the native pid associated with pid, and call prlimit on the native
-            // pid instead.
-            Err(Errno::EOPNOTSUPP.into())
+            // Process ids are local to a host, so processes on other hosts aren't visible here and
+            // are reported as nonexistent.
+            let pid = ProcessId::try_from(pid).or(Err(Errno::ESRCH))?;
+            let Some(target_process) = ctx.objs.host.process_borrow(pid) else {
+                debug!("Process {pid} not found");
+                return Err(Errno::ESRCH.into());
+            };
+            let target_process = target_process.borrow(ctx.objs.host.root());
+
+            // a process that has exited (but not been reaped) has no limits
+            let Some(runnable) = target_process.borrow_as_runnable() else {
+                debug!("Process {pid} is not running");
+                return Err(Errno::ESRCH.into());
+            };
+
+            Self::prlimit_helper(ctx.objs.host, &runnable, resource, new)?
+        };
+
+        if !old_rlim_ptr.is_null() {
+            ctx.objs.memory_mut().write(old_rlim_ptr, &old)?;
         }
+
+        Ok(())
+    }
+
+    /// Get the limits for `resource` of process `runnable`, and if `new` is set, replace them.
+    /// Returns the old limits.
+    fn prlimit_helper(
+        host: &Host,
+        runnable: &RunnableProcess,
+        resource: std::ffi::c_uint,
+        new: Option<rlimit64>,
+    ) -> Result<rlimit64, Errno> {
+        let Ok(resource) = Resource::try_from(resource) else {
+            debug!("Unknown resource {resource}");
+            return Err(Errno::EINVAL);
+        };
+
+        let old = runnable.rlimit(resource);
+
+        if let Some(new) = new {
+            // setrlimit(2): "EINVAL rlim->rlim_cur was greater than rlim->rlim_max"
+            if new.rlim_cur > new.rlim_max {
+                return Err(Errno::EINVAL);
+            }
+
+            // setrlimit(2): "EPERM An unprivileged process tried to raise the hard limit". We
+            // treat all managed processes as unprivileged.
+            if new.rlim_max > old.rlim_max {
+                return Err(Errno::EPERM);
+            }
+
+            runnable.set_rlimit(host, resource, new);
+        }
+
+        Ok(old)
     }
 }
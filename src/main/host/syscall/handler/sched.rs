@@ -10,12 +10,45 @@ use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
 use crate::host::syscall::types::ForeignArrayPtr;
 use crate::host::thread::ThreadId;
 
-// We always report that the thread is running on CPU 0, Node 0
-const CURRENT_CPU: u32 = 0;
+// If the scheduler isn't pinning worker threads, we report that the thread is running on CPU 0,
+// Node 0
+const DEFAULT_CPU: u32 = 0;
 
 const RSEQ_FLAG_UNREGISTER: i32 = 1;
 
+/// The cpu that the calling thread appears to be running on, and the only cpu in its affinity mask.
+/// This is the cpu that the scheduler has pinned the current worker thread to.
+fn current_cpu() -> u32 {
+    scheduler::core_affinity().unwrap_or(DEFAULT_CPU)
+}
+
 impl SyscallHandler {
+    log_syscall!(
+        getcpu,
+        /* rv */ std::ffi::c_int,
+        /* cpu */ *const std::ffi::c_void,
+        /* node */ *const std::ffi::c_void,
+        /* tcache */ *const std::ffi::c_void,
+    );
+    pub fn getcpu(
+        ctx: &mut SyscallContext,
+        cpu_ptr: ForeignPtr<std::ffi::c_uint>,
+        node_ptr: ForeignPtr<std::ffi::c_uint>,
+        // unused since linux 2.6.24
+        _tcache_ptr: ForeignPtr<()>,
+    ) -> Result<(), Errno> {
+        // must agree with the mask reported by `sched_getaffinity`
+        if !cpu_ptr.is_null() {
+            ctx.objs.memory_mut().write(cpu_ptr, &current_cpu())?;
+        }
+
+        if !node_ptr.is_null() {
+            ctx.objs.memory_mut().write(node_ptr, &0)?;
+        }
+
+        Ok(())
+    }
+
     log_syscall!(
         sched_getaffinity,
         /* rv */ i32,
@@ -34,7 +67,6 @@ impl SyscallHandler {
         mask_ptr: ForeignPtr<std::ffi::c_ulong>,
     ) -> Result<std::ffi::c_int, Errno> {
         let mask_ptr = mask_ptr.cast::<u8>();
-        let mask_ptr = ForeignArrayPtr::new(mask_ptr, cpusetsize);
 
         let tid = ThreadId::try_from(tid).or(Err(Errno::ESRCH))?;
         if !ctx.objs.host.has_thread(tid) && kernel_pid_t::from(tid) != 0 {
@@ -43,20 +75,27 @@ impl SyscallHandler {
 
         // Shadow doesn't have users, so no need to check for permissions
 
-        if cpusetsize == 0 {
+        let cpu = usize::try_from(current_cpu()).unwrap();
+
+        // the mask must be large enough to hold the cpu
+        if cpusetsize <= cpu / 8 {
             return Err(Errno::EINVAL);
         }
 
-        let mut mem = ctx.objs.process.memory_borrow_mut();
-        let mut mask = mem.memory_ref_mut(mask_ptr)?;
+        // write whole `c_ulong` words like linux, and the caller will zero any remaining bytes
+        let word_size = std::mem::size_of::<std::ffi::c_ulong>();
+        let bytes_written = std::cmp::min((cpu / 8 / word_size + 1) * word_size, cpusetsize);
+
+        let mut mem = ctx.objs.memory_mut();
+        let mut mask = mem.memory_ref_mut(ForeignArrayPtr::new(mask_ptr, bytes_written))?;
 
         // this assumes little endian
-        let bytes_written = 1;
-        mask[0] = 1;
+        mask.fill(0);
+        mask[cpu / 8] = 1 << (cpu % 8);
 
         mask.flush()?;
 
-        Ok(bytes_written)
+        Ok(bytes_written.try_into().unwrap())
     }
 
     log_syscall!(
@@ -77,7 +116,6 @@ impl SyscallHandler {
         mask_ptr: ForeignPtr<std::ffi::c_ulong>,
     ) -> Result<(), Errno> {
         let mask_ptr = mask_ptr.cast::<u8>();
-        let mask_ptr = ForeignArrayPtr::new(mask_ptr, cpusetsize);
 
         let tid = ThreadId::try_from(tid).or(Err(Errno::ESRCH))?;
         if !ctx.objs.host.has_thread(tid) && kernel_pid_t::from(tid) != 0 {
@@ -86,18 +124,24 @@ impl SyscallHandler {
 
         // Shadow doesn't have users, so no need to check for permissions
 
-        if cpusetsize == 0 {
+        let cpu = usize::try_from(current_cpu()).unwrap();
+
+        // the mask must include the only cpu the thread is allowed to run on
+        if cpusetsize <= cpu / 8 {
             return Err(Errno::EINVAL);
         }
 
-        let mem = ctx.objs.process.memory_borrow_mut();
-        let mask = mem.memory_ref(mask_ptr)?;
+        let mem = ctx.objs.memory_mut();
+        let mask = mem.memory_ref(ForeignArrayPtr::new(mask_ptr, cpu / 8 + 1))?;
 
         // this assumes little endian
-        if mask[0] & 0x01 == 0 {
+        if mask[cpu / 8] & (1 << (cpu % 8)) == 0 {
             return Err(Errno::EINVAL);
         }
 
+        // for determinism we never actually move the thread; it continues running wherever the
+        // scheduler runs its host
+
         Ok(())
     }
 
@@ -141,7 +185,7 @@ impl SyscallHandler {
         //
         // Instead, we should treat the rseq struct as a bunch of bytes and write to individual
         // fields if possible without making assumptions about the size of the data.
-        let mut mem = ctx.objs.process.memory_borrow_mut();
+        let mut mem = ctx.objs.memory_mut();
         let mut rseq_mem = mem.memory_ref_mut(ForeignArrayPtr::new(rseq_ptr, rseq_len))?;
         let rseq_bytes = &mut *rseq_mem;
 
@@ -159,15 +203,17 @@ impl SyscallHandler {
         // in a handler.
         // https://github.com/shadow/shadow/issues/2139
         //
-        // For now we just update to reflect that the thread is running on CPU 0.
+        // For now we just update to reflect the cpu that the thread is running on. This is only
+        // written at registration, so the cpu may become stale if the host is later moved to a
+        // worker thread pinned to a different cpu.
 
         let Some((cpu_id, cpu_id_start)) = field_project!(rseq_bytes, rseq, (cpu_id, cpu_id_start))
         else {
             return Err(Errno::EINVAL);
         };
 
-        cpu_id.write(CURRENT_CPU);
-        cpu_id_start.write(CURRENT_CPU);
+        cpu_id.write(current_cpu());
+        cpu_id_start.write(current_cpu());
 
         rseq_mem.flush()?;
 
@@ -2,6 +2,7 @@ use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
 use crate::cshadow as c;
 use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
+use crate::host::syscall::type_formatting::{SyscallFdSetArg, SyscallPselectSigmaskArg};
 use crate::host::syscall::types::SyscallError;
 
 impl SyscallHandler {
@@ -9,9 +10,9 @@ impl SyscallHandler {
         select,
         /* rv */ std::ffi::c_int,
         /* n */ std::ffi::c_int,
-        /* inp */ *const std::ffi::c_void,
-        /* outp */ *const std::ffi::c_void,
-        /* exp */ *const std::ffi::c_void,
+        /* inp */ SyscallFdSetArg</* n */ 0>,
+        /* outp */ SyscallFdSetArg</* n */ 0>,
+        /* exp */ SyscallFdSetArg</* n */ 0>,
         /* tvp */ *const linux_api::time::kernel_old_timeval,
     );
     pub fn select(
@@ -29,11 +30,11 @@ impl SyscallHandler {
         pselect6,
         /* rv */ std::ffi::c_int,
         /* n */ std::ffi::c_int,
-        /* inp */ *const std::ffi::c_void,
-        /* outp */ *const std::ffi::c_void,
-        /* exp */ *const std::ffi::c_void,
+        /* inp */ SyscallFdSetArg</* n */ 0>,
+        /* outp */ SyscallFdSetArg</* n */ 0>,
+        /* exp */ SyscallFdSetArg</* n */ 0>,
         /* tsp */ *const linux_api::time::kernel_timespec,
-        /* sig */ *const std::ffi::c_void,
+        /* sig */ SyscallPselectSigmaskArg,
     );
     pub fn pselect6(
         ctx: &mut SyscallContext,
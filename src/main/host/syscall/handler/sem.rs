@@ -0,0 +1,190 @@
+use linux_api::errno::Errno;
+use linux_api::ipc::{sembuf, GETVAL, IPC_RMID, SEMOPM, SETVAL};
+use shadow_shim_helper_rs::simulation_time::SimulationTime;
+use shadow_shim_helper_rs::syscall_types::ForeignPtr;
+
+use crate::core::worker::Worker;
+use crate::host::syscall::condition::SyscallCondition;
+use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
+use crate::host::syscall::types::{Blocked, ForeignArrayPtr, SyscallError};
+use crate::host::syscall::Trigger;
+
+impl SyscallHandler {
+    // <https://github.com/torvalds/linux/tree/v6.3/ipc/sem.c#L618>
+    // ```
+    // SYSCALL_DEFINE3(semget, key_t, key, int, nsems, int, semflg)
+    // ```
+    log_syscall!(
+        semget,
+        /* rv */ std::ffi::c_int,
+        /* key */ std::ffi::c_int,
+        /* nsems */ std::ffi::c_int,
+        /* semflg */ std::ffi::c_int,
+    );
+    pub fn semget(
+        ctx: &mut SyscallContext,
+        key: std::ffi::c_int,
+        nsems: std::ffi::c_int,
+        semflg: std::ffi::c_int,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        let semid = ctx
+            .objs
+            .host
+            .sysv_sem_table_borrow_mut()
+            .get(key, nsems, semflg)?;
+
+        Ok(semid)
+    }
+
+    // <https://github.com/torvalds/linux/tree/v6.3/ipc/sem.c#L2290>
+    // ```
+    // SYSCALL_DEFINE3(semop, int, semid, struct sembuf __user *, tsops, unsigned, nsops)
+    // ```
+    log_syscall!(
+        semop,
+        /* rv */ std::ffi::c_int,
+        /* semid */ std::ffi::c_int,
+        /* tsops */ *const std::ffi::c_void,
+        /* nsops */ std::ffi::c_uint,
+    );
+    pub fn semop(
+        ctx: &mut SyscallContext,
+        semid: std::ffi::c_int,
+        tsops: ForeignPtr<sembuf>,
+        nsops: std::ffi::c_uint,
+    ) -> Result<(), SyscallError> {
+        Self::semop_helper(ctx, semid, tsops, nsops, ForeignPtr::null())
+    }
+
+    // <https://github.com/torvalds/linux/tree/v6.3/ipc/sem.c#L2269>
+    // ```
+    // SYSCALL_DEFINE4(semtimedop, int, semid, struct sembuf __user *, tsops,
+    //                 unsigned int, nsops, const struct __kernel_timespec __user *, timeout)
+    // ```
+    log_syscall!(
+        semtimedop,
+        /* rv */ std::ffi::c_int,
+        /* semid */ std::ffi::c_int,
+        /* tsops */ *const std::ffi::c_void,
+        /* nsops */ std::ffi::c_uint,
+        /* timeout */ *const linux_api::time::timespec,
+    );
+    pub fn semtimedop(
+        ctx: &mut SyscallContext,
+        semid: std::ffi::c_int,
+        tsops: ForeignPtr<sembuf>,
+        nsops: std::ffi::c_uint,
+        timeout: ForeignPtr<linux_api::time::timespec>,
+    ) -> Result<(), SyscallError> {
+        Self::semop_helper(ctx, semid, tsops, nsops, timeout)
+    }
+
+    fn semop_helper(
+        ctx: &mut SyscallContext,
+        semid: std::ffi::c_int,
+        tsops: ForeignPtr<sembuf>,
+        nsops: std::ffi::c_uint,
+        timeout_ptr: ForeignPtr<linux_api::time::timespec>,
+    ) -> Result<(), SyscallError> {
+        let nsops = usize::try_from(nsops).unwrap();
+        if semid < 0 || nsops < 1 {
+            return Err(Errno::EINVAL.into());
+        }
+        if nsops > SEMOPM {
+            return Err(Errno::E2BIG.into());
+        }
+
+        let mut ops = vec![shadow_pod::zeroed::<sembuf>(); nsops];
+        ctx.objs
+            .memory()
+            .copy_from_ptr(&mut ops, ForeignArrayPtr::new(tsops, nsops))?;
+
+        let now = Worker::current_time().unwrap();
+
+        // The condition will exist after a wakeup, and holds the timeout we computed when we first
+        // blocked.
+        let condition = ctx.objs.thread.syscall_condition();
+        let is_rerun = condition.is_some();
+        let timeout = if let Some(cond) = condition {
+            cond.timeout()
+        } else if timeout_ptr.is_null() {
+            None
+        } else {
+            let ts = ctx.objs.memory().read(timeout_ptr)?;
+            let duration = SimulationTime::try_from(ts).or(Err(Errno::EINVAL))?;
+            Some(now + duration)
+        };
+
+        let mut sem_table = ctx.objs.host.sysv_sem_table_borrow_mut();
+
+        // semop(2): "The semaphore set was removed" while we were waiting
+        if is_rerun && sem_table.was_removed(semid) {
+            return Err(Errno::EIDRM.into());
+        }
+
+        if sem_table.semop(semid, &ops, ctx.objs.process.id())? {
+            return Ok(());
+        }
+
+        if timeout.is_some_and(|timeout| timeout <= now) {
+            return Err(Errno::EAGAIN.into());
+        }
+
+        // wait for a semaphore in the set to change, and then try again; semop(2) is never
+        // restarted after being interrupted by a signal handler
+        let futex = sem_table.wakeup_futex(semid).ptr();
+        let mut rv = SyscallError::Blocked(Blocked {
+            condition: SyscallCondition::new(unsafe { Trigger::from_futex(futex) }),
+            restartable: false,
+        });
+        if timeout.is_some() {
+            rv.blocked_condition().unwrap().set_timeout(timeout);
+        }
+
+        Err(rv)
+    }
+
+    // <https://github.com/torvalds/linux/tree/v6.3/ipc/sem.c#L1684>
+    // ```
+    // SYSCALL_DEFINE4(semctl, int, semid, int, semnum, int, cmd, unsigned long, arg)
+    // ```
+    log_syscall!(
+        semctl,
+        /* rv */ std::ffi::c_int,
+        /* semid */ std::ffi::c_int,
+        /* semnum */ std::ffi::c_int,
+        /* cmd */ std::ffi::c_int,
+        /* arg */ std::ffi::c_ulong,
+    );
+    pub fn semctl(
+        ctx: &mut SyscallContext,
+        semid: std::ffi::c_int,
+        semnum: std::ffi::c_int,
+        cmd: std::ffi::c_int,
+        arg: std::ffi::c_ulong,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        if semid < 0 {
+            return Err(Errno::EINVAL.into());
+        }
+
+        let mut sem_table = ctx.objs.host.sysv_sem_table_borrow_mut();
+
+        match cmd {
+            GETVAL => Ok(sem_table.value(semid, semnum)?),
+            SETVAL => {
+                // the argument is a `union semun`, which holds an int for `SETVAL`
+                let value = arg as std::ffi::c_int;
+                sem_table.set_value(semid, semnum, value)?;
+                Ok(0)
+            }
+            IPC_RMID => {
+                sem_table.remove(semid)?;
+                Ok(0)
+            }
+            _ => {
+                warn_once_then_debug!("Unsupported semctl command {cmd}");
+                Err(Errno::EINVAL.into())
+            }
+        }
+    }
+}
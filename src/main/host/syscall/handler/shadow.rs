@@ -21,7 +21,7 @@ impl SyscallHandler {
 
         log::trace!("Initializing memory mapper");
 
-        let mut memory_manager = ctx.objs.process.memory_borrow_mut();
+        let mut memory_manager = ctx.objs.memory_mut();
         if !memory_manager.has_mapper() {
             memory_manager.init_mapper(ctx.objs)
         }
@@ -29,6 +29,34 @@ impl SyscallHandler {
         Ok(())
     }
 
+    log_syscall!(
+        shadow_checkpoint,
+        /* rv */ std::ffi::c_int,
+        /* id */ u64,
+    );
+    pub fn shadow_checkpoint(ctx: &mut SyscallContext, id: u64) -> Result<(), Errno> {
+        let hash = ctx.objs.host.add_checkpoint(id);
+        log::trace!("Recorded checkpoint {id} with hash {hash:016x}");
+        Ok(())
+    }
+
+    log_syscall!(
+        shadow_get_sim_time,
+        /* rv */ std::ffi::c_int,
+        /* time_ptr */ *const std::ffi::c_void,
+    );
+    pub fn shadow_get_sim_time(
+        ctx: &mut SyscallContext,
+        time_ptr: ForeignPtr<u64>,
+    ) -> Result<(), Errno> {
+        let now = Worker::current_time().unwrap().to_abs_simtime();
+        let now = u64::try_from(now.as_nanos()).unwrap();
+
+        ctx.objs.memory_mut().write(time_ptr, &now)?;
+
+        Ok(())
+    }
+
     log_syscall!(
         shadow_hostname_to_addr_ipv4,
         /* rv */ std::ffi::c_int,
@@ -65,7 +93,7 @@ impl SyscallHandler {
         let name_ptr = ForeignArrayPtr::new(name_ptr, name_len + 1);
         let addr_ptr = addr_ptr.cast::<u32>();
 
-        let mut mem = ctx.objs.process.memory_borrow_mut();
+        let mut mem = ctx.objs.memory_mut();
 
         let lookup_name_ref = mem.memory_ref_prefix(name_ptr)?;
         let lookup_name = lookup_name_ref.get_cstr()?;
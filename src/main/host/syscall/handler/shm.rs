@@ -0,0 +1,228 @@
+use linux_api::errno::Errno;
+use linux_api::fcntl::OFlag;
+use linux_api::ipc::{shmid64_ds, IPC_RMID, IPC_STAT, SHM_EXEC, SHM_RDONLY, SHM_REMAP, SHM_RND};
+use linux_api::mman::{MapFlags, ProtFlags};
+use shadow_shim_helper_rs::emulated_time::EmulatedTime;
+use shadow_shim_helper_rs::syscall_types::ForeignPtr;
+
+use crate::core::worker::Worker;
+use crate::host::memory_manager::page_size;
+use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
+use crate::host::syscall::types::SyscallError;
+
+impl SyscallHandler {
+    // <https://github.com/torvalds/linux/tree/v6.3/ipc/shm.c#L1513>
+    // ```
+    // SYSCALL_DEFINE3(shmget, key_t, key, size_t, size, int, shmflg)
+    // ```
+    log_syscall!(
+        shmget,
+        /* rv */ std::ffi::c_int,
+        /* key */ std::ffi::c_int,
+        /* size */ usize,
+        /* shmflg */ std::ffi::c_int,
+    );
+    pub fn shmget(
+        ctx: &mut SyscallContext,
+        key: std::ffi::c_int,
+        size: usize,
+        shmflg: std::ffi::c_int,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        let now = Worker::current_time().unwrap();
+        let pid = ctx.objs.process.id();
+
+        let shmid = ctx
+            .objs
+            .host
+            .sysv_shm_table_borrow_mut()
+            .get(key, size, shmflg, pid, now)?;
+
+        Ok(shmid)
+    }
+
+    // <https://github.com/torvalds/linux/tree/v6.3/ipc/shm.c#L1696>
+    // ```
+    // SYSCALL_DEFINE3(shmat, int, shmid, char __user *, shmaddr, int, shmflg)
+    // ```
+    log_syscall!(
+        shmat,
+        /* rv */ *const std::ffi::c_void,
+        /* shmid */ std::ffi::c_int,
+        /* shmaddr */ *const std::ffi::c_void,
+        /* shmflg */ std::ffi::c_int,
+    );
+    pub fn shmat(
+        ctx: &mut SyscallContext,
+        shmid: std::ffi::c_int,
+        shmaddr: ForeignPtr<u8>,
+        shmflg: std::ffi::c_int,
+    ) -> Result<ForeignPtr<u8>, SyscallError> {
+        let (path, len) = {
+            let table = ctx.objs.host.sysv_shm_table_borrow();
+            let segment = table.segment(shmid).ok_or(Errno::EINVAL)?;
+            (segment.path(), segment.mapped_size())
+        };
+
+        let mut addr = usize::from(shmaddr);
+        if addr % page_size() != 0 {
+            if shmflg & SHM_RND == 0 {
+                return Err(Errno::EINVAL.into());
+            }
+            // on x86-64, SHMLBA is the page size
+            addr -= addr % page_size();
+        }
+
+        let mut map_flags = MapFlags::MAP_SHARED;
+        if addr != 0 {
+            // linux won't replace existing mappings unless asked to
+            if shmflg & SHM_REMAP != 0 {
+                map_flags |= MapFlags::MAP_FIXED;
+            } else {
+                map_flags |= MapFlags::MAP_FIXED_NOREPLACE;
+            }
+        } else if shmflg & SHM_REMAP != 0 {
+            return Err(Errno::EINVAL.into());
+        }
+
+        let (prot, open_flags) = if shmflg & SHM_RDONLY != 0 {
+            (ProtFlags::PROT_READ, OFlag::O_RDONLY)
+        } else {
+            (ProtFlags::PROT_READ | ProtFlags::PROT_WRITE, OFlag::O_RDWR)
+        };
+        let prot = if shmflg & SHM_EXEC != 0 {
+            prot | ProtFlags::PROT_EXEC
+        } else {
+            prot
+        };
+
+        // this fd exists in the plugin and not shadow; make sure to close this before returning
+        let Ok(plugin_fd) = Self::open_plugin_path(ctx.objs, &path, open_flags, 0) else {
+            log::warn!("Unable to open shared memory segment {shmid} in the plugin");
+            return Err(Errno::ENOMEM.into());
+        };
+
+        let mmap_result = ctx.objs.memory_mut().do_mmap(
+            ctx.objs,
+            ForeignPtr::<()>::from(addr).cast::<u8>(),
+            len,
+            prot,
+            map_flags,
+            plugin_fd,
+            0,
+        );
+
+        Self::close_plugin_file(ctx.objs, plugin_fd);
+
+        let addr = match mmap_result {
+            Ok(addr) => addr,
+            Err(Errno::EEXIST) => return Err(Errno::EINVAL.into()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let now = Worker::current_time().unwrap();
+        ctx.objs.host.sysv_shm_table_borrow_mut().attach(
+            shmid,
+            ctx.objs.process.id(),
+            usize::from(addr),
+            now,
+        );
+
+        Ok(addr)
+    }
+
+    // <https://github.com/torvalds/linux/tree/v6.3/ipc/shm.c#L1834>
+    // ```
+    // SYSCALL_DEFINE1(shmdt, char __user *, shmaddr)
+    // ```
+    log_syscall!(
+        shmdt,
+        /* rv */ std::ffi::c_int,
+        /* shmaddr */ *const std::ffi::c_void,
+    );
+    pub fn shmdt(ctx: &mut SyscallContext, shmaddr: ForeignPtr<u8>) -> Result<(), SyscallError> {
+        let now = Worker::current_time().unwrap();
+
+        let Some(len) = ctx.objs.host.sysv_shm_table_borrow_mut().detach(
+            ctx.objs.process.id(),
+            usize::from(shmaddr),
+            now,
+        ) else {
+            return Err(Errno::EINVAL.into());
+        };
+
+        ctx.objs.memory_mut().do_munmap(ctx.objs, shmaddr, len)?;
+
+        Ok(())
+    }
+
+    // <https://github.com/torvalds/linux/tree/v6.3/ipc/shm.c#L1279>
+    // ```
+    // SYSCALL_DEFINE3(shmctl, int, shmid, int, cmd, struct shmid_ds __user *, buf)
+    // ```
+    log_syscall!(
+        shmctl,
+        /* rv */ std::ffi::c_int,
+        /* shmid */ std::ffi::c_int,
+        /* cmd */ std::ffi::c_int,
+        /* buf */ *const std::ffi::c_void,
+    );
+    pub fn shmctl(
+        ctx: &mut SyscallContext,
+        shmid: std::ffi::c_int,
+        cmd: std::ffi::c_int,
+        buf: ForeignPtr<shmid64_ds>,
+    ) -> Result<(), SyscallError> {
+        if shmid < 0 {
+            return Err(Errno::EINVAL.into());
+        }
+
+        match cmd {
+            IPC_STAT => {
+                let stat = {
+                    let table = ctx.objs.host.sysv_shm_table_borrow();
+                    let segment = table.segment(shmid).ok_or(Errno::EINVAL)?;
+
+                    let secs = |time: Option<EmulatedTime>| {
+                        time.map_or(0, |x| {
+                            x.duration_since(&EmulatedTime::UNIX_EPOCH).as_secs() as i64
+                        })
+                    };
+
+                    let uid = rustix::process::getuid().as_raw();
+                    let gid = rustix::process::getgid().as_raw();
+
+                    let mut stat = shadow_pod::zeroed::<shmid64_ds>();
+                    stat.shm_perm.key = segment.key();
+                    stat.shm_perm.uid = uid;
+                    stat.shm_perm.gid = gid;
+                    stat.shm_perm.cuid = uid;
+                    stat.shm_perm.cgid = gid;
+                    stat.shm_perm.mode = segment.mode();
+                    stat.shm_segsz = segment.size() as u64;
+                    stat.shm_atime = secs(segment.attach_time());
+                    stat.shm_dtime = secs(segment.detach_time());
+                    stat.shm_ctime = secs(Some(segment.change_time()));
+                    stat.shm_cpid = segment.creator().into();
+                    stat.shm_lpid = segment.last_pid().into();
+                    stat.shm_nattch = segment.attach_count();
+                    stat
+                };
+
+                ctx.objs.memory_mut().write(buf, &stat)?;
+                Ok(())
+            }
+            IPC_RMID => {
+                let now = Worker::current_time().unwrap();
+                ctx.objs
+                    .host
+                    .sysv_shm_table_borrow_mut()
+                    .remove(shmid, now)?;
+                Ok(())
+            }
+            _ => {
+                warn_once_then_debug!("Unsupported shmctl command {cmd}");
+                Err(Errno::EINVAL.into())
+            }
+        }
+    }
+}
@@ -1,11 +1,19 @@
 use linux_api::errno::Errno;
-use linux_api::signal::{defaultaction, siginfo_t, LinuxDefaultAction, Signal, SignalHandler};
+use linux_api::signal::{
+    defaultaction, siginfo_t, sigset_t, LinuxDefaultAction, SigInfoCodeSi, Signal, SignalHandler,
+};
+use shadow_shim_helper_rs::emulated_time::EmulatedTime;
 use shadow_shim_helper_rs::explicit_drop::{ExplicitDrop, ExplicitDropper};
+use shadow_shim_helper_rs::shim_shmem::take_pending_signal_in;
+use shadow_shim_helper_rs::simulation_time::SimulationTime;
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
+use crate::core::worker::Worker;
 use crate::cshadow as c;
-use crate::host::process::Process;
+use crate::host::process::{Process, ProcessId};
 use crate::host::syscall::handler::{SyscallContext, SyscallHandler, ThreadContext};
+use crate::host::syscall::io::write_partial;
+use crate::host::syscall::type_formatting::SyscallSignalArg;
 use crate::host::syscall::types::SyscallError;
 use crate::host::thread::Thread;
 
@@ -14,7 +22,7 @@ impl SyscallHandler {
         kill,
         /* rv */ std::ffi::c_int,
         /* pid */ linux_api::posix_types::kernel_pid_t,
-        /* sig */ std::ffi::c_int,
+        /* sig */ SyscallSignalArg,
     );
     pub fn kill(
         ctx: &mut SyscallContext,
@@ -23,48 +31,68 @@ impl SyscallHandler {
     ) -> Result<(), Errno> {
         log::trace!("kill called on pid {pid} with signal {sig}");
 
-        let pid = if pid == -1 {
+        let target_pids: Vec<ProcessId> = if pid == -1 {
             // kill(2): If pid equals -1, then sig is sent to every process for which the calling
             // process has permission to send signals, except for process 1.
             //
-            // Currently unimplemented, and unlikely to be needed in the context of a shadow
-            // simulation.
-            log::warn!("kill with pid=-1 unimplemented");
-            return Err(Errno::ENOTSUP);
+            // Every process on the host may be signaled. Like Linux, we also skip the calling
+            // process itself.
+            let current_pid = ctx.objs.process.id();
+            ctx.objs
+                .host
+                .processes_borrow()
+                .keys()
+                .filter(|pid| **pid != ProcessId::INIT && **pid != current_pid)
+                .copied()
+                .collect()
         } else if pid == 0 {
             // kill(2): If pid equals 0, then sig is sent to every process in the process group of
             // the calling process.
-            //
-            // Currently every emulated process is in its own process group.
-            //
-            // FIXME: The above comment is no longer true since implementing fork(). See
-            // https://github.com/shadow/shadow/issues/3315
-            ctx.objs.process.id()
+            ctx.objs
+                .host
+                .process_ids_in_group(ctx.objs.process.group_id())
         } else if pid < -1 {
             // kill(2): If pid is less than -1, then sig is sent to every process in the process
             // group whose ID is -pid.
-            //
-            // Currently every emulated process is in its own process group, where pgid=pid.
-            //
-            // FIXME: The above comment is no longer true since implementing fork(). See
-            // https://github.com/shadow/shadow/issues/3315
-            (-pid).try_into().or(Err(Errno::ESRCH))?
+            let pgid: ProcessId = pid
+                .checked_neg()
+                .and_then(|pgid| pgid.try_into().ok())
+                .ok_or(Errno::ESRCH)?;
+            ctx.objs.host.process_ids_in_group(pgid)
         } else {
-            pid.try_into().or(Err(Errno::ESRCH))?
+            vec![pid.try_into().or(Err(Errno::ESRCH))?]
         };
 
-        let Some(target_process) = ctx.objs.host.process_borrow(pid) else {
-            log::debug!("Process {pid} not found");
-            return Err(Errno::ESRCH);
-        };
-        let target_process = &*target_process.borrow(ctx.objs.host.root());
+        Self::signal_processes(ctx.objs, &target_pids, sig)
+    }
 
-        Self::signal_process(ctx.objs, target_process, sig)
+    /// Send a signal to each of the processes in `target_pids`. Fails with `ESRCH` if none of them
+    /// exist.
+    fn signal_processes(
+        objs: &ThreadContext,
+        target_pids: &[ProcessId],
+        signal: std::ffi::c_int,
+    ) -> Result<(), Errno> {
+        let mut signaled_any = false;
+        for pid in target_pids {
+            let Some(target_process) = objs.host.process_borrow(*pid) else {
+                log::debug!("Process {pid} not found");
+                continue;
+            };
+            let target_process = &*target_process.borrow(objs.host.root());
+            Self::signal_process(objs, target_process, signal)?;
+            signaled_any = true;
+        }
+
+        if !signaled_any {
+            return Err(Errno::ESRCH);
+        }
+        Ok(())
     }
 
     /// Send a signal to `target_process` from the thread and process in `objs`. A signal of 0 will
     /// be ignored.
-    fn signal_process(
+    pub fn signal_process(
         objs: &ThreadContext,
         target_process: &Process,
         signal: std::ffi::c_int,
@@ -77,11 +105,6 @@ impl SyscallHandler {
             return Err(Errno::EINVAL);
         };
 
-        if signal.is_realtime() {
-            log::warn!("Unimplemented signal {signal:?}");
-            return Err(Errno::ENOTSUP);
-        }
-
         let sender_pid = objs.process.id().into();
         let siginfo = siginfo_t::new_for_kill(signal, sender_pid, 0);
 
@@ -90,11 +113,160 @@ impl SyscallHandler {
         Ok(())
     }
 
+    log_syscall!(
+        rt_sigqueueinfo,
+        /* rv */ std::ffi::c_int,
+        /* tgid */ linux_api::posix_types::kernel_pid_t,
+        /* sig */ SyscallSignalArg,
+        /* uinfo */ *const std::ffi::c_void,
+    );
+    pub fn rt_sigqueueinfo(
+        ctx: &mut SyscallContext,
+        tgid: linux_api::posix_types::kernel_pid_t,
+        sig: std::ffi::c_int,
+        uinfo: ForeignPtr<siginfo_t>,
+    ) -> Result<(), SyscallError> {
+        let info = ctx.objs.memory().read(uinfo)?;
+
+        let Ok(tgid) = ProcessId::try_from(tgid) else {
+            return Err(Errno::ESRCH.into());
+        };
+
+        // rt_sigqueueinfo(2): EPERM: The caller does not have permission to send the signal to the
+        // target. [...] Since Linux 2.6.39, the si_code field must be negative, and not SI_TKILL,
+        // unless the target is the caller.
+        let code = info.code_raw();
+        if (code >= 0 || code == i32::from(SigInfoCodeSi::SI_TKILL))
+            && tgid != ctx.objs.process.id()
+        {
+            return Err(Errno::EPERM.into());
+        }
+
+        let Some(target_process) = ctx.objs.host.process_borrow(tgid) else {
+            return Err(Errno::ESRCH.into());
+        };
+        let target_process = &*target_process.borrow(ctx.objs.host.root());
+
+        if sig == 0 {
+            return Ok(());
+        }
+        let Ok(signal) = Signal::try_from(sig) else {
+            return Err(Errno::EINVAL.into());
+        };
+
+        if signal.is_realtime() {
+            if let Some(runnable) = target_process.borrow_as_runnable() {
+                let host_shmem = ctx.objs.host.shim_shmem_lock_borrow().unwrap();
+                let shmem = runnable.shmem();
+                if shmem
+                    .protected
+                    .borrow(&host_shmem.root)
+                    .realtime_signal_queue_is_full()
+                {
+                    // sigqueue(3): EAGAIN: The limit of signals which may be queued has been
+                    // reached.
+                    return Err(Errno::EAGAIN.into());
+                }
+            }
+        }
+
+        target_process.signal(
+            ctx.objs.host,
+            Some(ctx.objs.thread),
+            &info.with_signal(signal),
+        );
+
+        Ok(())
+    }
+
+    log_syscall!(
+        rt_sigtimedwait,
+        /* rv */ std::ffi::c_int,
+        /* uthese */ *const linux_api::signal::sigset_t,
+        /* uinfo */ *const std::ffi::c_void,
+        /* uts */ *const linux_api::time::timespec,
+        /* sigsetsize */ libc::size_t,
+    );
+    pub fn rt_sigtimedwait(
+        ctx: &mut SyscallContext,
+        uthese: ForeignPtr<sigset_t>,
+        uinfo: ForeignPtr<siginfo_t>,
+        uts: ForeignPtr<linux_api::time::timespec>,
+        sigsetsize: libc::size_t,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        if sigsetsize != std::mem::size_of::<sigset_t>() {
+            return Err(Errno::EINVAL.into());
+        }
+
+        let mut mask = ctx.objs.memory().read(uthese)?;
+        // SIGKILL and SIGSTOP can't be caught, so they can't be waited for either.
+        mask.del(Signal::SIGKILL);
+        mask.del(Signal::SIGSTOP);
+
+        let now = Worker::current_time().unwrap();
+
+        // The condition will exist after a wakeup, and holds the timeout we computed when we first
+        // blocked.
+        let timeout = if let Some(cond) = ctx.objs.thread.syscall_condition() {
+            cond.timeout()
+        } else if uts.is_null() {
+            None
+        } else {
+            let ts = ctx.objs.memory().read(uts)?;
+            let duration = SimulationTime::try_from(ts).or(Err(Errno::EINVAL))?;
+            Some(now + duration)
+        };
+
+        let host_shmem = ctx.objs.host.shim_shmem_lock_borrow().unwrap();
+
+        let taken = take_pending_signal_in(
+            &host_shmem,
+            &ctx.objs.process.shmem(),
+            ctx.objs.thread.shmem(),
+            mask,
+        );
+        if let Some((signal, info)) = taken {
+            ctx.objs.thread.restore_sigtimedwait_mask(&host_shmem);
+            drop(host_shmem);
+
+            if !uinfo.is_null() {
+                ctx.objs.memory_mut().write(uinfo, &info)?;
+            }
+            return Ok(signal.into());
+        }
+
+        if timeout.is_some_and(|timeout| timeout <= now) {
+            ctx.objs.thread.restore_sigtimedwait_mask(&host_shmem);
+            return Err(Errno::EAGAIN.into());
+        }
+
+        // Unblock the signals we're waiting for so that their arrival wakes us up. They'll be
+        // accepted here when the syscall is re-run, before the shim has a chance to handle them.
+        ctx.objs.thread.unblock_for_sigtimedwait(&host_shmem, mask);
+
+        // None of the signals we're waiting for are pending, so any unblocked pending signal
+        // would interrupt the wait. Restore the original mask before returning EINTR rather than
+        // letting the syscall handler convert our blocked result.
+        if ctx
+            .objs
+            .thread
+            .unblocked_signal_pending(ctx.objs.process, &host_shmem)
+        {
+            ctx.objs.thread.restore_sigtimedwait_mask(&host_shmem);
+            return Err(SyscallError::new_interrupted(false));
+        }
+
+        Err(SyscallError::new_blocked_until(
+            timeout.unwrap_or(EmulatedTime::MAX),
+            false,
+        ))
+    }
+
     log_syscall!(
         tkill,
         /* rv */ std::ffi::c_int,
         /* pid */ linux_api::posix_types::kernel_pid_t,
-        /* sig */ std::ffi::c_int,
+        /* sig */ SyscallSignalArg,
     );
     pub fn tkill(
         ctx: &mut SyscallContext,
@@ -121,7 +293,7 @@ impl SyscallHandler {
         /* rv */ std::ffi::c_int,
         /* tgid */ linux_api::posix_types::kernel_pid_t,
         /* pid */ linux_api::posix_types::kernel_pid_t,
-        /* sig */ std::ffi::c_int,
+        /* sig */ SyscallSignalArg,
     );
     pub fn tgkill(
         ctx: &mut SyscallContext,
@@ -170,7 +342,7 @@ impl SyscallHandler {
         }
 
         // need to scope the shmem lock since `wakeup_for_signal` below takes its own shmem lock
-        let mut cond = {
+        let cond = {
             let shmem_lock = &*objs.host.shim_shmem_lock_borrow().unwrap();
 
             let target_process = objs
@@ -219,23 +391,30 @@ impl SyscallHandler {
             if sender_tid == target_thread.id() {
                 // Target is the current thread. It'll be handled synchronously when the current
                 // syscall returns (if it's unblocked).
-                return Ok(());
-            }
-
-            if thread_protected.blocked_signals.has(signal) {
+                None
+            } else if thread_protected.blocked_signals.has(signal) {
                 // Target thread has the signal blocked. We'll leave it pending, but no need to
                 // schedule an event to process the signal. It'll get processed synchronously when
                 // the thread executes a syscall that would unblock the signal.
-                return Ok(());
+                None
+            } else {
+                // We may get `None` here if a thread is signalled before it runs for the first
+                // time. The signal will be delivered when the thread runs.
+                target_thread.syscall_condition_mut()
             }
+        };
 
-            let Some(cond) = target_thread.syscall_condition_mut() else {
-                // We may be able to get here if a thread is signalled before it runs for the first
-                // time. Just return; the signal will be delivered when the thread runs.
-                return Ok(());
-            };
+        // The signal is now pending, so any signalfds watching for it may have become readable.
+        objs.host
+            .process_borrow(target_thread.process_id())
+            .unwrap()
+            .borrow(objs.host.root())
+            .borrow_as_runnable()
+            .unwrap()
+            .notify_signalfds(signal);
 
-            cond
+        let Some(mut cond) = cond else {
+            return Ok(());
         };
 
         let was_scheduled = cond.wakeup_for_signal(objs.host, signal);
@@ -250,7 +429,7 @@ impl SyscallHandler {
     log_syscall!(
         rt_sigaction,
         /* rv */ std::ffi::c_int,
-        /* sig */ std::ffi::c_int,
+        /* sig */ SyscallSignalArg,
         /* act */ *const std::ffi::c_void,
         /* oact */ *const std::ffi::c_void,
         /* sigsetsize */ libc::size_t,
@@ -267,12 +446,44 @@ impl SyscallHandler {
         Ok(())
     }
 
+    log_syscall!(
+        rt_sigpending,
+        /* rv */ std::ffi::c_int,
+        /* uset */ *const linux_api::signal::sigset_t,
+        /* sigsetsize */ libc::size_t,
+    );
+    pub fn rt_sigpending(
+        ctx: &mut SyscallContext,
+        uset: ForeignPtr<sigset_t>,
+        sigsetsize: libc::size_t,
+    ) -> Result<(), SyscallError> {
+        if sigsetsize > std::mem::size_of::<sigset_t>() {
+            return Err(Errno::EINVAL.into());
+        }
+
+        let pending = {
+            let host_shmem = ctx.objs.host.shim_shmem_lock_borrow().unwrap();
+            let thread_shmem = ctx.objs.thread.shmem().protected.borrow(&host_shmem.root);
+            let process_shmem = ctx.objs.process.shmem();
+            let process_shmem = process_shmem.protected.borrow(&host_shmem.root);
+
+            // sigpending(2): "returns the set of signals that are pending for delivery to the
+            // calling thread (i.e., the signals which have been raised while blocked)".
+            (thread_shmem.pending_signals | process_shmem.pending_signals)
+                & thread_shmem.blocked_signals
+        };
+
+        // Like linux, only write `sigsetsize` bytes.
+        write_partial(&mut ctx.objs.memory_mut(), &pending, uset, sigsetsize)?;
+        Ok(())
+    }
+
     log_syscall!(
         rt_sigprocmask,
         /* rv */ std::ffi::c_int,
         /* how */ std::ffi::c_int,
-        /* nset */ *const std::ffi::c_void,
-        /* oset */ *const std::ffi::c_void,
+        /* nset */ *const linux_api::signal::sigset_t,
+        /* oset */ *const linux_api::signal::sigset_t,
         /* sigsetsize */ libc::size_t,
     );
     pub fn rt_sigprocmask(
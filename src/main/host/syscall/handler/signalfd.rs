@@ -0,0 +1,152 @@
+use std::sync::Arc;
+
+use atomic_refcell::AtomicRefCell;
+use linux_api::errno::Errno;
+use linux_api::fcntl::DescriptorFlags;
+use linux_api::signal::sigset_t;
+use nix::sys::signalfd::SfdFlags;
+use shadow_shim_helper_rs::syscall_types::ForeignPtr;
+
+use crate::host::descriptor::descriptor_table::DescriptorHandle;
+use crate::host::descriptor::signalfd::SignalFd;
+use crate::host::descriptor::{CompatFile, Descriptor, File, FileStatus, OpenFile};
+use crate::host::syscall::handler::{SyscallContext, SyscallHandler, ThreadContext};
+use crate::host::syscall::types::SyscallError;
+use crate::utility::callback_queue::CallbackQueue;
+
+impl SyscallHandler {
+    log_syscall!(
+        signalfd,
+        /* rv */ std::ffi::c_int,
+        /* fd */ std::ffi::c_int,
+        /* mask */ *const linux_api::signal::sigset_t,
+        /* sizemask */ linux_api::posix_types::kernel_size_t,
+    );
+    pub fn signalfd(
+        ctx: &mut SyscallContext,
+        fd: std::ffi::c_int,
+        mask_ptr: ForeignPtr<sigset_t>,
+        sizemask: linux_api::posix_types::kernel_size_t,
+    ) -> Result<DescriptorHandle, SyscallError> {
+        Self::signalfd_helper(ctx, fd, mask_ptr, sizemask, 0)
+    }
+
+    log_syscall!(
+        signalfd4,
+        /* rv */ std::ffi::c_int,
+        /* fd */ std::ffi::c_int,
+        /* mask */ *const linux_api::signal::sigset_t,
+        /* sizemask */ linux_api::posix_types::kernel_size_t,
+        /* flags */ nix::sys::signalfd::SfdFlags,
+    );
+    pub fn signalfd4(
+        ctx: &mut SyscallContext,
+        fd: std::ffi::c_int,
+        mask_ptr: ForeignPtr<sigset_t>,
+        sizemask: linux_api::posix_types::kernel_size_t,
+        flags: std::ffi::c_int,
+    ) -> Result<DescriptorHandle, SyscallError> {
+        Self::signalfd_helper(ctx, fd, mask_ptr, sizemask, flags)
+    }
+
+    fn signalfd_helper(
+        ctx: &mut SyscallContext,
+        fd: std::ffi::c_int,
+        mask_ptr: ForeignPtr<sigset_t>,
+        sizemask: linux_api::posix_types::kernel_size_t,
+        flags: std::ffi::c_int,
+    ) -> Result<DescriptorHandle, SyscallError> {
+        // The kernel requires the size of its own sigset type.
+        if sizemask != std::mem::size_of::<sigset_t>() as linux_api::posix_types::kernel_size_t {
+            log::debug!("Invalid signalfd sizemask: {sizemask}");
+            return Err(Errno::EINVAL.into());
+        }
+
+        let Some(flags) = SfdFlags::from_bits(flags) else {
+            log::debug!("Invalid signalfd flags: {flags}");
+            return Err(Errno::EINVAL.into());
+        };
+
+        let mask: sigset_t = ctx.objs.memory().read(mask_ptr)?;
+        let pending = pending_signals(ctx.objs);
+
+        // signalfd(2): "If the fd argument is not -1, then it must specify a valid existing
+        // signalfd file descriptor, and mask is used to replace the signal set associated with
+        // that file descriptor."
+        if fd != -1 {
+            let file = {
+                let desc_table = ctx.objs.thread.descriptor_table_borrow(ctx.objs.host);
+                let desc = Self::get_descriptor(&desc_table, fd)?;
+                let CompatFile::New(file) = desc.file() else {
+                    return Err(Errno::EINVAL.into());
+                };
+                file.inner_file().clone()
+            };
+
+            let File::SignalFd(ref signalfd) = file else {
+                return Err(Errno::EINVAL.into());
+            };
+
+            CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+                signalfd.borrow_mut().set_mask(mask, pending, cb_queue);
+            });
+
+            return Ok(DescriptorHandle::try_from(fd).unwrap());
+        }
+
+        let mut file_flags = FileStatus::empty();
+        let mut desc_flags = DescriptorFlags::empty();
+
+        if flags.contains(SfdFlags::SFD_NONBLOCK) {
+            file_flags.insert(FileStatus::NONBLOCK);
+        }
+
+        if flags.contains(SfdFlags::SFD_CLOEXEC) {
+            desc_flags.insert(DescriptorFlags::FD_CLOEXEC);
+        }
+
+        let file = Arc::new(AtomicRefCell::new(SignalFd::new(mask, file_flags)));
+        CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+            file.borrow_mut().refresh_pending(pending, cb_queue);
+        });
+
+        // Signals sent to this process after this point will update the signalfd's readable state.
+        ctx.objs
+            .process
+            .borrow_as_runnable()
+            .unwrap()
+            .add_signalfd(&file);
+
+        let mut desc = Descriptor::new(CompatFile::New(OpenFile::new(File::SignalFd(file))));
+        desc.set_flags(desc_flags);
+
+        let fd = ctx
+            .objs
+            .thread
+            .descriptor_table_borrow_mut(ctx.objs.host)
+            .register_descriptor(desc)
+            .or(Err(Errno::EMFILE))?;
+
+        log::trace!("signalfd() returning fd {fd}");
+
+        Ok(fd)
+    }
+}
+
+/// The signals that are currently pending for the calling thread or its process.
+fn pending_signals(objs: &ThreadContext) -> sigset_t {
+    let host_shmem = objs.host.shim_shmem_lock_borrow().unwrap();
+    let process_pending = objs
+        .process
+        .shmem()
+        .protected
+        .borrow(&host_shmem.root)
+        .pending_signals;
+    let thread_pending = objs
+        .thread
+        .shmem()
+        .protected
+        .borrow(&host_shmem.root)
+        .pending_signals;
+    process_pending | thread_pending
+}
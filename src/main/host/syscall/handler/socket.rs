@@ -135,7 +135,7 @@ impl SyscallHandler {
             .thread
             .descriptor_table_borrow_mut(ctx.objs.host)
             .register_descriptor(desc)
-            .or(Err(Errno::ENFILE))?;
+            .or(Err(Errno::EMFILE))?;
 
         log::trace!("Created socket fd {fd}");
 
@@ -172,7 +172,7 @@ impl SyscallHandler {
             return Err(Errno::ENOTSOCK.into());
         };
 
-        let addr = io::read_sockaddr(&ctx.objs.process.memory_borrow(), addr_ptr, addr_len)?;
+        let addr = io::read_sockaddr(&ctx.objs.memory(), addr_ptr, addr_len)?;
 
         log::trace!("Attempting to bind fd {} to {:?}", fd, addr);
 
@@ -227,7 +227,7 @@ impl SyscallHandler {
             return Err(Errno::ENOTSOCK.into());
         };
 
-        let mut mem = ctx.objs.process.memory_borrow_mut();
+        let mut mem = ctx.objs.memory_mut();
         let mut rng = ctx.objs.host.random_mut();
         let net_ns = ctx.objs.host.network_namespace_borrow();
 
@@ -304,7 +304,7 @@ impl SyscallHandler {
             return Err(Errno::ENOTSOCK.into());
         };
 
-        let mut mem = ctx.objs.process.memory_borrow_mut();
+        let mut mem = ctx.objs.memory_mut();
         let mut rng = ctx.objs.host.random_mut();
         let net_ns = ctx.objs.host.network_namespace_borrow();
 
@@ -380,7 +380,7 @@ impl SyscallHandler {
             return Err(Errno::ENOTSOCK.into());
         };
 
-        let mut mem = ctx.objs.process.memory_borrow_mut();
+        let mut mem = ctx.objs.memory_mut();
 
         log::trace!("Attempting to recv {} bytes", buf_len);
 
@@ -461,7 +461,7 @@ impl SyscallHandler {
             return Err(Errno::ENOTSOCK.into());
         };
 
-        let mut mem = ctx.objs.process.memory_borrow_mut();
+        let mut mem = ctx.objs.memory_mut();
 
         let mut msg = io::read_msghdr(&mem, msg_ptr)?;
 
@@ -542,7 +542,7 @@ impl SyscallHandler {
 
         debug!("Returning socket address of {:?}", addr_to_write);
         io::write_sockaddr_and_len(
-            &mut ctx.objs.process.memory_borrow_mut(),
+            &mut ctx.objs.memory_mut(),
             addr_to_write.as_ref(),
             addr_ptr,
             addr_len_ptr,
@@ -591,7 +591,7 @@ impl SyscallHandler {
 
         debug!("Returning peer address of {:?}", addr_to_write);
         io::write_sockaddr_and_len(
-            &mut ctx.objs.process.memory_borrow_mut(),
+            &mut ctx.objs.memory_mut(),
             addr_to_write.as_ref(),
             addr_ptr,
             addr_len_ptr,
@@ -697,6 +697,11 @@ impl SyscallHandler {
         addr_len_ptr: ForeignPtr<libc::socklen_t>,
         flags: std::ffi::c_int,
     ) -> Result<DescriptorHandle, SyscallError> {
+        // linux checks the flags before looking up the fd
+        if flags & !(libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC) != 0 {
+            return Err(Errno::EINVAL.into());
+        }
+
         // if we were previously blocked, get the active file from the last syscall handler
         // invocation since it may no longer exist in the descriptor table
         let file = ctx
@@ -743,15 +748,8 @@ impl SyscallHandler {
             return Err(Errno::ENOTSOCK.into());
         };
 
-        // get the accept flags
-        let flags = match SockFlag::from_bits(flags) {
-            Some(x) => x,
-            None => {
-                // linux doesn't return an error if there are unexpected flags
-                warn!("Invalid recvfrom flags: {}", flags);
-                SockFlag::from_bits_truncate(flags)
-            }
-        };
+        // get the accept flags; accept4() has already returned EINVAL for any unknown flags
+        let flags = SockFlag::from_bits_truncate(flags);
 
         let mut rng = ctx.objs.host.random_mut();
         let net_ns = ctx.objs.host.network_namespace_borrow();
@@ -784,7 +782,7 @@ impl SyscallHandler {
 
         if !addr_ptr.is_null() {
             io::write_sockaddr_and_len(
-                &mut ctx.objs.process.memory_borrow_mut(),
+                &mut ctx.objs.memory_mut(),
                 from_addr.as_ref(),
                 addr_ptr,
                 addr_len_ptr,
@@ -809,7 +807,7 @@ impl SyscallHandler {
             .thread
             .descriptor_table_borrow_mut(ctx.objs.host)
             .register_descriptor(new_desc)
-            .or(Err(Errno::ENFILE))?)
+            .or(Err(Errno::EMFILE))?)
     }
 
     log_syscall!(
@@ -852,8 +850,8 @@ impl SyscallHandler {
             return Err(Errno::ENOTSOCK.into());
         };
 
-        let addr = io::read_sockaddr(&ctx.objs.process.memory_borrow(), addr_ptr, addr_len)?
-            .ok_or(Errno::EFAULT)?;
+        let addr =
+            io::read_sockaddr(&ctx.objs.memory(), addr_ptr, addr_len)?.ok_or(Errno::EFAULT)?;
 
         let mut rng = ctx.objs.host.random_mut();
         let net_ns = ctx.objs.host.network_namespace_borrow();
@@ -978,33 +976,8 @@ impl SyscallHandler {
         desc_1.set_flags(descriptor_flags);
         desc_2.set_flags(descriptor_flags);
 
-        // register the file descriptors
-        let mut dt = ctx.objs.thread.descriptor_table_borrow_mut(ctx.objs.host);
-        // unwrap here since the error handling would be messy (need to deregister) and we shouldn't
-        // ever need to worry about this in practice
-        let fd_1 = dt.register_descriptor(desc_1).unwrap();
-        let fd_2 = dt.register_descriptor(desc_2).unwrap();
-
-        // try to write them to the caller
-        let fds = [i32::from(fd_1), i32::from(fd_2)];
-        let write_res = ctx.objs.process.memory_borrow_mut().write(fd_ptr, &fds);
-
-        // clean up in case of error
-        match write_res {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
-                    // ignore any errors when closing
-                    dt.deregister_descriptor(fd_1)
-                        .unwrap()
-                        .close(ctx.objs.host, cb_queue);
-                    dt.deregister_descriptor(fd_2)
-                        .unwrap()
-                        .close(ctx.objs.host, cb_queue);
-                });
-                Err(e.into())
-            }
-        }
+        // register the file descriptors and write them to the caller
+        Self::register_descriptor_pair(ctx, desc_1, desc_2, fd_ptr)
     }
 
     log_syscall!(
@@ -1037,7 +1010,7 @@ impl SyscallHandler {
             return Err(Errno::ENOTSOCK.into());
         };
 
-        let mut mem = ctx.objs.process.memory_borrow_mut();
+        let mut mem = ctx.objs.memory_mut();
 
         // get the provided optlen
         let optlen = mem.read(optlen_ptr)?;
@@ -1094,7 +1067,7 @@ impl SyscallHandler {
             return Err(Errno::ENOTSOCK.into());
         };
 
-        let mem = ctx.objs.process.memory_borrow();
+        let mem = ctx.objs.memory();
 
         socket
             .borrow_mut()
@@ -3,10 +3,19 @@ use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 use crate::cshadow;
 use crate::host::descriptor::CompatFile;
 use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
+use crate::host::syscall::type_formatting::SyscallStringArg;
 use crate::host::syscall::types::{SyscallError, SyscallResult};
 
 impl SyscallHandler {
-    log_syscall!(statx, /* rv */ std::ffi::c_int);
+    log_syscall!(
+        statx,
+        /* rv */ std::ffi::c_int,
+        /* dirfd */ std::ffi::c_int,
+        /* pathname */ SyscallStringArg,
+        /* flags */ std::ffi::c_int,
+        /* mask */ std::ffi::c_uint,
+        /* statxbuf */ *const libc::statx,
+    );
     pub fn statx(ctx: &mut SyscallContext) -> SyscallResult {
         Self::legacy_syscall(cshadow::syscallhandler_statx, ctx)
     }
@@ -36,10 +45,7 @@ impl SyscallHandler {
 
         let stat = file.inner_file().borrow().stat()?;
 
-        ctx.objs
-            .process
-            .memory_borrow_mut()
-            .write(statbuf_ptr, &stat)?;
+        ctx.objs.memory_mut().write(statbuf_ptr, &stat)?;
 
         Ok(())
     }
@@ -49,7 +55,14 @@ impl SyscallHandler {
         Self::legacy_syscall(cshadow::syscallhandler_fstatfs, ctx)
     }
 
-    log_syscall!(newfstatat, /* rv */ std::ffi::c_int);
+    log_syscall!(
+        newfstatat,
+        /* rv */ std::ffi::c_int,
+        /* dirfd */ std::ffi::c_int,
+        /* pathname */ SyscallStringArg,
+        /* statbuf */ *const linux_api::stat::stat,
+        /* flags */ std::ffi::c_int,
+    );
     pub fn newfstatat(ctx: &mut SyscallContext) -> SyscallResult {
         Self::legacy_syscall(cshadow::syscallhandler_newfstatat, ctx)
     }
@@ -45,10 +45,7 @@ impl SyscallHandler {
         info.mem_unit = 1024 * 1024 * 1024; // GiB
 
         // Write the result to plugin memory.
-        ctx.objs
-            .process
-            .memory_borrow_mut()
-            .write(info_ptr, &info)?;
+        ctx.objs.memory_mut().write(info_ptr, &info)?;
         Ok(())
     }
 }
@@ -1,4 +1,5 @@
 use linux_api::errno::Errno;
+use linux_api::signal::{sigval, Signal};
 use linux_api::time::{ClockId, ClockNanosleepFlags, ITimerId};
 use log::*;
 use shadow_shim_helper_rs::emulated_time::EmulatedTime;
@@ -6,10 +7,33 @@ use shadow_shim_helper_rs::simulation_time::SimulationTime;
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
 use crate::core::worker::Worker;
+use crate::host::posix_timer::{PosixTimer, PosixTimerNotify};
 use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
 use crate::host::syscall::types::SyscallError;
+use crate::host::thread::ThreadId;
 use crate::host::timer::Timer;
 
+fn itimerspec_from_timer(timer: &Timer) -> linux_api::time::itimerspec {
+    linux_api::time::itimerspec {
+        it_interval: timer
+            .expire_interval()
+            .unwrap_or(SimulationTime::ZERO)
+            .try_into()
+            .unwrap(),
+        it_value: timer
+            .remaining_time()
+            .unwrap_or(SimulationTime::ZERO)
+            .try_into()
+            .unwrap(),
+    }
+}
+
+/// Validate the signal number of a timer's `sigevent`. Both standard and realtime signals are
+/// supported; e.g. glibc implements `SIGEV_THREAD` with a thread-directed `SIGRTMIN`.
+fn timer_signal(signo: std::ffi::c_int) -> Result<Signal, Errno> {
+    Signal::try_from(signo).map_err(|_| Errno::EINVAL)
+}
+
 fn itimerval_from_timer(timer: &Timer) -> linux_api::time::itimerval {
     linux_api::time::itimerval {
         it_interval: timer
@@ -48,10 +72,7 @@ impl SyscallHandler {
         }
 
         let itimerval = itimerval_from_timer(&ctx.objs.process.realtime_timer_borrow());
-        ctx.objs
-            .process
-            .memory_borrow_mut()
-            .write(curr_value_ptr, &itimerval)?;
+        ctx.objs.memory_mut().write(curr_value_ptr, &itimerval)?;
 
         Ok(())
     }
@@ -81,13 +102,10 @@ impl SyscallHandler {
 
         if !old_value_ptr.is_null() {
             let itimerval = itimerval_from_timer(&ctx.objs.process.realtime_timer_borrow());
-            ctx.objs
-                .process
-                .memory_borrow_mut()
-                .write(old_value_ptr, &itimerval)?;
+            ctx.objs.memory_mut().write(old_value_ptr, &itimerval)?;
         }
 
-        let new_value = ctx.objs.process.memory_borrow().read(new_value_ptr)?;
+        let new_value = ctx.objs.memory().read(new_value_ptr)?;
         let new_value_value =
             SimulationTime::try_from(new_value.it_value).map_err(|_| Errno::EINVAL)?;
         let new_value_interval =
@@ -162,6 +180,282 @@ impl SyscallHandler {
         Ok(prev_remaining_secs)
     }
 
+    log_syscall!(
+        timer_create,
+        /* rv */ std::ffi::c_int,
+        /* clockid */ linux_api::time::ClockId,
+        /* sevp */ *const std::ffi::c_void,
+        /* timerid */ *const std::ffi::c_void,
+    );
+    pub fn timer_create(
+        ctx: &mut SyscallContext,
+        clockid: linux_api::time::linux___kernel_clockid_t,
+        sevp: ForeignPtr<libc::sigevent>,
+        timerid_ptr: ForeignPtr<std::ffi::c_int>,
+    ) -> Result<(), SyscallError> {
+        let clockid = ClockId::try_from(clockid).map_err(|_| Errno::EINVAL)?;
+
+        // All of these clocks are emulated as the simulation time.
+        if ![
+            ClockId::CLOCK_REALTIME,
+            ClockId::CLOCK_MONOTONIC,
+            ClockId::CLOCK_BOOTTIME,
+            ClockId::CLOCK_TAI,
+        ]
+        .contains(&clockid)
+        {
+            warn_once_then_debug!("Clock id {clockid:?} unsupported for timer_create");
+            return Err(Errno::ENOTSUP.into());
+        }
+
+        let runnable = ctx.objs.process.borrow_as_runnable().unwrap();
+
+        // Use the lowest unused timer id.
+        let timer_id = {
+            let timers = runnable.posix_timers_borrow();
+            (0..).find(|id| !timers.contains_key(id)).unwrap()
+        };
+
+        let notify = if sevp.is_null() {
+            // timer_create(2): "Specifying sevp as NULL is equivalent to specifying a pointer to
+            // a sigevent structure in which sigev_notify is SIGEV_SIGNAL, sigev_signo is SIGALRM,
+            // and sigev_value.sival_int is the timer ID."
+            PosixTimerNotify::Signal {
+                signal: Signal::SIGALRM,
+                value: sigval {
+                    sival_int: timer_id,
+                },
+            }
+        } else {
+            let sev = ctx.objs.memory().read(sevp)?;
+            let value = sigval {
+                sival_ptr: sev.sigev_value.sival_ptr,
+            };
+
+            match sev.sigev_notify {
+                libc::SIGEV_NONE => PosixTimerNotify::None,
+                libc::SIGEV_SIGNAL => PosixTimerNotify::Signal {
+                    signal: timer_signal(sev.sigev_signo)?,
+                    value,
+                },
+                libc::SIGEV_THREAD_ID => {
+                    // timer_create(2): "The thread ID (tid) must be a thread in the same process
+                    // as the caller."
+                    let tid = ThreadId::try_from(sev.sigev_notify_thread_id)
+                        .map_err(|_| Errno::EINVAL)?;
+                    if runnable.thread_borrow(tid).is_none() {
+                        return Err(Errno::EINVAL.into());
+                    }
+                    PosixTimerNotify::ThreadSignal {
+                        tid,
+                        signal: timer_signal(sev.sigev_signo)?,
+                        value,
+                    }
+                }
+                // SIGEV_THREAD is implemented in libc on top of SIGEV_THREAD_ID.
+                notify => {
+                    debug!("Invalid sigev_notify {notify}");
+                    return Err(Errno::EINVAL.into());
+                }
+            }
+        };
+
+        ctx.objs.memory_mut().write(timerid_ptr, &timer_id)?;
+
+        runnable.posix_timers_borrow_mut().insert(
+            timer_id,
+            PosixTimer::new(ctx.objs.process.id(), timer_id, notify),
+        );
+
+        Ok(())
+    }
+
+    log_syscall!(
+        timer_settime,
+        /* rv */ std::ffi::c_int,
+        /* timerid */ std::ffi::c_int,
+        /* flags */ std::ffi::c_int,
+        /* new_value */ *const std::ffi::c_void,
+        /* old_value */ *const std::ffi::c_void,
+    );
+    pub fn timer_settime(
+        ctx: &mut SyscallContext,
+        timer_id: std::ffi::c_int,
+        flags: std::ffi::c_int,
+        new_value_ptr: ForeignPtr<linux_api::time::itimerspec>,
+        old_value_ptr: ForeignPtr<linux_api::time::itimerspec>,
+    ) -> Result<(), SyscallError> {
+        let runnable = ctx.objs.process.borrow_as_runnable().unwrap();
+        let mut timers = runnable.posix_timers_borrow_mut();
+        let Some(posix_timer) = timers.get_mut(&timer_id) else {
+            return Err(Errno::EINVAL.into());
+        };
+
+        let new_value = ctx.objs.memory().read(new_value_ptr)?;
+        let value = SimulationTime::try_from(new_value.it_value).or(Err(Errno::EINVAL))?;
+        let interval = SimulationTime::try_from(new_value.it_interval).or(Err(Errno::EINVAL))?;
+
+        if !old_value_ptr.is_null() {
+            let old_value = itimerspec_from_timer(posix_timer.timer());
+            ctx.objs.memory_mut().write(old_value_ptr, &old_value)?;
+        }
+
+        if value.is_zero() {
+            posix_timer.timer_mut().disarm();
+            return Ok(());
+        }
+
+        let now = Worker::current_time().unwrap();
+
+        // Shadow emulates all of the supported clocks as time since the unix epoch.
+        let base = if flags & libc::TIMER_ABSTIME != 0 {
+            EmulatedTime::UNIX_EPOCH
+        } else {
+            now
+        };
+
+        // timer_settime(2): "If the specified absolute time has already passed, then the timer
+        // expires immediately, and the overrun count will be set correctly."
+        posix_timer.timer_mut().arm(
+            ctx.objs.host,
+            EmulatedTime::max(base + value, now),
+            interval.is_positive().then_some(interval),
+        );
+
+        Ok(())
+    }
+
+    log_syscall!(
+        timer_gettime,
+        /* rv */ std::ffi::c_int,
+        /* timerid */ std::ffi::c_int,
+        /* curr_value */ *const std::ffi::c_void,
+    );
+    pub fn timer_gettime(
+        ctx: &mut SyscallContext,
+        timer_id: std::ffi::c_int,
+        curr_value_ptr: ForeignPtr<linux_api::time::itimerspec>,
+    ) -> Result<(), SyscallError> {
+        let runnable = ctx.objs.process.borrow_as_runnable().unwrap();
+        let timers = runnable.posix_timers_borrow();
+        let Some(posix_timer) = timers.get(&timer_id) else {
+            return Err(Errno::EINVAL.into());
+        };
+
+        let curr_value = itimerspec_from_timer(posix_timer.timer());
+        ctx.objs.memory_mut().write(curr_value_ptr, &curr_value)?;
+
+        Ok(())
+    }
+
+    log_syscall!(
+        timer_getoverrun,
+        /* rv */ std::ffi::c_int,
+        /* timerid */ std::ffi::c_int,
+    );
+    pub fn timer_getoverrun(
+        ctx: &mut SyscallContext,
+        timer_id: std::ffi::c_int,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        let runnable = ctx.objs.process.borrow_as_runnable().unwrap();
+        let timers = runnable.posix_timers_borrow();
+        let Some(posix_timer) = timers.get(&timer_id) else {
+            return Err(Errno::EINVAL.into());
+        };
+
+        Ok(posix_timer.overrun())
+    }
+
+    log_syscall!(
+        timer_delete,
+        /* rv */ std::ffi::c_int,
+        /* timerid */ std::ffi::c_int,
+    );
+    pub fn timer_delete(
+        ctx: &mut SyscallContext,
+        timer_id: std::ffi::c_int,
+    ) -> Result<(), SyscallError> {
+        let runnable = ctx.objs.process.borrow_as_runnable().unwrap();
+        // Dropping the timer cancels any pending expirations.
+        if runnable
+            .posix_timers_borrow_mut()
+            .remove(&timer_id)
+            .is_none()
+        {
+            return Err(Errno::EINVAL.into());
+        }
+
+        Ok(())
+    }
+
+    log_syscall!(
+        adjtimex,
+        /* rv */ std::ffi::c_int,
+        /* buf */ *const std::ffi::c_void,
+    );
+    pub fn adjtimex(
+        ctx: &mut SyscallContext,
+        buf_ptr: ForeignPtr<libc::timex>,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        Self::adjtimex_helper(ctx, buf_ptr)
+    }
+
+    log_syscall!(
+        clock_adjtime,
+        /* rv */ std::ffi::c_int,
+        /* clock_id */ linux_api::time::ClockId,
+        /* buf */ *const std::ffi::c_void,
+    );
+    pub fn clock_adjtime(
+        ctx: &mut SyscallContext,
+        clock_id: linux_api::time::linux___kernel_clockid_t,
+        buf_ptr: ForeignPtr<libc::timex>,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        let clock_id = ClockId::try_from(clock_id).map_err(|_| Errno::EINVAL)?;
+
+        // Linux only supports adjusting the realtime clock.
+        if clock_id != ClockId::CLOCK_REALTIME {
+            debug!("Clock id {clock_id:?} unsupported for clock_adjtime");
+            return Err(Errno::EOPNOTSUPP.into());
+        }
+
+        Self::adjtimex_helper(ctx, buf_ptr)
+    }
+
+    fn adjtimex_helper(
+        ctx: &mut SyscallContext,
+        buf_ptr: ForeignPtr<libc::timex>,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        let buf = ctx.objs.memory().read(buf_ptr)?;
+
+        // The emulated clock can't be adjusted, so we only support queries. adjtimex(2): "EPERM
+        // buf.modes is neither 0 nor ADJ_OFFSET_SS_READ, and the caller does not have sufficient
+        // privilege."
+        if buf.modes != 0 && buf.modes != libc::ADJ_OFFSET_SS_READ {
+            warn_once_then_debug!("Attempted to adjust the clock with modes {:#x}", buf.modes);
+            return Err(Errno::EPERM.into());
+        }
+
+        let now = Worker::current_time().unwrap();
+        let since_epoch = now.duration_since(&EmulatedTime::UNIX_EPOCH);
+
+        // The emulated clock is perfectly synchronized, so there's never an offset or frequency
+        // error to report.
+        let mut result: libc::timex = shadow_pod::zeroed();
+        result.modes = buf.modes;
+        result.time = libc::timeval {
+            tv_sec: since_epoch.as_secs().try_into().unwrap(),
+            tv_usec: since_epoch.subsec_micros().into(),
+        };
+        // The kernel's default for USER_HZ=100.
+        result.tick = 10_000;
+        result.precision = 1;
+
+        ctx.objs.memory_mut().write(buf_ptr, &result)?;
+
+        Ok(libc::TIME_OK)
+    }
+
     log_syscall!(
         clock_getres,
         /* rv */ std::ffi::c_int,
@@ -179,10 +473,7 @@ impl SyscallHandler {
         // All clocks have nanosecond resolution.
         if !res_ptr.is_null() {
             let res_time = linux_api::time::timespec::try_from(SimulationTime::NANOSECOND).unwrap();
-            ctx.objs
-                .process
-                .memory_borrow_mut()
-                .write(res_ptr, &res_time)?;
+            ctx.objs.memory_mut().write(res_ptr, &res_time)?;
         }
 
         Ok(())
@@ -265,7 +556,7 @@ impl SyscallHandler {
         remain_ptr: ForeignPtr<linux_api::time::timespec>,
         allow_unspec_bitflags: bool,
     ) -> Result<(), SyscallError> {
-        let request = ctx.objs.process.memory_borrow().read(request_ptr)?;
+        let request = ctx.objs.memory().read(request_ptr)?;
         let request_time = SimulationTime::try_from(request).or(Err(Errno::EINVAL))?;
         let flags = if allow_unspec_bitflags {
             ClockNanosleepFlags::from_bits_truncate(flags)
@@ -304,10 +595,7 @@ impl SyscallHandler {
             if !remain_ptr.is_null() && !flags.contains(ClockNanosleepFlags::TIMER_ABSTIME) {
                 let remain_time =
                     linux_api::time::timespec::try_from(expected_wakeup_time - now).unwrap();
-                ctx.objs
-                    .process
-                    .memory_borrow_mut()
-                    .write(remain_ptr, &remain_time)?;
+                ctx.objs.memory_mut().write(remain_ptr, &remain_time)?;
             }
 
             // Encodes that we were interrupted but will return EINTR to the plugin.
@@ -65,7 +65,7 @@ impl SyscallHandler {
             .thread
             .descriptor_table_borrow_mut(ctx.objs.host)
             .register_descriptor(desc)
-            .or(Err(Errno::ENFILE))?;
+            .or(Err(Errno::EMFILE))?;
 
         log::trace!("timerfd_create() returning fd {fd}");
 
@@ -124,10 +124,7 @@ impl SyscallHandler {
         };
 
         // Write the result to the plugin.
-        ctx.objs
-            .process
-            .memory_borrow_mut()
-            .write(value_ptr, &result)?;
+        ctx.objs.memory_mut().write(value_ptr, &result)?;
 
         Ok(())
     }
@@ -159,7 +156,7 @@ impl SyscallHandler {
         };
 
         // Read in the new value from the plugin.
-        let new_value = ctx.objs.process.memory_borrow().read(new_value_ptr)?;
+        let new_value = ctx.objs.memory().read(new_value_ptr)?;
 
         // Verify a valid range for new_time nanosecond vals.
         let value = SimulationTime::try_from(new_value.it_value).or(Err(Errno::EINVAL))?;
@@ -183,6 +180,9 @@ impl SyscallHandler {
             let now = Worker::current_time().unwrap();
 
             let expire_time = {
+                // Absolute times are relative to the clock's epoch. Shadow emulates both
+                // `CLOCK_REALTIME` and `CLOCK_MONOTONIC` as the emulated time since the unix
+                // epoch, so the base is the same for either clock.
                 let base = match flags.contains(TimerSetTimeFlags::TFD_TIMER_ABSTIME) {
                     true => EmulatedTime::UNIX_EPOCH,
                     false => now,
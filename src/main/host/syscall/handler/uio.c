@@ -7,6 +7,7 @@
 
 #include <assert.h>
 #include <errno.h>
+#include <limits.h>
 #include <sys/syscall.h>
 #include <sys/uio.h>
 
@@ -57,11 +58,20 @@ static int _syscallhandler_validateVecParams(SyscallHandler* sys, int fd, Untype
         return -EFAULT;
     }
 
-    /* Check that all of the buf pointers are valid. */
+    /* Check that all of the buf pointers are valid, and that the total length
+     * fits in the ssize_t return value. */
+    size_t totalSize = 0;
     for (unsigned long i = 0; i < iovlen; i++) {
         UntypedForeignPtr bufPtr = (UntypedForeignPtr){.val = (uint64_t)iov[i].iov_base};
         size_t bufSize = iov[i].iov_len;
 
+        if (bufSize > SSIZE_MAX || totalSize > SSIZE_MAX - bufSize) {
+            debug("Total iovec length overflows ssize_t at iovec[%ld]", i);
+            free(iov);
+            return -EINVAL;
+        }
+        totalSize += bufSize;
+
         if (!bufPtr.val && bufSize != 0) {
             debug("Invalid NULL pointer in iovec[%ld]", i);
             free(iov);
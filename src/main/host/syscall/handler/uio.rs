@@ -9,6 +9,11 @@ use crate::host::syscall::io::{self, IoVec};
 use crate::host::syscall::types::{ForeignArrayPtr, SyscallError};
 use crate::utility::callback_queue::CallbackQueue;
 
+/// The `RWF_*` flags supported for `preadv2` and `pwritev2`. Shadow performs file I/O
+/// synchronously, so all except `RWF_NOWAIT` are no-ops.
+const RWF_SUPPORTED: std::ffi::c_int =
+    libc::RWF_HIPRI | libc::RWF_DSYNC | libc::RWF_SYNC | libc::RWF_NOWAIT;
+
 impl SyscallHandler {
     log_syscall!(
         readv,
@@ -52,7 +57,7 @@ impl SyscallHandler {
         let iov_count = iov_count.try_into().or(Err(Errno::EINVAL))?;
 
         let iovs = {
-            let mem = ctx.objs.process.memory_borrow_mut();
+            let mem = ctx.objs.memory_mut();
             io::read_iovecs(&mem, iov_ptr, iov_count)?
         };
         assert_eq!(iovs.len(), iov_count);
@@ -125,7 +130,7 @@ impl SyscallHandler {
         let iov_count = iov_count.try_into().or(Err(Errno::EINVAL))?;
 
         let iovs = {
-            let mem = ctx.objs.process.memory_borrow_mut();
+            let mem = ctx.objs.memory_mut();
             io::read_iovecs(&mem, iov_ptr, iov_count)?
         };
         assert_eq!(iovs.len(), iov_count);
@@ -181,7 +186,9 @@ impl SyscallHandler {
             // get the file from the descriptor table, or return early if it doesn't exist
             None => {
                 let desc_table = ctx.objs.thread.descriptor_table_borrow(ctx.objs.host);
-                match Self::get_descriptor(&desc_table, fd)?.file() {
+                let desc = Self::get_descriptor(&desc_table, fd)?;
+                Self::check_rwf_flags(flags)?;
+                match desc.file() {
                     CompatFile::New(file) => file.clone(),
                     // if it's a legacy file, use the C syscall handler instead
                     CompatFile::Legacy(_) => {
@@ -206,7 +213,7 @@ impl SyscallHandler {
         let iov_count = iov_count.try_into().or(Err(Errno::EINVAL))?;
 
         let iovs = {
-            let mem = ctx.objs.process.memory_borrow_mut();
+            let mem = ctx.objs.memory_mut();
             io::read_iovecs(&mem, iov_ptr, iov_count)?
         };
         assert_eq!(iovs.len(), iov_count);
@@ -224,6 +231,21 @@ impl SyscallHandler {
         Ok(bytes_read)
     }
 
+    /// Validate the `RWF_*` flags of a `preadv2` or `pwritev2` call.
+    fn check_rwf_flags(flags: std::ffi::c_int) -> Result<(), Errno> {
+        if flags & !RWF_SUPPORTED != 0 {
+            // preadv2(2): "EOPNOTSUPP An unknown flag is specified in flags."
+            if flags & libc::RWF_APPEND != 0 {
+                warn_once_then_debug!("RWF_APPEND is not supported");
+            } else {
+                log::debug!("Unsupported preadv2/pwritev2 flags: {flags:#x}");
+            }
+            return Err(Errno::EOPNOTSUPP);
+        }
+
+        Ok(())
+    }
+
     pub fn readv_helper(
         ctx: &mut SyscallContext,
         file: &File,
@@ -231,7 +253,7 @@ impl SyscallHandler {
         offset: Option<libc::off_t>,
         flags: std::ffi::c_int,
     ) -> Result<libc::ssize_t, SyscallError> {
-        let mut mem = ctx.objs.process.memory_borrow_mut();
+        let mut mem = ctx.objs.memory_mut();
 
         // if it's a socket, call recvmsg_helper() instead
         if let File::Socket(ref socket) = file {
@@ -251,7 +273,7 @@ impl SyscallHandler {
             let args = RecvmsgArgs {
                 iovs,
                 control_ptr: ForeignArrayPtr::new(ForeignPtr::null(), 0),
-                flags: 0,
+                flags: Self::rwf_to_msg_flags(flags),
             };
 
             // call the socket's recvmsg(), and run any resulting events
@@ -277,8 +299,12 @@ impl SyscallHandler {
                 )
             });
 
-        // if the syscall would block and it's a blocking descriptor
-        if result == Err(Errno::EWOULDBLOCK.into()) && !file_status.contains(FileStatus::NONBLOCK) {
+        // if the syscall would block, it's a blocking descriptor, and the caller didn't ask us
+        // not to wait
+        if result == Err(Errno::EWOULDBLOCK.into())
+            && !file_status.contains(FileStatus::NONBLOCK)
+            && flags & libc::RWF_NOWAIT == 0
+        {
             return Err(SyscallError::new_blocked_on_file(
                 file.clone(),
                 FileState::READABLE,
@@ -289,6 +315,15 @@ impl SyscallHandler {
         result
     }
 
+    /// The socket message flags equivalent to the `RWF_*` flags of a `preadv2` or `pwritev2` call.
+    fn rwf_to_msg_flags(flags: std::ffi::c_int) -> std::ffi::c_int {
+        if flags & libc::RWF_NOWAIT != 0 {
+            libc::MSG_DONTWAIT
+        } else {
+            0
+        }
+    }
+
     log_syscall!(
         writev,
         /* rv */ libc::ssize_t,
@@ -331,7 +366,7 @@ impl SyscallHandler {
         let iov_count = iov_count.try_into().or(Err(Errno::EINVAL))?;
 
         let iovs = {
-            let mem = ctx.objs.process.memory_borrow_mut();
+            let mem = ctx.objs.memory_mut();
             io::read_iovecs(&mem, iov_ptr, iov_count)?
         };
         assert_eq!(iovs.len(), iov_count);
@@ -404,7 +439,7 @@ impl SyscallHandler {
         let iov_count = iov_count.try_into().or(Err(Errno::EINVAL))?;
 
         let iovs = {
-            let mem = ctx.objs.process.memory_borrow_mut();
+            let mem = ctx.objs.memory_mut();
             io::read_iovecs(&mem, iov_ptr, iov_count)?
         };
         assert_eq!(iovs.len(), iov_count);
@@ -460,7 +495,9 @@ impl SyscallHandler {
             // get the file from the descriptor table, or return early if it doesn't exist
             None => {
                 let desc_table = ctx.objs.thread.descriptor_table_borrow(ctx.objs.host);
-                match Self::get_descriptor(&desc_table, fd)?.file() {
+                let desc = Self::get_descriptor(&desc_table, fd)?;
+                Self::check_rwf_flags(flags)?;
+                match desc.file() {
                     CompatFile::New(file) => file.clone(),
                     // if it's a legacy file, use the C syscall handler instead
                     CompatFile::Legacy(_) => {
@@ -485,7 +522,7 @@ impl SyscallHandler {
         let iov_count = iov_count.try_into().or(Err(Errno::EINVAL))?;
 
         let iovs = {
-            let mem = ctx.objs.process.memory_borrow_mut();
+            let mem = ctx.objs.memory_mut();
             io::read_iovecs(&mem, iov_ptr, iov_count)?
         };
         assert_eq!(iovs.len(), iov_count);
@@ -510,7 +547,7 @@ impl SyscallHandler {
         offset: Option<libc::off_t>,
         flags: std::ffi::c_int,
     ) -> Result<libc::ssize_t, SyscallError> {
-        let mut mem = ctx.objs.process.memory_borrow_mut();
+        let mut mem = ctx.objs.memory_mut();
         let mut rng = ctx.objs.host.random_mut();
         let net_ns = ctx.objs.host.network_namespace_borrow();
 
@@ -525,7 +562,7 @@ impl SyscallHandler {
                 addr: None,
                 iovs,
                 control_ptr: ForeignArrayPtr::new(ForeignPtr::null(), 0),
-                flags: 0,
+                flags: Self::rwf_to_msg_flags(flags),
             };
 
             // call the socket's sendmsg(), and run any resulting events
@@ -550,8 +587,12 @@ impl SyscallHandler {
                 )
             });
 
-        // if the syscall would block and it's a blocking descriptor
-        if result == Err(Errno::EWOULDBLOCK.into()) && !file_status.contains(FileStatus::NONBLOCK) {
+        // if the syscall would block, it's a blocking descriptor, and the caller didn't ask us
+        // not to wait
+        if result == Err(Errno::EWOULDBLOCK.into())
+            && !file_status.contains(FileStatus::NONBLOCK)
+            && flags & libc::RWF_NOWAIT == 0
+        {
             return Err(SyscallError::new_blocked_on_file(
                 file.clone(),
                 FileState::WRITABLE,
@@ -561,4 +602,197 @@ impl SyscallHandler {
 
         result
     }
+
+    log_syscall!(
+        process_vm_readv,
+        /* rv */ libc::ssize_t,
+        /* pid */ linux_api::posix_types::kernel_pid_t,
+        /* local_iov */ *const libc::iovec,
+        /* liovcnt */ std::ffi::c_ulong,
+        /* remote_iov */ *const libc::iovec,
+        /* riovcnt */ std::ffi::c_ulong,
+        /* flags */ std::ffi::c_ulong,
+    );
+    pub fn process_vm_readv(
+        ctx: &mut SyscallContext,
+        pid: linux_api::posix_types::kernel_pid_t,
+        local_iov: ForeignPtr<libc::iovec>,
+        liovcnt: std::ffi::c_ulong,
+        remote_iov: ForeignPtr<libc::iovec>,
+        riovcnt: std::ffi::c_ulong,
+        flags: std::ffi::c_ulong,
+    ) -> Result<libc::ssize_t, Errno> {
+        Self::process_vm_rw_helper(
+            ctx, pid, local_iov, liovcnt, remote_iov, riovcnt, flags, false,
+        )
+    }
+
+    log_syscall!(
+        process_vm_writev,
+        /* rv */ libc::ssize_t,
+        /* pid */ linux_api::posix_types::kernel_pid_t,
+        /* local_iov */ *const libc::iovec,
+        /* liovcnt */ std::ffi::c_ulong,
+        /* remote_iov */ *const libc::iovec,
+        /* riovcnt */ std::ffi::c_ulong,
+        /* flags */ std::ffi::c_ulong,
+    );
+    pub fn process_vm_writev(
+        ctx: &mut SyscallContext,
+        pid: linux_api::posix_types::kernel_pid_t,
+        local_iov: ForeignPtr<libc::iovec>,
+        liovcnt: std::ffi::c_ulong,
+        remote_iov: ForeignPtr<libc::iovec>,
+        riovcnt: std::ffi::c_ulong,
+        flags: std::ffi::c_ulong,
+    ) -> Result<libc::ssize_t, Errno> {
+        Self::process_vm_rw_helper(
+            ctx, pid, local_iov, liovcnt, remote_iov, riovcnt, flags, true,
+        )
+    }
+
+    /// Copy data between the calling process and the process `pid`. If `write_remote` is true, the
+    /// data is copied from the local iovecs to the remote iovecs, otherwise it's copied from the
+    /// remote iovecs to the local iovecs.
+    ///
+    /// Process IDs in shadow are per-host, so `pid` always refers to a process on the caller's host.
+    /// Processes on other hosts can't be named and result in `ESRCH`, as for any other unknown pid.
+    #[allow(clippy::too_many_arguments)]
+    fn process_vm_rw_helper(
+        ctx: &mut SyscallContext,
+        pid: linux_api::posix_types::kernel_pid_t,
+        local_iov: ForeignPtr<libc::iovec>,
+        liovcnt: std::ffi::c_ulong,
+        remote_iov: ForeignPtr<libc::iovec>,
+        riovcnt: std::ffi::c_ulong,
+        flags: std::ffi::c_ulong,
+        write_remote: bool,
+    ) -> Result<libc::ssize_t, Errno> {
+        // process_vm_readv(2): "The flags argument is currently unused and must be set to 0."
+        if flags != 0 {
+            return Err(Errno::EINVAL);
+        }
+
+        let liovcnt = liovcnt.try_into().or(Err(Errno::EINVAL))?;
+        let riovcnt = riovcnt.try_into().or(Err(Errno::EINVAL))?;
+
+        // both iovec arrays are in the calling process' memory
+        let (local_iovs, remote_iovs) = {
+            let mem = ctx.objs.memory();
+            let local_iovs = io::read_iovecs(&mem, local_iov, liovcnt)?;
+            let remote_iovs = io::read_iovecs(&mem, remote_iov, riovcnt)?;
+            (local_iovs, remote_iovs)
+        };
+
+        // process_vm_readv(2): "EINVAL The sum of the iov_len values of either local_iov or
+        // remote_iov overflows a ssize_t value."
+        for iovs in [&local_iovs, &remote_iovs] {
+            let total = iovs
+                .iter()
+                .try_fold(0usize, |total, iov| total.checked_add(iov.len));
+            if total
+                .and_then(|x| libc::ssize_t::try_from(x).ok())
+                .is_none()
+            {
+                return Err(Errno::EINVAL);
+            }
+        }
+
+        let pid = pid.try_into().or(Err(Errno::ESRCH))?;
+
+        let (src_iovs, dst_iovs) = if write_remote {
+            (&local_iovs, &remote_iovs)
+        } else {
+            (&remote_iovs, &local_iovs)
+        };
+
+        let copied = if pid == ctx.objs.process.id() {
+            // the "remote" process is the calling process
+            let mut mem = ctx.objs.memory_mut();
+            copy_between_iovecs(src_iovs, dst_iovs, |src, dst, buf| {
+                mem.copy_from_ptr(buf, src)?;
+                mem.copy_to_ptr(dst, buf)
+            })
+        } else {
+            let Some(target) = ctx.objs.host.process_borrow(pid) else {
+                log::debug!("Process {pid:?} not found");
+                return Err(Errno::ESRCH);
+            };
+            let target = target.borrow(ctx.objs.host.root());
+
+            // a zombie process has no memory to access
+            if target.borrow_as_runnable().is_none() {
+                return Err(Errno::ESRCH);
+            }
+
+            let (src_process, dst_process) = if write_remote {
+                (ctx.objs.process, &*target)
+            } else {
+                (&*target, ctx.objs.process)
+            };
+
+            let src_mem = src_process.memory_borrow();
+            let mut dst_mem = dst_process.memory_borrow_mut();
+            copy_between_iovecs(src_iovs, dst_iovs, |src, dst, buf| {
+                src_mem.copy_from_ptr(buf, src)?;
+                dst_mem.copy_to_ptr(dst, buf)
+            })
+        }?;
+
+        Ok(copied.try_into().unwrap())
+    }
+}
+
+/// Copy bytes from the `src` iovecs to the `dst` iovecs using `copy_fn`, which is given a source
+/// pointer, a destination pointer, and a scratch buffer of the same length. The copy stops at the
+/// end of either list of iovecs, or at the first failed copy. Returns the number of bytes copied,
+/// or the error if nothing was copied.
+fn copy_between_iovecs(
+    src: &[IoVec],
+    dst: &[IoVec],
+    mut copy_fn: impl FnMut(ForeignArrayPtr<u8>, ForeignArrayPtr<u8>, &mut [u8]) -> Result<(), Errno>,
+) -> Result<usize, Errno> {
+    // limit the size of the scratch buffer
+    const MAX_CHUNK_LEN: usize = 64 * 1024;
+
+    let mut buf = Vec::new();
+    let mut copied = 0;
+
+    let mut src = src.iter().copied().filter(|iov| iov.len > 0).peekable();
+    let mut dst = dst.iter().copied().filter(|iov| iov.len > 0).peekable();
+    let mut src_offset = 0;
+    let mut dst_offset = 0;
+
+    while let (Some(src_iov), Some(dst_iov)) = (src.peek().copied(), dst.peek().copied()) {
+        let len = std::cmp::min(src_iov.len - src_offset, dst_iov.len - dst_offset);
+        let len = std::cmp::min(len, MAX_CHUNK_LEN);
+
+        let src_ptr = ForeignArrayPtr::new(src_iov.base.add(src_offset), len);
+        let dst_ptr = ForeignArrayPtr::new(dst_iov.base.add(dst_offset), len);
+
+        buf.resize(len, 0);
+        if let Err(e) = copy_fn(src_ptr, dst_ptr, &mut buf) {
+            // process_vm_readv(2): "In the event of a partial read/write, the return value will be
+            // the number of bytes successfully read/written, which may be less than the total"
+            if copied == 0 {
+                return Err(e);
+            }
+            break;
+        }
+
+        copied += len;
+        src_offset += len;
+        dst_offset += len;
+
+        if src_offset == src_iov.len {
+            src.next();
+            src_offset = 0;
+        }
+        if dst_offset == dst_iov.len {
+            dst.next();
+            dst_offset = 0;
+        }
+    }
+
+    Ok(copied)
 }
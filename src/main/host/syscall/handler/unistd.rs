@@ -72,7 +72,7 @@ impl SyscallHandler {
 
         Ok(desc_table
             .register_descriptor(new_desc)
-            .or(Err(Errno::ENFILE))?)
+            .or(Err(Errno::EMFILE))?)
     }
 
     log_syscall!(
@@ -99,6 +99,11 @@ impl SyscallHandler {
             return Ok(new_fd);
         }
 
+        // from 'man 2 dup2': "EBADF newfd is out of the allowed range for file descriptors"
+        if u64::from(new_fd) >= desc_table.fd_limit() {
+            return Err(Errno::EBADF.into());
+        }
+
         // duplicate the descriptor
         let new_desc = desc.dup(DescriptorFlags::empty());
         let replaced_desc = desc_table.register_descriptor_with_fd(new_desc, new_fd);
@@ -129,17 +134,8 @@ impl SyscallHandler {
         new_fd: std::ffi::c_int,
         flags: std::ffi::c_int,
     ) -> Result<DescriptorHandle, SyscallError> {
-        // get the descriptor, or return early if it doesn't exist
-        let mut desc_table = ctx.objs.thread.descriptor_table_borrow_mut(ctx.objs.host);
-        let desc = Self::get_descriptor(&desc_table, old_fd)?;
-
-        // from 'man 2 dup3': "If oldfd equals newfd, then dup3() fails with the error EINVAL"
-        if old_fd == new_fd {
-            return Err(linux_api::errno::Errno::EINVAL.into());
-        }
-
-        let new_fd = new_fd.try_into().or(Err(linux_api::errno::Errno::EBADF))?;
-
+        // Like linux, validate the flags and new fd before looking up the old fd, so that for
+        // example `dup3(fd, fd, 0)` fails with EINVAL even if `fd` isn't open.
         let Some(flags) = OFlag::from_bits(flags) else {
             debug!("Invalid flags: {flags}");
             return Err(linux_api::errno::Errno::EINVAL.into());
@@ -161,6 +157,23 @@ impl SyscallHandler {
             }
         }
 
+        // from 'man 2 dup3': "If oldfd equals newfd, then dup3() fails with the error EINVAL"
+        if old_fd == new_fd {
+            return Err(linux_api::errno::Errno::EINVAL.into());
+        }
+
+        let mut desc_table = ctx.objs.thread.descriptor_table_borrow_mut(ctx.objs.host);
+
+        let new_fd: DescriptorHandle = new_fd.try_into().or(Err(linux_api::errno::Errno::EBADF))?;
+
+        // from 'man 2 dup3': "EBADF newfd is out of the allowed range for file descriptors"
+        if u64::from(new_fd) >= desc_table.fd_limit() {
+            return Err(linux_api::errno::Errno::EBADF.into());
+        }
+
+        // get the descriptor, or return early if it doesn't exist
+        let desc = Self::get_descriptor(&desc_table, old_fd)?;
+
         // duplicate the descriptor
         let new_desc = desc.dup(descriptor_flags);
         let replaced_desc = desc_table.register_descriptor_with_fd(new_desc, new_fd);
@@ -504,33 +517,8 @@ impl SyscallHandler {
         reader_desc.set_flags(descriptor_flags);
         writer_desc.set_flags(descriptor_flags);
 
-        // register the file descriptors
-        let mut dt = ctx.objs.thread.descriptor_table_borrow_mut(ctx.objs.host);
-        // unwrap here since the error handling would be messy (need to deregister) and we shouldn't
-        // ever need to worry about this in practice
-        let read_fd = dt.register_descriptor(reader_desc).unwrap();
-        let write_fd = dt.register_descriptor(writer_desc).unwrap();
-
-        // try to write them to the caller
-        let fds = [i32::from(read_fd), i32::from(write_fd)];
-        let write_res = ctx.objs.process.memory_borrow_mut().write(fd_ptr, &fds);
-
-        // clean up in case of error
-        match write_res {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
-                    // ignore any errors when closing
-                    dt.deregister_descriptor(read_fd)
-                        .unwrap()
-                        .close(ctx.objs.host, cb_queue);
-                    dt.deregister_descriptor(write_fd)
-                        .unwrap()
-                        .close(ctx.objs.host, cb_queue);
-                });
-                Err(e.into())
-            }
-        }
+        // register the file descriptors and write them to the caller
+        Self::register_descriptor_pair(ctx, reader_desc, writer_desc, fd_ptr)
     }
 
     log_syscall!(getpid, /* rv */ linux_api::posix_types::kernel_pid_t);
@@ -610,13 +598,6 @@ impl SyscallHandler {
             // the calling process.
             return Err(Errno::ESRCH.into());
         }
-        if let Some(pgid) = pgid {
-            if ctx.objs.host.process_session_id_of_group_id(pgid) != Some(process.session_id()) {
-                // An attempt was made to move a process into a process group in
-                // a different session
-                return Err(Errno::EPERM.into());
-            }
-        }
         if process.session_id() != ctx.objs.process.session_id() {
             // `setpgid(2)`: ... or to change the process  group  ID of one of
             // the children of the calling process and the child was in a
@@ -631,18 +612,18 @@ impl SyscallHandler {
         // `setpgid(2): EACCES: An attempt was made to change the process group
         // ID of one of the children of the calling process and the child had
         // already performed an execve(2).
-        if let Some(pgid) = pgid {
-            if ctx.objs.host.process_session_id_of_group_id(pgid) != Some(process.session_id()) {
-                // `setpgid(2)`: An attempt was made to move a process into a
-                // process group in a different session
-                return Err(Errno::EPERM.into());
-            }
-            process.set_group_id(pgid);
-        } else {
-            // `setpgid(2)`: If pgid is zero, then the PGID of the process
-            // specified by pid is made the same as its process ID.
-            process.set_group_id(process.id());
+        // `setpgid(2)`: If pgid is zero, then the PGID of the process
+        // specified by pid is made the same as its process ID.
+        let pgid = pgid.unwrap_or(process.id());
+        if pgid != process.id()
+            && ctx.objs.host.process_session_id_of_group_id(pgid) != Some(process.session_id())
+        {
+            // `setpgid(2)`: An attempt was made to move a process into a
+            // process group in a different session, or the group doesn't
+            // exist. A process can always create a new group with its own ID.
+            return Err(Errno::EPERM.into());
         }
+        process.set_group_id(pgid);
         Ok(())
     }
 
@@ -746,7 +727,7 @@ impl SyscallHandler {
         let argv;
         let envv;
         {
-            let mem = ctx.objs.process.memory_borrow();
+            let mem = ctx.objs.memory();
             argv = read_cstring_vec(&mem, argv_ptr_ptr)?;
             envv = read_cstring_vec(&mem, envv_ptr_ptr)?;
         }
@@ -758,6 +739,11 @@ impl SyscallHandler {
             .unwrap()
             .spawn_mthread_for_exec(ctx.objs.host, abs_path, argv, envv)?;
 
+        // `prctl(2)`: the thread name is reset to the filename of the new executable.
+        ctx.objs
+            .thread
+            .set_comm(path.to_bytes().rsplit(|c| *c == b'/').next().unwrap());
+
         // If we get this far, then we should be able to ultimately succeed.
         // We need a mutable reference to the Process to update it, though, which we can't
         // get from here since it's already borrowed immutably.
@@ -845,7 +831,7 @@ impl SyscallHandler {
     ) -> Result<i64, SyscallError> {
         let mut path_buf = [0u8; linux_api::limits::PATH_MAX];
         let path_buf_capacity = path_buf.len();
-        let path = ctx.objs.process.memory_borrow().copy_str_from_ptr(
+        let path = ctx.objs.memory().copy_str_from_ptr(
             &mut path_buf,
             ForeignArrayPtr::new(pathname.cast::<u8>(), path_buf_capacity),
         )?;
@@ -945,10 +931,7 @@ impl SyscallHandler {
         name.version[..version.len()].copy_from_slice(version);
         name.machine[..machine.len()].copy_from_slice(machine);
 
-        ctx.objs
-            .process
-            .memory_borrow_mut()
-            .write(name_ptr, &name)?;
+        ctx.objs.memory_mut().write(name_ptr, &name)?;
 
         Ok(())
     }
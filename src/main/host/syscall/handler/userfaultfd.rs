@@ -0,0 +1,60 @@
+use std::sync::Arc;
+
+use atomic_refcell::AtomicRefCell;
+use linux_api::errno::Errno;
+use linux_api::fcntl::{DescriptorFlags, OFlag};
+use linux_api::userfaultfd::UFFD_USER_MODE_ONLY;
+
+use crate::host::descriptor::descriptor_table::DescriptorHandle;
+use crate::host::descriptor::userfaultfd::UserFaultFd;
+use crate::host::descriptor::{CompatFile, Descriptor, File, FileStatus, OpenFile};
+use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
+use crate::host::syscall::types::SyscallError;
+
+impl SyscallHandler {
+    log_syscall!(
+        userfaultfd,
+        /* rv */ std::ffi::c_int,
+        /* flags */ std::ffi::c_int,
+    );
+    pub fn userfaultfd(
+        ctx: &mut SyscallContext,
+        flags: std::ffi::c_int,
+    ) -> Result<DescriptorHandle, SyscallError> {
+        let Some(flags) = OFlag::from_bits(flags & !(UFFD_USER_MODE_ONLY as std::ffi::c_int))
+        else {
+            log::debug!("Invalid userfaultfd flags: {flags}");
+            return Err(Errno::EINVAL.into());
+        };
+
+        // userfaultfd(2): "O_CLOEXEC, O_NONBLOCK, UFFD_USER_MODE_ONLY"
+        if !(OFlag::O_CLOEXEC | OFlag::O_NONBLOCK).contains(flags) {
+            log::debug!("Invalid userfaultfd flags: {flags:?}");
+            return Err(Errno::EINVAL.into());
+        }
+
+        let mut file_flags = FileStatus::empty();
+        if flags.contains(OFlag::O_NONBLOCK) {
+            file_flags.insert(FileStatus::NONBLOCK);
+        }
+
+        // We never register any memory ranges, so `UFFD_USER_MODE_ONLY` has no effect.
+        let file = Arc::new(AtomicRefCell::new(UserFaultFd::new(file_flags)));
+
+        let mut desc = Descriptor::new(CompatFile::New(OpenFile::new(File::UserFaultFd(file))));
+        if flags.contains(OFlag::O_CLOEXEC) {
+            desc.set_flags(DescriptorFlags::FD_CLOEXEC);
+        }
+
+        let fd = ctx
+            .objs
+            .thread
+            .descriptor_table_borrow_mut(ctx.objs.host)
+            .register_descriptor(desc)
+            .or(Err(Errno::EMFILE))?;
+
+        log::trace!("userfaultfd() returning fd {fd}");
+
+        Ok(fd)
+    }
+}
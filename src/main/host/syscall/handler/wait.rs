@@ -8,7 +8,7 @@ use linux_api::signal::{siginfo_t, Signal};
 use linux_api::wait::{WaitFlags, WaitId};
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
-use crate::host::process::{ExitStatus, Process, ProcessId};
+use crate::host::process::{ExitStatus, JobControlEvent, Process, ProcessId};
 use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
 use crate::host::syscall::types::SyscallError;
 
@@ -111,29 +111,27 @@ impl SyscallHandler {
             return Err(Errno::ECHILD.into());
         }
 
-        if !options.contains(WaitFlags::WEXITED) {
-            warn_once_then_debug!("Waiting only for child events that currently never happen under Shadow: {options:?}");
-            // The other events that can be waited for (WUNTRACED, WSTOPPED,
-            // WCONTINUED) currently can't happen under Shadow.
-            // TODO: If and when those things *can* happen, check for them here.
-            return if options.contains(WaitFlags::WNOHANG) {
-                Ok(0)
-            } else {
-                Err(SyscallError::new_blocked_on_child(
-                    /* restartable */ true,
-                ))
-            };
-        }
-
-        let mut matching_child_zombies = matching_children.filter(|(_pid, process)| {
+        // Job-control state changes that the caller is waiting for.
+        let wants_event = |event: &JobControlEvent| match event {
+            JobControlEvent::Stopped(_) => options.contains(WaitFlags::WSTOPPED),
+            JobControlEvent::Continued => options.contains(WaitFlags::WCONTINUED),
+        };
+        let waitable_child = matching_children.find_map(|(pid, process)| {
             let process = process.borrow(ctx.objs.host.root());
-            let zombie = process.borrow_as_zombie();
-            zombie.is_some()
+            if let Some(runnable) = process.borrow_as_runnable() {
+                runnable
+                    .job_control_event()
+                    .filter(wants_event)
+                    .map(|event| (*pid, Some(event)))
+            } else if options.contains(WaitFlags::WEXITED) {
+                // The child is a zombie.
+                Some((*pid, None))
+            } else {
+                None
+            }
         });
-        let Some((matching_child_zombie_pid, matching_child_zombie)) =
-            matching_child_zombies.next()
-        else {
-            // There are matching children, but none are zombies yet.
+        let Some((child_pid, event)) = waitable_child else {
+            // There are matching children, but none have a state change to report yet.
             return if options.contains(WaitFlags::WNOHANG) {
                 Ok(0)
             } else {
@@ -146,9 +144,29 @@ impl SyscallHandler {
             };
         };
 
-        let zombie_process = matching_child_zombie.borrow(ctx.objs.host.root());
-        let zombie = zombie_process.borrow_as_zombie().unwrap();
-        let mut memory = ctx.objs.process.memory_borrow_mut();
+        let child_process = processes.get(&child_pid).unwrap();
+        let child_process = child_process.borrow(ctx.objs.host.root());
+        let mut memory = ctx.objs.memory_mut();
+
+        if let Some(event) = event {
+            if !status_ptr.is_null() {
+                memory.write(status_ptr, &event.wait_status())?;
+            }
+            if !infop.is_null() {
+                memory.write(infop, &event.siginfo(Signal::SIGCHLD, child_pid))?;
+            }
+            if !usage.is_null() {
+                memory.write(usage, &ctx.objs.process.rusage())?;
+            }
+            if !options.contains(WaitFlags::WNOWAIT) {
+                // The state change has been reported, and won't be again.
+                let runnable = child_process.borrow_as_runnable().unwrap();
+                runnable.take_job_control_event();
+            }
+            return Ok(child_pid.into());
+        }
+
+        let zombie = child_process.borrow_as_zombie().unwrap();
 
         if !status_ptr.is_null() {
             let status = match zombie.exit_status() {
@@ -170,23 +188,18 @@ impl SyscallHandler {
             memory.write(usage, &ctx.objs.process.rusage())?;
         }
 
-        let matching_child_zombie_pid: ProcessId = *matching_child_zombie_pid;
         // Drop our borrow of the process list so that we can reap without a runtime borrow error.
         drop(memory);
         drop(zombie);
-        drop(zombie_process);
+        drop(child_process);
         drop(processes);
 
         if !options.contains(WaitFlags::WNOWAIT) {
-            let zombie_process = ctx
-                .objs
-                .host
-                .process_remove(matching_child_zombie_pid)
-                .unwrap();
+            let zombie_process = ctx.objs.host.process_remove(child_pid).unwrap();
             zombie_process.explicit_drop_recursive(ctx.objs.host.root(), ctx.objs.host);
         }
 
-        Ok(matching_child_zombie_pid.into())
+        Ok(child_pid.into())
     }
 
     log_syscall!(
@@ -362,7 +362,15 @@ pub fn read_iovecs(
     let mem_ref = mem.memory_ref(iov_ptr)?;
     let plugin_iovs = mem_ref.deref();
 
+    // readv(2): "EINVAL The sum of the iov_len values overflows an ssize_t value."
+    let mut total_len: libc::ssize_t = 0;
+
     for plugin_iov in plugin_iovs {
+        total_len = libc::ssize_t::try_from(plugin_iov.iov_len)
+            .ok()
+            .and_then(|len| total_len.checked_add(len))
+            .ok_or(Errno::EINVAL)?;
+
         iovs.push(IoVec {
             base: ForeignPtr::from_raw_ptr(plugin_iov.iov_base as *mut u8),
             len: plugin_iov.iov_len,
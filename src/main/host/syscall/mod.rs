@@ -7,6 +7,7 @@ pub mod condition;
 pub mod formatter;
 pub mod handler;
 pub mod io;
+pub mod summary;
 pub mod type_formatting;
 pub mod types;
 
@@ -42,6 +43,22 @@ impl Trigger {
         })
     }
 
+    /// The trigger doesn't take a reference to the futex; the syscall condition created from it
+    /// will.
+    ///
+    /// # Safety
+    ///
+    /// `futex` must point to a valid [`Futex`](c::Futex).
+    pub unsafe fn from_futex(futex: *mut c::Futex) -> Self {
+        assert!(!futex.is_null());
+
+        Self(c::Trigger {
+            type_: c::_TriggerType_TRIGGER_FUTEX,
+            object: c::TriggerObject { as_futex: futex },
+            state: FileState::FUTEX_WAKEUP,
+        })
+    }
+
     pub fn child() -> Self {
         Self(c::Trigger {
             type_: c::_TriggerType_TRIGGER_CHILD,
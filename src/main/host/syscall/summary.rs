@@ -0,0 +1,206 @@
+//! A per-process summary of syscalls, similar to the output of `strace -c`.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+#[derive(Debug, Default, Copy, Clone)]
+struct SyscallStats {
+    calls: u64,
+    errors: u64,
+    time: Duration,
+}
+
+/// Call counts, error counts, and handling time for each syscall made by a process.
+#[derive(Debug, Default)]
+pub struct SyscallSummary {
+    stats: HashMap<&'static str, SyscallStats>,
+}
+
+impl SyscallSummary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add time spent handling the syscall `name`. A syscall that blocks may be handled several
+    /// times before it completes, so this is separate from [`add_call`](Self::add_call).
+    pub fn add_time(&mut self, name: &'static str, time: Duration) {
+        self.stats.entry(name).or_default().time += time;
+    }
+
+    /// Count a completed call to the syscall `name`.
+    pub fn add_call(&mut self, name: &'static str, failed: bool) {
+        let stats = self.stats.entry(name).or_default();
+        stats.calls += 1;
+        if failed {
+            stats.errors += 1;
+        }
+    }
+
+    /// The number of completed calls to the syscall `name`.
+    pub fn calls(&self, name: &str) -> u64 {
+        self.stats.get(name).map(|x| x.calls).unwrap_or(0)
+    }
+
+    /// The number of calls to the syscall `name` that returned an error.
+    pub fn errors(&self, name: &str) -> u64 {
+        self.stats.get(name).map(|x| x.errors).unwrap_or(0)
+    }
+
+    /// Render the summary as a table. If `with_time` is false, the time columns are omitted (for
+    /// example to keep the output deterministic) and rows are ordered by call count.
+    pub fn to_table(&self, with_time: bool) -> String {
+        let mut rows: Vec<_> = self.stats.iter().filter(|(_, x)| x.calls > 0).collect();
+        if with_time {
+            rows.sort_by(|(a_name, a), (b_name, b)| b.time.cmp(&a.time).then(a_name.cmp(b_name)));
+        } else {
+            rows.sort_by(|(a_name, a), (b_name, b)| b.calls.cmp(&a.calls).then(a_name.cmp(b_name)));
+        }
+
+        let total_calls: u64 = rows.iter().map(|(_, x)| x.calls).sum();
+        let total_errors: u64 = rows.iter().map(|(_, x)| x.errors).sum();
+        let total_time: Duration = rows.iter().map(|(_, x)| x.time).sum();
+
+        // strace leaves the error column empty rather than showing 0
+        let errors_str = |errors: u64| {
+            if errors == 0 {
+                String::new()
+            } else {
+                errors.to_string()
+            }
+        };
+
+        let mut lines = Vec::new();
+        let separator;
+        if with_time {
+            lines.push(format!(
+                "{:>6} {:>11} {:>11} {:>9} {:>9} syscall",
+                "% time", "seconds", "usecs/call", "calls", "errors"
+            ));
+            separator = "------ ----------- ----------- --------- --------- ----------------";
+            lines.push(separator.to_string());
+            for (name, stats) in &rows {
+                let percent = if total_time.is_zero() {
+                    0.0
+                } else {
+                    100.0 * stats.time.as_secs_f64() / total_time.as_secs_f64()
+                };
+                let usecs_per_call = stats.time.as_micros() / u128::from(stats.calls);
+                lines.push(format!(
+                    "{:>6.2} {:>11.6} {:>11} {:>9} {:>9} {}",
+                    percent,
+                    stats.time.as_secs_f64(),
+                    usecs_per_call,
+                    stats.calls,
+                    errors_str(stats.errors),
+                    name,
+                ));
+            }
+            lines.push(separator.to_string());
+            lines.push(format!(
+                "{:>6.2} {:>11.6} {:>11} {:>9} {:>9} total",
+                100.0,
+                total_time.as_secs_f64(),
+                "",
+                total_calls,
+                errors_str(total_errors),
+            ));
+        } else {
+            lines.push(format!("{:>9} {:>9} syscall", "calls", "errors"));
+            separator = "--------- --------- ----------------";
+            lines.push(separator.to_string());
+            for (name, stats) in &rows {
+                lines.push(format!(
+                    "{:>9} {:>9} {}",
+                    stats.calls,
+                    errors_str(stats.errors),
+                    name,
+                ));
+            }
+            lines.push(separator.to_string());
+            lines.push(format!(
+                "{:>9} {:>9} total",
+                total_calls,
+                errors_str(total_errors),
+            ));
+        }
+
+        let mut table = lines.join("\n");
+        table.push('\n');
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts() {
+        let mut summary = SyscallSummary::new();
+        for _ in 0..5 {
+            summary.add_call("write", false);
+        }
+        summary.add_call("read", true);
+        summary.add_time("read", Duration::from_micros(10));
+        summary.add_call("read", false);
+
+        assert_eq!(summary.calls("write"), 5);
+        assert_eq!(summary.errors("write"), 0);
+        assert_eq!(summary.calls("read"), 2);
+        assert_eq!(summary.errors("read"), 1);
+        assert_eq!(summary.calls("open"), 0);
+    }
+
+    #[test]
+    fn test_table_without_time() {
+        let mut summary = SyscallSummary::new();
+        for _ in 0..5 {
+            summary.add_call("write", false);
+        }
+        summary.add_call("read", true);
+
+        assert_eq!(
+            summary.to_table(false),
+            "    calls    errors syscall\n\
+             --------- --------- ----------------\n\
+             \x20       5           write\n\
+             \x20       1         1 read\n\
+             --------- --------- ----------------\n\
+             \x20       6         1 total\n"
+        );
+    }
+
+    #[test]
+    fn test_table_with_time() {
+        let mut summary = SyscallSummary::new();
+        for _ in 0..4 {
+            summary.add_call("write", false);
+        }
+        summary.add_time("write", Duration::from_micros(300));
+        summary.add_call("read", false);
+        summary.add_time("read", Duration::from_micros(100));
+
+        let table = summary.to_table(true);
+        let lines: Vec<_> = table.lines().collect();
+        assert_eq!(
+            lines[2],
+            " 75.00    0.000300          75         4           write"
+        );
+        assert_eq!(
+            lines[3],
+            " 25.00    0.000100         100         1           read"
+        );
+        assert_eq!(
+            lines[5],
+            "100.00    0.000400                     5           total"
+        );
+    }
+
+    #[test]
+    fn test_blocked_only() {
+        // a syscall that blocked but never completed isn't listed
+        let mut summary = SyscallSummary::new();
+        summary.add_time("nanosleep", Duration::from_micros(10));
+        assert_eq!(summary.to_table(false).lines().count(), 4);
+    }
+}
@@ -137,7 +137,22 @@ static void _syscallcondition_cleanupListeners(SysCallCondition* cond) {
             }
             case TRIGGER_FUTEX: {
                 utility_alwaysAssert(cond->trigger.object.as_pointer);
-                futex_removeListener(cond->trigger.object.as_futex, cond->triggerListener);
+                Futex* futex = cond->trigger.object.as_futex;
+                futex_removeListener(futex, cond->triggerListener);
+
+                // Dynamically clean up the futex if nobody else is waiting on it. We do this here
+                // rather than in the futex syscall handler since the listener may have been
+                // requeued from the futex that the thread originally waited on.
+                const Host* host = worker_getCurrentHost();
+                if (host != NULL && futex_getListenerCount(futex) == 0) {
+                    FutexTable* ftable = host_getFutexTable(host);
+                    ManagedPhysicalMemoryAddr addr = futex_getAddress(futex);
+                    if (futextable_get(ftable, addr) == futex) {
+                        trace("Dynamically freed a futex object for futex addr %p",
+                              (void*)addr.val);
+                        futextable_remove(ftable, addr);
+                    }
+                }
                 break;
             }
             case TRIGGER_CHILD: {
@@ -503,7 +518,7 @@ void syscallcondition_waitNonblock(SysCallCondition* cond, const Host* host, con
                     cond->triggerListener, cond->trigger.state, SLF_ALWAYS);
 
                 /* Attach the listener to the descriptor. */
-                futex_addListener(cond->trigger.object.as_futex, cond->triggerListener);
+                futex_addListener(cond->trigger.object.as_futex, cond->triggerListener, cond);
                 break;
             }
             case TRIGGER_CHILD: {
@@ -531,6 +546,22 @@ void syscallcondition_waitNonblock(SysCallCondition* cond, const Host* host, con
 #endif
 }
 
+void syscallcondition_requeueFutex(SysCallCondition* cond, Futex* futex) {
+    MAGIC_ASSERT(cond);
+    utility_alwaysAssert(cond->trigger.type == TRIGGER_FUTEX);
+    utility_alwaysAssert(cond->triggerListener);
+
+    Futex* oldFutex = cond->trigger.object.as_futex;
+
+    // The condition holds its own reference to the listener, so it remains valid after being
+    // removed from the old futex.
+    futex_removeListener(oldFutex, cond->triggerListener);
+    futex_ref(futex);
+    cond->trigger.object.as_futex = futex;
+    futex_addListener(futex, cond->triggerListener, cond);
+    futex_unref(oldFutex);
+}
+
 void syscallcondition_cancel(SysCallCondition* cond) {
     MAGIC_ASSERT(cond);
     _syscallcondition_cleanupListeners(cond);
@@ -67,6 +67,11 @@ void syscallcondition_setTimeout(SysCallCondition* cond, CEmulatedTime t);
  * the descriptor table). */
 void syscallcondition_setActiveFile(SysCallCondition* cond, OpenFile* file);
 
+/* Move the condition's listener from the futex it's currently waiting on to `futex`, so that it
+ * is triggered by wakeups on `futex` instead. The condition must have a futex trigger and be
+ * active. */
+void syscallcondition_requeueFutex(SysCallCondition* cond, Futex* futex);
+
 /* Increment the reference count on the given condition. */
 void syscallcondition_ref(SysCallCondition* cond);
 
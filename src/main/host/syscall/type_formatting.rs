@@ -1,4 +1,4 @@
-use shadow_shim_helper_rs::syscall_types::ForeignPtr;
+use shadow_shim_helper_rs::syscall_types::{ForeignPtr, SyscallReg};
 use shadow_shim_helper_rs::util::DebugFormatter;
 
 use super::formatter::{FmtOptions, SyscallDisplay, SyscallVal};
@@ -196,8 +196,6 @@ simple_display_impl!(u8, u16, u32, u64, usize);
 
 deref_pointer_impl!(i8, i16, i32, i64, isize);
 deref_pointer_impl!(u8, u16, u32, u64, usize);
-deref_pointer_impl!(linux_api::stat::stat);
-deref_pointer_impl!(linux_api::sched::clone_args);
 deref_pointer_impl!(linux_api::time::timespec);
 deref_pointer_impl!(linux_api::time::kernel_timespec);
 deref_pointer_impl!(linux_api::time::kernel_old_timeval);
@@ -211,11 +209,11 @@ safe_pointer_impl!(linux_api::sysinfo::sysinfo);
 safe_pointer_impl!(libc::iovec);
 
 // nix still uses an old bitflags version which isn't supported by `bitflags_impl`
-simple_debug_impl!(linux_api::sched::CloneFlags);
 simple_debug_impl!(linux_api::time::ITimerId);
 simple_debug_impl!(linux_api::time::ClockId);
 simple_debug_impl!(nix::sys::stat::Mode);
 simple_debug_impl!(nix::sys::eventfd::EfdFlags);
+simple_debug_impl!(nix::sys::signalfd::SfdFlags);
 simple_debug_impl!(nix::sys::socket::MsgFlags);
 
 simple_display_impl!(linux_api::prctl::PrctlOp);
@@ -228,6 +226,331 @@ bitflags_impl!(linux_api::mman::MapFlags);
 bitflags_impl!(linux_api::mman::MRemapFlags);
 bitflags_impl!(linux_api::time::ClockNanosleepFlags);
 
+/// Format flags in the style of strace: named flags are joined by `|`, and any remaining unnamed
+/// bits are appended in hex. An empty set of flags is shown as `0`.
+fn fmt_flags<F>(f: &mut std::fmt::Formatter<'_>, flags: &F) -> std::fmt::Result
+where
+    F: bitflags::Flags,
+    F::Bits: std::fmt::LowerHex,
+{
+    if flags.is_empty() {
+        return write!(f, "0");
+    }
+
+    let mut iter = flags.iter_names();
+    let mut first = true;
+
+    for (name, _) in &mut iter {
+        if !first {
+            write!(f, "|")?;
+        }
+        first = false;
+        write!(f, "{name}")?;
+    }
+
+    let remaining = iter.remaining();
+    if !remaining.is_empty() {
+        if !first {
+            write!(f, "|")?;
+        }
+        write!(f, "{:#x}", remaining.bits())?;
+    }
+
+    Ok(())
+}
+
+/// The name of a standard (non-realtime) signal.
+fn standard_signal_name(signal: linux_api::signal::Signal) -> Option<&'static str> {
+    use linux_api::signal::Signal;
+
+    Some(match signal {
+        Signal::SIGHUP => "SIGHUP",
+        Signal::SIGINT => "SIGINT",
+        Signal::SIGQUIT => "SIGQUIT",
+        Signal::SIGILL => "SIGILL",
+        Signal::SIGTRAP => "SIGTRAP",
+        Signal::SIGABRT => "SIGABRT",
+        Signal::SIGBUS => "SIGBUS",
+        Signal::SIGFPE => "SIGFPE",
+        Signal::SIGKILL => "SIGKILL",
+        Signal::SIGUSR1 => "SIGUSR1",
+        Signal::SIGSEGV => "SIGSEGV",
+        Signal::SIGUSR2 => "SIGUSR2",
+        Signal::SIGPIPE => "SIGPIPE",
+        Signal::SIGALRM => "SIGALRM",
+        Signal::SIGTERM => "SIGTERM",
+        Signal::SIGSTKFLT => "SIGSTKFLT",
+        Signal::SIGCHLD => "SIGCHLD",
+        Signal::SIGCONT => "SIGCONT",
+        Signal::SIGSTOP => "SIGSTOP",
+        Signal::SIGTSTP => "SIGTSTP",
+        Signal::SIGTTIN => "SIGTTIN",
+        Signal::SIGTTOU => "SIGTTOU",
+        Signal::SIGURG => "SIGURG",
+        Signal::SIGXCPU => "SIGXCPU",
+        Signal::SIGXFSZ => "SIGXFSZ",
+        Signal::SIGVTALRM => "SIGVTALRM",
+        Signal::SIGPROF => "SIGPROF",
+        Signal::SIGWINCH => "SIGWINCH",
+        Signal::SIGIO => "SIGIO",
+        Signal::SIGPWR => "SIGPWR",
+        Signal::SIGSYS => "SIGSYS",
+        _ => return None,
+    })
+}
+
+/// Format a signal number in the style of strace (ex: `SIGTERM`, `SIGRTMIN+3`). Unknown signal
+/// numbers are shown as integers.
+fn fmt_signal(f: &mut std::fmt::Formatter<'_>, sig: i32) -> std::fmt::Result {
+    use linux_api::signal::Signal;
+
+    let Ok(signal) = Signal::try_from(sig) else {
+        return write!(f, "{sig}");
+    };
+
+    if signal.is_realtime() {
+        let offset = sig - Signal::SIGRT_MIN.as_i32();
+        return if offset == 0 {
+            write!(f, "SIGRTMIN")
+        } else {
+            write!(f, "SIGRTMIN+{offset}")
+        };
+    }
+
+    match standard_signal_name(signal) {
+        Some(name) => write!(f, "{name}"),
+        None => write!(f, "{sig}"),
+    }
+}
+
+/// Format a file mode in the style of strace (ex: `S_IFREG|0644`).
+fn fmt_stat_mode(f: &mut std::fmt::Formatter<'_>, mode: u32) -> std::fmt::Result {
+    use linux_api::stat::SFlag;
+
+    let file_type = SFlag::from_bits_retain(mode) & SFlag::S_IFMT;
+    let name = match file_type {
+        SFlag::S_IFSOCK => Some("S_IFSOCK"),
+        SFlag::S_IFLNK => Some("S_IFLNK"),
+        SFlag::S_IFREG => Some("S_IFREG"),
+        SFlag::S_IFBLK => Some("S_IFBLK"),
+        SFlag::S_IFDIR => Some("S_IFDIR"),
+        SFlag::S_IFCHR => Some("S_IFCHR"),
+        SFlag::S_IFIFO => Some("S_IFIFO"),
+        _ => None,
+    };
+
+    // permission bits are shown in octal with a leading 0, like strace
+    let perms = mode & 0o7777;
+    match name {
+        Some(name) => write!(f, "{name}|0{perms:03o}"),
+        None if file_type.is_empty() => write!(f, "0{perms:03o}"),
+        None => write!(f, "0{:o}|0{perms:03o}", file_type.bits()),
+    }
+}
+
+/// Format the interesting fields of a `stat` or `statx` struct. The inode number depends on the
+/// host's file system, so it isn't shown in deterministic mode.
+fn fmt_stat_fields(
+    f: &mut std::fmt::Formatter<'_>,
+    mode: u32,
+    size: u64,
+    ino: u64,
+    nlink: u64,
+    options: FmtOptions,
+) -> std::fmt::Result {
+    write!(f, "{{st_mode=")?;
+    fmt_stat_mode(f, mode)?;
+    write!(f, ", st_size={size}")?;
+    if options == FmtOptions::Standard {
+        write!(f, ", st_ino={ino}")?;
+    }
+    write!(f, ", st_nlink={nlink}}}")
+}
+
+/// Format the signals in a signal set by name (ex: `[SIGINT SIGTERM]`).
+fn fmt_sigset(
+    f: &mut std::fmt::Formatter<'_>,
+    set: &linux_api::signal::sigset_t,
+) -> std::fmt::Result {
+    use linux_api::signal::Signal;
+
+    let signals = (Signal::MIN.as_i32()..=Signal::MAX.as_i32())
+        .filter(|sig| set.has(Signal::try_from(*sig).unwrap()));
+
+    write!(f, "[")?;
+    for (i, sig) in signals.enumerate() {
+        if i != 0 {
+            write!(f, " ")?;
+        }
+        fmt_signal(f, sig)?;
+    }
+    write!(f, "]")
+}
+
+/// Format the flags and exit signal of a `clone` syscall. The exit signal is only shown if it's
+/// non-zero.
+fn fmt_clone_flags(
+    f: &mut std::fmt::Formatter<'_>,
+    flags: linux_api::sched::CloneFlags,
+    exit_signal: i32,
+) -> std::fmt::Result {
+    if exit_signal == 0 {
+        return fmt_flags(f, &flags);
+    }
+
+    if !flags.is_empty() {
+        fmt_flags(f, &flags)?;
+        write!(f, "|")?;
+    }
+
+    fmt_signal(f, exit_signal)
+}
+
+/// Format a futex operation in the style of strace (ex: `FUTEX_WAIT_PRIVATE`). The
+/// `FUTEX_CLOCK_REALTIME` flag is shown separately from the command.
+fn fmt_futex_op(f: &mut std::fmt::Formatter<'_>, op: i32) -> std::fmt::Result {
+    use linux_api::futex::FutexOpFlags;
+
+    let cmd = op & linux_api::futex::FUTEX_CMD_MASK;
+    let flags = FutexOpFlags::from_bits_retain(op & !linux_api::futex::FUTEX_CMD_MASK);
+
+    let name = <FutexOpFlags as bitflags::Flags>::FLAGS
+        .iter()
+        .find(|flag| flag.value().bits() == cmd)
+        .map(|flag| flag.name());
+
+    match name {
+        Some(name) => {
+            write!(f, "{name}")?;
+            if flags.contains(FutexOpFlags::FUTEX_PRIVATE_FLAG) {
+                write!(f, "_PRIVATE")?;
+            }
+        }
+        None => {
+            write!(f, "{cmd:#x}")?;
+            if flags.contains(FutexOpFlags::FUTEX_PRIVATE_FLAG) {
+                write!(f, "|FUTEX_PRIVATE_FLAG")?;
+            }
+        }
+    }
+
+    if flags.contains(FutexOpFlags::FUTEX_CLOCK_REALTIME) {
+        write!(f, "|FUTEX_CLOCK_REALTIME")?;
+    }
+
+    Ok(())
+}
+
+/// The futex command of the futex operation, without the `FUTEX_PRIVATE_FLAG` and
+/// `FUTEX_CLOCK_REALTIME` flags.
+fn futex_cmd(op: SyscallReg) -> linux_api::futex::FutexOpFlags {
+    let op = i32::from(op);
+    linux_api::futex::FutexOpFlags::from_bits_retain(op & linux_api::futex::FUTEX_CMD_MASK)
+}
+
+/// Format an ioctl request by name (ex: `FIONBIO`). Unknown requests are decoded into their
+/// generic `_IOC` fields in the style of strace (ex: `_IOC(_IOC_READ, 0x54, 0x99, 0x4)`).
+fn fmt_ioctl_request(f: &mut std::fmt::Formatter<'_>, cmd: u32) -> std::fmt::Result {
+    use linux_api::ioctls::IoctlRequest;
+
+    if let Ok(request) = IoctlRequest::try_from(cmd) {
+        return write!(f, "{request:?}");
+    }
+
+    // the field layout of `_IOC` from "asm-generic/ioctl.h"
+    let nr = cmd & 0xff;
+    let type_ = (cmd >> 8) & 0xff;
+    let size = (cmd >> 16) & 0x3fff;
+    let dir = (cmd >> 30) & 0x3;
+
+    let dir = match dir {
+        0 => "_IOC_NONE",
+        1 => "_IOC_WRITE",
+        2 => "_IOC_READ",
+        _ => "_IOC_READ|_IOC_WRITE",
+    };
+
+    write!(f, "_IOC({dir}, {type_:#x}, {nr:#x}, {size:#x})")
+}
+
+impl SyscallDisplay for SyscallVal<'_, linux_api::sched::CloneFlags> {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        _options: FmtOptions,
+        _mem: &MemoryManager,
+    ) -> std::fmt::Result {
+        fmt_flags(f, &linux_api::sched::CloneFlags::from(self.reg))
+    }
+}
+
+/// Format the fds that are set in an `fd_set` bitmap as a list (ex: `[3 4 7]`). Only the first
+/// `nfds` bits are considered.
+fn fmt_fd_set(f: &mut std::fmt::Formatter<'_>, words: &[u64], nfds: usize) -> std::fmt::Result {
+    let fds = (0..nfds).filter(|fd| {
+        let word = words.get(fd / u64::BITS as usize).copied().unwrap_or(0);
+        word & (1 << (fd % u64::BITS as usize)) != 0
+    });
+
+    write!(f, "[")?;
+    for (i, fd) in fds.enumerate() {
+        if i != 0 {
+            write!(f, " ")?;
+        }
+        write!(f, "{fd}")?;
+    }
+    write!(f, "]")
+}
+
+/// Format an epoll event (ex: `{events=EPOLLIN|EPOLLET, data=0x5}`).
+fn fmt_epoll_event(
+    f: &mut std::fmt::Formatter<'_>,
+    event: &linux_api::epoll::epoll_event,
+) -> std::fmt::Result {
+    // copy the fields since the struct is packed
+    let events = linux_api::epoll::EpollEvents::from_bits_retain(event.events);
+    let data = event.data;
+
+    write!(f, "{{events=")?;
+    fmt_flags(f, &events)?;
+    write!(f, ", data={data:#x}}}")
+}
+
+/// Format a poll fd (ex: `{fd=5, events=POLLIN, revents=POLLIN}`).
+fn fmt_pollfd(
+    f: &mut std::fmt::Formatter<'_>,
+    pollfd: &linux_api::poll::pollfd,
+) -> std::fmt::Result {
+    let events = linux_api::poll::PollEvents::from_bits_retain(pollfd.events);
+    let revents = linux_api::poll::PollEvents::from_bits_retain(pollfd.revents);
+
+    write!(f, "{{fd={}, events=", pollfd.fd)?;
+    fmt_flags(f, &events)?;
+    write!(f, ", revents=")?;
+    fmt_flags(f, &revents)?;
+    write!(f, "}}")
+}
+
+/// Format a list of poll fds. If `len` is larger than the number of poll fds given, the list is
+/// shown as truncated.
+fn fmt_pollfds(
+    f: &mut std::fmt::Formatter<'_>,
+    pollfds: &[linux_api::poll::pollfd],
+    len: usize,
+) -> std::fmt::Result {
+    write!(f, "[")?;
+    for (i, pollfd) in pollfds.iter().enumerate() {
+        if i != 0 {
+            write!(f, ", ")?;
+        }
+        fmt_pollfd(f, pollfd)?;
+    }
+    if len > pollfds.len() {
+        write!(f, ", ...")?;
+    }
+    write!(f, "]")
+}
+
 fn fmt_buffer(
     f: &mut std::fmt::Formatter<'_>,
     ptr: ForeignPtr<u8>,
@@ -394,6 +717,208 @@ impl<const LEN_INDEX: usize> SyscallDisplay for SyscallVal<'_, SyscallBufferArg<
     }
 }
 
+/// Displays the combined flags and exit signal argument of the `clone` syscall. The low byte
+/// (`CSIGNAL`) holds the exit signal, and the remaining bits hold the clone flags.
+pub struct SyscallCloneFlagsArg {}
+
+impl SyscallDisplay for SyscallVal<'_, SyscallCloneFlagsArg> {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        _options: FmtOptions,
+        _mem: &MemoryManager,
+    ) -> std::fmt::Result {
+        let raw = u64::from(self.reg) & 0xffff_ffff;
+        let flags = linux_api::sched::CloneFlags::from_bits_retain(raw & !0xff);
+        fmt_clone_flags(f, flags, (raw & 0xff) as i32)
+    }
+}
+
+/// Displays the `pollfd` array argument of `poll`/`ppoll`. Only the first few entries are shown.
+pub struct SyscallPollFdsArg<const NFDS_INDEX: usize> {}
+
+impl<const NFDS_INDEX: usize> SyscallDisplay for SyscallVal<'_, SyscallPollFdsArg<NFDS_INDEX>> {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        options: FmtOptions,
+        mem: &MemoryManager,
+    ) -> std::fmt::Result {
+        const DISPLAY_LEN: usize = 8;
+
+        let ptr = ForeignPtr::<linux_api::poll::pollfd>::from(self.reg);
+
+        if ptr.is_null() {
+            return write!(f, "NULL");
+        }
+
+        let nfds: std::ffi::c_uint = self.args[NFDS_INDEX].into();
+        let nfds = usize::try_from(nfds).unwrap();
+        let display_len = std::cmp::min(nfds, DISPLAY_LEN);
+
+        let Ok(pollfds) = mem.memory_ref(ForeignArrayPtr::new(ptr, display_len)) else {
+            return match options {
+                FmtOptions::Standard => fmt_ptr_with_suffix(f, ptr, "<invalid-read>"),
+                FmtOptions::Deterministic => write!(f, "<pointer>"),
+            };
+        };
+
+        fmt_pollfds(f, &pollfds, nfds)?;
+
+        // the poll fds are deterministic, but the pointer isn't
+        match options {
+            FmtOptions::Standard => write!(f, " ({ptr:p})"),
+            FmtOptions::Deterministic => Ok(()),
+        }
+    }
+}
+
+/// Displays a signal number argument.
+pub struct SyscallSignalArg {}
+
+impl SyscallDisplay for SyscallVal<'_, SyscallSignalArg> {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        _options: FmtOptions,
+        _mem: &MemoryManager,
+    ) -> std::fmt::Result {
+        fmt_signal(f, self.reg.into())
+    }
+}
+
+/// Displays the `op` argument of the `futex` syscall.
+pub struct SyscallFutexOpArg {}
+
+impl SyscallDisplay for SyscallVal<'_, SyscallFutexOpArg> {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        _options: FmtOptions,
+        _mem: &MemoryManager,
+    ) -> std::fmt::Result {
+        fmt_futex_op(f, self.reg.into())
+    }
+}
+
+/// Displays the `cmd` argument of the `ioctl` syscall.
+pub struct SyscallIoctlRequestArg {}
+
+impl SyscallDisplay for SyscallVal<'_, SyscallIoctlRequestArg> {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        _options: FmtOptions,
+        _mem: &MemoryManager,
+    ) -> std::fmt::Result {
+        // the request is a 32-bit value, even though the syscall takes an `unsigned long`
+        fmt_ioctl_request(f, u64::from(self.reg) as u32)
+    }
+}
+
+/// Displays the `utime` argument of the `futex` syscall. Depending on the futex operation, this is
+/// either a timeout pointer or the `val2` integer.
+pub struct SyscallFutexTimeoutArg<const OP_INDEX: usize> {}
+
+impl<const OP_INDEX: usize> SyscallDisplay for SyscallVal<'_, SyscallFutexTimeoutArg<OP_INDEX>> {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        options: FmtOptions,
+        mem: &MemoryManager,
+    ) -> std::fmt::Result {
+        use linux_api::futex::FutexOpFlags;
+
+        match futex_cmd(self.args[OP_INDEX]) {
+            FutexOpFlags::FUTEX_WAIT
+            | FutexOpFlags::FUTEX_WAIT_BITSET
+            | FutexOpFlags::FUTEX_LOCK_PI
+            | FutexOpFlags::FUTEX_LOCK_PI2
+            | FutexOpFlags::FUTEX_WAIT_REQUEUE_PI => {
+                let val = SyscallVal::<*const linux_api::time::kernel_timespec>::new(
+                    self.reg, self.args, options, mem,
+                );
+                SyscallDisplay::fmt(&val, f, options, mem)
+            }
+            FutexOpFlags::FUTEX_REQUEUE
+            | FutexOpFlags::FUTEX_CMP_REQUEUE
+            | FutexOpFlags::FUTEX_CMP_REQUEUE_PI
+            | FutexOpFlags::FUTEX_WAKE_OP => write!(f, "{}", u32::from(self.reg)),
+            _ => {
+                let val =
+                    SyscallVal::<*const std::ffi::c_void>::new(self.reg, self.args, options, mem);
+                SyscallDisplay::fmt(&val, f, options, mem)
+            }
+        }
+    }
+}
+
+/// Displays the `val3` argument of the `futex` syscall. For the bitset operations this is a
+/// bitmask and is shown in hex.
+pub struct SyscallFutexVal3Arg<const OP_INDEX: usize> {}
+
+impl<const OP_INDEX: usize> SyscallDisplay for SyscallVal<'_, SyscallFutexVal3Arg<OP_INDEX>> {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        _options: FmtOptions,
+        _mem: &MemoryManager,
+    ) -> std::fmt::Result {
+        use linux_api::futex::FutexOpFlags;
+
+        let val3 = u32::from(self.reg);
+        match futex_cmd(self.args[OP_INDEX]) {
+            FutexOpFlags::FUTEX_WAIT_BITSET | FutexOpFlags::FUTEX_WAKE_BITSET => {
+                write!(f, "{val3:#x}")
+            }
+            _ => write!(f, "{val3}"),
+        }
+    }
+}
+
+/// Displays an `fd_set` pointer argument of `select`/`pselect6`. The `nfds` argument bounds how
+/// much of the bitmap is read.
+pub struct SyscallFdSetArg<const NFDS_INDEX: usize> {}
+
+impl<const NFDS_INDEX: usize> SyscallDisplay for SyscallVal<'_, SyscallFdSetArg<NFDS_INDEX>> {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        options: FmtOptions,
+        mem: &MemoryManager,
+    ) -> std::fmt::Result {
+        let ptr = ForeignPtr::<u64>::from(self.reg);
+
+        if ptr.is_null() {
+            return write!(f, "NULL");
+        }
+
+        let nfds: std::ffi::c_int = self.args[NFDS_INDEX].into();
+        let Ok(nfds) = usize::try_from(nfds) else {
+            return match options {
+                FmtOptions::Standard => fmt_ptr_with_suffix(f, ptr, "<invalid-nfds>"),
+                FmtOptions::Deterministic => write!(f, "<pointer>"),
+            };
+        };
+
+        let num_words = nfds.div_ceil(u64::BITS as usize);
+        let Ok(words) = mem.memory_ref(ForeignArrayPtr::new(ptr, num_words)) else {
+            return match options {
+                FmtOptions::Standard => fmt_ptr_with_suffix(f, ptr, "<invalid-read>"),
+                FmtOptions::Deterministic => write!(f, "<pointer>"),
+            };
+        };
+
+        fmt_fd_set(f, &words, nfds)?;
+
+        // the fds are deterministic, but the pointer isn't
+        match options {
+            FmtOptions::Standard => write!(f, " ({ptr:p})"),
+            FmtOptions::Deterministic => Ok(()),
+        }
+    }
+}
+
 /// Displays a nul-terminated string syscall argument.
 pub struct SyscallStringArg {}
 
@@ -437,6 +962,268 @@ impl<const LEN_INDEX: usize> SyscallDisplay for SyscallVal<'_, SyscallSockAddrAr
     }
 }
 
+impl SyscallDisplay for SyscallVal<'_, *const linux_api::sched::clone_args> {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        options: FmtOptions,
+        mem: &MemoryManager,
+    ) -> std::fmt::Result {
+        let ptr: ForeignPtr<linux_api::sched::clone_args> = self.reg.into();
+
+        let Ok(args) = mem.read(ptr) else {
+            return match options {
+                FmtOptions::Standard => fmt_ptr_with_suffix(f, ptr, "<invalid-read>"),
+                FmtOptions::Deterministic => write!(f, "<pointer>"),
+            };
+        };
+
+        let flags = linux_api::sched::CloneFlags::from_bits_retain(args.flags);
+        let flags = DebugFormatter(move |fmt| fmt_flags(fmt, &flags));
+        let stack = DebugFormatter(move |fmt| match options {
+            FmtOptions::Standard => write!(fmt, "{:#x}", args.stack),
+            FmtOptions::Deterministic => write!(fmt, "<pointer>"),
+        });
+
+        let exit_signal = DebugFormatter(move |fmt| match i32::try_from(args.exit_signal) {
+            Ok(sig) => fmt_signal(fmt, sig),
+            Err(_) => write!(fmt, "{}", args.exit_signal),
+        });
+
+        write!(
+            f,
+            "{{flags={flags:?}, stack={stack:?}, stack_size={:#x}, exit_signal={exit_signal:?}}}",
+            args.stack_size,
+        )?;
+
+        match options {
+            FmtOptions::Standard => write!(f, " ({ptr:p})"),
+            FmtOptions::Deterministic => Ok(()),
+        }
+    }
+}
+
+impl SyscallDisplay for SyscallVal<'_, *const linux_api::stat::stat> {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        options: FmtOptions,
+        mem: &MemoryManager,
+    ) -> std::fmt::Result {
+        let ptr: ForeignPtr<linux_api::stat::stat> = self.reg.into();
+
+        // the buffer is only written if the syscall was successful
+        let stat = match self.rv {
+            Some(_) => mem.read(ptr).ok(),
+            None => None,
+        };
+
+        let Some(stat) = stat else {
+            return match options {
+                FmtOptions::Standard if self.rv.is_some() => {
+                    fmt_ptr_with_suffix(f, ptr, "<invalid-read>")
+                }
+                FmtOptions::Standard => write!(f, "{ptr:p}"),
+                FmtOptions::Deterministic => write!(f, "<pointer>"),
+            };
+        };
+
+        fmt_stat_fields(
+            f,
+            stat.st_mode,
+            stat.st_size as u64,
+            stat.st_ino,
+            stat.st_nlink,
+            options,
+        )?;
+
+        match options {
+            FmtOptions::Standard => write!(f, " ({ptr:p})"),
+            FmtOptions::Deterministic => Ok(()),
+        }
+    }
+}
+
+impl SyscallDisplay for SyscallVal<'_, *const libc::statx> {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        options: FmtOptions,
+        mem: &MemoryManager,
+    ) -> std::fmt::Result {
+        let ptr: ForeignPtr<libc::statx> = self.reg.into();
+
+        // the buffer is only written if the syscall was successful
+        let stat = match self.rv {
+            Some(_) => mem.read(ptr).ok(),
+            None => None,
+        };
+
+        let Some(stat) = stat else {
+            return match options {
+                FmtOptions::Standard if self.rv.is_some() => {
+                    fmt_ptr_with_suffix(f, ptr, "<invalid-read>")
+                }
+                FmtOptions::Standard => write!(f, "{ptr:p}"),
+                FmtOptions::Deterministic => write!(f, "<pointer>"),
+            };
+        };
+
+        fmt_stat_fields(
+            f,
+            stat.stx_mode.into(),
+            stat.stx_size,
+            stat.stx_ino,
+            stat.stx_nlink.into(),
+            options,
+        )?;
+
+        match options {
+            FmtOptions::Standard => write!(f, " ({ptr:p})"),
+            FmtOptions::Deterministic => Ok(()),
+        }
+    }
+}
+
+impl SyscallDisplay for SyscallVal<'_, *const linux_api::epoll::epoll_event> {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        options: FmtOptions,
+        mem: &MemoryManager,
+    ) -> std::fmt::Result {
+        let ptr: ForeignPtr<linux_api::epoll::epoll_event> = self.reg.into();
+
+        if options == FmtOptions::Deterministic {
+            return write!(f, "<pointer>");
+        }
+
+        let Ok(event) = mem.read(ptr) else {
+            return fmt_ptr_with_suffix(f, ptr, "<invalid-read>");
+        };
+
+        fmt_epoll_event(f, &event)?;
+        write!(f, " ({ptr:p})")
+    }
+}
+
+/// Displays the events array written by `epoll_wait` and similar syscalls. Only the events returned
+/// by the syscall are shown, up to a small limit.
+pub struct SyscallEpollEventsArg {}
+
+impl SyscallDisplay for SyscallVal<'_, SyscallEpollEventsArg> {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        options: FmtOptions,
+        mem: &MemoryManager,
+    ) -> std::fmt::Result {
+        const DISPLAY_LEN: usize = 4;
+
+        let ptr: ForeignPtr<linux_api::epoll::epoll_event> = self.reg.into();
+
+        if options == FmtOptions::Deterministic {
+            return write!(f, "<pointer>");
+        }
+
+        // the number of events written by the syscall; if the syscall failed there are none
+        let num_events = self
+            .rv
+            .and_then(|rv| usize::try_from(std::ffi::c_int::from(rv)).ok())
+            .unwrap_or(0);
+
+        if num_events == 0 {
+            return write!(f, "{ptr:p}");
+        }
+
+        let display_len = std::cmp::min(num_events, DISPLAY_LEN);
+        let Ok(events) = mem.memory_ref(ForeignArrayPtr::new(ptr, display_len)) else {
+            return fmt_ptr_with_suffix(f, ptr, "<invalid-read>");
+        };
+
+        write!(f, "[")?;
+        for (i, event) in events.iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            fmt_epoll_event(f, event)?;
+        }
+        if num_events > display_len {
+            write!(f, ", ...")?;
+        }
+        write!(f, "] ({ptr:p})")
+    }
+}
+
+impl SyscallDisplay for SyscallVal<'_, *const linux_api::signal::sigset_t> {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        options: FmtOptions,
+        mem: &MemoryManager,
+    ) -> std::fmt::Result {
+        let ptr: ForeignPtr<linux_api::signal::sigset_t> = self.reg.into();
+
+        if ptr.is_null() {
+            return write!(f, "NULL");
+        }
+
+        let Ok(set) = mem.read(ptr) else {
+            return match options {
+                FmtOptions::Standard => fmt_ptr_with_suffix(f, ptr, "<invalid-read>"),
+                FmtOptions::Deterministic => write!(f, "<pointer>"),
+            };
+        };
+
+        fmt_sigset(f, &set)?;
+
+        // the signals are deterministic, but the pointer isn't
+        match options {
+            FmtOptions::Standard => write!(f, " ({ptr:p})"),
+            FmtOptions::Deterministic => Ok(()),
+        }
+    }
+}
+
+/// Displays the last argument of `pselect6`, which is a pointer to a `{ const sigset_t *ss; size_t
+/// ss_len; }` struct.
+pub struct SyscallPselectSigmaskArg {}
+
+impl SyscallDisplay for SyscallVal<'_, SyscallPselectSigmaskArg> {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        options: FmtOptions,
+        mem: &MemoryManager,
+    ) -> std::fmt::Result {
+        let ptr = ForeignPtr::<u64>::from(self.reg);
+
+        if ptr.is_null() {
+            return write!(f, "NULL");
+        }
+
+        let Ok(vals) = mem.memory_ref(ForeignArrayPtr::new(ptr, 2)) else {
+            return match options {
+                FmtOptions::Standard => fmt_ptr_with_suffix(f, ptr, "<invalid-read>"),
+                FmtOptions::Deterministic => write!(f, "<pointer>"),
+            };
+        };
+
+        let ss = SyscallVal::<*const linux_api::signal::sigset_t>::new(
+            SyscallReg::from(vals[0]),
+            self.args,
+            options,
+            mem,
+        );
+        write!(f, "{{ss={ss}, ss_len={}}}", vals[1])?;
+
+        match options {
+            FmtOptions::Standard => write!(f, " ({ptr:p})"),
+            FmtOptions::Deterministic => Ok(()),
+        }
+    }
+}
+
 impl SyscallDisplay for SyscallVal<'_, *const libc::msghdr> {
     fn fmt(
         &self,
@@ -465,3 +1252,227 @@ impl SyscallDisplay for SyscallVal<'_, *const libc::msghdr> {
         write!(f, " ({:p})", ptr.ptr())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use linux_api::sched::CloneFlags;
+
+    use super::*;
+
+    #[test]
+    fn test_clone_flags_pthread_create() {
+        // the flags that glibc uses for `pthread_create`
+        let flags = CloneFlags::CLONE_VM
+            | CloneFlags::CLONE_FS
+            | CloneFlags::CLONE_FILES
+            | CloneFlags::CLONE_SIGHAND
+            | CloneFlags::CLONE_THREAD
+            | CloneFlags::CLONE_SYSVSEM
+            | CloneFlags::CLONE_SETTLS
+            | CloneFlags::CLONE_PARENT_SETTID
+            | CloneFlags::CLONE_CHILD_CLEARTID;
+
+        let formatted = format!("{:?}", DebugFormatter(|f| fmt_clone_flags(f, flags, 0)));
+        assert_eq!(
+            formatted,
+            "CLONE_VM|CLONE_FS|CLONE_FILES|CLONE_SIGHAND|CLONE_THREAD|CLONE_SYSVSEM|CLONE_SETTLS|\
+             CLONE_PARENT_SETTID|CLONE_CHILD_CLEARTID"
+        );
+    }
+
+    #[test]
+    fn test_clone_flags_exit_signal() {
+        let flags = CloneFlags::CLONE_CHILD_SETTID | CloneFlags::CLONE_CHILD_CLEARTID;
+        let formatted = format!("{:?}", DebugFormatter(|f| fmt_clone_flags(f, flags, 17)));
+        assert_eq!(formatted, "CLONE_CHILD_CLEARTID|CLONE_CHILD_SETTID|SIGCHLD");
+
+        let formatted = format!(
+            "{:?}",
+            DebugFormatter(|f| fmt_clone_flags(f, CloneFlags::empty(), 17))
+        );
+        assert_eq!(formatted, "SIGCHLD");
+    }
+
+    #[test]
+    fn test_signal() {
+        let fmt = |sig: i32| format!("{:?}", DebugFormatter(|f| fmt_signal(f, sig)));
+
+        assert_eq!(fmt(15), "SIGTERM");
+        assert_eq!(fmt(9), "SIGKILL");
+        assert_eq!(
+            fmt(linux_api::signal::Signal::SIGRT_MIN.as_i32()),
+            "SIGRTMIN"
+        );
+        assert_eq!(
+            fmt(linux_api::signal::Signal::SIGRT_MIN.as_i32() + 3),
+            "SIGRTMIN+3"
+        );
+        assert_eq!(fmt(0), "0");
+        assert_eq!(fmt(100), "100");
+    }
+
+    #[test]
+    fn test_sigset() {
+        use linux_api::signal::{sigset_t, Signal};
+
+        let fmt = |set: sigset_t| format!("{:?}", DebugFormatter(|f| fmt_sigset(f, &set)));
+
+        let set = sigset_t::from(Signal::SIGINT) | sigset_t::from(Signal::SIGTERM);
+        assert_eq!(fmt(set), "[SIGINT SIGTERM]");
+
+        let set = sigset_t::from(Signal::SIGCHLD) | sigset_t::from(Signal::SIGRT_MAX);
+        assert_eq!(fmt(set), "[SIGCHLD SIGRTMIN+32]");
+
+        assert_eq!(fmt(sigset_t::EMPTY), "[]");
+    }
+
+    #[test]
+    fn test_pollfds() {
+        use linux_api::poll::{pollfd, PollEvents};
+
+        let pollfds = [
+            pollfd {
+                fd: 5,
+                events: PollEvents::POLLIN.bits(),
+                revents: PollEvents::POLLIN.bits(),
+            },
+            pollfd {
+                fd: 6,
+                events: (PollEvents::POLLIN | PollEvents::POLLOUT).bits(),
+                revents: 0,
+            },
+        ];
+
+        let formatted = format!("{:?}", DebugFormatter(|f| fmt_pollfds(f, &pollfds, 2)));
+        assert_eq!(
+            formatted,
+            "[{fd=5, events=POLLIN, revents=POLLIN}, {fd=6, events=POLLIN|POLLOUT, revents=0}]"
+        );
+
+        let formatted = format!("{:?}", DebugFormatter(|f| fmt_pollfds(f, &pollfds[..1], 2)));
+        assert_eq!(formatted, "[{fd=5, events=POLLIN, revents=POLLIN}, ...]");
+    }
+
+    #[test]
+    fn test_futex_op_private() {
+        use linux_api::futex::FutexOpFlags;
+
+        let op = |op: FutexOpFlags| format!("{:?}", DebugFormatter(|f| fmt_futex_op(f, op.bits())));
+
+        assert_eq!(
+            op(FutexOpFlags::FUTEX_WAIT | FutexOpFlags::FUTEX_PRIVATE_FLAG),
+            "FUTEX_WAIT_PRIVATE"
+        );
+        assert_eq!(
+            op(FutexOpFlags::FUTEX_WAKE | FutexOpFlags::FUTEX_PRIVATE_FLAG),
+            "FUTEX_WAKE_PRIVATE"
+        );
+        assert_eq!(op(FutexOpFlags::FUTEX_WAKE), "FUTEX_WAKE");
+        assert_eq!(
+            op(FutexOpFlags::FUTEX_WAIT_BITSET
+                | FutexOpFlags::FUTEX_PRIVATE_FLAG
+                | FutexOpFlags::FUTEX_CLOCK_REALTIME),
+            "FUTEX_WAIT_BITSET_PRIVATE|FUTEX_CLOCK_REALTIME"
+        );
+        assert_eq!(op(FutexOpFlags::from_bits_retain(100)), "0x64");
+    }
+
+    #[test]
+    fn test_ioctl_request() {
+        use linux_api::ioctls::IoctlRequest;
+
+        let fmt = |cmd: u32| format!("{:?}", DebugFormatter(|f| fmt_ioctl_request(f, cmd)));
+
+        assert_eq!(fmt(IoctlRequest::FIONBIO.into()), "FIONBIO");
+        assert_eq!(fmt(IoctlRequest::TIOCGWINSZ.into()), "TIOCGWINSZ");
+        assert_eq!(fmt(IoctlRequest::SIOCGIFADDR.into()), "SIOCGIFADDR");
+
+        // _IOR('T', 0x99, u32)
+        assert_eq!(fmt(0x8004_5499), "_IOC(_IOC_READ, 0x54, 0x99, 0x4)");
+        // _IOWR('V', 0x01, 16 bytes)
+        assert_eq!(
+            fmt(0xc010_5601),
+            "_IOC(_IOC_READ|_IOC_WRITE, 0x56, 0x1, 0x10)"
+        );
+        assert_eq!(fmt(0x0000_5699), "_IOC(_IOC_NONE, 0x56, 0x99, 0x0)");
+    }
+
+    #[test]
+    fn test_stat_regular_file() {
+        let mut stat: linux_api::stat::stat = shadow_pod::zeroed();
+        stat.st_mode = linux_api::stat::SFlag::S_IFREG.bits() | 0o644;
+        stat.st_size = 1234;
+        stat.st_ino = 5678;
+        stat.st_nlink = 1;
+
+        let fmt = |options| {
+            format!(
+                "{:?}",
+                DebugFormatter(|f| fmt_stat_fields(
+                    f,
+                    stat.st_mode,
+                    stat.st_size as u64,
+                    stat.st_ino,
+                    stat.st_nlink,
+                    options
+                ))
+            )
+        };
+
+        assert_eq!(
+            fmt(FmtOptions::Standard),
+            "{st_mode=S_IFREG|0644, st_size=1234, st_ino=5678, st_nlink=1}"
+        );
+        assert_eq!(
+            fmt(FmtOptions::Deterministic),
+            "{st_mode=S_IFREG|0644, st_size=1234, st_nlink=1}"
+        );
+    }
+
+    #[test]
+    fn test_stat_mode() {
+        let fmt = |mode: u32| format!("{:?}", DebugFormatter(|f| fmt_stat_mode(f, mode)));
+
+        assert_eq!(fmt(0o040755), "S_IFDIR|0755");
+        assert_eq!(fmt(0o104755), "S_IFREG|04755");
+        assert_eq!(fmt(0o600), "0600");
+    }
+
+    #[test]
+    fn test_fd_set() {
+        let fmt =
+            |words: &[u64], nfds| format!("{:?}", DebugFormatter(|f| fmt_fd_set(f, words, nfds)));
+
+        let words = [(1 << 3) | (1 << 4) | (1 << 7), 1 << 2];
+        assert_eq!(fmt(&words, 8), "[3 4 7]");
+        assert_eq!(fmt(&words, 5), "[3 4]");
+        assert_eq!(fmt(&words, 67), "[3 4 7 66]");
+        assert_eq!(fmt(&words, 0), "[]");
+    }
+
+    #[test]
+    fn test_epoll_event() {
+        use linux_api::epoll::{epoll_event, EpollEvents};
+
+        let event = epoll_event {
+            events: (EpollEvents::EPOLLIN | EpollEvents::EPOLLOUT).bits(),
+            data: 42,
+        };
+
+        let formatted = format!("{:?}", DebugFormatter(|f| fmt_epoll_event(f, &event)));
+        assert_eq!(formatted, "{events=EPOLLIN|EPOLLOUT, data=0x2a}");
+    }
+
+    #[test]
+    fn test_clone_flags_unknown_bits() {
+        let flags = CloneFlags::CLONE_VM | CloneFlags::from_bits_retain(1 << 40);
+        let formatted = format!("{:?}", DebugFormatter(|f| fmt_flags(f, &flags)));
+        assert_eq!(formatted, "CLONE_VM|0x10000000000");
+
+        let formatted = format!(
+            "{:?}",
+            DebugFormatter(|f| fmt_flags(f, &CloneFlags::empty()))
+        );
+        assert_eq!(formatted, "0");
+    }
+}
@@ -28,7 +28,15 @@ typedef enum {
     // debugging purposes, so that it doesn't appear that the managed code
     // issues a SYS_sched_yield.
     SYS_shadow_yield = 1005,
-    SYS_shadow_max = 1005,
+    // Writes the current simulation time (nanoseconds since the simulation
+    // started) as a `uint64_t` to the pointer given as the first argument.
+    SYS_shadow_get_sim_time = 1006,
+    // Records a hash of the host's state (simulation time and number of pending
+    // events) with the `uint64_t` id given as the first argument. The hashes are
+    // written to the host's data directory when the host shuts down, so that
+    // they can be compared across simulations.
+    SYS_shadow_checkpoint = 1007,
+    SYS_shadow_max = 1007,
 } ShadowSyscallNum;
 
 static inline bool syscall_num_is_shadow(long n) {
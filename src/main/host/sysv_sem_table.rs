@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+
+use linux_api::errno::Errno;
+use linux_api::ipc::{
+    sembuf, IPC_CREAT, IPC_EXCL, IPC_NOWAIT, IPC_PRIVATE, SEMMSL, SEMVMX, SEM_UNDO,
+};
+
+use crate::cshadow as c;
+use crate::host::futex_table::FutexRef;
+use crate::host::process::ProcessId;
+use crate::utility::ObjectCounter;
+
+/// A System V semaphore set, as created by semget(2).
+struct SemSet {
+    key: i32,
+    values: Vec<u16>,
+    /// Woken whenever a semaphore in the set changes, so that blocked operations can try again.
+    /// Created when the first operation needs to wait.
+    wakeup: Option<FutexRef>,
+}
+
+impl SemSet {
+    fn wake_waiters(&mut self) {
+        // the waiters will re-register if they still can't complete their operations
+        if let Some(wakeup) = self.wakeup.take() {
+            wakeup.wake(libc::c_uint::MAX);
+        }
+    }
+}
+
+/// The System V semaphore sets of a host. Sets are shared between all processes of the host.
+pub struct SysvSemTable {
+    sets: HashMap<i32, SemSet>,
+    /// The set id for each key, excluding `IPC_PRIVATE`.
+    keys: HashMap<i32, i32>,
+    /// The adjustments to apply to each semaphore of a set when a process exits, from operations
+    /// that used `SEM_UNDO`.
+    undo: HashMap<(ProcessId, i32), Vec<i32>>,
+    next_id: i32,
+    _counter: ObjectCounter,
+}
+
+impl SysvSemTable {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            sets: HashMap::new(),
+            keys: HashMap::new(),
+            undo: HashMap::new(),
+            next_id: 0,
+            _counter: ObjectCounter::new("SysvSemTable"),
+        }
+    }
+
+    /// Look up or create a semaphore set as described in semget(2), and return its id.
+    pub fn get(&mut self, key: i32, nsems: i32, flags: i32) -> Result<i32, Errno> {
+        if !(0..=SEMMSL).contains(&nsems) {
+            return Err(Errno::EINVAL);
+        }
+
+        if key != IPC_PRIVATE {
+            if let Some(semid) = self.keys.get(&key) {
+                if flags & IPC_CREAT != 0 && flags & IPC_EXCL != 0 {
+                    return Err(Errno::EEXIST);
+                }
+                if nsems as usize > self.sets[semid].values.len() {
+                    return Err(Errno::EINVAL);
+                }
+                return Ok(*semid);
+            }
+
+            if flags & IPC_CREAT == 0 {
+                return Err(Errno::ENOENT);
+            }
+        }
+
+        if nsems == 0 {
+            return Err(Errno::EINVAL);
+        }
+
+        let semid = self.next_id;
+        self.next_id = self.next_id.checked_add(1).ok_or(Errno::ENOSPC)?;
+
+        self.sets.insert(
+            semid,
+            SemSet {
+                key,
+                values: vec![0; nsems as usize],
+                wakeup: None,
+            },
+        );
+        if key != IPC_PRIVATE {
+            self.keys.insert(key, semid);
+        }
+
+        Ok(semid)
+    }
+
+    /// Returns `true` if a set with this id was created and has since been removed.
+    pub fn was_removed(&self, semid: i32) -> bool {
+        (0..self.next_id).contains(&semid) && !self.sets.contains_key(&semid)
+    }
+
+    /// Perform the operations atomically as described in semop(2). Returns `Ok(false)` if the
+    /// operations can't be completed yet and the caller should wait on
+    /// [`wakeup_futex`](Self::wakeup_futex) before trying again.
+    pub fn semop(&mut self, semid: i32, ops: &[sembuf], pid: ProcessId) -> Result<bool, Errno> {
+        let set = self.sets.get_mut(&semid).ok_or(Errno::EINVAL)?;
+
+        if ops
+            .iter()
+            .any(|op| usize::from(op.sem_num) >= set.values.len())
+        {
+            return Err(Errno::EFBIG);
+        }
+
+        // apply the operations to a copy so that they're all-or-nothing
+        let mut values: Vec<i32> = set.values.iter().map(|x| i32::from(*x)).collect();
+        for op in ops {
+            let value = &mut values[usize::from(op.sem_num)];
+            let result = *value + i32::from(op.sem_op);
+
+            let would_block = match op.sem_op {
+                0 => *value != 0,
+                _ => result < 0,
+            };
+            if would_block {
+                if i32::from(op.sem_flg) & IPC_NOWAIT != 0 {
+                    return Err(Errno::EAGAIN);
+                }
+                return Ok(false);
+            }
+            if result > SEMVMX {
+                return Err(Errno::ERANGE);
+            }
+
+            *value = result;
+        }
+
+        for op in ops.iter().filter(|op| op.sem_flg & SEM_UNDO != 0) {
+            let nsems = set.values.len();
+            let undo = self
+                .undo
+                .entry((pid, semid))
+                .or_insert_with(|| vec![0; nsems]);
+            undo[usize::from(op.sem_num)] -= i32::from(op.sem_op);
+        }
+
+        let changed = ops.iter().any(|op| op.sem_op != 0);
+        for (dst, src) in set.values.iter_mut().zip(values) {
+            *dst = src.try_into().unwrap();
+        }
+
+        if changed {
+            set.wake_waiters();
+        }
+
+        Ok(true)
+    }
+
+    pub fn value(&self, semid: i32, semnum: i32) -> Result<i32, Errno> {
+        let set = self.sets.get(&semid).ok_or(Errno::EINVAL)?;
+        let value = usize::try_from(semnum)
+            .ok()
+            .and_then(|num| set.values.get(num))
+            .ok_or(Errno::EINVAL)?;
+        Ok(i32::from(*value))
+    }
+
+    /// Set the value of a semaphore, as with `SETVAL`.
+    pub fn set_value(&mut self, semid: i32, semnum: i32, value: i32) -> Result<(), Errno> {
+        let set = self.sets.get_mut(&semid).ok_or(Errno::EINVAL)?;
+        let num = usize::try_from(semnum)
+            .ok()
+            .filter(|num| *num < set.values.len())
+            .ok_or(Errno::EINVAL)?;
+        if !(0..=SEMVMX).contains(&value) {
+            return Err(Errno::ERANGE);
+        }
+
+        set.values[num] = value.try_into().unwrap();
+        set.wake_waiters();
+
+        // semctl(2): "Undo entries for the corresponding semaphore are cleared in all processes."
+        for ((_, id), undo) in self.undo.iter_mut() {
+            if *id == semid {
+                undo[num] = 0;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Remove the set immediately, as with `IPC_RMID`. Blocked operations are woken.
+    pub fn remove(&mut self, semid: i32) -> Result<(), Errno> {
+        let mut set = self.sets.remove(&semid).ok_or(Errno::EINVAL)?;
+        if set.key != IPC_PRIVATE {
+            self.keys.remove(&set.key);
+        }
+        self.undo.retain(|(_, id), _| *id != semid);
+        set.wake_waiters();
+        Ok(())
+    }
+
+    /// Apply the process's `SEM_UNDO` adjustments, which happens when it exits.
+    pub fn release_process(&mut self, pid: ProcessId) {
+        let semids: Vec<_> = self
+            .undo
+            .keys()
+            .filter(|(x, _)| *x == pid)
+            .map(|(_, semid)| *semid)
+            .collect();
+
+        for semid in semids {
+            let undo = self.undo.remove(&(pid, semid)).unwrap();
+            let set = self.sets.get_mut(&semid).unwrap();
+
+            // linux clamps the adjusted values rather than failing
+            for (num, adjustment) in undo.into_iter().enumerate() {
+                if adjustment == 0 {
+                    continue;
+                }
+                let value = (i32::from(set.values[num]) + adjustment).clamp(0, SEMVMX);
+                set.values[num] = value.try_into().unwrap();
+            }
+            set.wake_waiters();
+        }
+    }
+
+    /// The futex that's woken when a semaphore in the set changes. The set must exist.
+    pub fn wakeup_futex(&mut self, semid: i32) -> &FutexRef {
+        self.sets
+            .get_mut(&semid)
+            .unwrap()
+            .wakeup
+            .get_or_insert_with(|| {
+                // the futex is only used for its listeners, so its address doesn't matter
+                let futex = unsafe { c::futex_new(0usize.into()) };
+                unsafe { FutexRef::new(futex) }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn op(sem_num: u16, sem_op: i16, sem_flg: i16) -> sembuf {
+        sembuf {
+            sem_num,
+            sem_op,
+            sem_flg,
+        }
+    }
+
+    #[test]
+    fn test_semop() {
+        let mut table = SysvSemTable::new();
+        let pid = ProcessId::try_from(1000u32).unwrap();
+
+        let semid = table.get(IPC_PRIVATE, 2, 0o600).unwrap();
+        assert_eq!(table.semop(semid, &[op(2, 1, 0)], pid), Err(Errno::EFBIG));
+
+        // decrementing below zero waits, unless asked not to
+        assert_eq!(table.semop(semid, &[op(0, -1, 0)], pid), Ok(false));
+        assert_eq!(
+            table.semop(semid, &[op(0, -1, IPC_NOWAIT as i16)], pid),
+            Err(Errno::EAGAIN)
+        );
+
+        // operations are all-or-nothing
+        assert_eq!(
+            table.semop(semid, &[op(1, 1, 0), op(0, -1, 0)], pid),
+            Ok(false)
+        );
+        assert_eq!(table.value(semid, 1), Ok(0));
+
+        assert_eq!(
+            table.semop(semid, &[op(0, 2, 0), op(0, -1, 0)], pid),
+            Ok(true)
+        );
+        assert_eq!(table.value(semid, 0), Ok(1));
+
+        // waiting for zero
+        assert_eq!(table.semop(semid, &[op(0, 0, 0)], pid), Ok(false));
+        assert_eq!(table.semop(semid, &[op(1, 0, 0)], pid), Ok(true));
+
+        // undone when the process exits
+        assert_eq!(table.semop(semid, &[op(1, 3, SEM_UNDO)], pid), Ok(true));
+        assert_eq!(table.value(semid, 1), Ok(3));
+        table.release_process(pid);
+        assert_eq!(table.value(semid, 1), Ok(0));
+
+        table.remove(semid).unwrap();
+        assert!(table.was_removed(semid));
+        assert_eq!(table.value(semid, 0), Err(Errno::EINVAL));
+    }
+}
@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::fs::File;
+use std::os::fd::AsRawFd;
+use std::path::PathBuf;
+
+use linux_api::errno::Errno;
+use linux_api::ipc::{IPC_CREAT, IPC_EXCL, IPC_PRIVATE, SHMMAX, SHMMIN};
+use rustix::fs::MemfdFlags;
+use shadow_shim_helper_rs::emulated_time::EmulatedTime;
+
+use crate::host::memory_manager::page_size;
+use crate::host::process::ProcessId;
+use crate::utility::ObjectCounter;
+
+/// A System V shared memory segment, as created by shmget(2).
+pub struct ShmSegment {
+    key: i32,
+    size: usize,
+    /// Backs the segment's memory. Processes map it by opening it through procfs.
+    file: File,
+    mode: u32,
+    creator: ProcessId,
+    last_pid: ProcessId,
+    attach_time: Option<EmulatedTime>,
+    detach_time: Option<EmulatedTime>,
+    change_time: EmulatedTime,
+    attach_count: u64,
+    /// Set by `IPC_RMID`. The segment is destroyed once it's no longer attached.
+    removed: bool,
+}
+
+impl ShmSegment {
+    /// The key that the segment was created with, or `IPC_PRIVATE` if it has been removed.
+    pub fn key(&self) -> i32 {
+        if self.removed {
+            IPC_PRIVATE
+        } else {
+            self.key
+        }
+    }
+
+    /// The size requested when the segment was created.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The size of the segment when mapped, which is a whole number of pages.
+    pub fn mapped_size(&self) -> usize {
+        self.size.next_multiple_of(page_size())
+    }
+
+    /// A path that another process can open to map the segment.
+    pub fn path(&self) -> PathBuf {
+        PathBuf::from(format!(
+            "/proc/{}/fd/{}",
+            std::process::id(),
+            self.file.as_raw_fd()
+        ))
+    }
+
+    /// The permission bits, along with `SHM_DEST` if the segment has been removed.
+    pub fn mode(&self) -> u32 {
+        // from linux/shm.h
+        const SHM_DEST: u32 = 0o1000;
+
+        if self.removed {
+            self.mode | SHM_DEST
+        } else {
+            self.mode
+        }
+    }
+
+    pub fn creator(&self) -> ProcessId {
+        self.creator
+    }
+
+    /// The process that last attached or detached the segment.
+    pub fn last_pid(&self) -> ProcessId {
+        self.last_pid
+    }
+
+    pub fn attach_time(&self) -> Option<EmulatedTime> {
+        self.attach_time
+    }
+
+    pub fn detach_time(&self) -> Option<EmulatedTime> {
+        self.detach_time
+    }
+
+    pub fn change_time(&self) -> EmulatedTime {
+        self.change_time
+    }
+
+    pub fn attach_count(&self) -> u64 {
+        self.attach_count
+    }
+}
+
+/// A segment attached to a process's address space.
+#[derive(Copy, Clone, Debug)]
+struct Attachment {
+    shmid: i32,
+    len: usize,
+}
+
+/// The System V shared memory segments of a host. Segments are shared between all processes of
+/// the host.
+pub struct SysvShmTable {
+    segments: HashMap<i32, ShmSegment>,
+    /// The segment id for each key, excluding `IPC_PRIVATE` and removed segments.
+    keys: HashMap<i32, i32>,
+    /// The attached segments, keyed by the process and the address that the segment is mapped at.
+    attachments: HashMap<(ProcessId, usize), Attachment>,
+    next_id: i32,
+    _counter: ObjectCounter,
+}
+
+impl SysvShmTable {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            segments: HashMap::new(),
+            keys: HashMap::new(),
+            attachments: HashMap::new(),
+            next_id: 0,
+            _counter: ObjectCounter::new("SysvShmTable"),
+        }
+    }
+
+    /// Look up or create a segment as described in shmget(2), and return its id.
+    pub fn get(
+        &mut self,
+        key: i32,
+        size: usize,
+        flags: i32,
+        pid: ProcessId,
+        now: EmulatedTime,
+    ) -> Result<i32, Errno> {
+        if key != IPC_PRIVATE {
+            if let Some(shmid) = self.keys.get(&key) {
+                if flags & IPC_CREAT != 0 && flags & IPC_EXCL != 0 {
+                    return Err(Errno::EEXIST);
+                }
+                if size > self.segments[shmid].size {
+                    return Err(Errno::EINVAL);
+                }
+                return Ok(*shmid);
+            }
+
+            if flags & IPC_CREAT == 0 {
+                return Err(Errno::ENOENT);
+            }
+        }
+
+        if !(SHMMIN..=SHMMAX).contains(&size) {
+            return Err(Errno::EINVAL);
+        }
+
+        let shmid = self.next_id;
+        self.next_id = self.next_id.checked_add(1).ok_or(Errno::ENOSPC)?;
+
+        let create_file = || -> std::io::Result<File> {
+            let name = CString::new(format!("shadow_sysv_shm_{shmid}")).unwrap();
+            let file = File::from(rustix::fs::memfd_create(&name, MemfdFlags::CLOEXEC)?);
+            file.set_len(size.next_multiple_of(page_size()) as u64)?;
+            Ok(file)
+        };
+        let file = create_file().map_err(|e| {
+            log::warn!("Unable to create the file for a shared memory segment: {e}");
+            Errno::ENOMEM
+        })?;
+
+        let segment = ShmSegment {
+            key,
+            size,
+            file,
+            mode: (flags & 0o777) as u32,
+            creator: pid,
+            last_pid: pid,
+            attach_time: None,
+            detach_time: None,
+            change_time: now,
+            attach_count: 0,
+            removed: false,
+        };
+        self.segments.insert(shmid, segment);
+        if key != IPC_PRIVATE {
+            self.keys.insert(key, shmid);
+        }
+
+        Ok(shmid)
+    }
+
+    pub fn segment(&self, shmid: i32) -> Option<&ShmSegment> {
+        self.segments.get(&shmid)
+    }
+
+    /// Record that the segment was mapped at `addr` in process `pid`.
+    pub fn attach(&mut self, shmid: i32, pid: ProcessId, addr: usize, now: EmulatedTime) {
+        let segment = self.segments.get_mut(&shmid).unwrap();
+        segment.attach_count += 1;
+        segment.attach_time = Some(now);
+        segment.last_pid = pid;
+
+        let len = segment.mapped_size();
+        self.attachments
+            .insert((pid, addr), Attachment { shmid, len });
+    }
+
+    /// Record that the segment mapped at `addr` in process `pid` was unmapped. Returns the length
+    /// of the mapping, or `None` if no segment was attached at `addr`.
+    pub fn detach(&mut self, pid: ProcessId, addr: usize, now: EmulatedTime) -> Option<usize> {
+        let attachment = self.attachments.remove(&(pid, addr))?;
+
+        let segment = self.segments.get_mut(&attachment.shmid).unwrap();
+        segment.detach_time = Some(now);
+        segment.last_pid = pid;
+
+        self.release(attachment.shmid);
+        Some(attachment.len)
+    }
+
+    /// Mark the segment to be destroyed once it's no longer attached, as with `IPC_RMID`.
+    pub fn remove(&mut self, shmid: i32, now: EmulatedTime) -> Result<(), Errno> {
+        let segment = self.segments.get_mut(&shmid).ok_or(Errno::EINVAL)?;
+        if !segment.removed {
+            segment.removed = true;
+            segment.change_time = now;
+            if segment.key != IPC_PRIVATE {
+                self.keys.remove(&segment.key);
+            }
+        }
+
+        if segment.attach_count == 0 {
+            self.segments.remove(&shmid);
+        }
+
+        Ok(())
+    }
+
+    /// A forked child inherits the attached segments of its parent.
+    pub fn fork_process(&mut self, parent: ProcessId, child: ProcessId) {
+        let inherited: Vec<_> = self
+            .attachments
+            .iter()
+            .filter(|((pid, _), _)| *pid == parent)
+            .map(|((_, addr), attachment)| (*addr, *attachment))
+            .collect();
+
+        for (addr, attachment) in inherited {
+            self.segments
+                .get_mut(&attachment.shmid)
+                .unwrap()
+                .attach_count += 1;
+            self.attachments.insert((child, addr), attachment);
+        }
+    }
+
+    /// Detach all segments from a process, which happens when it exits or execs.
+    pub fn release_process(&mut self, pid: ProcessId) {
+        let addrs: Vec<_> = self
+            .attachments
+            .keys()
+            .filter(|(x, _)| *x == pid)
+            .map(|(_, addr)| *addr)
+            .collect();
+
+        for addr in addrs {
+            let attachment = self.attachments.remove(&(pid, addr)).unwrap();
+            self.release(attachment.shmid);
+        }
+    }
+
+    fn release(&mut self, shmid: i32) {
+        let segment = self.segments.get_mut(&shmid).unwrap();
+        segment.attach_count -= 1;
+
+        if segment.removed && segment.attach_count == 0 {
+            self.segments.remove(&shmid);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segment_lifetime() {
+        let mut table = SysvShmTable::new();
+        let pid = ProcessId::try_from(1000u32).unwrap();
+        let now = EmulatedTime::UNIX_EPOCH;
+
+        // a key must be created before it's looked up
+        assert_eq!(table.get(5, 100, 0, pid, now), Err(Errno::ENOENT));
+        let shmid = table.get(5, 100, IPC_CREAT | 0o600, pid, now).unwrap();
+        assert_eq!(table.get(5, 50, 0, pid, now), Ok(shmid));
+        assert_eq!(table.get(5, 200, 0, pid, now), Err(Errno::EINVAL));
+        assert_eq!(
+            table.get(5, 100, IPC_CREAT | IPC_EXCL, pid, now),
+            Err(Errno::EEXIST)
+        );
+
+        // private segments are always new
+        let private = table.get(IPC_PRIVATE, 100, 0, pid, now).unwrap();
+        assert_ne!(private, shmid);
+
+        table.attach(shmid, pid, 0x1000, now);
+        let child = ProcessId::try_from(1001u32).unwrap();
+        table.fork_process(pid, child);
+        assert_eq!(table.segment(shmid).unwrap().attach_count(), 2);
+
+        // a removed segment can't be looked up, but lives until it's detached
+        table.remove(shmid, now).unwrap();
+        assert_eq!(table.get(5, 100, 0, pid, now), Err(Errno::ENOENT));
+        assert_eq!(table.segment(shmid).unwrap().key(), IPC_PRIVATE);
+
+        assert_eq!(table.detach(pid, 0x1000, now), Some(page_size()));
+        assert_eq!(table.detach(pid, 0x1000, now), None);
+        assert!(table.segment(shmid).is_some());
+
+        table.release_process(child);
+        assert!(table.segment(shmid).is_none());
+    }
+}
@@ -7,7 +7,8 @@ use linux_api::errno::Errno;
 use linux_api::fcntl::DescriptorFlags;
 use linux_api::mman::{MapFlags, ProtFlags};
 use linux_api::posix_types::Pid;
-use linux_api::signal::stack_t;
+use linux_api::prctl::TASK_COMM_LEN;
+use linux_api::signal::{sigset_t, stack_t};
 use shadow_shim_helper_rs::explicit_drop::ExplicitDrop;
 use shadow_shim_helper_rs::rootedcell::rc::RootedRc;
 use shadow_shim_helper_rs::rootedcell::refcell::RootedRefCell;
@@ -61,6 +62,11 @@ pub struct Thread {
     // TODO: convert to SyscallCondition (Rust wrapper for c::SysCallCondition).
     // Non-trivial because SyscallCondition is currently not `Send`.
     cond: Cell<SendPointer<c::SysCallCondition>>,
+    /// The signal mask to restore once a blocked `rt_sigtimedwait` completes. While the thread is
+    /// waiting, the signals it's waiting for are temporarily unblocked so that they wake it up.
+    sigtimedwait_saved_mask: Cell<Option<sigset_t>>,
+    /// The thread's name (`comm` in linux), nul-padded. See `PR_SET_NAME` in prctl(2).
+    comm: Cell<[u8; TASK_COMM_LEN]>,
     /// The native, managed thread
     mthread: RefCell<ManagedThread>,
     _counter: ObjectCounter,
@@ -434,13 +440,15 @@ impl Thread {
     }
 
     /// Create a new `Thread`, wrapping `mthread`. Intended for use by
-    /// syscall handlers such as `clone`.
+    /// syscall handlers such as `clone`. The thread's name is initialized from `comm`, truncated
+    /// as described in [`Self::set_comm`].
     pub fn wrap_mthread(
         host: &Host,
         mthread: ManagedThread,
         desc_table: RootedRc<RootedRefCell<DescriptorTable>>,
         pid: ProcessId,
         tid: ThreadId,
+        comm: &[u8],
     ) -> Result<Thread, Errno> {
         let child = Self {
             mthread: RefCell::new(mthread),
@@ -449,6 +457,8 @@ impl Thread {
                 SyscallHandler::new(host.id(), pid, tid, host.params.use_syscall_counters),
             ),
             cond: Cell::new(unsafe { SendPointer::new(std::ptr::null_mut()) }),
+            sigtimedwait_saved_mask: Cell::new(None),
+            comm: Cell::new([0; TASK_COMM_LEN]),
             id: tid,
             host_id: host.id(),
             process_id: pid,
@@ -460,9 +470,28 @@ impl Thread {
             desc_table: Some(desc_table),
             _counter: ObjectCounter::new("Thread"),
         };
+        child.set_comm(comm);
         Ok(child)
     }
 
+    /// The thread's name, nul-padded to [`TASK_COMM_LEN`] bytes.
+    pub fn comm(&self) -> [u8; TASK_COMM_LEN] {
+        self.comm.get()
+    }
+
+    /// Set the thread's name. Like linux, the name is truncated at the first nul byte or at
+    /// `TASK_COMM_LEN - 1` bytes, whichever comes first, so that it's always nul-terminated.
+    pub fn set_comm(&self, name: &[u8]) {
+        let len = name
+            .iter()
+            .position(|c| *c == 0)
+            .unwrap_or(name.len())
+            .min(TASK_COMM_LEN - 1);
+        let mut comm = [0; TASK_COMM_LEN];
+        comm[..len].copy_from_slice(&name[..len]);
+        self.comm.set(comm);
+    }
+
     /// Shared memory for this thread.
     pub fn shmem(&self) -> &ShMemBlock<ThreadShmem> {
         &self.shim_shared_memory
@@ -530,6 +559,30 @@ impl Thread {
         self.tid_address.set(ptr)
     }
 
+    /// Temporarily unblock the signals in `mask` for the duration of a `rt_sigtimedwait`, saving
+    /// the current signal mask so that it can be restored by
+    /// [`Self::restore_sigtimedwait_mask`]. Does nothing if the mask has already been saved.
+    pub fn unblock_for_sigtimedwait(&self, host_shmem: &HostShmemProtected, mask: sigset_t) {
+        if self.sigtimedwait_saved_mask.get().is_some() {
+            return;
+        }
+
+        let mut thread_shmem_protected = self.shmem().protected.borrow_mut(&host_shmem.root);
+        self.sigtimedwait_saved_mask
+            .set(Some(thread_shmem_protected.blocked_signals));
+        thread_shmem_protected.blocked_signals &= !mask;
+    }
+
+    /// Restore the signal mask saved by [`Self::unblock_for_sigtimedwait`], if any.
+    pub fn restore_sigtimedwait_mask(&self, host_shmem: &HostShmemProtected) {
+        if let Some(mask) = self.sigtimedwait_saved_mask.take() {
+            self.shmem()
+                .protected
+                .borrow_mut(&host_shmem.root)
+                .blocked_signals = mask;
+        }
+    }
+
     pub fn unblocked_signal_pending(
         &self,
         process: &Process,
@@ -725,7 +778,8 @@ mod export {
     }
 
     /// Register a `Descriptor`. This takes ownership of the descriptor and you must not access it
-    /// after.
+    /// after. Returns `-EMFILE` (and closes the descriptor) if the process has reached its
+    /// `RLIMIT_NOFILE` limit.
     #[no_mangle]
     pub extern "C-unwind" fn thread_registerDescriptor(
         thread: *const Thread,
@@ -734,10 +788,36 @@ mod export {
         let thread = unsafe { thread.as_ref().unwrap() };
         let desc = Descriptor::from_raw(desc).unwrap();
 
+        Worker::with_active_host(|host| {
+            let res = thread
+                .descriptor_table_borrow_mut(host)
+                .register_descriptor(*desc);
+            match res {
+                Ok(handle) => handle.into(),
+                Err(desc) => {
+                    CallbackQueue::queue_and_run_with_legacy(|cb_queue| desc.close(host, cb_queue));
+                    -libc::EMFILE
+                }
+            }
+        })
+        .unwrap()
+    }
+
+    /// Register a `Descriptor`, ignoring the process's `RLIMIT_NOFILE` limit. This takes ownership
+    /// of the descriptor and you must not access it after. This should only be used for descriptors
+    /// that will be re-registered before they're returned to the managed process.
+    #[no_mangle]
+    pub extern "C-unwind" fn thread_registerDescriptorIgnoringLimit(
+        thread: *const Thread,
+        desc: *mut Descriptor,
+    ) -> libc::c_int {
+        let thread = unsafe { thread.as_ref().unwrap() };
+        let desc = Descriptor::from_raw(desc).unwrap();
+
         Worker::with_active_host(|host| {
             thread
                 .descriptor_table_borrow_mut(host)
-                .register_descriptor(*desc)
+                .register_descriptor_ignoring_limit(*desc)
                 .unwrap()
                 .into()
         })
@@ -115,6 +115,7 @@ pub fn run_shadow(args: Vec<&OsStr>) -> anyhow::Result<()> {
     shadow_logger::init(
         log_level.to_level_filter(),
         shadow_config.experimental.report_errors_to_stderr.unwrap(),
+        shadow_config.experimental.gzip_log_output.unwrap(),
     )
     .unwrap();
 
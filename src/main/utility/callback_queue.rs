@@ -152,12 +152,48 @@ impl<T: Clone + Copy + 'static> EventSource<T> {
         self.inner.borrow_mut().add_listener(inner_ref, notify_fn)
     }
 
-    /// Notify all listeners.
+    /// Add an exclusive listener. When notified, exclusive listeners are called in the order they
+    /// were added until one of them returns `true`, similar to exclusive waiters on a Linux wait
+    /// queue.
+    pub fn add_exclusive_listener(
+        &mut self,
+        notify_fn: impl Fn(T, &mut CallbackQueue) -> bool + Send + Sync + 'static,
+    ) -> Handle<T> {
+        let inner_ref = Arc::downgrade(&Arc::clone(&self.inner));
+        self.inner
+            .borrow_mut()
+            .add_exclusive_listener(inner_ref, notify_fn)
+    }
+
+    /// Returns `true` if there are any listeners, exclusive or not.
+    pub fn has_listeners(&self) -> bool {
+        let inner = self.inner.borrow();
+        !inner.listeners.is_empty() || !inner.exclusive_listeners.is_empty()
+    }
+
+    /// Notify all listeners, and the exclusive listeners until one of them accepts the event.
     pub fn notify_listeners(&mut self, message: T, cb_queue: &mut CallbackQueue) {
-        for (_, l) in &self.inner.borrow().listeners {
+        let inner = self.inner.borrow();
+
+        for (_, l) in &inner.listeners {
             let l_clone = l.clone();
             cb_queue.add(move |cb_queue| (l_clone)(message, cb_queue));
         }
+
+        if !inner.exclusive_listeners.is_empty() {
+            let exclusive: Vec<_> = inner
+                .exclusive_listeners
+                .iter()
+                .map(|(_, l)| l.clone())
+                .collect();
+            cb_queue.add(move |cb_queue| {
+                for l in exclusive {
+                    if (l)(message, cb_queue) {
+                        break;
+                    }
+                }
+            });
+        }
     }
 }
 
@@ -168,9 +204,11 @@ impl<T: Clone + Copy + 'static> Default for EventSource<T> {
 }
 
 type Listener<T> = Arc<dyn Fn(T, &mut CallbackQueue) + Send + Sync>;
+type ExclusiveListener<T> = Arc<dyn Fn(T, &mut CallbackQueue) -> bool + Send + Sync>;
 
 struct EventSourceInner<T> {
     listeners: Vec<(HandleId, Listener<T>)>,
+    exclusive_listeners: Vec<(HandleId, ExclusiveListener<T>)>,
     next_id: Wrapping<u32>,
 }
 
@@ -178,6 +216,7 @@ impl<T> EventSourceInner<T> {
     pub fn new() -> Self {
         Self {
             listeners: Vec::new(),
+            exclusive_listeners: Vec::new(),
             next_id: Wrapping(0),
         }
     }
@@ -189,7 +228,10 @@ impl<T> EventSourceInner<T> {
             let id = HandleId(self.next_id.0);
             self.next_id += Wrapping(1);
 
-            if !self.listeners.iter().any(|x| x.0 == id) {
+            let in_use = self.listeners.iter().any(|x| x.0 == id)
+                || self.exclusive_listeners.iter().any(|x| x.0 == id);
+
+            if !in_use {
                 break id;
             }
         }
@@ -207,9 +249,31 @@ impl<T> EventSourceInner<T> {
         Handle::new(handle_id, inner)
     }
 
+    pub fn add_exclusive_listener(
+        &mut self,
+        inner: std::sync::Weak<AtomicRefCell<Self>>,
+        notify_fn: impl Fn(T, &mut CallbackQueue) -> bool + Send + Sync + 'static,
+    ) -> Handle<T> {
+        let handle_id = self.get_unused_id();
+
+        self.exclusive_listeners
+            .push((handle_id, Arc::new(notify_fn)));
+
+        Handle::new(handle_id, inner)
+    }
+
     pub fn remove_listener(&mut self, id: HandleId) {
-        self.listeners
-            .remove(self.listeners.iter().position(|x| x.0 == id).unwrap());
+        if let Some(pos) = self.listeners.iter().position(|x| x.0 == id) {
+            self.listeners.remove(pos);
+            return;
+        }
+
+        self.exclusive_listeners.remove(
+            self.exclusive_listeners
+                .iter()
+                .position(|x| x.0 == id)
+                .unwrap(),
+        );
     }
 }
 
@@ -238,4 +302,39 @@ mod tests {
 
         assert_eq!(*counter.borrow(), 4);
     }
+
+    #[test]
+    fn test_exclusive_listeners() {
+        let counters = Arc::new(AtomicRefCell::new([0u32; 3]));
+
+        let mut source = EventSource::new();
+
+        // the first listener never accepts the event, and the second only accepts odd values
+        let counters_clone = Arc::clone(&counters);
+        let _handle_1 = source.add_exclusive_listener(move |_: u32, _| {
+            counters_clone.borrow_mut()[0] += 1;
+            false
+        });
+        let counters_clone = Arc::clone(&counters);
+        let handle_2 = source.add_exclusive_listener(move |val, _| {
+            counters_clone.borrow_mut()[1] += 1;
+            val % 2 == 1
+        });
+        let counters_clone = Arc::clone(&counters);
+        let _handle_3 = source.add_exclusive_listener(move |_, _| {
+            counters_clone.borrow_mut()[2] += 1;
+            true
+        });
+
+        CallbackQueue::queue_and_run(|queue| source.notify_listeners(1, queue));
+        assert_eq!(*counters.borrow(), [1, 1, 0]);
+
+        CallbackQueue::queue_and_run(|queue| source.notify_listeners(2, queue));
+        assert_eq!(*counters.borrow(), [2, 2, 1]);
+
+        handle_2.stop_listening();
+
+        CallbackQueue::queue_and_run(|queue| source.notify_listeners(1, queue));
+        assert_eq!(*counters.borrow(), [3, 2, 2]);
+    }
 }
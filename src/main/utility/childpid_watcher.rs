@@ -1,10 +1,13 @@
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::thread;
 
 use linux_api::errno::Errno;
 use linux_api::posix_types::Pid;
+use linux_api::sched::{self, clone_args, CloneFlags, CloneResult};
 use rustix::event::{self, epoll};
 use rustix::fd::AsFd;
 use rustix::fd::OwnedFd;
@@ -18,10 +21,53 @@ use rustix::process::PidfdFlags;
 pub struct ChildPidWatcher {
     inner: Arc<Mutex<Inner>>,
     epoll: Arc<OwnedFd>,
+    // Next unique handle ID.
+    next_handle: AtomicU64,
+    // Callbacks registered via `register_callback_async`, which are added to
+    // `Inner` the next time anyone holds its lock.
+    pending_callbacks: mpsc::Sender<PendingCallback>,
+    // Same as `Inner::command_notifier`, for notifying the watcher thread
+    // without taking the lock.
+    command_notifier: Arc<OwnedFd>,
 }
 
 pub type WatchHandle = u64;
 
+/// A callback registration submitted by `register_callback_async`, which may
+/// not have been processed yet.
+#[derive(Debug)]
+pub struct PendingWatchHandle {
+    handle: WatchHandle,
+    registered: mpsc::Receiver<bool>,
+}
+
+impl PendingWatchHandle {
+    /// The handle that the callback will be registered under, which can be
+    /// used with `unregister_callback`.
+    pub fn handle(&self) -> WatchHandle {
+        self.handle
+    }
+
+    /// Wait until the registration has been processed.
+    ///
+    /// Panics if the pid wasn't registered, or had been unregistered.
+    pub fn wait(self) -> WatchHandle {
+        assert!(
+            self.registered.recv().unwrap(),
+            "Registered a callback for a pid that wasn't registered"
+        );
+        self.handle
+    }
+}
+
+struct PendingCallback {
+    pid: Pid,
+    handle: WatchHandle,
+    callback: Box<dyn Send + FnOnce(Pid)>,
+    // Whether the callback was registered.
+    registered: mpsc::SyncSender<bool>,
+}
+
 #[derive(Debug)]
 enum Command {
     RunCallbacks(Pid),
@@ -41,15 +87,15 @@ struct PidData {
 
 #[derive(Debug)]
 struct Inner {
-    // Next unique handle ID.
-    next_handle: WatchHandle,
     // Pending commands for watcher thread.
     commands: Vec<Command>,
     // Data for each monitored pid.
     pids: HashMap<Pid, PidData>,
     // event_fd used to notify watcher thread via epoll. Calling thread writes a
     // single byte, which the watcher thread reads to reset.
-    command_notifier: OwnedFd,
+    command_notifier: Arc<OwnedFd>,
+    // Callbacks registered via `register_callback_async`.
+    pending_callbacks: mpsc::Receiver<PendingCallback>,
     thread_handle: Option<thread::JoinHandle<()>>,
 }
 
@@ -97,6 +143,38 @@ impl Inner {
             self.remove_pid(epoll, pid)
         }
     }
+
+    /// Add a callback for `pid`. Returns false if `pid` isn't registered.
+    fn add_callback(
+        &mut self,
+        pid: Pid,
+        handle: WatchHandle,
+        callback: Box<dyn Send + FnOnce(Pid)>,
+    ) -> bool {
+        let Some(pid_data) = self.pids.get_mut(&pid) else {
+            return false;
+        };
+        if pid_data.unregistered {
+            return false;
+        }
+        pid_data.callbacks.insert(handle, callback);
+        if pid_data.pidfd.is_none() {
+            // pid is already dead. Run the callback we just registered.
+            self.send_command(Command::RunCallbacks(pid));
+        }
+        true
+    }
+
+    /// Add the callbacks submitted by `register_callback_async`. This must be
+    /// done before processing any other request while holding the lock, so
+    /// that requests from a given thread are processed in order.
+    fn add_pending_callbacks(&mut self) {
+        while let Ok(pending) = self.pending_callbacks.try_recv() {
+            let registered = self.add_callback(pending.pid, pending.handle, pending.callback);
+            // The caller may have dropped the `PendingWatchHandle`.
+            let _ = pending.registered.send(registered);
+        }
+    }
 }
 
 impl ChildPidWatcher {
@@ -104,11 +182,14 @@ impl ChildPidWatcher {
     /// when the object is dropped.
     pub fn new() -> Self {
         let epoll = Arc::new(epoll::create(epoll::CreateFlags::CLOEXEC).unwrap());
-        let command_notifier = event::eventfd(
-            0,
-            event::EventfdFlags::NONBLOCK | event::EventfdFlags::CLOEXEC,
-        )
-        .unwrap();
+        let command_notifier = Arc::new(
+            event::eventfd(
+                0,
+                event::EventfdFlags::NONBLOCK | event::EventfdFlags::CLOEXEC,
+            )
+            .unwrap(),
+        );
+        let (pending_callbacks_sender, pending_callbacks) = mpsc::channel();
         epoll::add(
             &epoll,
             &command_notifier,
@@ -118,13 +199,16 @@ impl ChildPidWatcher {
         .unwrap();
         let watcher = ChildPidWatcher {
             inner: Arc::new(Mutex::new(Inner {
-                next_handle: 1,
                 pids: HashMap::new(),
                 commands: Vec::new(),
-                command_notifier,
+                command_notifier: command_notifier.clone(),
+                pending_callbacks,
                 thread_handle: None,
             })),
             epoll,
+            next_handle: AtomicU64::new(1),
+            pending_callbacks: pending_callbacks_sender,
+            command_notifier,
         };
         let thread_handle = {
             let inner = Arc::clone(&watcher.inner);
@@ -158,6 +242,7 @@ impl ChildPidWatcher {
             // make it difficult to guarantee a callback *won't* be run if the
             // caller unregisters it.
             let mut inner = inner.lock().unwrap();
+            inner.add_pending_callbacks();
 
             for event in events.into_iter() {
                 if event.data.u64() == 0 {
@@ -209,8 +294,8 @@ impl ChildPidWatcher {
         }
     }
 
-    /// Fork a child and register it. Uses `fork` internally; it `vfork` is desired,
-    /// use `register_pid` instead.
+    /// Fork a child and register it. Uses `fork` internally; if `vfork` is desired,
+    /// use `vfork_watchable` instead.
     ///
     /// Panics if `child_fn` returns.
     /// TODO: change the type to `FnOnce() -> !` once that's stabilized in Rust.
@@ -223,15 +308,58 @@ impl ChildPidWatcher {
     /// Still, there may be some dragons here. Best to call exec before too long
     /// in the child.
     pub unsafe fn fork_watchable(&self, child_fn: impl FnOnce()) -> Result<Pid, Errno> {
-        let raw_pid = Errno::result_from_libc_errno(-1, unsafe { libc::syscall(libc::SYS_fork) })?;
-        if raw_pid == 0 {
-            child_fn();
-            panic!("child_fn shouldn't have returned");
+        unsafe { self.fork_watchable_internal(|| sched::fork(), child_fn) }
+    }
+
+    /// Create a child with `clone3` using the caller-supplied `args`, and
+    /// register it. This allows creating children with specific flags, such as
+    /// `CLONE_NEWPID`. The child's exit signal is taken from `args`, and should
+    /// usually be `SIGCHLD`.
+    ///
+    /// Returns `EINVAL` if `args` contains `CLONE_VM` or `CLONE_THREAD`, since
+    /// `child_fn` runs on a copy of the caller's stack. Use `vfork_watchable`
+    /// for vfork semantics.
+    ///
+    /// Panics if `child_fn` returns.
+    ///
+    /// # Safety
+    ///
+    /// As for `fork_watchable`, plus any requirements of the flags in `args`.
+    /// See `clone(2)`.
+    pub unsafe fn clone3_watchable(
+        &self,
+        args: &clone_args,
+        child_fn: impl FnOnce(),
+    ) -> Result<Pid, Errno> {
+        let flags = CloneFlags::from_bits_retain(args.flags);
+        if flags.intersects(CloneFlags::CLONE_VM | CloneFlags::CLONE_THREAD) {
+            return Err(Errno::EINVAL);
         }
-        let pid = Pid::from_raw(raw_pid.try_into().unwrap()).unwrap();
-        self.register_pid(pid);
+        unsafe { self.fork_watchable_internal(|| sched::clone3(args), child_fn) }
+    }
 
-        Ok(pid)
+    /// Create a child using `create_child`, run `child_fn` in the child, and
+    /// register the child in the parent.
+    ///
+    /// # Safety
+    ///
+    /// `create_child` must create a child process that has its own copy of the
+    /// caller's address space, as `fork` does.
+    unsafe fn fork_watchable_internal(
+        &self,
+        create_child: impl FnOnce() -> Result<CloneResult, Errno>,
+        child_fn: impl FnOnce(),
+    ) -> Result<Pid, Errno> {
+        match create_child()? {
+            CloneResult::CallerIsChild => {
+                child_fn();
+                panic!("child_fn shouldn't have returned");
+            }
+            CloneResult::CallerIsParent(pid) => {
+                self.register_pid(pid);
+                Ok(pid)
+            }
+        }
     }
 
     /// Register interest in `pid`.
@@ -276,10 +404,57 @@ impl ChildPidWatcher {
         assert!(prev.is_none());
     }
 
-    // TODO: Re-enable when Rust supports vfork: https://github.com/rust-lang/rust/issues/58314
-    // pub unsafe fn vfork_watchable(&self, child_fn: impl FnOnce()) -> Result<Pid, nix::Error> {
-    //     unsafe { self.fork_watchable_internal(libc::SYS_vfork, child_fn) }
-    // }
+    /// Create a child with vfork semantics and register it. The calling thread
+    /// is suspended until the child calls exec or exits, and the child shares
+    /// our address space until then, avoiding the cost of copying page tables
+    /// for the common fork-then-exec pattern.
+    ///
+    /// Rust doesn't support `vfork` itself
+    /// (<https://github.com/rust-lang/rust/issues/58314>), since the child would
+    /// scribble over the parent's stack. Instead we use `clone` with
+    /// `CLONE_VM | CLONE_VFORK` and give the child its own stack.
+    ///
+    /// Aborts the child if `child_fn` returns.
+    ///
+    /// # Safety
+    ///
+    /// `child_fn` runs in the parent's address space, so it must only call
+    /// async-signal-safe functions, must not allocate, unwind, or take locks,
+    /// and should do nothing but prepare arguments and call exec or `_exit`.
+    pub unsafe fn vfork_watchable<F: FnOnce()>(&self, child_fn: F) -> Result<Pid, Errno> {
+        extern "C" fn trampoline<F: FnOnce()>(arg: *mut std::ffi::c_void) -> std::ffi::c_int {
+            let child_fn = unsafe { &mut *(arg as *mut Option<F>) };
+            (child_fn.take().unwrap())();
+            // Unwinding or running destructors here would corrupt the parent's state.
+            unsafe { libc::abort() }
+        }
+
+        const CHILD_STACK_SIZE: usize = 256 * 1024;
+        let mut stack = vec![0u8; CHILD_STACK_SIZE];
+        // The stack grows down, so the child starts at the (16-byte aligned) end.
+        let stack_top = {
+            let end = unsafe { stack.as_mut_ptr().add(stack.len()) };
+            end.wrapping_sub(end as usize % 16)
+        };
+
+        let mut child_fn = Some(child_fn);
+        let raw_pid = Errno::result_from_libc_errno(-1, unsafe {
+            libc::clone(
+                trampoline::<F>,
+                stack_top as *mut std::ffi::c_void,
+                libc::CLONE_VM | libc::CLONE_VFORK | libc::SIGCHLD,
+                &mut child_fn as *mut Option<F> as *mut std::ffi::c_void,
+            )
+        })?;
+        // With `CLONE_VFORK` the child has already called exec or exited, so it
+        // no longer uses its stack.
+        drop(stack);
+
+        let pid = Pid::from_raw(raw_pid).unwrap();
+        self.register_pid(pid);
+
+        Ok(pid)
+    }
 
     /// Unregister the pid. After unregistration, no more callbacks may be
     /// registered for the given pid. Already-registered callbacks will still be
@@ -291,6 +466,7 @@ impl ChildPidWatcher {
         // where we unregister a pid at the same time as the worker thread
         // receives an epoll event for it.
         let mut inner = self.inner.lock().unwrap();
+        inner.add_pending_callbacks();
         inner.send_command(Command::UnregisterPid(pid));
     }
 
@@ -306,19 +482,43 @@ impl ChildPidWatcher {
         pid: Pid,
         callback: impl Send + FnOnce(Pid) + 'static,
     ) -> WatchHandle {
+        let handle = self.next_handle.fetch_add(1, Ordering::Relaxed);
         let mut inner = self.inner.lock().unwrap();
-        let handle = inner.next_handle;
-        inner.next_handle += 1;
-        let pid_data = inner.pids.get_mut(&pid).unwrap();
-        assert!(!pid_data.unregistered);
-        pid_data.callbacks.insert(handle, Box::new(callback));
-        if pid_data.pidfd.is_none() {
-            // pid is already dead. Run the callback we just registered.
-            inner.send_command(Command::RunCallbacks(pid));
-        }
+        inner.add_pending_callbacks();
+        assert!(
+            inner.add_callback(pid, handle, Box::new(callback)),
+            "{pid:?} isn't registered"
+        );
         handle
     }
 
+    /// Like `register_callback`, but doesn't wait for the registration to be
+    /// processed, and so never blocks on the watcher thread (e.g. while it's
+    /// running other callbacks).
+    ///
+    /// The registration is processed in order with respect to other calls on
+    /// this object from the same thread. Use `PendingWatchHandle::wait` to wait
+    /// for it to be processed and check that it succeeded; if `pid` isn't
+    /// registered the callback is dropped without running.
+    pub fn register_callback_async(
+        &self,
+        pid: Pid,
+        callback: impl Send + FnOnce(Pid) + 'static,
+    ) -> PendingWatchHandle {
+        let handle = self.next_handle.fetch_add(1, Ordering::Relaxed);
+        let (registered_sender, registered) = mpsc::sync_channel(1);
+        self.pending_callbacks
+            .send(PendingCallback {
+                pid,
+                handle,
+                callback: Box::new(callback),
+                registered: registered_sender,
+            })
+            .unwrap();
+        rustix::io::write(&self.command_notifier, &1u64.to_ne_bytes()).unwrap();
+        PendingWatchHandle { handle, registered }
+    }
+
     /// Unregisters a callback. After returning, the corresponding callback is
     /// guaranteed either to have already run, or to never run. i.e. it's safe to
     /// free data that the callback might otherwise access.
@@ -326,6 +526,7 @@ impl ChildPidWatcher {
     /// No-op if `pid` isn't registered.
     pub fn unregister_callback(&self, pid: Pid, handle: WatchHandle) {
         let mut inner = self.inner.lock().unwrap();
+        inner.add_pending_callbacks();
         if let Some(pid_data) = inner.pids.get_mut(&pid) {
             pid_data.callbacks.remove(&handle);
             inner.maybe_remove_pid(&self.epoll, pid);
@@ -350,6 +551,15 @@ impl Drop for ChildPidWatcher {
     }
 }
 
+impl std::fmt::Debug for PendingCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingCallback")
+            .field("pid", &self.pid)
+            .field("handle", &self.handle)
+            .finish_non_exhaustive()
+    }
+}
+
 impl std::fmt::Debug for PidData {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("PidData")
@@ -363,6 +573,7 @@ impl std::fmt::Debug for PidData {
 mod tests {
     use std::sync::{Arc, Condvar};
 
+    use linux_api::signal::Signal;
     use nix::sys::eventfd::EventFd;
     use rustix::fd::AsRawFd;
     use rustix::process::{waitpid, WaitOptions};
@@ -547,6 +758,153 @@ mod tests {
         );
     }
 
+    #[test]
+    // can't call foreign function: clone
+    #[cfg_attr(miri, ignore)]
+    fn vfork_watchable() {
+        let watcher = ChildPidWatcher::new();
+        let child = unsafe {
+            watcher.vfork_watchable(|| {
+                libc::_exit(42);
+            })
+        }
+        .unwrap();
+
+        let callback_ran = Arc::new((Mutex::new(false), Condvar::new()));
+        {
+            let callback_ran = callback_ran.clone();
+            watcher.register_callback(
+                child,
+                Box::new(move |pid| {
+                    assert_eq!(pid, child);
+                    *callback_ran.0.lock().unwrap() = true;
+                    callback_ran.1.notify_all();
+                }),
+            );
+        }
+        watcher.unregister_pid(child);
+
+        // Wait for our callback to run.
+        let mut callback_ran_lock = callback_ran.0.lock().unwrap();
+        while !*callback_ran_lock {
+            callback_ran_lock = callback_ran.1.wait(callback_ran_lock).unwrap();
+        }
+
+        assert_eq!(
+            waitpid(Some(child.into()), WaitOptions::empty())
+                .unwrap()
+                .unwrap()
+                .exit_status(),
+            Some(42)
+        );
+    }
+
+    #[test]
+    // can't call foreign function: clone3
+    #[cfg_attr(miri, ignore)]
+    fn clone3_watchable() {
+        let watcher = ChildPidWatcher::new();
+        let args = clone_args::default().with_exit_signal(Some(Signal::SIGCHLD));
+        let child = unsafe {
+            watcher.clone3_watchable(&args, || {
+                libc::_exit(42);
+            })
+        }
+        .unwrap();
+
+        let callback_ran = Arc::new((Mutex::new(false), Condvar::new()));
+        {
+            let callback_ran = callback_ran.clone();
+            watcher.register_callback(
+                child,
+                Box::new(move |pid| {
+                    assert_eq!(pid, child);
+                    *callback_ran.0.lock().unwrap() = true;
+                    callback_ran.1.notify_all();
+                }),
+            );
+        }
+        watcher.unregister_pid(child);
+
+        // Wait for our callback to run.
+        let mut callback_ran_lock = callback_ran.0.lock().unwrap();
+        while !*callback_ran_lock {
+            callback_ran_lock = callback_ran.1.wait(callback_ran_lock).unwrap();
+        }
+
+        assert_eq!(
+            waitpid(Some(child.into()), WaitOptions::empty())
+                .unwrap()
+                .unwrap()
+                .exit_status(),
+            Some(42)
+        );
+    }
+
+    #[test]
+    // can't call foreign function: epoll_create1
+    #[cfg_attr(miri, ignore)]
+    fn clone3_watchable_shared_vm() {
+        let watcher = ChildPidWatcher::new();
+        let args = clone_args::default()
+            .with_flags(CloneFlags::CLONE_VM)
+            .with_exit_signal(Some(Signal::SIGCHLD));
+        let res = unsafe { watcher.clone3_watchable(&args, || unreachable!()) };
+        assert_eq!(res, Err(Errno::EINVAL));
+    }
+
+    #[test]
+    // can't call foreign function: pipe
+    #[cfg_attr(miri, ignore)]
+    fn register_async() {
+        let notifier = EventFd::new().unwrap();
+
+        let watcher = ChildPidWatcher::new();
+        let child = unsafe {
+            watcher.fork_watchable(|| {
+                let mut buf = [0; 8];
+                // Wait for parent to register its callback.
+                nix::unistd::read(notifier.as_raw_fd(), &mut buf).unwrap();
+                libc::_exit(42);
+            })
+        }
+        .unwrap();
+
+        let callback_ran = Arc::new((Mutex::new(false), Condvar::new()));
+        let pending = {
+            let callback_ran = callback_ran.clone();
+            watcher.register_callback_async(child, move |pid| {
+                assert_eq!(pid, child);
+                *callback_ran.0.lock().unwrap() = true;
+                callback_ran.1.notify_all();
+            })
+        };
+
+        // Should be processed before the pid is unregistered.
+        watcher.unregister_pid(child);
+
+        // Let the child exit.
+        nix::unistd::write(&notifier, &1u64.to_ne_bytes()).unwrap();
+
+        // Wait for our callback to run.
+        let mut callback_ran_lock = callback_ran.0.lock().unwrap();
+        while !*callback_ran_lock {
+            callback_ran_lock = callback_ran.1.wait(callback_ran_lock).unwrap();
+        }
+        drop(callback_ran_lock);
+
+        let handle = pending.handle();
+        assert_eq!(pending.wait(), handle);
+
+        assert_eq!(
+            waitpid(Some(child.into()), WaitOptions::empty())
+                .unwrap()
+                .unwrap()
+                .exit_status(),
+            Some(42)
+        );
+    }
+
     #[test]
     // can't call foreign function
     #[cfg_attr(miri, ignore)]
@@ -0,0 +1,257 @@
+use std::os::fd::AsRawFd;
+
+use linux_api::aio::{aio_context_t, io_event, iocb, IOCB_CMD_PREAD, IOCB_CMD_PWRITE};
+use test_utils::TestEnvironment as TestEnv;
+use test_utils::{set, TestEnvironment};
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
+    let all_envs = set![TestEnvironment::Libc, TestEnvironment::Shadow];
+    let tests: Vec<test_utils::ShadowTest<_, _>> = vec![
+        test_utils::ShadowTest::new("test_read", test_read, all_envs.clone()),
+        test_utils::ShadowTest::new(
+            "test_write_then_read",
+            test_write_then_read,
+            all_envs.clone(),
+        ),
+        test_utils::ShadowTest::new("test_errors", test_errors, all_envs.clone()),
+        // linux may or may not support io_uring, depending on the kernel config and sysctls
+        test_utils::ShadowTest::new(
+            "test_io_uring_setup",
+            test_io_uring_setup,
+            set![TestEnvironment::Shadow],
+        ),
+    ];
+
+    tests
+}
+
+fn io_setup(nr_events: u32) -> Result<aio_context_t, i32> {
+    let mut ctx: aio_context_t = 0;
+    let rv = unsafe { libc::syscall(libc::SYS_io_setup, nr_events, &mut ctx) };
+    if rv < 0 {
+        return Err(test_utils::get_errno());
+    }
+    Ok(ctx)
+}
+
+fn io_destroy(ctx: aio_context_t) -> Result<(), i32> {
+    let rv = unsafe { libc::syscall(libc::SYS_io_destroy, ctx) };
+    if rv < 0 {
+        return Err(test_utils::get_errno());
+    }
+    Ok(())
+}
+
+fn io_submit(ctx: aio_context_t, iocbs: &mut [&mut iocb]) -> Result<usize, i32> {
+    let rv = unsafe { libc::syscall(libc::SYS_io_submit, ctx, iocbs.len(), iocbs.as_mut_ptr()) };
+    if rv < 0 {
+        return Err(test_utils::get_errno());
+    }
+    Ok(rv.try_into().unwrap())
+}
+
+fn io_getevents(
+    ctx: aio_context_t,
+    min_nr: usize,
+    events: &mut [io_event],
+    timeout: Option<&libc::timespec>,
+) -> Result<usize, i32> {
+    let timeout = timeout.map_or(std::ptr::null(), std::ptr::from_ref);
+    let rv = unsafe {
+        libc::syscall(
+            libc::SYS_io_getevents,
+            ctx,
+            min_nr,
+            events.len(),
+            events.as_mut_ptr(),
+            timeout,
+        )
+    };
+    if rv < 0 {
+        return Err(test_utils::get_errno());
+    }
+    Ok(rv.try_into().unwrap())
+}
+
+fn new_iocb(opcode: u16, fd: i32, buf: *const u8, len: usize, offset: i64, data: u64) -> iocb {
+    iocb {
+        aio_data: data,
+        aio_lio_opcode: opcode,
+        aio_fildes: fd as u32,
+        aio_buf: buf as u64,
+        aio_nbytes: len.try_into().unwrap(),
+        aio_offset: offset,
+        ..Default::default()
+    }
+}
+
+/// Create a file with the given contents, and open it for reading and writing. The file is
+/// removed once it's opened.
+fn temp_file(name: &str, contents: &[u8]) -> std::fs::File {
+    std::fs::write(name, contents).unwrap();
+    let file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(name)
+        .unwrap();
+    std::fs::remove_file(name).unwrap();
+    file
+}
+
+fn test_read() -> Result<(), String> {
+    let file = temp_file("aio_test_read", b"hello world");
+    let ctx = io_setup(8).unwrap();
+
+    let mut buf = [0u8; 5];
+    let mut cb = new_iocb(
+        IOCB_CMD_PREAD,
+        file.as_raw_fd(),
+        buf.as_mut_ptr(),
+        buf.len(),
+        6,
+        42,
+    );
+    let cb_addr = std::ptr::from_ref(&cb) as u64;
+    assert_eq!(io_submit(ctx, &mut [&mut cb]), Ok(1));
+
+    let mut events = [io_event::default(); 4];
+    assert_eq!(io_getevents(ctx, 1, &mut events, None), Ok(1));
+    assert_eq!(
+        events[0],
+        io_event {
+            data: 42,
+            obj: cb_addr,
+            res: 5,
+            res2: 0,
+        }
+    );
+    assert_eq!(&buf, b"world");
+
+    // the completion was consumed
+    let zero = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    assert_eq!(io_getevents(ctx, 0, &mut events, Some(&zero)), Ok(0));
+
+    assert_eq!(io_destroy(ctx), Ok(()));
+
+    Ok(())
+}
+
+fn test_write_then_read() -> Result<(), String> {
+    let file = temp_file("aio_test_write_then_read", b"");
+    let ctx = io_setup(8).unwrap();
+
+    let data = b"asynchronous";
+    let mut write_cb = new_iocb(
+        IOCB_CMD_PWRITE,
+        file.as_raw_fd(),
+        data.as_ptr(),
+        data.len(),
+        0,
+        1,
+    );
+    let mut buf = [0u8; 64];
+    let mut read_cb = new_iocb(
+        IOCB_CMD_PREAD,
+        file.as_raw_fd(),
+        buf.as_mut_ptr(),
+        buf.len(),
+        0,
+        2,
+    );
+
+    // submitted in order, so the read sees the written data
+    assert_eq!(io_submit(ctx, &mut [&mut write_cb, &mut read_cb]), Ok(2));
+
+    let mut events = [io_event::default(); 2];
+    assert_eq!(io_getevents(ctx, 2, &mut events, None), Ok(2));
+
+    events.sort_by_key(|e| e.data);
+    assert_eq!((events[0].data, events[0].res), (1, data.len() as i64));
+    assert_eq!((events[1].data, events[1].res), (2, data.len() as i64));
+    assert_eq!(&buf[..data.len()], data);
+
+    assert_eq!(io_destroy(ctx), Ok(()));
+
+    Ok(())
+}
+
+fn test_errors() -> Result<(), String> {
+    assert_eq!(io_setup(0), Err(libc::EINVAL));
+
+    // the context pointer must point to 0
+    let mut ctx: aio_context_t = 1;
+    let rv = unsafe { libc::syscall(libc::SYS_io_setup, 1, &mut ctx) };
+    assert_eq!(rv, -1);
+    assert_eq!(test_utils::get_errno(), libc::EINVAL);
+
+    let ctx = io_setup(1).unwrap();
+
+    let mut buf = [0u8; 1];
+    let mut cb = new_iocb(IOCB_CMD_PREAD, -1, buf.as_mut_ptr(), buf.len(), 0, 0);
+    assert_eq!(io_submit(ctx, &mut [&mut cb]), Err(libc::EBADF));
+
+    // the end of the transfer would overflow the file offset
+    let file = temp_file("aio_test_errors", b"hello world");
+    let mut cb = new_iocb(
+        IOCB_CMD_PREAD,
+        file.as_raw_fd(),
+        buf.as_mut_ptr(),
+        buf.len(),
+        i64::MAX,
+        0,
+    );
+    assert_eq!(io_submit(ctx, &mut [&mut cb]), Err(libc::EINVAL));
+
+    // no completions, so this times out
+    let timeout = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 10_000_000,
+    };
+    let mut events = [io_event::default(); 1];
+    assert_eq!(io_getevents(ctx, 1, &mut events, Some(&timeout)), Ok(0));
+
+    // min_nr can't be more than nr
+    assert_eq!(
+        io_getevents(ctx, 2, &mut events, Some(&timeout)),
+        Err(libc::EINVAL)
+    );
+
+    assert_eq!(io_destroy(ctx), Ok(()));
+    assert_eq!(io_destroy(ctx), Err(libc::EINVAL));
+
+    Ok(())
+}
+
+fn test_io_uring_setup() -> Result<(), String> {
+    // shadow doesn't support io_uring, and should report it as an unimplemented syscall
+    let mut params = [0u8; 120];
+    let rv = unsafe { libc::syscall(libc::SYS_io_uring_setup, 8, params.as_mut_ptr()) };
+    assert_eq!(rv, -1);
+    assert_eq!(test_utils::get_errno(), libc::ENOSYS);
+
+    Ok(())
+}
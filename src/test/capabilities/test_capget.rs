@@ -1,4 +1,6 @@
-use linux_api::capability::{user_cap_data, user_cap_header, LINUX_CAPABILITY_VERSION_3};
+use linux_api::capability::{
+    user_cap_data, user_cap_header, CAP_LAST_CAP, LINUX_CAPABILITY_VERSION_3,
+};
 
 use test_utils::{set, ShadowTest, TestEnvironment};
 
@@ -14,16 +16,24 @@ fn test_capget() -> anyhow::Result<()> {
         inheritable: 1,
     };
     // Put the non-empty to the array so that we check that it will be
-    // written to zeroes later
+    // overwritten later
     let mut data: [user_cap_data; 2] = [nonempty, nonempty];
     assert_eq!(linux_api::capability::capget(&hdr, Some(&mut data)), Ok(()));
 
-    for item in &data {
+    // A root process has all capabilities, and other processes have none
+    let all: u64 = (1 << (CAP_LAST_CAP + 1)) - 1;
+    let expected = if unsafe { libc::geteuid() } == 0 {
+        all
+    } else {
+        0
+    };
+    for (item, shift) in data.iter().zip([0, 32]) {
+        let expected = (expected >> shift) as u32;
         assert_eq!(
             *item,
             user_cap_data {
-                effective: 0,
-                permitted: 0,
+                effective: expected,
+                permitted: expected,
                 inheritable: 0,
             }
         );
@@ -31,6 +41,37 @@ fn test_capget() -> anyhow::Result<()> {
     Ok(())
 }
 
+fn test_capget_bad_version() -> anyhow::Result<()> {
+    let mut hdr = user_cap_header {
+        version: 0x12345678,
+        pid: 0,
+    };
+    let mut data = [user_cap_data {
+        effective: 0,
+        permitted: 0,
+        inheritable: 0,
+    }; 2];
+
+    // the kernel writes its preferred version to the header
+    let rv = unsafe { libc::syscall(libc::SYS_capget, &mut hdr, &mut data) };
+    assert_eq!(rv, -1);
+    assert_eq!(test_utils::get_errno(), libc::EINVAL);
+    assert_eq!(hdr.version, LINUX_CAPABILITY_VERSION_3);
+
+    // with a null data pointer, the version is queried without returning an error
+    hdr.version = 0x12345678;
+    let rv = unsafe {
+        libc::syscall(
+            libc::SYS_capget,
+            &mut hdr,
+            std::ptr::null_mut::<user_cap_data>(),
+        )
+    };
+    assert_eq!(rv, 0);
+    assert_eq!(hdr.version, LINUX_CAPABILITY_VERSION_3);
+    Ok(())
+}
+
 fn test_capget_null_datap() -> anyhow::Result<()> {
     let hdr = user_cap_header {
         version: LINUX_CAPABILITY_VERSION_3,
@@ -58,6 +99,11 @@ fn main() -> anyhow::Result<()> {
             test_capget_null_datap,
             all_envs.clone(),
         ),
+        ShadowTest::new(
+            "capget-bad-version",
+            test_capget_bad_version,
+            all_envs.clone(),
+        ),
     ];
 
     if filter_shadow_passing {
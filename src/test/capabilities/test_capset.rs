@@ -17,6 +17,47 @@ fn test_capset() -> anyhow::Result<()> {
     Ok(())
 }
 
+fn test_capset_drop() -> anyhow::Result<()> {
+    const CAP_NET_RAW: u32 = 13;
+
+    let hdr = user_cap_header {
+        version: LINUX_CAPABILITY_VERSION_3,
+        pid: 0,
+    };
+    let empty = user_cap_data {
+        effective: 0,
+        permitted: 0,
+        inheritable: 0,
+    };
+    let mut before = [empty, empty];
+    assert_eq!(
+        linux_api::capability::capget(&hdr, Some(&mut before)),
+        Ok(())
+    );
+
+    // drop CAP_NET_RAW from the effective and permitted sets
+    let mut dropped = before;
+    dropped[0].effective &= !(1 << CAP_NET_RAW);
+    dropped[0].permitted &= !(1 << CAP_NET_RAW);
+    assert_eq!(linux_api::capability::capset(&hdr, &dropped), Ok(()));
+
+    let mut after = [empty, empty];
+    assert_eq!(
+        linux_api::capability::capget(&hdr, Some(&mut after)),
+        Ok(())
+    );
+    assert_eq!(after, dropped);
+
+    // a dropped permitted capability can't be regained
+    if before[0].permitted & (1 << CAP_NET_RAW) != 0 {
+        assert_eq!(
+            linux_api::capability::capset(&hdr, &before),
+            Err(linux_api::errno::Errno::EPERM)
+        );
+    }
+    Ok(())
+}
+
 fn test_capset_nonempty() -> anyhow::Result<()> {
     let hdr = user_cap_header {
         version: LINUX_CAPABILITY_VERSION_3,
@@ -42,6 +83,8 @@ fn main() -> anyhow::Result<()> {
     let all_envs = set![TestEnvironment::Libc, TestEnvironment::Shadow];
 
     let mut tests: Vec<test_utils::ShadowTest<(), anyhow::Error>> = vec![
+        // Run before "capset", which drops all capabilities.
+        ShadowTest::new("capset-drop", test_capset_drop, all_envs.clone()),
         ShadowTest::new("capset", test_capset, all_envs.clone()),
         ShadowTest::new("capset-nonempty", test_capset_nonempty, all_envs.clone()),
     ];
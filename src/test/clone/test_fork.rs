@@ -236,6 +236,127 @@ fn test_child_change_group() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Validate that a parent can move its children into a new process group, and
+/// that `kill` with a negative pid signals every process in the group.
+fn test_signal_process_group() -> anyhow::Result<()> {
+    run_test_in_subprocess(|| {
+        let fork_sleeping_child = || -> libc::pid_t {
+            match unsafe { linux_api::sched::fork() }.unwrap() {
+                CloneResult::CallerIsChild => {
+                    // Sleep forever
+                    match rustix::thread::nanosleep(&rustix::fs::Timespec {
+                        tv_sec: i64::MAX,
+                        tv_nsec: 0,
+                    }) {
+                        rustix::thread::NanosleepRelativeResult::Ok => (),
+                        other => panic!("Unexpected nanosleep result: {other:?}"),
+                    }
+                    unsafe { libc::exit(0) };
+                }
+                CloneResult::CallerIsParent(child_pid) => child_pid.as_raw_nonzero().into(),
+            }
+        };
+        let leader = fork_sleeping_child();
+        let member = fork_sleeping_child();
+
+        // Make `leader` the leader of a new group, and move `member` into it.
+        assert_eq!(unsafe { libc::setpgid(leader, 0) }, 0);
+        assert_eq!(unsafe { libc::setpgid(member, leader) }, 0);
+        assert_eq!(unsafe { libc::getpgid(leader) }, leader);
+        assert_eq!(unsafe { libc::getpgid(member) }, leader);
+        assert_ne!(unsafe { libc::getpgrp() }, leader);
+
+        // Can't move a process into a group that doesn't exist in its session.
+        assert_eq!(
+            nix::errno::Errno::result(unsafe { libc::setpgid(member, i32::MAX) }),
+            Err(nix::errno::Errno::EPERM)
+        );
+        assert_eq!(unsafe { libc::getpgid(member) }, leader);
+
+        // Signal the whole group.
+        assert_eq!(unsafe { libc::kill(-leader, libc::SIGTERM) }, 0);
+
+        for child in [leader, member] {
+            let mut wstatus = 0;
+            assert_eq!(unsafe { libc::waitpid(child, &mut wstatus, 0) }, child);
+            assert!(libc::WIFSIGNALED(wstatus));
+            assert_eq!(libc::WTERMSIG(wstatus), libc::SIGTERM);
+        }
+
+        // The group no longer exists.
+        assert_eq!(
+            nix::errno::Errno::result(unsafe { libc::kill(-leader, libc::SIGTERM) }),
+            Err(nix::errno::Errno::ESRCH)
+        );
+    })
+}
+
+/// Validate that `kill` with a negative pid delivers the signal to each of
+/// several processes: the members of a process group if `all` is false, and
+/// every process the caller may signal (`kill(-1, ...)`) if `all` is true.
+fn test_kill_signals_each_process(all: bool) -> anyhow::Result<()> {
+    const NUM_CHILDREN: usize = 3;
+    const HANDLED_EXIT_CODE: i32 = 42;
+    // When signaling every process, use a signal that's ignored by default so
+    // that we don't kill the test's parent process.
+    let signal = if all { libc::SIGURG } else { libc::SIGTERM };
+
+    run_test_in_subprocess(|| {
+        let (reader, writer) = rustix::pipe::pipe().unwrap();
+
+        let mut children = Vec::new();
+        for _ in 0..NUM_CHILDREN {
+            let child_pid: libc::pid_t = match unsafe { linux_api::sched::fork() }.unwrap() {
+                CloneResult::CallerIsChild => {
+                    extern "C" fn handler(_signo: c_int) {
+                        unsafe { libc::_exit(HANDLED_EXIT_CODE) };
+                    }
+                    unsafe {
+                        nix::sys::signal::sigaction(
+                            nix::sys::signal::Signal::try_from(signal).unwrap(),
+                            &SigAction::new(
+                                SigHandler::Handler(handler),
+                                SaFlags::empty(),
+                                SigSet::empty(),
+                            ),
+                        )
+                    }
+                    .unwrap();
+                    assert_eq!(rustix::io::write(&writer, &[0]), Ok(1));
+                    // Sleep until signaled
+                    loop {
+                        let _ = rustix::thread::nanosleep(&rustix::fs::Timespec {
+                            tv_sec: i64::MAX,
+                            tv_nsec: 0,
+                        });
+                    }
+                }
+                CloneResult::CallerIsParent(child_pid) => child_pid.as_raw_nonzero().into(),
+            };
+            // Put all of the children into the first child's process group.
+            let pgid = children.first().copied().unwrap_or(0);
+            assert_eq!(unsafe { libc::setpgid(child_pid, pgid) }, 0);
+            children.push(child_pid);
+        }
+
+        // Wait for all of the children to install their signal handlers.
+        for _ in 0..NUM_CHILDREN {
+            let mut buf = [0xff_u8];
+            assert_eq!(rustix::io::read(&reader, &mut buf), Ok(1));
+        }
+
+        let target = if all { -1 } else { -children[0] };
+        assert_eq!(unsafe { libc::kill(target, signal) }, 0);
+
+        for child in children {
+            let mut wstatus = 0;
+            assert_eq!(unsafe { libc::waitpid(child, &mut wstatus, 0) }, child);
+            assert!(libc::WIFEXITED(wstatus));
+            assert_eq!(libc::WEXITSTATUS(wstatus), HANDLED_EXIT_CODE);
+        }
+    })
+}
+
 /// Helper to run the given test function in a child process. This is helpful to
 /// avoid cross-test interference. e.g. `f` can manipulate signal handlers and
 /// masks without having to restore them, and will only have child processes
@@ -875,6 +996,58 @@ fn test_waitfn_sets_signal_death_wstatus(
     })
 }
 
+/// Validate that `wait4` reports a stopped child when WUNTRACED is provided,
+/// and reports its exit normally after it's continued.
+fn test_wait4_reports_stopped_child() -> anyhow::Result<()> {
+    run_test_in_subprocess(|| {
+        const CHILD_EXIT_STATUS: i32 = 42;
+        let clone_res = unsafe { linux_api::sched::fork() }.unwrap();
+        let child_pid = match clone_res {
+            CloneResult::CallerIsChild => {
+                std::thread::sleep(std::time::Duration::from_millis(100));
+                unsafe { libc::exit(CHILD_EXIT_STATUS) };
+            }
+            CloneResult::CallerIsParent(child_pid) => child_pid,
+        };
+        let raw_child_pid = child_pid.as_raw_nonzero().get();
+        let wait4 = |options: i32| -> (i32, i32) {
+            let mut wstatus = 0;
+            let rv = nix::errno::Errno::result(unsafe {
+                libc::wait4(raw_child_pid, &mut wstatus, options, std::ptr::null_mut())
+            });
+            (rv.unwrap(), wstatus)
+        };
+
+        assert_eq!(
+            linux_api::signal::kill_process(child_pid, Some(Signal::SIGSTOP)),
+            Ok(())
+        );
+        let (pid, wstatus) = wait4(libc::WUNTRACED);
+        assert_eq!(pid, raw_child_pid);
+        assert!(libc::WIFSTOPPED(wstatus));
+        assert_eq!(libc::WSTOPSIG(wstatus), Signal::SIGSTOP.as_i32());
+        assert!(!libc::WIFEXITED(wstatus));
+        assert!(!libc::WIFSIGNALED(wstatus));
+
+        // The stop should only be reported once.
+        assert_eq!(wait4(libc::WUNTRACED | libc::WNOHANG).0, 0);
+
+        // The child's sleep would have finished by now if it weren't stopped.
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        assert_eq!(wait4(libc::WNOHANG).0, 0);
+
+        assert_eq!(
+            linux_api::signal::kill_process(child_pid, Some(Signal::SIGCONT)),
+            Ok(())
+        );
+        let (pid, wstatus) = wait4(0);
+        assert_eq!(pid, raw_child_pid);
+        assert!(libc::WIFEXITED(wstatus));
+        assert_eq!(libc::WEXITSTATUS(wstatus), CHILD_EXIT_STATUS);
+        assert!(!libc::WIFSTOPPED(wstatus));
+    })
+}
+
 /// Validate that `waitid` correctly sets the `infop` parameter for a child
 /// that has exited normally.
 fn test_waitid_sets_normal_exit_info() -> anyhow::Result<()> {
@@ -967,6 +1140,90 @@ fn test_waitid_sets_signal_death_info(fatal_signal: Signal) -> anyhow::Result<()
     })
 }
 
+/// Validate that `waitid` reports a child that was stopped and then continued
+/// when WCONTINUED is provided, and that WNOWAIT leaves it waitable.
+fn test_waitid_reports_continued_child() -> anyhow::Result<()> {
+    run_test_in_subprocess(|| {
+        let clone_res = unsafe { linux_api::sched::fork() }.unwrap();
+        let child_pid = match clone_res {
+            CloneResult::CallerIsChild => {
+                // Sleep forever
+                match rustix::thread::nanosleep(&rustix::fs::Timespec {
+                    tv_sec: i64::MAX,
+                    tv_nsec: 0,
+                }) {
+                    rustix::thread::NanosleepRelativeResult::Ok => (),
+                    other => panic!("Unexpected nanosleep result: {other:?}"),
+                }
+                unsafe { libc::exit(0) };
+            }
+            CloneResult::CallerIsParent(child_pid) => child_pid,
+        };
+        let waitid = |options: i32| -> siginfo_t {
+            let mut info: siginfo_t = unsafe { std::mem::zeroed() };
+            let rv = nix::errno::Errno::result(unsafe {
+                libc::waitid(
+                    libc::P_PID,
+                    child_pid.as_raw_nonzero().get().try_into().unwrap(),
+                    &mut info,
+                    options,
+                )
+            });
+            assert_eq!(rv, Ok(0));
+            info
+        };
+
+        // Wait for the child to actually stop, so that continuing it
+        // generates a state change.
+        assert_eq!(
+            linux_api::signal::kill_process(child_pid, Some(Signal::SIGSTOP)),
+            Ok(())
+        );
+        let info = waitid(libc::WSTOPPED);
+        assert_eq!(
+            info.si_code,
+            i32::from(linux_api::signal::SigInfoCodeCld::CLD_STOPPED)
+        );
+
+        assert_eq!(
+            linux_api::signal::kill_process(child_pid, Some(Signal::SIGCONT)),
+            Ok(())
+        );
+
+        // Should report the continued child, but leave it waitable.
+        let info = waitid(libc::WCONTINUED | libc::WNOWAIT);
+        assert_eq!(info.si_signo, Signal::SIGCHLD.as_i32());
+        assert_eq!(
+            info.si_code,
+            i32::from(linux_api::signal::SigInfoCodeCld::CLD_CONTINUED)
+        );
+        assert_eq!(unsafe { info.si_pid() }, child_pid.as_raw_nonzero().get());
+        assert_eq!(unsafe { info.si_status() }, Signal::SIGCONT.as_i32());
+
+        // Should report the same child again, and consume the state change.
+        let info = waitid(libc::WCONTINUED);
+        assert_eq!(
+            info.si_code,
+            i32::from(linux_api::signal::SigInfoCodeCld::CLD_CONTINUED)
+        );
+        assert_eq!(unsafe { info.si_pid() }, child_pid.as_raw_nonzero().get());
+
+        // Nothing left to report.
+        let info = waitid(libc::WCONTINUED | libc::WNOHANG);
+        assert_eq!(unsafe { info.si_pid() }, 0);
+
+        assert_eq!(
+            linux_api::signal::kill_process(child_pid, Some(Signal::SIGKILL)),
+            Ok(())
+        );
+        let info = waitid(libc::WEXITED);
+        assert_eq!(
+            info.si_code,
+            i32::from(linux_api::signal::SigInfoCodeCld::CLD_KILLED)
+        );
+    })
+}
+
 /// Core, minimal functionality for fork+exec
 fn test_fork_exec_and_reap(
     spawn_fn: impl FnOnce(&Path, &[&str]) -> Pid,
@@ -1293,6 +1550,212 @@ fn test_clone_clear_sighand(set_clear_sighand: bool) -> anyhow::Result<()> {
     })
 }
 
+/// Validate that a parent can read and write its child's memory using `process_vm_readv` and
+/// `process_vm_writev`.
+fn test_process_vm_readv_writev() -> anyhow::Result<()> {
+    run_test_in_subprocess(|| {
+        let (to_parent_reader, to_parent_writer) = rustix::pipe::pipe().unwrap();
+        let (to_child_reader, to_child_writer) = rustix::pipe::pipe().unwrap();
+
+        // after the fork, the parent and child each have their own copy of this buffer at the
+        // same address
+        let mut buf = [0u8; 16];
+
+        let clone_res = unsafe { linux_api::sched::fork() }.unwrap();
+        let child_pid = match clone_res {
+            CloneResult::CallerIsChild => {
+                unsafe { std::ptr::write_volatile(&mut buf, *b"hello from child") };
+                assert_eq!(rustix::io::write(&to_parent_writer, &[0]), Ok(1));
+
+                // wait for the parent to write to our buffer
+                let mut byte = [0];
+                assert_eq!(rustix::io::read(&to_child_reader, &mut byte), Ok(1));
+
+                let buf = unsafe { std::ptr::read_volatile(&buf) };
+                let rv = if &buf == b"hello from paren" { 0 } else { 1 };
+                unsafe { libc::exit(rv) };
+            }
+            CloneResult::CallerIsParent(pid) => pid,
+        };
+        let child_pid = child_pid.as_raw_nonzero().get();
+
+        // wait for the child to write to its buffer
+        let mut byte = [0];
+        assert_eq!(rustix::io::read(&to_parent_reader, &mut byte), Ok(1));
+
+        let remote_iov = [libc::iovec {
+            iov_base: buf.as_mut_ptr().cast(),
+            iov_len: buf.len(),
+        }];
+
+        // read the child's buffer, split across two local iovecs
+        let mut local_1 = [0u8; 6];
+        let mut local_2 = [0u8; 10];
+        let local_iov = [
+            libc::iovec {
+                iov_base: local_1.as_mut_ptr().cast(),
+                iov_len: local_1.len(),
+            },
+            libc::iovec {
+                iov_base: local_2.as_mut_ptr().cast(),
+                iov_len: local_2.len(),
+            },
+        ];
+        let rv = unsafe {
+            libc::process_vm_readv(child_pid, local_iov.as_ptr(), 2, remote_iov.as_ptr(), 1, 0)
+        };
+        assert_eq!(rv, 16);
+        assert_eq!(&local_1, b"hello ");
+        assert_eq!(&local_2, b"from child");
+
+        // our own copy of the buffer is unchanged
+        assert_eq!(unsafe { std::ptr::read_volatile(&buf) }, [0u8; 16]);
+
+        // non-zero flags are invalid
+        let rv = unsafe {
+            libc::process_vm_readv(child_pid, local_iov.as_ptr(), 2, remote_iov.as_ptr(), 1, 1)
+        };
+        assert_eq!(rv, -1);
+        assert_eq!(nix::errno::Errno::last(), nix::errno::Errno::EINVAL);
+
+        // write to the child's buffer
+        let mut msg = *b"hello from paren";
+        let local_iov = [libc::iovec {
+            iov_base: msg.as_mut_ptr().cast(),
+            iov_len: msg.len(),
+        }];
+        let rv = unsafe {
+            libc::process_vm_writev(child_pid, local_iov.as_ptr(), 1, remote_iov.as_ptr(), 1, 0)
+        };
+        assert_eq!(rv, 16);
+
+        // let the child check its buffer
+        assert_eq!(rustix::io::write(&to_child_writer, &[0]), Ok(1));
+
+        let child_pid = nix::unistd::Pid::from_raw(child_pid);
+        assert_eq!(
+            nix::sys::wait::waitpid(Some(child_pid), None).unwrap(),
+            nix::sys::wait::WaitStatus::Exited(child_pid, 0)
+        );
+    })
+}
+
+fn test_pidfd_sibling_exit() -> anyhow::Result<()> {
+    run_test_in_subprocess(|| {
+        let (release_reader, release_writer) = rustix::pipe::pipe().unwrap();
+
+        // the first child waits to be released, then exits
+        let sibling_pid = match unsafe { linux_api::sched::fork() }.unwrap() {
+            CloneResult::CallerIsChild => {
+                let mut byte = [0];
+                assert_eq!(rustix::io::read(&release_reader, &mut byte), Ok(1));
+                unsafe { libc::exit(0) };
+            }
+            CloneResult::CallerIsParent(pid) => pid.as_raw_nonzero().get(),
+        };
+
+        // the second child watches its sibling through a pidfd
+        let watcher_pid = match unsafe { linux_api::sched::fork() }.unwrap() {
+            CloneResult::CallerIsChild => {
+                let pidfd = unsafe { libc::syscall(libc::SYS_pidfd_open, sibling_pid, 0) };
+                assert!(pidfd >= 0);
+                let pidfd = pidfd as libc::c_int;
+
+                // the sibling hasn't exited yet
+                let mut pollfd = libc::pollfd {
+                    fd: pidfd,
+                    events: libc::POLLIN,
+                    revents: 0,
+                };
+                assert_eq!(unsafe { libc::poll(&mut pollfd, 1, 0) }, 0);
+
+                // release the sibling and wait for it to exit
+                assert_eq!(rustix::io::write(&release_writer, &[0]), Ok(1));
+                assert_eq!(unsafe { libc::poll(&mut pollfd, 1, -1) }, 1);
+                assert_eq!(pollfd.revents & libc::POLLIN, libc::POLLIN);
+
+                // pidfds can't be read
+                let mut buf = [0u8; 8];
+                let rv = unsafe { libc::read(pidfd, buf.as_mut_ptr().cast(), buf.len()) };
+                assert_eq!(rv, -1);
+                assert_eq!(nix::errno::Errno::last(), nix::errno::Errno::EINVAL);
+
+                unsafe { libc::exit(0) };
+            }
+            CloneResult::CallerIsParent(pid) => pid.as_raw_nonzero().get(),
+        };
+
+        let watcher_pid = nix::unistd::Pid::from_raw(watcher_pid);
+        assert_eq!(
+            nix::sys::wait::waitpid(Some(watcher_pid), None).unwrap(),
+            nix::sys::wait::WaitStatus::Exited(watcher_pid, 0)
+        );
+        let sibling_pid = nix::unistd::Pid::from_raw(sibling_pid);
+        assert_eq!(
+            nix::sys::wait::waitpid(Some(sibling_pid), None).unwrap(),
+            nix::sys::wait::WaitStatus::Exited(sibling_pid, 0)
+        );
+    })
+}
+
+fn test_pidfd_send_signal() -> anyhow::Result<()> {
+    run_test_in_subprocess(|| {
+        // the child blocks until it's killed, since nothing is ever written to the pipe
+        let (reader, _writer) = rustix::pipe::pipe().unwrap();
+        let child_pid = match unsafe { linux_api::sched::fork() }.unwrap() {
+            CloneResult::CallerIsChild => {
+                let mut byte = [0];
+                let _ = rustix::io::read(&reader, &mut byte);
+                unsafe { libc::exit(1) };
+            }
+            CloneResult::CallerIsParent(pid) => pid.as_raw_nonzero().get(),
+        };
+
+        let pidfd = unsafe { libc::syscall(libc::SYS_pidfd_open, child_pid, 0) };
+        assert!(pidfd >= 0);
+        let pidfd = pidfd as libc::c_int;
+
+        // non-zero flags are invalid
+        let rv = unsafe {
+            libc::syscall(
+                libc::SYS_pidfd_send_signal,
+                pidfd,
+                libc::SIGKILL,
+                std::ptr::null::<libc::siginfo_t>(),
+                1,
+            )
+        };
+        assert_eq!(rv, -1);
+        assert_eq!(nix::errno::Errno::last(), nix::errno::Errno::EINVAL);
+
+        let rv = unsafe {
+            libc::syscall(
+                libc::SYS_pidfd_send_signal,
+                pidfd,
+                libc::SIGKILL,
+                std::ptr::null::<libc::siginfo_t>(),
+                0,
+            )
+        };
+        assert_eq!(rv, 0);
+
+        let child_pid = nix::unistd::Pid::from_raw(child_pid);
+        assert_eq!(
+            nix::sys::wait::waitpid(Some(child_pid), None).unwrap(),
+            nix::sys::wait::WaitStatus::Signaled(
+                child_pid,
+                nix::sys::signal::Signal::SIGKILL,
+                false
+            )
+        );
+
+        // a pid that doesn't exist
+        let rv = unsafe { libc::syscall(libc::SYS_pidfd_open, 1_000_000, 0) };
+        assert_eq!(rv, -1);
+        assert_eq!(nix::errno::Errno::last(), nix::errno::Errno::ESRCH);
+    })
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     // FIXME: take as a command-line arg
     let python_path = Path::new("/usr/bin/python3");
@@ -1346,6 +1809,23 @@ fn main() -> Result<(), Box<dyn Error>> {
         test_child_change_group,
         all_envs.clone(),
     ));
+    tests.push(ShadowTest::new(
+        stringify!(test_signal_process_group),
+        test_signal_process_group,
+        all_envs.clone(),
+    ));
+    tests.push(ShadowTest::new(
+        "test_kill_signals_each_process:group",
+        || test_kill_signals_each_process(false),
+        all_envs.clone(),
+    ));
+    // Signaling every process outside of shadow would signal unrelated
+    // processes on the system.
+    tests.push(ShadowTest::new(
+        "test_kill_signals_each_process:all",
+        || test_kill_signals_each_process(true),
+        set![TestEnv::Shadow],
+    ));
 
     for exit_signal in &[nix::sys::signal::SIGCHLD, nix::sys::signal::SIGUSR1] {
         tests.push(ShadowTest::new(
@@ -1756,6 +2236,12 @@ fn main() -> Result<(), Box<dyn Error>> {
         ));
     }
 
+    tests.push(ShadowTest::new(
+        "test_wait4_reports_stopped_child",
+        test_wait4_reports_stopped_child,
+        all_envs.clone(),
+    ));
+
     tests.push(ShadowTest::new(
         "test_waitid_sets_normal_exit_info",
         test_waitid_sets_normal_exit_info,
@@ -1768,6 +2254,11 @@ fn main() -> Result<(), Box<dyn Error>> {
             all_envs.clone(),
         ));
     }
+    tests.push(ShadowTest::new(
+        "test_waitid_reports_continued_child",
+        test_waitid_reports_continued_child,
+        all_envs.clone(),
+    ));
 
     #[allow(clippy::type_complexity)]
     let spawn_fns: [(&str, Arc<dyn Fn(&Path, &[&str]) -> Pid>); 4] = [
@@ -1957,6 +2448,24 @@ fn main() -> Result<(), Box<dyn Error>> {
         ));
     }
 
+    tests.push(ShadowTest::new(
+        stringify!(test_process_vm_readv_writev),
+        test_process_vm_readv_writev,
+        all_envs.clone(),
+    ));
+
+    tests.push(ShadowTest::new(
+        stringify!(test_pidfd_sibling_exit),
+        test_pidfd_sibling_exit,
+        all_envs.clone(),
+    ));
+
+    tests.push(ShadowTest::new(
+        stringify!(test_pidfd_send_signal),
+        test_pidfd_send_signal,
+        all_envs.clone(),
+    ));
+
     // It'd be good to test signal config across exec, but this is tricky since
     // python re-initializes it at startup. We might have to write specialized
     // programs in C to exec, and have them verify or otherwise output the
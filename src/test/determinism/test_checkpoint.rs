@@ -0,0 +1,24 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+/// Shadow's custom `SYS_shadow_checkpoint` syscall.
+const SYS_SHADOW_CHECKPOINT: libc::c_long = 1007;
+
+fn checkpoint(id: u64) {
+    let rv = unsafe { libc::syscall(SYS_SHADOW_CHECKPOINT, id) };
+    assert_eq!(rv, 0);
+}
+
+fn main() {
+    checkpoint(1);
+
+    // let some simulated time pass between checkpoints
+    for id in 2..=3 {
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        checkpoint(id);
+    }
+
+    println!("Success.");
+}
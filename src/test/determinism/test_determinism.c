@@ -80,6 +80,17 @@ static int _test_open() {
     if(test_randomOpenRead("/dev/urandom") == EXIT_FAILURE) {
         return EXIT_FAILURE;
     }
+    /* a second open of the same device should also be deterministic */
+    if(test_randomOpenRead("/dev/urandom") == EXIT_FAILURE) {
+        return EXIT_FAILURE;
+    }
+    /* non-canonical paths to the devices should be handled the same way */
+    if(test_randomOpenRead("/dev/./urandom") == EXIT_FAILURE) {
+        return EXIT_FAILURE;
+    }
+    if(test_randomOpenRead("/dev/../dev/random") == EXIT_FAILURE) {
+        return EXIT_FAILURE;
+    }
 //    if(test_randomOpenRead("/dev/srandom") == EXIT_FAILURE) {
 //        return EXIT_FAILURE;
 //    }
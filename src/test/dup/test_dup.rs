@@ -139,6 +139,26 @@ fn test_dup3() -> Result<(), String> {
         check_system_call!(|| unsafe { libc::dup3(5000, target, flag) }, &[libc::EBADF])?;
         check_system_call!(|| unsafe { libc::dup3(fd, -1, flag) }, &[libc::EBADF])?;
 
+        // unlike dup2, the same fd is an error, even if it's not open
+        check_system_call!(|| unsafe { libc::dup3(fd, fd, 0) }, &[libc::EINVAL])?;
+        check_system_call!(|| unsafe { libc::dup3(5000, 5000, 0) }, &[libc::EINVAL])?;
+
+        // only O_CLOEXEC is allowed
+        check_system_call!(
+            || unsafe { libc::dup3(fd, target, libc::O_NONBLOCK) },
+            &[libc::EINVAL]
+        )?;
+        check_system_call!(|| unsafe { libc::dup3(5000, target, -1) }, &[libc::EINVAL])?;
+
+        // the close-on-exec flag is set only if requested
+        for (flags, expected) in [(libc::O_CLOEXEC, libc::FD_CLOEXEC), (0, 0)] {
+            let fd_dup = check_system_call!(|| unsafe { libc::dup3(fd, target, flags) }, &[])?;
+            let fd_flags =
+                check_system_call!(|| unsafe { libc::fcntl(fd_dup, libc::F_GETFD) }, &[])?;
+            assert_eq!(fd_flags & libc::FD_CLOEXEC, expected);
+            assert_eq!(unsafe { libc::close(fd_dup) }, 0);
+        }
+
         Ok(())
     };
 
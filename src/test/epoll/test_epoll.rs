@@ -1,3 +1,4 @@
+use std::os::fd::AsRawFd;
 use std::time::Duration;
 
 use nix::errno::Errno;
@@ -329,6 +330,256 @@ fn test_ctl_invalid_op() -> anyhow::Result<()> {
     })
 }
 
+fn test_oneshot() -> anyhow::Result<()> {
+    let (fd_1, fd_2) = nix::sys::socket::socketpair(
+        nix::sys::socket::AddressFamily::Unix,
+        nix::sys::socket::SockType::Stream,
+        None,
+        nix::sys::socket::SockFlag::empty(),
+    )?;
+    let epoll_fd = epoll::epoll_create()?;
+
+    test_utils::run_and_close_fds(&[epoll_fd, fd_1, fd_2], || {
+        let flags = EpollFlags::EPOLLIN | EpollFlags::EPOLLOUT | EpollFlags::EPOLLONESHOT;
+        let mut event = epoll::EpollEvent::new(flags, 0);
+        epoll::epoll_ctl(
+            epoll_fd,
+            epoll::EpollOp::EpollCtlAdd,
+            fd_1,
+            Some(&mut event),
+        )?;
+
+        let wait = || do_epoll_wait(epoll_fd, Duration::ZERO, /* do_read= */ false);
+
+        // the socket is writable
+        let res = wait();
+        assert_eq!(res.epoll_res, Ok(1));
+        assert_eq!(res.events[0].events(), EpollFlags::EPOLLOUT);
+
+        // the socket is still writable, and becomes readable, but the fd is disabled
+        assert_eq!(wait().epoll_res, Ok(0));
+        unistd::write(fd_2, &[0])?;
+        assert_eq!(wait().epoll_res, Ok(0));
+
+        // re-arm it
+        epoll::epoll_ctl(
+            epoll_fd,
+            epoll::EpollOp::EpollCtlMod,
+            fd_1,
+            Some(&mut event),
+        )?;
+
+        let res = wait();
+        assert_eq!(res.epoll_res, Ok(1));
+        assert_eq!(
+            res.events[0].events(),
+            EpollFlags::EPOLLIN | EpollFlags::EPOLLOUT
+        );
+
+        // disabled again
+        assert_eq!(wait().epoll_res, Ok(0));
+
+        Ok(())
+    })
+}
+
+fn test_exclusive() -> anyhow::Result<()> {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    listener.set_nonblocking(true)?;
+    let addr = listener.local_addr()?;
+    let listener_fd = listener.as_raw_fd();
+
+    let epoll_fd = epoll::epoll_create()?;
+    test_utils::run_and_close_fds(&[epoll_fd], || {
+        let flags = EpollFlags::EPOLLIN | EpollFlags::EPOLLEXCLUSIVE;
+
+        // EPOLLEXCLUSIVE can't be used with EPOLLONESHOT
+        let mut event = epoll::EpollEvent::new(flags | EpollFlags::EPOLLONESHOT, 0);
+        let rv = epoll::epoll_ctl(
+            epoll_fd,
+            epoll::EpollOp::EpollCtlAdd,
+            listener_fd,
+            Some(&mut event),
+        );
+        assert_eq!(rv, Err(Errno::EINVAL));
+
+        // once exclusive, the entry can't be modified
+        let mut event = epoll::EpollEvent::new(flags, 0);
+        epoll::epoll_ctl(
+            epoll_fd,
+            epoll::EpollOp::EpollCtlAdd,
+            listener_fd,
+            Some(&mut event),
+        )?;
+        let rv = epoll::epoll_ctl(
+            epoll_fd,
+            epoll::EpollOp::EpollCtlMod,
+            listener_fd,
+            Some(&mut event),
+        );
+        assert_eq!(rv, Err(Errno::EINVAL));
+
+        anyhow::Ok(())
+    })?;
+
+    // Wait on a new epoll for the listener to become readable, and accept the connection if it
+    // does. Returns the number of events.
+    let wait_and_accept = || -> nix::Result<usize> {
+        let epoll_fd = epoll::epoll_create()?;
+        let flags = EpollFlags::EPOLLIN | EpollFlags::EPOLLEXCLUSIVE;
+        let mut event = epoll::EpollEvent::new(flags, 0);
+        epoll::epoll_ctl(
+            epoll_fd,
+            epoll::EpollOp::EpollCtlAdd,
+            listener_fd,
+            Some(&mut event),
+        )?;
+
+        let res = do_epoll_wait(
+            epoll_fd,
+            Duration::from_millis(300),
+            /* do_read= */ false,
+        );
+        if res.epoll_res == Ok(1) {
+            let _ = listener.accept();
+        }
+
+        unistd::close(epoll_fd)?;
+        res.epoll_res
+    };
+
+    let child_pid = unsafe { libc::fork() };
+    assert!(child_pid >= 0);
+
+    if child_pid == 0 {
+        // the child's exit status is the number of events it received
+        let rv = match wait_and_accept() {
+            Ok(n) => n as i32,
+            Err(_) => 100,
+        };
+        unsafe { libc::_exit(rv) };
+    }
+
+    // connect once both processes are waiting
+    let connector = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(100));
+        std::net::TcpStream::connect(addr)
+    });
+
+    let parent_events = wait_and_accept()?;
+    let _stream = connector.join().unwrap()?;
+
+    let mut status = 0;
+    assert_eq!(
+        unsafe { libc::waitpid(child_pid, &mut status, 0) },
+        child_pid
+    );
+    assert!(libc::WIFEXITED(status));
+    let child_events: usize = libc::WEXITSTATUS(status).try_into()?;
+
+    // epoll_ctl(2): "one or more of the epoll file descriptors will receive an event". Shadow
+    // wakes exactly one of them.
+    if test_utils::running_in_shadow() {
+        assert_eq!(parent_events + child_events, 1);
+    } else {
+        assert!(parent_events + child_events >= 1);
+    }
+
+    Ok(())
+}
+
+fn test_nested() -> anyhow::Result<()> {
+    let (readfd, writefd) = unistd::pipe()?;
+    let inner_fd = epoll::epoll_create()?;
+    let outer_fd = epoll::epoll_create()?;
+
+    test_utils::run_and_close_fds(&[outer_fd, inner_fd, readfd, writefd], || {
+        let mut event = epoll::EpollEvent::new(EpollFlags::EPOLLIN, readfd.try_into().unwrap());
+        epoll::epoll_ctl(
+            inner_fd,
+            epoll::EpollOp::EpollCtlAdd,
+            readfd,
+            Some(&mut event),
+        )?;
+
+        let mut event = epoll::EpollEvent::new(EpollFlags::EPOLLIN, inner_fd.try_into().unwrap());
+        epoll::epoll_ctl(
+            outer_fd,
+            epoll::EpollOp::EpollCtlAdd,
+            inner_fd,
+            Some(&mut event),
+        )?;
+
+        // the epoll instances can't monitor each other
+        let mut event = epoll::EpollEvent::new(EpollFlags::EPOLLIN, 0);
+        let rv = epoll::epoll_ctl(
+            inner_fd,
+            epoll::EpollOp::EpollCtlAdd,
+            outer_fd,
+            Some(&mut event),
+        );
+        assert_eq!(rv, Err(Errno::ELOOP));
+
+        // nothing is ready yet
+        let res = do_epoll_wait(outer_fd, Duration::ZERO, /* do_read= */ false);
+        assert_eq!(res.epoll_res, Ok(0));
+
+        // the outer epoll should be woken when the pipe becomes readable
+        let writer = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            unistd::write(writefd, &[0])
+        });
+
+        let res = do_epoll_wait(outer_fd, Duration::from_secs(1), /* do_read= */ false);
+        writer.join().unwrap()?;
+
+        assert_eq!(res.epoll_res, Ok(1));
+        assert_eq!(res.events[0].events(), EpollFlags::EPOLLIN);
+        assert_eq!(res.events[0].data(), inner_fd.try_into().unwrap());
+        assert!(res.duration >= Duration::from_millis(100));
+
+        // the inner epoll reports the pipe
+        let res = do_epoll_wait(inner_fd, Duration::ZERO, /* do_read= */ true);
+        assert_eq!(res.epoll_res, Ok(1));
+        assert_eq!(res.events[0].data(), readfd.try_into().unwrap());
+
+        // the pipe was read, so neither epoll is ready
+        let res = do_epoll_wait(outer_fd, Duration::ZERO, /* do_read= */ false);
+        assert_eq!(res.epoll_res, Ok(0));
+
+        Ok(())
+    })
+}
+
+fn test_nested_too_deep() -> anyhow::Result<()> {
+    let fds: Vec<_> = (0..6)
+        .map(|_| epoll::epoll_create())
+        .collect::<nix::Result<_>>()?;
+
+    test_utils::run_and_close_fds(&fds, || {
+        let add = |epfd: i32, fd: i32| {
+            let mut event = epoll::EpollEvent::new(EpollFlags::EPOLLIN, 0);
+            epoll::epoll_ctl(epfd, epoll::EpollOp::EpollCtlAdd, fd, Some(&mut event))
+        };
+
+        // Build a chain where each epoll monitors the previous one. Five levels of epoll instances
+        // are allowed, but not six.
+        for i in 1..5 {
+            assert_eq!(add(fds[i], fds[i - 1]), Ok(()));
+        }
+
+        // A new level can't be added above the top of the chain, or below the bottom of it.
+        assert_eq!(add(fds[5], fds[4]), Err(Errno::ELOOP));
+        assert_eq!(add(fds[0], fds[5]), Err(Errno::ELOOP));
+
+        // Removing the top of the chain makes room for another level below the bottom.
+        epoll::epoll_ctl(fds[4], epoll::EpollOp::EpollCtlDel, fds[3], None)?;
+        assert_eq!(add(fds[0], fds[5]), Ok(()));
+
+        Ok(())
+    })
+}
+
 fn main() -> anyhow::Result<()> {
     // should we restrict the tests we run?
     let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
@@ -356,7 +607,11 @@ fn main() -> anyhow::Result<()> {
             test_wait_negative_timeout,
             all_envs.clone(),
         ),
-        ShadowTest::new("test_ctl_invalid_op", test_ctl_invalid_op, all_envs),
+        ShadowTest::new("test_ctl_invalid_op", test_ctl_invalid_op, all_envs.clone()),
+        ShadowTest::new("test_oneshot", test_oneshot, all_envs.clone()),
+        ShadowTest::new("test_exclusive", test_exclusive, all_envs.clone()),
+        ShadowTest::new("test_nested", test_nested, all_envs.clone()),
+        ShadowTest::new("test_nested_too_deep", test_nested_too_deep, all_envs),
     ];
 
     if filter_shadow_passing {
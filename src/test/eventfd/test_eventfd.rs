@@ -53,6 +53,11 @@ fn main() -> Result<(), String> {
             test_eventfd_read_write_semaphore_nonblock,
             set![TestEnv::Libc, TestEnv::Shadow],
         ),
+        test_utils::ShadowTest::new(
+            "test_eventfd_semaphore_decrement",
+            test_eventfd_semaphore_decrement,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
     ];
 
     if filter_shadow_passing {
@@ -408,3 +413,34 @@ fn test_eventfd_read_write_semaphore_nonblock() -> Result<(), String> {
         Ok(())
     })
 }
+
+fn test_eventfd_semaphore_decrement() -> Result<(), String> {
+    let flag = EfdFlags::EFD_NONBLOCK | EfdFlags::EFD_SEMAPHORE;
+    let efd: RawFd = call_eventfd(0, flag)?;
+
+    test_utils::result_assert(
+        efd > 0,
+        &format!(
+            "Unexpected retval {} from eventfd with flag {}",
+            efd,
+            flag.bits()
+        ),
+    )?;
+
+    test_utils::run_and_close_fds(&[efd], || {
+        // Nothing to read yet
+        check_read_eagain(efd)?;
+
+        // Each read should decrement the counter by 1 and return 1, rather than returning 3 and
+        // resetting the counter
+        check_write_success(efd, 3)?;
+        check_read_success(efd, 1)?;
+        check_read_success(efd, 1)?;
+        check_read_success(efd, 1)?;
+
+        // The counter is now 0, so a read would block
+        check_read_eagain(efd)?;
+
+        Ok(())
+    })
+}
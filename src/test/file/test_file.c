@@ -10,18 +10,25 @@
 #include <fcntl.h>
 #include <glib.h>
 #include <libgen.h>
+#include <limits.h>
 #include <stdio.h>
 #include <stdlib.h>
 #include <string.h>
+#include <sys/file.h>
 #include <sys/ioctl.h>
+#include <sys/socket.h>
 #include <sys/stat.h>
 #include <sys/types.h>
 #include <sys/uio.h>
+#include <sys/wait.h>
 #include <termios.h>
 #include <unistd.h>
 
+#include "test/test_common.h"
 #include "test/test_glib_helpers.h"
 
+#define ARRAY_LENGTH(arr)  (sizeof (arr) / sizeof ((arr)[0]))
+
 // For use in conjunction with g_auto so that the files/dirs will delete themselves on function
 // exit.
 #define AUTOFILE_NAME_MAXLEN 24
@@ -346,6 +353,79 @@ static void _test_readv() {
     assert_nonneg_errno(close(fd));
 }
 
+static void _test_readv_eof() {
+    g_auto(AutoDeleteFile) adf = _create_auto_file();
+    const char wbuf[] = "0123456789";
+    int fd, rv;
+    _set_contents(&adf, wbuf, sizeof(wbuf));
+
+    // the first buffer is filled, and the second reaches the end of the file
+    char buf_1[4] = {0};
+    char buf_2[16] = {0};
+    struct iovec iov[2] = {
+        {.iov_base = buf_1, .iov_len = sizeof(buf_1)},
+        {.iov_base = buf_2, .iov_len = sizeof(buf_2)},
+    };
+
+    assert_nonneg_errno(fd = open(adf.name, O_RDONLY));
+    assert_nonneg_errno(rv = readv(fd, iov, 2));
+    g_assert_cmpint(rv, ==, sizeof(wbuf));
+    g_assert_cmpmem(buf_1, sizeof(buf_1), wbuf, sizeof(buf_1));
+    g_assert_cmpmem(buf_2, sizeof(wbuf) - sizeof(buf_1), wbuf + sizeof(buf_1),
+                    sizeof(wbuf) - sizeof(buf_1));
+
+    // at the end of the file
+    assert_nonneg_errno(rv = readv(fd, iov, 2));
+    g_assert_cmpint(rv, ==, 0);
+    assert_nonneg_errno(close(fd));
+
+    // the same with a pipe, which only has some of the requested bytes available
+    int pipes[2] = {-1, -1};
+    assert_nonneg_errno(pipe(pipes));
+    assert_nonneg_errno(rv = write(pipes[1], "abcdef", 6));
+    memset(buf_1, 0, sizeof(buf_1));
+    memset(buf_2, 0, sizeof(buf_2));
+    assert_nonneg_errno(rv = readv(pipes[0], iov, 2));
+    g_assert_cmpint(rv, ==, 6);
+    g_assert_cmpmem(buf_1, sizeof(buf_1), "abcd", 4);
+    g_assert_cmpmem(buf_2, 2, "ef", 2);
+
+    // writev returns the total across all buffers
+    assert_nonneg_errno(rv = writev(pipes[1], iov, 2));
+    g_assert_cmpint(rv, ==, sizeof(buf_1) + sizeof(buf_2));
+
+    assert_nonneg_errno(close(pipes[0]));
+    assert_nonneg_errno(close(pipes[1]));
+}
+
+static void _test_readv_too_long() {
+    g_auto(AutoDeleteFile) adf = _create_auto_file();
+    int pipes[2] = {-1, -1};
+    char buf[4] = {0};
+    int fd;
+
+    // the length doesn't fit in the ssize_t return value
+    struct iovec iov[2] = {
+        {.iov_base = buf, .iov_len = sizeof(buf)},
+        {.iov_base = buf, .iov_len = (size_t)SSIZE_MAX + 1},
+    };
+
+    assert_nonneg_errno(fd = open(adf.name, O_RDWR));
+    g_assert_cmpint(readv(fd, iov, 2), ==, -1);
+    assert_errno_is(EINVAL);
+    g_assert_cmpint(writev(fd, iov, 2), ==, -1);
+    assert_errno_is(EINVAL);
+    assert_nonneg_errno(close(fd));
+
+    assert_nonneg_errno(pipe(pipes));
+    g_assert_cmpint(readv(pipes[0], iov, 2), ==, -1);
+    assert_errno_is(EINVAL);
+    g_assert_cmpint(writev(pipes[1], iov, 2), ==, -1);
+    assert_errno_is(EINVAL);
+    assert_nonneg_errno(close(pipes[0]));
+    assert_nonneg_errno(close(pipes[1]));
+}
+
 static void _test_preadv() {
     g_auto(AutoDeleteFile) adf = _create_auto_file();
     const char wbuf[] = "0123456789";
@@ -506,6 +586,32 @@ static void _test_preadv2() {
     assert_nonneg_errno(close(fd));
 }
 
+static void _test_preadv2_flags() {
+    int fds[2];
+    assert_nonneg_errno(socketpair(AF_UNIX, SOCK_STREAM, 0, fds));
+
+    char buf[4] = {0};
+    struct iovec iov = {.iov_base = buf, .iov_len = sizeof(buf)};
+
+    // the socket is blocking, but RWF_NOWAIT means we shouldn't wait for data
+    g_assert_cmpint(preadv2(fds[0], &iov, 1, -1, RWF_NOWAIT), ==, -1);
+    assert_errno_is(EAGAIN);
+
+    // the other flags are accepted
+    assert_nonneg_errno(write(fds[1], "abc", 3));
+    ssize_t rv;
+    assert_nonneg_errno(rv = preadv2(fds[0], &iov, 1, -1, RWF_HIPRI | RWF_DSYNC | RWF_SYNC));
+    g_assert_cmpint(rv, ==, 3);
+    g_assert_cmpmem(buf, 3, "abc", 3);
+
+    // unknown flags aren't
+    g_assert_cmpint(preadv2(fds[0], &iov, 1, -1, 0x40000000), ==, -1);
+    assert_errno_is(EOPNOTSUPP);
+
+    assert_nonneg_errno(close(fds[0]));
+    assert_nonneg_errno(close(fds[1]));
+}
+
 static void _test_lseek() {
     g_auto(AutoDeleteFile) adf = _create_auto_file();
     const char wbuf[] = "test file lseek";
@@ -534,6 +640,170 @@ static void _test_lseek_pipe() {
     assert_nonneg_errno(close(pipes[1]));
 }
 
+static void _test_lseek_data_hole() {
+    g_auto(AutoDeleteFile) adf = _create_auto_file();
+    const off_t data_len = 4096;
+    const off_t second_data = 1024 * 1024;
+    const off_t file_len = second_data + data_len;
+    char wbuf[4096];
+    memset(wbuf, 'x', sizeof(wbuf));
+    off_t rv;
+
+    // data in [0, 4K) and [1M, 1M+4K), with a hole between them
+    assert_nonneg_errno(rv = pwrite(adf.fd, wbuf, data_len, 0));
+    g_assert_cmpint(rv, ==, data_len);
+    assert_nonneg_errno(rv = pwrite(adf.fd, wbuf, data_len, second_data));
+    g_assert_cmpint(rv, ==, data_len);
+
+    assert_nonneg_errno(rv = lseek(adf.fd, 0, SEEK_DATA));
+    g_assert_cmpint(rv, ==, 0);
+
+    off_t hole;
+    assert_nonneg_errno(hole = lseek(adf.fd, 0, SEEK_HOLE));
+    if (hole == file_len) {
+        // filesystems that don't support holes report the whole file as data
+        g_test_skip("The native filesystem doesn't report holes");
+        return;
+    }
+    g_assert_cmpint(hole, >=, data_len);
+    g_assert_cmpint(hole, <, second_data);
+
+    // the file offset is set to the result
+    assert_nonneg_errno(rv = lseek(adf.fd, 0, SEEK_CUR));
+    g_assert_cmpint(rv, ==, hole);
+
+    assert_nonneg_errno(rv = lseek(adf.fd, hole, SEEK_DATA));
+    g_assert_cmpint(rv, ==, second_data);
+
+    // there's an implicit hole at the end of the file
+    assert_nonneg_errno(rv = lseek(adf.fd, second_data, SEEK_HOLE));
+    g_assert_cmpint(rv, ==, file_len);
+
+    // at or past the end of the file
+    g_assert_cmpint(lseek(adf.fd, file_len, SEEK_DATA), ==, -1);
+    assert_errno_is(ENXIO);
+    g_assert_cmpint(lseek(adf.fd, file_len, SEEK_HOLE), ==, -1);
+    assert_errno_is(ENXIO);
+}
+
+static void _test_sync_file_range() {
+    g_auto(AutoDeleteFile) adf = _create_auto_file();
+    const char wbuf[] = "test file sync_file_range";
+    _set_contents(&adf, wbuf, sizeof(wbuf));
+
+    assert_nonneg_errno(sync_file_range(adf.fd, 0, 0, 0));
+    assert_nonneg_errno(sync_file_range(adf.fd, 0, sizeof(wbuf),
+                                        SYNC_FILE_RANGE_WAIT_BEFORE | SYNC_FILE_RANGE_WRITE |
+                                            SYNC_FILE_RANGE_WAIT_AFTER));
+
+    g_assert_cmpint(sync_file_range(adf.fd, 0, 0, 0x100), ==, -1);
+    assert_errno_is(EINVAL);
+
+    g_assert_cmpint(sync_file_range(adf.fd, -1, 0, 0), ==, -1);
+    assert_errno_is(EINVAL);
+
+    g_assert_cmpint(sync_file_range(-1, 0, 0, 0), ==, -1);
+    assert_errno_is(EBADF);
+}
+
+static void _test_sync_file_range_pipe() {
+    int pipes[2] = {-1, -1};
+
+    assert_nonneg_errno(pipe(pipes));
+
+    g_assert_cmpint(sync_file_range(pipes[1], 0, 0, SYNC_FILE_RANGE_WRITE), ==, -1);
+    assert_errno_is(ESPIPE);
+
+    assert_nonneg_errno(close(pipes[0]));
+    assert_nonneg_errno(close(pipes[1]));
+}
+
+static void _test_fadvise() {
+    g_auto(AutoDeleteFile) adf = _create_auto_file();
+
+    const int advices[] = {POSIX_FADV_NORMAL,   POSIX_FADV_RANDOM,   POSIX_FADV_SEQUENTIAL,
+                           POSIX_FADV_WILLNEED, POSIX_FADV_DONTNEED, POSIX_FADV_NOREUSE};
+    for (size_t i = 0; i < sizeof(advices) / sizeof(*advices); i++) {
+        // posix_fadvise returns the error number rather than setting errno
+        g_assert_cmpint(posix_fadvise(adf.fd, 0, 0, advices[i]), ==, 0);
+    }
+
+    g_assert_cmpint(posix_fadvise(adf.fd, 0, 0, 100), ==, EINVAL);
+    g_assert_cmpint(posix_fadvise(adf.fd, 0, -1, POSIX_FADV_NORMAL), ==, EINVAL);
+    g_assert_cmpint(posix_fadvise(-1, 0, 0, POSIX_FADV_NORMAL), ==, EBADF);
+
+    int pipes[2] = {-1, -1};
+    assert_nonneg_errno(pipe(pipes));
+    g_assert_cmpint(posix_fadvise(pipes[0], 0, 0, POSIX_FADV_NORMAL), ==, ESPIPE);
+    assert_nonneg_errno(close(pipes[0]));
+    assert_nonneg_errno(close(pipes[1]));
+}
+
+static void _test_readahead() {
+    g_auto(AutoDeleteFile) adf = _create_auto_file();
+    const char wbuf[] = "test file readahead";
+    _set_contents(&adf, wbuf, sizeof(wbuf));
+
+    assert_nonneg_errno(readahead(adf.fd, 0, sizeof(wbuf)));
+
+    int fd;
+    assert_nonneg_errno(fd = open(adf.name, O_WRONLY));
+    g_assert_cmpint(readahead(fd, 0, sizeof(wbuf)), ==, -1);
+    assert_errno_is(EBADF);
+    assert_nonneg_errno(close(fd));
+
+    g_assert_cmpint(readahead(-1, 0, 0), ==, -1);
+    assert_errno_is(EBADF);
+}
+
+static void _test_readahead_socket() {
+    int fd;
+    assert_nonneg_errno(fd = socket(AF_INET, SOCK_DGRAM, 0));
+
+    // sockets have no page cache to read ahead into
+    g_assert_cmpint(readahead(fd, 0, 0), ==, -1);
+    assert_errno_is(EINVAL);
+
+    assert_nonneg_errno(close(fd));
+}
+
+static void _test_name_to_handle_at() {
+    g_auto(AutoDeleteFile) adf = _create_auto_file();
+    const char* contents = "hello";
+    _set_contents(&adf, contents, strlen(contents));
+
+    g_autofree struct file_handle* handle = g_malloc0(sizeof(*handle) + MAX_HANDLE_SZ);
+    int mount_id = 0;
+
+    // with no space for the handle, the required size is returned
+    handle->handle_bytes = 0;
+    int rv = name_to_handle_at(AT_FDCWD, adf.name, handle, &mount_id, 0);
+    if (rv == -1 && errno == EOPNOTSUPP) {
+        g_test_skip("The filesystem doesn't support file handles");
+        return;
+    }
+    g_assert_cmpint(rv, ==, -1);
+    assert_errno_is(EOVERFLOW);
+    g_assert_cmpint(handle->handle_bytes, >, 0);
+    g_assert_cmpint(handle->handle_bytes, <=, MAX_HANDLE_SZ);
+
+    assert_nonneg_errno(name_to_handle_at(AT_FDCWD, adf.name, handle, &mount_id, 0));
+
+    // opening a file by its handle requires the CAP_DAC_READ_SEARCH capability
+    int fd = open_by_handle_at(adf.fd, handle, O_RDONLY);
+    if (fd == -1 && errno == EPERM) {
+        g_test_skip("Not permitted to open files by handle");
+        return;
+    }
+    assert_nonneg_errno(fd);
+
+    char buf[16] = {0};
+    g_assert_cmpint(read(fd, buf, sizeof(buf)), ==, strlen(contents));
+    g_assert_cmpstr(buf, ==, contents);
+
+    assert_nonneg_errno(close(fd));
+}
+
 static void _test_fopen() {
     g_auto(AutoDeleteFile) adf = _create_auto_file();
     FILE* file;
@@ -713,6 +983,48 @@ static void _test_dir() {
     assert_nonneg_errno(rmdir(adf.name));
 }
 
+// Reads the names of the entries in `dirname`, in the order returned by readdir.
+static GPtrArray* _read_dir_names(const char* dirname) {
+    GPtrArray* names = g_ptr_array_new_with_free_func(g_free);
+    DIR* dir;
+    struct dirent* de;
+    assert_nonnull_errno(dir = opendir(dirname));
+    while ((de = readdir(dir)) != NULL) {
+        g_ptr_array_add(names, g_strdup(de->d_name));
+    }
+    assert_nonneg_errno(closedir(dir));
+    return names;
+}
+
+static void _test_dir_order() {
+    g_auto(AutoDeleteFile) adf = _create_auto_dir();
+    const char* filenames[] = {"zeta", "alpha", "mid", "beta", "omega"};
+
+    for (int i = 0; i < ARRAY_LENGTH(filenames); ++i) {
+        char* path = g_build_filename(adf.name, filenames[i], NULL);
+        int fd;
+        assert_nonneg_errno(fd = open(path, O_CREAT | O_WRONLY, S_IRUSR | S_IWUSR));
+        assert_nonneg_errno(close(fd));
+        g_free(path);
+    }
+
+    g_autoptr(GPtrArray) first = _read_dir_names(adf.name);
+    g_autoptr(GPtrArray) second = _read_dir_names(adf.name);
+
+    // The files, ".", and "..".
+    g_assert_cmpint(first->len, ==, ARRAY_LENGTH(filenames) + 2);
+    g_assert_cmpint(second->len, ==, first->len);
+    for (int i = 0; i < first->len; ++i) {
+        g_assert_cmpstr(g_ptr_array_index(first, i), ==, g_ptr_array_index(second, i));
+    }
+
+    for (int i = 0; i < ARRAY_LENGTH(filenames); ++i) {
+        char* path = g_build_filename(adf.name, filenames[i], NULL);
+        assert_nonneg_errno(unlink(path));
+        g_free(path);
+    }
+}
+
 static void _test_tmpfile() {
     const char wbuf[] = "test file tmpfile";
     char rbuf[sizeof(wbuf)] = {0};
@@ -733,6 +1045,42 @@ static void _test_tmpfile() {
     assert_nonneg_errno(fclose(file));
 }
 
+static void _test_open_direct() {
+    // natively, direct I/O requires aligned buffers and isn't supported by all filesystems
+    if (!running_in_shadow()) {
+        g_test_skip("O_DIRECT behaviour depends on the native filesystem");
+        return;
+    }
+
+    g_auto(AutoDeleteFile) adf = _create_auto_file();
+    char rbuf[6] = {0};
+    int fd;
+    ssize_t rv;
+
+    assert_nonneg_errno(fd = open(adf.name, O_RDWR | O_DIRECT | O_SYNC));
+
+    // the flags are accepted and reported back
+    int flags;
+    assert_nonneg_errno(flags = fcntl(fd, F_GETFL));
+    g_assert_cmpint(flags & O_DIRECT, ==, O_DIRECT);
+    g_assert_cmpint(flags & O_SYNC, ==, O_SYNC);
+
+    // unaligned I/O works since it's buffered
+    assert_nonneg_errno(rv = write(fd, "hello", 6));
+    g_assert_cmpint(rv, ==, 6);
+    assert_nonneg_errno(lseek(fd, 0, SEEK_SET));
+    assert_nonneg_errno(rv = read(fd, rbuf, sizeof(rbuf)));
+    g_assert_cmpint(rv, ==, sizeof(rbuf));
+    g_assert_cmpstr(rbuf, ==, "hello");
+
+    // O_DIRECT can be cleared with F_SETFL
+    assert_nonneg_errno(fcntl(fd, F_SETFL, flags & ~O_DIRECT));
+    assert_nonneg_errno(flags = fcntl(fd, F_GETFL));
+    g_assert_cmpint(flags & O_DIRECT, ==, 0);
+
+    assert_nonneg_errno(close(fd));
+}
+
 static void _test_dup() {
     g_auto(AutoDeleteFile) adf = _create_auto_file();
     char rbuf[3] = {0};
@@ -766,6 +1114,113 @@ static void _test_dup() {
     assert_nonneg_errno(close(fd2));
 }
 
+/* Fork a child that opens the file separately and tries to take a non-blocking lock with the
+ * given operation. Returns the errno of the child's flock, or 0 if it succeeded. */
+static int _flock_in_child(const char* name, int operation) {
+    pid_t pid = fork();
+    assert_nonneg_errno(pid);
+
+    if (pid == 0) {
+        int fd = open(name, O_RDONLY);
+        if (fd < 0) {
+            _exit(255);
+        }
+        int rv = flock(fd, operation | LOCK_NB);
+        _exit(rv < 0 ? errno : 0);
+    }
+
+    int status = 0;
+    assert_nonneg_errno(waitpid(pid, &status, 0));
+    g_assert_true(WIFEXITED(status));
+    g_assert_cmpint(WEXITSTATUS(status), !=, 255);
+    return WEXITSTATUS(status);
+}
+
+static void _test_flock() {
+    g_auto(AutoDeleteFile) adf = _create_auto_file();
+    int fd;
+
+    assert_nonneg_errno(fd = open(adf.name, O_RDONLY));
+
+    // an exclusive lock blocks both exclusive and shared locks from another process
+    assert_nonneg_errno(flock(fd, LOCK_EX));
+    g_assert_cmpint(_flock_in_child(adf.name, LOCK_EX), ==, EWOULDBLOCK);
+    g_assert_cmpint(_flock_in_child(adf.name, LOCK_SH), ==, EWOULDBLOCK);
+
+    // a shared lock only blocks exclusive locks
+    assert_nonneg_errno(flock(fd, LOCK_SH));
+    g_assert_cmpint(_flock_in_child(adf.name, LOCK_SH), ==, 0);
+    g_assert_cmpint(_flock_in_child(adf.name, LOCK_EX), ==, EWOULDBLOCK);
+
+    // the lock is released by unlocking, and the child's locks by its exit
+    assert_nonneg_errno(flock(fd, LOCK_UN));
+    g_assert_cmpint(_flock_in_child(adf.name, LOCK_EX), ==, 0);
+
+    // a lock is released when the last descriptor for the open file is closed
+    assert_nonneg_errno(flock(fd, LOCK_EX));
+    assert_nonneg_errno(close(fd));
+    g_assert_cmpint(_flock_in_child(adf.name, LOCK_EX), ==, 0);
+}
+
+static void _set_record_lock(struct flock* lock, short type, off_t start, off_t len) {
+    *lock = (struct flock){.l_type = type, .l_whence = SEEK_SET, .l_start = start, .l_len = len};
+}
+
+static void _test_fcntl_record_lock() {
+    g_auto(AutoDeleteFile) adf = _create_auto_file();
+    struct flock lock;
+    int fd;
+
+    assert_nonneg_errno(fd = open(adf.name, O_RDWR));
+
+    _set_record_lock(&lock, F_WRLCK, 0, 10);
+    assert_nonneg_errno(fcntl(fd, F_SETLK, &lock));
+
+    pid_t parent = getpid();
+    pid_t pid = fork();
+    assert_nonneg_errno(pid);
+
+    if (pid == 0) {
+        int fd2 = open(adf.name, O_RDWR);
+        g_assert_cmpint(fd2, >=, 0);
+
+        // an overlapping range conflicts with the parent's lock
+        _set_record_lock(&lock, F_WRLCK, 5, 10);
+        g_assert_cmpint(fcntl(fd2, F_SETLK, &lock), ==, -1);
+        g_assert_true(errno == EAGAIN || errno == EACCES);
+
+        _set_record_lock(&lock, F_RDLCK, 5, 10);
+        g_assert_cmpint(fcntl(fd2, F_SETLK, &lock), ==, -1);
+
+        // F_GETLK describes the conflicting lock
+        _set_record_lock(&lock, F_WRLCK, 5, 10);
+        g_assert_cmpint(fcntl(fd2, F_GETLK, &lock), ==, 0);
+        g_assert_cmpint(lock.l_type, ==, F_WRLCK);
+        g_assert_cmpint(lock.l_whence, ==, SEEK_SET);
+        g_assert_cmpint(lock.l_start, ==, 0);
+        g_assert_cmpint(lock.l_len, ==, 10);
+        g_assert_cmpint(lock.l_pid, ==, parent);
+
+        // a range after the parent's lock doesn't conflict
+        _set_record_lock(&lock, F_WRLCK, 10, 10);
+        g_assert_cmpint(fcntl(fd2, F_SETLK, &lock), ==, 0);
+
+        _exit(0);
+    }
+
+    int status = 0;
+    assert_nonneg_errno(waitpid(pid, &status, 0));
+    g_assert_true(WIFEXITED(status));
+    g_assert_cmpint(WEXITSTATUS(status), ==, 0);
+
+    // the child's lock was released when it exited
+    _set_record_lock(&lock, F_WRLCK, 0, 0);
+    assert_nonneg_errno(fcntl(fd, F_GETLK, &lock));
+    g_assert_cmpint(lock.l_type, ==, F_UNLCK);
+
+    assert_nonneg_errno(close(fd));
+}
+
 static void _ioctl_check_enotty(int fd, int request) {
     struct termios term = {0};
     int rv = ioctl(fd, request, &term);
@@ -834,8 +1289,6 @@ __attribute__((unused)) static void _test_iov() {
     // '0' iovcnt
     assert_nonneg_errno(readv(filed, iov, 0));
 
-#define ARRAY_LENGTH(arr)  (sizeof (arr) / sizeof ((arr)[0]))
-
     // make all bases point to a string but all len to 0
     for (int i = 0; i < ARRAY_LENGTH(iov); ++i) {
         iov[i].iov_base = "REAL DATA";
@@ -993,10 +1446,20 @@ int main(int argc, char* argv[]) {
     g_test_add_func("/file/read", _test_read);
     g_test_add_func("/file/pread", _test_pread);
     g_test_add_func("/file/readv", _test_readv);
+    g_test_add_func("/file/readv_eof", _test_readv_eof);
+    g_test_add_func("/file/readv_too_long", _test_readv_too_long);
     g_test_add_func("/file/preadv", _test_preadv);
     g_test_add_func("/file/preadv2", _test_preadv2);
+    g_test_add_func("/file/preadv2_flags", _test_preadv2_flags);
     g_test_add_func("/file/lseek", _test_lseek);
     g_test_add_func("/file/lseek_pipe", _test_lseek_pipe);
+    g_test_add_func("/file/lseek_data_hole", _test_lseek_data_hole);
+    g_test_add_func("/file/sync_file_range", _test_sync_file_range);
+    g_test_add_func("/file/sync_file_range_pipe", _test_sync_file_range_pipe);
+    g_test_add_func("/file/fadvise", _test_fadvise);
+    g_test_add_func("/file/readahead", _test_readahead);
+    g_test_add_func("/file/readahead_socket", _test_readahead_socket);
+    g_test_add_func("/file/name_to_handle_at", _test_name_to_handle_at);
     g_test_add_func("/file/fopen", _test_fopen);
     g_test_add_func("/file/fclose", _test_fclose);
     g_test_add_func("/file/fileno", _test_fileno);
@@ -1010,8 +1473,12 @@ int main(int argc, char* argv[]) {
     g_test_add_func("/file/stat", _test_stat);
 
     g_test_add_func("/file/dir", _test_dir);
+    g_test_add_func("/file/dir_order", _test_dir_order);
     g_test_add_func("/file/tmpfile", _test_tmpfile);
+    g_test_add_func("/file/open_direct", _test_open_direct);
     g_test_add_func("/file/dup", _test_dup);
+    g_test_add_func("/file/flock", _test_flock);
+    g_test_add_func("/file/fcntl_record_lock", _test_fcntl_record_lock);
     g_test_add_func("/file/ioctl_tty", _test_ioctl_tty);
 
     //    TODO: debug and fix iov test
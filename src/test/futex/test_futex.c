@@ -12,9 +12,11 @@
 #include <stdio.h>
 #include <stdlib.h>
 #include <string.h>
+#include <sys/mman.h>
 #include <sys/syscall.h>
 #include <sys/time.h>
 #include <sys/types.h>
+#include <sys/wait.h>
 #include <unistd.h>
 
 #include "lib/logger/logger.h"
@@ -98,6 +100,193 @@ static void _futex_wait_test() {
     _wait_for_condition(&arg.child_finished);
 }
 
+static void* _futex_wake_op_test_child(void* void_arg) {
+    FutexWaitTestChildArg* arg = void_arg;
+    atomic_store(&arg->child_started, true);
+    // The parent modifies the futex word without setting the low bit, so keep
+    // waiting with the latest value until it's set.
+    while (1) {
+        int val = atomic_load(&arg->futex);
+        if (val & AVAILABLE) {
+            break;
+        }
+        long rv = syscall(SYS_futex, &arg->futex, FUTEX_WAIT, val, NULL, NULL, 0);
+        if (rv != 0) {
+            assert_errno_is(EAGAIN);
+        }
+    }
+    atomic_store(&arg->child_finished, true);
+    return NULL;
+}
+
+static void _futex_wake_op_test() {
+    FutexWaitTestChildArg arg = {
+        .child_started = false, .futex = UNAVAILABLE, .child_finished = false};
+    atomic_int other_futex = 0;
+    pthread_t child = {0};
+    assert_nonneg_errno(pthread_create(&child, NULL, _futex_wake_op_test_child, &arg));
+    _wait_for_condition(&arg.child_started);
+
+    // Add 2 to the child's futex word, but don't wake it since the old value
+    // isn't less than 0.
+    long woken = syscall(SYS_futex, &other_futex, FUTEX_WAKE_OP, 1, (void*)1, &arg.futex,
+                         FUTEX_OP(FUTEX_OP_ADD, 2, FUTEX_OP_CMP_LT, 0));
+    assert_nonneg_errno(woken);
+    g_assert_cmpint(woken, ==, 0);
+    g_assert_cmpint(atomic_load(&arg.futex), ==, 2);
+    g_assert_false(atomic_load(&arg.child_finished));
+
+    // Add 2 and wake the child, since the old value is now greater than 0.
+    // There's no way to guarantee that the child is already asleep on the
+    // futex, so we need to loop.
+    int expected = 2;
+    while (1) {
+        woken = syscall(SYS_futex, &other_futex, FUTEX_WAKE_OP, 1, (void*)1, &arg.futex,
+                        FUTEX_OP(FUTEX_OP_ADD, 2, FUTEX_OP_CMP_GT, 0));
+        assert_nonneg_errno(woken);
+        expected += 2;
+        g_assert_cmpint(atomic_load(&arg.futex), ==, expected);
+        if (woken == 1) {
+            break;
+        }
+        g_assert_cmpint(woken, ==, 0);
+        usleep(1);
+    }
+
+    // The other futex word is left untouched.
+    g_assert_cmpint(atomic_load(&other_futex), ==, 0);
+
+    // Set the low bit to let the child finish, waking it if it went back to sleep.
+    woken = syscall(SYS_futex, &other_futex, FUTEX_WAKE_OP, 1, (void*)1, &arg.futex,
+                    FUTEX_OP(FUTEX_OP_OR, AVAILABLE, FUTEX_OP_CMP_GE, 0));
+    assert_nonneg_errno(woken);
+    g_assert_cmpint(woken, <=, 1);
+
+    _wait_for_condition(&arg.child_finished);
+}
+
+#define NUM_REQUEUE_WAITERS 3
+
+typedef struct {
+    atomic_int* cond;
+    atomic_bool child_started;
+    atomic_bool child_finished;
+} FutexCmpRequeueTestChildArg;
+
+static void* _futex_cmp_requeue_test_child(void* void_arg) {
+    FutexCmpRequeueTestChildArg* arg = void_arg;
+    atomic_store(&arg->child_started, true);
+    // The value of the condition variable never changes, so this only returns
+    // once we're woken up.
+    long rv = syscall(SYS_futex, arg->cond, FUTEX_WAIT, 0, NULL, NULL, 0);
+    assert_nonneg_errno(rv);
+    atomic_store(&arg->child_finished, true);
+    return NULL;
+}
+
+static int _futex_cmp_requeue_test_num_finished(FutexCmpRequeueTestChildArg* args) {
+    int num_finished = 0;
+    for (int i = 0; i < NUM_REQUEUE_WAITERS; ++i) {
+        if (atomic_load(&args[i].child_finished)) {
+            num_finished++;
+        }
+    }
+    return num_finished;
+}
+
+// Broadcast on a condition variable, requeueing waiters onto the mutex as
+// condition variable implementations do.
+static void _futex_cmp_requeue_test() {
+    atomic_int cond = 0;
+    atomic_int mutex = 0;
+    FutexCmpRequeueTestChildArg args[NUM_REQUEUE_WAITERS];
+
+    for (int i = 0; i < NUM_REQUEUE_WAITERS; ++i) {
+        args[i] = (FutexCmpRequeueTestChildArg){
+            .cond = &cond,
+            .child_started = false,
+            .child_finished = false,
+        };
+        pthread_t child = {0};
+        assert_nonneg_errno(pthread_create(&child, NULL, _futex_cmp_requeue_test_child, &args[i]));
+        _wait_for_condition(&args[i].child_started);
+    }
+
+    // Wait a bit until they're (hopefully) all blocked on the futex.
+    usleep(10000);
+
+    // The operation fails if the value doesn't match.
+    long rv = syscall(SYS_futex, &cond, FUTEX_CMP_REQUEUE, 1, (void*)(long)INT_MAX, &mutex, 1);
+    g_assert_cmpint(rv, ==, -1);
+    assert_errno_is(EAGAIN);
+
+    // Wake one waiter and requeue the rest onto the mutex.
+    rv = syscall(SYS_futex, &cond, FUTEX_CMP_REQUEUE, 1, (void*)(long)INT_MAX, &mutex, 0);
+    assert_nonneg_errno(rv);
+    g_assert_cmpint(rv, ==, NUM_REQUEUE_WAITERS);
+
+    // Only the woken waiter should finish.
+    usleep(10000);
+    g_assert_cmpint(_futex_cmp_requeue_test_num_finished(args), ==, 1);
+
+    // The requeued waiters are no longer waiting on the condition variable.
+    g_assert_cmpint(syscall(SYS_futex, &cond, FUTEX_WAKE, INT_MAX, NULL, NULL, 0), ==, 0);
+
+    // Waking the mutex wakes the requeued waiters.
+    g_assert_cmpint(syscall(SYS_futex, &mutex, FUTEX_WAKE, INT_MAX, NULL, NULL, 0), ==,
+                    NUM_REQUEUE_WAITERS - 1);
+    for (int i = 0; i < NUM_REQUEUE_WAITERS; ++i) {
+        _wait_for_condition(&args[i].child_finished);
+    }
+}
+
+// A futex without FUTEX_PRIVATE_FLAG in shared memory can be used to synchronize processes.
+static void _futex_wait_shared_process_test() {
+    atomic_int* futex =
+        mmap(NULL, sizeof(*futex), PROT_READ | PROT_WRITE, MAP_SHARED | MAP_ANONYMOUS, -1, 0);
+    g_assert_true(futex != MAP_FAILED);
+    atomic_store(futex, UNAVAILABLE);
+
+    pid_t pid = fork();
+    assert_nonneg_errno(pid);
+    if (pid == 0) {
+        do {
+            long rv = syscall(SYS_futex, futex, FUTEX_WAIT, UNAVAILABLE, NULL, NULL, 0);
+            if (rv != 0 && errno != EAGAIN) {
+                _exit(EXIT_FAILURE);
+            }
+        } while (atomic_load(futex) != AVAILABLE);
+        _exit(EXIT_SUCCESS);
+    }
+
+    // Wake the child process. There's no way to guarantee that the child is
+    // already asleep on the futex, so we need to loop.
+    long woken = 0;
+    while (1) {
+        woken = syscall(SYS_futex, futex, FUTEX_WAKE, 1, NULL, NULL, 0);
+        assert_nonneg_errno(woken);
+        if (woken == 1) {
+            break;
+        }
+        g_assert_cmpint(woken, ==, 0);
+        usleep(1);
+    }
+
+    // Release the futex so that the child can finish. It may have gone back to
+    // sleep since the previous wake-up, so wake it again.
+    g_assert_cmpint(atomic_exchange(futex, AVAILABLE), ==, UNAVAILABLE);
+    woken = syscall(SYS_futex, futex, FUTEX_WAKE, 1, NULL, NULL, 0);
+    assert_nonneg_errno(woken);
+    g_assert_cmpint(woken, <=, 1);
+
+    int status = 0;
+    assert_nonneg_errno(waitpid(pid, &status, 0));
+    g_assert_true(WIFEXITED(status));
+    g_assert_cmpint(WEXITSTATUS(status), ==, EXIT_SUCCESS);
+
+    assert_nonneg_errno(munmap(futex, sizeof(*futex)));
+}
+
 static void _futex_wait_stale_test() {
     int futex = AVAILABLE;
     g_assert_cmpint(syscall(SYS_futex, &futex, FUTEX_WAIT, UNAVAILABLE, NULL, NULL, 0), ==, -1);
@@ -402,6 +591,9 @@ int main(int argc, char** argv) {
 
     g_test_add_func("/futex/wait", _futex_wait_test);
     g_test_add_func("/futex/wait_intr", _futex_wait_intr_test);
+    g_test_add_func("/futex/wait_shared_process", _futex_wait_shared_process_test);
+    g_test_add_func("/futex/wake_op", _futex_wake_op_test);
+    g_test_add_func("/futex/cmp_requeue", _futex_cmp_requeue_test);
     g_test_add_func("/futex/wait_stale", _futex_wait_stale_test);
     g_test_add_func("/futex/wake_nobody", _futex_wake_nobody_test);
     g_test_add_func("/futex/wake_stress", _futex_stress_test);
@@ -0,0 +1,186 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+use std::io::Write;
+use std::os::unix::ffi::OsStrExt;
+
+use test_utils::TestEnvironment as TestEnv;
+use test_utils::{set, ShadowTest};
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let all_envs = set![TestEnv::Libc, TestEnv::Shadow];
+    let mut tests: Vec<ShadowTest<_, _>> = vec![
+        ShadowTest::new(
+            "test_inotify_init_flags",
+            test_inotify_init_flags,
+            all_envs.clone(),
+        ),
+        ShadowTest::new(
+            "test_inotify_not_inotify_fd",
+            test_inotify_not_inotify_fd,
+            all_envs.clone(),
+        ),
+        ShadowTest::new(
+            "test_inotify_modify_event",
+            test_inotify_modify_event,
+            all_envs.clone(),
+        ),
+        ShadowTest::new(
+            "test_inotify_rm_watch",
+            test_inotify_rm_watch,
+            all_envs.clone(),
+        ),
+    ];
+
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+const EVENT_HEADER_LEN: usize = std::mem::size_of::<libc::inotify_event>();
+
+fn inotify_init1(flags: libc::c_int) -> Result<libc::c_int, String> {
+    test_utils::check_system_call!(|| unsafe { libc::inotify_init1(flags) }, &[])
+}
+
+fn add_watch(fd: libc::c_int, path: &std::path::Path, mask: u32) -> Result<libc::c_int, String> {
+    let path = std::ffi::CString::new(path.as_os_str().as_bytes()).unwrap();
+    test_utils::check_system_call!(
+        || unsafe { libc::inotify_add_watch(fd, path.as_ptr(), mask) },
+        &[]
+    )
+}
+
+/// Read all available events from the non-blocking inotify instance `fd`.
+fn read_events(fd: libc::c_int) -> Result<Vec<libc::inotify_event>, String> {
+    let mut buf = [0u8; 4096];
+    let rv = unsafe { libc::read(fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) };
+    if rv < 0 {
+        let errno = nix::errno::Errno::last();
+        if errno == nix::errno::Errno::EAGAIN {
+            return Ok(Vec::new());
+        }
+        return Err(format!("Unexpected error from read: {errno}"));
+    }
+
+    let mut events = Vec::new();
+    let mut remaining = &buf[..rv as usize];
+    while !remaining.is_empty() {
+        let event: libc::inotify_event =
+            unsafe { std::ptr::read_unaligned(remaining.as_ptr() as *const _) };
+        remaining = &remaining[EVENT_HEADER_LEN + event.len as usize..];
+        events.push(event);
+    }
+    Ok(events)
+}
+
+fn test_inotify_init_flags() -> Result<(), String> {
+    for flags in [0, libc::IN_NONBLOCK, libc::IN_CLOEXEC] {
+        let fd = inotify_init1(flags)?;
+        nix::unistd::close(fd).map_err(|e| e.to_string())?;
+    }
+
+    // an unknown flag
+    test_utils::check_system_call!(
+        || unsafe { libc::inotify_init1(libc::O_APPEND) },
+        &[libc::EINVAL]
+    )?;
+
+    Ok(())
+}
+
+fn test_inotify_not_inotify_fd() -> Result<(), String> {
+    let (read_fd, write_fd) = nix::unistd::pipe().map_err(|e| e.to_string())?;
+    let path = std::ffi::CString::new("/tmp").unwrap();
+
+    test_utils::check_system_call!(
+        || unsafe { libc::inotify_add_watch(read_fd, path.as_ptr(), libc::IN_MODIFY) },
+        &[libc::EINVAL]
+    )?;
+
+    nix::unistd::close(read_fd).map_err(|e| e.to_string())?;
+    nix::unistd::close(write_fd).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn test_inotify_modify_event() -> Result<(), String> {
+    let mut file = tempfile::NamedTempFile::new().map_err(|e| e.to_string())?;
+
+    let fd = inotify_init1(libc::IN_NONBLOCK)?;
+    let wd = add_watch(fd, file.path(), libc::IN_MODIFY)?;
+
+    // no events yet
+    test_utils::result_assert(read_events(fd)?.is_empty(), "Unexpected event")?;
+
+    file.write_all(b"hello").map_err(|e| e.to_string())?;
+    file.flush().map_err(|e| e.to_string())?;
+
+    // the instance should be readable
+    let mut poll_fds = [nix::poll::PollFd::new(fd, nix::poll::PollFlags::POLLIN)];
+    let count = nix::poll::poll(&mut poll_fds, 0).map_err(|e| e.to_string())?;
+    test_utils::result_assert_eq(count, 1, "inotify instance isn't readable")?;
+
+    // a buffer too small for the event is an error
+    let mut small_buf = [0u8; 1];
+    test_utils::check_system_call!(
+        || unsafe { libc::read(fd, small_buf.as_mut_ptr() as *mut libc::c_void, 1) },
+        &[libc::EINVAL]
+    )?;
+
+    let events = read_events(fd)?;
+    test_utils::result_assert(!events.is_empty(), "Expected a modify event")?;
+    for event in &events {
+        test_utils::result_assert_eq(event.wd, wd, "Unexpected watch descriptor")?;
+        test_utils::result_assert_eq(event.mask, libc::IN_MODIFY, "Unexpected event mask")?;
+        // events on a watched file (rather than directory) don't include a name
+        test_utils::result_assert_eq(event.len, 0, "Unexpected name length")?;
+    }
+
+    // the events were consumed
+    test_utils::result_assert(read_events(fd)?.is_empty(), "Unexpected event")?;
+
+    nix::unistd::close(fd).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn test_inotify_rm_watch() -> Result<(), String> {
+    let file = tempfile::NamedTempFile::new().map_err(|e| e.to_string())?;
+
+    let fd = inotify_init1(libc::IN_NONBLOCK)?;
+    let wd = add_watch(fd, file.path(), libc::IN_MODIFY)?;
+
+    test_utils::check_system_call!(|| unsafe { libc::inotify_rm_watch(fd, wd) }, &[])?;
+
+    // removing a watch generates an IN_IGNORED event
+    let events = read_events(fd)?;
+    test_utils::result_assert_eq(events.len(), 1, "Expected a single event")?;
+    test_utils::result_assert_eq(events[0].wd, wd, "Unexpected watch descriptor")?;
+    test_utils::result_assert_eq(events[0].mask, libc::IN_IGNORED, "Unexpected event mask")?;
+
+    // the watch no longer exists
+    test_utils::check_system_call!(
+        || unsafe { libc::inotify_rm_watch(fd, wd) },
+        &[libc::EINVAL]
+    )?;
+
+    nix::unistd::close(fd).map_err(|e| e.to_string())?;
+    Ok(())
+}
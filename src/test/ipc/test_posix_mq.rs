@@ -0,0 +1,417 @@
+use std::time::Duration;
+
+use test_utils::set;
+use test_utils::TestEnvironment as TestEnv;
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
+    let tests: Vec<test_utils::ShadowTest<_, _>> = vec![
+        test_utils::ShadowTest::new(
+            "test_prioritized_between_processes",
+            test_prioritized_between_processes,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_open_and_unlink",
+            test_open_and_unlink,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_open_at_fd_limit",
+            test_open_at_fd_limit,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_nonblock_and_timeout",
+            test_nonblock_and_timeout,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_notify_signal",
+            test_notify_signal,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+    ];
+
+    tests
+}
+
+fn errno() -> i32 {
+    std::io::Error::last_os_error().raw_os_error().unwrap()
+}
+
+fn mq_open(name: &std::ffi::CStr, oflag: i32, attr: Option<&libc::mq_attr>) -> libc::mqd_t {
+    let attr = attr.map_or(std::ptr::null(), |x| x as *const _);
+    unsafe { libc::mq_open(name.as_ptr(), oflag, 0o600 as libc::mode_t, attr) }
+}
+
+fn new_attr(maxmsg: i64, msgsize: i64) -> libc::mq_attr {
+    let mut attr: libc::mq_attr = unsafe { std::mem::zeroed() };
+    attr.mq_maxmsg = maxmsg;
+    attr.mq_msgsize = msgsize;
+    attr
+}
+
+fn mq_send(mq: libc::mqd_t, msg: &[u8], prio: u32) -> i32 {
+    unsafe { libc::mq_send(mq, msg.as_ptr().cast(), msg.len(), prio) }
+}
+
+/// Returns the message and its priority.
+fn mq_receive(mq: libc::mqd_t) -> Result<(Vec<u8>, u32), i32> {
+    let mut buf = vec![0u8; 64];
+    let mut prio = 0;
+    let rv = unsafe { libc::mq_receive(mq, buf.as_mut_ptr().cast(), buf.len(), &mut prio) };
+    if rv < 0 {
+        return Err(errno());
+    }
+    buf.truncate(rv as usize);
+    Ok((buf, prio))
+}
+
+fn test_prioritized_between_processes() -> Result<(), String> {
+    let name = c"/shadow_test_mq_prio";
+    let attr = new_attr(8, 64);
+
+    let mq = mq_open(
+        name,
+        libc::O_RDONLY | libc::O_CREAT | libc::O_EXCL,
+        Some(&attr),
+    );
+    assert!(mq >= 0);
+
+    let child_pid = unsafe { libc::fork() };
+    assert!(child_pid >= 0);
+
+    if child_pid == 0 {
+        // the child opens the queue by name and sends messages of different priorities
+        let mq = mq_open(name, libc::O_WRONLY, None);
+        let ok = mq >= 0
+            && mq_send(mq, b"low", 1) == 0
+            && mq_send(mq, b"high", 9) == 0
+            && mq_send(mq, b"medium", 5) == 0
+            && mq_send(mq, b"high again", 9) == 0;
+        unsafe { libc::_exit(if ok { 0 } else { 1 }) };
+    }
+
+    // the first receive blocks until the child sends something
+    let first = mq_receive(mq).unwrap();
+
+    let mut status = 0;
+    assert_eq!(
+        unsafe { libc::waitpid(child_pid, &mut status, 0) },
+        child_pid
+    );
+    assert!(libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0);
+
+    let mut received = vec![first];
+    for _ in 0..3 {
+        received.push(mq_receive(mq).unwrap());
+    }
+
+    // the first message may have been received before the others were sent
+    if received[0] == (b"low".to_vec(), 1) {
+        assert_eq!(
+            received[1..],
+            [
+                (b"high".to_vec(), 9),
+                (b"high again".to_vec(), 9),
+                (b"medium".to_vec(), 5),
+            ]
+        );
+    } else {
+        assert_eq!(
+            received,
+            [
+                (b"high".to_vec(), 9),
+                (b"high again".to_vec(), 9),
+                (b"medium".to_vec(), 5),
+                (b"low".to_vec(), 1),
+            ]
+        );
+    }
+
+    // can't send on a read-only descriptor
+    assert_eq!(mq_send(mq, b"x", 0), -1);
+    assert_eq!(errno(), libc::EBADF);
+
+    assert_eq!(unsafe { libc::mq_close(mq) }, 0);
+    assert_eq!(unsafe { libc::mq_unlink(name.as_ptr()) }, 0);
+
+    Ok(())
+}
+
+fn test_open_and_unlink() -> Result<(), String> {
+    let name = c"/shadow_test_mq_unlink";
+
+    assert_eq!(mq_open(name, libc::O_RDWR, None), -1);
+    assert_eq!(errno(), libc::ENOENT);
+
+    let mq = mq_open(name, libc::O_RDWR | libc::O_CREAT, None);
+    assert!(mq >= 0);
+
+    assert_eq!(
+        mq_open(name, libc::O_RDWR | libc::O_CREAT | libc::O_EXCL, None),
+        -1
+    );
+    assert_eq!(errno(), libc::EEXIST);
+
+    let bad_attr = new_attr(0, 64);
+    assert_eq!(
+        mq_open(
+            c"/shadow_test_mq_bad",
+            libc::O_RDWR | libc::O_CREAT,
+            Some(&bad_attr)
+        ),
+        -1
+    );
+    assert_eq!(errno(), libc::EINVAL);
+
+    assert_eq!(mq_send(mq, b"hello", 0), 0);
+
+    // the queue remains usable through open descriptors after it's unlinked
+    assert_eq!(unsafe { libc::mq_unlink(name.as_ptr()) }, 0);
+    assert_eq!(unsafe { libc::mq_unlink(name.as_ptr()) }, -1);
+    assert_eq!(errno(), libc::ENOENT);
+    assert_eq!(mq_open(name, libc::O_RDWR, None), -1);
+    assert_eq!(errno(), libc::ENOENT);
+
+    let mut buf = vec![0u8; 64];
+    let mut prio = 0;
+    // the buffer must be able to hold the largest message
+    assert_eq!(
+        unsafe { libc::mq_receive(mq, buf.as_mut_ptr().cast(), buf.len(), &mut prio) },
+        -1
+    );
+    assert_eq!(errno(), libc::EMSGSIZE);
+
+    let mut attr: libc::mq_attr = unsafe { std::mem::zeroed() };
+    assert_eq!(unsafe { libc::mq_getattr(mq, &mut attr) }, 0);
+    assert_eq!(attr.mq_curmsgs, 1);
+
+    let mut buf = vec![0u8; attr.mq_msgsize as usize];
+    assert_eq!(
+        unsafe { libc::mq_receive(mq, buf.as_mut_ptr().cast(), buf.len(), &mut prio) },
+        5
+    );
+    assert_eq!(&buf[..5], b"hello");
+
+    assert_eq!(unsafe { libc::mq_close(mq) }, 0);
+
+    Ok(())
+}
+
+fn test_open_at_fd_limit() -> Result<(), String> {
+    let name = c"/shadow_test_mq_fd_limit";
+
+    let mut orig: libc::rlimit = unsafe { std::mem::zeroed() };
+    assert_eq!(
+        unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut orig) },
+        0
+    );
+
+    // find the lowest unused descriptor, and don't allow it to be used
+    let fd = unsafe { libc::open(c"/dev/null".as_ptr(), libc::O_RDONLY) };
+    assert!(fd >= 0);
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    let limit = libc::rlimit {
+        rlim_cur: fd.try_into().unwrap(),
+        rlim_max: orig.rlim_max,
+    };
+    assert_eq!(unsafe { libc::setrlimit(libc::RLIMIT_NOFILE, &limit) }, 0);
+
+    assert_eq!(mq_open(name, libc::O_RDWR | libc::O_CREAT, None), -1);
+    assert_eq!(errno(), libc::EMFILE);
+
+    assert_eq!(unsafe { libc::setrlimit(libc::RLIMIT_NOFILE, &orig) }, 0);
+
+    // the failed call didn't create the queue
+    assert_eq!(mq_open(name, libc::O_RDWR, None), -1);
+    assert_eq!(errno(), libc::ENOENT);
+
+    Ok(())
+}
+
+fn test_nonblock_and_timeout() -> Result<(), String> {
+    let name = c"/shadow_test_mq_timeout";
+    let attr = new_attr(1, 64);
+
+    let mq = mq_open(
+        name,
+        libc::O_RDWR | libc::O_CREAT | libc::O_NONBLOCK,
+        Some(&attr),
+    );
+    assert!(mq >= 0);
+    assert_eq!(unsafe { libc::mq_unlink(name.as_ptr()) }, 0);
+
+    let mut attr: libc::mq_attr = unsafe { std::mem::zeroed() };
+    assert_eq!(unsafe { libc::mq_getattr(mq, &mut attr) }, 0);
+    assert_eq!(attr.mq_flags, libc::O_NONBLOCK as i64);
+    assert_eq!(attr.mq_maxmsg, 1);
+    assert_eq!(attr.mq_msgsize, 64);
+
+    assert_eq!(mq_receive(mq), Err(libc::EAGAIN));
+    assert_eq!(mq_send(mq, b"a", 0), 0);
+    assert_eq!(mq_send(mq, b"b", 0), -1);
+    assert_eq!(errno(), libc::EAGAIN);
+
+    // messages larger than the queue's message size are rejected
+    assert_eq!(mq_send(mq, &[0; 65], 0), -1);
+    assert_eq!(errno(), libc::EMSGSIZE);
+
+    // make the descriptor blocking
+    let blocking: libc::mq_attr = unsafe { std::mem::zeroed() };
+    assert_eq!(
+        unsafe { libc::mq_setattr(mq, &blocking, std::ptr::null_mut()) },
+        0
+    );
+
+    let mut now: libc::timespec = unsafe { std::mem::zeroed() };
+    assert_eq!(
+        unsafe { libc::clock_gettime(libc::CLOCK_REALTIME, &mut now) },
+        0
+    );
+    let mut deadline = now;
+    deadline.tv_nsec += 100_000_000;
+    if deadline.tv_nsec >= 1_000_000_000 {
+        deadline.tv_sec += 1;
+        deadline.tv_nsec -= 1_000_000_000;
+    }
+
+    // the queue is full
+    let start = std::time::Instant::now();
+    let msg = b"b";
+    assert_eq!(
+        unsafe { libc::mq_timedsend(mq, msg.as_ptr().cast(), msg.len(), 0, &deadline) },
+        -1
+    );
+    assert_eq!(errno(), libc::ETIMEDOUT);
+    assert!(start.elapsed() >= Duration::from_millis(90));
+
+    assert_eq!(mq_receive(mq), Ok((b"a".to_vec(), 0)));
+
+    // the queue is empty, and the deadline has already passed
+    let mut buf = vec![0u8; 64];
+    assert_eq!(
+        unsafe {
+            libc::mq_timedreceive(
+                mq,
+                buf.as_mut_ptr().cast(),
+                buf.len(),
+                std::ptr::null_mut(),
+                &deadline,
+            )
+        },
+        -1
+    );
+    assert_eq!(errno(), libc::ETIMEDOUT);
+
+    assert_eq!(unsafe { libc::mq_close(mq) }, 0);
+
+    Ok(())
+}
+
+fn test_notify_signal() -> Result<(), String> {
+    let name = c"/shadow_test_mq_notify";
+    let attr = new_attr(8, 64);
+
+    let mq = mq_open(name, libc::O_RDWR | libc::O_CREAT, Some(&attr));
+    assert!(mq >= 0);
+    assert_eq!(unsafe { libc::mq_unlink(name.as_ptr()) }, 0);
+
+    // block the signal so that we can check that it's pending
+    let mut mask: libc::sigset_t = unsafe { std::mem::zeroed() };
+    let mut old_mask: libc::sigset_t = unsafe { std::mem::zeroed() };
+    unsafe { libc::sigemptyset(&mut mask) };
+    unsafe { libc::sigaddset(&mut mask, libc::SIGUSR1) };
+    assert_eq!(
+        unsafe { libc::sigprocmask(libc::SIG_BLOCK, &mask, &mut old_mask) },
+        0
+    );
+
+    let mut sev: libc::sigevent = unsafe { std::mem::zeroed() };
+    sev.sigev_notify = libc::SIGEV_SIGNAL;
+    sev.sigev_signo = libc::SIGUSR1;
+    sev.sigev_value = libc::sigval {
+        sival_ptr: 1234 as *mut libc::c_void,
+    };
+
+    // the libc crate doesn't wrap mq_notify()
+    let mq_notify =
+        |sev: *const libc::sigevent| unsafe { libc::syscall(libc::SYS_mq_notify, mq, sev) };
+    assert_eq!(mq_notify(&sev), 0);
+
+    // only one process can be registered
+    let child_pid = unsafe { libc::fork() };
+    assert!(child_pid >= 0);
+    if child_pid == 0 {
+        let ok = mq_notify(&sev) == -1 && errno() == libc::EBUSY;
+        unsafe { libc::_exit(if ok { 0 } else { 1 }) };
+    }
+    let mut status = 0;
+    assert_eq!(
+        unsafe { libc::waitpid(child_pid, &mut status, 0) },
+        child_pid
+    );
+    assert!(libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0);
+
+    assert_eq!(mq_send(mq, b"a", 0), 0);
+
+    let timeout = libc::timespec {
+        tv_sec: 1,
+        tv_nsec: 0,
+    };
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+    assert_eq!(
+        unsafe { libc::sigtimedwait(&mask, &mut info, &timeout) },
+        libc::SIGUSR1
+    );
+    assert_eq!(info.si_code, libc::SI_MESGQ);
+    assert_eq!(unsafe { info.si_value().sival_ptr } as usize, 1234);
+    assert_eq!(unsafe { info.si_pid() }, unsafe { libc::getpid() });
+
+    // the registration was removed after the notification
+    assert_eq!(mq_receive(mq), Ok((b"a".to_vec(), 0)));
+    assert_eq!(mq_send(mq, b"b", 0), 0);
+    assert_eq!(
+        unsafe {
+            libc::sigtimedwait(
+                &mask,
+                &mut info,
+                &libc::timespec {
+                    tv_sec: 0,
+                    tv_nsec: 0,
+                },
+            )
+        },
+        -1
+    );
+    assert_eq!(errno(), libc::EAGAIN);
+
+    assert_eq!(
+        unsafe { libc::sigprocmask(libc::SIG_SETMASK, &old_mask, std::ptr::null_mut()) },
+        0
+    );
+    assert_eq!(unsafe { libc::mq_close(mq) }, 0);
+
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use std::time::Duration;
+
+use test_utils::set;
+use test_utils::TestEnvironment as TestEnv;
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
+    let tests: Vec<test_utils::ShadowTest<_, _>> = vec![
+        test_utils::ShadowTest::new(
+            "test_post_wakes_other_process",
+            test_post_wakes_other_process,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_setval_getval",
+            test_setval_getval,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_nowait_and_timeout",
+            test_nowait_and_timeout,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+    ];
+
+    tests
+}
+
+fn errno() -> i32 {
+    std::io::Error::last_os_error().raw_os_error().unwrap()
+}
+
+fn semop(semid: i32, sem_op: i16, sem_flg: i16) -> i32 {
+    let mut op = libc::sembuf {
+        sem_num: 0,
+        sem_op,
+        sem_flg,
+    };
+    unsafe { libc::semop(semid, &mut op, 1) }
+}
+
+fn test_post_wakes_other_process() -> Result<(), String> {
+    let semid = unsafe { libc::semget(libc::IPC_PRIVATE, 1, libc::IPC_CREAT | 0o600) };
+    assert!(semid >= 0);
+
+    let (reader, writer) = rustix::pipe::pipe_with(rustix::pipe::PipeFlags::NONBLOCK).unwrap();
+
+    let child_pid = unsafe { libc::fork() };
+    assert!(child_pid >= 0);
+
+    if child_pid == 0 {
+        // wait for the parent to post, and then tell the parent that we woke up
+        let ok = semop(semid, -1, 0) == 0 && rustix::io::write(&writer, &[1]) == Ok(1);
+        unsafe { libc::_exit(if ok { 0 } else { 1 }) };
+    }
+
+    // the child is still waiting
+    std::thread::sleep(Duration::from_millis(100));
+    let mut buf = [0u8];
+    assert_eq!(
+        rustix::io::read(&reader, &mut buf),
+        Err(rustix::io::Errno::AGAIN)
+    );
+
+    assert_eq!(semop(semid, 1, 0), 0);
+
+    let mut status = 0;
+    assert_eq!(
+        unsafe { libc::waitpid(child_pid, &mut status, 0) },
+        child_pid
+    );
+    assert!(libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0);
+    assert_eq!(rustix::io::read(&reader, &mut buf), Ok(1));
+
+    // the child consumed the post
+    assert_eq!(unsafe { libc::semctl(semid, 0, libc::GETVAL) }, 0);
+
+    assert_eq!(unsafe { libc::semctl(semid, 0, libc::IPC_RMID) }, 0);
+
+    Ok(())
+}
+
+fn test_setval_getval() -> Result<(), String> {
+    let semid = unsafe { libc::semget(libc::IPC_PRIVATE, 2, libc::IPC_CREAT | 0o600) };
+    assert!(semid >= 0);
+
+    assert_eq!(unsafe { libc::semctl(semid, 1, libc::SETVAL, 5) }, 0);
+    assert_eq!(unsafe { libc::semctl(semid, 0, libc::GETVAL) }, 0);
+    assert_eq!(unsafe { libc::semctl(semid, 1, libc::GETVAL) }, 5);
+
+    assert_eq!(unsafe { libc::semctl(semid, 2, libc::GETVAL) }, -1);
+    assert_eq!(errno(), libc::EINVAL);
+    assert_eq!(unsafe { libc::semctl(semid, 0, libc::SETVAL, -1) }, -1);
+    assert_eq!(errno(), libc::ERANGE);
+
+    assert_eq!(unsafe { libc::semctl(semid, 0, libc::IPC_RMID) }, 0);
+    assert_eq!(unsafe { libc::semctl(semid, 0, libc::GETVAL) }, -1);
+    assert_eq!(errno(), libc::EINVAL);
+
+    Ok(())
+}
+
+fn test_nowait_and_timeout() -> Result<(), String> {
+    let semid = unsafe { libc::semget(libc::IPC_PRIVATE, 1, libc::IPC_CREAT | 0o600) };
+    assert!(semid >= 0);
+
+    assert_eq!(semop(semid, -1, libc::IPC_NOWAIT as i16), -1);
+    assert_eq!(errno(), libc::EAGAIN);
+
+    let mut op = libc::sembuf {
+        sem_num: 0,
+        sem_op: -1,
+        sem_flg: 0,
+    };
+    let timeout = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 100_000_000,
+    };
+    let start = std::time::Instant::now();
+    let rv = unsafe { libc::syscall(libc::SYS_semtimedop, semid, &mut op, 1, &timeout) };
+    assert_eq!(rv, -1);
+    assert_eq!(errno(), libc::EAGAIN);
+    assert!(start.elapsed() >= Duration::from_millis(100));
+
+    assert_eq!(unsafe { libc::semctl(semid, 0, libc::IPC_RMID) }, 0);
+
+    Ok(())
+}
@@ -0,0 +1,167 @@
+use test_utils::set;
+use test_utils::TestEnvironment as TestEnv;
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
+    let tests: Vec<test_utils::ShadowTest<_, _>> = vec![
+        test_utils::ShadowTest::new(
+            "test_shared_between_processes",
+            test_shared_between_processes,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_key_lookup",
+            test_key_lookup,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_remove_while_attached",
+            test_remove_while_attached,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+    ];
+
+    tests
+}
+
+fn errno() -> i32 {
+    std::io::Error::last_os_error().raw_os_error().unwrap()
+}
+
+fn shm_stat(shmid: i32) -> Result<libc::shmid_ds, i32> {
+    let mut stat: libc::shmid_ds = unsafe { std::mem::zeroed() };
+    match unsafe { libc::shmctl(shmid, libc::IPC_STAT, &mut stat) } {
+        0 => Ok(stat),
+        _ => Err(errno()),
+    }
+}
+
+fn test_shared_between_processes() -> Result<(), String> {
+    let shmid = unsafe { libc::shmget(libc::IPC_PRIVATE, 100, libc::IPC_CREAT | 0o600) };
+    assert!(shmid >= 0);
+
+    let child_pid = unsafe { libc::fork() };
+    assert!(child_pid >= 0);
+
+    if child_pid == 0 {
+        // the child attaches the segment on its own and writes to it
+        let addr = unsafe { libc::shmat(shmid, std::ptr::null(), 0) };
+        let ok = addr != usize::MAX as *mut libc::c_void;
+        if ok {
+            unsafe { addr.cast::<u32>().write(0xabcd) };
+        }
+        let ok = ok && unsafe { libc::shmdt(addr) } == 0;
+        unsafe { libc::_exit(if ok { 0 } else { 1 }) };
+    }
+
+    let mut status = 0;
+    assert_eq!(
+        unsafe { libc::waitpid(child_pid, &mut status, 0) },
+        child_pid
+    );
+    assert!(libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0);
+
+    // the parent sees the child's write
+    let addr = unsafe { libc::shmat(shmid, std::ptr::null(), 0) };
+    assert_ne!(addr, usize::MAX as *mut libc::c_void);
+    assert_eq!(unsafe { addr.cast::<u32>().read() }, 0xabcd);
+
+    let stat = shm_stat(shmid).unwrap();
+    assert_eq!(stat.shm_segsz, 100);
+    assert_eq!(stat.shm_nattch, 1);
+    assert_eq!(stat.shm_cpid, unsafe { libc::getpid() });
+    assert_eq!(stat.shm_perm.mode & 0o777, 0o600);
+
+    assert_eq!(unsafe { libc::shmdt(addr) }, 0);
+    assert_eq!(unsafe { libc::shmdt(addr) }, -1);
+    assert_eq!(errno(), libc::EINVAL);
+
+    assert_eq!(shm_stat(shmid).unwrap().shm_nattch, 0);
+    assert_eq!(
+        unsafe { libc::shmctl(shmid, libc::IPC_RMID, std::ptr::null_mut()) },
+        0
+    );
+
+    Ok(())
+}
+
+fn test_key_lookup() -> Result<(), String> {
+    let key = 0x5adc_0001;
+
+    // the key doesn't exist until it's created
+    assert_eq!(unsafe { libc::shmget(key, 4096, 0o600) }, -1);
+    assert_eq!(errno(), libc::ENOENT);
+
+    let shmid = unsafe { libc::shmget(key, 4096, libc::IPC_CREAT | 0o600) };
+    assert!(shmid >= 0);
+
+    // later lookups find the same segment
+    assert_eq!(unsafe { libc::shmget(key, 1000, 0o600) }, shmid);
+    assert_eq!(
+        unsafe { libc::shmget(key, 4096, libc::IPC_CREAT | 0o600) },
+        shmid
+    );
+
+    assert_eq!(
+        unsafe { libc::shmget(key, 4096, libc::IPC_CREAT | libc::IPC_EXCL | 0o600) },
+        -1
+    );
+    assert_eq!(errno(), libc::EEXIST);
+
+    // can't ask for more than the segment's size
+    assert_eq!(unsafe { libc::shmget(key, 8192, 0o600) }, -1);
+    assert_eq!(errno(), libc::EINVAL);
+
+    assert_eq!(shm_stat(shmid).unwrap().shm_perm.__key, key);
+
+    assert_eq!(
+        unsafe { libc::shmctl(shmid, libc::IPC_RMID, std::ptr::null_mut()) },
+        0
+    );
+    assert_eq!(unsafe { libc::shmget(key, 4096, 0o600) }, -1);
+    assert_eq!(errno(), libc::ENOENT);
+
+    Ok(())
+}
+
+fn test_remove_while_attached() -> Result<(), String> {
+    let shmid = unsafe { libc::shmget(libc::IPC_PRIVATE, 4096, libc::IPC_CREAT | 0o600) };
+    assert!(shmid >= 0);
+
+    let addr = unsafe { libc::shmat(shmid, std::ptr::null(), 0) };
+    assert_ne!(addr, usize::MAX as *mut libc::c_void);
+
+    // the segment stays usable until it's detached
+    assert_eq!(
+        unsafe { libc::shmctl(shmid, libc::IPC_RMID, std::ptr::null_mut()) },
+        0
+    );
+    unsafe { addr.cast::<u32>().write(1) };
+    assert_eq!(unsafe { addr.cast::<u32>().read() }, 1);
+    assert_eq!(shm_stat(shmid).unwrap().shm_nattch, 1);
+
+    assert_eq!(unsafe { libc::shmdt(addr) }, 0);
+    assert_eq!(shm_stat(shmid).unwrap_err(), libc::EINVAL);
+
+    Ok(())
+}
@@ -0,0 +1,122 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+use linux_api::membarrier::{
+    MEMBARRIER_CMD_GLOBAL, MEMBARRIER_CMD_PRIVATE_EXPEDITED, MEMBARRIER_CMD_QUERY,
+    MEMBARRIER_CMD_REGISTER_PRIVATE_EXPEDITED,
+};
+use test_utils::TestEnvironment as TestEnv;
+use test_utils::{check_system_call, set};
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests: Vec<test_utils::ShadowTest<_, _>> = vec![
+        test_utils::ShadowTest::new(
+            "test_query",
+            test_query,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_global",
+            test_global,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_private_expedited",
+            test_private_expedited,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_invalid_command",
+            test_invalid_command,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_invalid_flags",
+            test_invalid_flags,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+    ];
+
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn membarrier(cmd: libc::c_int, flags: libc::c_uint) -> libc::c_int {
+    unsafe { libc::syscall(libc::SYS_membarrier, cmd, flags, 0) as libc::c_int }
+}
+
+fn test_query() -> Result<(), String> {
+    let supported = check_system_call!(|| membarrier(MEMBARRIER_CMD_QUERY, 0), &[])?;
+
+    for cmd in [
+        MEMBARRIER_CMD_GLOBAL,
+        MEMBARRIER_CMD_PRIVATE_EXPEDITED,
+        MEMBARRIER_CMD_REGISTER_PRIVATE_EXPEDITED,
+    ] {
+        test_utils::result_assert(
+            supported & cmd != 0,
+            &format!("Command {cmd:#x} is not supported: {supported:#x}"),
+        )?;
+    }
+
+    Ok(())
+}
+
+fn test_global() -> Result<(), String> {
+    let rv = check_system_call!(|| membarrier(MEMBARRIER_CMD_GLOBAL, 0), &[])?;
+    test_utils::result_assert_eq(rv, 0, "Unexpected return value")?;
+    Ok(())
+}
+
+fn test_private_expedited() -> Result<(), String> {
+    // linux requires that the process registers before using the private expedited command
+    check_system_call!(
+        || membarrier(MEMBARRIER_CMD_REGISTER_PRIVATE_EXPEDITED, 0),
+        &[]
+    )?;
+    let rv = check_system_call!(|| membarrier(MEMBARRIER_CMD_PRIVATE_EXPEDITED, 0), &[])?;
+    test_utils::result_assert_eq(rv, 0, "Unexpected return value")?;
+    Ok(())
+}
+
+fn test_invalid_command() -> Result<(), String> {
+    // not a valid command
+    check_system_call!(|| membarrier(1 << 30, 0), &[libc::EINVAL])?;
+    // multiple commands at once
+    check_system_call!(
+        || membarrier(MEMBARRIER_CMD_GLOBAL | MEMBARRIER_CMD_PRIVATE_EXPEDITED, 0),
+        &[libc::EINVAL]
+    )?;
+    Ok(())
+}
+
+fn test_invalid_flags() -> Result<(), String> {
+    check_system_call!(
+        || membarrier(MEMBARRIER_CMD_QUERY, 1 << 30),
+        &[libc::EINVAL]
+    )?;
+    check_system_call!(
+        || membarrier(MEMBARRIER_CMD_GLOBAL, 1 << 30),
+        &[libc::EINVAL]
+    )?;
+    Ok(())
+}
@@ -5,6 +5,7 @@
 
 use std::error::Error;
 
+use test_utils::check_system_call;
 use test_utils::set;
 use test_utils::TestEnvironment as TestEnv;
 
@@ -94,6 +95,89 @@ fn test_mmap_file(offset: libc::off_t, unlink_before_mmap: bool) -> Result<(), B
     Ok(())
 }
 
+/// Map a file `MAP_SHARED` in two processes, write through one mapping, and check that the write
+/// is visible through the other mapping and through `read()`.
+fn test_mmap_file_shared_between_processes() -> Result<(), Box<dyn Error>> {
+    let template = b"test_mmapXXXXXX";
+    let (temp_fd, path) = nix::unistd::mkstemp(template.as_ref())?;
+    nix::unistd::ftruncate(temp_fd, page_size() as libc::off_t)?;
+
+    let msg = b"Hello shared world!";
+
+    // the child tells the parent when it has mapped the file, and the parent tells the child when
+    // it has written to its mapping
+    let mut mapped_pipe = [-1; 2];
+    let mut written_pipe = [-1; 2];
+    check_system_call!(|| unsafe { libc::pipe(mapped_pipe.as_mut_ptr()) }, &[])?;
+    check_system_call!(|| unsafe { libc::pipe(written_pipe.as_mut_ptr()) }, &[])?;
+
+    let mmap_file = || unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            page_size(),
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED,
+            temp_fd,
+            0,
+        )
+    };
+
+    let child_pid = check_system_call!(|| unsafe { libc::fork() }, &[])?;
+    if child_pid == 0 {
+        let mapbuf = mmap_file();
+        if mapbuf == libc::MAP_FAILED {
+            unsafe { libc::_exit(1) };
+        }
+
+        let mut buf = [0u8; 1];
+        unsafe { libc::write(mapped_pipe[1], buf.as_ptr().cast(), 1) };
+        let rv = unsafe { libc::read(written_pipe[0], buf.as_mut_ptr().cast(), 1) };
+
+        let map = unsafe { std::slice::from_raw_parts(mapbuf as *const u8, msg.len()) };
+        let mut rdbuf = vec![0u8; msg.len()];
+        let read_rv = unsafe { libc::pread(temp_fd, rdbuf.as_mut_ptr().cast(), rdbuf.len(), 0) };
+
+        let ok = rv == 1 && map == msg && read_rv == msg.len() as isize && rdbuf == msg;
+        unsafe { libc::_exit(if ok { 0 } else { 2 }) };
+    }
+
+    // wait for the child to map the file before writing
+    let mut buf = [0u8; 1];
+    check_system_call!(
+        || unsafe { libc::read(mapped_pipe[0], buf.as_mut_ptr().cast(), 1) },
+        &[]
+    )?;
+
+    let mapbuf = mmap_file();
+    assert!(mapbuf != libc::MAP_FAILED);
+    {
+        let map = unsafe { std::slice::from_raw_parts_mut(mapbuf as *mut u8, msg.len()) };
+        map.copy_from_slice(msg.as_ref());
+    }
+
+    check_system_call!(
+        || unsafe { libc::write(written_pipe[1], buf.as_ptr().cast(), 1) },
+        &[]
+    )?;
+
+    let mut status = 0;
+    check_system_call!(|| unsafe { libc::waitpid(child_pid, &mut status, 0) }, &[])?;
+
+    check_system_call!(|| unsafe { libc::munmap(mapbuf, page_size()) }, &[])?;
+    for fd in mapped_pipe.into_iter().chain(written_pipe) {
+        nix::unistd::close(fd)?;
+    }
+    nix::unistd::unlink(&path)?;
+    nix::unistd::close(temp_fd)?;
+
+    test_utils::result_assert(
+        libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0,
+        "Child didn't observe the write through the shared mapping",
+    )?;
+
+    Ok(())
+}
+
 fn page_size() -> usize {
     nix::unistd::sysconf(nix::unistd::SysconfVar::PAGE_SIZE)
         .unwrap()
@@ -187,6 +271,130 @@ fn test_mmap_anon() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+/// Move a private anonymous mapping with `MREMAP_DONTUNMAP`, and check that the data is at the new
+/// location while the old location stays mapped with zeroed pages.
+fn test_mremap_dontunmap() -> Result<(), Box<dyn Error>> {
+    let size = 3 * page_size();
+    let old_ptr = mmap_and_init_buf(size);
+
+    let new_ptr = unsafe {
+        libc::mremap(
+            old_ptr,
+            size,
+            size,
+            libc::MREMAP_MAYMOVE | libc::MREMAP_DONTUNMAP,
+        )
+    };
+    test_utils::assert_true_else_errno(new_ptr != libc::MAP_FAILED);
+    assert_ne!(old_ptr, new_ptr);
+
+    let new_buf = unsafe { std::slice::from_raw_parts::<u8>(new_ptr as *const u8, size) };
+    check_buf(new_buf);
+    validate_shadow_access(new_buf)?;
+
+    // the old mapping should still exist, and be empty
+    let old_buf = unsafe { std::slice::from_raw_parts_mut::<u8>(old_ptr as *mut u8, size) };
+    assert!(old_buf.iter().all(|x| *x == 0));
+    validate_shadow_access(old_buf)?;
+
+    // writing to the old mapping shouldn't affect the new one
+    old_buf.fill(0xff);
+    check_buf(new_buf);
+
+    let rv = unsafe { libc::munmap(old_ptr, size) };
+    nix::errno::Errno::result(rv)?;
+    let rv = unsafe { libc::munmap(new_ptr, size) };
+    nix::errno::Errno::result(rv)?;
+
+    Ok(())
+}
+
+/// `MREMAP_DONTUNMAP` requires `MREMAP_MAYMOVE`, and the old and new sizes must be equal.
+fn test_mremap_dontunmap_invalid() -> Result<(), Box<dyn Error>> {
+    let size = 2 * page_size();
+    let buf_ptr = mmap_and_init_buf(size);
+
+    check_system_call!(
+        || unsafe { libc::mremap(buf_ptr, size, size, libc::MREMAP_DONTUNMAP) },
+        &[libc::EINVAL]
+    )?;
+    check_system_call!(
+        || unsafe {
+            libc::mremap(
+                buf_ptr,
+                size,
+                page_size(),
+                libc::MREMAP_MAYMOVE | libc::MREMAP_DONTUNMAP,
+            )
+        },
+        &[libc::EINVAL]
+    )?;
+
+    let rv = unsafe { libc::munmap(buf_ptr, size) };
+    nix::errno::Errno::result(rv)?;
+
+    Ok(())
+}
+
+/// Use `remap_file_pages` to map the second page of a file at the start of a shared mapping of that
+/// file, and check that the relocated page is visible there.
+fn test_remap_file_pages() -> Result<(), Box<dyn Error>> {
+    let template = b"test_mmapXXXXXX";
+    let (temp_fd, path) = nix::unistd::mkstemp(template.as_ref())?;
+
+    // the first page of the file is filled with 'a', and the second with 'b'
+    let mut contents = vec![b'a'; page_size()];
+    contents.extend(vec![b'b'; page_size()]);
+    nix::unistd::write(temp_fd, &contents)?;
+
+    let size = 2 * page_size();
+    let mapbuf = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            size,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED,
+            temp_fd,
+            0,
+        )
+    };
+    test_utils::assert_true_else_errno(mapbuf != libc::MAP_FAILED);
+
+    // map the file's second page (pgoff 1) at the start of the mapping
+    check_system_call!(
+        || unsafe { libc::syscall(libc::SYS_remap_file_pages, mapbuf, page_size(), 0, 1, 0) },
+        &[]
+    )?;
+
+    let map = unsafe { std::slice::from_raw_parts(mapbuf as *const u8, size) };
+    test_utils::result_assert(
+        map.iter().all(|x| *x == b'b'),
+        "Relocated page has the wrong contents",
+    )?;
+
+    // a range that extends past the mapping is invalid
+    check_system_call!(
+        || unsafe { libc::syscall(libc::SYS_remap_file_pages, mapbuf, 2 * size, 0, 0, 0) },
+        &[libc::EINVAL]
+    )?;
+
+    let rv = unsafe { libc::munmap(mapbuf, size) };
+    nix::errno::Errno::result(rv)?;
+    nix::unistd::unlink(&path)?;
+    nix::unistd::close(temp_fd)?;
+
+    // anonymous mappings aren't supported
+    let buf_ptr = mmap_and_init_buf(page_size());
+    check_system_call!(
+        || unsafe { libc::syscall(libc::SYS_remap_file_pages, buf_ptr, page_size(), 0, 0, 0) },
+        &[libc::EINVAL]
+    )?;
+    let rv = unsafe { libc::munmap(buf_ptr, page_size()) };
+    nix::errno::Errno::result(rv)?;
+
+    Ok(())
+}
+
 /// Test an anonymous mapping with `PROT_EXEC` set. This is to catch environments where the /dev/shm
 /// mount was not mounted with the "exec" option. See #2400.
 fn test_mmap_anon_exec() -> Result<(), Box<dyn Error>> {
@@ -384,6 +592,62 @@ fn test_mmap_mprotect_exe() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+/// Map a region, touch part of it, and check that `mincore` reports the touched pages as
+/// resident. Linux won't report the untouched pages as resident, but Shadow currently treats all
+/// mapped pages as resident, so we don't check them.
+fn test_mincore() -> Result<(), Box<dyn Error>> {
+    let num_pages = 4;
+    let size = num_pages * page_size();
+
+    let buf_ptr = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            size,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    test_utils::assert_true_else_errno(buf_ptr != libc::MAP_FAILED);
+
+    // touch the first two pages
+    let buf = unsafe { std::slice::from_raw_parts_mut::<u8>(buf_ptr as *mut u8, size) };
+    buf[..2 * page_size()].fill(1);
+
+    let mut vec = vec![0u8; num_pages];
+    let rv = unsafe { libc::mincore(buf_ptr, size, vec.as_mut_ptr()) };
+    test_utils::assert_true_else_errno(rv == 0);
+    assert_eq!(vec[0] & 1, 1);
+    assert_eq!(vec[1] & 1, 1);
+
+    // a partial page still reports the whole page
+    let mut vec = [0u8; 1];
+    let rv = unsafe { libc::mincore(buf_ptr, 1, vec.as_mut_ptr()) };
+    test_utils::assert_true_else_errno(rv == 0);
+    assert_eq!(vec[0] & 1, 1);
+
+    // unaligned address
+    let rv = unsafe { libc::mincore(buf_ptr.add(1), 1, vec.as_mut_ptr()) };
+    assert_eq!(rv, -1);
+    assert_eq!(test_utils::get_errno(), libc::EINVAL);
+
+    // unmap the last page and make sure a range including it is rejected
+    let rv = unsafe { libc::munmap(buf_ptr.add(3 * page_size()), page_size()) };
+    nix::errno::Errno::result(rv)?;
+
+    let mut vec = vec![0u8; num_pages];
+    let rv = unsafe { libc::mincore(buf_ptr, size, vec.as_mut_ptr()) };
+    assert_eq!(rv, -1);
+    assert_eq!(test_utils::get_errno(), libc::ENOMEM);
+
+    // Unmap allocated memory
+    let rv = unsafe { libc::munmap(buf_ptr, 3 * page_size()) };
+    nix::errno::Errno::result(rv)?;
+
+    Ok(())
+}
+
 fn test_mmap_file_low(unlink_before_mmap: bool) -> Result<(), Box<dyn Error>> {
     test_mmap_file(0, unlink_before_mmap)
 }
@@ -445,6 +709,8 @@ fn main() -> Result<(), Box<dyn Error>> {
     let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
     // should we summarize the results rather than exit on a failed test
     let summarize = std::env::args().any(|x| x == "--summarize");
+    // should we skip tests that fork a child process
+    let skip_fork = std::env::args().any(|x| x == "--skip-fork");
 
     let mut tests: Vec<test_utils::ShadowTest<_, _>> = vec![
         test_utils::ShadowTest::new(
@@ -477,6 +743,21 @@ fn main() -> Result<(), Box<dyn Error>> {
             test_mremap_clobber,
             set![TestEnv::Libc, TestEnv::Shadow],
         ),
+        test_utils::ShadowTest::new(
+            "test_mremap_dontunmap",
+            test_mremap_dontunmap,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_mremap_dontunmap_invalid",
+            test_mremap_dontunmap_invalid,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_remap_file_pages",
+            test_remap_file_pages,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
         test_utils::ShadowTest::new(
             "test_mmap_prot_none_mprotect",
             test_mmap_prot_none_mprotect,
@@ -492,8 +773,21 @@ fn main() -> Result<(), Box<dyn Error>> {
             test_mmap_nofollow_file,
             set![TestEnv::Libc, TestEnv::Shadow],
         ),
+        test_utils::ShadowTest::new(
+            "test_mincore",
+            test_mincore,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
     ];
 
+    if !skip_fork {
+        tests.push(test_utils::ShadowTest::new(
+            "test_mmap_file_shared_between_processes",
+            test_mmap_file_shared_between_processes,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ));
+    }
+
     for &unlink_before_mmap in [false, true].iter() {
         tests.push(test_utils::ShadowTest::new(
             &format!(
@@ -109,6 +109,11 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
             test_o_direct_write_fd,
             set![TestEnv::Libc, TestEnv::Shadow],
         ),
+        test_utils::ShadowTest::new(
+            "test_o_direct_write_boundaries",
+            test_o_direct_write_boundaries,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
         test_utils::ShadowTest::new(
             "test_o_direct_stream_to_packet_mode_empty",
             test_o_direct_stream_to_packet_mode_empty,
@@ -858,6 +863,42 @@ fn test_o_direct_write_fd() -> Result<(), String> {
     Ok(())
 }
 
+// a pipe created with O_DIRECT preserves the boundaries between writes
+fn test_o_direct_write_boundaries() -> Result<(), String> {
+    let mut fds = [0 as libc::c_int; 2];
+    test_utils::check_system_call!(
+        || { unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_NONBLOCK | libc::O_DIRECT) } },
+        &[]
+    )?;
+
+    test_utils::result_assert(fds[0] > 0, "fds[0] not set")?;
+    test_utils::result_assert(fds[1] > 0, "fds[1] not set")?;
+
+    let (read_fd, write_fd) = (fds[0], fds[1]);
+
+    test_utils::run_and_close_fds(&[write_fd, read_fd], || {
+        nix::unistd::write(write_fd, b"hello").unwrap();
+        nix::unistd::write(write_fd, b"world!").unwrap();
+
+        let mut in_buf = vec![0u8; 100];
+
+        // each read returns exactly one write
+        let len = nix::unistd::read(read_fd, &mut in_buf).unwrap();
+        assert_eq!(&in_buf[..len], b"hello");
+
+        let len = nix::unistd::read(read_fd, &mut in_buf).unwrap();
+        assert_eq!(&in_buf[..len], b"world!");
+
+        // no packets left
+        assert_eq!(
+            nix::unistd::read(read_fd, &mut in_buf).err(),
+            Some(nix::errno::Errno::EWOULDBLOCK)
+        );
+    });
+
+    Ok(())
+}
+
 fn test_o_direct_stream_to_packet_mode_empty() -> Result<(), String> {
     let mut fds = [0 as libc::c_int; 2];
     test_utils::check_system_call!(
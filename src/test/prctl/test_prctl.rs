@@ -34,12 +34,32 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
             test_dumpable,
             set![TestEnv::Libc, TestEnv::Shadow],
         ),
+        test_utils::ShadowTest::new(
+            "test_keepcaps",
+            test_keepcaps,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
         test_utils::ShadowTest::new(
             "test_tid_addr",
             test_tid_addr,
             set![TestEnv::Libc, TestEnv::Shadow],
         ),
         test_utils::ShadowTest::new("test_name", test_name, set![TestEnv::Libc, TestEnv::Shadow]),
+        test_utils::ShadowTest::new(
+            "test_set_name",
+            test_set_name,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_parent_death_signal",
+            test_parent_death_signal,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_personality",
+            test_personality,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
     ];
 
     tests
@@ -66,6 +86,34 @@ fn test_dumpable() -> Result<(), String> {
 
     assert_with_errno!(unsafe { libc::prctl(libc::PR_GET_DUMPABLE) } == SUID_DUMP_DISABLE as i32);
 
+    // set as enabled again
+    assert_with_errno!(unsafe { libc::prctl(libc::PR_SET_DUMPABLE, SUID_DUMP_USER) } == 0);
+
+    assert_with_errno!(unsafe { libc::prctl(libc::PR_GET_DUMPABLE) } == SUID_DUMP_USER as i32);
+
+    // SUID_DUMP_ROOT (2) can't be set with prctl
+    assert_eq!(-1, unsafe { libc::prctl(libc::PR_SET_DUMPABLE, 2) });
+    assert_eq!(libc::EINVAL, test_utils::get_errno());
+
+    assert_with_errno!(unsafe { libc::prctl(libc::PR_GET_DUMPABLE) } == SUID_DUMP_USER as i32);
+
+    Ok(())
+}
+
+fn test_keepcaps() -> Result<(), String> {
+    // should initially be disabled
+    assert_with_errno!(unsafe { libc::prctl(libc::PR_GET_KEEPCAPS) } == 0);
+
+    assert_with_errno!(unsafe { libc::prctl(libc::PR_SET_KEEPCAPS, 1) } == 0);
+    assert_with_errno!(unsafe { libc::prctl(libc::PR_GET_KEEPCAPS) } == 1);
+
+    assert_eq!(-1, unsafe { libc::prctl(libc::PR_SET_KEEPCAPS, 2) });
+    assert_eq!(libc::EINVAL, test_utils::get_errno());
+    assert_with_errno!(unsafe { libc::prctl(libc::PR_GET_KEEPCAPS) } == 1);
+
+    assert_with_errno!(unsafe { libc::prctl(libc::PR_SET_KEEPCAPS, 0) } == 0);
+    assert_with_errno!(unsafe { libc::prctl(libc::PR_GET_KEEPCAPS) } == 0);
+
     Ok(())
 }
 
@@ -94,3 +142,137 @@ fn test_name() -> Result<(), String> {
 
     Ok(())
 }
+
+fn test_set_name() -> Result<(), String> {
+    fn get_name() -> [u8; 16] {
+        let mut buffer = [0xffu8; 16];
+        assert_eq!(unsafe { libc::prctl(libc::PR_GET_NAME, &mut buffer) }, 0);
+        buffer
+    }
+
+    // use a separate thread so that we don't rename the test's main thread
+    std::thread::spawn(|| {
+        assert_eq!(
+            unsafe { libc::prctl(libc::PR_SET_NAME, c"worker".as_ptr()) },
+            0
+        );
+        assert_eq!(&get_name(), b"worker\0\0\0\0\0\0\0\0\0\0");
+
+        // truncated to 15 bytes plus the nul terminator
+        let long_name = c"a-very-long-thread-name";
+        assert_eq!(
+            unsafe { libc::prctl(libc::PR_SET_NAME, long_name.as_ptr()) },
+            0
+        );
+        assert_eq!(&get_name(), b"a-very-long-thr\0");
+
+        // the name doesn't need to be nul-terminated
+        let unterminated = *b"0123456789abcdef";
+        assert_eq!(unsafe { libc::prctl(libc::PR_SET_NAME, &unterminated) }, 0);
+        assert_eq!(&get_name(), b"0123456789abcde\0");
+
+        // new threads inherit the name of the thread that created them
+        let inherited = std::thread::spawn(get_name).join().unwrap();
+        assert_eq!(&inherited, b"0123456789abcde\0");
+    })
+    .join()
+    .map_err(|_| "thread panicked".to_string())?;
+
+    Ok(())
+}
+
+fn test_parent_death_signal() -> Result<(), String> {
+    fn get_pdeathsig() -> libc::c_int {
+        let mut signal: libc::c_int = -1;
+        assert_eq!(
+            unsafe { libc::prctl(libc::PR_GET_PDEATHSIG, &mut signal) },
+            0
+        );
+        signal
+    }
+
+    // should initially be unset
+    assert_eq!(get_pdeathsig(), 0);
+
+    assert_eq!(unsafe { libc::prctl(libc::PR_SET_PDEATHSIG, 1000) }, -1);
+    assert_eq!(test_utils::get_errno(), libc::EINVAL);
+
+    // the grandchild reports the signal it received over this pipe
+    let (reader, writer) = rustix::pipe::pipe().unwrap();
+
+    let parent_pid = unsafe { libc::fork() };
+    assert!(parent_pid >= 0);
+
+    if parent_pid == 0 {
+        // the grandchild tells its parent when it's ready over this pipe
+        let (ready_reader, ready_writer) = rustix::pipe::pipe().unwrap();
+
+        let child_pid = unsafe { libc::fork() };
+        if child_pid == 0 {
+            // block the signal so that we can wait for it
+            let mut mask: libc::sigset_t = unsafe { std::mem::zeroed() };
+            unsafe { libc::sigemptyset(&mut mask) };
+            unsafe { libc::sigaddset(&mut mask, libc::SIGTERM) };
+            unsafe { libc::sigprocmask(libc::SIG_BLOCK, &mask, std::ptr::null_mut()) };
+
+            let mut ok = unsafe { libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGTERM) } == 0;
+            ok &= get_pdeathsig() == libc::SIGTERM;
+            ok &= rustix::io::write(&ready_writer, &[1]) == Ok(1);
+
+            let timeout = libc::timespec {
+                tv_sec: 10,
+                tv_nsec: 0,
+            };
+            let signal = unsafe { libc::sigtimedwait(&mask, std::ptr::null_mut(), &timeout) };
+            let signal = if ok { signal } else { -1 };
+            rustix::io::write(&writer, &signal.to_ne_bytes()).unwrap();
+            unsafe { libc::_exit(0) };
+        }
+
+        // wait for the grandchild to set its parent-death signal, and then exit
+        let mut buf = [0u8];
+        let ok = child_pid > 0 && rustix::io::read(&ready_reader, &mut buf) == Ok(1);
+        unsafe { libc::_exit(if ok { 0 } else { 1 }) };
+    }
+
+    let mut status = 0;
+    assert_eq!(
+        unsafe { libc::waitpid(parent_pid, &mut status, 0) },
+        parent_pid
+    );
+    assert!(libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0);
+
+    let mut buf = [0u8; 4];
+    assert_eq!(rustix::io::read(&reader, &mut buf), Ok(4));
+    assert_eq!(libc::c_int::from_ne_bytes(buf), libc::SIGTERM);
+
+    Ok(())
+}
+
+fn test_personality() -> Result<(), String> {
+    let personality = |persona: u32| unsafe { libc::personality(persona.into()) };
+    // queries the personality without changing it
+    const QUERY: u32 = 0xffffffff;
+
+    let orig = personality(QUERY);
+    assert_with_errno!(orig != -1);
+    let orig = orig as u32;
+
+    // each call returns the previous personality
+    let new = orig | libc::ADDR_NO_RANDOMIZE as u32;
+    assert_eq!(personality(new) as u32, orig);
+    assert_eq!(personality(QUERY) as u32, new);
+
+    let new = new & !(libc::ADDR_NO_RANDOMIZE as u32);
+    assert_eq!(
+        personality(new) as u32,
+        orig | libc::ADDR_NO_RANDOMIZE as u32
+    );
+    assert_eq!(personality(QUERY) as u32, new);
+
+    // restore the original personality
+    personality(orig);
+    assert_eq!(personality(QUERY) as u32, orig);
+
+    Ok(())
+}
@@ -32,6 +32,11 @@ fn main() -> Result<(), String> {
             test_getrandom,
             set![TestEnv::Libc, TestEnv::Shadow],
         ),
+        test_utils::ShadowTest::new(
+            "test_getrandom_flags",
+            test_getrandom_flags,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
     ];
     if filter_shadow_passing {
         tests.retain(|x| x.passing(TestEnv::Shadow))
@@ -143,3 +148,35 @@ fn test_getrandom() -> Result<(), String> {
 
     check_randomness(&values)
 }
+
+fn test_getrandom_flags() -> Result<(), String> {
+    let mut buf = [0_u8; 16];
+
+    let getrandom = |buf: &mut [u8], flags: libc::c_uint| unsafe {
+        libc::syscall(
+            libc::SYS_getrandom,
+            buf.as_mut_ptr() as *mut libc::c_void,
+            buf.len(),
+            flags,
+        )
+    };
+
+    for flags in [
+        0,
+        libc::GRND_NONBLOCK,
+        libc::GRND_RANDOM,
+        libc::GRND_NONBLOCK | libc::GRND_RANDOM,
+    ] {
+        let num_bytes = getrandom(&mut buf, flags);
+        test_utils::result_assert_eq(
+            num_bytes,
+            buf.len() as libc::c_long,
+            &format!("Unexpected getrandom byte count with flags {flags:#x}"),
+        )?;
+    }
+
+    // an unknown flag bit
+    test_utils::check_system_call!(|| getrandom(&mut buf, 0x80), &[libc::EINVAL])?;
+
+    Ok(())
+}
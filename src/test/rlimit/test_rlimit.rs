@@ -0,0 +1,271 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+use test_utils::check_system_call;
+use test_utils::set;
+use test_utils::TestEnvironment as TestEnv;
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
+    vec![
+        test_utils::ShadowTest::new(
+            "test_nofile_limit",
+            test_nofile_limit,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_nofile_limit_dup",
+            test_nofile_limit_dup,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_set_and_get",
+            test_set_and_get,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_prlimit_other_process",
+            test_prlimit_other_process,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_invalid",
+            test_invalid,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+    ]
+}
+
+fn getrlimit(resource: libc::__rlimit_resource_t) -> Result<libc::rlimit, String> {
+    let mut rlim = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    check_system_call!(|| unsafe { libc::getrlimit(resource, &mut rlim) }, &[])?;
+    Ok(rlim)
+}
+
+fn setrlimit(resource: libc::__rlimit_resource_t, rlim: libc::rlimit) -> Result<(), String> {
+    check_system_call!(|| unsafe { libc::setrlimit(resource, &rlim) }, &[])?;
+    Ok(())
+}
+
+fn open_dev_null() -> libc::c_int {
+    unsafe { libc::open(c"/dev/null".as_ptr(), libc::O_RDONLY) }
+}
+
+/// Run `f` with the `RLIMIT_NOFILE` soft limit set so that exactly one more descriptor can be
+/// opened, then restore the original limit.
+fn with_one_free_fd(f: impl FnOnce() -> Result<(), String>) -> Result<(), String> {
+    let orig = getrlimit(libc::RLIMIT_NOFILE)?;
+
+    // find the lowest unused descriptor
+    let fd = check_system_call!(open_dev_null, &[])?;
+    check_system_call!(|| unsafe { libc::close(fd) }, &[])?;
+
+    // only allow descriptors below `fd + 1`
+    setrlimit(
+        libc::RLIMIT_NOFILE,
+        libc::rlimit {
+            rlim_cur: (fd + 1).try_into().unwrap(),
+            rlim_max: orig.rlim_max,
+        },
+    )?;
+
+    let rv = f();
+
+    setrlimit(libc::RLIMIT_NOFILE, orig)?;
+
+    rv
+}
+
+fn test_nofile_limit() -> Result<(), String> {
+    with_one_free_fd(|| {
+        let fd = check_system_call!(open_dev_null, &[])?;
+
+        // we've reached the limit
+        check_system_call!(open_dev_null, &[libc::EMFILE])?;
+        check_system_call!(|| unsafe { libc::eventfd(0, 0) }, &[libc::EMFILE])?;
+        let mut fds = [-1; 2];
+        check_system_call!(|| unsafe { libc::pipe(fds.as_mut_ptr()) }, &[libc::EMFILE])?;
+
+        // closing a descriptor allows us to open another
+        check_system_call!(|| unsafe { libc::close(fd) }, &[])?;
+        let fd = check_system_call!(open_dev_null, &[])?;
+        check_system_call!(|| unsafe { libc::close(fd) }, &[])?;
+
+        Ok(())
+    })
+}
+
+fn test_nofile_limit_dup() -> Result<(), String> {
+    with_one_free_fd(|| {
+        let limit: libc::c_int = getrlimit(libc::RLIMIT_NOFILE)?.rlim_cur.try_into().unwrap();
+
+        let fd = check_system_call!(|| unsafe { libc::dup(0) }, &[])?;
+        check_system_call!(|| unsafe { libc::dup(0) }, &[libc::EMFILE])?;
+        check_system_call!(
+            || unsafe { libc::fcntl(0, libc::F_DUPFD, 0) },
+            &[libc::EMFILE]
+        )?;
+        check_system_call!(
+            || unsafe { libc::fcntl(0, libc::F_DUPFD, limit) },
+            &[libc::EINVAL]
+        )?;
+        check_system_call!(|| unsafe { libc::dup2(0, limit) }, &[libc::EBADF])?;
+
+        // replacing an existing descriptor is still allowed
+        check_system_call!(|| unsafe { libc::dup2(0, fd) }, &[])?;
+        check_system_call!(|| unsafe { libc::close(fd) }, &[])?;
+
+        Ok(())
+    })
+}
+
+fn test_set_and_get() -> Result<(), String> {
+    let orig = getrlimit(libc::RLIMIT_CORE)?;
+
+    let new = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: orig.rlim_max,
+    };
+    setrlimit(libc::RLIMIT_CORE, new)?;
+
+    let rlim = getrlimit(libc::RLIMIT_CORE)?;
+    test_utils::result_assert_eq(rlim.rlim_cur, new.rlim_cur, "Unexpected soft limit")?;
+    test_utils::result_assert_eq(rlim.rlim_max, new.rlim_max, "Unexpected hard limit")?;
+
+    // prlimit returns the old limit when setting a new one
+    let mut old = libc::rlimit64 {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    let orig64 = libc::rlimit64 {
+        rlim_cur: orig.rlim_cur,
+        rlim_max: orig.rlim_max,
+    };
+    check_system_call!(
+        || unsafe { libc::prlimit64(0, libc::RLIMIT_CORE, &orig64, &mut old) },
+        &[]
+    )?;
+    test_utils::result_assert_eq(old.rlim_cur, new.rlim_cur, "Unexpected old soft limit")?;
+    test_utils::result_assert_eq(old.rlim_max, new.rlim_max, "Unexpected old hard limit")?;
+
+    let rlim = getrlimit(libc::RLIMIT_CORE)?;
+    test_utils::result_assert_eq(rlim.rlim_cur, orig.rlim_cur, "Unexpected soft limit")?;
+
+    Ok(())
+}
+
+fn test_prlimit_other_process() -> Result<(), String> {
+    let orig = getrlimit(libc::RLIMIT_NOFILE)?;
+    let new_soft = std::cmp::min(orig.rlim_cur, 100);
+
+    let mut fds = [-1; 2];
+    check_system_call!(|| unsafe { libc::pipe(fds.as_mut_ptr()) }, &[])?;
+    let [read_fd, write_fd] = fds;
+
+    let child_pid = check_system_call!(|| unsafe { libc::fork() }, &[])?;
+    if child_pid == 0 {
+        // wait for the parent to change our limit
+        let mut buf = [0u8; 1];
+        let rv = unsafe { libc::read(read_fd, buf.as_mut_ptr().cast(), 1) };
+        let mut rlim = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        let ok = rv == 1
+            && unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut rlim) } == 0
+            && rlim.rlim_cur == new_soft;
+        unsafe { libc::_exit(if ok { 0 } else { 1 }) };
+    }
+
+    let new = libc::rlimit64 {
+        rlim_cur: new_soft,
+        rlim_max: orig.rlim_max,
+    };
+    let mut old = libc::rlimit64 {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    check_system_call!(
+        || unsafe { libc::prlimit64(child_pid, libc::RLIMIT_NOFILE, &new, &mut old) },
+        &[]
+    )?;
+    test_utils::result_assert_eq(old.rlim_cur, orig.rlim_cur, "Unexpected old soft limit")?;
+
+    // let the child check its limit
+    check_system_call!(
+        || unsafe { libc::write(write_fd, [0u8].as_ptr().cast(), 1) },
+        &[]
+    )?;
+
+    let mut status = 0;
+    check_system_call!(|| unsafe { libc::waitpid(child_pid, &mut status, 0) }, &[])?;
+    test_utils::result_assert(
+        libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0,
+        "Child didn't observe the new limit",
+    )?;
+
+    // our own limit is unchanged
+    let rlim = getrlimit(libc::RLIMIT_NOFILE)?;
+    test_utils::result_assert_eq(rlim.rlim_cur, orig.rlim_cur, "Unexpected soft limit")?;
+
+    check_system_call!(|| unsafe { libc::close(read_fd) }, &[])?;
+    check_system_call!(|| unsafe { libc::close(write_fd) }, &[])?;
+
+    // the child has been reaped, so no longer exists
+    check_system_call!(
+        || unsafe { libc::prlimit64(child_pid, libc::RLIMIT_NOFILE, std::ptr::null(), &mut old) },
+        &[libc::ESRCH]
+    )?;
+
+    Ok(())
+}
+
+fn test_invalid() -> Result<(), String> {
+    let orig = getrlimit(libc::RLIMIT_NOFILE)?;
+
+    // soft limit greater than hard limit
+    if orig.rlim_max != libc::RLIM_INFINITY {
+        let rlim = libc::rlimit {
+            rlim_cur: orig.rlim_max + 1,
+            rlim_max: orig.rlim_max,
+        };
+        check_system_call!(
+            || unsafe { libc::setrlimit(libc::RLIMIT_NOFILE, &rlim) },
+            &[libc::EINVAL]
+        )?;
+    }
+
+    // unknown resource
+    let mut rlim = orig;
+    check_system_call!(
+        || unsafe { libc::syscall(libc::SYS_getrlimit, 1000, &mut rlim) },
+        &[libc::EINVAL]
+    )?;
+
+    Ok(())
+}
@@ -8,28 +8,50 @@ use nix::unistd::Pid;
 
 fn main() {
     let shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    // is shadow pinning its worker threads to cpus?
+    let pinned = std::env::args().any(|x| x == "--pinned");
 
-    get_affinity(shadow_passing);
-    set_affinity();
+    let cpu = get_affinity(shadow_passing, pinned);
+    set_affinity(cpu);
+    getcpu(cpu);
     sysconf(shadow_passing);
     println!("Success.");
 }
 
-fn get_affinity(shadow: bool) {
+/// Returns the first cpu in the affinity mask.
+fn get_affinity(shadow: bool, pinned: bool) -> usize {
+    let mut cpus = Vec::new();
+
     for pid in [Pid::from_raw(0), Pid::this()] {
         let cpu_set = sched_getaffinity(pid).unwrap();
         // on Linux this could in theory be false if the test are not allowed to schedule on some
-        // cores
-        assert!(cpu_set.is_set(0).unwrap());
+        // cores, and in shadow the thread will be running on the worker's pinned cpu
+        if !pinned {
+            assert!(cpu_set.is_set(0).unwrap());
+        }
         if shadow {
+            // shadow should report only the cpu that the host is running on
             assert_eq!(cpu_set_count(&cpu_set), 1);
         }
+        cpus.push(first_cpu(&cpu_set));
     }
+
+    // the calling thread and its process should report the same cpu
+    assert_eq!(cpus[0], cpus[1]);
+
     assert_eq!(
         unsafe { libc::sched_getaffinity(0, 0, std::ptr::null_mut()) },
         -1,
     );
     assert_eq!(test_utils::get_errno(), libc::EINVAL,);
+
+    cpus[0]
+}
+
+fn first_cpu(cpu_set: &CpuSet) -> usize {
+    (0..CpuSet::count())
+        .find(|index| cpu_set.is_set(*index).unwrap())
+        .unwrap()
 }
 
 fn cpu_set_count(cpu_set: &CpuSet) -> usize {
@@ -38,9 +60,9 @@ fn cpu_set_count(cpu_set: &CpuSet) -> usize {
         .count()
 }
 
-fn set_affinity() {
+fn set_affinity(cpu: usize) {
     let mut cpu_set = CpuSet::new();
-    cpu_set.set(0).unwrap();
+    cpu_set.set(cpu).unwrap();
 
     for pid in [Pid::from_raw(0), Pid::this()] {
         sched_setaffinity(pid, &cpu_set).unwrap();
@@ -48,7 +70,7 @@ fn set_affinity() {
         assert_eq!(new_cpu_set, cpu_set);
     }
 
-    cpu_set.unset(0).unwrap();
+    cpu_set.unset(cpu).unwrap();
     sched_setaffinity(Pid::from_raw(0), &cpu_set).unwrap_err();
     assert_eq!(
         unsafe { libc::sched_setaffinity(0, 0, std::ptr::null()) },
@@ -57,6 +79,44 @@ fn set_affinity() {
     assert_eq!(test_utils::get_errno(), libc::EINVAL);
 }
 
+/// The cpu reported by `getcpu` must be in the affinity mask, which now only contains `cpu`.
+fn getcpu(cpu: usize) {
+    let mut getcpu_cpu: libc::c_uint = u32::MAX;
+    let mut getcpu_node: libc::c_uint = u32::MAX;
+    assert_eq!(
+        unsafe {
+            libc::syscall(
+                libc::SYS_getcpu,
+                &mut getcpu_cpu,
+                &mut getcpu_node,
+                std::ptr::null_mut::<libc::c_void>(),
+            )
+        },
+        0
+    );
+    assert_eq!(getcpu_cpu as usize, cpu);
+    assert_ne!(getcpu_node, u32::MAX);
+
+    // null pointers are allowed
+    assert_eq!(
+        unsafe {
+            libc::syscall(
+                libc::SYS_getcpu,
+                std::ptr::null_mut::<libc::c_uint>(),
+                std::ptr::null_mut::<libc::c_uint>(),
+                std::ptr::null_mut::<libc::c_void>(),
+            )
+        },
+        0
+    );
+
+    // libc may use the vdso or rseq rather than the syscall
+    assert_eq!(unsafe { libc::sched_getcpu() }, cpu as libc::c_int);
+
+    let cpu_set = sched_getaffinity(Pid::from_raw(0)).unwrap();
+    assert!(cpu_set.is_set(cpu).unwrap());
+}
+
 fn sysconf(shadow: bool) {
     let online = nix::unistd::sysconf(nix::unistd::SysconfVar::_NPROCESSORS_ONLN)
         .unwrap()
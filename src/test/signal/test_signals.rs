@@ -1,13 +1,15 @@
 use std::arch::asm;
 use std::error::Error;
-use std::os::unix::io::RawFd;
+use std::os::unix::io::{AsRawFd, RawFd};
 use std::sync::mpsc::channel;
 use std::sync::Arc;
 use std::time::Duration;
 
 use nix::errno::Errno;
+use nix::poll::{poll, PollFd, PollFlags};
 use nix::sys::signal;
 use nix::sys::signal::Signal;
+use nix::sys::signalfd::{SfdFlags, SignalFd};
 use nix::unistd;
 use once_cell::sync::OnceCell;
 use rustix::thread::NanosleepRelativeResult;
@@ -308,6 +310,225 @@ fn test_sigprocmask() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+fn test_signalfd() -> Result<(), Box<dyn Error>> {
+    let signal = Signal::SIGUSR1;
+
+    // Block the signal so that it stays pending instead of being delivered.
+    let mut mask = signal::SigSet::empty();
+    mask.add(signal);
+    let mut old_mask = signal::SigSet::empty();
+    signal::sigprocmask(
+        signal::SigmaskHow::SIG_BLOCK,
+        Some(&mask),
+        Some(&mut old_mask),
+    )?;
+
+    let mut sfd = SignalFd::with_flags(&mask, SfdFlags::SFD_NONBLOCK | SfdFlags::SFD_CLOEXEC)?;
+
+    // Nothing is pending yet.
+    assert!(sfd.read_signal()?.is_none());
+
+    signal::raise(signal)?;
+
+    // The signalfd should now be readable.
+    let mut fds = [PollFd::new(sfd.as_raw_fd(), PollFlags::POLLIN)];
+    assert_eq!(poll(&mut fds, 0)?, 1);
+    assert_eq!(fds[0].revents(), Some(PollFlags::POLLIN));
+
+    let info = sfd.read_signal()?.unwrap();
+    assert_eq!(info.ssi_signo, signal as u32);
+    assert_eq!(info.ssi_pid, unistd::getpid().as_raw() as u32);
+
+    // Reading consumed the pending signal.
+    assert!(sfd.read_signal()?.is_none());
+
+    // A `sigqueue` value is reported in both `ssi_int` and `ssi_ptr`.
+    let value = 0x1_2345_6789usize;
+    let sigval = libc::sigval {
+        sival_ptr: value as *mut std::ffi::c_void,
+    };
+    Errno::result(unsafe { libc::sigqueue(unistd::getpid().as_raw(), signal as i32, sigval) })?;
+
+    let info = sfd.read_signal()?.unwrap();
+    assert_eq!(info.ssi_signo, signal as u32);
+    assert_eq!(info.ssi_code, libc::SI_QUEUE);
+    assert_eq!(info.ssi_ptr, value as u64);
+    assert_eq!(info.ssi_int, value as i32);
+
+    signal::sigprocmask(signal::SigmaskHow::SIG_SETMASK, Some(&old_mask), None)?;
+    Ok(())
+}
+
+fn test_sigpending() -> Result<(), Box<dyn Error>> {
+    let signal = Signal::SIGUSR2;
+
+    // Block the signal so that it stays pending instead of being delivered.
+    let mut mask = signal::SigSet::empty();
+    mask.add(signal);
+    let mut old_mask = signal::SigSet::empty();
+    signal::sigprocmask(
+        signal::SigmaskHow::SIG_BLOCK,
+        Some(&mask),
+        Some(&mut old_mask),
+    )?;
+
+    let mut pending: libc::sigset_t = unsafe { std::mem::zeroed() };
+    Errno::result(unsafe { libc::sigpending(&mut pending) })?;
+    assert_eq!(unsafe { libc::sigismember(&pending, signal as i32) }, 0);
+
+    signal::raise(signal)?;
+
+    Errno::result(unsafe { libc::sigpending(&mut pending) })?;
+    assert_eq!(unsafe { libc::sigismember(&pending, signal as i32) }, 1);
+    assert_eq!(
+        unsafe { libc::sigismember(&pending, Signal::SIGUSR1 as i32) },
+        0
+    );
+
+    // A smaller set size only writes that many bytes.
+    let mut raw_pending = u64::MAX;
+    Errno::result(unsafe {
+        libc::syscall(
+            libc::SYS_rt_sigpending,
+            &mut raw_pending,
+            std::mem::size_of::<u32>(),
+        )
+    })?;
+    assert_eq!(
+        raw_pending,
+        0xffff_ffff_0000_0000 | (1 << (signal as i32 - 1))
+    );
+
+    // A larger set size isn't supported.
+    let rv = unsafe {
+        libc::syscall(
+            libc::SYS_rt_sigpending,
+            &mut raw_pending,
+            2 * std::mem::size_of::<u64>(),
+        )
+    };
+    assert_eq!(Errno::result(rv), Err(Errno::EINVAL));
+
+    // Consume the pending signal so that it isn't delivered when we restore the mask.
+    let mut sig = 0;
+    Errno::result(unsafe { libc::sigwait(mask.as_ref(), &mut sig) })?;
+    assert_eq!(sig, signal as i32);
+
+    Errno::result(unsafe { libc::sigpending(&mut pending) })?;
+    assert_eq!(unsafe { libc::sigismember(&pending, signal as i32) }, 0);
+
+    signal::sigprocmask(signal::SigmaskHow::SIG_SETMASK, Some(&old_mask), None)?;
+    Ok(())
+}
+
+fn test_sigtimedwait() -> Result<(), Box<dyn Error>> {
+    let signal = Signal::SIGUSR1;
+    unsafe {
+        signal::sigaction(
+            signal,
+            &signal::SigAction::new(
+                signal::SigHandler::Handler(signal_handler),
+                signal::SaFlags::empty(),
+                signal::SigSet::empty(),
+            ),
+        )
+        .unwrap()
+    };
+
+    // Block the signal so that it stays pending instead of being delivered.
+    let mut mask = signal::SigSet::empty();
+    mask.add(signal);
+    let mut old_mask = signal::SigSet::empty();
+    signal::sigprocmask(
+        signal::SigmaskHow::SIG_BLOCK,
+        Some(&mask),
+        Some(&mut old_mask),
+    )?;
+
+    // Nothing is pending yet, so we should time out.
+    let timeout = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 1_000_000,
+    };
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+    assert_eq!(
+        Errno::result(unsafe { libc::sigtimedwait(mask.as_ref(), &mut info, &timeout) }),
+        Err(Errno::EAGAIN)
+    );
+
+    signal::raise(signal)?;
+
+    let rv = Errno::result(unsafe { libc::sigtimedwait(mask.as_ref(), &mut info, &timeout) })?;
+    assert_eq!(rv, signal as i32);
+    assert_eq!(info.si_signo, signal as i32);
+    assert_eq!(unsafe { info.si_pid() }, unistd::getpid().as_raw());
+
+    // The signal was accepted by `sigtimedwait`, so the handler should never run, even after
+    // unblocking.
+    signal::sigprocmask(signal::SigmaskHow::SIG_SETMASK, Some(&old_mask), None)?;
+    assert_eq!(signal_channel().recv(), None);
+
+    unsafe {
+        signal::sigaction(
+            signal,
+            &signal::SigAction::new(
+                signal::SigHandler::SigDfl,
+                signal::SaFlags::empty(),
+                signal::SigSet::empty(),
+            ),
+        )
+        .unwrap()
+    };
+    Ok(())
+}
+
+// Multiple instances of a realtime signal are queued, and delivered in the
+// order they were sent along with their `sigqueue` values.
+fn test_sigqueue_realtime() -> Result<(), Box<dyn Error>> {
+    // nix's `Signal` doesn't support realtime signals, so use libc directly.
+    let signal = libc::SIGRTMIN();
+
+    let mut action: libc::sigaction = unsafe { std::mem::zeroed() };
+    action.sa_sigaction = signal_action as usize;
+    action.sa_flags = libc::SA_SIGINFO;
+    Errno::result(unsafe { libc::sigaction(signal, &action, std::ptr::null_mut()) })?;
+
+    // Block the signal so that the instances are queued.
+    let mut mask: libc::sigset_t = unsafe { std::mem::zeroed() };
+    unsafe { libc::sigemptyset(&mut mask) };
+    unsafe { libc::sigaddset(&mut mask, signal) };
+    Errno::result(unsafe { libc::sigprocmask(libc::SIG_BLOCK, &mask, std::ptr::null_mut()) })?;
+
+    let values = [1usize, 2, 3];
+    for value in values {
+        let sigval = libc::sigval {
+            sival_ptr: value as *mut std::ffi::c_void,
+        };
+        Errno::result(unsafe { libc::sigqueue(unistd::getpid().as_raw(), signal, sigval) })?;
+    }
+
+    // Should be nothing delivered yet, since the signal is blocked.
+    assert_eq!(signal_channel().recv(), None);
+
+    // Unblock. All of the queued instances should be delivered synchronously, in order.
+    Errno::result(unsafe { libc::sigprocmask(libc::SIG_UNBLOCK, &mask, std::ptr::null_mut()) })?;
+
+    for value in values {
+        let record = signal_channel().recv().unwrap();
+        assert_eq!(record.signal, signal);
+        let info = record.info.unwrap();
+        assert_eq!(info.si_code, libc::SI_QUEUE);
+        assert_eq!(unsafe { info.si_pid() }, unistd::getpid().as_raw());
+        assert_eq!(unsafe { info.si_value() }.sival_ptr as usize, value);
+    }
+    assert_eq!(signal_channel().recv(), None);
+
+    action.sa_sigaction = libc::SIG_DFL;
+    action.sa_flags = 0;
+    Errno::result(unsafe { libc::sigaction(signal, &action, std::ptr::null_mut()) })?;
+    Ok(())
+}
+
 fn test_send_to_thread_and_process() -> Result<(), Box<dyn Error>> {
     let signal = Signal::SIGUSR1;
     unsafe {
@@ -1284,6 +1505,14 @@ fn main() -> Result<(), Box<dyn Error>> {
             all_envs.clone(),
         ),
         ShadowTest::new("sigprocmask", test_sigprocmask, all_envs.clone()),
+        ShadowTest::new("signalfd", test_signalfd, all_envs.clone()),
+        ShadowTest::new("sigtimedwait", test_sigtimedwait, all_envs.clone()),
+        ShadowTest::new("sigpending", test_sigpending, all_envs.clone()),
+        ShadowTest::new(
+            "sigqueue realtime",
+            test_sigqueue_realtime,
+            all_envs.clone(),
+        ),
         ShadowTest::new(
             "send to thread and process",
             test_send_to_thread_and_process,
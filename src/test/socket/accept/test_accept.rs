@@ -71,6 +71,14 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
             ),
         ]);
 
+        if accept_fn == AcceptFn::Accept4 {
+            tests.push(test_utils::ShadowTest::new(
+                &append_args("test_invalid_flags"),
+                test_invalid_flags,
+                set![TestEnv::Libc, TestEnv::Shadow],
+            ));
+        }
+
         let accept_flags = [
             0,
             libc::SOCK_NONBLOCK,
@@ -341,6 +349,32 @@ fn test_non_listening_fd(
     })
 }
 
+/// Test accept4 using flags other than SOCK_NONBLOCK and SOCK_CLOEXEC.
+fn test_invalid_flags() -> Result<(), String> {
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM | libc::SOCK_NONBLOCK, 0) };
+    assert!(fd >= 0);
+
+    socket_utils::autobind_helper(fd, libc::AF_INET);
+
+    let rv = unsafe { libc::listen(fd, 10) };
+    assert_eq!(rv, 0);
+
+    test_utils::run_and_close_fds(&[fd], || {
+        for flags in [libc::O_RDWR, libc::SOCK_NONBLOCK | libc::O_APPEND, -1] {
+            let mut args = AcceptArguments {
+                fd,
+                addr: None,
+                addr_len: None,
+                flags,
+            };
+
+            check_accept_call(&mut args, AcceptFn::Accept4, Some(libc::EINVAL))?;
+        }
+
+        Ok(())
+    })
+}
+
 /// Test accept using a NULL pointer in the address argument.
 fn test_null_addr(
     accept_fn: AcceptFn,
@@ -507,8 +541,23 @@ fn test_short_len(
     test_utils::run_and_close_fds(&[fd_client, fd_server], || -> Result<(), String> {
         let fd = check_accept_call(&mut args, accept_fn, expected_errno)?;
         if let Some(fd) = fd {
+            // the new fd's flags are set by accept4() and not inherited from the listening socket
+            let status_flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+            let fd_flags = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+
             let rv = unsafe { libc::close(fd) };
             assert_eq!(rv, 0, "Could not close the fd");
+
+            test_utils::result_assert_eq(
+                status_flags & libc::O_NONBLOCK != 0,
+                accept_flag & libc::SOCK_NONBLOCK != 0,
+                "Unexpected O_NONBLOCK status for the accepted fd",
+            )?;
+            test_utils::result_assert_eq(
+                fd_flags & libc::FD_CLOEXEC != 0,
+                accept_flag & libc::SOCK_CLOEXEC != 0,
+                "Unexpected FD_CLOEXEC flag for the accepted fd",
+            )?;
         }
         Ok(())
     })?;
@@ -621,8 +670,23 @@ fn test_zero_len(
     test_utils::run_and_close_fds(&[fd_client, fd_server], || -> Result<(), String> {
         let fd = check_accept_call(&mut args, accept_fn, expected_errno)?;
         if let Some(fd) = fd {
+            // the new fd's flags are set by accept4() and not inherited from the listening socket
+            let status_flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+            let fd_flags = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+
             let rv = unsafe { libc::close(fd) };
             assert_eq!(rv, 0, "Could not close the fd");
+
+            test_utils::result_assert_eq(
+                status_flags & libc::O_NONBLOCK != 0,
+                accept_flag & libc::SOCK_NONBLOCK != 0,
+                "Unexpected O_NONBLOCK status for the accepted fd",
+            )?;
+            test_utils::result_assert_eq(
+                fd_flags & libc::FD_CLOEXEC != 0,
+                accept_flag & libc::SOCK_CLOEXEC != 0,
+                "Unexpected FD_CLOEXEC flag for the accepted fd",
+            )?;
         }
         Ok(())
     })?;
@@ -788,8 +852,23 @@ fn test_correctness(
     test_utils::run_and_close_fds(&[fd_client, fd_server], || -> Result<(), String> {
         let fd = check_accept_call(&mut args, accept_fn, expected_errno)?;
         if let Some(fd) = fd {
+            // the new fd's flags are set by accept4() and not inherited from the listening socket
+            let status_flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+            let fd_flags = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+
             let rv = unsafe { libc::close(fd) };
             assert_eq!(rv, 0, "Could not close the fd");
+
+            test_utils::result_assert_eq(
+                status_flags & libc::O_NONBLOCK != 0,
+                accept_flag & libc::SOCK_NONBLOCK != 0,
+                "Unexpected O_NONBLOCK status for the accepted fd",
+            )?;
+            test_utils::result_assert_eq(
+                fd_flags & libc::FD_CLOEXEC != 0,
+                accept_flag & libc::SOCK_CLOEXEC != 0,
+                "Unexpected FD_CLOEXEC flag for the accepted fd",
+            )?;
         }
         Ok(())
     })?;
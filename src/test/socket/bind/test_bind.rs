@@ -51,6 +51,11 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
             test_non_socket_fd,
             set![TestEnv::Libc, TestEnv::Shadow],
         ),
+        test_utils::ShadowTest::new(
+            "test_unix_path_dgram_between_processes",
+            test_unix_path_dgram_between_processes,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
         // don't test outside of shadow since the host will already have ports in use
         test_utils::ShadowTest::new(
             "test_all_ports_used",
@@ -770,3 +775,135 @@ fn check_bind_call(
 
     Ok(())
 }
+
+/// Build a pathname unix socket address for `path`.
+fn unix_path_addr(path: &[u8]) -> libc::sockaddr_un {
+    let mut addr = libc::sockaddr_un {
+        sun_family: libc::AF_UNIX as u16,
+        sun_path: [0; 108],
+    };
+    addr.sun_path[..path.len()].copy_from_slice(test_utils::u8_to_i8_slice(path));
+    addr
+}
+
+/// Test two processes exchanging datagrams over unix sockets bound to (relative) pathnames.
+fn test_unix_path_dgram_between_processes() -> Result<(), String> {
+    let pid = std::process::id();
+    let server_path = format!("unix-dgram-server-{pid}.sock");
+    let client_path = format!("unix-dgram-client-{pid}.sock");
+    let server_addr = unix_path_addr(server_path.as_bytes());
+    let client_addr = unix_path_addr(client_path.as_bytes());
+    let addr_len = std::mem::size_of::<libc::sockaddr_un>() as libc::socklen_t;
+
+    // remove any socket files left over from a previous run
+    for path in [&server_path, &client_path] {
+        let _ = std::fs::remove_file(path);
+    }
+
+    let fd_server = unsafe { libc::socket(libc::AF_UNIX, libc::SOCK_DGRAM, 0) };
+    assert!(fd_server >= 0);
+
+    check_bind_call(
+        &BindArguments {
+            fd: fd_server,
+            addr: Some(SockAddr::Unix(server_addr)),
+            addr_len,
+        },
+        None,
+    )?;
+
+    // binding creates the socket file, so the path can't be bound again
+    let fd_other = unsafe { libc::socket(libc::AF_UNIX, libc::SOCK_DGRAM, 0) };
+    assert!(fd_other >= 0);
+    let rv = test_utils::run_and_close_fds(&[fd_other], || {
+        check_bind_call(
+            &BindArguments {
+                fd: fd_other,
+                addr: Some(SockAddr::Unix(server_addr)),
+                addr_len,
+            },
+            Some(libc::EADDRINUSE),
+        )
+    });
+    if let Err(e) = rv {
+        unsafe { libc::close(fd_server) };
+        let _ = std::fs::remove_file(&server_path);
+        return Err(e);
+    }
+
+    let child_pid = unsafe { libc::fork() };
+    assert!(child_pid >= 0);
+
+    if child_pid == 0 {
+        // the client binds to its own path so that the server can reply
+        let fd = unsafe { libc::socket(libc::AF_UNIX, libc::SOCK_DGRAM, 0) };
+        let bind_rv = unsafe { libc::bind(fd, std::ptr::from_ref(&client_addr).cast(), addr_len) };
+        let send_rv = unsafe {
+            libc::sendto(
+                fd,
+                b"ping".as_ptr().cast(),
+                4,
+                0,
+                std::ptr::from_ref(&server_addr).cast(),
+                addr_len,
+            )
+        };
+        let mut buf = [0u8; 16];
+        let recv_rv = unsafe { libc::recv(fd, buf.as_mut_ptr().cast(), buf.len(), 0) };
+
+        let ok = fd >= 0 && bind_rv == 0 && send_rv == 4 && recv_rv == 4 && &buf[..4] == b"pong";
+        unsafe { libc::_exit(if ok { 0 } else { 1 }) };
+    }
+
+    let rv = (|| {
+        let mut buf = [0u8; 16];
+        let mut src_addr: libc::sockaddr_un = unsafe { std::mem::zeroed() };
+        let mut src_len = addr_len;
+        let rv = unsafe {
+            libc::recvfrom(
+                fd_server,
+                buf.as_mut_ptr().cast(),
+                buf.len(),
+                0,
+                std::ptr::from_mut(&mut src_addr).cast(),
+                &mut src_len,
+            )
+        };
+        test_utils::result_assert_eq(rv, 4, "Unexpected recvfrom() return value")?;
+        test_utils::result_assert_eq(&buf[..4], &b"ping"[..], "Unexpected message")?;
+
+        // the source address is the client's bound path
+        let src_path = test_utils::i8_to_u8_slice(&src_addr.sun_path);
+        test_utils::result_assert(
+            src_path.starts_with(client_path.as_bytes()),
+            "Unexpected source address",
+        )?;
+
+        let rv = unsafe {
+            libc::sendto(
+                fd_server,
+                b"pong".as_ptr().cast(),
+                4,
+                0,
+                std::ptr::from_ref(&src_addr).cast(),
+                src_len,
+            )
+        };
+        test_utils::result_assert_eq(rv, 4, "Unexpected sendto() return value")?;
+
+        let mut status = 0;
+        let rv = unsafe { libc::waitpid(child_pid, &mut status, 0) };
+        test_utils::result_assert_eq(rv, child_pid, "Unexpected waitpid() return value")?;
+        test_utils::result_assert(
+            libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0,
+            "Child failed to exchange datagrams",
+        )
+    })();
+
+    unsafe { libc::close(fd_server) };
+    for path in [&server_path, &client_path] {
+        let _ = std::fs::remove_file(path);
+    }
+
+    rv
+}
@@ -7,6 +7,11 @@ use test_utils::set;
 use test_utils::AsMutPtr;
 use test_utils::TestEnvironment as TestEnv;
 
+// from linux's "include/net/tcp_states.h"
+const TCP_ESTABLISHED: u8 = 1;
+const TCP_CLOSE: u8 = 7;
+const TCP_LISTEN: u8 = 10;
+
 #[derive(Debug, Clone)]
 struct GetsockoptArguments {
     fd: libc::c_int,
@@ -139,6 +144,16 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
             test_invalid_level,
             set![TestEnv::Libc, TestEnv::Shadow],
         ),
+        test_utils::ShadowTest::new(
+            "test_tcp_info_connected",
+            test_tcp_info_connected,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_so_error_connection_refused",
+            test_so_error_connection_refused,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
     ];
 
     let domains = [libc::AF_INET];
@@ -640,9 +655,72 @@ fn test_so_error(domain: libc::c_int, sock_type: libc::c_int) -> Result<(), Stri
 
         test_utils::result_assert_eq(returned_optval, 0, "Expected there to be no socket error")?;
 
-        // We could try to trigger a socket error here and check to see that the value changed.
-        // I tried to do this by making a non-blocking connection to localhost, but it didn't
-        // seem to update the error either within Shadow or outside Shadow.
+        // see `test_so_error_connection_refused` for a test that triggers a socket error
+
+        Ok(())
+    })
+}
+
+/// Test that SO_ERROR reports a refused non-blocking connect() exactly once.
+fn test_so_error_connection_refused() -> Result<(), String> {
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM | libc::SOCK_NONBLOCK, 0) };
+    assert!(fd >= 0);
+
+    let addr = libc::sockaddr_in {
+        sin_family: libc::AF_INET as u16,
+        // this port should not be in use
+        sin_port: 11111u16.to_be(),
+        sin_addr: libc::in_addr {
+            s_addr: libc::INADDR_LOOPBACK.to_be(),
+        },
+        sin_zero: [0; 8],
+    };
+
+    let get_so_error = || -> Result<libc::c_int, String> {
+        let mut args = GetsockoptArguments::new(
+            fd,
+            libc::SOL_SOCKET,
+            libc::SO_ERROR,
+            Some(0i32.to_ne_bytes().into()),
+        );
+        check_getsockopt_call(&mut args, &[])?;
+        Ok(i32::from_ne_bytes(
+            args.optval.as_ref().unwrap()[..].try_into().unwrap(),
+        ))
+    };
+
+    test_utils::run_and_close_fds(&[fd], || {
+        let rv = unsafe {
+            libc::connect(
+                fd,
+                std::ptr::from_ref(&addr) as *const libc::sockaddr,
+                std::mem::size_of_val(&addr) as libc::socklen_t,
+            )
+        };
+        test_utils::result_assert_eq(rv, -1, "Expected connect() to fail")?;
+        test_utils::result_assert_eq(
+            test_utils::get_errno(),
+            libc::EINPROGRESS,
+            "Expected a non-blocking connect()",
+        )?;
+
+        // wait for the connection attempt to finish
+        let mut pfd = libc::pollfd {
+            fd,
+            events: libc::POLLOUT,
+            revents: 0,
+        };
+        let rv = unsafe { libc::poll(&mut pfd, 1, 1000) };
+        test_utils::result_assert_eq(rv, 1, "Expected the connection attempt to finish")?;
+
+        test_utils::result_assert_eq(
+            get_so_error()?,
+            libc::ECONNREFUSED,
+            "Expected the refused connection to be reported",
+        )?;
+
+        // the error should have been cleared when it was reported
+        test_utils::result_assert_eq(get_so_error()?, 0, "Expected the error to be cleared")?;
 
         Ok(())
     })
@@ -797,14 +875,83 @@ fn test_tcp_info(domain: libc::c_int, sock_type: libc::c_int) -> Result<(), Stri
         check_getsockopt_call(&mut get_args, &expected_errnos)?;
         check_setsockopt_call(&mut set_args, &[libc::ENOPROTOOPT])?;
 
-        // the libc package doesn't expose 'struct tcp_info' so if we wanted to look at the actual
-        // values we'd have to use our own binding, but it's probably good enough here just to make
-        // sure getsockopt() is returning something without an error
+        if sock_type == libc::SOCK_STREAM {
+            // 'tcpi_state' is the first byte of 'struct tcp_info'
+            let state = get_args.optval.as_ref().unwrap()[0];
+            test_utils::result_assert_eq(state, TCP_CLOSE, "Unexpected TCP state")?;
+        }
 
         Ok(())
     })
 }
 
+/// Test getsockopt() using the TCP_INFO option on listening and connected sockets.
+fn test_tcp_info_connected() -> Result<(), String> {
+    let fd_server = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
+    let fd_client = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
+    assert!(fd_server >= 0);
+    assert!(fd_client >= 0);
+
+    let mut addr = libc::sockaddr_in {
+        sin_family: libc::AF_INET as u16,
+        sin_port: 0u16.to_be(),
+        sin_addr: libc::in_addr {
+            s_addr: libc::INADDR_LOOPBACK.to_be(),
+        },
+        sin_zero: [0; 8],
+    };
+    let mut addr_len = std::mem::size_of_val(&addr) as libc::socklen_t;
+
+    let get_tcp_info = |fd| -> Result<libc::tcp_info, String> {
+        let mut info: libc::tcp_info = unsafe { std::mem::zeroed() };
+        let mut info_len = std::mem::size_of_val(&info) as libc::socklen_t;
+        let rv = unsafe {
+            libc::getsockopt(
+                fd,
+                libc::SOL_TCP,
+                libc::TCP_INFO,
+                std::ptr::from_mut(&mut info) as *mut libc::c_void,
+                &mut info_len,
+            )
+        };
+        test_utils::result_assert_eq(rv, 0, "getsockopt(TCP_INFO) failed")?;
+        Ok(info)
+    };
+
+    test_utils::run_and_close_fds(&[fd_server, fd_client], || {
+        let addr_ptr = std::ptr::from_mut(&mut addr) as *mut libc::sockaddr;
+        assert_eq!(0, unsafe { libc::bind(fd_server, addr_ptr, addr_len) });
+        assert_eq!(0, unsafe {
+            libc::getsockname(fd_server, addr_ptr, &mut addr_len)
+        });
+        assert_eq!(0, unsafe { libc::listen(fd_server, 10) });
+
+        let info = get_tcp_info(fd_server)?;
+        test_utils::result_assert_eq(info.tcpi_state, TCP_LISTEN, "Expected a listening state")?;
+
+        assert_eq!(0, unsafe { libc::connect(fd_client, addr_ptr, addr_len) });
+
+        let fd_accepted =
+            unsafe { libc::accept(fd_server, std::ptr::null_mut(), std::ptr::null_mut()) };
+        assert!(fd_accepted >= 0);
+
+        test_utils::run_and_close_fds(&[fd_accepted], || {
+            for fd in [fd_client, fd_accepted] {
+                let info = get_tcp_info(fd)?;
+                test_utils::result_assert_eq(
+                    info.tcpi_state,
+                    TCP_ESTABLISHED,
+                    "Expected an established state",
+                )?;
+                test_utils::result_assert(info.tcpi_snd_mss > 0, "Expected a non-zero send MSS")?;
+                test_utils::result_assert(info.tcpi_snd_cwnd > 0, "Expected a non-zero cwnd")?;
+            }
+
+            Ok(())
+        })
+    })
+}
+
 /// Test getsockopt() and setsockopt() using the TCP_NODELAY option.
 fn test_tcp_nodelay(domain: libc::c_int, sock_type: libc::c_int) -> Result<(), String> {
     let fd = unsafe { libc::socket(domain, sock_type, 0) };
@@ -23,11 +23,35 @@ fn main() -> Result<(), String> {
 }
 
 fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
-    let tests: Vec<test_utils::ShadowTest<_, _>> = vec![test_utils::ShadowTest::new(
-        "test_fstat_pipe",
-        test_fstat_pipe,
-        set![TestEnv::Libc, TestEnv::Shadow],
-    )];
+    let tests: Vec<test_utils::ShadowTest<_, _>> = vec![
+        test_utils::ShadowTest::new(
+            "test_fstat_pipe",
+            test_fstat_pipe,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_fstat_socket",
+            test_fstat_socket,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_fstat_eventfd",
+            test_fstat_eventfd,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_utimensat_omit",
+            test_utimensat_omit,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_utimensat_null_times",
+            test_utimensat_null_times,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        // linux may report fields that weren't requested
+        test_utils::ShadowTest::new("test_statx_mask", test_statx_mask, set![TestEnv::Shadow]),
+    ];
 
     tests
 }
@@ -69,3 +93,141 @@ fn test_fstat_pipe() -> Result<(), String> {
 
     Ok(())
 }
+
+fn test_fstat_socket() -> Result<(), String> {
+    for (domain, sock_type) in [
+        (libc::AF_INET, libc::SOCK_STREAM),
+        (libc::AF_INET, libc::SOCK_DGRAM),
+        (libc::AF_UNIX, libc::SOCK_STREAM),
+    ] {
+        let fd = unsafe { libc::socket(domain, sock_type, 0) };
+        assert!(fd >= 0);
+
+        let mut statbuf: libc::stat = unsafe { std::mem::zeroed() };
+        assert_eq!(0, unsafe { libc::fstat(fd, &mut statbuf) });
+
+        assert_eq!(statbuf.st_mode & libc::S_IFMT, libc::S_IFSOCK);
+        assert_eq!(statbuf.st_mode & !libc::S_IFMT, 0o777);
+
+        assert_eq!(0, unsafe { libc::close(fd) });
+    }
+
+    Ok(())
+}
+
+fn test_fstat_eventfd() -> Result<(), String> {
+    let fd = unsafe { libc::eventfd(0, 0) };
+    assert!(fd >= 0);
+
+    let mut statbuf: libc::stat = unsafe { std::mem::zeroed() };
+    assert_eq!(0, unsafe { libc::fstat(fd, &mut statbuf) });
+
+    // an anonymous inode, which isn't a socket or pipe
+    assert_ne!(statbuf.st_mode & libc::S_IFMT, libc::S_IFSOCK);
+    assert_ne!(statbuf.st_mode & libc::S_IFMT, libc::S_IFIFO);
+    assert_eq!(statbuf.st_mode & !libc::S_IFMT, 0o600);
+
+    assert_eq!(0, unsafe { libc::close(fd) });
+
+    Ok(())
+}
+
+fn test_statx_mask() -> Result<(), String> {
+    let path = std::ffi::CString::new("test_statx_mask.txt").unwrap();
+    std::fs::write("test_statx_mask.txt", b"hello").unwrap();
+
+    let mut statxbuf: libc::statx = unsafe { std::mem::zeroed() };
+    let rv = unsafe {
+        libc::statx(
+            libc::AT_FDCWD,
+            path.as_ptr(),
+            0,
+            libc::STATX_SIZE,
+            &mut statxbuf,
+        )
+    };
+
+    std::fs::remove_file("test_statx_mask.txt").unwrap();
+
+    assert_eq!(rv, 0);
+    assert_eq!(statxbuf.stx_mask, libc::STATX_SIZE);
+    assert_eq!(statxbuf.stx_size, 5);
+
+    // fields that weren't requested shouldn't be filled in
+    assert_eq!(statxbuf.stx_nlink, 0);
+    assert_eq!(statxbuf.stx_ino, 0);
+    assert_eq!(statxbuf.stx_mode, 0);
+
+    Ok(())
+}
+
+fn test_utimensat_omit() -> Result<(), String> {
+    let path = std::ffi::CString::new("test_utimensat_omit.txt").unwrap();
+    std::fs::write("test_utimensat_omit.txt", b"hello").unwrap();
+
+    let mut before: libc::stat = unsafe { std::mem::zeroed() };
+    assert_eq!(0, unsafe { libc::stat(path.as_ptr(), &mut before) });
+
+    // leave the access time alone and set an explicit modification time
+    let times = [
+        libc::timespec {
+            tv_sec: 0,
+            tv_nsec: libc::UTIME_OMIT,
+        },
+        libc::timespec {
+            tv_sec: 1_000_000,
+            tv_nsec: 500,
+        },
+    ];
+    let rv = unsafe { libc::utimensat(libc::AT_FDCWD, path.as_ptr(), times.as_ptr(), 0) };
+
+    let mut after: libc::stat = unsafe { std::mem::zeroed() };
+    assert_eq!(0, unsafe { libc::stat(path.as_ptr(), &mut after) });
+
+    std::fs::remove_file("test_utimensat_omit.txt").unwrap();
+
+    assert_eq!(rv, 0);
+    assert_eq!(after.st_atime, before.st_atime);
+    assert_eq!(after.st_atime_nsec, before.st_atime_nsec);
+    assert_eq!(after.st_mtime, 1_000_000);
+    assert_eq!(after.st_mtime_nsec, 500);
+
+    Ok(())
+}
+
+fn test_utimensat_null_times() -> Result<(), String> {
+    let path = std::ffi::CString::new("test_utimensat_null_times.txt").unwrap();
+    std::fs::write("test_utimensat_null_times.txt", b"hello").unwrap();
+
+    let times = [
+        libc::timespec {
+            tv_sec: 1_000_000,
+            tv_nsec: 0,
+        },
+        libc::timespec {
+            tv_sec: 1_000_000,
+            tv_nsec: 0,
+        },
+    ];
+    assert_eq!(0, unsafe {
+        libc::utimensat(libc::AT_FDCWD, path.as_ptr(), times.as_ptr(), 0)
+    });
+
+    // a null pointer sets both timestamps to the current time
+    let rv = unsafe { libc::utimensat(libc::AT_FDCWD, path.as_ptr(), std::ptr::null(), 0) };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+
+    let mut statbuf: libc::stat = unsafe { std::mem::zeroed() };
+    assert_eq!(0, unsafe { libc::stat(path.as_ptr(), &mut statbuf) });
+
+    std::fs::remove_file("test_utimensat_null_times.txt").unwrap();
+
+    assert_eq!(rv, 0);
+    assert!((now - statbuf.st_atime).abs() <= 1);
+    assert!((now - statbuf.st_mtime).abs() <= 1);
+
+    Ok(())
+}
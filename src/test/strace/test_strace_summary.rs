@@ -0,0 +1,25 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! Makes a known number of `write` syscalls, so that the strace summary can be checked after the
+//! simulation.
+
+const NUM_WRITES: usize = 10;
+
+fn main() {
+    let mut fds = [-1; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    let [read_fd, write_fd] = fds;
+
+    for _ in 0..NUM_WRITES {
+        let rv = unsafe { libc::write(write_fd, [0u8].as_ptr().cast(), 1) };
+        assert_eq!(rv, 1);
+    }
+
+    assert_eq!(unsafe { libc::close(read_fd) }, 0);
+    assert_eq!(unsafe { libc::close(write_fd) }, 0);
+
+    // don't print anything, since that would make another `write` syscall
+}
@@ -0,0 +1,94 @@
+use test_utils::{ensure_ord, set, TestEnvironment};
+
+fn main() -> anyhow::Result<()> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnvironment::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnvironment::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), anyhow::Error>> {
+    let all_envs = set![TestEnvironment::Libc, TestEnvironment::Shadow];
+
+    // The native clock state depends on the system's time synchronization, so we only check the
+    // reported values under shadow. Likewise we don't try to adjust the native clock.
+    let tests: Vec<test_utils::ShadowTest<(), anyhow::Error>> = vec![
+        test_utils::ShadowTest::new("read_only", test_read_only, all_envs),
+        test_utils::ShadowTest::new(
+            "read_only_state",
+            test_read_only_state,
+            set![TestEnvironment::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "clock_adjtime_read_only_state",
+            test_clock_adjtime_read_only_state,
+            set![TestEnvironment::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "modify_eperm",
+            test_modify_eperm,
+            set![TestEnvironment::Shadow],
+        ),
+    ];
+
+    tests
+}
+
+fn test_read_only() -> anyhow::Result<()> {
+    let mut buf: libc::timex = unsafe { std::mem::zeroed() };
+    let rv = unsafe { libc::adjtimex(&mut buf) };
+    ensure_ord!(rv, >=, 0);
+    Ok(())
+}
+
+fn test_read_only_state() -> anyhow::Result<()> {
+    let mut buf: libc::timex = unsafe { std::mem::zeroed() };
+    // Make sure the fields are overwritten.
+    buf.offset = 1;
+    buf.freq = 1;
+
+    let rv = unsafe { libc::adjtimex(&mut buf) };
+    ensure_ord!(rv, ==, libc::TIME_OK);
+    ensure_ord!(buf.offset, ==, 0);
+    ensure_ord!(buf.freq, ==, 0);
+    ensure_ord!(buf.time.tv_sec, >, 0);
+    Ok(())
+}
+
+fn test_clock_adjtime_read_only_state() -> anyhow::Result<()> {
+    let mut buf: libc::timex = unsafe { std::mem::zeroed() };
+    let rv = unsafe { libc::clock_adjtime(libc::CLOCK_REALTIME, &mut buf) };
+    ensure_ord!(rv, ==, libc::TIME_OK);
+    ensure_ord!(buf.offset, ==, 0);
+
+    // Only the realtime clock can be adjusted.
+    let rv = unsafe { libc::clock_adjtime(libc::CLOCK_MONOTONIC, &mut buf) };
+    ensure_ord!(rv, ==, -1);
+    ensure_ord!(test_utils::get_errno(), ==, libc::EOPNOTSUPP);
+    Ok(())
+}
+
+fn test_modify_eperm() -> anyhow::Result<()> {
+    let mut buf: libc::timex = unsafe { std::mem::zeroed() };
+    buf.modes = libc::ADJ_FREQUENCY;
+    buf.freq = 1;
+
+    let rv = unsafe { libc::adjtimex(&mut buf) };
+    ensure_ord!(rv, ==, -1);
+    ensure_ord!(test_utils::get_errno(), ==, libc::EPERM);
+    Ok(())
+}
@@ -1,5 +1,11 @@
 use test_utils::{set, FuzzArg, FuzzError, FuzzOrder, TestEnvironment};
 
+/// Shadow's custom `SYS_shadow_get_sim_time` syscall.
+const SYS_SHADOW_GET_SIM_TIME: libc::c_long = 1006;
+
+/// The unix time (in seconds) at which shadow simulations start.
+const SIMULATION_START_SEC: i64 = 946684800;
+
 fn main() -> anyhow::Result<()> {
     // should we restrict the tests we run?
     let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
@@ -102,9 +108,52 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), anyhow::Error>> {
         }
     }
 
+    tests.push(test_utils::ShadowTest::new(
+        "shadow_get_sim_time",
+        test_shadow_get_sim_time,
+        set![TestEnvironment::Shadow],
+    ));
+
     tests
 }
 
+fn clock_gettime_nanos(clockid: libc::clockid_t) -> i128 {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    assert_eq!(unsafe { libc::clock_gettime(clockid, &mut ts) }, 0);
+    i128::from(ts.tv_sec) * 1_000_000_000 + i128::from(ts.tv_nsec)
+}
+
+fn test_shadow_get_sim_time() -> anyhow::Result<()> {
+    let sim_start = i128::from(SIMULATION_START_SEC) * 1_000_000_000;
+
+    let before = clock_gettime_nanos(libc::CLOCK_REALTIME) - sim_start;
+
+    let mut sim_time: u64 = 0;
+    let rv = unsafe { libc::syscall(SYS_SHADOW_GET_SIM_TIME, &mut sim_time) };
+    anyhow::ensure!(rv == 0, "Unexpected return value {rv}");
+
+    let after = clock_gettime_nanos(libc::CLOCK_REALTIME) - sim_start;
+
+    // the simulation time should agree with the shimmed clock_gettime
+    let sim_time = i128::from(sim_time);
+    anyhow::ensure!(
+        before <= sim_time && sim_time <= after,
+        "Simulation time {sim_time} is not between {before} and {after}"
+    );
+
+    // the process starts 1 second into the simulation
+    anyhow::ensure!(sim_time >= 1_000_000_000);
+
+    let rv = unsafe { libc::syscall(SYS_SHADOW_GET_SIM_TIME, std::ptr::null_mut::<u64>()) };
+    anyhow::ensure!(rv == -1);
+    anyhow::ensure!(test_utils::get_errno() == libc::EFAULT);
+
+    Ok(())
+}
+
 fn test_clock_gettime(
     clockid: FuzzArg<libc::clockid_t>,
     mut ts: FuzzArg<Option<libc::timespec>>,
@@ -405,6 +405,205 @@ fn test_alarm_with_zero_remaining() -> anyhow::Result<()> {
     Ok(())
 }
 
+fn test_posix_timer_oneshot() -> anyhow::Result<()> {
+    reset()?;
+
+    let mut sev: libc::sigevent = unsafe { std::mem::zeroed() };
+    sev.sigev_notify = libc::SIGEV_SIGNAL;
+    sev.sigev_signo = libc::SIGALRM;
+
+    let mut timer_id: libc::timer_t = std::ptr::null_mut();
+    let rv = unsafe { libc::timer_create(libc::CLOCK_MONOTONIC, &mut sev, &mut timer_id) };
+    ensure_ord!(rv, ==, 0);
+
+    let get_remaining = || -> anyhow::Result<libc::itimerspec> {
+        let mut curr_value: libc::itimerspec = unsafe { std::mem::zeroed() };
+        let rv = unsafe { libc::timer_gettime(timer_id, &mut curr_value) };
+        ensure_ord!(rv, ==, 0);
+        Ok(curr_value)
+    };
+
+    // Newly created timers are disarmed.
+    ensure_ord!(get_remaining()?.it_value.tv_sec, ==, 0);
+    ensure_ord!(get_remaining()?.it_value.tv_nsec, ==, 0);
+
+    // Expire once, 100 ms from now.
+    let new_value = libc::itimerspec {
+        it_interval: libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        },
+        it_value: libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 100_000_000,
+        },
+    };
+    let rv = unsafe { libc::timer_settime(timer_id, 0, &new_value, std::ptr::null_mut()) };
+    ensure_ord!(rv, ==, 0);
+
+    // Sleep for 50 ms.
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    // Shouldn't have fired yet.
+    ensure_ord!(SIGNAL_CTR.load(Ordering::Relaxed), ==, 0);
+    // Should be somewhere between 0 and 100 ms left.
+    let remaining = get_remaining()?;
+    ensure_ord!(remaining.it_value.tv_sec, ==, 0);
+    ensure_ord!(remaining.it_value.tv_nsec, >, 0);
+    ensure_ord!(remaining.it_value.tv_nsec, <, 100_000_000);
+
+    // Sleep another 100 ms, which should put us at about 150ms since setting the timer.
+    std::thread::sleep(std::time::Duration::from_millis(100));
+
+    // Should have fired exactly once, without any overruns.
+    ensure_ord!(SIGNAL_CTR.load(Ordering::Relaxed), ==, 1);
+    ensure_ord!(unsafe { libc::timer_getoverrun(timer_id) }, ==, 0);
+
+    // Timer should no longer be armed.
+    ensure_ord!(get_remaining()?.it_value.tv_nsec, ==, 0);
+
+    let rv = unsafe { libc::timer_delete(timer_id) };
+    ensure_ord!(rv, ==, 0);
+
+    // The timer id is no longer valid.
+    let rv = unsafe { libc::timer_getoverrun(timer_id) };
+    ensure_ord!(rv, ==, -1);
+    ensure_ord!(nix::errno::Errno::last(), ==, nix::errno::Errno::EINVAL);
+
+    Ok(())
+}
+
+// `si_code` of signals sent by POSIX timers. Not exposed by the libc crate.
+const SI_TIMER: i32 = -2;
+
+fn test_posix_timer_rt_signal() -> anyhow::Result<()> {
+    let signo = libc::SIGRTMIN();
+
+    // Block the signal so that we can wait for it below.
+    let mut sigset: libc::sigset_t = unsafe { std::mem::zeroed() };
+    unsafe { libc::sigemptyset(&mut sigset) };
+    unsafe { libc::sigaddset(&mut sigset, signo) };
+    let rv = unsafe { libc::pthread_sigmask(libc::SIG_BLOCK, &sigset, std::ptr::null_mut()) };
+    ensure_ord!(rv, ==, 0);
+
+    let mut sev: libc::sigevent = unsafe { std::mem::zeroed() };
+    sev.sigev_notify = libc::SIGEV_SIGNAL;
+    sev.sigev_signo = signo;
+    sev.sigev_value.sival_ptr = 42 as *mut libc::c_void;
+
+    let mut timer_id: libc::timer_t = std::ptr::null_mut();
+    let rv = unsafe { libc::timer_create(libc::CLOCK_MONOTONIC, &mut sev, &mut timer_id) };
+    ensure_ord!(rv, ==, 0);
+
+    // Expire once, 10 ms from now.
+    let new_value = libc::itimerspec {
+        it_interval: libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        },
+        it_value: libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 10_000_000,
+        },
+    };
+    let rv = unsafe { libc::timer_settime(timer_id, 0, &new_value, std::ptr::null_mut()) };
+    ensure_ord!(rv, ==, 0);
+
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+    let timeout = libc::timespec {
+        tv_sec: 1,
+        tv_nsec: 0,
+    };
+    let rv = unsafe { libc::sigtimedwait(&sigset, &mut info, &timeout) };
+    ensure_ord!(rv, ==, signo);
+    ensure_ord!(info.si_signo, ==, signo);
+    ensure_ord!(info.si_code, ==, SI_TIMER);
+    ensure_ord!(unsafe { info.si_value() }.sival_ptr as usize, ==, 42);
+
+    let rv = unsafe { libc::timer_delete(timer_id) };
+    ensure_ord!(rv, ==, 0);
+
+    let rv = unsafe { libc::pthread_sigmask(libc::SIG_UNBLOCK, &sigset, std::ptr::null_mut()) };
+    ensure_ord!(rv, ==, 0);
+
+    Ok(())
+}
+
+// glibc's `struct sigevent` as used for `SIGEV_THREAD`. The libc crate only exposes
+// `sigev_notify_thread_id` from the union at the end of the struct.
+#[repr(C)]
+struct SigeventThread {
+    sigev_value: libc::sigval,
+    sigev_signo: libc::c_int,
+    sigev_notify: libc::c_int,
+    sigev_notify_function: Option<extern "C" fn(libc::sigval)>,
+    sigev_notify_attributes: *mut libc::pthread_attr_t,
+    _pad: [u64; 4],
+}
+const _: () =
+    assert!(std::mem::size_of::<SigeventThread>() == std::mem::size_of::<libc::sigevent>());
+
+// Counts how many times `sigev_thread_notify` ran.
+static SIGEV_THREAD_CTR: AtomicU64 = AtomicU64::new(0);
+
+extern "C" fn sigev_thread_notify(value: libc::sigval) {
+    assert_eq!(value.sival_ptr as usize, 42);
+    SIGEV_THREAD_CTR.fetch_add(1, Ordering::Relaxed);
+}
+
+fn test_posix_timer_sigev_thread() -> anyhow::Result<()> {
+    // glibc implements `SIGEV_THREAD` with a helper thread that waits for a thread-directed
+    // `SIGRTMIN` sent by the timer (with `SIGEV_THREAD_ID`), and then runs the notify function in
+    // a new thread.
+    let mut sev = SigeventThread {
+        sigev_value: libc::sigval {
+            sival_ptr: 42 as *mut libc::c_void,
+        },
+        sigev_signo: 0,
+        sigev_notify: libc::SIGEV_THREAD,
+        sigev_notify_function: Some(sigev_thread_notify),
+        sigev_notify_attributes: std::ptr::null_mut(),
+        _pad: [0; 4],
+    };
+
+    let mut timer_id: libc::timer_t = std::ptr::null_mut();
+    let rv = unsafe {
+        libc::timer_create(
+            libc::CLOCK_MONOTONIC,
+            std::ptr::from_mut(&mut sev).cast::<libc::sigevent>(),
+            &mut timer_id,
+        )
+    };
+    ensure_ord!(rv, ==, 0);
+
+    // Expire every 100 ms.
+    let new_value = libc::itimerspec {
+        it_interval: libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 100_000_000,
+        },
+        it_value: libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 100_000_000,
+        },
+    };
+    let rv = unsafe { libc::timer_settime(timer_id, 0, &new_value, std::ptr::null_mut()) };
+    ensure_ord!(rv, ==, 0);
+
+    // Sleep for 50 ms.
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    // Shouldn't have fired yet.
+    ensure_ord!(SIGEV_THREAD_CTR.load(Ordering::Relaxed), ==, 0);
+
+    // Sleep another 200 ms, which should put us at about 250ms since setting the timer.
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    ensure_ord!(SIGEV_THREAD_CTR.load(Ordering::Relaxed), ==, 2);
+
+    let rv = unsafe { libc::timer_delete(timer_id) };
+    ensure_ord!(rv, ==, 0);
+
+    Ok(())
+}
+
 fn main() -> anyhow::Result<()> {
     // Install a SIGALRM handler that counts how many times it's been received.
     unsafe {
@@ -453,6 +652,21 @@ fn main() -> anyhow::Result<()> {
             test_alarm_with_zero_remaining,
             all_envs.clone(),
         ),
+        ShadowTest::new(
+            "posix_timer_oneshot",
+            test_posix_timer_oneshot,
+            all_envs.clone(),
+        ),
+        ShadowTest::new(
+            "posix_timer_rt_signal",
+            test_posix_timer_rt_signal,
+            all_envs.clone(),
+        ),
+        ShadowTest::new(
+            "posix_timer_sigev_thread",
+            test_posix_timer_sigev_thread,
+            all_envs.clone(),
+        ),
         // Must be last.
         // Validate proper cleanup for a timer that's still running when the
         // process exits.
@@ -242,6 +242,47 @@ static void _test_double_arm_timer() {
     close(tfd);
 }
 
+static void _test_realtime_periodic_timer() {
+    int tfd;
+
+    /* create new realtime timerfd */
+    assert_nonneg_errno(tfd = timerfd_create(CLOCK_REALTIME, TFD_NONBLOCK));
+
+    struct timespec now = {0};
+    assert_nonneg_errno(clock_gettime(CLOCK_REALTIME, &now));
+
+    /* Timer will expire at an absolute time 1 second from now, then every 1 second */
+    struct itimerspec t = {0};
+    t.it_value = now;
+    t.it_value.tv_sec += 1;
+    t.it_interval.tv_sec = 1;
+    assert_nonneg_errno(timerfd_settime(tfd, TFD_TIMER_ABSTIME, &t, NULL));
+
+    /* nothing has expired yet */
+    uint64_t num_expires = 0;
+    int rv = read(tfd, &num_expires, sizeof(uint64_t));
+    g_assert_cmpint(rv, ==, -1);
+    g_assert_cmpint(errno, ==, EAGAIN);
+
+    /* after 3.5 seconds, the timer should have expired at 1, 2, and 3 seconds */
+    struct timespec delay = {.tv_sec = 3, .tv_nsec = S_TO_NS / 2};
+    assert_nonneg_errno(nanosleep(&delay, NULL));
+
+    rv = read(tfd, &num_expires, sizeof(uint64_t));
+    g_assert_cmpint(rv, ==, sizeof(uint64_t));
+    g_assert_cmpint(num_expires, ==, 3);
+
+    /* the read reset the count, so after 2 more seconds we should see 2 more expirations */
+    delay.tv_sec = 2;
+    delay.tv_nsec = 0;
+    assert_nonneg_errno(nanosleep(&delay, NULL));
+
+    rv = read(tfd, &num_expires, sizeof(uint64_t));
+    g_assert_cmpint(rv, ==, sizeof(uint64_t));
+    g_assert_cmpint(num_expires, ==, 2);
+
+    close(tfd);
+}
 
 int main(int argc, char* argv[]) {
     g_test_init(&argc, &argv, NULL);
@@ -253,6 +294,7 @@ int main(int argc, char* argv[]) {
     g_test_add_func("/timerfd/disarm", _test_disarm_timer);
     g_test_add_func("/timerfd/rearm", _test_rearm_timer);
     g_test_add_func("/timerfd/double-arm", _test_double_arm_timer);
+    g_test_add_func("/timerfd/realtime-periodic", _test_realtime_periodic_timer);
 
     return g_test_run();
 }
\ No newline at end of file
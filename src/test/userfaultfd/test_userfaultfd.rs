@@ -0,0 +1,120 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+use linux_api::ioctls::IoctlRequest;
+use linux_api::userfaultfd::{uffdio_api, UFFD_API, UFFD_USER_MODE_ONLY};
+use test_utils::TestEnvironment as TestEnv;
+use test_utils::{check_system_call, set};
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests: Vec<test_utils::ShadowTest<_, _>> = vec![
+        test_utils::ShadowTest::new(
+            "test_invalid_flags",
+            test_invalid_flags,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new("test_api", test_api, set![TestEnv::Libc, TestEnv::Shadow]),
+        test_utils::ShadowTest::new(
+            "test_api_invalid_version",
+            test_api_invalid_version,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+    ];
+
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn userfaultfd(flags: libc::c_int) -> libc::c_int {
+    // user-mode-only userfaultfds don't need any extra privileges
+    let flags = flags | UFFD_USER_MODE_ONLY as libc::c_int;
+    unsafe { libc::syscall(libc::SYS_userfaultfd, flags) as libc::c_int }
+}
+
+fn uffdio_api_ioctl(fd: libc::c_int, api: &mut uffdio_api) -> libc::c_int {
+    let request: u32 = IoctlRequest::UFFDIO_API.into();
+    unsafe { libc::ioctl(fd, request as _, std::ptr::from_mut(api)) }
+}
+
+fn test_invalid_flags() -> Result<(), String> {
+    check_system_call!(|| userfaultfd(libc::O_APPEND), &[libc::EINVAL])?;
+    Ok(())
+}
+
+fn test_api() -> Result<(), String> {
+    let fd = check_system_call!(|| userfaultfd(libc::O_CLOEXEC | libc::O_NONBLOCK), &[])?;
+
+    test_utils::result_assert(
+        unsafe { libc::fcntl(fd, libc::F_GETFD) } & libc::FD_CLOEXEC != 0,
+        "Expected FD_CLOEXEC to be set",
+    )?;
+
+    let mut api = uffdio_api {
+        api: UFFD_API,
+        features: 0,
+        ioctls: 0,
+    };
+    check_system_call!(|| uffdio_api_ioctl(fd, &mut api), &[])?;
+
+    test_utils::result_assert_eq(api.api, UFFD_API, "Unexpected api version")?;
+
+    // Linux may support any number of features, but shadow supports none of them
+    if test_utils::running_in_shadow() {
+        test_utils::result_assert_eq(api.features, 0, "Expected no supported features")?;
+    }
+
+    // the UFFDIO_API ioctl must be supported
+    let api_bit = linux_api::userfaultfd::UFFDIO_API & 0xff;
+    test_utils::result_assert(
+        api.ioctls & (1 << api_bit) != 0,
+        "Expected UFFDIO_API to be supported",
+    )?;
+
+    // no events are available
+    let mut buf = [0u8; 32];
+    check_system_call!(
+        || unsafe { libc::read(fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) },
+        &[libc::EAGAIN],
+    )?;
+
+    // the handshake can't be repeated
+    check_system_call!(|| uffdio_api_ioctl(fd, &mut api), &[libc::EINVAL])?;
+
+    check_system_call!(|| unsafe { libc::close(fd) }, &[])?;
+
+    Ok(())
+}
+
+fn test_api_invalid_version() -> Result<(), String> {
+    let fd = check_system_call!(|| userfaultfd(libc::O_CLOEXEC), &[])?;
+
+    let mut api = uffdio_api {
+        api: UFFD_API + 1,
+        features: 0,
+        ioctls: 0,
+    };
+    check_system_call!(|| uffdio_api_ioctl(fd, &mut api), &[libc::EINVAL])?;
+
+    check_system_call!(|| unsafe { libc::close(fd) }, &[])?;
+
+    Ok(())
+}
@@ -139,6 +139,12 @@ def do_reduce(data, m, result, with_packet_data):
                     data['nodes'][n]['send'][l][s] += d['nodes'][n]['send'][l][s]
     return data, m
 
+def strip_sequence_number(parts):
+    # newer log lines start with a "#N" sequence number
+    if len(parts) > 0 and parts[0].startswith('#'):
+        return parts[1:]
+    return parts
+
 def process_shadow_lines(passed_args):
     line, with_packet_data = passed_args
     signal(SIGINT, SIG_IGN) # ignore interrupts
@@ -147,7 +153,7 @@ def process_shadow_lines(passed_args):
     d = {'ticks':{}, 'nodes':{}}
 
     if re.search("[Pp]rocess resource usage at simtime", line) is not None:
-        parts = line.strip().split()
+        parts = strip_sequence_number(line.strip().split())
         if len(parts) < 14: return None
 
         real_seconds = timestamp_to_seconds(parts[0])
@@ -174,7 +180,7 @@ def process_shadow_lines(passed_args):
         if real_seconds > max_seconds: max_seconds = real_seconds
 
     elif re.search("shadow-heartbeat", line) is not None:
-        parts = line.strip().split()
+        parts = strip_sequence_number(line.strip().split())
         if len(parts) < 10 or '[node]' != parts[8]: return None
 
         real_seconds = timestamp_to_seconds(parts[0])
@@ -20,6 +20,8 @@ outf = open(sys.argv[2], 'w')
 n = 0
 for line in inf:
     parts = line.strip().split()
+    if len(parts) > 0 and parts[0].startswith('#'):
+        parts = parts[1:] # skip the sequence number, which depends on thread scheduling
     parts = parts[1:] # skip the first timer column
     parts = [p + ' ' for p in parts if not p.startswith("0x")] # skip printing memory addresses
     parts.append("\n")